@@ -0,0 +1,74 @@
+(
+    name: "BoneWeightsShader",
+
+    properties: [],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                // Color of the bone with the largest weight, written by the bone weight view.
+                layout(location = 7) in vec4 in_vertex_color;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                uniform sampler2D fyrox_boneMatrices;
+
+                out vec4 color;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(vertexPosition, 1.0);
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = localPosition;
+                        localPosition = vec4(0.0);
+                        for (int i = 0; i < 4; ++i)
+                        {
+                            localPosition += S_FetchMatrix(fyrox_boneMatrices, int(boneIndices[i]))
+                                * vertex * boneWeights[i];
+                        }
+                    }
+
+                    color = in_vertex_color;
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                }
+               "#,
+            fragment_shader:
+               r#"
+                in vec4 color;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    // Unlit, the color of the vertex is the final color.
+                    FragColor = vec4(color.rgb, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
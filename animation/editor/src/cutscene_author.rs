@@ -0,0 +1,236 @@
+//! Editor plugin, that allows to author camera cutscenes without writing code. Keyframes are
+//! recorded from the current view of the editor camera, can be previewed in the editor and
+//! exported to a file.
+use editor_utils::{current_game_scene, make_button, open_window, place_camera, set_text};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        math::lerpf,
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        button::ButtonMessage,
+        message::UiMessage,
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Orientation, Thickness, UiNode,
+    },
+    scene::{camera::Camera, camera::Projection},
+};
+use fyroxed_base::{plugin::EditorPlugin, Editor};
+use std::time::Instant;
+
+/// Path of the file, the keyframes are exported to.
+const EXPORT_PATH: &str = "data/cutscene.bin";
+/// Time between neighbouring keyframes during playback, in seconds.
+const KEYFRAME_DURATION: f32 = 2.0;
+
+#[derive(Visit, Default, Clone, Debug)]
+pub struct CutsceneKeyframe {
+    pub position: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    /// Vertical field of view in radians.
+    pub fov: f32,
+}
+
+impl CutsceneKeyframe {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            rotation: self.rotation.nlerp(&other.rotation, t),
+            fov: lerpf(self.fov, other.fov, t),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CutsceneAuthor {
+    window: Handle<UiNode>,
+    record: Handle<UiNode>,
+    play: Handle<UiNode>,
+    export: Handle<UiNode>,
+    clear: Handle<UiNode>,
+    info: Handle<UiNode>,
+    keyframes: Vec<CutsceneKeyframe>,
+    /// Start of the playback, `None` if the cutscene is not playing.
+    playback_start: Option<Instant>,
+}
+
+impl CutsceneAuthor {
+    fn sync_info(&self, editor: &Editor) {
+        let state = if self.playback_start.is_some() {
+            "Playing"
+        } else {
+            "Stopped"
+        };
+        set_text(
+            editor,
+            self.info,
+            format!("Keyframes: {} - {}", self.keyframes.len(), state),
+        );
+    }
+
+    fn record_keyframe(&mut self, editor: &mut Editor) {
+        let Some((controller, scene)) = current_game_scene(editor) else {
+            return;
+        };
+        let Some(camera) = scene.graph.try_get_of_type::<Camera>(controller.camera) else {
+            return;
+        };
+        let fov = match camera.projection() {
+            Projection::Perspective(perspective) => perspective.fov,
+            // Orthographic projection has no field of view, keep the previous one.
+            Projection::Orthographic(_) => self
+                .keyframes
+                .last()
+                .map_or(75.0f32.to_radians(), |keyframe| keyframe.fov),
+        };
+        self.keyframes.push(CutsceneKeyframe {
+            position: camera.global_position(),
+            rotation: camera.global_rotation(),
+            fov,
+        });
+    }
+
+    /// Keyframe at the given playback time, `None` when the playback has reached the end.
+    fn sample(&self, time: f32) -> Option<CutsceneKeyframe> {
+        let position = time / KEYFRAME_DURATION;
+        let index = position as usize;
+        if index + 1 >= self.keyframes.len() {
+            return None;
+        }
+        Some(self.keyframes[index].interpolate(&self.keyframes[index + 1], position.fract()))
+    }
+
+    /// Moves the editor camera to the keyframe and changes its field of view, the keyframes of
+    /// orthographic cameras keep the projection as is.
+    fn apply_keyframe(editor: &mut Editor, keyframe: &CutsceneKeyframe) {
+        let Some((controller, scene)) = current_game_scene(editor) else {
+            return;
+        };
+        place_camera(controller, scene, keyframe.position, keyframe.rotation);
+        if let Some(camera) = scene.graph.try_get_mut_of_type::<Camera>(controller.camera) {
+            let mut projection = camera.projection().clone();
+            if let Projection::Perspective(perspective) = &mut projection {
+                perspective.fov = keyframe.fov;
+            }
+            camera.set_projection(projection);
+        }
+    }
+
+    fn stop(&mut self, editor: &mut Editor) {
+        self.playback_start = None;
+        // Leave the camera at the last keyframe instead of jumping back to where it was.
+        if let Some(last) = self.keyframes.last() {
+            if let Some((controller, scene)) = current_game_scene(editor) {
+                controller.set_position(&mut scene.graph, last.position);
+            }
+        }
+        self.sync_info(editor);
+    }
+
+    fn export(&mut self, editor: &Editor) {
+        let mut visitor = Visitor::new();
+        Log::verify(self.keyframes.visit("Keyframes", &mut visitor));
+        match visitor.save_binary(EXPORT_PATH) {
+            Ok(_) => set_text(
+                editor,
+                self.info,
+                format!("Exported {} keyframes", self.keyframes.len()),
+            ),
+            Err(err) => Log::err(format!(
+                "Unable to export the cutscene to {EXPORT_PATH}. Reason: {err:?}"
+            )),
+        }
+    }
+}
+
+impl EditorPlugin for CutsceneAuthor {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interfaces.first_mut().build_ctx();
+
+        self.record = make_button(ctx, "Record Keyframe");
+        self.play = make_button(ctx, "Play");
+        self.export = make_button(ctx, "Export");
+        self.clear = make_button(ctx, "Clear");
+        self.info = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_text("Keyframes: 0 - Stopped")
+            .build(ctx);
+
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(480.0)
+                .with_height(70.0)
+                .with_desired_position(Vector2::new(300.0, 60.0)),
+        )
+        .with_title(WindowTitle::text("Cutscene Authoring"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(self.record)
+                                .with_child(self.play)
+                                .with_child(self.export)
+                                .with_child(self.clear),
+                        )
+                        .with_orientation(Orientation::Horizontal)
+                        .build(ctx),
+                    )
+                    .with_child(self.info),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+
+        open_window(editor, self.window);
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        let Some(ButtonMessage::Click) = message.data() else {
+            return;
+        };
+
+        let destination = message.destination();
+        if destination == self.record {
+            self.record_keyframe(editor);
+            self.sync_info(editor);
+        } else if destination == self.play {
+            if self.playback_start.is_some() {
+                self.stop(editor);
+            } else if self.keyframes.len() >= 2 {
+                self.playback_start = Some(Instant::now());
+                self.sync_info(editor);
+            } else {
+                set_text(
+                    editor,
+                    self.info,
+                    "Record at least two keyframes".to_string(),
+                );
+            }
+        } else if destination == self.export {
+            self.export(editor);
+        } else if destination == self.clear {
+            self.keyframes.clear();
+            self.stop(editor);
+        }
+    }
+
+    fn on_post_update(&mut self, editor: &mut Editor) {
+        let Some(start) = self.playback_start else {
+            return;
+        };
+
+        match self.sample(start.elapsed().as_secs_f32()) {
+            Some(keyframe) => Self::apply_keyframe(editor, &keyframe),
+            None => self.stop(editor),
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Editor plugin, that generates levels of detail for the selected meshes. The meshes are
+//! simplified with vertex clustering - vertices are snapped to a uniform grid and all the vertices
+//! in a cell of the grid are merged into one. It is fast and works with any mesh, but it does not
+//! preserve texture seams as well as edge collapse methods do. The generated meshes are added next
+//! to the original ones with an editor command, so the generation can be undone.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        Thickness, UiNode,
+    },
+    scene::{
+        base::{BaseBuilder, LevelOfDetail, LodGroup},
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::{Node, SubGraph},
+    },
+};
+use fyroxed_base::{
+    command::{Command, CommandContext, CommandGroup, CommandTrait},
+    plugin::EditorPlugin,
+    scene::{commands::GameSceneContext, GameScene},
+    Editor,
+};
+use std::collections::HashMap;
+
+/// Target vertex count of every generated level relative to the original mesh and the distance
+/// range (relative to the far plane of the camera), where the level is shown.
+const LEVELS: [(f32, f32, f32); 3] = [(0.5, 0.1, 0.25), (0.25, 0.25, 0.5), (0.1, 0.5, 1.0)];
+/// The original mesh is shown up to this distance, relative to the far plane of the camera.
+const ORIGINAL_LEVEL_END: f32 = 0.1;
+/// Amount of steps of the search of the grid cell size, that gives the target vertex count.
+const SEARCH_STEPS: usize = 20;
+
+#[derive(Default)]
+pub struct AutoLodGenerator {
+    window: Handle<UiNode>,
+    generate: Handle<UiNode>,
+    results: Handle<UiNode>,
+}
+
+/// Returns the index of the cell of the grid, that contains the point.
+fn cell_of(point: Vector3<f32>, origin: Vector3<f32>, cell_size: f32) -> [i32; 3] {
+    let cell = (point - origin) / cell_size;
+    [
+        cell.x.floor() as i32,
+        cell.y.floor() as i32,
+        cell.z.floor() as i32,
+    ]
+}
+
+/// Simplifies the surface, so it has about `ratio` of its vertices. Every cluster of vertices is
+/// replaced with its first vertex (so texture coordinates, normals and bone weights are kept),
+/// moved to the average position of the cluster. Triangles that collapse are removed.
+fn decimate(data: &SurfaceData, ratio: f32) -> Option<SurfaceData> {
+    let positions = data
+        .vertex_buffer
+        .iter()
+        .map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let first = *positions.first()?;
+    let (min, max) = positions
+        .iter()
+        .fold((first, first), |(min, max), p| (min.inf(p), max.sup(p)));
+    let extent = (max - min).max();
+    if extent <= 0.0 {
+        return None;
+    }
+
+    // Larger cells give less vertices, find the smallest cell size, that reaches the target.
+    let target = ((positions.len() as f32 * ratio).ceil() as usize).max(3);
+    let count_cells = |cell_size: f32| {
+        let mut cells = HashMap::new();
+        for position in positions.iter() {
+            cells
+                .entry(cell_of(*position, min, cell_size))
+                .or_insert(());
+        }
+        cells.len()
+    };
+    let (mut low, mut high) = (0.0, extent);
+    for _ in 0..SEARCH_STEPS {
+        let middle = (low + high) * 0.5;
+        if count_cells(middle) > target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    // Cluster index of every vertex and the first vertex, sum of positions and size of every
+    // cluster.
+    let mut clusters = HashMap::new();
+    let mut representatives: Vec<(usize, Vector3<f32>, f32)> = Vec::new();
+    let remap = positions
+        .iter()
+        .enumerate()
+        .map(|(index, position)| {
+            let cluster = *clusters
+                .entry(cell_of(*position, min, high))
+                .or_insert_with(|| {
+                    representatives.push((index, Vector3::default(), 0.0));
+                    representatives.len() - 1
+                });
+            let representative = &mut representatives[cluster];
+            representative.1 += *position;
+            representative.2 += 1.0;
+            cluster as u32
+        })
+        .collect::<Vec<_>>();
+
+    let triangles = data
+        .geometry_buffer
+        .iter()
+        .map(|triangle| TriangleDefinition(triangle.0.map(|index| remap[index as usize])))
+        .filter(|triangle| {
+            let [a, b, c] = triangle.0;
+            a != b && b != c && c != a
+        })
+        .collect::<Vec<_>>();
+
+    let vertex_size = data.vertex_buffer.vertex_size() as usize;
+    let raw_data = data.vertex_buffer.raw_data();
+    let mut vertex_buffer = data.vertex_buffer.clone();
+    {
+        let mut vertices = vertex_buffer.modify();
+        vertices.clear();
+        for (index, _, _) in representatives.iter() {
+            Log::verify(
+                vertices.push_vertex_raw(&raw_data[index * vertex_size..(index + 1) * vertex_size]),
+            );
+        }
+        for (mut vertex, (_, sum, count)) in vertices.iter_mut().zip(representatives.iter()) {
+            Log::verify(vertex.write_3_f32(VertexAttributeUsage::Position, sum.scale(1.0 / count)));
+        }
+    }
+
+    Some(SurfaceData::new(
+        vertex_buffer,
+        TriangleBuffer::new(triangles),
+    ))
+}
+
+/// Creates the simplified copies of the mesh, that are placed next to it. Returns the copies with
+/// the indices of their levels (the original mesh is the level 0) and a line of the report for
+/// every level.
+fn generate_lods(graph: &Graph, handle: Handle<Node>) -> (Vec<(Node, usize)>, Vec<String>) {
+    let Some(mesh) = graph.try_get_of_type::<Mesh>(handle) else {
+        return Default::default();
+    };
+    let name = mesh.name().to_string();
+    let transform = mesh.local_transform().clone();
+    let render_path = mesh.render_path();
+    let original_vertex_count = mesh
+        .surfaces()
+        .iter()
+        .map(|surface| surface.data().data_ref().vertex_buffer.vertex_count() as usize)
+        .sum::<usize>();
+    let levels = LEVELS.map(|(ratio, _, _)| {
+        mesh.surfaces()
+            .iter()
+            .filter_map(|surface| {
+                let data = decimate(&surface.data().data_ref(), ratio)?;
+                Some((data, surface.material().clone(), surface.bones().to_vec()))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut lods = Vec::new();
+    let mut report = Vec::new();
+    for (index, (surfaces, (ratio, _, _))) in levels.into_iter().zip(LEVELS).enumerate() {
+        if surfaces.is_empty() {
+            continue;
+        }
+        let vertex_count = surfaces
+            .iter()
+            .map(|(data, _, _)| data.vertex_buffer.vertex_count() as usize)
+            .sum::<usize>();
+        report.push(format!(
+            "{name} LOD{}: {original_vertex_count} -> {vertex_count} vertices (target {}%)",
+            index + 1,
+            (ratio * 100.0) as u32
+        ));
+
+        let lod = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name(lod_name(&name, index + 1))
+                .with_local_transform(transform.clone()),
+        )
+        .with_surfaces(
+            surfaces
+                .into_iter()
+                .map(|(data, material, bones)| {
+                    SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, data))
+                        .with_material(material)
+                        .with_bones(bones)
+                        .build()
+                })
+                .collect(),
+        )
+        .with_render_path(render_path)
+        .build_node();
+        lods.push((lod, index + 1));
+    }
+    (lods, report)
+}
+
+fn lod_name(name: &str, level: usize) -> String {
+    format!("{name}_LOD{level}")
+}
+
+/// Returns the meshes, that were generated for the mesh by a previous run of the generator.
+fn previous_lods(graph: &Graph, mesh: Handle<Node>) -> Vec<Handle<Node>> {
+    let Some(node) = graph.try_get(mesh) else {
+        return Vec::new();
+    };
+    let names = (1..=LEVELS.len())
+        .map(|level| lod_name(node.name(), level))
+        .collect::<Vec<_>>();
+    graph
+        .try_get(node.parent())
+        .map(|parent| {
+            parent
+                .children()
+                .iter()
+                .copied()
+                .filter(|child| {
+                    names
+                        .iter()
+                        .any(|name| graph[*child].name() == name.as_str())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replaces the generated meshes of the children of a node and sets the LOD group of the node.
+/// The first execution adds the new meshes to the graph. After that, the command swaps the meshes,
+/// that are in the graph, with the detached ones, so their handles stay the same on undo and redo.
+#[derive(Debug)]
+struct SetLodsCommand {
+    parent: Handle<Node>,
+    /// Original meshes, that are shown at the closest level.
+    originals: Vec<Handle<Node>>,
+    /// Generated meshes, that are not added to the graph yet, with the indices of their levels.
+    new_lods: Vec<(Node, usize)>,
+    /// Generated meshes in the graph, that are detached, when the command is executed or reverted.
+    attached: Vec<Handle<Node>>,
+    detached: Vec<SubGraph>,
+    /// LOD group of the parent, that is set together with the detached meshes.
+    detached_group: Option<LodGroup>,
+}
+
+impl SetLodsCommand {
+    /// Levels of the group of the parent with the new meshes. The levels of the other children of
+    /// the parent are kept, the removed meshes are excluded from them.
+    fn merged_levels(
+        &self,
+        group: Option<&LodGroup>,
+        removed: &[Handle<Node>],
+    ) -> Vec<LevelOfDetail> {
+        std::iter::once((0.0, ORIGINAL_LEVEL_END))
+            .chain(LEVELS.map(|(_, begin, end)| (begin, end)))
+            .enumerate()
+            .map(|(index, (begin, end))| {
+                let mut objects = group
+                    .and_then(|group| group.levels.get(index))
+                    .map(|level| level.objects.clone())
+                    .unwrap_or_default();
+                objects
+                    .retain(|object| !removed.contains(object) && !self.originals.contains(object));
+                if index == 0 {
+                    objects.extend_from_slice(&self.originals);
+                }
+                LevelOfDetail::new(begin, end, objects)
+            })
+            .collect()
+    }
+
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        let removed = std::mem::take(&mut self.attached);
+        let detached = removed
+            .iter()
+            .filter(|lod| graph.is_valid_handle(**lod))
+            .map(|lod| graph.take_reserve_sub_graph(*lod))
+            .collect::<Vec<_>>();
+        let group = graph
+            .try_get(self.parent)
+            .and_then(|parent| parent.lod_group().cloned());
+
+        let new_group = if self.new_lods.is_empty() {
+            for sub_graph in self.detached.drain(..) {
+                self.attached.push(graph.put_sub_graph_back(sub_graph));
+            }
+            self.detached_group.take()
+        } else {
+            let mut levels = self.merged_levels(group.as_ref(), &removed);
+            for (lod, level) in self.new_lods.drain(..) {
+                let lod = graph.add_node(lod);
+                graph.link_nodes(lod, self.parent);
+                levels[level].objects.push(lod);
+                self.attached.push(lod);
+            }
+            Some(LodGroup { levels })
+        };
+        if let Some(parent) = graph.try_get_mut(self.parent) {
+            parent.set_lod_group(new_group);
+        }
+
+        self.detached = detached;
+        self.detached_group = group;
+    }
+}
+
+impl CommandTrait for SetLodsCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Generate LODs".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+}
+
+impl AutoLodGenerator {
+    /// Generates levels of detail for all the selected meshes of the current scene. The meshes
+    /// with the same parent share one LOD group, the meshes of a previous run are replaced.
+    fn generate(&self, editor: &mut Editor) -> Vec<String> {
+        let Some(entry) = editor.scenes.current_scene_entry_ref() else {
+            return vec!["No scene is opened".to_string()];
+        };
+        let Some(game_scene) = entry.controller.downcast_ref::<GameScene>() else {
+            return vec!["The current scene is not a game scene".to_string()];
+        };
+        let selected = entry
+            .selection
+            .as_graph()
+            .map(|selection| selection.nodes().to_vec())
+            .unwrap_or_default();
+        let Some(scene) = editor.engine.scenes.try_get(game_scene.scene) else {
+            return Vec::new();
+        };
+        let graph = &scene.graph;
+
+        let mut commands = HashMap::new();
+        let mut report = Vec::new();
+        for handle in selected {
+            let (lods, lines) = generate_lods(graph, handle);
+            if lods.is_empty() {
+                continue;
+            }
+            report.extend(lines);
+            let command = commands
+                .entry(graph[handle].parent())
+                .or_insert_with_key(|parent| SetLodsCommand {
+                    parent: *parent,
+                    originals: Vec::new(),
+                    new_lods: Vec::new(),
+                    attached: Vec::new(),
+                    detached: Vec::new(),
+                    detached_group: None,
+                });
+            command.originals.push(handle);
+            command.new_lods.extend(lods);
+            // The generated meshes of a previous run are removed.
+            command.attached.extend(previous_lods(graph, handle));
+        }
+
+        if report.is_empty() {
+            return vec!["Select one or more meshes".to_string()];
+        }
+        let group =
+            CommandGroup::from(commands.into_values().map(Command::new).collect::<Vec<_>>())
+                .with_custom_name("Generate LODs");
+        editor.message_sender.do_command(group);
+        report
+    }
+}
+
+impl EditorPlugin for AutoLodGenerator {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interfaces.first_mut().build_ctx();
+
+        self.generate = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_height(24.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Generate LODs")
+        .build(ctx);
+        self.results = ListViewBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .build(ctx);
+
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(420.0)
+                .with_height(220.0)
+                .with_desired_position(Vector2::new(300.0, 150.0)),
+        )
+        .with_title(WindowTitle::text("LOD Generator"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.generate)
+                    .with_child(self.results),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                false,
+                false,
+            ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        let Some(ButtonMessage::Click) = message.data() else {
+            return;
+        };
+        if message.destination() != self.generate {
+            return;
+        }
+
+        let report = self.generate(editor);
+        let ui = editor.engine.user_interfaces.first_mut();
+        let items = report
+            .into_iter()
+            .map(|line| {
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                    .with_text(line)
+                    .build(&mut ui.build_ctx())
+            })
+            .collect();
+        ui.send_message(ListViewMessage::items(
+            self.results,
+            MessageDirection::ToWidget,
+            items,
+        ));
+    }
+}
@@ -1,7 +1,18 @@
 //! Editor with your game connected to it as a plugin.
+mod cutscene_author;
+mod lod_generator;
+mod mesh_repair;
+mod node_clipboard;
+mod scene_diff;
+
 use animation::Game;
+use cutscene_author::CutsceneAuthor;
 use fyrox::event_loop::EventLoop;
 use fyroxed_base::{Editor, StartupData};
+use lod_generator::AutoLodGenerator;
+use mesh_repair::MeshRepair;
+use node_clipboard::NodeClipboard;
+use scene_diff::SceneDiff;
 
 fn main() {
     let event_loop = EventLoop::new().unwrap();
@@ -10,5 +21,10 @@ fn main() {
         scenes: vec!["data/scene.rgs".into()],
     }));
     editor.add_game_plugin(Game::default());
+    editor.add_editor_plugin(CutsceneAuthor::default());
+    editor.add_editor_plugin(AutoLodGenerator::default());
+    editor.add_editor_plugin(MeshRepair::default());
+    editor.add_editor_plugin(NodeClipboard::default());
+    editor.add_editor_plugin(SceneDiff::default());
     editor.run(event_loop)
 }
@@ -0,0 +1,669 @@
+//! Editor plugin, that finds and fixes broken triangles of the selected meshes. It is opened from
+//! the "Utils" menu of the editor. The checks are:
+//!
+//! - zero-area triangles - the vertices of the triangle are duplicated or collinear;
+//! - slivers - triangles with extremely large aspect ratio, they give shading artifacts and break
+//!   physics queries;
+//! - flipped triangles - the winding of the triangle is opposite to the winding of its neighbours;
+//! - duplicate vertices - vertices, that are closer to each other than the merge epsilon.
+use editor_utils::make_button;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        button::ButtonMessage,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        menu::MenuItemMessage,
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        Orientation, Thickness, UiNode,
+    },
+    scene::{
+        mesh::{
+            buffer::{TriangleBuffer, VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+            surface::{Surface, SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh,
+        },
+        node::Node,
+    },
+};
+use fyroxed_base::{
+    command::{Command, CommandContext, CommandGroup, CommandTrait},
+    menu::create_menu_item,
+    plugin::EditorPlugin,
+    scene::{commands::GameSceneContext, GameScene},
+    Editor,
+};
+use std::collections::{HashMap, VecDeque};
+
+/// A triangle has zero area, if its doubled area is less than this fraction of the squared length
+/// of its longest edge.
+const ZERO_AREA_TOLERANCE: f32 = 1.0e-6;
+/// Max ratio of the longest edge of a triangle to the height of the triangle.
+const MAX_ASPECT_RATIO: f32 = 1000.0;
+const MIN_MERGE_EPSILON: f32 = 0.0001;
+const MAX_MERGE_EPSILON: f32 = 0.05;
+const DEFAULT_MERGE_EPSILON: f32 = 0.001;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Defect {
+    ZeroArea,
+    Sliver,
+}
+
+/// Amount of the issues of every type.
+#[derive(Copy, Clone, Default, Debug)]
+struct Issues {
+    zero_area: usize,
+    slivers: usize,
+    flipped: usize,
+    duplicates: usize,
+}
+
+impl Issues {
+    fn add(&mut self, other: Issues) {
+        self.zero_area += other.zero_area;
+        self.slivers += other.slivers;
+        self.flipped += other.flipped;
+        self.duplicates += other.duplicates;
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} zero-area, {} sliver(s), {} flipped, {} duplicate vertices",
+            self.zero_area, self.slivers, self.flipped, self.duplicates
+        )
+    }
+}
+
+/// A fix, that is applied to every surface of the selected meshes.
+#[derive(Copy, Clone, Debug)]
+enum Fix {
+    Remove(Defect),
+    Normals,
+    Merge,
+}
+
+fn classify(a: Vector3<f32>, b: Vector3<f32>, c: Vector3<f32>) -> Option<Defect> {
+    let longest_squared = (b - a)
+        .norm_squared()
+        .max((c - b).norm_squared())
+        .max((a - c).norm_squared());
+    let double_area = (b - a).cross(&(c - a)).norm();
+    if double_area <= longest_squared * ZERO_AREA_TOLERANCE {
+        Some(Defect::ZeroArea)
+    } else if longest_squared / double_area > MAX_ASPECT_RATIO {
+        Some(Defect::Sliver)
+    } else {
+        None
+    }
+}
+
+fn classify_triangle(positions: &[Vector3<f32>], triangle: [u32; 3]) -> Option<Defect> {
+    let [a, b, c] = triangle.map(|index| positions[index as usize]);
+    classify(a, b, c)
+}
+
+/// Returns a flag for every triangle, that tells whether the triangle has to be flipped to have
+/// the same winding as its neighbours. The orientation is propagated across the edges, that are
+/// shared by exactly two triangles. The vertices are matched by position, so the orientation
+/// propagates across texture seams as well. In every connected part of the mesh the winding of the
+/// majority of the triangles is considered correct.
+fn flipped_triangles(positions: &[Vector3<f32>], triangles: &[[u32; 3]]) -> Vec<bool> {
+    let mut ids = HashMap::new();
+    let welded = positions
+        .iter()
+        .map(|p| {
+            let count = ids.len() as u32;
+            *ids.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()])
+                .or_insert(count)
+        })
+        .collect::<Vec<_>>();
+
+    let mut edges = HashMap::<(u32, u32), Vec<(usize, bool)>>::new();
+    for (index, triangle) in triangles.iter().enumerate() {
+        for k in 0..3 {
+            let a = welded[triangle[k] as usize];
+            let b = welded[triangle[(k + 1) % 3] as usize];
+            if a != b {
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push((index, a < b));
+            }
+        }
+    }
+
+    // Neighbours have the same winding, if they go through the shared edge in opposite directions.
+    let mut neighbours = vec![Vec::new(); triangles.len()];
+    for sharing in edges.values() {
+        if let [(first, first_forward), (second, second_forward)] = sharing[..] {
+            let opposite = first_forward == second_forward;
+            neighbours[first].push((second, opposite));
+            neighbours[second].push((first, opposite));
+        }
+    }
+
+    let mut flipped = vec![false; triangles.len()];
+    let mut visited = vec![false; triangles.len()];
+    for seed in 0..triangles.len() {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        let mut component = vec![seed];
+        let mut queue = VecDeque::from([seed]);
+        while let Some(current) = queue.pop_front() {
+            for &(neighbour, opposite) in neighbours[current].iter() {
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    flipped[neighbour] = flipped[current] ^ opposite;
+                    component.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        let flipped_count = component.iter().filter(|&&index| flipped[index]).count();
+        if flipped_count * 2 > component.len() {
+            for index in component {
+                flipped[index] = !flipped[index];
+            }
+        }
+    }
+    flipped
+}
+
+fn grid_cell(position: Vector3<f32>, cell_size: f32) -> [i32; 3] {
+    [
+        (position.x / cell_size).floor() as i32,
+        (position.y / cell_size).floor() as i32,
+        (position.z / cell_size).floor() as i32,
+    ]
+}
+
+/// Returns the index of the vertex, that every vertex is merged into. A vertex is merged into the
+/// first vertex, that is closer than `epsilon` to it.
+fn weld(positions: &[Vector3<f32>], epsilon: f32) -> Vec<u32> {
+    let mut cells = HashMap::<[i32; 3], Vec<u32>>::new();
+    positions
+        .iter()
+        .enumerate()
+        .map(|(index, position)| {
+            let cell = grid_cell(*position, epsilon);
+            let mut found = None;
+            'search: for x in -1..=1 {
+                for y in -1..=1 {
+                    for z in -1..=1 {
+                        let neighbour = [cell[0] + x, cell[1] + y, cell[2] + z];
+                        for &other in cells.get(&neighbour).into_iter().flatten() {
+                            if (positions[other as usize] - position).norm() <= epsilon {
+                                found = Some(other);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+            found.unwrap_or_else(|| {
+                cells.entry(cell).or_default().push(index as u32);
+                index as u32
+            })
+        })
+        .collect()
+}
+
+fn positions_of(data: &SurfaceData) -> Option<Vec<Vector3<f32>>> {
+    data.vertex_buffer
+        .iter()
+        .map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+}
+
+fn triangles_of(data: &SurfaceData) -> Vec<[u32; 3]> {
+    data.geometry_buffer
+        .iter()
+        .map(|triangle| triangle.0)
+        .collect()
+}
+
+fn analyze(data: &SurfaceData, epsilon: f32) -> Issues {
+    let Some(positions) = positions_of(data) else {
+        return Issues::default();
+    };
+    let triangles = triangles_of(data);
+    let mut issues = Issues::default();
+    for triangle in triangles.iter() {
+        match classify_triangle(&positions, *triangle) {
+            Some(Defect::ZeroArea) => issues.zero_area += 1,
+            Some(Defect::Sliver) => issues.slivers += 1,
+            None => (),
+        }
+    }
+    issues.flipped = flipped_triangles(&positions, &triangles)
+        .into_iter()
+        .filter(|flipped| *flipped)
+        .count();
+    issues.duplicates = weld(&positions, epsilon)
+        .into_iter()
+        .enumerate()
+        .filter(|(index, target)| *index != *target as usize)
+        .count();
+    issues
+}
+
+/// Removes the triangles with the given defect.
+fn remove_triangles(data: &SurfaceData, defect: Defect) -> Option<SurfaceData> {
+    let positions = positions_of(data)?;
+    let triangles = triangles_of(data)
+        .into_iter()
+        .filter(|triangle| classify_triangle(&positions, *triangle) != Some(defect))
+        .map(TriangleDefinition)
+        .collect::<Vec<_>>();
+    Some(SurfaceData::new(
+        data.vertex_buffer.clone(),
+        TriangleBuffer::new(triangles),
+    ))
+}
+
+/// Flips the triangles, that have the winding opposite to their neighbours, and recomputes the
+/// normals of the vertices as the sum of the normals (cross products of the edges) of the
+/// adjacent triangles. Tangents depend on the normals, so they are recomputed too.
+fn fix_normals(data: &SurfaceData) -> Option<SurfaceData> {
+    let positions = positions_of(data)?;
+    let triangles = triangles_of(data)
+        .into_iter()
+        .zip(flipped_triangles(&positions, &triangles_of(data)))
+        .map(|([a, b, c], flipped)| if flipped { [a, c, b] } else { [a, b, c] })
+        .collect::<Vec<_>>();
+
+    let mut vertex_buffer = data.vertex_buffer.clone();
+    if vertex_buffer.has_attribute(VertexAttributeUsage::Normal) {
+        let mut normals = vec![Vector3::default(); positions.len()];
+        for triangle in triangles.iter() {
+            let [a, b, c] = triangle.map(|index| positions[index as usize]);
+            let normal = (b - a).cross(&(c - a));
+            for index in triangle {
+                normals[*index as usize] += normal;
+            }
+        }
+        let mut vertices = vertex_buffer.modify();
+        for (mut vertex, normal) in vertices.iter_mut().zip(normals) {
+            Log::verify(
+                vertex.write_3_f32(
+                    VertexAttributeUsage::Normal,
+                    normal
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                ),
+            );
+        }
+    }
+
+    let mut data = SurfaceData::new(
+        vertex_buffer,
+        TriangleBuffer::new(triangles.into_iter().map(TriangleDefinition).collect()),
+    );
+    if data
+        .vertex_buffer
+        .has_attribute(VertexAttributeUsage::Tangent)
+    {
+        Log::verify(data.calculate_tangents());
+    }
+    Some(data)
+}
+
+/// Merges the vertices, that are closer than `epsilon` to each other. The merged vertex keeps the
+/// attributes of the first vertex, so texture seams within `epsilon` are merged too. Triangles,
+/// that collapse after the merge, are removed.
+fn merge_vertices(data: &SurfaceData, epsilon: f32) -> Option<SurfaceData> {
+    let positions = positions_of(data)?;
+    let targets = weld(&positions, epsilon);
+
+    // New index of every vertex, that is kept.
+    let mut kept = Vec::new();
+    let mut new_indices = vec![0; positions.len()];
+    for (index, target) in targets.iter().enumerate() {
+        if index == *target as usize {
+            new_indices[index] = kept.len() as u32;
+            kept.push(index);
+        }
+    }
+
+    let triangles = triangles_of(data)
+        .into_iter()
+        .map(|triangle| triangle.map(|index| new_indices[targets[index as usize] as usize]))
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .map(TriangleDefinition)
+        .collect::<Vec<_>>();
+
+    let vertex_size = data.vertex_buffer.vertex_size() as usize;
+    let raw_data = data.vertex_buffer.raw_data();
+    let mut vertex_buffer = data.vertex_buffer.clone();
+    {
+        let mut vertices = vertex_buffer.modify();
+        vertices.clear();
+        for index in kept {
+            Log::verify(
+                vertices.push_vertex_raw(&raw_data[index * vertex_size..(index + 1) * vertex_size]),
+            );
+        }
+    }
+
+    Some(SurfaceData::new(
+        vertex_buffer,
+        TriangleBuffer::new(triangles),
+    ))
+}
+
+/// Returns the surfaces of the mesh with the fix applied to every one of them. The fixed surfaces
+/// are embedded into the scene, so the source model of the mesh is not changed.
+fn repaired_surfaces(mesh: &Mesh, fix: Fix, epsilon: f32) -> Vec<Surface> {
+    mesh.surfaces()
+        .iter()
+        .map(|surface| {
+            let fixed = {
+                let data = surface.data();
+                let data = data.data_ref();
+                match fix {
+                    Fix::Remove(defect) => remove_triangles(&data, defect),
+                    Fix::Normals => fix_normals(&data),
+                    Fix::Merge => merge_vertices(&data, epsilon),
+                }
+            };
+            match fixed {
+                Some(fixed) => {
+                    SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, fixed))
+                        .with_material(surface.material().clone())
+                        .with_bones(surface.bones().to_vec())
+                        .build()
+                }
+                None => surface.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Replaces the surfaces of a mesh. The replaced surfaces are stored in the command, so the same
+/// swap both executes and reverts it.
+#[derive(Debug)]
+struct SetSurfacesCommand {
+    mesh: Handle<Node>,
+    surfaces: Vec<Surface>,
+}
+
+impl SetSurfacesCommand {
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        if let Some(mesh) = graph.try_get_mut_of_type::<Mesh>(self.mesh) {
+            let surfaces = mesh.surfaces_mut();
+            if surfaces.len() == self.surfaces.len() {
+                surfaces.swap_with_slice(&mut self.surfaces);
+            }
+        }
+    }
+}
+
+impl CommandTrait for SetSurfacesCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Repair Mesh".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+}
+
+#[derive(Default)]
+pub struct MeshRepair {
+    menu_item: Handle<UiNode>,
+    window: Handle<UiNode>,
+    analyze: Handle<UiNode>,
+    remove_zero_area: Handle<UiNode>,
+    remove_slivers: Handle<UiNode>,
+    fix_normals: Handle<UiNode>,
+    merge_vertices: Handle<UiNode>,
+    epsilon_text: Handle<UiNode>,
+    epsilon_bar: Handle<UiNode>,
+    results: Handle<UiNode>,
+    epsilon: f32,
+}
+
+impl MeshRepair {
+    /// Applies the fix (if any) to the selected meshes of the current scene and reports the
+    /// issues of every mesh after that. The fix is applied with a single command, so it can be
+    /// undone.
+    fn run(&self, editor: &mut Editor, fix: Option<Fix>) -> Vec<String> {
+        let Some(entry) = editor.scenes.current_scene_entry_ref() else {
+            return vec!["No scene is opened".to_string()];
+        };
+        let Some(game_scene) = entry.controller.downcast_ref::<GameScene>() else {
+            return vec!["The current scene is not a game scene".to_string()];
+        };
+        let selected = entry
+            .selection
+            .as_graph()
+            .map(|selection| selection.nodes().to_vec())
+            .unwrap_or_default();
+        let Some(scene) = editor.engine.scenes.try_get(game_scene.scene) else {
+            return Vec::new();
+        };
+
+        let mut report = Vec::new();
+        let mut total = Issues::default();
+        let mut commands = Vec::new();
+        for handle in selected {
+            let Some(mesh) = scene.graph.try_get_of_type::<Mesh>(handle) else {
+                continue;
+            };
+            // The command is executed later, so the report is made from the repaired surfaces.
+            let repaired = fix.map(|fix| repaired_surfaces(mesh, fix, self.epsilon));
+            let mut issues = Issues::default();
+            for surface in repaired.as_deref().unwrap_or(mesh.surfaces()) {
+                issues.add(analyze(&surface.data().data_ref(), self.epsilon));
+            }
+            total.add(issues);
+            report.push(format!("{}: {}", mesh.name(), issues.describe()));
+            if let Some(surfaces) = repaired {
+                commands.push(Command::new(SetSurfacesCommand {
+                    mesh: handle,
+                    surfaces,
+                }));
+            }
+        }
+        if !commands.is_empty() {
+            editor
+                .message_sender
+                .do_command(CommandGroup::from(commands).with_custom_name("Repair Meshes"));
+        }
+
+        if report.is_empty() {
+            vec!["Select one or more meshes".to_string()]
+        } else {
+            report.insert(0, format!("Total: {}", total.describe()));
+            report
+        }
+    }
+
+    fn show_report(&self, editor: &mut Editor, report: Vec<String>) {
+        let ui = editor.engine.user_interfaces.first_mut();
+        let items = report
+            .into_iter()
+            .map(|line| {
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                    .with_text(line)
+                    .build(&mut ui.build_ctx())
+            })
+            .collect();
+        ui.send_message(ListViewMessage::items(
+            self.results,
+            MessageDirection::ToWidget,
+            items,
+        ));
+    }
+}
+
+impl EditorPlugin for MeshRepair {
+    fn on_start(&mut self, editor: &mut Editor) {
+        self.epsilon = DEFAULT_MERGE_EPSILON;
+
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+
+        self.menu_item = create_menu_item("Mesh Repair", vec![], ctx);
+
+        self.analyze = make_button(ctx, "Analyze");
+        self.remove_zero_area = make_button(ctx, "Remove Zero-Area");
+        self.remove_slivers = make_button(ctx, "Remove Slivers");
+        self.fix_normals = make_button(ctx, "Fix Normals");
+        self.merge_vertices = make_button(ctx, "Merge Vertices");
+        self.epsilon_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_column(0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text(format!("Merge Epsilon: {DEFAULT_MERGE_EPSILON:.4}"))
+        .build(ctx);
+        self.epsilon_bar = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_height(20.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .with_min(MIN_MERGE_EPSILON)
+        .with_max(MAX_MERGE_EPSILON)
+        .with_step(MIN_MERGE_EPSILON)
+        .with_value(DEFAULT_MERGE_EPSILON)
+        .build(ctx);
+        self.results = ListViewBuilder::new(
+            WidgetBuilder::new()
+                .on_row(3)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .build(ctx);
+
+        let buttons = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_child(self.analyze)
+                .with_child(self.remove_zero_area)
+                .with_child(self.remove_slivers),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+        let merge = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_child(self.fix_normals)
+                .with_child(self.merge_vertices),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+        let epsilon = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_child(self.epsilon_text)
+                .with_child(self.epsilon_bar),
+        )
+        .add_column(Column::strict(150.0))
+        .add_column(Column::stretch())
+        .add_row(Row::auto())
+        .build(ctx);
+
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(460.0)
+                .with_height(260.0)
+                .with_desired_position(Vector2::new(300.0, 200.0)),
+        )
+        .with_title(WindowTitle::text("Mesh Repair"))
+        .open(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(buttons)
+                    .with_child(merge)
+                    .with_child(epsilon)
+                    .with_child(self.results),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        ui.send_message(MenuItemMessage::add_item(
+            editor.menu.utils_menu.menu,
+            MessageDirection::ToWidget,
+            self.menu_item,
+        ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.menu_item {
+                editor
+                    .engine
+                    .user_interfaces
+                    .first()
+                    .send_message(WindowMessage::open(
+                        self.window,
+                        MessageDirection::ToWidget,
+                        true,
+                        true,
+                    ));
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.epsilon_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.epsilon = *value;
+                editor
+                    .engine
+                    .user_interfaces
+                    .first()
+                    .send_message(TextMessage::text(
+                        self.epsilon_text,
+                        MessageDirection::ToWidget,
+                        format!("Merge Epsilon: {value:.4}"),
+                    ));
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            let destination = message.destination();
+            let fix = if destination == self.analyze {
+                None
+            } else if destination == self.remove_zero_area {
+                Some(Fix::Remove(Defect::ZeroArea))
+            } else if destination == self.remove_slivers {
+                Some(Fix::Remove(Defect::Sliver))
+            } else if destination == self.fix_normals {
+                Some(Fix::Normals)
+            } else if destination == self.merge_vertices {
+                Some(Fix::Merge)
+            } else {
+                return;
+            };
+            let report = self.run(editor, fix);
+            self.show_report(editor, report);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Editor plugin, that copies the selected nodes with their descendants to a clipboard and pastes
+//! them as children of the selected node. Ctrl+Shift+C copies, Ctrl+Shift+X cuts and Ctrl+Shift+V
+//! pastes, Ctrl+C and Ctrl+V without Shift are left to the editor's own clipboard. The copied
+//! nodes are stored in a graph of their own, that is serialized to memory, so the clipboard does
+//! not depend on the scene, that the nodes were copied from. Cut and paste are done with editor
+//! commands, so they can be undone.
+use fyrox::{
+    core::{
+        algebra::Vector2,
+        log::Log,
+        pool::Handle,
+        visitor::{Visit, Visitor},
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::{KeyCode, MessageDirection, UiMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        Thickness, UiNode,
+    },
+    scene::{
+        graph::{Graph, SubGraph},
+        node::Node,
+    },
+};
+use fyroxed_base::{
+    command::{Command, CommandContext, CommandGroup, CommandTrait},
+    plugin::EditorPlugin,
+    scene::{
+        commands::{graph::DeleteSubGraphCommand, ChangeSelectionCommand, GameSceneContext},
+        GameScene, Selection,
+    },
+    Editor,
+};
+
+#[derive(Default)]
+pub struct NodeClipboard {
+    window: Handle<UiNode>,
+    status: Handle<UiNode>,
+    /// Serialized graph with the copied nodes as children of its root.
+    buffer: Vec<u8>,
+    node_count: usize,
+}
+
+/// Copies the nodes with their descendants to a new graph and serializes it.
+fn serialize_nodes(graph: &Graph, nodes: &[Handle<Node>]) -> Result<(Vec<u8>, usize), String> {
+    let mut clipboard = Graph::new();
+    let root = clipboard.get_root();
+    for &node in nodes {
+        let (copy, _) = graph.copy_node(node, &mut clipboard, &mut |_, _| true);
+        clipboard.link_nodes(copy, root);
+    }
+    // The root of the graph is not a part of the clipboard.
+    let node_count = clipboard.linear_iter().count() - 1;
+
+    let mut visitor = Visitor::new();
+    clipboard
+        .visit("Clipboard", &mut visitor)
+        .map_err(|err| format!("{err:?}"))?;
+    let mut buffer = Vec::new();
+    visitor
+        .save_binary_to_memory(&mut buffer)
+        .map_err(|err| format!("{err:?}"))?;
+    Ok((buffer, node_count))
+}
+
+/// Returns the nodes, that have no ancestors among the given nodes. Descendants are copied and
+/// removed together with their ancestors.
+fn topmost_nodes(graph: &Graph, nodes: &[Handle<Node>]) -> Vec<Handle<Node>> {
+    nodes
+        .iter()
+        .copied()
+        .filter(|&node| {
+            let mut parent = graph[node].parent();
+            while parent.is_some() {
+                if nodes.contains(&parent) {
+                    return false;
+                }
+                parent = graph[parent].parent();
+            }
+            true
+        })
+        .collect()
+}
+
+fn deserialize_nodes(buffer: &[u8]) -> Result<Graph, String> {
+    let mut visitor = Visitor::load_from_memory(buffer).map_err(|err| format!("{err:?}"))?;
+    let mut clipboard = Graph::new();
+    clipboard
+        .visit("Clipboard", &mut visitor)
+        .map_err(|err| format!("{err:?}"))?;
+    Ok(clipboard)
+}
+
+/// Pastes the nodes from a clipboard graph as children of a node. The first execution copies the
+/// nodes from the clipboard graph. After that, the pasted nodes are detached on undo and put back
+/// on redo, so their handles stay the same.
+#[derive(Debug)]
+struct PasteNodesCommand {
+    parent: Handle<Node>,
+    /// Clipboard graph, that is taken by the first execution.
+    clipboard: Option<Graph>,
+    pasted: Vec<Handle<Node>>,
+    detached: Vec<SubGraph>,
+}
+
+impl CommandTrait for PasteNodesCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Paste Nodes".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        if let Some(clipboard) = self.clipboard.take() {
+            let clipboard_root = clipboard.get_root();
+            for &node in clipboard[clipboard_root].children() {
+                let (copy, _) = clipboard.copy_node(node, graph, &mut |_, _| true);
+                graph.link_nodes(copy, self.parent);
+                self.pasted.push(copy);
+            }
+        } else {
+            for sub_graph in self.detached.drain(..) {
+                self.pasted.push(graph.put_sub_graph_back(sub_graph));
+            }
+        }
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        for node in self.pasted.drain(..) {
+            if graph.is_valid_handle(node) {
+                self.detached.push(graph.take_reserve_sub_graph(node));
+            }
+        }
+    }
+}
+
+impl NodeClipboard {
+    /// Copies the selected nodes to the clipboard and removes them from the scene with a command,
+    /// if `cut` is set.
+    fn copy(&mut self, editor: &mut Editor, cut: bool) -> Result<(), String> {
+        let entry = editor
+            .scenes
+            .current_scene_entry_ref()
+            .ok_or("no scene is opened")?;
+        let game_scene = entry
+            .controller
+            .downcast_ref::<GameScene>()
+            .ok_or("the current scene is not a game scene")?;
+        let scene = editor
+            .engine
+            .scenes
+            .try_get(game_scene.scene)
+            .ok_or("the scene does not exist")?;
+        let root = scene.graph.get_root();
+        let selected = entry
+            .selection
+            .as_graph()
+            .map(|selection| selection.nodes().to_vec())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&node| node != root && scene.graph.is_valid_handle(node))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            return Err("select one or more nodes".to_string());
+        }
+        let selected = topmost_nodes(&scene.graph, &selected);
+
+        let (buffer, node_count) = serialize_nodes(&scene.graph, &selected)?;
+        self.buffer = buffer;
+        self.node_count = node_count;
+
+        if cut {
+            // The selection is cleared first, so it does not refer to the deleted nodes.
+            let commands = std::iter::once(Command::new(ChangeSelectionCommand::new(
+                Selection::default(),
+            )))
+            .chain(
+                selected
+                    .into_iter()
+                    .map(|node| Command::new(DeleteSubGraphCommand::new(node))),
+            )
+            .collect::<Vec<_>>();
+            editor
+                .message_sender
+                .do_command(CommandGroup::from(commands).with_custom_name("Cut Nodes"));
+        }
+        Ok(())
+    }
+
+    /// Pastes the nodes from the clipboard as children of the first selected node or the root.
+    fn paste(&self, editor: &mut Editor) -> Result<(), String> {
+        if self.buffer.is_empty() {
+            return Err("the clipboard is empty".to_string());
+        }
+        let clipboard = deserialize_nodes(&self.buffer)?;
+
+        let entry = editor
+            .scenes
+            .current_scene_entry_ref()
+            .ok_or("no scene is opened")?;
+        let game_scene = entry
+            .controller
+            .downcast_ref::<GameScene>()
+            .ok_or("the current scene is not a game scene")?;
+        let scene = editor
+            .engine
+            .scenes
+            .try_get(game_scene.scene)
+            .ok_or("the scene does not exist")?;
+        let parent = entry
+            .selection
+            .as_graph()
+            .and_then(|selection| selection.nodes().first().copied())
+            .filter(|&node| scene.graph.is_valid_handle(node))
+            .unwrap_or_else(|| scene.graph.get_root());
+
+        editor.message_sender.do_command(PasteNodesCommand {
+            parent,
+            clipboard: Some(clipboard),
+            pasted: Vec::new(),
+            detached: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn set_status(&self, editor: &Editor, status: String) {
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(TextMessage::text(
+                self.status,
+                MessageDirection::ToWidget,
+                status,
+            ));
+    }
+}
+
+impl EditorPlugin for NodeClipboard {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interfaces.first_mut().build_ctx();
+
+        self.status = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_text("Clipboard: empty (Ctrl+Shift+C/X/V)")
+            .build(ctx);
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(320.0)
+                .with_height(60.0)
+                .with_desired_position(Vector2::new(300.0, 400.0)),
+        )
+        .with_title(WindowTitle::text("Node Clipboard"))
+        .can_close(false)
+        .with_content(self.status)
+        .build(ctx);
+
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                false,
+                false,
+            ));
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        let Some(WidgetMessage::KeyDown(key)) = message.data() else {
+            return;
+        };
+        let modifiers = editor.engine.user_interfaces.first().keyboard_modifiers();
+        if message.handled() || !modifiers.control || !modifiers.shift {
+            return;
+        }
+
+        let result = match key {
+            KeyCode::KeyC => self.copy(editor, false),
+            KeyCode::KeyX => self.copy(editor, true),
+            KeyCode::KeyV => self.paste(editor),
+            _ => return,
+        };
+        message.set_handled(true);
+
+        let status = match result {
+            Ok(()) => format!("Clipboard: {} node(s)", self.node_count),
+            Err(err) => {
+                Log::warn(format!("Node clipboard: {err}"));
+                format!("Clipboard: {} node(s) - {err}", self.node_count)
+            }
+        };
+        self.set_status(editor, status);
+    }
+}
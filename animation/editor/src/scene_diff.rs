@@ -0,0 +1,530 @@
+//! Editor plugin, that shows which nodes changed since the scene was opened. When a scene is opened,
+//! the transforms and the script fields of all its nodes are remembered together with a copy of
+//! the nodes. Ctrl+D compares the scene with the snapshot and lists added, removed and modified
+//! nodes with the values of their changed fields. "Undo All Changes" brings the scene back to the
+//! snapshot with an editor command, so it can be undone. Restored removed nodes get new handles,
+//! so references to them are not restored.
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::Reflect,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode, MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        text::{TextBuilder, TextMessage},
+        tree::{TreeBuilder, TreeRootBuilder, TreeRootMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode,
+    },
+    scene::{graph::Graph, node::Node, Scene},
+};
+use fyroxed_base::{
+    command::{CommandContext, CommandTrait},
+    plugin::EditorPlugin,
+    scene::{commands::GameSceneContext, GameScene},
+    Editor,
+};
+use std::collections::{HashMap, HashSet};
+
+const ADDED_COLOR: Color = Color::opaque(80, 200, 80);
+const REMOVED_COLOR: Color = Color::opaque(220, 70, 70);
+const MODIFIED_COLOR: Color = Color::opaque(230, 200, 60);
+
+/// State of a node at the moment, when the scene was opened.
+#[derive(Debug)]
+struct Snapshot {
+    name: String,
+    parent: Handle<Node>,
+    /// Names and formatted values of the transform and the script fields.
+    fields: Vec<(String, String)>,
+    /// Copy of the node in the backup graph, that is used to undo the changes.
+    backup: Handle<Node>,
+}
+
+#[derive(Debug)]
+struct SceneSnapshot {
+    nodes: HashMap<Handle<Node>, Snapshot>,
+    backup: Graph,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+struct NodeDiff {
+    name: String,
+    kind: ChangeKind,
+    /// Names of the fields with their values before and after the change.
+    fields: Vec<(String, String, String)>,
+}
+
+#[derive(Default)]
+pub struct SceneDiff {
+    window: Handle<UiNode>,
+    status: Handle<UiNode>,
+    tree_root: Handle<UiNode>,
+    undo: Handle<UiNode>,
+    snapshots: HashMap<Handle<Scene>, SceneSnapshot>,
+}
+
+fn format_vector(v: &Vector3<f32>) -> String {
+    format!("({:.3}, {:.3}, {:.3})", v.x, v.y, v.z)
+}
+
+fn format_rotation(rotation: &UnitQuaternion<f32>) -> String {
+    let (roll, pitch, yaw) = rotation.euler_angles();
+    format_vector(&Vector3::new(roll, pitch, yaw).map(f32::to_degrees))
+}
+
+/// Names and formatted values of the fields of the node, that are compared. Values are compared
+/// as strings, the transform is rounded, so float noise does not count as a change.
+fn node_fields(node: &Node) -> Vec<(String, String)> {
+    let transform = node.local_transform();
+    let mut fields = vec![
+        ("Position".to_string(), format_vector(transform.position())),
+        (
+            "Rotation".to_string(),
+            format_rotation(transform.rotation()),
+        ),
+        ("Scale".to_string(), format_vector(transform.scale())),
+    ];
+
+    for script in node.scripts() {
+        let script_name = script
+            .type_name()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let mut names = Vec::new();
+        script.fields_info(&mut |info| {
+            names = info.iter().map(|field| field.name.to_string()).collect();
+        });
+        script.fields(&mut |values: &[&dyn Reflect]| {
+            for (name, value) in names.iter().zip(values) {
+                fields.push((format!("{script_name}.{name}"), format!("{value:?}")));
+            }
+        });
+    }
+    fields
+}
+
+/// Remembers the state of all the nodes under the root, the root itself is not compared.
+fn take_snapshot(graph: &Graph, root: Handle<Node>) -> SceneSnapshot {
+    let mut backup = Graph::new();
+    let (_, handle_map) = graph.copy_node(root, &mut backup, &mut |_, _| true);
+    let nodes = graph
+        .traverse_handle_iter(root)
+        .filter(|&handle| handle != root)
+        .map(|handle| {
+            let node = &graph[handle];
+            let snapshot = Snapshot {
+                name: node.name().to_string(),
+                parent: node.parent(),
+                fields: node_fields(node),
+                backup: handle_map.inner().get(&handle).copied().unwrap_or_default(),
+            };
+            (handle, snapshot)
+        })
+        .collect();
+    SceneSnapshot { nodes, backup }
+}
+
+fn diff(snapshot: &SceneSnapshot, graph: &Graph, root: Handle<Node>) -> Vec<NodeDiff> {
+    let mut diffs = Vec::new();
+    let mut present = HashSet::new();
+
+    for handle in graph.traverse_handle_iter(root).filter(|&h| h != root) {
+        present.insert(handle);
+        let node = &graph[handle];
+        let current = node_fields(node);
+        let Some(previous) = snapshot.nodes.get(&handle) else {
+            diffs.push(NodeDiff {
+                name: node.name().to_string(),
+                kind: ChangeKind::Added,
+                fields: current
+                    .into_iter()
+                    .map(|(name, value)| (name, "-".to_string(), value))
+                    .collect(),
+            });
+            continue;
+        };
+
+        let before = previous.fields.iter().cloned().collect::<HashMap<_, _>>();
+        let mut fields = current
+            .iter()
+            .filter(|(name, value)| before.get(name) != Some(value))
+            .map(|(name, value)| {
+                let old = before.get(name).cloned().unwrap_or_else(|| "-".to_string());
+                (name.clone(), old, value.clone())
+            })
+            .collect::<Vec<_>>();
+        let after = current.into_iter().collect::<HashMap<_, _>>();
+        fields.extend(
+            previous
+                .fields
+                .iter()
+                .filter(|(name, _)| !after.contains_key(name))
+                .map(|(name, value)| (name.clone(), value.clone(), "-".to_string())),
+        );
+        if previous.name != node.name() {
+            fields.insert(
+                0,
+                (
+                    "Name".to_string(),
+                    previous.name.clone(),
+                    node.name().to_string(),
+                ),
+            );
+        }
+        if !fields.is_empty() {
+            diffs.push(NodeDiff {
+                name: node.name().to_string(),
+                kind: ChangeKind::Modified,
+                fields,
+            });
+        }
+    }
+
+    for (handle, previous) in snapshot.nodes.iter() {
+        if !present.contains(handle) {
+            diffs.push(NodeDiff {
+                name: previous.name.clone(),
+                kind: ChangeKind::Removed,
+                fields: previous
+                    .fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone(), "-".to_string()))
+                    .collect(),
+            });
+        }
+    }
+
+    diffs.sort_by(|a, b| a.name.cmp(&b.name));
+    diffs
+}
+
+/// Brings the nodes under the root back to the state of the snapshot.
+fn undo_all(snapshot: &SceneSnapshot, graph: &mut Graph, root: Handle<Node>) {
+    let present = graph
+        .traverse_handle_iter(root)
+        .filter(|&h| h != root)
+        .collect::<Vec<_>>();
+
+    // Added nodes, a node could be already removed together with its added ancestor.
+    for &handle in present.iter() {
+        if !snapshot.nodes.contains_key(&handle) && graph.is_valid_handle(handle) {
+            graph.remove_node(handle);
+        }
+    }
+
+    // Modified nodes.
+    for &handle in present.iter() {
+        let (Some(previous), Some(node)) = (snapshot.nodes.get(&handle), graph.try_get_mut(handle))
+        else {
+            continue;
+        };
+        let backup = &snapshot.backup[previous.backup];
+        node.set_name(previous.name.clone());
+        *node.local_transform_mut() = backup.local_transform().clone();
+        // Scripts, that were added after the snapshot was taken, are removed.
+        let scripts = backup.scripts().cloned().collect::<Vec<_>>();
+        for index in 0..node.scripts().count().max(scripts.len()) {
+            node.replace_script(index, scripts.get(index).cloned());
+        }
+    }
+
+    // Removed nodes are copied back from the backup graph, only the topmost removed nodes are
+    // copied, their removed descendants are copied with them.
+    let removed = snapshot
+        .nodes
+        .iter()
+        .filter(|(handle, _)| !graph.is_valid_handle(**handle))
+        .collect::<HashMap<_, _>>();
+    let removed_backups = removed
+        .values()
+        .map(|previous| previous.backup)
+        .collect::<HashSet<_>>();
+    for previous in removed.values() {
+        if removed.contains_key(&previous.parent) {
+            continue;
+        }
+        let (copy, _) = snapshot
+            .backup
+            .copy_node(previous.backup, graph, &mut |handle, _| {
+                removed_backups.contains(&handle)
+            });
+        let parent = if graph.is_valid_handle(previous.parent) {
+            previous.parent
+        } else {
+            root
+        };
+        graph.link_nodes(copy, parent);
+    }
+}
+
+/// Brings the scene back to the snapshot. The state of the scene before the command is taken as a
+/// snapshot too, so the same swap both executes and reverts the command.
+#[derive(Debug)]
+struct UndoAllCommand {
+    root: Handle<Node>,
+    snapshot: SceneSnapshot,
+}
+
+impl UndoAllCommand {
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        let current = take_snapshot(graph, self.root);
+        undo_all(&self.snapshot, graph, self.root);
+        self.snapshot = current;
+    }
+}
+
+impl CommandTrait for UndoAllCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Undo All Changes".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+}
+
+fn make_diff_item(ctx: &mut BuildContext, diff: &NodeDiff) -> Handle<UiNode> {
+    let (color, kind) = match diff.kind {
+        ChangeKind::Added => (ADDED_COLOR, "added"),
+        ChangeKind::Removed => (REMOVED_COLOR, "removed"),
+        ChangeKind::Modified => (MODIFIED_COLOR, "modified"),
+    };
+
+    let mut children = Vec::new();
+    let rows = std::iter::once(("Field", "Before", "After")).chain(
+        diff.fields
+            .iter()
+            .map(|(name, before, after)| (name.as_str(), before.as_str(), after.as_str())),
+    );
+    for (row, (name, before, after)) in rows.enumerate() {
+        for (column, text) in [name, before, after].into_iter().enumerate() {
+            children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(row)
+                        .on_column(column)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_text(text)
+                .build(ctx),
+            );
+        }
+    }
+    let fields = GridBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::left(16.0))
+            .with_children(children),
+    )
+    .add_column(Column::strict(160.0))
+    .add_column(Column::stretch())
+    .add_column(Column::stretch())
+    .add_rows(vec![Row::auto(); diff.fields.len() + 1])
+    .build(ctx);
+
+    TreeBuilder::new(WidgetBuilder::new())
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(color)))
+                .with_text(format!("{} ({kind})", diff.name))
+                .build(ctx),
+        )
+        .with_items(vec![TreeBuilder::new(WidgetBuilder::new())
+            .with_content(fields)
+            .build(ctx)])
+        .build(ctx)
+}
+
+/// Returns the handle, the scene and the root of the content of the current game scene.
+fn current_scene(editor: &mut Editor) -> Option<(Handle<Scene>, &mut Scene, Handle<Node>)> {
+    let entry = editor.scenes.current_scene_entry_mut()?;
+    let game_scene = entry.controller.downcast_mut::<GameScene>()?;
+    let scene = editor.engine.scenes.try_get_mut(game_scene.scene)?;
+    Some((game_scene.scene, scene, game_scene.scene_content_root))
+}
+
+impl SceneDiff {
+    fn set_status(&self, editor: &Editor, status: String) {
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(TextMessage::text(
+                self.status,
+                MessageDirection::ToWidget,
+                status,
+            ));
+    }
+
+    fn show_diff(&mut self, editor: &mut Editor) {
+        let diffs = match current_scene(editor) {
+            Some((handle, scene, root)) => match self.snapshots.get(&handle) {
+                Some(snapshot) => diff(snapshot, &scene.graph, root),
+                None => Vec::new(),
+            },
+            None => {
+                Log::warn("Scene diff: no game scene is opened");
+                return;
+            }
+        };
+
+        let status = if diffs.is_empty() {
+            "No changes since the scene was opened".to_string()
+        } else {
+            format!("{} node(s) changed since the scene was opened", diffs.len())
+        };
+        self.set_status(editor, status);
+
+        let ui = editor.engine.user_interfaces.first_mut();
+        let items = diffs
+            .iter()
+            .map(|diff| make_diff_item(&mut ui.build_ctx(), diff))
+            .collect();
+        ui.send_message(TreeRootMessage::items(
+            self.tree_root,
+            MessageDirection::ToWidget,
+            items,
+        ));
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            false,
+            true,
+        ));
+    }
+
+    fn undo_all(&mut self, editor: &mut Editor) {
+        let Some((handle, _, root)) = current_scene(editor) else {
+            return;
+        };
+        // Restored nodes get new handles, so the snapshot is taken again in `on_post_update`,
+        // after the command is executed.
+        if let Some(snapshot) = self.snapshots.remove(&handle) {
+            editor
+                .message_sender
+                .do_command(UndoAllCommand { root, snapshot });
+        }
+        self.set_status(editor, "All changes are undone".to_string());
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(TreeRootMessage::items(
+                self.tree_root,
+                MessageDirection::ToWidget,
+                Vec::new(),
+            ));
+    }
+}
+
+impl EditorPlugin for SceneDiff {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interfaces.first_mut().build_ctx();
+
+        self.status = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Press Ctrl+D to compare the scene with its opened state")
+        .build(ctx);
+        self.tree_root = TreeRootBuilder::new(WidgetBuilder::new()).build(ctx);
+        let tree = ScrollViewerBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_content(self.tree_root)
+        .build(ctx);
+        self.undo = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_height(24.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Undo All Changes")
+        .build(ctx);
+
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(520.0)
+                .with_height(400.0)
+                .with_desired_position(Vector2::new(300.0, 200.0)),
+        )
+        .with_title(WindowTitle::text("Scene Diff"))
+        .open(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.status)
+                    .with_child(tree)
+                    .with_child(self.undo),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .add_row(Row::auto())
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.undo {
+                self.undo_all(editor);
+            }
+            return;
+        }
+
+        let Some(WidgetMessage::KeyDown(KeyCode::KeyD)) = message.data() else {
+            return;
+        };
+        if message.handled()
+            || !editor
+                .engine
+                .user_interfaces
+                .first()
+                .keyboard_modifiers()
+                .control
+        {
+            return;
+        }
+        message.set_handled(true);
+        self.show_diff(editor);
+    }
+
+    fn on_post_update(&mut self, editor: &mut Editor) {
+        // Snapshots of the closed scenes are not needed anymore.
+        let scenes = &editor.engine.scenes;
+        self.snapshots
+            .retain(|handle, _| scenes.try_get(*handle).is_some());
+
+        if let Some((handle, scene, root)) = current_scene(editor) {
+            self.snapshots
+                .entry(handle)
+                .or_insert_with(|| take_snapshot(&scene.graph, root));
+        }
+    }
+}
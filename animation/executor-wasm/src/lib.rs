@@ -45,6 +45,7 @@ pub fn set_panic_hook() {
 #[wasm_bindgen]
 pub fn main() {
     set_panic_hook();
+    common_scripts::throttle::watch_page_visibility();
     let mut window_attributes = WindowAttributes::default();
     window_attributes.inner_size = Some(LogicalSize::new(1280.0, 720.0).into());
     window_attributes.title = "Animation".to_string();
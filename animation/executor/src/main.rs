@@ -1,11 +1,24 @@
 //! Executor with your game connected to it as a plugin.
-use animation::Game;
+use animation::{prefab_bench::PrefabBench, Game};
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
 use fyrox::{
     dpi::LogicalSize, engine::executor::Executor, engine::GraphicsContextParams,
     event_loop::EventLoop, window::WindowAttributes,
 };
 
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+
 fn main() {
+    // `--ecs-bench` compares script iteration strategies, writes the results to stdout and exits.
+    if std::env::args().any(|arg| arg == "--ecs-bench") {
+        animation::ecs_bench::run();
+        return;
+    }
+
     let mut window_attributes = WindowAttributes::default();
     window_attributes.inner_size = Some(LogicalSize::new(1280.0, 720.0).into());
     window_attributes.title = "Animation".to_string();
@@ -18,6 +31,41 @@ fn main() {
             graphics_server_constructor: Default::default(),
         },
     );
+    // `--prefab-bench` measures instantiation of the player prefab, writes the results to stdout
+    // and exits.
+    if std::env::args().any(|arg| arg == "--prefab-bench") {
+        executor.add_plugin(PrefabBench::default());
+        executor.run();
+        return;
+    }
+
+    #[cfg(feature = "net")]
+    executor.add_plugin(Game::with_network_sync(connect_address()));
+    #[cfg(not(feature = "net"))]
     executor.add_plugin(Game::default());
+    #[cfg(feature = "record")]
+    if let Some(recorder) = Recorder::from_args() {
+        executor.add_plugin(recorder);
+    }
+    executor.add_plugin(FramePacer::default());
     executor.run()
 }
+
+/// Parses `--connect <ip:port>` argument - the address of the peer, that will receive the state
+/// of the player.
+#[cfg(feature = "net")]
+fn connect_address() -> Option<std::net::SocketAddr> {
+    let mut args = std::env::args()
+        .skip_while(|arg| arg != "--connect")
+        .skip(1);
+    let address = args.next()?;
+    match address.parse() {
+        Ok(address) => Some(address),
+        Err(err) => {
+            fyrox::core::log::Log::err(format!(
+                "Unable to parse peer address {address}. Reason: {err}"
+            ));
+            None
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Customizable keyboard bindings of the player and a window, that allows to change them.
+use fyrox::{
+    core::{algebra::Vector2, log::Log, pool::Handle, visitor::prelude::*},
+    event::{ElementState, Event, WindowEvent},
+    gui::{
+        button::{ButtonBuilder, ButtonContent, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode, MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    keyboard::PhysicalKey,
+    utils::translate_key_to_ui,
+};
+
+/// Path of the file with the bindings, relative to the working directory.
+const BINDINGS_PATH: &str = "data/bindings.bin";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    WalkForward,
+    WalkBackward,
+    WalkLeft,
+    WalkRight,
+    Run,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::WalkForward,
+        Action::WalkBackward,
+        Action::WalkLeft,
+        Action::WalkRight,
+        Action::Run,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Action::WalkForward => "Walk Forward",
+            Action::WalkBackward => "Walk Backward",
+            Action::WalkLeft => "Walk Left",
+            Action::WalkRight => "Walk Right",
+            Action::Run => "Run",
+        }
+    }
+}
+
+#[derive(Visit, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Bindings {
+    pub walk_forward: KeyCode,
+    pub walk_backward: KeyCode,
+    pub walk_left: KeyCode,
+    pub walk_right: KeyCode,
+    pub run: KeyCode,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            walk_forward: KeyCode::KeyW,
+            walk_backward: KeyCode::KeyS,
+            walk_left: KeyCode::KeyA,
+            walk_right: KeyCode::KeyD,
+            run: KeyCode::ShiftLeft,
+        }
+    }
+}
+
+impl Bindings {
+    /// Loads the bindings from the file, falls back to the default bindings if the file does not
+    /// exist or is corrupted.
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read(BINDINGS_PATH) else {
+            return Self::default();
+        };
+
+        let mut bindings = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => {
+                if let Err(err) = bindings.visit("Bindings", &mut visitor) {
+                    Log::err(format!("Unable to read key bindings: {err:?}"));
+                    bindings = Self::default();
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load key bindings: {err:?}")),
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        let mut bindings = *self;
+        let mut visitor = Visitor::new();
+        Log::verify(bindings.visit("Bindings", &mut visitor));
+        Log::verify(visitor.save_binary(BINDINGS_PATH));
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        match action {
+            Action::WalkForward => self.walk_forward,
+            Action::WalkBackward => self.walk_backward,
+            Action::WalkLeft => self.walk_left,
+            Action::WalkRight => self.walk_right,
+            Action::Run => self.run,
+        }
+    }
+
+    fn key_mut(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::WalkForward => &mut self.walk_forward,
+            Action::WalkBackward => &mut self.walk_backward,
+            Action::WalkLeft => &mut self.walk_left,
+            Action::WalkRight => &mut self.walk_right,
+            Action::Run => &mut self.run,
+        }
+    }
+
+    /// Returns an action, that is bound to the given key, if any.
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .find(|action| self.key(**action) == key)
+            .cloned()
+    }
+}
+
+fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+#[derive(Default, Debug)]
+pub struct KeyBindingsWindow {
+    window: Handle<UiNode>,
+    /// Binding buttons, in the order of [`Action::ALL`].
+    buttons: Vec<Handle<UiNode>>,
+    /// An action, that waits for a key press to be rebound.
+    capturing: Option<Action>,
+}
+
+impl KeyBindingsWindow {
+    pub fn new(ctx: &mut BuildContext, bindings: &Bindings) -> Self {
+        let mut children = Vec::new();
+        let mut buttons = Vec::new();
+        for (row, action) in Action::ALL.iter().enumerate() {
+            children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(row)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_vertical_alignment(VerticalAlignment::Center),
+                )
+                .with_text(action.name())
+                .build(ctx),
+            );
+
+            let button = ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(row)
+                    .on_column(1)
+                    .with_margin(Thickness::uniform(2.0)),
+            )
+            .with_text(&key_name(bindings.key(*action)))
+            .build(ctx);
+            children.push(button);
+            buttons.push(button);
+        }
+
+        let mut grid = GridBuilder::new(WidgetBuilder::new().with_children(children))
+            .add_column(Column::stretch())
+            .add_column(Column::stretch());
+        for _ in Action::ALL {
+            grid = grid.add_row(Row::strict(26.0));
+        }
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(170.0)
+                .with_desired_position(Vector2::new(20.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Key Bindings"))
+        .open(false)
+        .with_content(grid.build(ctx))
+        .build(ctx);
+
+        Self {
+            window,
+            buttons,
+            capturing: None,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            false,
+            true,
+        ));
+    }
+
+    fn set_button_text(&self, action: Action, text: &str, ui: &UserInterface) {
+        ui.send_message(ButtonMessage::content(
+            self.buttons[action as usize],
+            MessageDirection::ToWidget,
+            ButtonContent::text(text),
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        bindings: &Bindings,
+        ui: &UserInterface,
+    ) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if let Some(index) = self
+                .buttons
+                .iter()
+                .position(|button| *button == message.destination())
+            {
+                // Only one binding can be captured at a time, so restore the previous one.
+                if let Some(previous) = self.capturing.take() {
+                    self.set_button_text(previous, &key_name(bindings.key(previous)), ui);
+                }
+
+                let action = Action::ALL[index];
+                self.capturing = Some(action);
+                self.set_button_text(action, "Press any key...", ui);
+            }
+        }
+    }
+
+    /// Assigns a pressed key to the captured action, `Escape` cancels the capture. Returns `true`
+    /// if the event was consumed.
+    pub fn handle_os_event(
+        &mut self,
+        event: &Event<()>,
+        bindings: &mut Bindings,
+        ui: &UserInterface,
+    ) -> bool {
+        let Some(action) = self.capturing else {
+            return false;
+        };
+
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state == ElementState::Pressed {
+                if let PhysicalKey::Code(code) = input.physical_key {
+                    let key = translate_key_to_ui(code);
+                    if key != KeyCode::Escape {
+                        *bindings.key_mut(action) = key;
+                        bindings.save();
+                    }
+                    self.capturing = None;
+                    self.set_button_text(action, &key_name(bindings.key(action)), ui);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,283 @@
+//! Bone weight view - colors the vertices of the skinned meshes of the player by the bone with the
+//! largest weight, so it is easy to see which bone moves which part of the mesh. Every bone gets
+//! its own hue, vertices of the bone, that is selected in the scene graph browser, are drawn at
+//! full brightness and the rest are dimmed.
+use crate::player::Player;
+use fyrox::{
+    asset::{manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        color::{Color, Hsv},
+        log::Log,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::{shader::Shader, Material, MaterialResource},
+    scene::{
+        mesh::{
+            buffer::{
+                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+                VertexReadTrait, VertexWriteTrait,
+            },
+            surface::SurfaceResource,
+            Mesh, RenderPath,
+        },
+        node::Node,
+        Scene,
+    },
+};
+
+const SHADER_PATH: &str = "data/bone_weights.shader";
+/// Hues of consecutive bones are this fraction of the color wheel apart, so even neighbouring
+/// bones get contrasting colors.
+const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+/// Brightness of the vertices of the bones, that are not selected, in percents.
+const DIMMED_BRIGHTNESS: f32 = 30.0;
+/// The standard vertex layout takes the locations up to 6, the color goes right after them.
+const COLOR_SHADER_LOCATION: u8 = 7;
+
+#[derive(Debug)]
+struct ColoredSurface {
+    mesh: Handle<Node>,
+    surface: usize,
+    original_material: MaterialResource,
+    original_render_path: RenderPath,
+    /// Surface data is shared between all the instances of the model, so the colors are written
+    /// to a copy of it.
+    original_data: SurfaceResource,
+    colored_data: SurfaceResource,
+    /// Index of the bone with the largest weight in [`BoneWeightView::bones`] for every vertex.
+    dominant_bones: Vec<Option<usize>>,
+}
+
+#[derive(Default, Debug)]
+pub struct BoneWeightView {
+    enabled: bool,
+    surfaces: Vec<ColoredSurface>,
+    /// Bones of all the colored surfaces, the index of a bone defines its hue.
+    bones: Vec<Handle<Node>>,
+    /// The bone, that is drawn at full brightness, if any.
+    selected: Handle<Node>,
+    text: Handle<UiNode>,
+}
+
+fn bone_color(index: usize, brightness: f32) -> Color {
+    let hue = (index as f32 * GOLDEN_RATIO_CONJUGATE).fract() * 360.0;
+    Color::from(Hsv::new(hue, 100.0, brightness))
+}
+
+impl BoneWeightView {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::uniform(4.0))
+                .with_visibility(false),
+        )
+        .build(ctx);
+
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    pub fn toggle(
+        &mut self,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        ui: &UserInterface,
+    ) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            self.colorize(scene, resource_manager);
+            self.selected = Handle::NONE;
+            self.update_text(scene, ui);
+        } else {
+            self.restore(scene);
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            self.enabled,
+        ));
+    }
+
+    /// Replaces the data of every skinned surface of the player with its copy, that has a color
+    /// attribute, and the materials with an unlit material, that shows the colors.
+    fn colorize(&mut self, scene: &mut Scene, resource_manager: &ResourceManager) {
+        let Some(player) = scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.try_get_script::<Player>().is_some())
+            .map(|(handle, _)| handle)
+        else {
+            return;
+        };
+
+        let meshes = scene
+            .graph
+            .traverse_iter(player)
+            .filter(|(_, node)| node.cast::<Mesh>().is_some())
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        let material = MaterialResource::new_ok(
+            ResourceKind::Embedded,
+            Material::from_shader(resource_manager.request::<Shader>(SHADER_PATH), None),
+        );
+
+        for handle in meshes {
+            let Some(mesh) = scene.graph.try_get_mut_of_type::<Mesh>(handle) else {
+                continue;
+            };
+            let original_render_path = mesh.render_path();
+            let mut is_skinned = false;
+            for (index, surface) in mesh.surfaces_mut().iter_mut().enumerate() {
+                if surface.bones().is_empty() {
+                    continue;
+                }
+                is_skinned = true;
+
+                let original_data = surface.data();
+                let mut data = original_data.data_ref().clone();
+                let mut vertex_buffer = data.vertex_buffer.modify();
+                if !vertex_buffer.has_attribute(VertexAttributeUsage::Color) {
+                    Log::verify(vertex_buffer.add_attribute(
+                        VertexAttributeDescriptor {
+                            usage: VertexAttributeUsage::Color,
+                            data_type: VertexAttributeDataType::U8,
+                            size: 4,
+                            divisor: 0,
+                            shader_location: COLOR_SHADER_LOCATION,
+                            normalized: true,
+                        },
+                        [0u8; 4],
+                    ));
+                }
+
+                let dominant_bones = vertex_buffer
+                    .iter()
+                    .map(|vertex| {
+                        let weights = vertex.read_4_f32(VertexAttributeUsage::BoneWeight).ok()?;
+                        let indices = vertex.read_4_u8(VertexAttributeUsage::BoneIndices).ok()?;
+                        let (_, index) = weights
+                            .iter()
+                            .zip(indices.iter())
+                            .max_by(|(a, _), (b, _)| a.total_cmp(b))?;
+                        let bone = *surface.bones().get(*index as usize)?;
+                        Some(match self.bones.iter().position(|b| *b == bone) {
+                            Some(position) => position,
+                            None => {
+                                self.bones.push(bone);
+                                self.bones.len() - 1
+                            }
+                        })
+                    })
+                    .collect();
+                drop(vertex_buffer);
+
+                let colored_data = SurfaceResource::new_ok(ResourceKind::Embedded, data);
+                surface.set_data(colored_data.clone());
+                let original_material = surface.material().clone();
+                surface.set_material(material.clone());
+
+                self.surfaces.push(ColoredSurface {
+                    mesh: handle,
+                    surface: index,
+                    original_material,
+                    original_render_path,
+                    original_data,
+                    colored_data,
+                    dominant_bones,
+                });
+            }
+
+            // The material has a forward pass only.
+            if is_skinned {
+                mesh.set_render_path(RenderPath::Forward);
+            }
+        }
+
+        self.write_colors();
+    }
+
+    fn write_colors(&self) {
+        let selected = self.bones.iter().position(|bone| *bone == self.selected);
+        for colored in self.surfaces.iter() {
+            let mut data = colored.colored_data.data_ref();
+            for (mut vertex, bone) in data
+                .vertex_buffer
+                .modify()
+                .iter_mut()
+                .zip(colored.dominant_bones.iter())
+            {
+                let color = match bone {
+                    Some(bone) => {
+                        let brightness = if selected.is_none() || selected == Some(*bone) {
+                            100.0
+                        } else {
+                            DIMMED_BRIGHTNESS
+                        };
+                        bone_color(*bone, brightness)
+                    }
+                    None => Color::BLACK,
+                };
+                Log::verify(vertex.write_4_u8(VertexAttributeUsage::Color, color.into()));
+            }
+        }
+    }
+
+    fn restore(&mut self, scene: &mut Scene) {
+        for colored in self.surfaces.drain(..) {
+            let Some(mesh) = scene.graph.try_get_mut_of_type::<Mesh>(colored.mesh) else {
+                continue;
+            };
+            mesh.set_render_path(colored.original_render_path);
+            if let Some(surface) = mesh.surfaces_mut().get_mut(colored.surface) {
+                surface.set_data(colored.original_data);
+                surface.set_material(colored.original_material);
+            }
+        }
+        self.bones.clear();
+    }
+
+    fn update_text(&self, scene: &Scene, ui: &UserInterface) {
+        let text = match scene.graph.try_get(self.selected) {
+            Some(bone) => format!("Bone Weights - Selected Bone: {}", bone.name()),
+            None => format!(
+                "Bone Weights - {} bones, select a bone in the scene graph [F2] to highlight it",
+                self.bones.len()
+            ),
+        };
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    /// Highlights the bone, that is selected in the scene graph browser. Selection of a node,
+    /// that is not a bone of the colored surfaces, resets the highlight.
+    pub fn update(&mut self, scene: &Scene, selected: Handle<Node>, ui: &UserInterface) {
+        if !self.enabled {
+            return;
+        }
+        let selected = if self.bones.contains(&selected) {
+            selected
+        } else {
+            Handle::NONE
+        };
+        if selected != self.selected {
+            self.selected = selected;
+            self.write_colors();
+            self.update_text(scene, ui);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Chat overlay - a window with the history of the chat messages and an input field. Messages are
+//! sent to the peer over the socket of the network sync. The text uses a font with a wide range
+//! of Unicode characters, including emoticons.
+use crate::net::ChatMessage;
+use fyrox::{
+    asset::manager::ResourceManager,
+    core::{algebra::Vector2, pool::Handle},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{KeyCode, MessageDirection, UiMessage},
+        scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBox, TextBoxBuilder},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface,
+    },
+    resource::font::{Font, FontResource},
+};
+use std::collections::VecDeque;
+
+/// Amount of messages in the history, the oldest ones are removed.
+const MAX_MESSAGES: usize = 50;
+const FONT_PATH: &str = "data/fonts/DejaVuSans.ttf";
+const BOLD_FONT_PATH: &str = "data/fonts/DejaVuSans-Bold.ttf";
+
+#[derive(Debug)]
+pub struct ChatOverlay {
+    scroll_viewer: Handle<UiNode>,
+    messages_panel: Handle<UiNode>,
+    text_box: Handle<UiNode>,
+    /// Rows of the history, from the oldest to the newest.
+    messages: VecDeque<Handle<UiNode>>,
+    font: FontResource,
+    /// The font of the names of the senders.
+    bold_font: FontResource,
+    /// Name of the local player, that is sent with every message.
+    name: String,
+}
+
+impl ChatOverlay {
+    pub fn new(ctx: &mut BuildContext, resource_manager: &ResourceManager) -> Self {
+        let font = resource_manager.request::<Font>(FONT_PATH);
+        let bold_font = resource_manager.request::<Font>(BOLD_FONT_PATH);
+
+        let messages_panel = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+        let scroll_viewer =
+            ScrollViewerBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                .with_content(messages_panel)
+                .build(ctx);
+        let text_box = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_font(font.clone())
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(320.0)
+                .with_height(240.0)
+                .with_desired_position(Vector2::new(5.0, 470.0)),
+        )
+        .with_title(WindowTitle::text("Chat"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(scroll_viewer)
+                    .with_child(text_box),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::stretch())
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            scroll_viewer,
+            messages_panel,
+            text_box,
+            messages: Default::default(),
+            font,
+            bold_font,
+            name: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_string()),
+        }
+    }
+
+    /// Adds the message to the end of the history.
+    pub fn add_message(&mut self, ui: &mut UserInterface, message: &ChatMessage) {
+        let ctx = &mut ui.build_ctx();
+        let row = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(1.0))
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_font(self.bold_font.clone())
+                        .with_text(format!("{}: ", message.sender))
+                        .build(ctx),
+                )
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_font(self.font.clone())
+                        .with_text(&message.text)
+                        .build(ctx),
+                ),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        ui.send_message(WidgetMessage::link(
+            row,
+            MessageDirection::ToWidget,
+            self.messages_panel,
+        ));
+        ui.send_message(ScrollViewerMessage::bring_into_view(
+            self.scroll_viewer,
+            MessageDirection::ToWidget,
+            row,
+        ));
+
+        self.messages.push_back(row);
+        while self.messages.len() > MAX_MESSAGES {
+            if let Some(oldest) = self.messages.pop_front() {
+                ui.send_message(WidgetMessage::remove(oldest, MessageDirection::ToWidget));
+            }
+        }
+    }
+
+    /// Returns the typed message, when Enter is pressed in the input field. The message is added
+    /// to the local history as well.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+    ) -> Option<ChatMessage> {
+        let Some(WidgetMessage::KeyDown(KeyCode::Enter | KeyCode::NumpadEnter)) = message.data()
+        else {
+            return None;
+        };
+        if message.destination() != self.text_box {
+            return None;
+        }
+
+        let text = ui
+            .try_get_of_type::<TextBox>(self.text_box)
+            .map(|text_box| text_box.text())?;
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        let chat_message = ChatMessage {
+            sender: self.name.clone(),
+            text: text.to_string(),
+        };
+        ui.send_message(TextMessage::text(
+            self.text_box,
+            MessageDirection::ToWidget,
+            String::new(),
+        ));
+        self.add_message(ui, &chat_message);
+        Some(chat_message)
+    }
+}
@@ -0,0 +1,158 @@
+//! Debug view of frustum culling: meshes inside the frustum of the camera are tinted green, the
+//! ones outside of it are tinted red. The renderer does not report which objects it has culled,
+//! so the same frustum test is done here.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{color::Color, log::Log, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    material::MaterialResource,
+    scene::{camera::Camera, mesh::Mesh, node::Node, Scene},
+};
+
+const VISIBLE_COLOR: Color = Color::opaque(0, 255, 0);
+const CULLED_COLOR: Color = Color::opaque(255, 0, 0);
+
+#[derive(Debug)]
+struct TintedSurface {
+    mesh: Handle<Node>,
+    surface: usize,
+    original: MaterialResource,
+    tinted: MaterialResource,
+    /// Whether the surface is tinted as visible.
+    visible: bool,
+}
+
+#[derive(Default, Debug)]
+pub struct CullingView {
+    enabled: bool,
+    surfaces: Vec<TintedSurface>,
+    visible_meshes: usize,
+    total_meshes: usize,
+}
+
+impl CullingView {
+    pub fn toggle(&mut self, scene: &mut Scene) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            self.tint(scene);
+        } else {
+            self.restore(scene);
+        }
+    }
+
+    /// Replaces the materials of every mesh with their copies, materials are usually shared
+    /// between meshes and each mesh needs its own color. The copies use the same shader, so
+    /// the shadow passes render the meshes the same way as before. Surfaces with materials, that
+    /// have no diffuse color, keep their materials.
+    fn tint(&mut self, scene: &mut Scene) {
+        let mut untinted = 0;
+        for (handle, node) in scene.graph.pair_iter_mut() {
+            let Some(mesh) = node.cast_mut::<Mesh>() else {
+                continue;
+            };
+
+            for (index, surface) in mesh.surfaces_mut().iter_mut().enumerate() {
+                let original = surface.material().clone();
+                let mut material = original.data_ref().clone();
+                if material
+                    .set_property(&"diffuseColor".into(), VISIBLE_COLOR.into())
+                    .is_err()
+                {
+                    untinted += 1;
+                    continue;
+                }
+                let tinted = MaterialResource::new_ok(ResourceKind::Embedded, material);
+                surface.set_material(tinted.clone());
+                self.surfaces.push(TintedSurface {
+                    mesh: handle,
+                    surface: index,
+                    original,
+                    tinted,
+                    visible: true,
+                });
+            }
+        }
+
+        if untinted > 0 {
+            Log::warn(format!(
+                "Culling view is unable to tint {untinted} surfaces, their materials have no diffuse color."
+            ));
+        }
+    }
+
+    fn restore(&mut self, scene: &mut Scene) {
+        for tinted in self.surfaces.drain(..) {
+            if let Some(surface) = scene
+                .graph
+                .try_get_mut_of_type::<Mesh>(tinted.mesh)
+                .and_then(|mesh| mesh.surfaces_mut().get_mut(tinted.surface))
+            {
+                surface.set_material(tinted.original);
+            }
+        }
+    }
+
+    pub fn update(&mut self, scene: &Scene) {
+        if !self.enabled {
+            return;
+        }
+
+        let Some(camera) = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled())
+        else {
+            return;
+        };
+        let frustum = camera.frustum();
+
+        self.visible_meshes = 0;
+        self.total_meshes = 0;
+        let mut last_mesh = Handle::NONE;
+        let mut is_visible = false;
+        for tinted in self.surfaces.iter_mut() {
+            // Surfaces of the same mesh go one after another.
+            if tinted.mesh != last_mesh {
+                last_mesh = tinted.mesh;
+                let Some(mesh) = scene.graph.try_get(tinted.mesh) else {
+                    continue;
+                };
+                is_visible = frustum.is_intersects_aabb(&mesh.world_bounding_box());
+                self.total_meshes += 1;
+                if is_visible {
+                    self.visible_meshes += 1;
+                }
+            }
+
+            // The color is changed only when the visibility changes, the property is known to exist,
+            // since it was set when the surface was tinted.
+            if tinted.visible == is_visible {
+                continue;
+            }
+            tinted.visible = is_visible;
+            let color = if is_visible {
+                VISIBLE_COLOR
+            } else {
+                CULLED_COLOR
+            };
+            Log::verify(
+                tinted
+                    .tinted
+                    .data_ref()
+                    .set_property(&"diffuseColor".into(), color.into()),
+            );
+        }
+    }
+
+    pub fn stats_text(&self) -> String {
+        if self.enabled {
+            format!(
+                "Visible Meshes: {} of {}",
+                self.visible_meshes, self.total_meshes
+            )
+        } else {
+            Default::default()
+        }
+    }
+}
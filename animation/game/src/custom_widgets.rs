@@ -0,0 +1,552 @@
+//! Node graph - a custom widget for visual scripting. It shows a canvas with boxes of nodes, that
+//! are connected by wires going from output ports to input ports. This is only a visual editor,
+//! the graph does not run anything.
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, math::Rect, pool::Handle, reflect::prelude::*,
+        type_traits::prelude::*, visitor::prelude::*,
+    },
+    gui::{
+        brush::Brush,
+        define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        formatted_text::{FormattedText, FormattedTextBuilder},
+        message::{MessageDirection, MouseButton, UiMessage},
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+    resource::font::FontResource,
+};
+use std::ops::{Deref, DerefMut};
+
+const NODE_WIDTH: f32 = 150.0;
+const HEADER_HEIGHT: f32 = 24.0;
+const ROW_HEIGHT: f32 = 22.0;
+const PORT_RADIUS: f32 = 6.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.0;
+/// Labels are not readable when the canvas is zoomed out too much, so they are not drawn.
+const MIN_LABEL_ZOOM: f32 = 0.6;
+
+/// Type of the values, that go through a port. Only ports of the same type can be connected.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect)]
+pub enum PortKind {
+    /// Order of execution.
+    #[default]
+    Exec,
+    Float,
+    Bool,
+}
+
+impl PortKind {
+    fn color(self) -> Color {
+        match self {
+            PortKind::Exec => Color::opaque(230, 230, 230),
+            PortKind::Float => Color::opaque(120, 220, 120),
+            PortKind::Bool => Color::opaque(220, 80, 80),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Visit, Reflect)]
+pub struct Port {
+    name: String,
+    kind: PortKind,
+}
+
+impl Port {
+    pub fn new(name: &str, kind: PortKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Visit, Reflect)]
+pub struct PortId {
+    node: usize,
+    port: usize,
+    is_output: bool,
+}
+
+/// A box on the canvas with input ports on the left side and output ports on the right side.
+#[derive(Clone, Debug, Default, Visit, Reflect)]
+pub struct NodeBox {
+    title: String,
+    /// Position of the top-left corner on the canvas.
+    position: Vector2<f32>,
+    inputs: Vec<Port>,
+    outputs: Vec<Port>,
+}
+
+impl NodeBox {
+    pub fn new(title: &str, position: Vector2<f32>) -> Self {
+        Self {
+            title: title.to_string(),
+            position,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_input(mut self, port: Port) -> Self {
+        self.inputs.push(port);
+        self
+    }
+
+    pub fn with_output(mut self, port: Port) -> Self {
+        self.outputs.push(port);
+        self
+    }
+
+    fn bounds(&self) -> Rect<f32> {
+        let rows = self.inputs.len().max(self.outputs.len());
+        Rect::new(
+            self.position.x,
+            self.position.y,
+            NODE_WIDTH,
+            HEADER_HEIGHT + rows as f32 * ROW_HEIGHT + 4.0,
+        )
+    }
+
+    fn port(&self, index: usize, is_output: bool) -> Option<&Port> {
+        if is_output {
+            self.outputs.get(index)
+        } else {
+            self.inputs.get(index)
+        }
+    }
+
+    /// Position of the center of the port on the canvas.
+    fn port_position(&self, index: usize, is_output: bool) -> Vector2<f32> {
+        let x = if is_output { NODE_WIDTH } else { 0.0 };
+        self.position + Vector2::new(x, HEADER_HEIGHT + (index as f32 + 0.5) * ROW_HEIGHT)
+    }
+}
+
+/// State of the mouse between pressing and releasing a button.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+enum Drag {
+    #[default]
+    None,
+    /// A node is moved, the offset is from the cursor to the top-left corner of the node.
+    Node { index: usize, offset: Vector2<f32> },
+    /// A new wire goes from the port to the cursor.
+    Wire(PortId),
+    /// The canvas is panned, the position is the last position of the cursor on the screen.
+    Pan(Vector2<f32>),
+}
+
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "9d4b6e21-3f8a-4c57-a2e9-5b1c7d0f8e36")]
+struct NodeGraph {
+    widget: Widget,
+    nodes: Vec<NodeBox>,
+    /// Connections from output ports to input ports.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    edges: Vec<(PortId, PortId)>,
+    /// Offset of the canvas in screen units.
+    pan: Vector2<f32>,
+    zoom: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    font: FontResource,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    drag: Drag,
+    /// Position of the cursor on the canvas.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cursor: Vector2<f32>,
+}
+
+define_widget_deref!(NodeGraph);
+
+impl NodeGraph {
+    /// Converts a point on the canvas to the local space of the widget, where it is drawn.
+    fn canvas_to_local(&self, point: Vector2<f32>) -> Vector2<f32> {
+        self.bounding_rect().position + self.pan + point.scale(self.zoom)
+    }
+
+    fn screen_to_canvas(&self, point: Vector2<f32>) -> Vector2<f32> {
+        (self.screen_to_local(point) - self.bounding_rect().position - self.pan)
+            .scale(1.0 / self.zoom)
+    }
+
+    fn port_position(&self, id: PortId) -> Option<Vector2<f32>> {
+        self.nodes
+            .get(id.node)
+            .map(|node| node.port_position(id.port, id.is_output))
+    }
+
+    fn port_kind(&self, id: PortId) -> Option<PortKind> {
+        self.nodes
+            .get(id.node)?
+            .port(id.port, id.is_output)
+            .map(|port| port.kind)
+    }
+
+    fn port_at(&self, point: Vector2<f32>) -> Option<PortId> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(node, node_box)| {
+                [
+                    (false, node_box.inputs.len()),
+                    (true, node_box.outputs.len()),
+                ]
+                .into_iter()
+                .flat_map(|(is_output, count)| {
+                    (0..count).map(move |port| PortId {
+                        node,
+                        port,
+                        is_output,
+                    })
+                })
+                .find(|id| {
+                    node_box
+                        .port_position(id.port, id.is_output)
+                        .metric_distance(&point)
+                        <= PORT_RADIUS * 1.5
+                })
+            })
+    }
+
+    fn node_at(&self, point: Vector2<f32>) -> Option<usize> {
+        self.nodes
+            .iter()
+            .rposition(|node| node.bounds().contains(point))
+    }
+
+    /// Connects an output to an input of the same type, the wire goes from the output, no matter
+    /// which port was dragged. An input can have only one wire.
+    fn connect(&mut self, a: PortId, b: PortId) {
+        if a.is_output == b.is_output || a.node == b.node {
+            return;
+        }
+        if self.port_kind(a).is_none() || self.port_kind(a) != self.port_kind(b) {
+            return;
+        }
+        let (output, input) = if a.is_output { (a, b) } else { (b, a) };
+        self.edges.retain(|(_, existing)| *existing != input);
+        self.edges.push((output, input));
+    }
+
+    fn push_wire(
+        &self,
+        drawing_context: &mut DrawingContext,
+        from: Vector2<f32>,
+        to: Vector2<f32>,
+        color: Color,
+    ) {
+        let from = self.canvas_to_local(from);
+        let to = self.canvas_to_local(to);
+        // Wires leave outputs to the right and enter inputs from the left.
+        let tangent = Vector2::new(((to.x - from.x).abs() * 0.5).max(30.0 * self.zoom), 0.0);
+        drawing_context.push_bezier(from, from + tangent, to - tangent, to, 24, 2.0);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(color),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    fn push_rect(&self, drawing_context: &mut DrawingContext, rect: Rect<f32>, color: Color) {
+        let position = self.canvas_to_local(rect.position);
+        drawing_context.push_rect_filled(
+            &Rect::new(
+                position.x,
+                position.y,
+                rect.w() * self.zoom,
+                rect.h() * self.zoom,
+            ),
+            None,
+        );
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(color),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    fn push_label(
+        &self,
+        drawing_context: &mut DrawingContext,
+        text: &str,
+        position: Vector2<f32>,
+        right_aligned: bool,
+    ) {
+        if self.zoom < MIN_LABEL_ZOOM {
+            return;
+        }
+        let mut formatted_text: FormattedText = FormattedTextBuilder::new(self.font.clone())
+            .with_text(text)
+            .with_brush(Brush::Solid(Color::WHITE))
+            .build();
+        let size = formatted_text.build();
+        let mut position = self.canvas_to_local(position);
+        position.y -= size.y * 0.5;
+        if right_aligned {
+            position.x -= size.x;
+        }
+        drawing_context.draw_text(self.clip_bounds(), position, &formatted_text);
+    }
+
+    fn draw_node(&self, drawing_context: &mut DrawingContext, node: &NodeBox) {
+        let bounds = node.bounds();
+        self.push_rect(drawing_context, bounds, Color::opaque(50, 50, 55));
+        self.push_rect(
+            drawing_context,
+            Rect::new(bounds.x(), bounds.y(), bounds.w(), HEADER_HEIGHT),
+            Color::opaque(70, 90, 140),
+        );
+        self.push_label(
+            drawing_context,
+            &node.title,
+            node.position + Vector2::new(6.0, HEADER_HEIGHT * 0.5),
+            false,
+        );
+
+        for (ports, is_output) in [(&node.inputs, false), (&node.outputs, true)] {
+            for (index, port) in ports.iter().enumerate() {
+                let position = node.port_position(index, is_output);
+                drawing_context.push_circle(
+                    self.canvas_to_local(position),
+                    PORT_RADIUS * self.zoom,
+                    12,
+                    Color::WHITE,
+                );
+                drawing_context.commit(
+                    self.clip_bounds(),
+                    Brush::Solid(port.kind.color()),
+                    CommandTexture::None,
+                    None,
+                );
+
+                let offset = PORT_RADIUS + 4.0;
+                let label_position = if is_output {
+                    position - Vector2::new(offset, 0.0)
+                } else {
+                    position + Vector2::new(offset, 0.0)
+                };
+                self.push_label(drawing_context, &port.name, label_position, is_output);
+            }
+        }
+    }
+
+    fn handle_mouse_down(
+        &mut self,
+        ui: &mut UserInterface,
+        pos: Vector2<f32>,
+        button: MouseButton,
+    ) {
+        let point = self.screen_to_canvas(pos);
+        self.drag = match button {
+            MouseButton::Left => {
+                if let Some(port) = self.port_at(point) {
+                    Drag::Wire(port)
+                } else if let Some(index) = self.node_at(point) {
+                    // The dragged node goes on top of the others.
+                    let node = self.nodes.remove(index);
+                    let offset = node.position - point;
+                    self.nodes.push(node);
+                    let last = self.nodes.len() - 1;
+                    for (output, input) in self.edges.iter_mut() {
+                        for id in [output, input] {
+                            if id.node == index {
+                                id.node = last;
+                            } else if id.node > index {
+                                id.node -= 1;
+                            }
+                        }
+                    }
+                    Drag::Node {
+                        index: last,
+                        offset,
+                    }
+                } else {
+                    Drag::None
+                }
+            }
+            MouseButton::Middle => Drag::Pan(pos),
+            _ => Drag::None,
+        };
+        self.cursor = point;
+        if self.drag != Drag::None {
+            ui.capture_mouse(self.handle());
+        }
+    }
+
+    fn handle_mouse_move(&mut self, pos: Vector2<f32>) {
+        match self.drag {
+            Drag::Node { index, offset } => {
+                let point = self.screen_to_canvas(pos);
+                if let Some(node) = self.nodes.get_mut(index) {
+                    node.position = point + offset;
+                }
+            }
+            Drag::Pan(last) => {
+                self.pan += pos - last;
+                self.drag = Drag::Pan(pos);
+            }
+            Drag::Wire(_) | Drag::None => (),
+        }
+        self.cursor = self.screen_to_canvas(pos);
+    }
+
+    fn handle_mouse_up(&mut self, ui: &mut UserInterface, pos: Vector2<f32>) {
+        if let Drag::Wire(from) = self.drag {
+            if let Some(to) = self.port_at(self.screen_to_canvas(pos)) {
+                self.connect(from, to);
+            }
+        }
+        if self.drag != Drag::None {
+            self.drag = Drag::None;
+            ui.release_mouse_capture();
+        }
+    }
+
+    /// Zooms the canvas around the cursor, so the point under it stays in place.
+    fn handle_mouse_wheel(&mut self, pos: Vector2<f32>, amount: f32) {
+        let point = self.screen_to_canvas(pos);
+        self.zoom = (self.zoom * (1.0 + amount * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.pan += self.screen_to_local(pos) - self.canvas_to_local(point);
+    }
+}
+
+impl Control for NodeGraph {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        drawing_context.push_rect_filled(&self.bounding_rect(), None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::opaque(30, 30, 32)),
+            CommandTexture::None,
+            None,
+        );
+
+        for (output, input) in self.edges.iter() {
+            if let (Some(from), Some(to), Some(kind)) = (
+                self.port_position(*output),
+                self.port_position(*input),
+                self.port_kind(*output),
+            ) {
+                self.push_wire(drawing_context, from, to, kind.color());
+            }
+        }
+
+        for node in self.nodes.iter() {
+            self.draw_node(drawing_context, node);
+        }
+
+        // The wire, that is being dragged, is drawn on top of everything.
+        if let Drag::Wire(port) = self.drag {
+            if let (Some(position), Some(kind)) = (self.port_position(port), self.port_kind(port)) {
+                let (from, to) = if port.is_output {
+                    (position, self.cursor)
+                } else {
+                    (self.cursor, position)
+                };
+                self.push_wire(drawing_context, from, to, kind.color());
+            }
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() != self.handle() {
+            return;
+        }
+        let Some(msg) = message.data::<WidgetMessage>() else {
+            return;
+        };
+        match msg {
+            WidgetMessage::MouseDown { pos, button } => {
+                self.handle_mouse_down(ui, *pos, *button);
+            }
+            WidgetMessage::MouseMove { pos, .. } => {
+                self.handle_mouse_move(*pos);
+            }
+            WidgetMessage::MouseUp { pos, .. } => {
+                self.handle_mouse_up(ui, *pos);
+            }
+            WidgetMessage::MouseWheel { pos, amount } => {
+                self.handle_mouse_wheel(*pos, *amount);
+            }
+            _ => return,
+        }
+        message.set_handled(true);
+        self.invalidate_visual();
+    }
+}
+
+pub struct NodeGraphBuilder {
+    widget_builder: WidgetBuilder,
+    nodes: Vec<NodeBox>,
+}
+
+impl NodeGraphBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            nodes: Default::default(),
+        }
+    }
+
+    pub fn with_nodes(mut self, nodes: Vec<NodeBox>) -> Self {
+        self.nodes = nodes;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let graph = NodeGraph {
+            widget: self.widget_builder.build(),
+            nodes: self.nodes,
+            edges: Default::default(),
+            pan: Default::default(),
+            zoom: 1.0,
+            font: ctx.default_font(),
+            drag: Drag::None,
+            cursor: Default::default(),
+        };
+
+        ctx.add_node(UiNode::new(graph))
+    }
+}
+
+/// Creates a window with a node graph, that has a few nodes to play with.
+pub fn build_blueprint_window(ctx: &mut BuildContext) -> Handle<UiNode> {
+    let nodes = vec![
+        NodeBox::new("On Key Pressed", Vector2::new(20.0, 40.0))
+            .with_output(Port::new("Exec", PortKind::Exec))
+            .with_output(Port::new("Is Running", PortKind::Bool)),
+        NodeBox::new("Branch", Vector2::new(220.0, 30.0))
+            .with_input(Port::new("Exec", PortKind::Exec))
+            .with_input(Port::new("Condition", PortKind::Bool))
+            .with_output(Port::new("True", PortKind::Exec))
+            .with_output(Port::new("False", PortKind::Exec)),
+        NodeBox::new("Get Walk Speed", Vector2::new(20.0, 180.0))
+            .with_output(Port::new("Speed", PortKind::Float)),
+        NodeBox::new("Play Animation", Vector2::new(420.0, 120.0))
+            .with_input(Port::new("Exec", PortKind::Exec))
+            .with_input(Port::new("Speed", PortKind::Float))
+            .with_output(Port::new("Finished", PortKind::Exec)),
+    ];
+
+    WindowBuilder::new(WidgetBuilder::new().with_width(640.0).with_height(360.0))
+        .with_title(WindowTitle::text(
+            "Blueprint - [LMB] drag nodes and ports, [MMB] pan, [Wheel] zoom",
+        ))
+        .open(false)
+        .with_content(
+            NodeGraphBuilder::new(WidgetBuilder::new())
+                .with_nodes(nodes)
+                .build(ctx),
+        )
+        .build(ctx)
+}
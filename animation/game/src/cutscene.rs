@@ -0,0 +1,151 @@
+//! Cutscene trigger, that shows letterbox bars with a caption when the player walks into it.
+use crate::{player::Player, Game};
+use common_scripts::{TriggerCallback, TriggerVolume};
+use fyrox::{
+    core::{algebra::Vector3, color::Color, pool::Handle},
+    graph::BaseSceneGraph,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        message::MessageDirection,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext},
+};
+
+/// How long the cutscene stays on screen, in seconds.
+const CUTSCENE_DURATION: f32 = 4.0;
+
+#[derive(Default, Debug)]
+pub struct Cutscene {
+    grid: Handle<UiNode>,
+    timer: f32,
+}
+
+impl Cutscene {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let bar = |row, ctx: &mut BuildContext| {
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(row)
+                    .with_background(Brush::Solid(Color::BLACK)),
+            )
+            .build(ctx)
+        };
+
+        let top_bar = bar(0, ctx);
+        let bottom_bar = bar(2, ctx);
+        let caption = TextBuilder::new(WidgetBuilder::new().on_row(2))
+            .with_text("Somewhere in the old castle...")
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_font_size(24.0)
+            .build(ctx);
+
+        let grid = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(top_bar)
+                .with_child(bottom_bar)
+                .with_child(caption),
+        )
+        .add_column(Column::stretch())
+        .add_row(Row::strict(90.0))
+        .add_row(Row::stretch())
+        .add_row(Row::strict(90.0))
+        .build(ctx);
+
+        Self { grid, timer: 0.0 }
+    }
+
+    pub fn start(&mut self, ui: &UserInterface) {
+        self.timer = CUTSCENE_DURATION;
+        ui.send_message(WidgetMessage::visibility(
+            self.grid,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn update(&mut self, dt: f32, ui: &UserInterface) {
+        if self.timer <= 0.0 {
+            return;
+        }
+
+        self.timer -= dt;
+        if self.timer <= 0.0 {
+            ui.send_message(WidgetMessage::visibility(
+                self.grid,
+                MessageDirection::ToWidget,
+                false,
+            ));
+        }
+    }
+
+    pub fn handle_resize(&self, ui: &UserInterface, width: f32, height: f32) {
+        ui.send_message(WidgetMessage::width(
+            self.grid,
+            MessageDirection::ToWidget,
+            width,
+        ));
+        ui.send_message(WidgetMessage::height(
+            self.grid,
+            MessageDirection::ToWidget,
+            height,
+        ));
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CutsceneCallback;
+
+impl TriggerCallback for CutsceneCallback {
+    fn on_enter(&mut self, body: Handle<Node>, ctx: &mut ScriptContext) {
+        if ctx.scene.graph[body].try_get_script::<Player>().is_none() {
+            return;
+        }
+
+        if let Some(game) = ctx.plugins.of_type_mut::<Game>() {
+            game.start_cutscene(ctx.user_interfaces.first());
+        }
+    }
+
+    fn on_exit(&mut self, _body: Handle<Node>, _ctx: &mut ScriptContext) {}
+
+    fn clone_box(&self) -> Box<dyn TriggerCallback> {
+        Box::new(self.clone())
+    }
+}
+
+/// Creates an invisible sensor, that starts the cutscene each time the player enters it.
+pub fn spawn_cutscene_trigger(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    let sensor = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(1.0, 1.0, 1.0))
+        .with_sensor(true)
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name("CutsceneTrigger")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[sensor])
+            .with_script(Script::new(TriggerVolume::new(CutsceneCallback))),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(graph)
+}
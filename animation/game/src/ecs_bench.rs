@@ -0,0 +1,199 @@
+//! Benchmark of script iteration strategies. A lot of nodes with a minimal [`Counter`] script are
+//! updated with three different approaches to the storage of the scripts, the throughput of each
+//! approach is written to stdout. This is a research tool to inform the design of the scripting,
+//! it is started with the `--ecs-bench` argument of the executor.
+use dashmap::DashMap;
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    graph::SceneGraph,
+    scene::{base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder},
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use rayon::prelude::*;
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// Amount of nodes with the script.
+const NODE_COUNT: usize = 10_000;
+/// Amount of updates of all the nodes, that are measured for each strategy.
+const CYCLE_COUNT: usize = 100;
+const DT: f32 = 1.0 / 60.0;
+
+/// Minimal script, that only accumulates the time.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "6f2d8b41-93c7-4e5a-b0d6-1a7e3c9f5b28")]
+#[visit(optional)]
+pub struct Counter {
+    time: f32,
+    ticks: u64,
+}
+
+impl Counter {
+    fn tick(&mut self, dt: f32) {
+        self.time += dt;
+        self.ticks += 1;
+    }
+}
+
+impl ScriptTrait for Counter {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.tick(ctx.dt);
+    }
+}
+
+/// Dense storage of the components of one type, the components are stored in a `Vec` and the
+/// index of a component is the index of its node in the pool of the graph.
+struct ComponentStorage<T> {
+    components: Vec<Option<T>>,
+}
+
+impl<T: Send> ComponentStorage<T> {
+    fn new() -> Self {
+        Self {
+            components: Default::default(),
+        }
+    }
+
+    fn insert(&mut self, owner: Handle<Node>, component: T) {
+        let index = owner.index() as usize;
+        if index >= self.components.len() {
+            self.components.resize_with(index + 1, || None);
+        }
+        self.components[index] = Some(component);
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        self.components.iter().flatten()
+    }
+
+    fn par_for_each_mut(&mut self, func: impl Fn(&mut T) + Sync) {
+        self.components.par_iter_mut().flatten().for_each(func);
+    }
+}
+
+struct BenchResult {
+    strategy: &'static str,
+    elapsed: Duration,
+    /// Sum of the ticks of all the counters, it is used to check that every strategy did the same
+    /// amount of work.
+    ticks: u64,
+}
+
+impl BenchResult {
+    fn updates_per_second(&self) -> f64 {
+        (NODE_COUNT * CYCLE_COUNT) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Calls `update` [`CYCLE_COUNT`] times and measures the total time.
+fn measure(mut update: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..CYCLE_COUNT {
+        update();
+    }
+    start.elapsed()
+}
+
+/// Scripts are stored in the nodes of the graph, every node is checked for the script.
+fn bench_graph() -> BenchResult {
+    let mut graph = Graph::new();
+    for _ in 0..NODE_COUNT {
+        PivotBuilder::new(BaseBuilder::new().with_script(Script::new(Counter::default())))
+            .build(&mut graph);
+    }
+
+    let elapsed = measure(|| {
+        for node in graph.linear_iter_mut() {
+            if let Some(counter) = node.try_get_script_mut::<Counter>() {
+                black_box(counter).tick(DT);
+            }
+        }
+    });
+
+    BenchResult {
+        strategy: "Graph nodes",
+        elapsed,
+        ticks: graph
+            .linear_iter()
+            .filter_map(|node| node.try_get_script::<Counter>())
+            .map(|counter| counter.ticks)
+            .sum(),
+    }
+}
+
+/// Scripts are stored in a separate dense storage and updated in parallel.
+fn bench_component_storage(owners: &[Handle<Node>]) -> BenchResult {
+    let mut storage = ComponentStorage::new();
+    for owner in owners {
+        storage.insert(*owner, Counter::default());
+    }
+
+    let elapsed = measure(|| storage.par_for_each_mut(|counter| black_box(counter).tick(DT)));
+
+    BenchResult {
+        strategy: "ComponentStorage + rayon",
+        elapsed,
+        ticks: storage.iter().map(|counter| counter.ticks).sum(),
+    }
+}
+
+/// Scripts are stored in a concurrent hash map, that is indexed by the handles of the nodes.
+fn bench_dash_map(owners: &[Handle<Node>]) -> BenchResult {
+    let map = owners
+        .iter()
+        .map(|owner| (*owner, Counter::default()))
+        .collect::<DashMap<_, _>>();
+
+    let elapsed = measure(|| {
+        for mut entry in map.iter_mut() {
+            black_box(entry.value_mut()).tick(DT);
+        }
+    });
+
+    BenchResult {
+        strategy: "DashMap",
+        elapsed,
+        ticks: map.iter().map(|entry| entry.ticks).sum(),
+    }
+}
+
+/// Runs all the strategies and writes the results as a table to stdout.
+pub fn run() {
+    // Separate storages need handles of real nodes, so they index the same set of nodes as the
+    // graph.
+    let mut graph = Graph::new();
+    let owners = (0..NODE_COUNT)
+        .map(|_| PivotBuilder::new(BaseBuilder::new()).build(&mut graph))
+        .collect::<Vec<_>>();
+
+    let results = [
+        bench_graph(),
+        bench_component_storage(&owners),
+        bench_dash_map(&owners),
+    ];
+
+    println!("{NODE_COUNT} nodes, {CYCLE_COUNT} update cycles");
+    println!(
+        "| {:<26} | {:>12} | {:>16} |",
+        "Strategy", "Total (ms)", "Updates/second"
+    );
+    println!("|{:-<28}|{:->14}|{:->18}|", "", "", "");
+    for result in results.iter() {
+        println!(
+            "| {:<26} | {:>12.3} | {:>16.0} |",
+            result.strategy,
+            result.elapsed.as_secs_f64() * 1000.0,
+            result.updates_per_second()
+        );
+        if result.ticks != (NODE_COUNT * CYCLE_COUNT) as u64 {
+            println!(
+                "Warning: {} made {} updates instead of {}",
+                result.strategy,
+                result.ticks,
+                NODE_COUNT * CYCLE_COUNT
+            );
+        }
+    }
+}
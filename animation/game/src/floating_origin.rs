@@ -0,0 +1,117 @@
+//! Floating origin keeps the player near the origin of the scene, so `f32` coordinates of the
+//! nodes around it stay precise in large worlds. When the player goes too far, the whole world is
+//! shifted back and the shift is accumulated in `f64`.
+use crate::player::Player;
+use fyrox::{
+    core::{algebra::Vector3, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{graph::Graph, Scene},
+};
+
+/// Precision of `f32` becomes noticeable at ~10 km and a real game would shift the world every few
+/// kilometers, but the demo level is only a few dozen meters across, so the world is shifted
+/// after a short walk from the origin.
+pub const DEFAULT_SHIFT_THRESHOLD: f32 = 20.0;
+
+#[derive(Debug)]
+pub struct FloatingOrigin {
+    /// Distance (in meters) from the origin, after which the world is shifted.
+    pub shift_threshold: f32,
+    /// Sum of all shifts. Absolute position of a node in the world is `world_offset` plus its
+    /// position in the scene.
+    world_offset: Vector3<f64>,
+    text: Handle<UiNode>,
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self {
+            shift_threshold: DEFAULT_SHIFT_THRESHOLD,
+            world_offset: Default::default(),
+            text: Default::default(),
+        }
+    }
+}
+
+impl FloatingOrigin {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .build(ctx);
+
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    /// Converts a position in the scene to the absolute position in the world.
+    pub fn to_world(&self, local_position: Vector3<f32>) -> Vector3<f64> {
+        self.world_offset + local_position.cast::<f64>()
+    }
+
+    /// Converts an absolute position in the world to the position in the scene.
+    #[cfg(feature = "net")]
+    pub fn to_local(&self, world_position: Vector3<f64>) -> Vector3<f32> {
+        (world_position - self.world_offset).cast::<f32>()
+    }
+
+    /// Moves every node of the scene by `-offset`. Only the children of the root are moved, the
+    /// rest of the nodes follow their parents.
+    fn shift(&mut self, graph: &mut Graph, offset: Vector3<f32>) {
+        let root_children = graph[graph.get_root()].children().to_vec();
+        for child in root_children {
+            graph[child].local_transform_mut().offset(-offset);
+        }
+
+        // IK targets are stored in world space and are not attached to any node.
+        for node in graph.linear_iter_mut() {
+            if let Some(player) = node.try_get_script_mut::<Player>() {
+                player.shift_ik_targets(-offset);
+            }
+        }
+
+        self.world_offset += offset.cast::<f64>();
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, ui: &UserInterface) {
+        let graph = &mut scene.graph;
+        let Some(mut position) = graph
+            .linear_iter()
+            .find(|node| node.try_get_script::<Player>().is_some())
+            .map(|player| player.global_position())
+        else {
+            return;
+        };
+
+        if position.norm() > self.shift_threshold {
+            self.shift(graph, position);
+            // Global transforms will be updated only on the next frame.
+            position = Vector3::default();
+        }
+
+        let world_position = self.to_world(position);
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            format!(
+                "Local Position: {:.2}, {:.2}, {:.2}\nWorld Position: {:.2}, {:.2}, {:.2}",
+                position.x,
+                position.y,
+                position.z,
+                world_position.x,
+                world_position.y,
+                world_position.z
+            ),
+        ));
+    }
+}
@@ -0,0 +1,432 @@
+//! In-game transform gizmo, that allows to move, rotate and scale scene nodes.
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    graph::BaseSceneGraph,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    scene::{camera::Camera, debug::Line, node::Node, Scene},
+};
+
+/// Maximum distance (in pixels) between the cursor and a handle of the gizmo to start dragging.
+const PICK_DISTANCE: f32 = 10.0;
+/// Amount of segments in rotation circles.
+const CIRCLE_SEGMENTS: usize = 32;
+/// Rotation angle (in radians) per one pixel of mouse movement.
+const ROTATION_SPEED: f32 = 0.01;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl GizmoMode {
+    fn name(self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Gizmo {
+    enabled: bool,
+    mode: GizmoMode,
+    /// Length of the arrows in world units.
+    size: f32,
+    window: Handle<UiNode>,
+    size_slider: Handle<UiNode>,
+    mode_text: Handle<UiNode>,
+    cursor_position: Vector2<f32>,
+    screen_size: Vector2<f32>,
+    /// Index of the axis that is being dragged.
+    drag_axis: Option<usize>,
+}
+
+fn axis_color(axis: usize) -> Color {
+    match axis {
+        0 => Color::RED,
+        1 => Color::GREEN,
+        _ => Color::BLUE,
+    }
+}
+
+/// Returns local axes of the node in world space.
+fn world_axes(node: &Node) -> [Vector3<f32>; 3] {
+    [node.side_vector(), node.up_vector(), node.look_vector()]
+        .map(|axis| axis.try_normalize(f32::EPSILON).unwrap_or_default())
+}
+
+fn distance_to_segment(point: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let t = if ab.norm_squared() > 0.0 {
+        ((point - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a + ab.scale(t)).metric_distance(&point)
+}
+
+fn active_camera(scene: &Scene) -> Option<&Camera> {
+    scene
+        .graph
+        .linear_iter()
+        .find_map(|node| node.cast::<Camera>().filter(|camera| camera.is_enabled()))
+}
+
+impl Gizmo {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let size = 1.0;
+
+        let mode_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(0)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text(format!("Mode: {}", GizmoMode::default().name()))
+        .build(ctx);
+
+        let size_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.1)
+        .with_max(5.0)
+        .with_step(0.1)
+        .with_value(size)
+        .show_value(true)
+        .build(ctx);
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(250.0)
+                .with_height(100.0)
+                .with_desired_position(Vector2::new(20.0, 640.0)),
+        )
+        .with_title(WindowTitle::text("Gizmo Size"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(mode_text)
+                    .with_child(size_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            enabled: false,
+            mode: Default::default(),
+            size,
+            window,
+            size_slider,
+            mode_text,
+            cursor_position: Default::default(),
+            screen_size: Default::default(),
+            drag_axis: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_mode(&mut self, mode: GizmoMode, ui: &UserInterface) {
+        // Pressing the same button again returns the gizmo back to translation mode.
+        self.mode = if self.mode == mode {
+            GizmoMode::Translate
+        } else {
+            mode
+        };
+        self.drag_axis = None;
+
+        ui.send_message(TextMessage::text(
+            self.mode_text,
+            MessageDirection::ToWidget,
+            format!("Mode: {}", self.mode.name()),
+        ));
+    }
+
+    /// Returns screen-space polylines for each handle of the gizmo.
+    fn handle_polylines(
+        &self,
+        scene: &Scene,
+        node: Handle<Node>,
+    ) -> Option<[Vec<Vector2<f32>>; 3]> {
+        let camera = active_camera(scene)?;
+        let node = scene.graph.try_get(node)?;
+        let origin = node.global_position();
+        let axes = world_axes(node);
+
+        let mut polylines: [Vec<Vector2<f32>>; 3] = Default::default();
+        for (i, polyline) in polylines.iter_mut().enumerate() {
+            let points = match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    vec![origin, origin + axes[i].scale(self.size)]
+                }
+                GizmoMode::Rotate => self.circle_points(origin, &axes, i),
+            };
+
+            for point in points {
+                polyline.push(camera.project(point, self.screen_size)?);
+            }
+        }
+
+        Some(polylines)
+    }
+
+    /// Returns points of a circle, that lies in the plane perpendicular to the given axis.
+    fn circle_points(
+        &self,
+        origin: Vector3<f32>,
+        axes: &[Vector3<f32>; 3],
+        axis: usize,
+    ) -> Vec<Vector3<f32>> {
+        let u = axes[(axis + 1) % 3];
+        let v = axes[(axis + 2) % 3];
+        let radius = self.size * 0.8;
+        (0..=CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                origin + u.scale(angle.cos() * radius) + v.scale(angle.sin() * radius)
+            })
+            .collect()
+    }
+
+    fn pick_axis(&self, scene: &Scene, node: Handle<Node>) -> Option<usize> {
+        let polylines = self.handle_polylines(scene, node)?;
+
+        polylines
+            .iter()
+            .enumerate()
+            .filter_map(|(axis, polyline)| {
+                polyline
+                    .windows(2)
+                    .map(|segment| {
+                        distance_to_segment(self.cursor_position, segment[0], segment[1])
+                    })
+                    .min_by(|a, b| a.total_cmp(b))
+                    .map(|distance| (axis, distance))
+            })
+            .filter(|(_, distance)| *distance < PICK_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| axis)
+    }
+
+    fn drag(&self, scene: &mut Scene, node_handle: Handle<Node>, axis: usize, delta: Vector2<f32>) {
+        let Some(polylines) = self.handle_polylines(scene, node_handle) else {
+            return;
+        };
+
+        // Projection of the mouse movement on the screen-space axis gives the amount of movement
+        // in world units.
+        let amount = match polylines[axis].as_slice() {
+            [begin, end] => {
+                let screen_axis = end - begin;
+                if screen_axis.norm_squared() < 1.0 {
+                    return;
+                }
+                delta.dot(&screen_axis) / screen_axis.norm_squared() * self.size
+            }
+            _ => 0.0,
+        };
+
+        let node = &scene.graph[node_handle];
+        let world_axis = world_axes(node)[axis];
+        // Translation happens in the space of the parent node.
+        let local_axis = scene
+            .graph
+            .try_get(node.parent())
+            .and_then(|parent| parent.global_transform().try_inverse())
+            .map(|inv_parent| inv_parent.transform_vector(&world_axis))
+            .unwrap_or(world_axis);
+
+        let transform = scene.graph[node_handle].local_transform_mut();
+        match self.mode {
+            GizmoMode::Translate => {
+                let position = **transform.position();
+                transform.set_position(position + local_axis.scale(amount));
+            }
+            GizmoMode::Rotate => {
+                // Rotation happens around the axis of the node itself.
+                let mut basis = Vector3::default();
+                basis[axis] = 1.0;
+                let rotation = **transform.rotation()
+                    * UnitQuaternion::from_scaled_axis(
+                        basis.scale((delta.x + delta.y) * ROTATION_SPEED),
+                    );
+                transform.set_rotation(rotation);
+            }
+            GizmoMode::Scale => {
+                let mut scale = **transform.scale();
+                scale[axis] = (scale[axis] * (1.0 + amount / self.size)).max(0.01);
+                transform.set_scale(scale);
+            }
+        }
+    }
+
+    pub fn handle_os_event(
+        &mut self,
+        event: &Event<()>,
+        scene: &mut Scene,
+        selected: Handle<Node>,
+        ui: &UserInterface,
+    ) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+
+        match event {
+            WindowEvent::KeyboardInput { event: input, .. } => {
+                if input.state == ElementState::Pressed
+                    && input.physical_key == PhysicalKey::Code(KeyCode::KeyG)
+                {
+                    self.enabled = !self.enabled;
+                    self.drag_axis = None;
+                    ui.send_message(WidgetMessage::visibility(
+                        self.window,
+                        MessageDirection::ToWidget,
+                        self.enabled,
+                    ));
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let new_position = Vector2::new(position.x as f32, position.y as f32);
+                let delta = new_position - self.cursor_position;
+                self.cursor_position = new_position;
+
+                if let Some(axis) = self.drag_axis.filter(|_| self.enabled) {
+                    self.drag(scene, selected, axis, delta);
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } if self.enabled => {
+                match (button, state) {
+                    (MouseButton::Left, ElementState::Pressed) => {
+                        self.drag_axis = self.pick_axis(scene, selected);
+                    }
+                    (MouseButton::Left, ElementState::Released) => {
+                        self.drag_axis = None;
+                    }
+                    (MouseButton::Right, ElementState::Pressed) => {
+                        self.set_mode(GizmoMode::Rotate, ui);
+                    }
+                    (MouseButton::Middle, ElementState::Pressed) => {
+                        self.set_mode(GizmoMode::Scale, ui);
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.size_slider
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.size = *value;
+            }
+        }
+    }
+
+    /// Draws the gizmo for the selected node. Must be called after the lines of the scene's drawing
+    /// context were cleared.
+    pub fn update(&mut self, scene: &mut Scene, selected: Handle<Node>, screen_size: Vector2<f32>) {
+        self.screen_size = screen_size;
+
+        if !self.enabled {
+            return;
+        }
+
+        let Some(node) = scene.graph.try_get(selected) else {
+            return;
+        };
+        let origin = node.global_position();
+        let axes = world_axes(node);
+
+        for (i, axis) in axes.iter().enumerate() {
+            let color = if self.drag_axis == Some(i) {
+                Color::opaque(255, 255, 0)
+            } else {
+                axis_color(i)
+            };
+            let end = origin + axis.scale(self.size);
+            let side = axes[(i + 1) % 3].scale(self.size * 0.05);
+
+            match self.mode {
+                GizmoMode::Translate => {
+                    scene.drawing_context.add_line(Line {
+                        begin: origin,
+                        end,
+                        color,
+                    });
+                    let back = end - axis.scale(self.size * 0.15);
+                    for head in [back + side, back - side] {
+                        scene.drawing_context.add_line(Line {
+                            begin: end,
+                            end: head,
+                            color,
+                        });
+                    }
+                }
+                GizmoMode::Rotate => {
+                    for segment in self.circle_points(origin, &axes, i).windows(2) {
+                        scene.drawing_context.add_line(Line {
+                            begin: segment[0],
+                            end: segment[1],
+                            color,
+                        });
+                    }
+                }
+                GizmoMode::Scale => {
+                    scene.drawing_context.add_line(Line {
+                        begin: origin,
+                        end,
+                        color,
+                    });
+                    // A small square at the end of the axis.
+                    let other_side = axes[(i + 2) % 3].scale(self.size * 0.05);
+                    let corners = [
+                        end + side + other_side,
+                        end + side - other_side,
+                        end - side - other_side,
+                        end - side + other_side,
+                    ];
+                    for (a, b) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+                        scene.drawing_context.add_line(Line {
+                            begin: *a,
+                            end: *b,
+                            color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
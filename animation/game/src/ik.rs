@@ -0,0 +1,343 @@
+//! Two-bone inverse kinematics for limbs of the character and an in-game handle, that allows to
+//! place IK targets by clicking in the scene with `Alt` held.
+use crate::player::Player;
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        utils::make_dropdown_list_option,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    scene::{camera::Camera, graph::physics::RayCastOptions, graph::Graph, node::Node, Scene},
+};
+
+/// Radius of the spheres, that show IK targets.
+const TARGET_SPHERE_RADIUS: f32 = 0.05;
+/// Maximum distance of the ray, that is used to pick IK targets.
+const PICK_DISTANCE: f32 = 100.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Limb {
+    #[default]
+    LeftHand,
+    RightHand,
+    LeftFoot,
+    RightFoot,
+}
+
+impl Limb {
+    pub const ALL: [Limb; 4] = [
+        Limb::LeftHand,
+        Limb::RightHand,
+        Limb::LeftFoot,
+        Limb::RightFoot,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Limb::LeftHand => "Left Hand",
+            Limb::RightHand => "Right Hand",
+            Limb::LeftFoot => "Left Foot",
+            Limb::RightFoot => "Right Foot",
+        }
+    }
+
+    /// Names of the upper, the lower and the end bones of the limb.
+    pub fn bone_names(self) -> [&'static str; 3] {
+        match self {
+            Limb::LeftHand => [
+                "mixamorig:LeftArm",
+                "mixamorig:LeftForeArm",
+                "mixamorig:LeftHand",
+            ],
+            Limb::RightHand => [
+                "mixamorig:RightArm",
+                "mixamorig:RightForeArm",
+                "mixamorig:RightHand",
+            ],
+            Limb::LeftFoot => [
+                "mixamorig:LeftUpLeg",
+                "mixamorig:LeftLeg",
+                "mixamorig:LeftFoot",
+            ],
+            Limb::RightFoot => [
+                "mixamorig:RightUpLeg",
+                "mixamorig:RightLeg",
+                "mixamorig:RightFoot",
+            ],
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Limb::LeftHand => Color::RED,
+            Limb::RightHand => Color::GREEN,
+            Limb::LeftFoot => Color::BLUE,
+            Limb::RightFoot => Color::opaque(255, 255, 0),
+        }
+    }
+}
+
+/// Sets local rotation of the node so its global rotation becomes the given one.
+fn set_global_rotation(graph: &mut Graph, node: Handle<Node>, rotation: UnitQuaternion<f32>) {
+    let parent_rotation = graph
+        .try_get(graph[node].parent())
+        .map(|parent| parent.global_rotation())
+        .unwrap_or_default();
+    graph[node]
+        .local_transform_mut()
+        .set_rotation(parent_rotation.inverse() * rotation);
+}
+
+/// Rotates the bones of the chain (upper, lower, end) so the end bone reaches the target, or as
+/// close as possible. The bend plane of the limb is preserved. Global transforms of the bones are
+/// taken from the previous frame, it is fine since the solver runs every frame.
+pub fn solve_two_bone_ik(graph: &mut Graph, chain: [Handle<Node>; 3], target: Vector3<f32>) {
+    let [upper, lower, end] = chain;
+    if !chain.iter().all(|bone| graph.is_valid_handle(*bone)) {
+        return;
+    }
+
+    let a = graph[upper].global_position();
+    let b = graph[lower].global_position();
+    let c = graph[end].global_position();
+    let upper_rotation = graph[upper].global_rotation();
+    let lower_rotation = graph[lower].global_rotation();
+
+    let upper_length = a.metric_distance(&b);
+    let lower_length = b.metric_distance(&c);
+    let Some((direction, distance)) = (target - a).try_normalize(f32::EPSILON).map(|direction| {
+        (
+            direction,
+            a.metric_distance(&target)
+                .clamp(f32::EPSILON, (upper_length + lower_length) * 0.999),
+        )
+    }) else {
+        return;
+    };
+
+    // Keep the limb bent in the same plane, the pole is the part of the current middle joint
+    // offset, that is perpendicular to the direction to the target.
+    let offset = b - a;
+    let pole = (offset - direction.scale(offset.dot(&direction)))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| direction.cross(&Vector3::y()).normalize());
+
+    // Law of cosines gives the angle between the upper bone and the direction to the target.
+    let cos_angle = ((upper_length * upper_length + distance * distance
+        - lower_length * lower_length)
+        / (2.0 * upper_length * distance))
+        .clamp(-1.0, 1.0);
+    let sin_angle = (1.0 - cos_angle * cos_angle).sqrt();
+    let new_b = a + (direction.scale(cos_angle) + pole.scale(sin_angle)).scale(upper_length);
+
+    let upper_delta = UnitQuaternion::rotation_between(&(b - a), &(new_b - a)).unwrap_or_default();
+    let new_upper_rotation = upper_delta * upper_rotation;
+    set_global_rotation(graph, upper, new_upper_rotation);
+
+    let rotated_lower = upper_delta * (c - b);
+    let lower_delta =
+        UnitQuaternion::rotation_between(&rotated_lower, &(target - new_b)).unwrap_or_default();
+    let new_lower_rotation = lower_delta * upper_delta * lower_rotation;
+    graph[lower]
+        .local_transform_mut()
+        .set_rotation(new_upper_rotation.inverse() * new_lower_rotation);
+}
+
+/// A window with a limb selector and coordinates of its IK target. `Alt + LMB` places the IK
+/// target of the selected limb at a point under the cursor, `Alt + RMB` removes it.
+#[derive(Default, Debug)]
+pub struct IkHandle {
+    limb: Limb,
+    alt_pressed: bool,
+    cursor_position: Vector2<f32>,
+    limb_selector: Handle<UiNode>,
+    coordinates_text: Handle<UiNode>,
+}
+
+fn find_player(scene: &mut Scene) -> Option<(Handle<Node>, &mut Player)> {
+    let handle = scene
+        .graph
+        .pair_iter()
+        .find(|(_, node)| node.try_get_script::<Player>().is_some())
+        .map(|(handle, _)| handle)?;
+    scene.graph[handle]
+        .try_get_script_mut::<Player>()
+        .map(|player| (handle, player))
+}
+
+impl IkHandle {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let items = Limb::ALL
+            .iter()
+            .map(|limb| make_dropdown_list_option(ctx, limb.name()))
+            .collect::<Vec<_>>();
+
+        let limb_selector = DropdownListBuilder::new(
+            WidgetBuilder::new()
+                .with_height(22.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_items(items)
+        .with_selected(0)
+        .build(ctx);
+
+        let coordinates_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                .with_text("Target: None")
+                .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_desired_position(Vector2::new(20.0, 520.0)),
+        )
+        .with_title(WindowTitle::text("IK Target"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(limb_selector)
+                    .with_child(coordinates_text),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            limb: Default::default(),
+            alt_pressed: false,
+            cursor_position: Default::default(),
+            limb_selector,
+            coordinates_text,
+        }
+    }
+
+    /// Casts a ray from the cursor into the scene and returns the closest point, that does not
+    /// belong to the character.
+    fn pick(
+        &self,
+        scene: &Scene,
+        player: Handle<Node>,
+        screen_size: Vector2<f32>,
+    ) -> Option<Vector3<f32>> {
+        let camera = scene
+            .graph
+            .linear_iter()
+            .find_map(|node| node.cast::<Camera>().filter(|camera| camera.is_enabled()))?;
+        let ray = camera.make_ray(self.cursor_position, screen_size);
+
+        let mut intersections = Vec::new();
+        scene.graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(ray.origin),
+                ray_direction: ray.dir.try_normalize(f32::EPSILON)?,
+                max_len: PICK_DISTANCE,
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+
+        intersections
+            .into_iter()
+            .find(|intersection| {
+                scene
+                    .graph
+                    .try_get(intersection.collider)
+                    .map_or(false, |collider| collider.parent() != player)
+            })
+            .map(|intersection| intersection.position.coords)
+    }
+
+    pub fn handle_os_event(
+        &mut self,
+        event: &Event<()>,
+        scene: &mut Scene,
+        screen_size: Vector2<f32>,
+    ) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.alt_pressed = modifiers.state().alt_key();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Vector2::new(position.x as f32, position.y as f32);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } if self.alt_pressed => {
+                let player = find_player(scene).map(|(handle, _)| handle);
+                let Some(player) = player else {
+                    return;
+                };
+                let target = match button {
+                    MouseButton::Left => match self.pick(scene, player, screen_size) {
+                        Some(point) => Some(point),
+                        None => return,
+                    },
+                    MouseButton::Right => None,
+                    _ => return,
+                };
+                if let Some((_, player)) = find_player(scene) {
+                    player.set_ik_target(self.limb, target);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.limb_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(limb) = Limb::ALL.get(*index) {
+                    self.limb = *limb;
+                }
+            }
+        }
+    }
+
+    /// Draws spheres at every IK target and shows coordinates of the target of the selected limb.
+    /// Must be called after the lines of the scene's drawing context were cleared.
+    pub fn update(&self, scene: &mut Scene, ui: &UserInterface) {
+        let Some(targets) = find_player(scene).map(|(_, player)| player.ik_targets()) else {
+            return;
+        };
+
+        for (limb, target) in Limb::ALL.iter().zip(targets) {
+            if let Some(target) = target {
+                scene
+                    .drawing_context
+                    .draw_sphere(target, 8, 8, TARGET_SPHERE_RADIUS, limb.color());
+            }
+        }
+
+        let text = match targets[self.limb as usize] {
+            Some(target) => format!("Target: {:.2}, {:.2}, {:.2}", target.x, target.y, target.z),
+            None => "Target: None".to_string(),
+        };
+        ui.send_message(TextMessage::text(
+            self.coordinates_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
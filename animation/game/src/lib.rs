@@ -1,26 +1,78 @@
 //! Game project.
-use crate::player::Player;
+use crate::{
+    bindings::{Bindings, KeyBindingsWindow},
+    bone_weights::BoneWeightView,
+    culling_view::CullingView,
+    custom_widgets::build_blueprint_window,
+    cutscene::Cutscene,
+    floating_origin::FloatingOrigin,
+    gizmo::Gizmo,
+    ik::IkHandle,
+    occlusion::SoftwareOcclusionCuller,
+    player::Player,
+    ragdoll::Ragdoll,
+    resource_stats::ResourceStats,
+    scene_browser::SceneGraphBrowser,
+    stats::{PlayerStats, StatsWindow},
+    timeline::AnimationTimeline,
+};
+use common_scripts::Throttle;
 use fyrox::{
-    core::{algebra::Vector2, log::Log, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        parking_lot::Mutex,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
     engine::GraphicsContext,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
     gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
         grid::{Column, GridBuilder, Row},
-        message::MessageDirection,
+        message::{MessageDirection, UiMessage},
         progress_bar::{ProgressBarBuilder, ProgressBarMessage},
         stack_panel::StackPanelBuilder,
         text::{TextBuilder, TextMessage},
         widget::{WidgetBuilder, WidgetMessage},
-        HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
+    keyboard::{KeyCode, PhysicalKey},
     plugin::{Plugin, PluginContext, PluginRegistrationContext},
     renderer::QualitySettings,
     resource::texture::{loader::TextureLoader, CompressionOptions, TextureImportOptions},
-    scene::Scene,
+    scene::{animation::absm::AnimationBlendingStateMachine, Scene},
+    script::Script,
 };
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
+mod bindings;
+mod bone_weights;
+#[cfg(feature = "net")]
+mod chat;
+mod culling_view;
+mod custom_widgets;
+mod cutscene;
+pub mod ecs_bench;
+mod floating_origin;
+mod gizmo;
+mod ik;
+#[cfg(feature = "net")]
+mod net;
+mod occlusion;
 mod player;
+pub mod prefab_bench;
+mod ragdoll;
+mod resource_stats;
+mod scene_browser;
+mod stats;
+mod timeline;
+mod wireframe;
+
+const SCENE_NAME: &str = "scene";
 
 #[derive(Default, Debug, Visit, Reflect)]
 pub struct Game {
@@ -28,9 +80,95 @@ pub struct Game {
     progress_bar: Handle<UiNode>,
     overlay_grid: Handle<UiNode>,
     debug_text: Handle<UiNode>,
+    blueprint_window: Handle<UiNode>,
+    wireframe_check_box: Handle<UiNode>,
+    /// Draws the triangles of the visible meshes on top of them.
+    show_wireframe: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    scene_browser: SceneGraphBrowser,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    gizmo: Gizmo,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cutscene: Cutscene,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ik_handle: IkHandle,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bindings: Bindings,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    key_bindings_window: KeyBindingsWindow,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    culling_view: CullingView,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    occlusion_culler: SoftwareOcclusionCuller,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bone_weights: BoneWeightView,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    resource_stats: ResourceStats,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    floating_origin: FloatingOrigin,
+    /// Shared with the player, which updates the walked distance.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player_stats: Arc<Mutex<PlayerStats>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stats_window: StatsWindow,
+    /// Timeline with the animations of the player, exists once the scene is loaded.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    timeline: Option<AnimationTimeline>,
+    #[cfg(feature = "net")]
+    #[visit(skip)]
+    #[reflect(hidden)]
+    network_sync: Option<net::NetworkSync>,
+    /// Exists only when the network sync is running.
+    #[cfg(feature = "net")]
+    #[visit(skip)]
+    #[reflect(hidden)]
+    chat: Option<chat::ChatOverlay>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
 }
 
 impl Game {
+    /// Creates the game, that mirrors the state of the player to a remote peer. If the address of
+    /// the peer is not specified, the game waits until the peer connects to it.
+    #[cfg(feature = "net")]
+    pub fn with_network_sync(peer: Option<std::net::SocketAddr>) -> Self {
+        Self {
+            network_sync: net::NetworkSync::new(peer),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_gizmo_enabled(&self) -> bool {
+        self.gizmo.is_enabled()
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn player_stats(&self) -> Arc<Mutex<PlayerStats>> {
+        self.player_stats.clone()
+    }
+
+    pub fn start_cutscene(&mut self, ui: &UserInterface) {
+        self.cutscene.start(ui);
+    }
+
     fn handle_resize(&self, context: &mut PluginContext, new_size: Vector2<f32>) {
         context
             .user_interfaces
@@ -48,15 +186,27 @@ impl Game {
                 MessageDirection::ToWidget,
                 new_size.y,
             ));
+        self.cutscene
+            .handle_resize(context.user_interfaces.first(), new_size.x, new_size.y);
     }
 }
 
+/// Registers the scripts of the game, so the scenes and the prefabs with them could be loaded.
+fn register_scripts(context: &PluginRegistrationContext) {
+    context
+        .serialization_context
+        .script_constructors
+        .add::<Player>("Player");
+    context
+        .serialization_context
+        .script_constructors
+        .add::<Ragdoll>("Ragdoll");
+    common_scripts::register(&context.serialization_context.script_constructors);
+}
+
 impl Plugin for Game {
     fn register(&self, context: PluginRegistrationContext) {
-        context
-            .serialization_context
-            .script_constructors
-            .add::<Player>("Player");
+        register_scripts(&context);
     }
 
     fn init(&mut self, scene_path: Option<&str>, context: PluginContext) {
@@ -110,10 +260,58 @@ impl Plugin for Game {
         .build(ctx);
 
         self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        self.scene_browser = SceneGraphBrowser::new(ctx);
+        self.gizmo = Gizmo::new(ctx);
+        self.cutscene = Cutscene::new(ctx);
+        self.ik_handle = IkHandle::new(ctx);
+        self.floating_origin = FloatingOrigin::new(ctx);
+        self.bone_weights = BoneWeightView::new(ctx);
+        self.player_stats = Arc::new(Mutex::new(PlayerStats::load()));
+        self.stats_window = StatsWindow::new(ctx);
+        self.blueprint_window = build_blueprint_window(ctx);
+        self.bindings = Bindings::load();
+        self.key_bindings_window = KeyBindingsWindow::new(ctx, &self.bindings);
+
+        self.wireframe_check_box =
+            CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                .with_content(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Show Wireframe")
+                        .build(ctx),
+                )
+                .checked(Some(self.show_wireframe))
+                .build(ctx);
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(160.0)
+                .with_height(60.0)
+                .with_desired_position(Vector2::new(5.0, 400.0)),
+        )
+        .with_title(WindowTitle::text("Debug"))
+        .can_close(false)
+        .with_content(self.wireframe_check_box)
+        .build(ctx);
+
+        #[cfg(feature = "net")]
+        if self.network_sync.is_some() {
+            self.chat = Some(chat::ChatOverlay::new(ctx, context.resource_manager));
+        }
     }
 
     fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
         let ui = context.user_interfaces.first();
+
+        {
+            let mut player_stats = self.player_stats.lock();
+            player_stats.time_played_seconds += context.dt;
+            self.stats_window.update(&player_stats, ui);
+        }
+
         let progress = context.resource_manager.state().loading_progress() as f32 / 100.0;
         ui.send_message(ProgressBarMessage::progress(
             self.progress_bar,
@@ -121,42 +319,225 @@ impl Plugin for Game {
             progress,
         ));
 
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            self.culling_view.update(scene);
+            self.bone_weights
+                .update(scene, self.scene_browser.selected(), ui);
+        }
+        self.resource_stats
+            .update(context.dt, context.resource_manager);
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.floating_origin.update(scene, ui);
+        }
+
+        if let (Some(timeline), Some(scene)) =
+            (self.timeline.as_ref(), context.scenes.try_get(self.scene))
+        {
+            timeline.update(&scene.graph, ui);
+        }
+
+        #[cfg(feature = "net")]
+        if let (Some(network_sync), Some(scene)) = (
+            self.network_sync.as_mut(),
+            context.scenes.try_get_mut(self.scene),
+        ) {
+            network_sync.update(&mut scene.graph, &self.floating_origin);
+        }
+
         if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            if let Some(scene) = context.scenes.try_get(self.scene) {
+                self.occlusion_culler
+                    .update(scene, &graphics_context.renderer);
+            }
+
             ui.send_message(TextMessage::text(
                 self.debug_text,
                 MessageDirection::ToWidget,
-                format!("{}", graphics_context.renderer.get_statistics()),
-            ))
+                format!(
+                    "{}\n{}\n[F2] - Scene Graph\n[F3] - Key Bindings\n[G] - Gizmo (select a node in the scene \
+                    graph first, [RMB] - rotate mode, [MMB] - scale mode)\nWalk forward to trigger a cutscene\n\
+                    [Alt+LMB] - place IK target of the selected limb, [Alt+RMB] - remove it\n\
+                    [K] - kill the player, [Space] - get up\n[F5] - Frustum culling view\n[Tab] - Player statistics\n[F6] - Blueprint editor\n\
+                    [F7] - Software occlusion culling benchmark\n[F8] - Bone weights\n\
+                    Drag the playhead of the timeline to scrub the animations of the player\n{}\n{}\n{}",
+                    graphics_context.renderer.get_statistics(),
+                    self.resource_stats.text(),
+                    self.culling_view.stats_text(),
+                    self.occlusion_culler.stats_text(),
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+
+            if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                // Debug lines are drawn from scratch every frame, the gizmo, the IK targets and the
+                // wireframe only add their lines.
+                scene.drawing_context.clear_lines();
+                let frame_size = graphics_context.renderer.get_frame_size();
+                self.gizmo.update(
+                    scene,
+                    self.scene_browser.selected(),
+                    Vector2::new(frame_size.0 as f32, frame_size.1 as f32),
+                );
+                self.ik_handle.update(scene, ui);
+
+                if self.show_wireframe {
+                    wireframe::draw_wireframe(scene);
+                }
+            }
+        }
+
+        self.cutscene
+            .update(context.dt, context.user_interfaces.first());
+
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            self.scene_browser
+                .update(context.dt, context.user_interfaces.first_mut(), scene);
+        }
+
+        #[cfg(feature = "net")]
+        if let (Some(network_sync), Some(chat)) = (self.network_sync.as_mut(), self.chat.as_mut()) {
+            for message in network_sync.take_chat_messages() {
+                chat.add_message(context.user_interfaces.first_mut(), &message);
+            }
         }
     }
 
     fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if self.key_bindings_window.handle_os_event(
+            event,
+            &mut self.bindings,
+            context.user_interfaces.first(),
+        ) {
+            return;
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.gizmo.handle_os_event(
+                event,
+                scene,
+                self.scene_browser.selected(),
+                context.user_interfaces.first(),
+            );
+
+            if let GraphicsContext::Initialized(graphics_context) = &context.graphics_context {
+                let frame_size = graphics_context.renderer.get_frame_size();
+                self.ik_handle.handle_os_event(
+                    event,
+                    scene,
+                    Vector2::new(frame_size.0 as f32, frame_size.1 as f32),
+                );
+            }
+        }
+
         match event {
-            Event::WindowEvent { event, .. } => {
-                if let WindowEvent::Resized(size) = event {
-                    self.handle_resize(
-                        &mut context,
-                        Vector2::new(size.width as f32, size.height as f32),
-                    )
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::Resized(size) => self.handle_resize(
+                    &mut context,
+                    Vector2::new(size.width as f32, size.height as f32),
+                ),
+                WindowEvent::KeyboardInput { event: input, .. } => {
+                    if input.state == ElementState::Pressed {
+                        if input.physical_key == PhysicalKey::Code(KeyCode::F2) {
+                            if let Some(scene) = context.scenes.try_get(self.scene) {
+                                self.scene_browser
+                                    .toggle(context.user_interfaces.first_mut(), scene);
+                            }
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::F3) {
+                            self.key_bindings_window
+                                .open(context.user_interfaces.first());
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::F5) {
+                            if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                                self.culling_view.toggle(scene);
+                            }
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::F6) {
+                            context
+                                .user_interfaces
+                                .first()
+                                .send_message(WindowMessage::open(
+                                    self.blueprint_window,
+                                    MessageDirection::ToWidget,
+                                    true,
+                                    true,
+                                ));
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::F7) {
+                            self.occlusion_culler.toggle();
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::F8) {
+                            if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                                self.bone_weights.toggle(
+                                    scene,
+                                    context.resource_manager,
+                                    context.user_interfaces.first(),
+                                );
+                            }
+                        } else if input.physical_key == PhysicalKey::Code(KeyCode::Tab) {
+                            self.stats_window.toggle(context.user_interfaces.first());
+                        }
+                    }
                 }
-            }
+                _ => (),
+            },
             _ => (),
         }
     }
 
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.wireframe_check_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.show_wireframe = *value;
+            }
+        }
+
+        #[cfg(feature = "net")]
+        if let (Some(network_sync), Some(chat)) = (self.network_sync.as_ref(), self.chat.as_mut()) {
+            if let Some(chat_message) =
+                chat.handle_ui_message(message, context.user_interfaces.first_mut())
+            {
+                network_sync.send_chat(chat_message);
+            }
+        }
+
+        if let (Some(timeline), Some(scene)) = (
+            self.timeline.as_mut(),
+            context.scenes.try_get_mut(self.scene),
+        ) {
+            timeline.handle_ui_message(message, &mut scene.graph);
+        }
+
+        self.gizmo.handle_ui_message(message);
+        self.ik_handle.handle_ui_message(message);
+        self.key_bindings_window.handle_ui_message(
+            message,
+            &self.bindings,
+            context.user_interfaces.first(),
+        );
+
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            self.scene_browser.handle_ui_message(
+                message,
+                context.user_interfaces.first_mut(),
+                scene,
+            );
+        }
+    }
+
     fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
         let graphics_context = context.graphics_context.as_initialized_mut();
 
-        let mut quality_settings = QualitySettings::high();
+        if !prefs::quality::restore(&mut graphics_context.renderer, SCENE_NAME) {
+            let mut quality_settings = QualitySettings::high();
 
-        quality_settings.point_shadows_distance = 6.0;
-        quality_settings.spot_shadows_distance = 6.0;
+            quality_settings.point_shadows_distance = 6.0;
+            quality_settings.spot_shadows_distance = 6.0;
 
-        Log::verify(
-            graphics_context
-                .renderer
-                .set_quality_settings(&quality_settings),
-        );
+            Log::verify(
+                graphics_context
+                    .renderer
+                    .set_quality_settings(&quality_settings),
+            );
+        }
 
         let inner_size = graphics_context.window.inner_size();
         self.handle_resize(
@@ -174,6 +555,39 @@ impl Plugin for Game {
     ) {
         self.scene = scene;
 
+        let graph = &mut context.scenes[scene].graph;
+
+        // The copy must be made before the ragdoll is added to the player.
+        #[cfg(feature = "net")]
+        if let Some(network_sync) = self.network_sync.as_mut() {
+            network_sync.spawn_remote_player(graph);
+        }
+
+        if let Some((player, player_position)) = graph
+            .pair_iter()
+            .find(|(_, node)| node.try_get_script::<Player>().is_some())
+            .map(|(handle, player)| (handle, player.global_position()))
+        {
+            let forward = Vector3::new(0.0, 0.0, 4.0);
+            cutscene::spawn_cutscene_trigger(graph, player_position + forward);
+
+            // The scene has no ragdoll, it is built from the skeleton of the player at runtime.
+            graph[player].add_script(Script::new(Ragdoll::default()));
+
+            let animation_player = graph[player]
+                .try_get_script::<Player>()
+                .and_then(|player| {
+                    graph.try_get_of_type::<AnimationBlendingStateMachine>(player.state_machine())
+                })
+                .map(|machine| machine.animation_player())
+                .unwrap_or_default();
+            self.timeline = AnimationTimeline::new(
+                context.user_interfaces.first_mut(),
+                graph,
+                animation_player,
+            );
+        }
+
         context
             .user_interfaces
             .first()
@@ -183,4 +597,12 @@ impl Plugin for Game {
                 false,
             ));
     }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        self.player_stats.lock().save();
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
 }
@@ -0,0 +1,290 @@
+//! Mirrors the state of the player to a remote peer over UDP, the state of the peer is shown on
+//! a copy of the player - the remote player. The same socket carries chat messages.
+use crate::{floating_origin::FloatingOrigin, player::Player};
+use fyrox::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3, Vector4},
+        log::Log,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{
+        animation::absm::prelude::*,
+        camera::Camera,
+        collider::Collider,
+        graph::Graph,
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyType},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+};
+
+/// Port, that is used when it is not taken by another instance of the demo.
+pub const DEFAULT_PORT: u16 = 7780;
+/// Max size of a packet, longer chat messages are not sent.
+const MAX_PACKET_SIZE: usize = 1024;
+
+/// State of the player, that is sent every frame. Math types of the engine can't be serialized
+/// with serde, so the vectors are stored as arrays. The position is absolute (see
+/// [`FloatingOrigin`]), the peers may have different origins.
+#[derive(Serialize, Deserialize, Debug)]
+struct PlayerState {
+    /// Incremented with every packet, packets that are older than the last received one are
+    /// ignored.
+    sequence: u32,
+    position: [f64; 3],
+    /// Rotation of the model pivot (`x`, `y`, `z`, `w`).
+    rotation: [f32; 4],
+    moving: bool,
+    animation_index: u8,
+    model_yaw: f32,
+}
+
+/// Text message, that is shown in the chat of the peer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Packet {
+    State(PlayerState),
+    Chat(ChatMessage),
+}
+
+/// Nodes of the copy of the player, that are driven by the received state.
+#[derive(Default, Debug)]
+struct RemotePlayer {
+    root: Handle<Node>,
+    model_pivot: Handle<Node>,
+    model: Handle<Node>,
+    state_machine: Handle<Node>,
+}
+
+#[derive(Debug)]
+pub struct NetworkSync {
+    socket: UdpSocket,
+    /// Address of the remote peer. If it is not specified, it is taken from the first received
+    /// packet.
+    peer: Option<SocketAddr>,
+    sequence: u32,
+    last_received: Option<u32>,
+    remote_player: RemotePlayer,
+    /// Chat messages, that are received, but not yet taken by the chat.
+    received_chat: Vec<ChatMessage>,
+}
+
+impl NetworkSync {
+    /// Binds a socket to [`DEFAULT_PORT`] or to any free port, if the default one is taken (when
+    /// both instances are running on the same machine).
+    pub fn new(peer: Option<SocketAddr>) -> Option<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DEFAULT_PORT))
+            .or_else(|_| UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)))
+            .map_err(|err| Log::err(format!("Unable to bind a sync socket. Reason: {err}")))
+            .ok()?;
+        Log::verify(socket.set_nonblocking(true));
+        if let Ok(address) = socket.local_addr() {
+            Log::info(format!("Network sync is listening on {address}"));
+        }
+
+        Some(Self {
+            socket,
+            peer,
+            sequence: 0,
+            last_received: None,
+            remote_player: Default::default(),
+            received_chat: Default::default(),
+        })
+    }
+
+    /// Creates a copy of the player, that does not react on input and does not push other
+    /// bodies around.
+    pub fn spawn_remote_player(&mut self, graph: &mut Graph) {
+        let Some((player_handle, player)) = graph
+            .pair_iter()
+            .find_map(|(handle, node)| node.try_get_script::<Player>().map(|p| (handle, p)))
+        else {
+            return;
+        };
+        let nodes = [player.model_pivot(), player.model(), player.state_machine()];
+
+        let (root, mapping) = graph.copy_node_inplace(player_handle, &mut |_, _| true);
+        let [mut model_pivot, mut model, mut state_machine] = nodes;
+        mapping
+            .map(&mut model_pivot)
+            .map(&mut model)
+            .map(&mut state_machine);
+        self.remote_player = RemotePlayer {
+            root,
+            model_pivot,
+            model,
+            state_machine,
+        };
+
+        let root_node = &mut graph[root];
+        root_node.remove_all_scripts();
+        root_node.set_name("Remote Player");
+        // Hidden until the first packet arrives.
+        root_node.set_visibility(false);
+        if let Some(body) = root_node.cast_mut::<RigidBody>() {
+            body.set_body_type(RigidBodyType::KinematicPositionBased);
+        }
+
+        let descendants = graph.traverse_handle_iter(root).collect::<Vec<_>>();
+        for handle in descendants {
+            let node = &mut graph[handle];
+            if let Some(collider) = node.cast_mut::<Collider>() {
+                collider.set_is_sensor(true);
+            } else if let Some(camera) = node.cast_mut::<Camera>() {
+                camera.set_enabled(false);
+            }
+        }
+    }
+
+    fn send_state(&mut self, graph: &Graph, origin: &FloatingOrigin) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        let Some((node, player)) = graph
+            .linear_iter()
+            .find_map(|node| node.try_get_script::<Player>().map(|p| (node, p)))
+        else {
+            return;
+        };
+        let rotation = graph
+            .try_get(player.model_pivot())
+            .map(|pivot| **pivot.local_transform().rotation())
+            .unwrap_or_else(UnitQuaternion::identity);
+
+        self.sequence = self.sequence.wrapping_add(1);
+        let state = Packet::State(PlayerState {
+            sequence: self.sequence,
+            position: origin.to_world(node.global_position()).into(),
+            rotation: rotation.coords.into(),
+            moving: player.is_moving(),
+            animation_index: player.animation_index(),
+            model_yaw: player.model_yaw(),
+        });
+        self.send_packet(peer, &state, "player state");
+    }
+
+    fn send_packet(&self, peer: SocketAddr, packet: &Packet, description: &str) {
+        match bincode::serialize(packet) {
+            Ok(bytes) => {
+                if bytes.len() > MAX_PACKET_SIZE {
+                    Log::err(format!(
+                        "Unable to send {description}. Reason: it is too long"
+                    ));
+                } else if let Err(err) = self.socket.send_to(&bytes, peer) {
+                    if err.kind() != ErrorKind::WouldBlock {
+                        Log::err(format!("Unable to send {description}. Reason: {err}"));
+                    }
+                }
+            }
+            Err(err) => Log::err(format!("Unable to serialize {description}. Reason: {err}")),
+        }
+    }
+
+    /// Sends the message to the peer. The message is lost, if the peer is not connected yet.
+    pub fn send_chat(&self, message: ChatMessage) {
+        if let Some(peer) = self.peer {
+            self.send_packet(peer, &Packet::Chat(message), "chat message");
+        }
+    }
+
+    /// Returns the chat messages, that are received since the last call.
+    pub fn take_chat_messages(&mut self) -> Vec<ChatMessage> {
+        std::mem::take(&mut self.received_chat)
+    }
+
+    /// Reads every packet in the queue, stores the chat messages and returns the newest state.
+    fn receive_packets(&mut self) -> Option<PlayerState> {
+        let mut newest = None;
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, address)) => {
+                    let state = match bincode::deserialize::<Packet>(&buffer[..size]) {
+                        Ok(Packet::State(state)) => state,
+                        Ok(Packet::Chat(message)) => {
+                            self.peer.get_or_insert(address);
+                            self.received_chat.push(message);
+                            continue;
+                        }
+                        Err(_) => continue,
+                    };
+                    // Sequence numbers wrap around, so the difference tells which one is newer.
+                    let is_newer = self
+                        .last_received
+                        .map_or(true, |last| (state.sequence.wrapping_sub(last) as i32) > 0);
+                    if !is_newer {
+                        continue;
+                    }
+                    self.last_received = Some(state.sequence);
+                    self.peer.get_or_insert(address);
+                    newest = Some(state);
+                }
+                Err(err) => {
+                    if err.kind() != ErrorKind::WouldBlock {
+                        Log::err(format!("Unable to receive a packet. Reason: {err}"));
+                    }
+                    break;
+                }
+            }
+        }
+        newest
+    }
+
+    fn apply_state(&self, graph: &mut Graph, origin: &FloatingOrigin, state: PlayerState) {
+        let remote = &self.remote_player;
+        let Some(root) = graph.try_get_mut(remote.root) else {
+            return;
+        };
+        root.set_visibility(true);
+        root.local_transform_mut()
+            .set_position(origin.to_local(Vector3::from(state.position)));
+
+        if let Some(model_pivot) = graph.try_get_mut(remote.model_pivot) {
+            model_pivot
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::new_normalize(Quaternion::from_vector(
+                    Vector4::from(state.rotation),
+                )));
+        }
+
+        if let Some(model) = graph.try_get_mut(remote.model) {
+            model
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    state.model_yaw,
+                ));
+        }
+
+        if let Some(state_machine) = graph
+            .try_get_mut(remote.state_machine)
+            .and_then(|node| node.component_mut::<AnimationBlendingStateMachine>())
+        {
+            state_machine
+                .machine_mut()
+                .get_value_mut_silent()
+                .set_parameter("Moving", Parameter::Rule(state.moving))
+                .set_parameter(
+                    "MoveAnimationIndex",
+                    Parameter::Index(state.animation_index as u32),
+                );
+        }
+    }
+
+    pub fn update(&mut self, graph: &mut Graph, origin: &FloatingOrigin) {
+        self.send_state(graph, origin);
+        if let Some(state) = self.receive_packets() {
+            self.apply_state(graph, origin, state);
+        }
+    }
+}
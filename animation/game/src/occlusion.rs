@@ -0,0 +1,201 @@
+//! Software occlusion culling benchmark. Bounding boxes of large meshes are rasterized into a
+//! small depth buffer on CPU, then bounding boxes of every mesh are tested against it. This is a
+//! research tool to compare a simple CPU approach with the renderer, not a replacement for the
+//! culling of the renderer.
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3, Vector4},
+        instant::Instant,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::Renderer,
+    scene::{camera::Camera, mesh::Mesh, node::Node, Scene},
+};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// Size of the depth buffer along each axis.
+const BUFFER_SIZE: usize = 128;
+/// Meshes, that cover less pixels of the buffer, are not used as occluders.
+const MIN_OCCLUDER_AREA: usize = 64;
+
+/// Rectangle of the buffer covered by a bounding box, bounds are inclusive.
+#[derive(Copy, Clone, Debug)]
+struct ScreenRect {
+    min: (usize, usize),
+    max: (usize, usize),
+    /// Depth of the nearest corner of the box.
+    near: f32,
+    /// Depth of the farthest corner of the box.
+    far: f32,
+}
+
+impl ScreenRect {
+    fn area(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) * (self.max.1 - self.min.1 + 1)
+    }
+}
+
+/// Projects the bounding box to the buffer. Returns `None` if the box is outside the view or
+/// crosses the near plane - such boxes can't be tested reliably.
+fn project(aabb: &AxisAlignedBoundingBox, view_projection: &Matrix4<f32>) -> Option<ScreenRect> {
+    let mut min = Vector3::repeat(f32::MAX);
+    let mut max = Vector3::repeat(-f32::MAX);
+    for corner in aabb.corners() {
+        let clip = view_projection * Vector4::new(corner.x, corner.y, corner.z, 1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+        let ndc = clip.xyz() / clip.w;
+        min = min.inf(&ndc);
+        max = max.sup(&ndc);
+    }
+    if max.x < -1.0 || min.x > 1.0 || max.y < -1.0 || min.y > 1.0 || min.z > 1.0 {
+        return None;
+    }
+
+    let to_pixel = |v: f32| {
+        (((v * 0.5 + 0.5) * BUFFER_SIZE as f32) as isize).clamp(0, BUFFER_SIZE as isize - 1)
+            as usize
+    };
+    Some(ScreenRect {
+        min: (to_pixel(min.x), to_pixel(min.y)),
+        max: (to_pixel(max.x), to_pixel(max.y)),
+        near: min.z * 0.5 + 0.5,
+        far: max.z * 0.5 + 0.5,
+    })
+}
+
+#[derive(Default, Debug)]
+pub struct SoftwareOcclusionCuller {
+    enabled: bool,
+    depth: Vec<f32>,
+    /// Meshes, that passed the occlusion test in the last update.
+    visible_set: HashSet<Handle<Node>>,
+    total_meshes: usize,
+    occluders: usize,
+    /// Amount of surfaces of the visible meshes, each surface is a separate draw call.
+    visible_surfaces: usize,
+    gpu_draw_calls: usize,
+    /// Time of the last update in seconds.
+    update_time: f32,
+}
+
+impl SoftwareOcclusionCuller {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.visible_set.clear();
+    }
+
+    /// Rasterizes every occluder into the buffer at the depth of its farthest corner, so an
+    /// object is occluded only if it is behind the whole bounding box of an occluder. Bounding
+    /// boxes are larger than the meshes, so the result is still an approximation.
+    fn rasterize(&mut self, occluders: &[ScreenRect]) {
+        self.depth.clear();
+        self.depth.resize(BUFFER_SIZE * BUFFER_SIZE, 1.0);
+        self.depth
+            .par_chunks_mut(BUFFER_SIZE)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for occluder in occluders {
+                    if y < occluder.min.1 || y > occluder.max.1 {
+                        continue;
+                    }
+                    for depth in row[occluder.min.0..=occluder.max.0].iter_mut() {
+                        *depth = depth.min(occluder.far);
+                    }
+                }
+            });
+    }
+
+    /// A box is visible, if its nearest corner is in front of the buffer in at least one pixel.
+    fn is_visible(&self, rect: &ScreenRect) -> bool {
+        (rect.min.1..=rect.max.1).any(|y| {
+            self.depth[y * BUFFER_SIZE + rect.min.0..=y * BUFFER_SIZE + rect.max.0]
+                .iter()
+                .any(|depth| rect.near <= *depth)
+        })
+    }
+
+    pub fn update(&mut self, scene: &Scene, renderer: &Renderer) {
+        if !self.enabled {
+            return;
+        }
+
+        let Some(camera) = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled())
+        else {
+            return;
+        };
+        let view_projection = camera.view_projection_matrix();
+        let frustum = camera.frustum();
+
+        let start = Instant::now();
+
+        let meshes = scene
+            .graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                let mesh = node.cast::<Mesh>()?;
+                mesh.global_visibility()
+                    .then(|| (handle, mesh.world_bounding_box(), mesh.surfaces().len()))
+            })
+            .collect::<Vec<_>>();
+
+        let rects = meshes
+            .par_iter()
+            .map(|(_, aabb, _)| project(aabb, &view_projection))
+            .collect::<Vec<_>>();
+        let occluders = rects
+            .iter()
+            .flatten()
+            .filter(|rect| rect.area() >= MIN_OCCLUDER_AREA)
+            .copied()
+            .collect::<Vec<_>>();
+        self.rasterize(&occluders);
+
+        let visible = meshes
+            .par_iter()
+            .zip(rects.par_iter())
+            .filter_map(|((handle, aabb, surfaces), rect)| {
+                let visible = match rect {
+                    Some(rect) => self.is_visible(rect),
+                    // Boxes outside of the view or the ones, that cross the near plane.
+                    None => frustum.is_intersects_aabb(aabb),
+                };
+                visible.then_some((*handle, *surfaces))
+            })
+            .collect::<Vec<_>>();
+
+        self.visible_surfaces = visible.iter().map(|(_, surfaces)| surfaces).sum();
+        self.visible_set = visible.into_iter().map(|(handle, _)| handle).collect();
+        self.total_meshes = meshes.len();
+        self.occluders = occluders.len();
+        self.update_time = start.elapsed().as_secs_f32();
+        self.gpu_draw_calls = renderer.get_statistics().geometry.draw_calls;
+    }
+
+    pub fn stats_text(&self) -> String {
+        if !self.enabled {
+            return Default::default();
+        }
+
+        // The renderer does not report which objects it has culled, only the amount of draw
+        // calls, so the results can be compared only by the amount of drawn surfaces.
+        format!(
+            "Software Occlusion: {} of {} meshes visible, {} occluders, {:.2} ms\n\
+            Surfaces: {} software / {} GPU draw calls",
+            self.visible_set.len(),
+            self.total_meshes,
+            self.occluders,
+            self.update_time * 1000.0,
+            self.visible_surfaces,
+            self.gpu_draw_calls,
+        )
+    }
+}
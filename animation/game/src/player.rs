@@ -1,8 +1,16 @@
+use crate::{
+    bindings::Action,
+    ik::{solve_two_bone_ik, Limb},
+    ragdoll::Ragdoll,
+    stats::PlayerStats,
+    Game,
+};
 use fyrox::graph::{BaseSceneGraph, SceneGraph, SceneGraphNode};
 use fyrox::{
     core::{
         algebra::{UnitQuaternion, Vector3},
         math::SmoothAngle,
+        parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -14,9 +22,14 @@ use fyrox::{
     keyboard::{KeyCode, PhysicalKey},
     scene::{animation::absm::prelude::*, node::Node, rigidbody::RigidBody},
     script::{ScriptContext, ScriptTrait},
+    utils::translate_key_to_ui,
 };
+use std::sync::Arc;
+
+/// Health of the player at the start and after getting up.
+const MAX_HP: f32 = 100.0;
 
-#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
 #[type_uuid(id = "e224206c-856b-40ff-84e1-7f9bf52c2bb2")]
 #[visit(optional)]
 pub struct Player {
@@ -26,6 +39,7 @@ pub struct Player {
     model_pivot: InheritableVariable<Handle<Node>>,
     model: InheritableVariable<Handle<Node>>,
     model_yaw: InheritableVariable<SmoothAngle>,
+    hp: InheritableVariable<f32>,
 
     #[reflect(hidden)]
     #[visit(skip)]
@@ -54,28 +68,296 @@ pub struct Player {
     #[reflect(hidden)]
     #[visit(skip)]
     pitch: f32,
+
+    /// IK targets of the limbs in world space, in the order of [`Limb::ALL`].
+    #[reflect(hidden)]
+    #[visit(skip)]
+    ik_targets: [Option<Vector3<f32>>; 4],
+
+    /// Bone chains of the limbs, in the order of [`Limb::ALL`].
+    #[reflect(hidden)]
+    #[visit(skip)]
+    ik_chains: Vec<[Handle<Node>; 3]>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    ik_masks_dirty: bool,
+
+    /// Velocity of the root motion in the last frame, it is passed to the ragdoll on death.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    velocity: Vector3<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    dead: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    get_up: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    stats: Option<Arc<Mutex<PlayerStats>>>,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            camera_pivot: Default::default(),
+            camera_hinge: Default::default(),
+            state_machine: Default::default(),
+            model_pivot: Default::default(),
+            model: Default::default(),
+            model_yaw: Default::default(),
+            hp: MAX_HP.into(),
+            walk_forward: false,
+            walk_backward: false,
+            walk_left: false,
+            walk_right: false,
+            run: false,
+            yaw: 0.0,
+            pitch: 0.0,
+            ik_targets: Default::default(),
+            ik_chains: Default::default(),
+            ik_masks_dirty: false,
+            velocity: Default::default(),
+            dead: false,
+            get_up: false,
+            stats: None,
+        }
+    }
+}
+
+impl Player {
+    pub fn damage(&mut self, amount: f32) {
+        self.hp
+            .set_value_and_mark_modified((*self.hp - amount).max(0.0));
+    }
+
+    fn update_camera(&self, ctx: &mut ScriptContext) {
+        if let Some(camera_pivot) = ctx.scene.graph.try_get_mut(*self.camera_pivot) {
+            camera_pivot
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    self.yaw,
+                ));
+        }
+
+        // Rotate camera hinge - this will make camera move up and down while look at character
+        // (well not exactly on character - on characters head)
+        if let Some(camera_hinge) = ctx.scene.graph.try_get_mut(*self.camera_hinge) {
+            camera_hinge
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    self.pitch,
+                ));
+        }
+    }
+
+    fn set_state_machine_enabled(&self, ctx: &mut ScriptContext, enabled: bool) {
+        if let Some(state_machine) = ctx.scene.graph.try_get_mut(*self.state_machine) {
+            state_machine.set_enabled(enabled);
+        }
+    }
+
+    /// Collapses the character into the ragdoll, it keeps moving with the velocity of the
+    /// animation at the moment of death.
+    fn die(&mut self, ctx: &mut ScriptContext) {
+        self.dead = true;
+        self.set_state_machine_enabled(ctx, false);
+
+        // The capsule of the character stays where it is, only the ragdoll keeps moving.
+        if let Some(body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+        }
+
+        if let Some(ragdoll) = ctx.scene.graph[ctx.handle].try_get_script_mut::<Ragdoll>() {
+            ragdoll.activate(self.velocity);
+        }
+    }
+
+    /// Moves the character to the place where the ragdoll has fallen and returns the control over
+    /// the bones back to animation.
+    fn revive(&mut self, ctx: &mut ScriptContext) {
+        self.dead = false;
+        self.hp.set_value_and_mark_modified(MAX_HP);
+        self.set_state_machine_enabled(ctx, true);
+
+        let graph = &mut ctx.scene.graph;
+        let ragdoll_position = graph[ctx.handle]
+            .try_get_script::<Ragdoll>()
+            .and_then(|ragdoll| ragdoll.root_position(graph));
+        if let Some(ragdoll_position) = ragdoll_position {
+            let transform = graph[ctx.handle].local_transform_mut();
+            let position = **transform.position();
+            transform.set_position(Vector3::new(
+                ragdoll_position.x,
+                position.y,
+                ragdoll_position.z,
+            ));
+        }
+
+        if let Some(ragdoll) = graph[ctx.handle].try_get_script_mut::<Ragdoll>() {
+            ragdoll.deactivate();
+        }
+    }
+
+    #[cfg(feature = "net")]
+    pub fn model_pivot(&self) -> Handle<Node> {
+        *self.model_pivot
+    }
+
+    #[cfg(feature = "net")]
+    pub fn model(&self) -> Handle<Node> {
+        *self.model
+    }
+
+    #[cfg(feature = "net")]
+    pub fn state_machine(&self) -> Handle<Node> {
+        *self.state_machine
+    }
+
+    #[cfg(feature = "net")]
+    pub fn model_yaw(&self) -> f32 {
+        self.model_yaw.angle
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.walk_left || self.walk_right || self.walk_forward || self.walk_backward
+    }
+
+    /// Index of the move animation: 0 - walk, 1 - run.
+    pub fn animation_index(&self) -> u8 {
+        if self.run {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn ik_targets(&self) -> [Option<Vector3<f32>>; 4] {
+        self.ik_targets
+    }
+
+    /// Sets IK target of the limb in world space, `None` returns the limb back to animation.
+    pub fn set_ik_target(&mut self, limb: Limb, target: Option<Vector3<f32>>) {
+        self.ik_targets[limb as usize] = target;
+        self.ik_masks_dirty = true;
+    }
+
+    /// Moves the IK targets along with the world, when the floating origin is shifted.
+    pub fn shift_ik_targets(&mut self, offset: Vector3<f32>) {
+        for target in self.ik_targets.iter_mut().flatten() {
+            *target += offset;
+        }
+    }
+
+    fn update_ik(&mut self, ctx: &mut ScriptContext) {
+        if self.ik_chains.is_empty() {
+            self.ik_chains = Limb::ALL
+                .iter()
+                .map(|limb| {
+                    limb.bone_names().map(|name| {
+                        ctx.scene
+                            .graph
+                            .find_by_name(*self.model, name)
+                            .map(|(handle, _)| handle)
+                            .unwrap_or_default()
+                    })
+                })
+                .collect();
+        }
+
+        // Animation must not touch the bones, that are controlled by IK, otherwise it'll overwrite
+        // rotations of the bones.
+        if std::mem::take(&mut self.ik_masks_dirty) {
+            if let Some(state_machine) = ctx
+                .scene
+                .graph
+                .try_get_mut(*self.state_machine)
+                .and_then(|node| node.component_mut::<AnimationBlendingStateMachine>())
+            {
+                for layer in state_machine
+                    .machine_mut()
+                    .get_value_mut_silent()
+                    .layers_mut()
+                {
+                    let mut excluded = layer
+                        .mask()
+                        .inner()
+                        .iter()
+                        .filter(|bone| !self.ik_chains.iter().any(|chain| chain.contains(bone)))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    for (chain, target) in self.ik_chains.iter().zip(self.ik_targets.iter()) {
+                        if target.is_some() {
+                            excluded.extend_from_slice(chain);
+                        }
+                    }
+                    layer.set_mask(LayerMask::from(excluded));
+                }
+            }
+        }
+
+        for (chain, target) in self.ik_chains.iter().zip(self.ik_targets.iter()) {
+            if let Some(target) = target {
+                solve_two_bone_ik(&mut ctx.scene.graph, *chain, *target);
+            }
+        }
+    }
 }
 
 impl ScriptTrait for Player {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.stats = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| game.player_stats());
+    }
+
     fn on_os_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) {
         match event {
             Event::WindowEvent { event, .. } => {
                 if let WindowEvent::KeyboardInput { event, .. } = event {
                     let pressed = event.state == ElementState::Pressed;
                     if let PhysicalKey::Code(code) = event.physical_key {
-                        match code {
-                            KeyCode::KeyW => self.walk_forward = pressed,
-                            KeyCode::KeyS => self.walk_backward = pressed,
-                            KeyCode::KeyA => self.walk_left = pressed,
-                            KeyCode::KeyD => self.walk_right = pressed,
-                            KeyCode::ShiftLeft => self.run = pressed,
-                            _ => (),
+                        if pressed {
+                            match code {
+                                KeyCode::KeyK => self.damage(MAX_HP),
+                                KeyCode::Space if self.dead => self.get_up = true,
+                                _ => (),
+                            }
+                        }
+
+                        let action = ctx
+                            .plugins
+                            .of_type_ref::<Game>()
+                            .and_then(|game| game.bindings().action(translate_key_to_ui(code)));
+                        match action {
+                            Some(Action::WalkForward) => self.walk_forward = pressed,
+                            Some(Action::WalkBackward) => self.walk_backward = pressed,
+                            Some(Action::WalkLeft) => self.walk_left = pressed,
+                            Some(Action::WalkRight) => self.walk_right = pressed,
+                            Some(Action::Run) => self.run = pressed,
+                            None => (),
                         }
                     }
                 }
             }
             Event::DeviceEvent { event, .. } => {
                 if let DeviceEvent::MouseMotion { delta } = event {
+                    // The mouse is used to drag the gizmo, so the camera must stay still.
+                    if ctx
+                        .plugins
+                        .of_type_ref::<Game>()
+                        .map_or(false, |game| game.is_gizmo_enabled())
+                    {
+                        return;
+                    }
+
                     let mouse_sens = 0.2 * ctx.dt;
                     self.yaw -= (delta.0 as f32) * mouse_sens;
                     self.pitch = (self.pitch + (delta.1 as f32) * mouse_sens)
@@ -87,6 +369,20 @@ impl ScriptTrait for Player {
     }
 
     fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if *self.hp <= 0.0 && !self.dead {
+            self.die(ctx);
+        }
+        if std::mem::take(&mut self.get_up) && self.dead {
+            self.revive(ctx);
+        }
+
+        self.update_camera(ctx);
+
+        // Physics drives the bones of the dead character.
+        if self.dead {
+            return;
+        }
+
         let pivot = &ctx.scene.graph[*self.model];
 
         let transform = pivot.global_transform();
@@ -105,6 +401,10 @@ impl ScriptTrait for Player {
                     .scale(1.0 / ctx.dt);
             }
         }
+        self.velocity = velocity;
+        if let Some(stats) = self.stats.as_ref() {
+            stats.lock().total_distance_m += velocity.norm() * ctx.dt;
+        }
 
         if let Some(body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
             let quat_yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw);
@@ -153,21 +453,6 @@ impl ScriptTrait for Player {
                         ));
                 }
             }
-
-            if let Some(camera_pivot) = ctx.scene.graph.try_get_mut(*self.camera_pivot) {
-                camera_pivot.local_transform_mut().set_rotation(quat_yaw);
-            }
-
-            // Rotate camera hinge - this will make camera move up and down while look at character
-            // (well not exactly on character - on characters head)
-            if let Some(camera_hinge) = ctx.scene.graph.try_get_mut(*self.camera_hinge) {
-                camera_hinge
-                    .local_transform_mut()
-                    .set_rotation(UnitQuaternion::from_axis_angle(
-                        &Vector3::x_axis(),
-                        self.pitch,
-                    ));
-            }
         }
 
         if let Some(state_machine) = ctx
@@ -176,17 +461,16 @@ impl ScriptTrait for Player {
             .try_get_mut(*self.state_machine)
             .and_then(|node| node.component_mut::<AnimationBlendingStateMachine>())
         {
-            let moving =
-                self.walk_left || self.walk_right || self.walk_forward || self.walk_backward;
-
             state_machine
                 .machine_mut()
                 .get_value_mut_silent()
-                .set_parameter("Moving", Parameter::Rule(moving))
+                .set_parameter("Moving", Parameter::Rule(self.is_moving()))
                 .set_parameter(
                     "MoveAnimationIndex",
-                    Parameter::Index(if self.run { 1 } else { 0 }),
+                    Parameter::Index(self.animation_index() as u32),
                 );
         }
+
+        self.update_ik(ctx);
     }
 }
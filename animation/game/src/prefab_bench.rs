@@ -0,0 +1,130 @@
+//! Benchmark of the instantiation of prefabs. The player prefab is instantiated [`INSTANCE_COUNT`]
+//! times into an empty scene, the time and the memory of the instantiation are written to stdout.
+//! It is started with the `--prefab-bench` argument of the executor. Memory is measured only if
+//! the executor is built with the `track_alloc` feature.
+use fyrox::{
+    asset::manager::ResourceManager,
+    core::{futures::executor::block_on, log::Log, reflect::prelude::*, visitor::prelude::*},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::model::{Model, ModelResourceExtension},
+    scene::Scene,
+};
+use std::time::{Duration, Instant};
+
+const PREFAB_PATH: &str = "data/paladin/paladin.rgs";
+/// Amount of the instances, that are created on the warm path.
+const INSTANCE_COUNT: usize = 1000;
+
+struct BenchResult {
+    path: &'static str,
+    instances: usize,
+    elapsed: Duration,
+    /// Amount of bytes, that stayed allocated after the instantiation.
+    memory_delta: Option<isize>,
+}
+
+impl BenchResult {
+    fn mean_microseconds(&self) -> f64 {
+        self.elapsed.as_secs_f64() * 1_000_000.0 / self.instances as f64
+    }
+}
+
+/// Measures the time and the memory of `func`.
+fn measure(path: &'static str, instances: usize, func: impl FnOnce()) -> BenchResult {
+    let memory_before = tracked_alloc::current_bytes();
+    let start = Instant::now();
+    func();
+    let elapsed = start.elapsed();
+    BenchResult {
+        path,
+        instances,
+        elapsed,
+        memory_delta: tracked_alloc::is_installed()
+            .then(|| tracked_alloc::current_bytes() as isize - memory_before as isize),
+    }
+}
+
+/// Runs both paths and writes the results as a table to stdout. Returns `false` if the prefab
+/// can't be loaded.
+fn run(resource_manager: &ResourceManager) -> bool {
+    let mut scene = Scene::new();
+
+    // The prefab is not in the cache yet, so the time includes the loading of the prefab and all
+    // its resources.
+    let mut prefab = None;
+    let cold = measure("Cold (load + instantiate)", 1, || {
+        match block_on(resource_manager.request::<Model>(PREFAB_PATH)) {
+            Ok(model) => {
+                model.instantiate(&mut scene);
+                prefab = Some(model);
+            }
+            Err(err) => Log::err(format!("Unable to load {PREFAB_PATH}. Reason: {err:?}")),
+        }
+    });
+    let Some(prefab) = prefab else {
+        return false;
+    };
+
+    let warm = measure("Warm (instantiate)", INSTANCE_COUNT, || {
+        for _ in 0..INSTANCE_COUNT {
+            prefab.instantiate(&mut scene);
+        }
+    });
+
+    println!(
+        "{PREFAB_PATH}, {} nodes in the scene",
+        scene.graph.node_count()
+    );
+    println!(
+        "| {:<26} | {:>9} | {:>12} | {:>14} | {:>14} |",
+        "Path", "Instances", "Total (ms)", "Mean (us)", "Memory (KB)"
+    );
+    println!(
+        "|{:-<28}|{:->11}|{:->14}|{:->16}|{:->16}|",
+        "", "", "", "", ""
+    );
+    for result in [cold, warm] {
+        println!(
+            "| {:<26} | {:>9} | {:>12.3} | {:>14.1} | {:>14} |",
+            result.path,
+            result.instances,
+            result.elapsed.as_secs_f64() * 1000.0,
+            result.mean_microseconds(),
+            result.memory_delta.map_or("N/A".to_string(), |delta| {
+                format!("{:.1}", delta as f64 / 1024.0)
+            })
+        );
+    }
+    if !tracked_alloc::is_installed() {
+        println!("Build the executor with --features track_alloc to measure memory");
+    }
+    true
+}
+
+/// Plugin, that runs the benchmark on the first update and exits.
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct PrefabBench {
+    finished: bool,
+}
+
+impl Plugin for PrefabBench {
+    fn register(&self, context: PluginRegistrationContext) {
+        // The prefab has scripts of the game.
+        crate::register_scripts(&context);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+
+        // The event loop can't report an exit code, so the process is terminated right away.
+        if !run(context.resource_manager) {
+            std::process::exit(1);
+        }
+        if let Some(window_target) = context.window_target {
+            window_target.exit();
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! A ragdoll made of capsule rigid bodies, that mirrors the skeleton of the character. The bodies
+//! follow the animated bones while the ragdoll is inactive and drive the bones when it is active.
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{
+        base::BaseBuilder,
+        collider::{BitMask, Collider, ColliderBuilder, ColliderShape, InteractionGroups},
+        graph::Graph,
+        joint::{BallJoint, JointBuilder, JointParams},
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+    },
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+/// Collision group of the ragdoll bodies. The character's own collider ignores this group, so the
+/// ragdoll does not collide with the capsule of the character.
+const RAGDOLL_GROUP: u32 = 1 << 15;
+
+/// Describes a body of the ragdoll.
+struct LimbDefinition {
+    /// Name of the bone, that is driven by the body.
+    bone: &'static str,
+    /// Name of the bone, where the body ends.
+    end: &'static str,
+    radius: f32,
+    /// Index of the parent limb in [`LIMBS`], the limbs are connected with ball joints.
+    parent: Option<usize>,
+}
+
+/// Major bones of the character, parent limbs always go before their children.
+const LIMBS: [LimbDefinition; 11] = [
+    LimbDefinition {
+        bone: "mixamorig:Hips",
+        end: "mixamorig:Spine2",
+        radius: 0.14,
+        parent: None,
+    },
+    LimbDefinition {
+        bone: "mixamorig:Spine2",
+        end: "mixamorig:Neck",
+        radius: 0.14,
+        parent: Some(0),
+    },
+    LimbDefinition {
+        bone: "mixamorig:Head",
+        end: "mixamorig:HeadTop_End",
+        radius: 0.1,
+        parent: Some(1),
+    },
+    LimbDefinition {
+        bone: "mixamorig:LeftArm",
+        end: "mixamorig:LeftForeArm",
+        radius: 0.05,
+        parent: Some(1),
+    },
+    LimbDefinition {
+        bone: "mixamorig:LeftForeArm",
+        end: "mixamorig:LeftHand",
+        radius: 0.04,
+        parent: Some(3),
+    },
+    LimbDefinition {
+        bone: "mixamorig:RightArm",
+        end: "mixamorig:RightForeArm",
+        radius: 0.05,
+        parent: Some(1),
+    },
+    LimbDefinition {
+        bone: "mixamorig:RightForeArm",
+        end: "mixamorig:RightHand",
+        radius: 0.04,
+        parent: Some(5),
+    },
+    LimbDefinition {
+        bone: "mixamorig:LeftUpLeg",
+        end: "mixamorig:LeftLeg",
+        radius: 0.07,
+        parent: Some(0),
+    },
+    LimbDefinition {
+        bone: "mixamorig:LeftLeg",
+        end: "mixamorig:LeftFoot",
+        radius: 0.05,
+        parent: Some(7),
+    },
+    LimbDefinition {
+        bone: "mixamorig:RightUpLeg",
+        end: "mixamorig:RightLeg",
+        radius: 0.07,
+        parent: Some(0),
+    },
+    LimbDefinition {
+        bone: "mixamorig:RightLeg",
+        end: "mixamorig:RightFoot",
+        radius: 0.05,
+        parent: Some(9),
+    },
+];
+
+#[derive(Default, Debug, Clone)]
+struct Limb {
+    bone: Handle<Node>,
+    body: Handle<Node>,
+    /// A joint, that connects the body with the body of the parent limb.
+    joint: Handle<Node>,
+    /// Local position of the bone before the ragdoll has moved it.
+    bind_position: Vector3<f32>,
+}
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "3b8f6a0e-54d1-4c8e-9a57-0f2d6c1e7b94")]
+#[visit(optional)]
+pub struct Ragdoll {
+    /// Mass of every body of the ragdoll.
+    mass: InheritableVariable<f32>,
+    /// Linear and angular damping of the bodies.
+    drag: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    limbs: Vec<Limb>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    active: bool,
+
+    /// Set when the ragdoll is activated or deactivated, the bodies are switched in the next
+    /// update.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state_changed: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    initial_velocity: Vector3<f32>,
+}
+
+impl Default for Ragdoll {
+    fn default() -> Self {
+        Self {
+            mass: 5.0.into(),
+            drag: 0.5.into(),
+            limbs: Default::default(),
+            active: false,
+            state_changed: false,
+            initial_velocity: Default::default(),
+        }
+    }
+}
+
+impl Ragdoll {
+    /// Hands the bones over to physics, every body starts moving with the given velocity.
+    pub fn activate(&mut self, velocity: Vector3<f32>) {
+        self.active = true;
+        self.state_changed = true;
+        self.initial_velocity = velocity;
+    }
+
+    /// Returns the bones back to animation.
+    pub fn deactivate(&mut self) {
+        self.active = false;
+        self.state_changed = true;
+    }
+
+    /// Position of the root body (hips) in world space.
+    pub fn root_position(&self, graph: &Graph) -> Option<Vector3<f32>> {
+        let limb = self.limbs.first()?;
+        graph.try_get(limb.body).map(|body| body.global_position())
+    }
+
+    fn build(&mut self, graph: &mut Graph, root: Handle<Node>) {
+        // The character must not collide with its own ragdoll.
+        for child in graph[root].children().to_vec() {
+            if let Some(collider) = graph.try_get_mut_of_type::<Collider>(child) {
+                collider.set_collision_groups(InteractionGroups::new(
+                    BitMask(u32::MAX),
+                    BitMask(!RAGDOLL_GROUP),
+                ));
+            }
+        }
+
+        let mut bodies = Vec::<Handle<Node>>::new();
+        for definition in LIMBS.iter() {
+            let bone = graph
+                .find_by_name(root, definition.bone)
+                .map(|(handle, _)| handle)
+                .unwrap_or_default();
+            let end = graph
+                .find_by_name(root, definition.end)
+                .map(|(handle, _)| handle)
+                .unwrap_or_default();
+            if bone.is_none() || end.is_none() {
+                // Keep the indices of the limbs in sync with the definitions.
+                bodies.push(Handle::NONE);
+                continue;
+            }
+
+            let (rotation, position) = graph.global_rotation_position_no_scale(bone);
+            let end_position = graph[end].global_position();
+
+            // The capsule goes from the bone to its end, it is shortened by the radius on both
+            // sides, so neighbouring capsules do not overlap.
+            let axis = rotation.inverse_transform_vector(&(end_position - position));
+            let length = axis.norm();
+            let direction = axis.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::y);
+            let offset = definition.radius.min(length * 0.5);
+            let collider = ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::capsule(
+                    direction.scale(offset),
+                    direction.scale(length - offset),
+                    definition.radius,
+                ))
+                .with_collision_groups(InteractionGroups::new(
+                    BitMask(RAGDOLL_GROUP),
+                    BitMask(u32::MAX),
+                ))
+                .build(graph);
+
+            let body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_name(format!("Ragdoll {}", definition.bone))
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(position)
+                            .with_local_rotation(rotation)
+                            .build(),
+                    )
+                    .with_children(&[collider]),
+            )
+            .with_body_type(RigidBodyType::KinematicPositionBased)
+            .with_mass(*self.mass)
+            .with_lin_damping(*self.drag)
+            .with_ang_damping(*self.drag)
+            .build(graph);
+
+            let mut joint = Handle::NONE;
+            if let Some(parent_body) = definition
+                .parent
+                .and_then(|parent| bodies.get(parent).cloned())
+                .filter(|parent_body| parent_body.is_some())
+            {
+                joint = JointBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(position)
+                            .build(),
+                    ),
+                )
+                .with_params(JointParams::BallJoint(BallJoint::default()))
+                .with_body1(parent_body)
+                .with_body2(body)
+                .with_contacts_enabled(false)
+                .build(graph);
+            }
+
+            bodies.push(body);
+            self.limbs.push(Limb {
+                bone,
+                body,
+                joint,
+                bind_position: **graph[bone].local_transform().position(),
+            });
+        }
+    }
+
+    fn apply_state(&self, graph: &mut Graph) {
+        for limb in self.limbs.iter() {
+            if let Some(body) = graph.try_get_mut_of_type::<RigidBody>(limb.body) {
+                if self.active {
+                    body.set_body_type(RigidBodyType::Dynamic);
+                    body.set_mass(*self.mass);
+                    body.set_lin_damping(*self.drag);
+                    body.set_ang_damping(*self.drag);
+                    body.set_lin_vel(self.initial_velocity);
+                    body.set_ang_vel(Default::default());
+                } else {
+                    body.set_body_type(RigidBodyType::KinematicPositionBased);
+                }
+            }
+
+            if !self.active {
+                graph[limb.bone]
+                    .local_transform_mut()
+                    .set_position(limb.bind_position);
+            }
+        }
+    }
+
+    /// Moves the bodies to the animated bones.
+    fn follow_bones(&self, graph: &mut Graph) {
+        for limb in self.limbs.iter() {
+            let (rotation, position) = graph.global_rotation_position_no_scale(limb.bone);
+            graph[limb.body]
+                .local_transform_mut()
+                .set_position(position)
+                .set_rotation(rotation);
+        }
+    }
+
+    /// Moves the bones to the simulated bodies. The bodies are not parented to anything, so their
+    /// local transform is the global one.
+    fn drive_bones(&self, graph: &mut Graph) {
+        for limb in self.limbs.iter() {
+            let body_transform = graph[limb.body].local_transform();
+            let body_position = **body_transform.position();
+            let body_rotation = **body_transform.rotation();
+
+            let parent = graph[limb.bone].parent();
+            let Some(parent_inverse) = graph[parent].global_transform().try_inverse() else {
+                continue;
+            };
+            let (parent_rotation, _) = graph.global_rotation_position_no_scale(parent);
+
+            graph[limb.bone]
+                .local_transform_mut()
+                .set_position(
+                    parent_inverse
+                        .transform_point(&Point3::from(body_position))
+                        .coords,
+                )
+                .set_rotation(parent_rotation.inverse() * body_rotation);
+
+            // Children of the bone must see its new transform.
+            graph.update_hierarchical_data_for_descendants(limb.bone);
+        }
+    }
+}
+
+impl ScriptTrait for Ragdoll {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.build(&mut ctx.scene.graph, ctx.handle);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        // The bodies are not attached to the character, so they must be removed explicitly.
+        for limb in self.limbs.iter() {
+            for node in [limb.joint, limb.body] {
+                if ctx.scene.graph.is_valid_handle(node) {
+                    ctx.scene.graph.remove_node(node);
+                }
+            }
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+
+        if std::mem::take(&mut self.state_changed) {
+            self.apply_state(graph);
+        }
+
+        if self.active {
+            self.drive_bones(graph);
+        } else {
+            self.follow_bones(graph);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Statistics of the resource manager: how many resources are loaded and how much memory the
+//! textures take.
+use fyrox::{asset::manager::ResourceManager, resource::texture::Texture};
+
+/// Iterating over every resource is not free, so the statistics are updated once per this time
+/// (in seconds).
+const UPDATE_INTERVAL: f32 = 1.0;
+
+#[derive(Default, Debug)]
+pub struct ResourceStats {
+    time_since_last_update: f32,
+    loaded: usize,
+    pending: usize,
+    failed: usize,
+    /// Total size of the pixels of loaded textures (including mips), in bytes.
+    texture_memory: usize,
+}
+
+impl ResourceStats {
+    pub fn update(&mut self, dt: f32, resource_manager: &ResourceManager) {
+        self.time_since_last_update += dt;
+        if self.time_since_last_update < UPDATE_INTERVAL {
+            return;
+        }
+        *self = Self::default();
+        for resource in resource_manager.state().resources() {
+            if resource.is_ok() {
+                self.loaded += 1;
+                if let Some(texture) = resource.try_cast::<Texture>() {
+                    self.texture_memory += texture.data_ref().data().len();
+                }
+            } else if resource.is_loading() {
+                self.pending += 1;
+            } else {
+                self.failed += 1;
+            }
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.loaded + self.pending + self.failed
+    }
+
+    pub fn text(&self) -> String {
+        let hit_rate = if self.total() > 0 {
+            self.loaded as f32 / self.total() as f32 * 100.0
+        } else {
+            0.0
+        };
+        format!(
+            "Resources: {} loaded, {} pending, {} failed (hit rate {:.1}%)\nTexture Memory: {:.1} MiB",
+            self.loaded,
+            self.pending,
+            self.failed,
+            hit_rate,
+            self.texture_memory as f32 / (1024.0 * 1024.0)
+        )
+    }
+}
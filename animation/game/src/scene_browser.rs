@@ -0,0 +1,222 @@
+//! Debug window that shows live scene hierarchy and properties of a selected node.
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle},
+    graph::BaseSceneGraph,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        inspector::{
+            editors::PropertyEditorDefinitionContainer, InspectorBuilder, InspectorContext,
+            InspectorMessage,
+        },
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        text::TextBuilder,
+        tree::{TreeBuilder, TreeRootBuilder, TreeRootMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    scene::{node::Node, Scene},
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Interval between automatic refreshes of the tree, in seconds.
+const REFRESH_INTERVAL: f32 = 5.0;
+
+#[derive(Default, Debug)]
+pub struct SceneGraphBrowser {
+    window: Handle<UiNode>,
+    tree_root: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    opened: bool,
+    refresh_timer: f32,
+    selected: Handle<Node>,
+    /// Maps tree items to the scene nodes they're representing.
+    items: HashMap<Handle<UiNode>, Handle<Node>>,
+}
+
+/// Returns the name of a type without its module path.
+fn short_type_name(node: &Node) -> &'static str {
+    let name = node.type_name();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl SceneGraphBrowser {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let tree_root;
+        let inspector;
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(700.0)
+                .with_height(500.0)
+                .with_desired_position(Vector2::new(20.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Scene Graph [F2 - Refresh]"))
+        .open(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        ScrollViewerBuilder::new(
+                            WidgetBuilder::new()
+                                .on_column(0)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_content({
+                            tree_root = TreeRootBuilder::new(WidgetBuilder::new()).build(ctx);
+                            tree_root
+                        })
+                        .build(ctx),
+                    )
+                    .with_child(
+                        ScrollViewerBuilder::new(
+                            WidgetBuilder::new()
+                                .on_column(1)
+                                .with_margin(Thickness::uniform(1.0)),
+                        )
+                        .with_content({
+                            inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+                            inspector
+                        })
+                        .build(ctx),
+                    ),
+            )
+            .add_row(Row::stretch())
+            .add_column(Column::stretch())
+            .add_column(Column::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            window,
+            tree_root,
+            inspector,
+            opened: false,
+            refresh_timer: 0.0,
+            selected: Handle::NONE,
+            items: Default::default(),
+        }
+    }
+
+    fn build_item(
+        &mut self,
+        ctx: &mut BuildContext,
+        scene: &Scene,
+        node_handle: Handle<Node>,
+    ) -> Handle<UiNode> {
+        let node = &scene.graph[node_handle];
+
+        let children = node
+            .children()
+            .iter()
+            .map(|child| self.build_item(ctx, scene, *child))
+            .collect::<Vec<_>>();
+
+        let item = TreeBuilder::new(WidgetBuilder::new())
+            .with_content(
+                TextBuilder::new(WidgetBuilder::new())
+                    .with_text(format!("{} ({})", node.name(), short_type_name(node)))
+                    .build(ctx),
+            )
+            .with_items(children)
+            .build(ctx);
+
+        self.items.insert(item, node_handle);
+
+        item
+    }
+
+    /// Returns a node that is currently selected in the tree.
+    pub fn selected(&self) -> Handle<Node> {
+        self.selected
+    }
+
+    /// Rebuilds the tree from scratch.
+    pub fn refresh(&mut self, ui: &mut UserInterface, scene: &Scene) {
+        self.items.clear();
+        self.refresh_timer = 0.0;
+
+        let root = self.build_item(&mut ui.build_ctx(), scene, scene.graph.get_root());
+
+        ui.send_message(TreeRootMessage::items(
+            self.tree_root,
+            MessageDirection::ToWidget,
+            vec![root],
+        ));
+    }
+
+    /// Opens the window if it is closed, otherwise refreshes it.
+    pub fn toggle(&mut self, ui: &mut UserInterface, scene: &Scene) {
+        if !self.opened {
+            self.opened = true;
+            ui.send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                false,
+                true,
+            ));
+        }
+
+        self.refresh(ui, scene);
+    }
+
+    pub fn update(&mut self, dt: f32, ui: &mut UserInterface, scene: &Scene) {
+        if !self.opened {
+            return;
+        }
+
+        self.refresh_timer += dt;
+        if self.refresh_timer >= REFRESH_INTERVAL {
+            self.refresh(ui, scene);
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        scene: &Scene,
+    ) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.opened = false;
+            }
+        } else if let Some(TreeRootMessage::Selected(selection)) = message.data() {
+            if message.destination() != self.tree_root {
+                return;
+            }
+
+            self.selected = selection
+                .first()
+                .and_then(|item| self.items.get(item))
+                .cloned()
+                .unwrap_or_default();
+
+            let Some(node) = scene.graph.try_get(self.selected) else {
+                return;
+            };
+
+            let context = InspectorContext::from_object(
+                node,
+                &mut ui.build_ctx(),
+                Arc::new(PropertyEditorDefinitionContainer::with_default_editors()),
+                None,
+                u64::MAX,
+                0,
+                true,
+                Default::default(),
+            );
+
+            ui.send_message(InspectorMessage::context(
+                self.inspector,
+                MessageDirection::ToWidget,
+                context,
+            ));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Statistics of the player, that are kept between the runs of the game, and a window, that
+//! shows them.
+use fyrox::{
+    core::{algebra::Vector2, log::Log, pool::Handle, visitor::prelude::*},
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+};
+
+/// Path of the file with the statistics, relative to the working directory.
+const STATS_PATH: &str = "data/stats.bin";
+
+#[derive(Visit, Default, Clone, Debug)]
+pub struct PlayerStats {
+    pub total_distance_m: f32,
+    /// The demo has no enemies, the counter is a part of the file format for the games, that
+    /// are based on it.
+    pub enemies_defeated: u32,
+    pub time_played_seconds: f32,
+}
+
+impl PlayerStats {
+    /// Loads the statistics from the file, starts from scratch if the file does not exist or is
+    /// corrupted.
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read(STATS_PATH) else {
+            return Self::default();
+        };
+
+        let mut stats = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => {
+                if let Err(err) = stats.visit("PlayerStats", &mut visitor) {
+                    Log::err(format!("Unable to read player statistics: {err:?}"));
+                    stats = Self::default();
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load player statistics: {err:?}")),
+        }
+        stats
+    }
+
+    pub fn save(&self) {
+        let mut stats = self.clone();
+        let mut visitor = Visitor::new();
+        Log::verify(stats.visit("PlayerStats", &mut visitor));
+        Log::verify(visitor.save_binary(STATS_PATH));
+    }
+
+    fn distance_text(&self) -> String {
+        if self.total_distance_m > 1000.0 {
+            format!("{:.2} km", self.total_distance_m / 1000.0)
+        } else {
+            format!("{:.0} m", self.total_distance_m)
+        }
+    }
+
+    fn time_played_text(&self) -> String {
+        let seconds = self.time_played_seconds as u32;
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct StatsWindow {
+    window: Handle<UiNode>,
+    text: Handle<UiNode>,
+    is_open: bool,
+}
+
+impl StatsWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0))).build(ctx);
+
+        let window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(220.0)
+                .with_height(100.0)
+                .with_desired_position(Vector2::new(20.0, 300.0)),
+        )
+        .with_title(WindowTitle::text("Player Statistics"))
+        // The window is closed by the same key, that opens it.
+        .can_close(false)
+        .open(false)
+        .with_content(text)
+        .build(ctx);
+
+        Self {
+            window,
+            text,
+            is_open: false,
+        }
+    }
+
+    pub fn toggle(&mut self, ui: &UserInterface) {
+        self.is_open = !self.is_open;
+        if self.is_open {
+            ui.send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                false,
+                false,
+            ));
+        } else {
+            ui.send_message(WindowMessage::close(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    pub fn update(&self, stats: &PlayerStats, ui: &UserInterface) {
+        if !self.is_open {
+            return;
+        }
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            format!(
+                "Distance Walked: {}\nEnemies Defeated: {}\nTime Played: {}",
+                stats.distance_text(),
+                stats.enemies_defeated,
+                stats.time_played_text()
+            ),
+        ));
+    }
+}
@@ -0,0 +1,499 @@
+//! Timeline - a custom widget, that shows named tracks with keyframes and a playhead. The playhead
+//! could be dragged to scrub the playback, keyframes could be selected by clicking on them. The
+//! timeline of the game shows the animations of the animation player of the player.
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, math::Rect, pool::Handle, reflect::prelude::*,
+        type_traits::prelude::*, visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    scene::{
+        animation::{Animation, AnimationPlayer},
+        graph::Graph,
+        node::Node,
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+const TRACK_HEIGHT: f32 = 20.0;
+/// Width of the column with the names of the tracks.
+const LABEL_WIDTH: f32 = 60.0;
+/// Distance from the right side of the widget to the end of the timeline.
+const PADDING: f32 = 8.0;
+/// Half of the size of a keyframe diamond.
+const KEYFRAME_SIZE: f32 = 5.0;
+const PLAYHEAD_WIDTH: f32 = 2.0;
+const BACKGROUND_COLOR: Color = Color::from_rgba(20, 20, 20, 200);
+const TRACK_COLOR: Color = Color::from_rgba(50, 50, 50, 200);
+const KEYFRAME_COLOR: Color = Color::opaque(200, 200, 200);
+const SELECTED_KEYFRAME_COLOR: Color = Color::opaque(255, 200, 0);
+const PLAYHEAD_COLOR: Color = Color::opaque(230, 60, 60);
+const TIMELINE_WIDTH: f32 = 400.0;
+/// Keys of different curves, that are closer than this (in seconds), are shown as one keyframe.
+const KEYFRAME_EPSILON: f32 = 0.001;
+
+#[derive(Default, Clone, Debug, PartialEq, Reflect, Visit)]
+pub struct TimelineTrack {
+    pub name: String,
+    /// Times of the keyframes, in seconds.
+    pub keyframes: Vec<f32>,
+    /// Length of the track, in seconds.
+    pub length: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineMessage {
+    /// Position of the playhead, in seconds. It is sent from the widget when the playhead is
+    /// dragged, and to the widget to move the playhead without any messages in response.
+    Scrub(f32),
+    /// The playhead is released after dragging.
+    ScrubFinished,
+    /// A keyframe is clicked. The index goes through the keyframes of all the tracks in order,
+    /// see [`keyframe_location`].
+    KeyframeSelected(usize),
+}
+
+impl TimelineMessage {
+    define_constructor!(
+        TimelineMessage:Scrub => fn scrub(f32), layout: false
+    );
+    define_constructor!(
+        TimelineMessage:ScrubFinished => fn scrub_finished(), layout: false
+    );
+    define_constructor!(
+        TimelineMessage:KeyframeSelected => fn keyframe_selected(usize), layout: false
+    );
+}
+
+/// Converts the index of [`TimelineMessage::KeyframeSelected`] to the index of the track and the
+/// index of the keyframe in it.
+pub fn keyframe_location(tracks: &[TimelineTrack], mut index: usize) -> Option<(usize, usize)> {
+    for (track_index, track) in tracks.iter().enumerate() {
+        if index < track.keyframes.len() {
+            return Some((track_index, index));
+        }
+        index -= track.keyframes.len();
+    }
+    None
+}
+
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "b4e7c2d9-5a13-4f86-9c0e-27d8a6f31b54")]
+struct Timeline {
+    widget: Widget,
+    tracks: Vec<TimelineTrack>,
+    /// Position of the playhead, in seconds.
+    time: f32,
+    selected: Option<usize>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    dragging: bool,
+}
+
+define_widget_deref!(Timeline);
+
+impl Timeline {
+    fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .map(|track| track.length)
+            .fold(0.0, f32::max)
+    }
+
+    /// Horizontal range of the timeline in local coordinates.
+    fn time_axis(&self) -> (f32, f32) {
+        let bounds = self.bounding_rect();
+        let begin = bounds.x() + LABEL_WIDTH;
+        (begin, (bounds.x() + bounds.w() - PADDING - begin).max(1.0))
+    }
+
+    fn time_to_x(&self, time: f32) -> f32 {
+        let (begin, width) = self.time_axis();
+        let duration = self.duration();
+        if duration > 0.0 {
+            begin + time / duration * width
+        } else {
+            begin
+        }
+    }
+
+    fn x_to_time(&self, x: f32) -> f32 {
+        let (begin, width) = self.time_axis();
+        ((x - begin) / width).clamp(0.0, 1.0) * self.duration()
+    }
+
+    /// Centers of the keyframe diamonds in the order of [`TimelineMessage::KeyframeSelected`].
+    fn keyframe_positions(&self) -> impl Iterator<Item = Vector2<f32>> + '_ {
+        let top = self.bounding_rect().y();
+        self.tracks
+            .iter()
+            .enumerate()
+            .flat_map(move |(row, track)| {
+                track.keyframes.iter().map(move |time| {
+                    Vector2::new(
+                        self.time_to_x(*time),
+                        top + (row as f32 + 0.5) * TRACK_HEIGHT,
+                    )
+                })
+            })
+    }
+
+    fn push_diamond(drawing_context: &mut DrawingContext, center: Vector2<f32>) {
+        let left = center - Vector2::new(KEYFRAME_SIZE, 0.0);
+        let right = center + Vector2::new(KEYFRAME_SIZE, 0.0);
+        drawing_context.push_triangle_filled([
+            left,
+            center - Vector2::new(0.0, KEYFRAME_SIZE),
+            right,
+        ]);
+        drawing_context.push_triangle_filled([
+            left,
+            right,
+            center + Vector2::new(0.0, KEYFRAME_SIZE),
+        ]);
+    }
+
+    fn scrub_to(&mut self, ui: &UserInterface, x: f32) {
+        let time = self.x_to_time(x);
+        if time != self.time {
+            self.time = time;
+            ui.send_message(TimelineMessage::scrub(
+                self.handle(),
+                MessageDirection::FromWidget,
+                time,
+            ));
+            self.invalidate_visual();
+        }
+    }
+}
+
+impl Control for Timeline {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.bounding_rect();
+        drawing_context.push_rect_filled(&bounds, None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(BACKGROUND_COLOR),
+            CommandTexture::None,
+            None,
+        );
+
+        // Every other track is highlighted, so it is easier to see, which keyframe belongs to
+        // which track.
+        for row in (0..self.tracks.len()).step_by(2) {
+            drawing_context.push_rect_filled(
+                &Rect::new(
+                    bounds.x(),
+                    bounds.y() + row as f32 * TRACK_HEIGHT,
+                    bounds.w(),
+                    TRACK_HEIGHT,
+                ),
+                None,
+            );
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(TRACK_COLOR),
+            CommandTexture::None,
+            None,
+        );
+
+        for (index, center) in self.keyframe_positions().enumerate() {
+            if Some(index) != self.selected {
+                Self::push_diamond(drawing_context, center);
+            }
+        }
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(KEYFRAME_COLOR),
+            CommandTexture::None,
+            None,
+        );
+        if let Some(center) = self
+            .selected
+            .and_then(|selected| self.keyframe_positions().nth(selected))
+        {
+            Self::push_diamond(drawing_context, center);
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(SELECTED_KEYFRAME_COLOR),
+                CommandTexture::None,
+                None,
+            );
+        }
+
+        let x = self.time_to_x(self.time);
+        drawing_context.push_rect_filled(
+            &Rect::new(
+                x - PLAYHEAD_WIDTH * 0.5,
+                bounds.y(),
+                PLAYHEAD_WIDTH,
+                bounds.h(),
+            ),
+            None,
+        );
+        drawing_context.push_triangle_filled([
+            Vector2::new(x - KEYFRAME_SIZE, bounds.y()),
+            Vector2::new(x + KEYFRAME_SIZE, bounds.y()),
+            Vector2::new(x, bounds.y() + KEYFRAME_SIZE),
+        ]);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(PLAYHEAD_COLOR),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() != self.handle() {
+            return;
+        }
+
+        if let Some(TimelineMessage::Scrub(time)) = message.data() {
+            if message.direction() == MessageDirection::ToWidget && !self.dragging {
+                self.time = time.clamp(0.0, self.duration());
+                self.invalidate_visual();
+            }
+            return;
+        }
+
+        let Some(msg) = message.data::<WidgetMessage>() else {
+            return;
+        };
+        match msg {
+            WidgetMessage::MouseDown { pos, .. } => {
+                let pos = self.screen_to_local(*pos);
+                if let Some(index) = self
+                    .keyframe_positions()
+                    .position(|center| (center - pos).norm() <= KEYFRAME_SIZE)
+                {
+                    self.selected = Some(index);
+                    ui.send_message(TimelineMessage::keyframe_selected(
+                        self.handle(),
+                        MessageDirection::FromWidget,
+                        index,
+                    ));
+                    self.invalidate_visual();
+                } else {
+                    self.dragging = true;
+                    ui.capture_mouse(self.handle());
+                    self.scrub_to(ui, pos.x);
+                }
+            }
+            WidgetMessage::MouseMove { pos, .. } => {
+                if self.dragging {
+                    let pos = self.screen_to_local(*pos);
+                    self.scrub_to(ui, pos.x);
+                }
+            }
+            WidgetMessage::MouseUp { .. } => {
+                if self.dragging {
+                    self.dragging = false;
+                    ui.release_mouse_capture();
+                    ui.send_message(TimelineMessage::scrub_finished(
+                        self.handle(),
+                        MessageDirection::FromWidget,
+                    ));
+                }
+            }
+            _ => return,
+        }
+        message.set_handled(true);
+    }
+}
+
+pub struct TimelineBuilder {
+    widget_builder: WidgetBuilder,
+    tracks: Vec<TimelineTrack>,
+}
+
+impl TimelineBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            tracks: Default::default(),
+        }
+    }
+
+    pub fn with_tracks(mut self, tracks: Vec<TimelineTrack>) -> Self {
+        self.tracks = tracks;
+        self
+    }
+
+    pub fn build(mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        // Names of the tracks are regular text widgets on top of the timeline.
+        for (row, track) in self.tracks.iter().enumerate() {
+            let label = TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(LABEL_WIDTH)
+                    .with_height(TRACK_HEIGHT)
+                    .with_horizontal_alignment(HorizontalAlignment::Left)
+                    .with_vertical_alignment(VerticalAlignment::Top)
+                    .with_margin(Thickness {
+                        left: 4.0,
+                        top: row as f32 * TRACK_HEIGHT,
+                        right: 0.0,
+                        bottom: 0.0,
+                    })
+                    .with_hit_test_visibility(false),
+            )
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .with_text(&track.name)
+            .build(ctx);
+            self.widget_builder = self.widget_builder.with_child(label);
+        }
+
+        let timeline = Timeline {
+            widget: self
+                .widget_builder
+                .with_height(self.tracks.len() as f32 * TRACK_HEIGHT)
+                .build(),
+            tracks: self.tracks,
+            time: 0.0,
+            selected: None,
+            dragging: false,
+        };
+
+        ctx.add_node(UiNode::new(timeline))
+    }
+}
+
+/// Times of the keys of all the curves of the animation, without duplicates.
+fn keyframe_times(animation: &Animation) -> Vec<f32> {
+    let mut times = Vec::new();
+    if let Some(tracks_data) = animation.tracks_data().as_ref() {
+        for track in tracks_data.data_ref().tracks() {
+            for curve in track.data_container().curves_ref() {
+                times.extend(curve.keys().iter().map(|key| key.location()));
+            }
+        }
+    }
+    times.sort_by(f32::total_cmp);
+    times.dedup_by(|a, b| (*a - *b).abs() < KEYFRAME_EPSILON);
+    times
+}
+
+/// Timeline in the bottom-right corner of the screen with a track for every animation of an
+/// animation player. While the playhead is dragged, the animations are paused and follow it.
+#[derive(Default, Debug)]
+pub struct AnimationTimeline {
+    timeline: Handle<UiNode>,
+    tracks: Vec<TimelineTrack>,
+    animation_player: Handle<Node>,
+    /// Speeds of the animations before the playhead was grabbed, `None` while it is not dragged.
+    saved_speeds: Option<Vec<f32>>,
+}
+
+impl AnimationTimeline {
+    pub fn new(
+        ui: &mut UserInterface,
+        graph: &Graph,
+        animation_player: Handle<Node>,
+    ) -> Option<Self> {
+        let player = graph.try_get_of_type::<AnimationPlayer>(animation_player)?;
+        let tracks = player
+            .animations()
+            .iter()
+            .map(|animation| TimelineTrack {
+                name: animation.name().to_string(),
+                keyframes: keyframe_times(animation),
+                length: animation.time_slice().end,
+            })
+            .collect::<Vec<_>>();
+        let timeline = TimelineBuilder::new(
+            WidgetBuilder::new()
+                .with_width(TIMELINE_WIDTH)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_tracks(tracks.clone())
+        .build(&mut ui.build_ctx());
+
+        Some(Self {
+            timeline,
+            tracks,
+            animation_player,
+            saved_speeds: None,
+        })
+    }
+
+    /// The playhead follows the first enabled animation, unless it is dragged. Time positions of
+    /// the animations are in seconds already, so paused animations (with zero speed) are fine.
+    pub fn update(&self, graph: &Graph, ui: &UserInterface) {
+        if self.saved_speeds.is_some() {
+            return;
+        }
+        if let Some(animation) = graph
+            .try_get_of_type::<AnimationPlayer>(self.animation_player)
+            .and_then(|player| player.animations().iter().find(|a| a.is_enabled()))
+        {
+            ui.send_message(TimelineMessage::scrub(
+                self.timeline,
+                MessageDirection::ToWidget,
+                animation.time_position(),
+            ));
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, graph: &mut Graph) {
+        if message.destination() != self.timeline
+            || message.direction() != MessageDirection::FromWidget
+        {
+            return;
+        }
+        let Some(player) = graph.try_get_mut_of_type::<AnimationPlayer>(self.animation_player)
+        else {
+            return;
+        };
+        let animations = player.animations_mut().get_value_mut_silent();
+
+        match message.data() {
+            Some(TimelineMessage::Scrub(time)) => {
+                // The animations are paused by zero speed, the state machine keeps blending them.
+                if self.saved_speeds.is_none() {
+                    self.saved_speeds = Some(animations.iter().map(|a| a.speed()).collect());
+                    for animation in animations.iter_mut() {
+                        animation.set_speed(0.0);
+                    }
+                }
+                for animation in animations.iter_mut() {
+                    let end = animation.time_slice().end;
+                    animation.set_time_position(time.min(end));
+                }
+            }
+            Some(TimelineMessage::ScrubFinished) => {
+                if let Some(speeds) = self.saved_speeds.take() {
+                    for (animation, speed) in animations.iter_mut().zip(speeds) {
+                        animation.set_speed(speed);
+                    }
+                }
+            }
+            Some(TimelineMessage::KeyframeSelected(index)) => {
+                let Some((track, keyframe)) = keyframe_location(&self.tracks, *index) else {
+                    return;
+                };
+                if let (Some(animation), Some(time)) = (
+                    animations.iter_mut().nth(track),
+                    self.tracks[track].keyframes.get(keyframe),
+                ) {
+                    animation.set_time_position(*time);
+                }
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Wireframe overlay, that shows the triangles of every visible mesh in front of the camera. The
+//! renderer has no wireframe mode for materials, so the edges of the triangles are drawn as debug
+//! lines on top of the solid meshes. Skinned meshes are skinned on CPU, so the overlay follows the
+//! animation.
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        color::Color,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{
+        camera::Camera,
+        debug::Line,
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::Surface,
+            Mesh,
+        },
+        Scene,
+    },
+};
+use std::collections::HashSet;
+
+const WIREFRAME_COLOR: Color = Color::opaque(0, 255, 0);
+
+/// Returns the positions of the vertices of the surface in world space.
+fn world_positions(surface: &Surface, world: &Matrix4<f32>, graph: &Graph) -> Vec<Vector3<f32>> {
+    let data = surface.data();
+    let data = data.data_ref();
+
+    // Bones move the vertices in world space, so the transform of the mesh is not used then.
+    let bone_matrices = surface
+        .bones()
+        .iter()
+        .map(|&bone| {
+            graph
+                .try_get(bone)
+                .map(|bone| bone.global_transform() * bone.inv_bind_pose_transform())
+                .unwrap_or_else(Matrix4::identity)
+        })
+        .collect::<Vec<_>>();
+
+    data.vertex_buffer
+        .iter()
+        .map(|vertex| {
+            let position = vertex
+                .read_3_f32(VertexAttributeUsage::Position)
+                .unwrap_or_default();
+            if bone_matrices.is_empty() {
+                return world.transform_point(&Point3::from(position)).coords;
+            }
+
+            let (Ok(weights), Ok(indices)) = (
+                vertex.read_4_f32(VertexAttributeUsage::BoneWeight),
+                vertex.read_4_u8(VertexAttributeUsage::BoneIndices),
+            ) else {
+                return world.transform_point(&Point3::from(position)).coords;
+            };
+            weights
+                .iter()
+                .zip(indices.iter())
+                .filter_map(|(weight, index)| {
+                    let matrix = bone_matrices.get(*index as usize)?;
+                    Some(
+                        matrix
+                            .transform_point(&Point3::from(position))
+                            .coords
+                            .scale(*weight),
+                    )
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Draws the edges of the triangles of all the visible meshes, that are inside the frustum of
+/// the active camera. Must be called after the lines of the scene's drawing context were cleared.
+pub fn draw_wireframe(scene: &mut Scene) {
+    let Some(frustum) = scene
+        .graph
+        .linear_iter()
+        .filter_map(|node| node.cast::<Camera>())
+        .find(|camera| camera.is_enabled())
+        .map(|camera| camera.frustum())
+    else {
+        return;
+    };
+
+    let mut lines = Vec::new();
+    for node in scene.graph.linear_iter() {
+        let Some(mesh) = node.cast::<Mesh>() else {
+            continue;
+        };
+        if !mesh.global_visibility() || !frustum.is_intersects_aabb(&mesh.world_bounding_box()) {
+            continue;
+        }
+
+        for surface in mesh.surfaces() {
+            let positions = world_positions(surface, &mesh.global_transform(), &scene.graph);
+            let data = surface.data();
+            let data = data.data_ref();
+
+            // Neighbouring triangles share edges, every edge is drawn once.
+            let mut edges = HashSet::new();
+            for triangle in data.geometry_buffer.iter() {
+                let [a, b, c] = triangle.0;
+                for (begin, end) in [(a, b), (b, c), (c, a)] {
+                    if !edges.insert((begin.min(end), begin.max(end))) {
+                        continue;
+                    }
+                    if let (Some(begin), Some(end)) =
+                        (positions.get(begin as usize), positions.get(end as usize))
+                    {
+                        lines.push(Line {
+                            begin: *begin,
+                            end: *end,
+                            color: WIREFRAME_COLOR,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    for line in lines {
+        scene.drawing_context.add_line(line);
+    }
+}
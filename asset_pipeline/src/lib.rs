@@ -0,0 +1,142 @@
+//! Platform-specific asset pipeline - transcodes PNG textures of a demo to a compressed format,
+//! that suits the target platform. It is called from build scripts of the game crates:
+//!
+//! - `x86_64` Windows and Linux - DDS with BC1 (DXT1) or BC3 (DXT5) compression, if the texture has
+//!   transparent pixels.
+//!
+//! Other targets use the textures as is. The texture loader of the engine reads DDS and the common
+//! image formats only, so there's no container for ASTC textures of `aarch64` Android and iOS (and
+//! the GPUs of the phones do not support BC formats), and WebP for `wasm32` is not supported
+//! either.
+//!
+//! Textures with `NoCompression` in their import options (pixel art) are used as is too. Data
+//! textures, such as noise, must not be passed to the pipeline at all - block compression breaks
+//! them. The import options of a transcoded texture are copied next to it, so it keeps
+//! its filtering and wrapping.
+//!
+//! Transcoded textures are written to `compressed_assets/<format>` in the output directory of the
+//! build script with the same relative paths. The directory, the extension and the list of the
+//! transcoded textures are passed to the game crate in `ASSET_PATH`, `ASSET_EXTENSION` and
+//! `TRANSCODED_ASSETS` environment variables.
+use image::RgbaImage;
+use image_dds::{ImageFormat, Mipmaps, Quality};
+use std::{
+    env,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+/// Directory inside `OUT_DIR`, where the transcoded textures are stored.
+const OUTPUT_DIR: &str = "compressed_assets";
+
+/// Extension of the transcoded textures for the target, it is also the name of their directory.
+fn extension(arch: &str, os: &str) -> Option<&'static str> {
+    match (arch, os) {
+        ("x86_64", "windows" | "linux") => Some("dds"),
+        _ => None,
+    }
+}
+
+fn encode_dds(image: RgbaImage, destination: &Path) -> Result<(), String> {
+    // BC1 has 1-bit alpha at most, textures with transparency need BC3.
+    let format = if image.pixels().all(|pixel| pixel[3] == u8::MAX) {
+        ImageFormat::BC1RgbaUnorm
+    } else {
+        ImageFormat::BC3RgbaUnorm
+    };
+    let dds =
+        image_dds::dds_from_image(&image, format, Quality::Normal, Mipmaps::GeneratedAutomatic)
+            .map_err(|err| err.to_string())?;
+    let mut file = BufWriter::new(File::create(destination).map_err(|err| err.to_string())?);
+    dds.write(&mut file).map_err(|err| err.to_string())
+}
+
+fn transcode(source: &Path, destination: &Path) -> Result<(), String> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    encode_dds(
+        image::open(source)
+            .map_err(|err| err.to_string())?
+            .into_rgba8(),
+        destination,
+    )
+}
+
+/// Returns `true` if the import options of the texture forbid its compression.
+fn is_uncompressed(options: &Path) -> bool {
+    fs::read_to_string(options).is_ok_and(|options| {
+        options
+            .lines()
+            .any(|line| line.trim().trim_end_matches(',') == "compression: NoCompression")
+    })
+}
+
+/// Returns `true` if the transcoded texture is older than the source one or does not exist.
+fn is_outdated(source: &Path, destination: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    match (modified(source), modified(destination)) {
+        (Ok(source), Ok(destination)) => source > destination,
+        _ => true,
+    }
+}
+
+/// Transcodes the given textures (paths are relative to the root of the demo, for example
+/// `data/tiles.png`) for the target platform of the build. Must be called from a build script.
+/// Only the textures, that are loaded from code, should be listed - textures of scenes and models
+/// are referenced by the resources themselves, so their transcoded copies would never be used.
+pub fn run(textures: &[&str]) {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    // Game crates are in `game` directory of a demo.
+    let root = manifest_dir.parent().unwrap();
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+
+    let Some(extension) = extension(&arch, &os) else {
+        println!("cargo:rustc-env=ASSET_PATH=data");
+        println!("cargo:rustc-env=ASSET_EXTENSION=png");
+        println!("cargo:rustc-env=TRANSCODED_ASSETS=");
+        return;
+    };
+
+    let output = PathBuf::from(env::var("OUT_DIR").unwrap())
+        .join(OUTPUT_DIR)
+        .join(extension);
+    let mut transcoded = Vec::new();
+    for texture in textures {
+        let source = root.join(texture);
+        let options = PathBuf::from(format!("{}.options", source.display()));
+        println!("cargo:rerun-if-changed={}", source.display());
+        println!("cargo:rerun-if-changed={}", options.display());
+
+        if is_uncompressed(&options) {
+            continue;
+        }
+
+        let relative = Path::new(texture).strip_prefix("data").unwrap_or_else(|_| {
+            panic!("The texture {texture} must be in data directory.");
+        });
+        let destination = output.join(relative).with_extension(extension);
+        if is_outdated(&source, &destination) {
+            if let Err(err) = transcode(&source, &destination) {
+                panic!("Unable to transcode {texture}. Reason: {err}");
+            }
+        }
+        if options.exists() {
+            let destination_options = format!("{}.options", destination.display());
+            if let Err(err) = fs::copy(&options, destination_options) {
+                panic!("Unable to copy import options of {texture}. Reason: {err:?}");
+            }
+        }
+        transcoded.push(*texture);
+    }
+
+    // Paths of the resources use forward slashes on every platform.
+    println!(
+        "cargo:rustc-env=ASSET_PATH={}",
+        output.to_string_lossy().replace('\\', "/")
+    );
+    println!("cargo:rustc-env=ASSET_EXTENSION={extension}");
+    println!("cargo:rustc-env=TRANSCODED_ASSETS={}", transcoded.join(";"));
+}
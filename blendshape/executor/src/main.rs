@@ -1,9 +1,31 @@
 //! Executor with your game connected to it as a plugin.
 use blendshape::Game;
-use fyrox::engine::executor::Executor;
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
+use fyrox::{
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+};
+
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
 
 fn main() {
-    let mut executor = Executor::new();
+    // Frames are paced by `FramePacer`, which needs vsync to be disabled.
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            vsync: false,
+            ..Default::default()
+        },
+    );
     executor.add_plugin(Game::default());
+    #[cfg(feature = "record")]
+    if let Some(recorder) = Recorder::from_args() {
+        executor.add_plugin(recorder);
+    }
+    executor.add_plugin(FramePacer::default());
     executor.run()
 }
@@ -1,9 +1,11 @@
 //! Game project.
+use common_scripts::Throttle;
 use fyrox::graph::SceneGraph;
 use fyrox::keyboard::PhysicalKey;
 use fyrox::{
     core::{
         algebra::{UnitQuaternion, Vector2, Vector3},
+        log::Log,
         pool::Handle,
         reflect::prelude::*,
         visitor::prelude::*,
@@ -11,6 +13,7 @@ use fyrox::{
     engine::GraphicsContext,
     event::{ElementState, Event, WindowEvent},
     gui::{
+        button::{ButtonBuilder, ButtonContent, ButtonMessage},
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, UiMessage},
         scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
@@ -18,20 +21,31 @@ use fyrox::{
         text::{TextBuilder, TextMessage},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowTitle},
-        UiNode,
+        BuildContext, Thickness, UiNode, VerticalAlignment,
     },
     keyboard::KeyCode,
     plugin::{Plugin, PluginContext},
-    scene::{node::Node, Scene},
+    scene::{
+        animation::{Animation, AnimationPlayer},
+        node::Node,
+        Scene,
+    },
 };
+use presets::PresetPanel;
 use std::{collections::BTreeSet, path::Path};
 
+mod presets;
+#[cfg(target_arch = "wasm32")]
+mod touch;
+
 #[derive(Default, Debug, Reflect, Visit)]
 struct InputController {
     rotate_left: bool,
     rotate_right: bool,
 }
 
+const SCENE_NAME: &str = "scene";
+
 #[derive(Debug, Visit, Reflect, Default)]
 pub struct Game {
     scene: Handle<Scene>,
@@ -42,6 +56,357 @@ pub struct Game {
     #[visit(skip)]
     #[reflect(hidden)]
     sliders: Vec<(String, Handle<UiNode>)>,
+    animation_player: Handle<Node>,
+    playing: bool,
+    timeline: Handle<UiNode>,
+    play_pause: Handle<UiNode>,
+    speed: Handle<UiNode>,
+    head: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    preview: ExpressionPreview,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    presets: PresetPanel,
+    #[cfg(target_arch = "wasm32")]
+    #[visit(skip)]
+    #[reflect(hidden)]
+    touch_camera: touch::TouchCamera,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// A set of blend shape weights, blend shapes that are not listed have zero weight.
+struct ExpressionPreset {
+    name: &'static str,
+    weights: &'static [(&'static str, f32)],
+}
+
+const PRESETS: &[ExpressionPreset] = &[
+    ExpressionPreset {
+        name: "Neutral",
+        weights: &[],
+    },
+    ExpressionPreset {
+        name: "Smile",
+        weights: &[
+            ("MouthSmile_L", 100.0),
+            ("MouthSmile_R", 100.0),
+            ("CheekSquint_L", 40.0),
+            ("CheekSquint_R", 40.0),
+        ],
+    },
+    ExpressionPreset {
+        name: "Frown",
+        weights: &[
+            ("MouthFrown_L", 100.0),
+            ("MouthFrown_R", 100.0),
+            ("BrowsD_L", 80.0),
+            ("BrowsD_R", 80.0),
+        ],
+    },
+    ExpressionPreset {
+        name: "Surprised",
+        weights: &[
+            ("BrowsU_C", 100.0),
+            ("BrowsU_L", 100.0),
+            ("BrowsU_R", 100.0),
+            ("EyeOpen_L", 80.0),
+            ("EyeOpen_R", 80.0),
+            ("JawOpen", 60.0),
+        ],
+    },
+];
+
+/// How long each expression stays on the face after the transition, in seconds.
+const HOLD_TIME: f32 = 0.5;
+
+/// Cycles through expression presets with smooth transitions between them.
+#[derive(Default, Debug)]
+struct ExpressionPreview {
+    play_pause: Handle<UiNode>,
+    transition: Handle<UiNode>,
+    expression_text: Handle<UiNode>,
+    export: Handle<UiNode>,
+    playing: bool,
+    preset: usize,
+    timer: f32,
+    transition_duration: f32,
+    /// Current weights, in the same order as sliders.
+    weights: Vec<f32>,
+}
+
+// Marks messages that were sent to sync the UI with the animation, so they won't be treated as
+// user input.
+const SYNC_FLAG: u64 = 1;
+
+fn make_labeled_row(
+    ctx: &mut BuildContext,
+    row: usize,
+    label: &str,
+    content: Handle<UiNode>,
+) -> [Handle<UiNode>; 2] {
+    [
+        TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text(label)
+        .build(ctx),
+        content,
+    ]
+}
+
+fn short_blend_shape_name(name: &str) -> &str {
+    name.strip_prefix("ExpressionBlendshapes.").unwrap_or(name)
+}
+
+fn set_blend_shape_weight(scene: &mut Scene, head: Handle<Node>, name: &str, weight: f32) {
+    if let Some(head) = scene.graph.try_get_mut(head) {
+        for blend_shape in head.as_mesh_mut().blend_shapes_mut().iter_mut() {
+            if blend_shape.name == name {
+                blend_shape.weight = weight;
+            }
+        }
+    }
+}
+
+impl ExpressionPreview {
+    fn new(ctx: &mut BuildContext, blend_shape_count: usize) -> Self {
+        let transition_duration = 1.0;
+
+        let play_pause = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Preview Timeline")
+        .build(ctx);
+
+        let transition = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.2)
+        .with_max(3.0)
+        .with_step(0.1)
+        .with_value(transition_duration)
+        .show_value(true)
+        .build(ctx);
+
+        let expression_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .on_column(1)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text(PRESETS[0].name)
+        .build(ctx);
+
+        let export = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(3)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Export To Log")
+        .build(ctx);
+
+        let mut children = Vec::new();
+        children.extend(make_labeled_row(ctx, 0, "Timeline", play_pause));
+        children.extend(make_labeled_row(ctx, 1, "Transition", transition));
+        children.extend(make_labeled_row(ctx, 2, "Expression", expression_text));
+        children.extend(make_labeled_row(ctx, 3, "Weights", export));
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(150.0)
+                .with_desired_position(Vector2::new(260.0, 180.0)),
+        )
+        .with_title(WindowTitle::text("Expression Preview"))
+        .with_content(
+            GridBuilder::new(WidgetBuilder::new().with_children(children))
+                .add_column(Column::strict(70.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            play_pause,
+            transition,
+            expression_text,
+            export,
+            playing: false,
+            preset: 0,
+            timer: 0.0,
+            transition_duration,
+            weights: vec![0.0; blend_shape_count],
+        }
+    }
+
+    fn target_weight(&self, name: &str) -> f32 {
+        PRESETS[self.preset]
+            .weights
+            .iter()
+            .find_map(|(preset_name, weight)| {
+                (*preset_name == short_blend_shape_name(name)).then_some(*weight)
+            })
+            .unwrap_or_default()
+    }
+
+    fn export_to_json(&self, sliders: &[(String, Handle<UiNode>)]) -> String {
+        let entries = sliders
+            .iter()
+            .zip(self.weights.iter())
+            .map(|((name, _), weight)| format!("  \"{}\": {}", name, weight))
+            .collect::<Vec<_>>();
+        format!("{{\n{}\n}}", entries.join(",\n"))
+    }
+}
+
+impl Game {
+    fn update_preview(&mut self, context: &mut PluginContext) {
+        let preview = &mut self.preview;
+        if !preview.playing {
+            return;
+        }
+
+        preview.timer += context.dt;
+        if preview.timer >= preview.transition_duration + HOLD_TIME {
+            preview.timer = 0.0;
+            preview.preset = (preview.preset + 1) % PRESETS.len();
+            context.user_interfaces.first().send_message(TextMessage::text(
+                preview.expression_text,
+                MessageDirection::ToWidget,
+                PRESETS[preview.preset].name.to_owned(),
+            ));
+        }
+
+        // The closer the weight to its target, the slower it moves. The speed is chosen so
+        // the transition is almost finished at the end of the transition duration.
+        let speed = 4.0 / preview.transition_duration;
+        let scene = &mut context.scenes[self.scene];
+        for (index, (name, slider)) in self.sliders.iter().enumerate() {
+            let target = preview.target_weight(name);
+            let current = preview.weights[index];
+            let distance = (current - target).abs();
+            let step = (distance * context.dt * speed).min(distance);
+            let weight = if current < target {
+                current + step
+            } else {
+                current - step
+            };
+            preview.weights[index] = weight;
+
+            set_blend_shape_weight(scene, self.head, name, weight);
+
+            let mut message =
+                ScrollBarMessage::value(*slider, MessageDirection::ToWidget, weight);
+            message.flags = SYNC_FLAG;
+            context.user_interfaces.first().send_message(message);
+        }
+    }
+
+    /// Blends the weights towards the loaded preset and syncs the blend shapes and the sliders.
+    fn update_presets(&mut self, context: &mut PluginContext) {
+        if !self.presets.update(context.dt, &mut self.preview.weights) {
+            return;
+        }
+
+        let scene = &mut context.scenes[self.scene];
+        for ((name, slider), weight) in self.sliders.iter().zip(self.preview.weights.iter()) {
+            set_blend_shape_weight(scene, self.head, name, *weight);
+
+            let mut message = ScrollBarMessage::value(*slider, MessageDirection::ToWidget, *weight);
+            message.flags = SYNC_FLAG;
+            context.user_interfaces.first().send_message(message);
+        }
+    }
+
+    fn for_each_animation<F>(&self, scene: &mut Scene, mut func: F)
+    where
+        F: FnMut(&mut Animation),
+    {
+        if let Some(animation_player) = scene
+            .graph
+            .try_get_mut(self.animation_player)
+            .and_then(|node| node.cast_mut::<AnimationPlayer>())
+        {
+            for animation in animation_player.animations_mut().get_value_mut_silent().iter_mut() {
+                func(animation)
+            }
+        }
+    }
+
+    fn build_animation_window(&mut self, ctx: &mut BuildContext, duration: f32) {
+        self.timeline = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.0)
+        .with_max(duration)
+        .with_step(0.01)
+        .show_value(true)
+        .build(ctx);
+
+        self.play_pause = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Pause")
+        .build(ctx);
+
+        self.speed = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.1)
+        .with_max(3.0)
+        .with_step(0.1)
+        .with_value(1.0)
+        .show_value(true)
+        .build(ctx);
+
+        let mut children = Vec::new();
+        children.extend(make_labeled_row(ctx, 0, "Time", self.timeline));
+        children.extend(make_labeled_row(ctx, 1, "Playback", self.play_pause));
+        children.extend(make_labeled_row(ctx, 2, "Speed", self.speed));
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(120.0)
+                .with_desired_position(Vector2::new(260.0, 50.0)),
+        )
+        .with_title(WindowTitle::text("Animation"))
+        .with_content(
+            GridBuilder::new(WidgetBuilder::new().with_children(children))
+                .add_column(Column::strict(70.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .build(ctx),
+        )
+        .build(ctx);
+    }
 }
 
 impl Plugin for Game {
@@ -56,6 +421,10 @@ impl Plugin for Game {
     }
 
     fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
         if let Some(scene) = context.scenes.try_get_mut(self.scene) {
             // Rotate model according to input controller state
             if self.input_controller.rotate_left {
@@ -71,16 +440,38 @@ impl Plugin for Game {
                     self.model_angle,
                 ));
 
+            #[cfg(target_arch = "wasm32")]
+            self.touch_camera.update(scene);
+
             if let GraphicsContext::Initialized(ref graphics_context) = context.graphics_context {
                 context.user_interfaces.first().send_message(TextMessage::text(
                     self.debug_text,
                     MessageDirection::ToWidget,
                     format!(
-                        "Example - Blend Shapes\nUse [A][D] keys to rotate the model and sliders to select facial expression.\nFPS: {}",
-                        graphics_context.renderer.get_statistics().frames_per_second
+                        "Example - Blend Shapes\nUse [A][D] keys to rotate the model and sliders to select facial expression.\nFPS: {}\n{}",
+                        graphics_context.renderer.get_statistics().frames_per_second,
+                        tracked_alloc::peak_ram_text()
                     ),
                 ));
             }
+
+            if self.playing {
+                let mut time = None;
+                self.for_each_animation(scene, |animation| {
+                    time.get_or_insert(animation.time_position());
+                });
+                if let Some(time) = time {
+                    let mut message =
+                        ScrollBarMessage::value(self.timeline, MessageDirection::ToWidget, time);
+                    message.flags = SYNC_FLAG;
+                    context.user_interfaces.first().send_message(message);
+                }
+            }
+        }
+
+        if self.scene.is_some() {
+            self.update_preview(context);
+            self.update_presets(context);
         }
     }
 
@@ -102,23 +493,86 @@ impl Plugin for Game {
                 }
             }
         }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Event::WindowEvent {
+            event: WindowEvent::Touch(touch),
+            ..
+        } = event
+        {
+            self.touch_camera.handle_touch(touch);
+        }
     }
 
     fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let ui = context.user_interfaces.first_mut();
+        if self
+            .presets
+            .handle_ui_message(message, ui, &self.preview.weights)
+            && self.preview.playing
+        {
+            // The preview would override the weights of the preset.
+            self.preview.playing = false;
+            ui.send_message(ButtonMessage::content(
+                self.preview.play_pause,
+                MessageDirection::ToWidget,
+                ButtonContent::text("Resume Preview"),
+            ));
+        }
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.play_pause {
+                self.playing = !self.playing;
+                let playing = self.playing;
+                self.for_each_animation(&mut context.scenes[self.scene], |animation| {
+                    animation.set_enabled(playing);
+                });
+                context.user_interfaces.first().send_message(ButtonMessage::content(
+                    self.play_pause,
+                    MessageDirection::ToWidget,
+                    ButtonContent::text(if playing { "Pause" } else { "Play" }),
+                ));
+            } else if message.destination() == self.preview.play_pause {
+                self.preview.playing = !self.preview.playing;
+                context.user_interfaces.first().send_message(ButtonMessage::content(
+                    self.preview.play_pause,
+                    MessageDirection::ToWidget,
+                    ButtonContent::text(if self.preview.playing {
+                        "Pause Preview"
+                    } else {
+                        "Resume Preview"
+                    }),
+                ));
+            } else if message.destination() == self.preview.export {
+                Log::info(self.preview.export_to_json(&self.sliders));
+            }
+        }
+
         if let Some(ScrollBarMessage::Value(value)) = message.data() {
-            if message.direction() == MessageDirection::FromWidget {
-                for (name, slider) in self.sliders.iter() {
+            if message.direction() == MessageDirection::FromWidget && message.flags != SYNC_FLAG
+            {
+                if message.destination() == self.timeline {
+                    self.for_each_animation(&mut context.scenes[self.scene], |animation| {
+                        animation.set_time_position(*value);
+                    });
+                } else if message.destination() == self.speed {
+                    self.for_each_animation(&mut context.scenes[self.scene], |animation| {
+                        animation.set_speed(*value);
+                    });
+                } else if message.destination() == self.preview.transition {
+                    self.preview.transition_duration = *value;
+                }
+
+                for (index, (name, slider)) in self.sliders.iter().enumerate() {
                     if message.destination() == *slider {
-                        let scene = &mut context.scenes[self.scene];
-                        let sphere = scene.graph.find_by_name_from_root("Head_Mesh").unwrap().0;
-                        for blend_shape in scene.graph[sphere]
-                            .as_mesh_mut()
-                            .blend_shapes_mut()
-                            .iter_mut()
-                        {
-                            if &blend_shape.name == name {
-                                blend_shape.weight = *value;
-                            }
+                        set_blend_shape_weight(
+                            &mut context.scenes[self.scene],
+                            self.head,
+                            name,
+                            *value,
+                        );
+                        if let Some(weight) = self.preview.weights.get_mut(index) {
+                            *weight = *value;
                         }
                     }
                 }
@@ -137,6 +591,7 @@ impl Plugin for Game {
         let scene = &mut context.scenes[scene];
 
         let head = scene.graph.find_by_name_from_root("Head_Mesh").unwrap().0;
+        self.head = head;
         let blend_shape = scene.graph[head].as_mesh_mut();
 
         let mut blend_shape_names = BTreeSet::new();
@@ -156,10 +611,7 @@ impl Plugin for Game {
         let mut sliders = Vec::new();
 
         for (row, blend_shape_name) in blend_shape_names.iter().enumerate() {
-            let short_name = blend_shape_name
-                .strip_prefix("ExpressionBlendshapes.")
-                .map(|n| n.to_owned())
-                .unwrap_or_else(|| blend_shape_name.clone());
+            let short_name = short_blend_shape_name(blend_shape_name);
 
             let name = TextBuilder::new(WidgetBuilder::new().on_row(row))
                 .with_text(short_name)
@@ -174,28 +626,40 @@ impl Plugin for Game {
             sliders.push((blend_shape_name.clone(), slider));
         }
 
+        self.presets = PresetPanel::new(ctx, 0);
+
         WindowBuilder::new(
             WidgetBuilder::new()
                 .with_width(250.0)
-                .with_height(400.0)
+                .with_height(550.0)
                 .with_desired_position(Vector2::new(5.0, 50.0)),
         )
         .with_title(WindowTitle::text("Blend Shapes"))
         .with_content(
-            ScrollViewerBuilder::new(WidgetBuilder::new())
-                .with_content(
-                    GridBuilder::new(WidgetBuilder::new().with_children(children))
-                        .add_column(Column::auto())
-                        .add_column(Column::stretch())
-                        .add_rows(
-                            blend_shape_names
-                                .iter()
-                                .map(|_| Row::strict(20.0))
-                                .collect(),
-                        )
-                        .build(ctx),
-                )
-                .build(ctx),
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.presets.panel)
+                    .with_child(
+                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                            .with_content(
+                                GridBuilder::new(WidgetBuilder::new().with_children(children))
+                                    .add_column(Column::auto())
+                                    .add_column(Column::stretch())
+                                    .add_rows(
+                                        blend_shape_names
+                                            .iter()
+                                            .map(|_| Row::strict(20.0))
+                                            .collect(),
+                                    )
+                                    .build(ctx),
+                            )
+                            .build(ctx),
+                    ),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .build(ctx),
         )
         .build(ctx);
 
@@ -205,5 +669,38 @@ impl Plugin for Game {
             .map(|(h, _)| h)
             .unwrap_or_default();
         self.sliders = sliders;
+
+        self.animation_player = scene
+            .graph
+            .find(self.model_handle, &mut |node| {
+                node.cast::<AnimationPlayer>().is_some()
+            })
+            .map(|(h, _)| h)
+            .unwrap_or_default();
+        self.playing = true;
+
+        let mut duration = 0.0f32;
+        self.for_each_animation(scene, |animation| {
+            duration = duration.max(animation.time_slice().end);
+        });
+        self.build_animation_window(ctx, duration);
+
+        self.preview = ExpressionPreview::new(ctx, self.sliders.len());
+
+        #[cfg(target_arch = "wasm32")]
+        self.touch_camera.attach(scene, self.head);
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
     }
 }
@@ -0,0 +1,286 @@
+//! Named sets of blend shape weights (facial expressions), that are saved between runs. Loading of
+//! a preset blends the weights from their current values to the preset values.
+use fyrox::{
+    core::{log::Log, math::lerpf, pool::Handle, visitor::prelude::*},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBox, TextBoxBuilder},
+        widget::WidgetBuilder,
+        BuildContext, Orientation, Thickness, UiNode, UserInterface,
+    },
+};
+use std::collections::HashMap;
+
+const PRESETS_PATH: &str = "data/presets.bin";
+/// Duration of the blending from the current weights to the weights of a loaded preset, in
+/// seconds.
+const BLEND_DURATION: f32 = 0.5;
+
+/// Maps names of the presets to the weights of the blend shapes, in the same order as sliders.
+#[derive(Visit, Default, Debug, Clone)]
+pub struct PresetManager {
+    presets: HashMap<String, Vec<f32>>,
+}
+
+impl PresetManager {
+    /// Loads the presets from the file, falls back to no presets if the file does not exist or is
+    /// corrupted.
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read(PRESETS_PATH) else {
+            return Self::default();
+        };
+
+        let mut manager = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => {
+                if let Err(err) = manager.visit("PresetManager", &mut visitor) {
+                    Log::err(format!("Unable to read expression presets: {err:?}"));
+                    manager = Self::default();
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load expression presets: {err:?}")),
+        }
+        manager
+    }
+
+    pub fn save(&self) {
+        let mut manager = self.clone();
+        let mut visitor = Visitor::new();
+        Log::verify(manager.visit("PresetManager", &mut visitor));
+        Log::verify(visitor.save_binary(PRESETS_PATH));
+    }
+
+    /// Names of the presets in alphabetical order.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.presets.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[f32]> {
+        self.presets.get(name).map(|weights| weights.as_slice())
+    }
+
+    pub fn insert(&mut self, name: String, weights: Vec<f32>) {
+        self.presets.insert(name, weights);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+}
+
+#[derive(Debug)]
+struct Blend {
+    from: Vec<f32>,
+    to: Vec<f32>,
+    elapsed: f32,
+}
+
+/// Controls of the presets - name of a preset, buttons and the list of the saved presets.
+#[derive(Default, Debug)]
+pub struct PresetPanel {
+    pub panel: Handle<UiNode>,
+    name: Handle<UiNode>,
+    save: Handle<UiNode>,
+    load: Handle<UiNode>,
+    delete: Handle<UiNode>,
+    list: Handle<UiNode>,
+    manager: PresetManager,
+    /// Names of the presets, in the same order as the items of the list.
+    names: Vec<String>,
+    selected: Option<usize>,
+    blend: Option<Blend>,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_width(70.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl PresetPanel {
+    pub fn new(ctx: &mut BuildContext, row: usize) -> Self {
+        let name = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_height(22.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_text("Preset")
+        .build(ctx);
+        let save = make_button(ctx, "Save");
+        let load = make_button(ctx, "Load");
+        let delete = make_button(ctx, "Delete");
+        let buttons = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_child(save)
+                .with_child(load)
+                .with_child(delete),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+        let manager = PresetManager::load();
+        let names = manager.names();
+        let items = names.iter().map(|name| make_item(ctx, name)).collect();
+        let list = ListViewBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_items(items)
+        .build(ctx);
+
+        let panel = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .with_child(name)
+                .with_child(buttons)
+                .with_child(list),
+        )
+        .add_column(Column::stretch())
+        .add_row(Row::strict(26.0))
+        .add_row(Row::strict(26.0))
+        .add_row(Row::strict(90.0))
+        .build(ctx);
+
+        Self {
+            panel,
+            name,
+            save,
+            load,
+            delete,
+            list,
+            manager,
+            names,
+            selected: None,
+            blend: None,
+        }
+    }
+
+    fn sync_list(&mut self, ui: &mut UserInterface) {
+        self.names = self.manager.names();
+        self.selected = None;
+        let items = self
+            .names
+            .iter()
+            .map(|name| make_item(&mut ui.build_ctx(), name))
+            .collect();
+        ui.send_message(ListViewMessage::items(
+            self.list,
+            MessageDirection::ToWidget,
+            items,
+        ));
+    }
+
+    /// Name of the selected preset or the typed name, if no preset is selected.
+    fn current_name(&self, ui: &UserInterface) -> Option<String> {
+        if let Some(name) = self.selected.and_then(|index| self.names.get(index)) {
+            return Some(name.clone());
+        }
+        let name = ui
+            .try_get_of_type::<TextBox>(self.name)
+            .map(|text_box| text_box.text())?;
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    /// Handles the controls of the panel. `weights` are the current weights of the blend shapes.
+    /// Returns `true`, when a preset starts blending in.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        weights: &[f32],
+    ) -> bool {
+        if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.selected = *selection;
+                if let Some(name) = (*selection).and_then(|index| self.names.get(index)) {
+                    ui.send_message(TextMessage::text(
+                        self.name,
+                        MessageDirection::ToWidget,
+                        name.clone(),
+                    ));
+                }
+            }
+        }
+
+        let Some(ButtonMessage::Click) = message.data() else {
+            return false;
+        };
+        let destination = message.destination();
+        if destination == self.save {
+            // Always saves under the typed name, so a selected preset could be copied.
+            let name = ui
+                .try_get_of_type::<TextBox>(self.name)
+                .map(|text_box| text_box.text())
+                .unwrap_or_default();
+            let name = name.trim();
+            if !name.is_empty() {
+                self.manager.insert(name.to_string(), weights.to_vec());
+                self.manager.save();
+                self.sync_list(ui);
+            }
+        } else if destination == self.load {
+            let Some(name) = self.current_name(ui) else {
+                return false;
+            };
+            if let Some(preset) = self.manager.get(&name) {
+                self.blend = Some(Blend {
+                    from: weights.to_vec(),
+                    to: preset.to_vec(),
+                    elapsed: 0.0,
+                });
+                return true;
+            }
+        } else if destination == self.delete {
+            if let Some(name) = self.current_name(ui) {
+                if self.manager.remove(&name) {
+                    self.manager.save();
+                    self.sync_list(ui);
+                }
+            }
+        }
+        false
+    }
+
+    /// Moves the weights towards the loaded preset. Returns `true` if the weights were changed.
+    pub fn update(&mut self, dt: f32, weights: &mut [f32]) -> bool {
+        let Some(blend) = self.blend.as_mut() else {
+            return false;
+        };
+        blend.elapsed += dt;
+        let t = (blend.elapsed / BLEND_DURATION).min(1.0);
+        // Weights, that are missing in the preset (if the model has changed), are left as is.
+        for ((weight, from), to) in weights
+            .iter_mut()
+            .zip(blend.from.iter())
+            .zip(blend.to.iter())
+        {
+            *weight = lerpf(*from, *to, t);
+        }
+        if t >= 1.0 {
+            self.blend = None;
+        }
+        true
+    }
+}
+
+fn make_item(ctx: &mut BuildContext, name: &str) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_text(name)
+        .build(ctx)
+}
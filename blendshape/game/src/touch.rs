@@ -0,0 +1,126 @@
+//! Touch controls of the camera for the web version of the demo: dragging with one finger orbits
+//! the camera around the model, pinching with two fingers zooms it.
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        pool::Handle,
+    },
+    event::{Touch, TouchPhase},
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{camera::Camera, node::Node, Scene},
+};
+
+/// Angle (in radians) per pixel of finger movement.
+const ORBIT_SPEED: f32 = 0.005;
+/// Limits of the pitch (about 80 degrees), so the camera never flips over the model.
+const MIN_PITCH: f32 = -1.4;
+const MAX_PITCH: f32 = 1.4;
+const MIN_DISTANCE: f32 = 0.5;
+const MAX_DISTANCE: f32 = 10.0;
+
+#[derive(Default, Debug)]
+pub struct TouchCamera {
+    camera: Handle<Node>,
+    target: Vector3<f32>,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    /// The camera is left as is until the first gesture.
+    controlled: bool,
+    /// Ids and positions of active touches, only the first two are used.
+    touches: Vec<(u64, Vector2<f32>)>,
+}
+
+impl TouchCamera {
+    /// Starts orbiting around the given node, the initial orbit is defined by the current
+    /// position of the first enabled camera of the scene.
+    pub fn attach(&mut self, scene: &mut Scene, target: Handle<Node>) {
+        scene.graph.update_hierarchical_data();
+
+        let Some(target) = scene.graph.try_get(target) else {
+            return;
+        };
+        self.target = target.world_bounding_box().center();
+
+        let Some((camera, camera_ref)) = scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.cast::<Camera>().map_or(false, |c| c.is_enabled()))
+        else {
+            return;
+        };
+        self.camera = camera;
+
+        let direction = self.target - camera_ref.global_position();
+        self.distance = direction.norm().clamp(MIN_DISTANCE, MAX_DISTANCE);
+        if let Some(direction) = direction.try_normalize(f32::EPSILON) {
+            self.yaw = direction.x.atan2(direction.z);
+            self.pitch = (-direction.y).asin().clamp(MIN_PITCH, MAX_PITCH);
+        }
+    }
+
+    pub fn handle_touch(&mut self, touch: &Touch) {
+        let location = Vector2::new(touch.location.x as f32, touch.location.y as f32);
+        let index = self.touches.iter().position(|(id, _)| *id == touch.id);
+
+        match touch.phase {
+            TouchPhase::Started => {
+                if index.is_none() {
+                    self.touches.push((touch.id, location));
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if let Some(index) = index {
+                    self.touches.remove(index);
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(index) = index else {
+                    return;
+                };
+
+                match self.touches.as_slice() {
+                    [(_, previous)] => {
+                        let delta = location - previous;
+                        self.yaw -= delta.x * ORBIT_SPEED;
+                        self.pitch =
+                            (self.pitch + delta.y * ORBIT_SPEED).clamp(MIN_PITCH, MAX_PITCH);
+                    }
+                    [(_, first), (_, second), ..] if index < 2 => {
+                        let old_span = (first - second).norm();
+                        let other = if index == 0 { second } else { first };
+                        let new_span = (location - other).norm();
+                        // Spreading the fingers apart brings the camera closer.
+                        if new_span > f32::EPSILON {
+                            self.distance = (self.distance * old_span / new_span)
+                                .clamp(MIN_DISTANCE, MAX_DISTANCE);
+                        }
+                    }
+                    _ => return,
+                }
+
+                self.controlled = true;
+                self.touches[index].1 = location;
+            }
+        }
+    }
+
+    pub fn update(&self, scene: &mut Scene) {
+        if !self.controlled {
+            return;
+        }
+
+        let Some(camera) = scene.graph.try_get_mut(self.camera) else {
+            return;
+        };
+
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch);
+        // The camera looks along its local Z axis, zoom moves it back along this axis.
+        let position = self.target - rotation * Vector3::z().scale(self.distance);
+        camera
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(rotation);
+    }
+}
@@ -0,0 +1,50 @@
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    scene::node::Node,
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// A piece of equipment (a sword, a shield, etc.) that rigidly follows a bone of a skeleton. The
+/// node with this script should not have any parent (except the root of the graph), because its
+/// local transform is overwritten with the global transform of the bone every frame.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "2d7f3ac1-6b2e-4b8c-9a0e-1f6c3d9b5e47")]
+#[visit(optional)]
+pub struct AttachedEquipment {
+    /// Root node of the skeleton, bones are searched in its descendants.
+    pub skeleton_root: InheritableVariable<Handle<Node>>,
+    /// Name of the bone to follow.
+    pub bone_name: InheritableVariable<String>,
+    /// Offset in the local space of the bone.
+    pub position_offset: InheritableVariable<Vector3<f32>>,
+    /// Additional rotation relative to the bone.
+    pub rotation_offset: InheritableVariable<UnitQuaternion<f32>>,
+}
+
+impl ScriptTrait for AttachedEquipment {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let Some((_, bone)) = ctx
+            .scene
+            .graph
+            .find_by_name(*self.skeleton_root, &self.bone_name)
+        else {
+            return;
+        };
+
+        let bone_rotation = bone.global_rotation();
+        let position = bone.global_position() + bone_rotation * *self.position_offset;
+        let rotation = bone_rotation * *self.rotation_offset;
+
+        let transform = ctx.scene.graph[ctx.handle].local_transform_mut();
+        transform.set_position(position);
+        transform.set_rotation(rotation);
+    }
+}
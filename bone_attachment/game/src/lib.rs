@@ -0,0 +1,323 @@
+//! Game project.
+use crate::equipment::AttachedEquipment;
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        text::{TextBuilder, TextMessage},
+        utils::make_dropdown_list_option,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::model::{Model, ModelResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+use std::collections::BTreeSet;
+
+mod equipment;
+
+const SCENE_NAME: &str = "bone_attachment";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    model: Handle<Node>,
+    sword: Handle<Node>,
+    shield: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    sword_bone_selector: Handle<UiNode>,
+    shield_bone_selector: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bone_names: Vec<String>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_equipment(
+    scene: &mut Scene,
+    skeleton_root: Handle<Node>,
+    bone_name: &str,
+    geometry: Matrix4<f32>,
+    color: Color,
+) -> Handle<Node> {
+    let mut material = Material::standard();
+    Log::verify(material.set_property(&"diffuseColor".into(), color.into()));
+
+    MeshBuilder::new(BaseBuilder::new().with_script(Script::new(AttachedEquipment {
+        skeleton_root: skeleton_root.into(),
+        bone_name: bone_name.to_string().into(),
+        ..Default::default()
+    })))
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::make_cube(geometry),
+    ))
+    .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+    .build()])
+    .build(&mut scene.graph)
+}
+
+fn make_bone_selector(
+    ctx: &mut BuildContext,
+    row: usize,
+    bone_names: &[String],
+    selected: &str,
+) -> Handle<UiNode> {
+    DropdownListBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_height(22.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_items(
+        bone_names
+            .iter()
+            .map(|name| make_dropdown_list_option(ctx, name))
+            .collect(),
+    )
+    .with_selected(
+        bone_names
+            .iter()
+            .position(|name| name == selected)
+            .unwrap_or_default(),
+    )
+    .build(ctx)
+}
+
+impl Game {
+    fn on_model_loaded(&mut self, model: Handle<Node>, context: &mut PluginContext) {
+        let scene = &mut context.scenes[self.scene];
+
+        self.model = model;
+        scene.graph[model]
+            .local_transform_mut()
+            .set_scale(Vector3::repeat(0.01))
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                180.0f32.to_radians(),
+            ));
+
+        // Collect the names of every bone used by skinned meshes of the model.
+        let mut bone_names = BTreeSet::new();
+        for (_, node) in scene.graph.traverse_iter(model) {
+            if let Some(mesh) = node.cast::<Mesh>() {
+                for surface in mesh.surfaces() {
+                    for bone in surface.bones() {
+                        if let Some(bone) = scene.graph.try_get(*bone) {
+                            bone_names.insert(bone.name_owned());
+                        }
+                    }
+                }
+            }
+        }
+        self.bone_names = bone_names.into_iter().collect();
+
+        let sword_bone = "mixamorig:RightHand";
+        let shield_bone = "mixamorig:LeftForeArm";
+
+        self.sword = make_equipment(
+            scene,
+            model,
+            sword_bone,
+            Matrix4::new_translation(&Vector3::new(0.0, 0.45, 0.0))
+                * Matrix4::new_nonuniform_scaling(&Vector3::new(0.04, 0.9, 0.01)),
+            Color::opaque(180, 180, 190),
+        );
+        self.shield = make_equipment(
+            scene,
+            model,
+            shield_bone,
+            Matrix4::new_nonuniform_scaling(&Vector3::new(0.5, 0.6, 0.03)),
+            Color::opaque(120, 60, 30),
+        );
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_desired_position(Vector2::new(5.0, 50.0)),
+        )
+        .with_title(WindowTitle::text("Equipment"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(0)
+                                .with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text("Sword")
+                        .build(ctx),
+                    )
+                    .with_child({
+                        self.sword_bone_selector =
+                            make_bone_selector(ctx, 0, &self.bone_names, sword_bone);
+                        self.sword_bone_selector
+                    })
+                    .with_child(
+                        TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text("Shield")
+                        .build(ctx),
+                    )
+                    .with_child({
+                        self.shield_bone_selector =
+                            make_bone_selector(ctx, 1, &self.bone_names, shield_bone);
+                        self.shield_bone_selector
+                    }),
+            )
+            .add_column(Column::strict(60.0))
+            .add_column(Column::stretch())
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<AttachedEquipment>("Attached Equipment");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.2, -2.5))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new())
+            .build(&mut context.user_interfaces.first_mut().build_ctx());
+
+        context.task_pool.spawn_plugin_task(
+            context.resource_manager.request::<Model>("data/paladin.fbx"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(model) => {
+                    let model = model.instantiate(&mut ctx.scenes[game.scene]);
+                    game.on_model_loaded(model, ctx);
+                }
+                Err(err) => Log::err(format!("Unable to load paladin model. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Bone Attachment\nUse dropdown lists to attach equipment to other bones.\nFPS: {}\n{}",
+                    graphics_context.renderer.get_statistics().frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.direction() != MessageDirection::FromWidget {
+                return;
+            }
+
+            let equipment = if message.destination() == self.sword_bone_selector {
+                self.sword
+            } else if message.destination() == self.shield_bone_selector {
+                self.shield
+            } else {
+                return;
+            };
+
+            if let (Some(bone_name), Some(scene)) = (
+                self.bone_names.get(*index),
+                context.scenes.try_get_mut(self.scene),
+            ) {
+                if let Some(script) = scene
+                    .graph
+                    .try_get_mut(equipment)
+                    .and_then(|node| node.try_get_script_mut::<AttachedEquipment>())
+                {
+                    script.bone_name.set_value_and_mark_modified(bone_name.clone());
+                }
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Cloth simulation, that uses Verlet integration and XPBD distance constraints.
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        log::Log,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    scene::mesh::{
+        buffer::{VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+        Mesh,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+use std::collections::HashSet;
+
+/// Maximum time step of the simulation, larger steps make the cloth unstable.
+const MAX_TIME_STEP: f32 = 1.0 / 30.0;
+/// Compliance of the constraints when stiffness is zero.
+const MAX_COMPLIANCE: f32 = 0.001;
+/// Particles, that are this close to the top of the cloth, are pinned.
+const PIN_EPSILON: f32 = 0.001;
+
+#[derive(Debug, Clone)]
+struct Particle {
+    position: Vector3<f32>,
+    previous_position: Vector3<f32>,
+    /// Zero for pinned particles, so constraints won't move them.
+    inverse_mass: f32,
+}
+
+#[derive(Debug, Clone)]
+struct DistanceConstraint {
+    a: usize,
+    b: usize,
+    rest_length: f32,
+    lambda: f32,
+}
+
+/// Simulates cloth using vertices of the first surface of a mesh as particles. Neighbouring
+/// particles (connected by triangle edges) are kept at their initial distance, the top row of
+/// particles is pinned in place. The simulation runs in local space of the mesh, so the cloth
+/// follows its parent (a flag pole, a character, etc.).
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8f3a1c5e-2b7d-4e96-a0c4-6d1e9b2f7a38")]
+#[visit(optional)]
+pub struct Cloth {
+    /// Gravity acceleration in world space.
+    gravity: InheritableVariable<Vector3<f32>>,
+    /// Direction of the wind in world space.
+    wind: InheritableVariable<Vector3<f32>>,
+    wind_strength: InheritableVariable<f32>,
+    /// Stiffness of the constraints in `[0; 1]` range, where `1` means inextensible cloth.
+    stiffness: InheritableVariable<f32>,
+    /// Fraction of velocity, that is lost every second.
+    damping: InheritableVariable<f32>,
+    iterations: InheritableVariable<u32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    particles: Vec<Particle>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    constraints: Vec<DistanceConstraint>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    triangles: Vec<[usize; 3]>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0).into(),
+            wind: Vector3::new(0.3, 0.0, 1.0).into(),
+            wind_strength: 10.0.into(),
+            stiffness: 0.9.into(),
+            damping: 1.0.into(),
+            iterations: 8.into(),
+            particles: Default::default(),
+            constraints: Default::default(),
+            triangles: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl Cloth {
+    pub fn set_stiffness(&mut self, stiffness: f32) {
+        self.stiffness.set_value_and_mark_modified(stiffness);
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping.set_value_and_mark_modified(damping);
+    }
+
+    pub fn set_wind_strength(&mut self, wind_strength: f32) {
+        self.wind_strength
+            .set_value_and_mark_modified(wind_strength);
+    }
+
+    pub fn stiffness(&self) -> f32 {
+        *self.stiffness
+    }
+
+    pub fn damping(&self) -> f32 {
+        *self.damping
+    }
+
+    pub fn wind_strength(&self) -> f32 {
+        *self.wind_strength
+    }
+
+    /// Reads particles and constraints from the vertex and triangle buffers of the mesh.
+    fn read_particles(&mut self, mesh: &Mesh) {
+        let Some(surface) = mesh.surfaces().first() else {
+            return;
+        };
+        let resource = surface.data();
+        if !resource.is_ok() {
+            return;
+        }
+        let data = resource.data_ref();
+
+        let positions = data
+            .vertex_buffer
+            .iter()
+            .filter_map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position).ok())
+            .collect::<Vec<_>>();
+        let top = positions
+            .iter()
+            .map(|position| position.y)
+            .fold(f32::MIN, f32::max);
+
+        self.particles = positions
+            .iter()
+            .map(|position| Particle {
+                position: *position,
+                previous_position: *position,
+                inverse_mass: if top - position.y < PIN_EPSILON {
+                    0.0
+                } else {
+                    1.0
+                },
+            })
+            .collect();
+
+        self.triangles = data
+            .geometry_buffer
+            .iter()
+            .map(|triangle| triangle.0.map(|index| index as usize))
+            .collect();
+
+        // Triangles share edges, every edge must produce only one constraint.
+        let mut edges = HashSet::new();
+        for [a, b, c] in self.triangles.iter() {
+            for (a, b) in [(*a, *b), (*b, *c), (*c, *a)] {
+                edges.insert((a.min(b), a.max(b)));
+            }
+        }
+        self.constraints = edges
+            .into_iter()
+            .map(|(a, b)| DistanceConstraint {
+                a,
+                b,
+                rest_length: positions[a].metric_distance(&positions[b]),
+                lambda: 0.0,
+            })
+            .collect();
+    }
+
+    /// Calculates forces acting on every particle, in local space of the cloth.
+    fn forces(&self, to_local: &Matrix4<f32>) -> Vec<Vector3<f32>> {
+        let gravity = to_local.transform_vector(&*self.gravity);
+        let mut forces = vec![gravity; self.particles.len()];
+
+        // Make the wind a bit gusty, constant wind looks unnatural.
+        let gust = 1.0 + 0.5 * (self.time * 1.7).sin() * (self.time * 0.6).cos();
+        let wind = to_local.transform_vector(&self.wind.scale(*self.wind_strength * gust));
+
+        // Wind pushes triangles along their normals, proportionally to the angle of attack.
+        for [a, b, c] in self.triangles.iter() {
+            let pa = self.particles[*a].position;
+            let pb = self.particles[*b].position;
+            let pc = self.particles[*c].position;
+            if let Some(normal) = (pb - pa).cross(&(pc - pa)).try_normalize(f32::EPSILON) {
+                let force = normal.scale(normal.dot(&wind) / 3.0);
+                forces[*a] += force;
+                forces[*b] += force;
+                forces[*c] += force;
+            }
+        }
+
+        forces
+    }
+
+    fn integrate(&mut self, forces: &[Vector3<f32>], dt: f32) {
+        let velocity_scale = (1.0 - *self.damping * dt).clamp(0.0, 1.0);
+        for (particle, force) in self.particles.iter_mut().zip(forces) {
+            if particle.inverse_mass == 0.0 {
+                continue;
+            }
+            let velocity = (particle.position - particle.previous_position).scale(velocity_scale);
+            particle.previous_position = particle.position;
+            particle.position += velocity + force.scale(particle.inverse_mass * dt * dt);
+        }
+    }
+
+    fn solve_constraints(&mut self, dt: f32) {
+        let compliance = (1.0 - (*self.stiffness).clamp(0.0, 1.0)) * MAX_COMPLIANCE / (dt * dt);
+
+        for constraint in self.constraints.iter_mut() {
+            constraint.lambda = 0.0;
+        }
+
+        for _ in 0..*self.iterations {
+            for constraint in self.constraints.iter_mut() {
+                let a = &self.particles[constraint.a];
+                let b = &self.particles[constraint.b];
+                let total_inverse_mass = a.inverse_mass + b.inverse_mass;
+                if total_inverse_mass == 0.0 {
+                    continue;
+                }
+
+                let delta = a.position - b.position;
+                let length = delta.norm();
+                if length < f32::EPSILON {
+                    continue;
+                }
+                let direction = delta.scale(1.0 / length);
+
+                let error = length - constraint.rest_length;
+                let delta_lambda =
+                    (-error - compliance * constraint.lambda) / (total_inverse_mass + compliance);
+                constraint.lambda += delta_lambda;
+
+                let (a_inverse_mass, b_inverse_mass) = (a.inverse_mass, b.inverse_mass);
+                self.particles[constraint.a].position +=
+                    direction.scale(a_inverse_mass * delta_lambda);
+                self.particles[constraint.b].position -=
+                    direction.scale(b_inverse_mass * delta_lambda);
+            }
+        }
+    }
+
+    fn write_particles(&self, mesh: &Mesh) {
+        let Some(surface) = mesh.surfaces().first() else {
+            return;
+        };
+        let resource = surface.data();
+        let mut data = resource.data_ref();
+
+        for (mut vertex, particle) in data
+            .vertex_buffer
+            .modify()
+            .iter_mut()
+            .zip(self.particles.iter())
+        {
+            Log::verify(vertex.write_3_f32(VertexAttributeUsage::Position, particle.position));
+        }
+
+        Log::verify(data.calculate_normals());
+    }
+}
+
+impl ScriptTrait for Cloth {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        if let Some(mesh) = ctx.scene.graph.try_get_of_type::<Mesh>(ctx.handle) {
+            self.read_particles(mesh);
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let Some(mesh) = ctx.scene.graph.try_get_of_type::<Mesh>(ctx.handle) else {
+            return;
+        };
+
+        let dt = ctx.dt.min(MAX_TIME_STEP);
+        self.time += dt;
+
+        let to_local = mesh
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let forces = self.forces(&to_local);
+        self.integrate(&forces, dt);
+        self.solve_constraints(dt);
+        self.write_particles(mesh);
+    }
+}
@@ -0,0 +1,323 @@
+//! Game project.
+use crate::cloth::Cloth;
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        math::TriangleDefinition,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+
+mod cloth;
+
+/// Amount of quads along the horizontal side of the banner.
+const BANNER_COLUMNS: u32 = 24;
+/// Amount of quads along the vertical side of the banner.
+const BANNER_ROWS: u32 = 20;
+const BANNER_WIDTH: f32 = 2.5;
+const BANNER_HEIGHT: f32 = 2.0;
+const POLE_HEIGHT: f32 = 4.0;
+const CROSSBAR_LENGTH: f32 = 3.0;
+
+const SCENE_NAME: &str = "cloth";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    banner: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    stiffness_slider: Handle<UiNode>,
+    damping_slider: Handle<UiNode>,
+    wind_strength_slider: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Creates a flat grid of vertices, the top row of it is at the origin and pinned by the cloth.
+fn make_banner() -> SurfaceData {
+    let mut vertices = Vec::new();
+    for row in 0..=BANNER_ROWS {
+        for column in 0..=BANNER_COLUMNS {
+            let u = column as f32 / BANNER_COLUMNS as f32;
+            let v = row as f32 / BANNER_ROWS as f32;
+            vertices.push(StaticVertex::from_pos_uv_normal(
+                Vector3::new((u - 0.5) * BANNER_WIDTH, -v * BANNER_HEIGHT, 0.0),
+                Vector2::new(u, v),
+                Vector3::z(),
+            ));
+        }
+    }
+
+    let mut triangles = Vec::new();
+    let index = |row: u32, column: u32| row * (BANNER_COLUMNS + 1) + column;
+    for row in 0..BANNER_ROWS {
+        for column in 0..BANNER_COLUMNS {
+            let top_left = index(row, column);
+            let top_right = index(row, column + 1);
+            let bottom_left = index(row + 1, column);
+            let bottom_right = index(row + 1, column + 1);
+            triangles.push(TriangleDefinition([top_left, top_right, bottom_right]));
+            triangles.push(TriangleDefinition([top_left, bottom_right, bottom_left]));
+        }
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+    )
+}
+
+fn make_pole() -> SurfaceData {
+    SurfaceData::make_cylinder(16, 0.05, POLE_HEIGHT, true, &Matrix4::identity())
+}
+
+/// Cylinders are built along Y axis, so the crossbar is rotated to lie along X axis, centered on
+/// top of the pole.
+fn make_crossbar() -> SurfaceData {
+    SurfaceData::make_cylinder(
+        16,
+        0.04,
+        CROSSBAR_LENGTH,
+        true,
+        &(Matrix4::new_translation(&Vector3::new(CROSSBAR_LENGTH * 0.5, POLE_HEIGHT, 0.0))
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90.0f32.to_radians())
+                .to_homogeneous()),
+    )
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(ctx: &mut BuildContext, row: usize, max: f32, value: f32) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(0.0)
+    .with_max(max)
+    .with_step(max / 100.0)
+    .with_value(value)
+    .with_value_precision(2)
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    fn cloth_mut<'a>(&self, context: &'a mut PluginContext) -> Option<&'a mut Cloth> {
+        context
+            .scenes
+            .try_get_mut(self.scene)?
+            .graph
+            .try_get_mut(self.banner)?
+            .try_get_script_mut::<Cloth>()
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Cloth>("Cloth");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 3.0, -6.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, Material::standard());
+        // The banner is visible from both sides, when the wind turns it around.
+        let banner_material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::standard_two_sides());
+
+        self.banner = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, POLE_HEIGHT - 0.05, 0.0))
+                        .build(),
+                )
+                .with_script(Script::new(Cloth::default())),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            make_banner(),
+        ))
+        .with_material(banner_material)
+        .build()])
+        .build(&mut scene.graph);
+
+        MeshBuilder::new(BaseBuilder::new().with_children(&[self.banner]))
+            .with_surfaces(vec![
+                SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, make_pole()))
+                    .with_material(material.clone())
+                    .build(),
+                SurfaceBuilder::new(SurfaceResource::new_ok(
+                    ResourceKind::Embedded,
+                    make_crossbar(),
+                ))
+                .with_material(material)
+                .build(),
+            ])
+            .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let defaults = Cloth::default();
+        let stiffness_text = make_text(ctx, 0, "Stiffness");
+        self.stiffness_slider = make_slider(ctx, 1, 1.0, defaults.stiffness());
+        let damping_text = make_text(ctx, 2, "Damping");
+        self.damping_slider = make_slider(ctx, 3, 5.0, defaults.damping());
+        let wind_strength_text = make_text(ctx, 4, "Wind Strength");
+        self.wind_strength_slider = make_slider(ctx, 5, 30.0, defaults.wind_strength());
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(190.0)
+                .with_desired_position(Vector2::new(5.0, 80.0)),
+        )
+        .with_title(WindowTitle::text("Cloth Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(stiffness_text)
+                    .with_child(self.stiffness_slider)
+                    .with_child(damping_text)
+                    .with_child(self.damping_slider)
+                    .with_child(wind_strength_text)
+                    .with_child(self.wind_strength_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Cloth Simulation\nParticles: {}\n{}\n{}",
+                        (BANNER_ROWS + 1) * (BANNER_COLUMNS + 1),
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        let destination = message.destination();
+        if let Some(cloth) = self.cloth_mut(context) {
+            if destination == self.stiffness_slider {
+                cloth.set_stiffness(*value);
+            } else if destination == self.damping_slider {
+                cloth.set_damping(*value);
+            } else if destination == self.wind_strength_slider {
+                cloth.set_wind_strength(*value);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
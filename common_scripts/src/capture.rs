@@ -0,0 +1,89 @@
+//! Reading of rendered frames back to the CPU, for tests and recording. The back buffer can't be
+//! used for it - its contents are undefined once the frame is presented. Instead, scenes are read
+//! from their frame buffers, that the renderer keeps between frames, and user interfaces are
+//! rendered to a texture of the requested size.
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::UserInterface,
+    renderer::{
+        framework::{framebuffer::ReadTarget, gpu_texture::PixelKind},
+        Renderer,
+    },
+    resource::texture::TextureResource,
+    scene::Scene,
+};
+use image::{imageops, RgbaImage};
+
+/// Creates an image from the rows of a texture, that go from the bottom to the top.
+fn image_from_rows(size: Vector2<u32>, pixels: Vec<u8>) -> Result<RgbaImage, String> {
+    let mut image = RgbaImage::from_raw(size.x, size.y, pixels)
+        .ok_or_else(|| format!("the frame is not {}x{} RGBA8 image", size.x, size.y))?;
+    imageops::flip_vertical_in_place(&mut image);
+    Ok(image)
+}
+
+/// Reads the last frame of a scene. The scene must be rendered to the screen (not to a render
+/// target of its own), so the size of its frame is the size of the screen.
+pub fn capture_scene(renderer: &Renderer, scene: Handle<Scene>) -> Result<RgbaImage, String> {
+    let (width, height) = renderer.get_frame_size();
+    let pixels = renderer
+        .scene_data_map
+        .get(&scene)
+        .ok_or("the scene is not rendered yet")?
+        .ldr_scene_framebuffer
+        .read_pixels(ReadTarget::Color(0))
+        .ok_or("unable to read the frame of the scene")?;
+    image_from_rows(Vector2::new(width, height), pixels)
+}
+
+/// Renders user interfaces to a texture and reads it back. The texture is reused, while the size
+/// of the captured frames stays the same.
+#[derive(Default, Debug)]
+pub struct UiCapture {
+    render_target: Option<TextureResource>,
+    size: Vector2<u32>,
+}
+
+impl UiCapture {
+    /// Renders the user interface to a texture of the given size and returns its pixels. If the
+    /// size differs from the size of the screen of the interface, the interface is laid out for
+    /// the new size first, so the result does not depend on the size (or the DPI) of the window.
+    pub fn capture(
+        &mut self,
+        renderer: &mut Renderer,
+        ui: &mut UserInterface,
+        size: Vector2<u32>,
+        clear_color: Color,
+    ) -> Result<RgbaImage, String> {
+        let screen_size = size.cast::<f32>();
+        if ui.screen_size() != screen_size {
+            ui.update(screen_size, 0.0, &Default::default());
+        }
+
+        if self.size != size {
+            self.render_target = None;
+            self.size = size;
+        }
+        let render_target = self
+            .render_target
+            .get_or_insert_with(|| TextureResource::new_render_target(size.x, size.y))
+            .clone();
+        renderer
+            .render_ui_to_texture(
+                render_target.clone(),
+                screen_size,
+                ui.draw(),
+                clear_color,
+                PixelKind::RGBA8,
+            )
+            .map_err(|err| format!("{err:?}"))?;
+
+        let pixels = renderer
+            .texture_cache
+            .get(&*renderer.server, &render_target)
+            .ok_or("the render target is not uploaded")?
+            .gpu_texture
+            .read_pixels();
+        image_from_rows(size, pixels)
+    }
+}
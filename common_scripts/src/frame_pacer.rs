@@ -0,0 +1,219 @@
+//! Frame pacing for desktop executors. Frames are spaced evenly at the refresh interval of the
+//! monitor: the pacer sleeps before the rendering until the next frame slot and a PID controller
+//! corrects the sleep by the phase error of the previous frame (the difference between the actual
+//! and the ideal time, when the frame was presented), so the rendering time and a constant
+//! oversleep of the OS scheduler are compensated. The executor must be created with vsync
+//! disabled, otherwise the swap of the buffers waits for the next vertical blank on top of the
+//! sleep.
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    engine::GraphicsContext,
+    event::Event,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        UiNode, UserInterface,
+    },
+    plugin::{Plugin, PluginContext},
+};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Amount of the frame present times, that are used to compute the jitter.
+const HISTORY_SIZE: usize = 120;
+/// Refresh rate, that is used when the refresh rate of the monitor is unknown.
+const DEFAULT_REFRESH_RATE: f64 = 60.0;
+const PROPORTIONAL_GAIN: f64 = 0.5;
+const INTEGRAL_GAIN: f64 = 0.05;
+const DERIVATIVE_GAIN: f64 = 0.05;
+/// Max correction of the sleep relative to the frame interval.
+const MAX_CORRECTION: f64 = 0.5;
+
+#[derive(Default, Debug)]
+struct PidController {
+    integral: f64,
+    previous_error: f64,
+}
+
+impl PidController {
+    fn update(&mut self, error: f64) -> f64 {
+        self.integral += error;
+        let derivative = error - self.previous_error;
+        self.previous_error = error;
+        PROPORTIONAL_GAIN * error + INTEGRAL_GAIN * self.integral + DERIVATIVE_GAIN * derivative
+    }
+}
+
+/// Difference between two instants in seconds, positive if `a` is later than `b`.
+fn signed_seconds(a: Instant, b: Instant) -> f64 {
+    a.saturating_duration_since(b).as_secs_f64() - b.saturating_duration_since(a).as_secs_f64()
+}
+
+/// Plugin, that paces the frames of the game. It must be added to the executor after the game, so
+/// it sleeps after the game has finished its update and its overlay is drawn on top of the user
+/// interfaces of the game. Shows the jitter of the frames in the bottom left corner of the screen.
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct FramePacer {
+    text: Handle<UiNode>,
+    /// The overlay lives in its own user interface, because games replace their first one (for
+    /// example, with a menu loaded from a file).
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui: Handle<UserInterface>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    target_interval: Duration,
+    /// Present times of the last frames.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    timestamps: VecDeque<Instant>,
+    /// Ideal present time of the current frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    deadline: Option<Instant>,
+    /// Whether a frame was rendered after the last sleep and is not measured yet.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rendering: bool,
+    /// Amount of seconds, that the next sleep is shortened by.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    correction: f64,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pid: PidController,
+}
+
+impl FramePacer {
+    pub fn set_refresh_rate(&mut self, refresh_rate: f64) {
+        self.target_interval = Duration::from_secs_f64(1.0 / refresh_rate.max(1.0));
+        self.deadline = None;
+    }
+
+    /// Sleeps until the frame, that is about to be rendered, could be presented in the next frame
+    /// slot. Must be called right before the rendering.
+    pub fn sleep_until_next_frame(&mut self) {
+        if self.target_interval.is_zero() {
+            self.set_refresh_rate(DEFAULT_REFRESH_RATE);
+        }
+
+        let now = Instant::now();
+        let deadline = match self.deadline {
+            Some(deadline)
+                if signed_seconds(deadline + self.target_interval, now) > self.correction =>
+            {
+                deadline + self.target_interval
+            }
+            // The frame took longer than the interval (or it is the first frame), there's no
+            // point to catch up, so the slots are aligned with the current frame.
+            _ => {
+                self.pid = Default::default();
+                self.correction = 0.0;
+                now
+            }
+        };
+
+        let sleep = signed_seconds(deadline, now) - self.correction;
+        if sleep > 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(sleep));
+        }
+        self.deadline = Some(deadline);
+        self.rendering = true;
+    }
+
+    /// Measures the phase error of the frame, that was rendered after the last sleep. Must be
+    /// called right after the buffers are swapped.
+    pub fn on_frame_presented(&mut self) {
+        let Some(deadline) = self.deadline.filter(|_| self.rendering) else {
+            return;
+        };
+        self.rendering = false;
+
+        let presented = Instant::now();
+        let max_correction = self.target_interval.as_secs_f64() * MAX_CORRECTION;
+        self.correction = self
+            .pid
+            .update(signed_seconds(presented, deadline))
+            .clamp(-max_correction, max_correction);
+
+        if self.timestamps.len() == HISTORY_SIZE {
+            self.timestamps.pop_front();
+        }
+        self.timestamps.push_back(presented);
+    }
+
+    /// Standard deviation of the intervals between the last frames, in seconds.
+    pub fn jitter(&self) -> f64 {
+        let intervals = self
+            .timestamps
+            .iter()
+            .zip(self.timestamps.iter().skip(1))
+            .map(|(a, b)| b.duration_since(*a).as_secs_f64())
+            .collect::<Vec<_>>();
+        if intervals.len() < 2 {
+            return 0.0;
+        }
+        let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+        let variance = intervals
+            .iter()
+            .map(|interval| (interval - mean).powi(2))
+            .sum::<f64>()
+            / intervals.len() as f64;
+        variance.sqrt()
+    }
+}
+
+impl Plugin for FramePacer {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        self.text = TextBuilder::new(WidgetBuilder::new().with_hit_test_visibility(false))
+            .build(&mut ui.build_ctx());
+        self.ui = context.user_interfaces.add(ui);
+    }
+
+    fn on_graphics_context_initialized(&mut self, context: PluginContext) {
+        let GraphicsContext::Initialized(graphics_context) = context.graphics_context else {
+            return;
+        };
+        let refresh_rate = graphics_context
+            .window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .map_or(DEFAULT_REFRESH_RATE, |millihertz| {
+                millihertz as f64 / 1000.0
+            });
+        self.set_refresh_rate(refresh_rate);
+    }
+
+    fn before_rendering(&mut self, context: PluginContext) {
+        if let Some(ui) = context.user_interfaces.try_get(self.ui) {
+            ui.send_message(WidgetMessage::desired_position(
+                self.text,
+                MessageDirection::ToWidget,
+                Vector2::new(5.0, ui.screen_size().y - 20.0),
+            ));
+            ui.send_message(TextMessage::text(
+                self.text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Frame Pacing: {:.1} Hz, Jitter: {:.3} ms",
+                    1.0 / self.target_interval.as_secs_f64().max(f64::EPSILON),
+                    self.jitter() * 1000.0
+                ),
+            ));
+        }
+
+        self.sleep_until_next_frame();
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, _context: PluginContext) {
+        // The executor renders and swaps the buffers on `RedrawRequested`, so `AboutToWait` is the
+        // first event after the frame was presented.
+        if matches!(event, Event::AboutToWait) {
+            self.on_frame_presented();
+        }
+    }
+}
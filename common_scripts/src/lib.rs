@@ -0,0 +1,26 @@
+//! Scripts shared between demo projects.
+use fyrox::script::constructor::ScriptConstructorContainer;
+
+pub mod capture;
+pub mod frame_pacer;
+pub mod platform_assets;
+pub mod projection;
+#[cfg(feature = "recording")]
+pub mod recorder;
+pub mod throttle;
+pub mod trigger;
+pub mod visual_regression;
+
+pub use capture::{capture_scene, UiCapture};
+pub use frame_pacer::FramePacer;
+pub use platform_assets::transcoded_texture_path;
+pub use projection::world_to_screen;
+#[cfg(feature = "recording")]
+pub use recorder::Recorder;
+pub use throttle::Throttle;
+pub use trigger::{TriggerCallback, TriggerVolume};
+
+/// Registers all scripts of the crate, so they could be used in the editor.
+pub fn register(container: &ScriptConstructorContainer) {
+    container.add::<TriggerVolume>("Trigger Volume");
+}
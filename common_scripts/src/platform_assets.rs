@@ -0,0 +1,22 @@
+//! Paths of the textures, that are transcoded for the target platform by `asset_pipeline` in the
+//! build scripts of the game crates.
+use std::path::{Path, PathBuf};
+
+/// Maps the path of a texture in `data` to the path of its transcoded copy. `asset_path`,
+/// `extension` and `transcoded` are the values of `ASSET_PATH`, `ASSET_EXTENSION` and
+/// `TRANSCODED_ASSETS` environment variables, that are set by the build script. The path is
+/// returned as is, if the texture is not transcoded for the target platform.
+pub fn transcoded_texture_path(
+    path: &str,
+    asset_path: &str,
+    extension: &str,
+    transcoded: &str,
+) -> PathBuf {
+    let is_transcoded = transcoded.split(';').any(|transcoded| transcoded == path);
+    match Path::new(path).strip_prefix("data") {
+        Ok(relative) if is_transcoded => Path::new(asset_path)
+            .join(relative)
+            .with_extension(extension),
+        _ => PathBuf::from(path),
+    }
+}
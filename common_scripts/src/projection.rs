@@ -0,0 +1,37 @@
+//! Projection of world space points to the screen.
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    scene::{camera::Camera, node::Node, Scene},
+};
+
+/// Projects a point from world space to the screen space of the given camera. Returned point is
+/// in normalized coordinates, where (0, 0) is the top-left corner of the screen and (1, 1) is the
+/// bottom-right one. Returns `None` if the point is behind the camera or outside its depth range.
+pub fn world_to_screen(
+    scene: &Scene,
+    camera: Handle<Node>,
+    world_pos: Vector3<f32>,
+) -> Option<Vector2<f32>> {
+    let camera = scene.graph.try_get_of_type::<Camera>(camera)?;
+
+    let clip_space =
+        camera.view_projection_matrix() * Vector4::new(world_pos.x, world_pos.y, world_pos.z, 1.0);
+    if clip_space.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = clip_space.xyz().scale(1.0 / clip_space.w);
+    if !(-1.0..=1.0).contains(&ndc.z) {
+        return None;
+    }
+
+    let viewport = camera.viewport();
+    Some(Vector2::new(
+        viewport.position.x + (ndc.x + 1.0) * 0.5 * viewport.size.x,
+        viewport.position.y + (1.0 - ndc.y) * 0.5 * viewport.size.y,
+    ))
+}
@@ -0,0 +1,324 @@
+//! Recording of the screen to a WebM file. The contents of the back buffer are undefined after the
+//! frame is presented, so a frame is composed on the CPU instead: the frame buffers of the scenes,
+//! that are rendered to the screen, are read and the user interfaces are rendered to a texture and
+//! drawn over them. Frames are captured at most [`MAX_FRAME_RATE`] times per second and sent to
+//! the encoder thread, which converts them to YUV 4:2:0, encodes them with VP8 and writes them to
+//! the WebM container. The file is finalized, when the plugin is deinitialized (on shutdown of the
+//! executor).
+use crate::capture::{capture_scene, UiCapture};
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, log::Log, pool::Handle, reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::Event,
+    gui::{
+        brush::Brush,
+        message::MessageDirection,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        UiNode, UserInterface,
+    },
+    plugin::{Plugin, PluginContext},
+};
+use image::{imageops, Rgba, RgbaImage};
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use webm::mux::{self, Track};
+
+/// Frames, that are rendered more often than this, are dropped.
+pub const MAX_FRAME_RATE: f64 = 30.0;
+/// Target bitrate of the video in kbit/s.
+const BITRATE: u32 = 5000;
+/// Amount of the frames, that wait for the encoder. Frames are dropped, when the encoder is
+/// slower than the game and the queue is full.
+const QUEUE_SIZE: usize = 8;
+
+/// A composed frame of the screen.
+struct Frame {
+    image: RgbaImage,
+    timestamp: Duration,
+}
+
+/// Converts the frame to planar YUV 4:2:0 (BT.601, limited range). The size of the image must be
+/// even, an odd column or row of the frame is cropped.
+fn rgba_to_i420(frame: &Frame, width: usize, height: usize) -> Vec<u8> {
+    let pixel = |x: usize, y: usize| {
+        let [r, g, b, _] = frame.image.get_pixel(x as u32, y as u32).0;
+        (r as i32, g as i32, b as i32)
+    };
+
+    let mut yuv = vec![0; width * height * 3 / 2];
+    let (luma, chroma) = yuv.split_at_mut(width * height);
+    let (u_plane, v_plane) = chroma.split_at_mut(width * height / 4);
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = pixel(x, y);
+            luma[y * width + x] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        }
+    }
+    for y in 0..height / 2 {
+        for x in 0..width / 2 {
+            let (mut r, mut g, mut b) = (0, 0, 0);
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (pr, pg, pb) = pixel(x * 2 + dx, y * 2 + dy);
+                r += pr;
+                g += pg;
+                b += pb;
+            }
+            let (r, g, b) = (r / 4, g / 4, b / 4);
+            let index = y * (width / 2) + x;
+            u_plane[index] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            v_plane[index] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+    }
+    yuv
+}
+
+/// Encodes the frames until the channel is closed, then flushes the encoder and finalizes the
+/// file. The size of the video is the size of the first frame, frames of other sizes (when the
+/// window is resized) are skipped.
+fn encode(path: PathBuf, first: Frame, frames: Receiver<Frame>) -> Result<(), String> {
+    let (full_width, full_height) = first.image.dimensions();
+    let (width, height) = (full_width & !1, full_height & !1);
+
+    let file = File::create(&path)
+        .map_err(|err| format!("Unable to create {}. Reason: {err:?}", path.display()))?;
+    let mut segment = mux::Segment::new(mux::Writer::new(file))
+        .ok_or_else(|| format!("Unable to create WebM segment in {}", path.display()))?;
+    let mut track = segment.add_video_track(width, height, None, mux::VideoCodecId::VP8);
+    let mut encoder = vpx_encode::Encoder::new(vpx_encode::Config {
+        width,
+        height,
+        timebase: [1, 1000],
+        bitrate: BITRATE,
+        codec: vpx_encode::VideoCodecId::VP8,
+    })
+    .map_err(|err| format!("Unable to create VP8 encoder. Reason: {err:?}"))?;
+
+    for frame in std::iter::once(first).chain(frames) {
+        if frame.image.dimensions() != (full_width, full_height) {
+            continue;
+        }
+        let yuv = rgba_to_i420(&frame, width as usize, height as usize);
+        let packets = encoder
+            .encode(frame.timestamp.as_millis() as i64, &yuv)
+            .map_err(|err| format!("Unable to encode frame. Reason: {err:?}"))?;
+        for packet in packets {
+            track.add_frame(packet.data, packet.pts as u64 * 1_000_000, packet.key);
+        }
+    }
+
+    let mut packets = encoder
+        .finish()
+        .map_err(|err| format!("Unable to flush VP8 encoder. Reason: {err:?}"))?;
+    while let Some(packet) = packets
+        .next()
+        .map_err(|err| format!("Unable to flush VP8 encoder. Reason: {err:?}"))?
+    {
+        track.add_frame(packet.data, packet.pts as u64 * 1_000_000, packet.key);
+    }
+
+    segment
+        .finalize(None)
+        .map(|_| ())
+        .map_err(|_| format!("Unable to finalize {}", path.display()))
+}
+
+/// Plugin, that records the screen to a WebM file. Shows a red "REC" indicator in the top right
+/// corner of the screen while recording.
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Recorder {
+    indicator: Handle<UiNode>,
+    /// The indicator lives in its own user interface, because games replace their first one.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui: Handle<UserInterface>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    path: PathBuf,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    start: Option<Instant>,
+    /// Time (since the start of the recording), when the next frame is captured.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    next_capture: Duration,
+    /// Time of the frame, that is being rendered and must be captured, when it is presented.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending_capture: Option<Duration>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui_capture: UiCapture,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    sender: Option<SyncSender<Frame>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    encoder_thread: Option<JoinHandle<Result<(), String>>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stopped: bool,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates the recorder, if the executor is started with `--record <output.webm>` argument.
+    pub fn from_args() -> Option<Self> {
+        std::env::args()
+            .skip_while(|arg| arg != "--record")
+            .nth(1)
+            .map(Self::new)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        !self.stopped
+    }
+
+    /// Decides whether the frame, that is about to be rendered, is captured.
+    fn schedule_capture(&mut self) {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let elapsed = start.elapsed();
+        if elapsed < self.next_capture {
+            return;
+        }
+        self.next_capture =
+            (self.next_capture + Duration::from_secs_f64(1.0 / MAX_FRAME_RATE)).max(elapsed);
+        self.pending_capture = Some(elapsed);
+    }
+
+    /// Composes the frame from the last frames of the scenes, that are rendered to the screen, and
+    /// the user interfaces of the game (the indicator is not recorded).
+    fn compose_frame(&mut self, context: &mut PluginContext) -> Result<RgbaImage, String> {
+        let GraphicsContext::Initialized(graphics_context) = context.graphics_context else {
+            return Err("the graphics context is not initialized".to_string());
+        };
+        let renderer = &mut graphics_context.renderer;
+        let (width, height) = renderer.get_frame_size();
+        let mut image = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+        for (handle, scene) in context.scenes.pair_iter() {
+            if *scene.enabled && scene.rendering_options.render_target.is_none() {
+                imageops::overlay(&mut image, &capture_scene(renderer, handle)?, 0, 0);
+            }
+        }
+        for (handle, ui) in context.user_interfaces.pair_iter_mut() {
+            if handle != self.ui {
+                let ui_image = self.ui_capture.capture(
+                    renderer,
+                    ui,
+                    Vector2::new(width, height),
+                    Color::TRANSPARENT,
+                )?;
+                imageops::overlay(&mut image, &ui_image, 0, 0);
+            }
+        }
+        Ok(image)
+    }
+
+    fn capture(&mut self, context: &mut PluginContext) {
+        let Some(timestamp) = self.pending_capture.take() else {
+            return;
+        };
+        let image = match self.compose_frame(context) {
+            Ok(image) if image.width() > 1 && image.height() > 1 => image,
+            Ok(_) => return,
+            Err(err) => {
+                Log::err(format!("Unable to capture a frame. Reason: {err}"));
+                return;
+            }
+        };
+        let frame = Frame { image, timestamp };
+        match self.sender.as_ref() {
+            Some(sender) => match sender.try_send(frame) {
+                Ok(()) | Err(TrySendError::Full(_)) => (),
+                // The encoder thread has failed, the error is reported, when the thread is
+                // joined.
+                Err(TrySendError::Disconnected(_)) => self.stop(),
+            },
+            None => {
+                let (sender, receiver) = mpsc::sync_channel(QUEUE_SIZE);
+                let path = self.path.clone();
+                self.encoder_thread =
+                    Some(std::thread::spawn(move || encode(path, frame, receiver)));
+                self.sender = Some(sender);
+            }
+        }
+    }
+
+    /// Stops the recording and waits until the file is finalized.
+    pub fn stop(&mut self) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+        // Closes the channel, so the encoder thread finishes.
+        self.sender = None;
+        let Some(encoder_thread) = self.encoder_thread.take() else {
+            return;
+        };
+        match encoder_thread.join() {
+            Ok(Ok(())) => Log::info(format!("Recording saved to {}", self.path.display())),
+            Ok(Err(err)) => Log::err(err),
+            Err(_) => Log::err("The encoder thread has panicked."),
+        }
+    }
+}
+
+impl Plugin for Recorder {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        self.indicator = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_foreground(Brush::Solid(Color::RED)),
+        )
+        .with_text("REC")
+        .build(&mut ui.build_ctx());
+        self.ui = context.user_interfaces.add(ui);
+    }
+
+    fn before_rendering(&mut self, context: PluginContext) {
+        if let Some(ui) = context.user_interfaces.try_get(self.ui) {
+            ui.send_message(WidgetMessage::visibility(
+                self.indicator,
+                MessageDirection::ToWidget,
+                self.is_recording(),
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                self.indicator,
+                MessageDirection::ToWidget,
+                Vector2::new(ui.screen_size().x - 40.0, 5.0),
+            ));
+        }
+
+        if self.is_recording() {
+            self.schedule_capture();
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        // The executor renders the frame on `RedrawRequested`, so `AboutToWait` is the first event,
+        // that comes after the scheduled frame was rendered. The frame buffers of the scenes keep
+        // their contents until the next frame.
+        if matches!(event, Event::AboutToWait) && self.is_recording() {
+            self.capture(&mut context);
+        }
+    }
+
+    fn on_deinit(&mut self, _context: PluginContext) {
+        self.stop();
+    }
+}
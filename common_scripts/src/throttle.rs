@@ -0,0 +1,134 @@
+//! Throttling of games, that run in a hidden browser tab. Browsers keep running WebAssembly games
+//! in background tabs, so the game must stop burning CPU by itself. The visibility of the page is
+//! reported by WebAssembly executors, that call [`watch_page_visibility`] on start.
+use fyrox::{
+    core::pool::Handle,
+    graph::BaseSceneGraph,
+    gui::{
+        message::MessageDirection,
+        text::{Text, TextMessage},
+        UiNode,
+    },
+    plugin::PluginContext,
+    scene::Scene,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Interval between updates of the scenes (in seconds), while the page is hidden.
+const HIDDEN_UPDATE_INTERVAL: f32 = 1.0;
+
+static PAGE_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Marks the page with the game as hidden or visible.
+pub fn set_page_hidden(hidden: bool) {
+    PAGE_HIDDEN.store(hidden, Ordering::Relaxed);
+}
+
+/// Returns `true` if the page with the game is hidden (e.g. its tab is in background). Always
+/// `false` on platforms other than WebAssembly.
+pub fn is_page_hidden() -> bool {
+    PAGE_HIDDEN.load(Ordering::Relaxed)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod page_visibility {
+    use fyrox::core::wasm_bindgen::{self, prelude::*};
+
+    #[wasm_bindgen(inline_js = "export function on_visibility_change(callback) {
+        document.addEventListener('visibilitychange', () => callback(document.hidden));
+    }")]
+    extern "C" {
+        fn on_visibility_change(callback: &Closure<dyn FnMut(bool)>);
+    }
+
+    /// Reports visibility of the page to the game, so it could throttle itself in a hidden tab.
+    pub fn watch_page_visibility() {
+        let callback = Closure::<dyn FnMut(bool)>::new(super::set_page_hidden);
+        on_visibility_change(&callback);
+        // The listener lives as long as the page, so the closure must never be dropped.
+        callback.forget();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use page_visibility::watch_page_visibility;
+
+/// Disables all scenes while the page is hidden, so they're neither updated (physics, animation,
+/// scripts) nor rendered. The scenes are enabled for a single frame once per second, to keep the
+/// game alive. Enabled state of every scene is restored when the page becomes visible again.
+#[derive(Default, Debug)]
+pub struct Throttle {
+    timer: f32,
+    /// Enabled state of the scenes before the throttling has started.
+    saved_states: Option<Vec<(Handle<Scene>, bool)>>,
+    /// Debug text before it was replaced with the notice.
+    saved_text: Option<String>,
+}
+
+impl Throttle {
+    /// Must be called at the beginning of `Plugin::update`. Returns `true` if the game must skip
+    /// its own non-essential updates, in this case the debug text is replaced with a notice. The
+    /// debug text is restored, when the page becomes visible again.
+    pub fn update(&mut self, context: &mut PluginContext, debug_text: Handle<UiNode>) -> bool {
+        if !is_page_hidden() {
+            if let Some(saved_states) = self.saved_states.take() {
+                for (handle, enabled) in saved_states {
+                    if let Some(scene) = context.scenes.try_get_mut(handle) {
+                        scene.enabled.set_value_and_mark_modified(enabled);
+                    }
+                }
+            }
+            // Games with static debug text won't overwrite the notice by themselves.
+            if let Some(text) = self.saved_text.take() {
+                context
+                    .user_interfaces
+                    .first()
+                    .send_message(TextMessage::text(
+                        debug_text,
+                        MessageDirection::ToWidget,
+                        text,
+                    ));
+            }
+            return false;
+        }
+
+        if self.saved_text.is_none() {
+            self.saved_text = context
+                .user_interfaces
+                .first()
+                .try_get_of_type::<Text>(debug_text)
+                .map(|text| text.text());
+        }
+
+        let saved_states = self.saved_states.get_or_insert_with(|| {
+            context
+                .scenes
+                .pair_iter()
+                .map(|(handle, scene)| (handle, *scene.enabled))
+                .collect()
+        });
+
+        self.timer -= context.dt;
+        let tick = self.timer <= 0.0;
+        if tick {
+            self.timer = HIDDEN_UPDATE_INTERVAL;
+        }
+
+        for (handle, enabled) in saved_states.iter() {
+            if let Some(scene) = context.scenes.try_get_mut(*handle) {
+                scene.enabled.set_value_and_mark_modified(*enabled && tick);
+            }
+        }
+
+        context
+            .user_interfaces
+            .first()
+            .send_message(TextMessage::text(
+                debug_text,
+                MessageDirection::ToWidget,
+                "PAUSED (tab hidden)".to_string(),
+            ));
+
+        true
+    }
+}
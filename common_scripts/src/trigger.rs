@@ -0,0 +1,142 @@
+//! Trigger volume, that notifies about bodies entering and leaving it.
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{collider::Collider, dim2, graph::Graph, node::Node},
+    script::{ScriptContext, ScriptTrait},
+};
+use std::{collections::HashSet, fmt::Debug};
+
+/// Receives notifications from [`TriggerVolume`].
+pub trait TriggerCallback: Debug + Send + 'static {
+    /// Called when a body starts overlapping the trigger.
+    fn on_enter(&mut self, body: Handle<Node>, ctx: &mut ScriptContext);
+
+    /// Called when a body stops overlapping the trigger.
+    fn on_exit(&mut self, body: Handle<Node>, ctx: &mut ScriptContext);
+
+    fn clone_box(&self) -> Box<dyn TriggerCallback>;
+}
+
+impl Clone for Box<dyn TriggerCallback> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Tracks bodies that overlap colliders of the node (the node itself, or its direct children),
+/// both 2D and 3D colliders are supported. Colliders of the trigger are usually sensors, but
+/// solid ones will work too. The callback can't be set in the editor, it must be assigned from
+/// code using [`TriggerVolume::new`] or [`TriggerVolume::set_callback`].
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "d2b7f4e8-3c19-4a5d-9e60-7f1a2b8c4d93")]
+#[visit(optional)]
+pub struct TriggerVolume {
+    #[visit(skip)]
+    #[reflect(hidden)]
+    callback: Option<Box<dyn TriggerCallback>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    overlapping: HashSet<Handle<Node>>,
+}
+
+impl TriggerVolume {
+    pub fn new<C: TriggerCallback>(callback: C) -> Self {
+        Self {
+            callback: Some(Box::new(callback)),
+            overlapping: Default::default(),
+        }
+    }
+
+    pub fn set_callback<C: TriggerCallback>(&mut self, callback: C) {
+        self.callback = Some(Box::new(callback));
+    }
+
+    /// Returns a set of bodies that are currently inside the trigger.
+    pub fn overlapping(&self) -> &HashSet<Handle<Node>> {
+        &self.overlapping
+    }
+}
+
+/// Returns a body, that owns the given collider. Colliders are always direct children of bodies.
+fn owner_body(graph: &Graph, collider: Handle<Node>) -> Handle<Node> {
+    graph
+        .try_get(collider)
+        .map(|collider| collider.parent())
+        .unwrap_or_default()
+}
+
+fn collect_overlapping_bodies(graph: &Graph, trigger: Handle<Node>) -> HashSet<Handle<Node>> {
+    let mut bodies = HashSet::new();
+
+    let Some(node) = graph.try_get(trigger) else {
+        return bodies;
+    };
+
+    let mut add_pair = |this: Handle<Node>, a: Handle<Node>, b: Handle<Node>| {
+        let other = if a == this { b } else { a };
+        let body = owner_body(graph, other);
+        if body.is_some() && body != trigger {
+            bodies.insert(body);
+        }
+    };
+
+    for collider_handle in std::iter::once(trigger).chain(node.children().iter().cloned()) {
+        let Some(collider_node) = graph.try_get(collider_handle) else {
+            continue;
+        };
+
+        if let Some(collider) = collider_node.cast::<Collider>() {
+            for pair in collider.contacts(&graph.physics) {
+                if pair.has_any_active_contact {
+                    add_pair(collider_handle, pair.collider1, pair.collider2);
+                }
+            }
+            for pair in collider.intersects(&graph.physics) {
+                if pair.has_any_active_contact {
+                    add_pair(collider_handle, pair.collider1, pair.collider2);
+                }
+            }
+        } else if let Some(collider) = collider_node.cast::<dim2::collider::Collider>() {
+            for pair in collider.contacts(&graph.physics2d) {
+                if pair.has_any_active_contact {
+                    add_pair(collider_handle, pair.collider1, pair.collider2);
+                }
+            }
+            for pair in collider.intersects(&graph.physics2d) {
+                if pair.has_any_active_contact {
+                    add_pair(collider_handle, pair.collider1, pair.collider2);
+                }
+            }
+        }
+    }
+
+    bodies
+}
+
+impl ScriptTrait for TriggerVolume {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let current = collect_overlapping_bodies(&ctx.scene.graph, ctx.handle);
+
+        let entered = current
+            .difference(&self.overlapping)
+            .cloned()
+            .collect::<Vec<_>>();
+        let exited = self
+            .overlapping
+            .difference(&current)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        self.overlapping = current;
+
+        if let Some(callback) = self.callback.as_mut() {
+            for body in entered {
+                callback.on_enter(body, ctx);
+            }
+            for body in exited {
+                callback.on_exit(body, ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Pixel-by-pixel comparison of rendered frames with golden reference images, that is used to
+//! catch visual regressions. Frames must be captured from the frame buffers of the scenes (see
+//! [`crate::capture_scene`]), not from the back buffer. A missing reference must be reported as a
+//! failure, references are written only on an explicit request ("bless" mode), so a lost reference
+//! is never silently replaced with a broken frame.
+use image::{Rgba, RgbaImage};
+
+/// Color of the pixels of a diff image, that differ from the reference.
+const MISMATCH_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+#[derive(Debug)]
+pub struct Comparison {
+    /// Mean absolute error of the color channels of all the pixels, in `[0; 255]` range.
+    pub mean_absolute_error: f32,
+    /// Percentage of the pixels, that differ by more than the tolerance.
+    pub differing_percent: f32,
+    /// Dimmed grayscale copy of the reference image with the differing pixels in red.
+    pub diff: RgbaImage,
+}
+
+/// Compares the frame with the reference image. A pixel differs, if any of its color channels
+/// differs by more than `tolerance`. Alpha is ignored, because the frames are opaque. Returns
+/// `None` if the images have different sizes.
+pub fn compare(frame: &RgbaImage, reference: &RgbaImage, tolerance: u8) -> Option<Comparison> {
+    if frame.dimensions() != reference.dimensions() {
+        return None;
+    }
+
+    let mut diff = RgbaImage::new(frame.width(), frame.height());
+    let mut total_error = 0u64;
+    let mut differing = 0usize;
+    for ((actual, expected), diff) in frame
+        .pixels()
+        .zip(reference.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let mut max_error = 0u8;
+        for channel in 0..3 {
+            let error = actual[channel].abs_diff(expected[channel]);
+            total_error += error as u64;
+            max_error = max_error.max(error);
+        }
+
+        *diff = if max_error > tolerance {
+            differing += 1;
+            MISMATCH_COLOR
+        } else {
+            let luminance = (0.299 * expected[0] as f32
+                + 0.587 * expected[1] as f32
+                + 0.114 * expected[2] as f32)
+                * 0.5;
+            let luminance = luminance as u8;
+            Rgba([luminance, luminance, luminance, 255])
+        };
+    }
+
+    let pixel_count = (frame.width() as usize * frame.height() as usize).max(1);
+    Some(Comparison {
+        mean_absolute_error: total_error as f32 / (pixel_count * 3) as f32,
+        differing_percent: differing as f32 / pixel_count as f32 * 100.0,
+        diff,
+    })
+}
@@ -0,0 +1,37 @@
+//! Executor with your game connected to it as a plugin.
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
+use crafting::Game;
+use fyrox::{
+    dpi::LogicalSize,
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+    window::WindowAttributes,
+};
+
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+
+fn main() {
+    let mut window_attributes = WindowAttributes::default();
+    window_attributes.inner_size = Some(LogicalSize::new(1280.0, 720.0).into());
+    window_attributes.title = "Crafting".to_string();
+    window_attributes.resizable = true;
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            window_attributes,
+            vsync: false,
+            msaa_sample_count: None,
+        },
+    );
+    executor.add_plugin(Game::default());
+    #[cfg(feature = "record")]
+    if let Some(recorder) = Recorder::from_args() {
+        executor.add_plugin(recorder);
+    }
+    executor.add_plugin(FramePacer::default());
+    executor.run()
+}
@@ -0,0 +1,628 @@
+//! Game project.
+use crate::recipe::{CraftingData, ItemId, SLOT_COUNT};
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, io, log::Log, math::Rect, pool::Handle,
+        reflect::prelude::*, visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        image::{ImageBuilder, ImageMessage},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+    },
+    plugin::{Plugin, PluginContext},
+    resource::texture::{Texture, TextureResource},
+};
+use std::path::PathBuf;
+
+mod recipe;
+
+/// Size of the item sheet in cells.
+const ICON_COLUMNS: usize = 6;
+const ICON_ROWS: usize = 3;
+const ICON_SIZE: f32 = 48.0;
+
+const ERROR_COLOR: Color = Color::opaque(255, 80, 80);
+const INFO_COLOR: Color = Color::opaque(220, 220, 220);
+
+const SCENE_NAME: &str = "crafting";
+
+/// Directory with the textures, that are transcoded for the target platform by `build.rs`.
+const ASSET_PATH: &str = env!("ASSET_PATH");
+
+/// Returns the path of the transcoded copy of a texture from `data`.
+fn platform_texture_path(path: &str) -> PathBuf {
+    common_scripts::transcoded_texture_path(
+        path,
+        ASSET_PATH,
+        env!("ASSET_EXTENSION"),
+        env!("TRANSCODED_ASSETS"),
+    )
+}
+
+fn icon_uv_rect(icon: usize) -> Rect<f32> {
+    Rect::new(
+        (icon % ICON_COLUMNS) as f32 / ICON_COLUMNS as f32,
+        (icon / ICON_COLUMNS) as f32 / ICON_ROWS as f32,
+        1.0 / ICON_COLUMNS as f32,
+        1.0 / ICON_ROWS as f32,
+    )
+}
+
+fn make_icon(ctx: &mut BuildContext, texture: &TextureResource, column: usize) -> Handle<UiNode> {
+    ImageBuilder::new(
+        WidgetBuilder::new()
+            .on_column(column)
+            .with_width(ICON_SIZE)
+            .with_height(ICON_SIZE)
+            .with_margin(Thickness::uniform(2.0))
+            // Items are dropped on the slot, not on its content.
+            .with_hit_test_visibility(false)
+            .with_visibility(false),
+    )
+    .with_texture(texture.clone().into())
+    .build(ctx)
+}
+
+/// An ingredient slot, it holds some amount of a single item.
+#[derive(Default, Debug, Clone)]
+struct Slot {
+    widget: Handle<UiNode>,
+    icon: Handle<UiNode>,
+    amount_text: Handle<UiNode>,
+    content: Option<(ItemId, u32)>,
+}
+
+impl Slot {
+    fn new(ctx: &mut BuildContext, texture: &TextureResource, column: usize) -> Self {
+        let icon = make_icon(ctx, texture, 0);
+        let amount_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_margin(Thickness::uniform(3.0)),
+        )
+        .build(ctx);
+        let widget = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_column(column)
+                .with_margin(Thickness::uniform(4.0))
+                .with_allow_drop(true)
+                .with_background(Brush::Solid(Color::opaque(40, 40, 40)))
+                .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
+                .with_child(icon)
+                .with_child(amount_text),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        Self {
+            widget,
+            icon,
+            amount_text,
+            content: None,
+        }
+    }
+}
+
+/// Crafting, that is in progress.
+#[derive(Debug)]
+struct CraftingJob {
+    recipe: usize,
+    elapsed: f32,
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    debug_text: Handle<UiNode>,
+    inventory_list: Handle<UiNode>,
+    output_icon: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    craft_button: Handle<UiNode>,
+    clear_button: Handle<UiNode>,
+    status_text: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    data: CraftingData,
+    /// Amount of every item that the player has.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    inventory: Vec<u32>,
+    /// Widgets of the inventory list and the items they show.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    inventory_widgets: Vec<(Handle<UiNode>, ItemId)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    slots: Vec<Slot>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    job: Option<CraftingJob>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    items_texture: Option<TextureResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    fn set_data(&mut self, data: CraftingData, context: &mut PluginContext) {
+        self.inventory = vec![0; data.items.len()];
+        for (item, amount) in data.inventory.iter() {
+            self.inventory[*item] += amount;
+        }
+        self.data = data;
+        self.refresh_inventory(context);
+    }
+
+    fn set_status(&self, context: &PluginContext, text: &str, color: Color) {
+        let ui = context.user_interfaces.first();
+        ui.send_message(TextMessage::text(
+            self.status_text,
+            MessageDirection::ToWidget,
+            text.to_string(),
+        ));
+        ui.send_message(WidgetMessage::foreground(
+            self.status_text,
+            MessageDirection::ToWidget,
+            Brush::Solid(color),
+        ));
+    }
+
+    fn refresh_inventory(&mut self, context: &mut PluginContext) {
+        let Some(texture) = self.items_texture.clone() else {
+            return;
+        };
+        let ui = context.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+
+        self.inventory_widgets.clear();
+        for (item, amount) in self.inventory.iter().enumerate() {
+            if *amount == 0 {
+                continue;
+            }
+
+            let icon = make_icon(ctx, &texture, 0);
+            let widget = GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_allow_drag(true)
+                    .with_child(icon)
+                    .with_child(
+                        TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_column(1)
+                                .with_hit_test_visibility(false)
+                                .with_margin(Thickness::uniform(4.0))
+                                .with_vertical_alignment(VerticalAlignment::Center),
+                        )
+                        .with_text(format!("{} x{}", self.data.items[item].name, amount))
+                        .build(ctx),
+                    ),
+            )
+            .add_column(Column::auto())
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .build(ctx);
+            self.inventory_widgets.push((widget, item));
+
+            ui.send_message(ImageMessage::uv_rect(
+                icon,
+                MessageDirection::ToWidget,
+                icon_uv_rect(self.data.items[item].icon),
+            ));
+            ui.send_message(WidgetMessage::visibility(
+                icon,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+
+        ui.send_message(ListViewMessage::items(
+            self.inventory_list,
+            MessageDirection::ToWidget,
+            self.inventory_widgets
+                .iter()
+                .map(|(widget, _)| *widget)
+                .collect(),
+        ));
+    }
+
+    fn refresh_slot(&self, context: &PluginContext, slot: &Slot) {
+        let ui = context.user_interfaces.first();
+        if let Some((item, amount)) = slot.content {
+            ui.send_message(ImageMessage::uv_rect(
+                slot.icon,
+                MessageDirection::ToWidget,
+                icon_uv_rect(self.data.items[item].icon),
+            ));
+            ui.send_message(TextMessage::text(
+                slot.amount_text,
+                MessageDirection::ToWidget,
+                format!("x{amount}"),
+            ));
+        } else {
+            ui.send_message(TextMessage::text(
+                slot.amount_text,
+                MessageDirection::ToWidget,
+                Default::default(),
+            ));
+        }
+        ui.send_message(WidgetMessage::visibility(
+            slot.icon,
+            MessageDirection::ToWidget,
+            slot.content.is_some(),
+        ));
+    }
+
+    /// Moves a single item from the inventory into the slot.
+    fn drop_item(&mut self, context: &mut PluginContext, slot_index: usize, item: ItemId) {
+        if self.job.is_some() {
+            return;
+        }
+
+        if self.inventory[item] == 0 {
+            self.set_status(
+                context,
+                &format!("There's no {} left.", self.data.items[item].name),
+                ERROR_COLOR,
+            );
+            return;
+        }
+
+        let slot = &mut self.slots[slot_index];
+        let amount = match slot.content {
+            Some((current, amount)) if current == item => amount,
+            Some((current, amount)) => {
+                // The slot holds a single kind of items, previous items go back to the inventory.
+                self.inventory[current] += amount;
+                0
+            }
+            None => 0,
+        };
+        slot.content = Some((item, amount + 1));
+        self.inventory[item] -= 1;
+
+        self.refresh_slot(context, &self.slots[slot_index]);
+        self.refresh_inventory(context);
+        self.set_status(context, "", INFO_COLOR);
+    }
+
+    fn clear_slots(&mut self, context: &mut PluginContext, return_to_inventory: bool) {
+        for slot in self.slots.iter_mut() {
+            if let Some((item, amount)) = slot.content.take() {
+                if return_to_inventory {
+                    self.inventory[item] += amount;
+                }
+            }
+        }
+        for slot in self.slots.iter() {
+            self.refresh_slot(context, slot);
+        }
+        self.refresh_inventory(context);
+    }
+
+    fn start_crafting(&mut self, context: &mut PluginContext) {
+        if self.job.is_some() {
+            return;
+        }
+
+        let ingredients = self
+            .slots
+            .iter()
+            .filter_map(|slot| slot.content)
+            .collect::<Vec<_>>();
+        if ingredients.is_empty() {
+            self.set_status(
+                context,
+                "Drag ingredients from the inventory into the slots.",
+                ERROR_COLOR,
+            );
+            return;
+        }
+
+        let Some(recipe) = self.data.find_recipe(&ingredients) else {
+            self.set_status(
+                context,
+                "These ingredients do not match any recipe.",
+                ERROR_COLOR,
+            );
+            return;
+        };
+
+        self.job = Some(CraftingJob {
+            recipe,
+            elapsed: 0.0,
+        });
+        let output = &self.data.items[self.data.recipes[recipe].output];
+        self.set_status(context, &format!("Crafting {}...", output.name), INFO_COLOR);
+        for button in [self.craft_button, self.clear_button] {
+            context
+                .user_interfaces
+                .first()
+                .send_message(WidgetMessage::enabled(
+                    button,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+        }
+    }
+
+    fn update_crafting(&mut self, context: &mut PluginContext) {
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+
+        let recipe = &self.data.recipes[job.recipe];
+        job.elapsed += context.dt;
+        let progress = (job.elapsed / recipe.craft_time).min(1.0);
+        context
+            .user_interfaces
+            .first()
+            .send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                progress,
+            ));
+        if progress < 1.0 {
+            return;
+        }
+
+        // The ingredients are consumed, the output goes to the inventory.
+        let output = recipe.output;
+        self.job = None;
+        self.inventory[output] += 1;
+        self.clear_slots(context, false);
+
+        let ui = context.user_interfaces.first();
+        ui.send_message(ImageMessage::uv_rect(
+            self.output_icon,
+            MessageDirection::ToWidget,
+            icon_uv_rect(self.data.items[output].icon),
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.output_icon,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        for button in [self.craft_button, self.clear_button] {
+            ui.send_message(WidgetMessage::enabled(
+                button,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+        self.set_status(
+            context,
+            &format!("Crafted {}!", self.data.items[output].name),
+            INFO_COLOR,
+        );
+    }
+
+    fn build_ui(&mut self, context: &mut PluginContext, texture: &TextureResource) {
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        self.inventory_list = ListViewBuilder::new(WidgetBuilder::new()).build(ctx);
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_height(400.0)
+                .with_desired_position(Vector2::new(5.0, 60.0)),
+        )
+        .with_title(WindowTitle::text("Inventory"))
+        .can_close(false)
+        .with_content(self.inventory_list)
+        .build(ctx);
+
+        self.slots = (0..SLOT_COUNT)
+            .map(|column| Slot::new(ctx, texture, column))
+            .collect();
+        let equals = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_column(SLOT_COUNT)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_horizontal_alignment(HorizontalAlignment::Center),
+        )
+        .with_text("=")
+        .build(ctx);
+        self.output_icon = make_icon(ctx, texture, 0);
+        let output_slot = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_column(SLOT_COUNT + 1)
+                .with_margin(Thickness::uniform(4.0))
+                .with_background(Brush::Solid(Color::opaque(40, 60, 40)))
+                .with_foreground(Brush::Solid(Color::opaque(120, 160, 120)))
+                .with_child(self.output_icon),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        let mut slot_row = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_children(self.slots.iter().map(|slot| slot.widget))
+                .with_child(equals)
+                .with_child(output_slot),
+        )
+        .add_row(Row::strict(ICON_SIZE + 12.0));
+        for _ in 0..SLOT_COUNT {
+            slot_row = slot_row.add_column(Column::strict(ICON_SIZE + 12.0));
+        }
+        let slot_row = slot_row
+            .add_column(Column::strict(30.0))
+            .add_column(Column::strict(ICON_SIZE + 12.0))
+            .build(ctx);
+
+        self.progress_bar = ProgressBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_height(20.0)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .build(ctx);
+
+        self.craft_button = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_column(0)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .with_text("Craft")
+        .build(ctx);
+        self.clear_button = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .with_text("Clear")
+        .build(ctx);
+        let buttons = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_child(self.craft_button)
+                .with_child(self.clear_button),
+        )
+        .add_column(Column::stretch())
+        .add_column(Column::stretch())
+        .add_row(Row::strict(30.0))
+        .build(ctx);
+
+        self.status_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(3)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .with_wrap(WrapMode::Word)
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(340.0)
+                .with_height(220.0)
+                .with_desired_position(Vector2::new(270.0, 60.0)),
+        )
+        .with_title(WindowTitle::text("Crafting"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(slot_row)
+                    .with_child(self.progress_bar)
+                    .with_child(buttons)
+                    .with_child(self.status_text),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, mut context: PluginContext) {
+        let texture = context
+            .resource_manager
+            .request::<Texture>(platform_texture_path("data/items.png"));
+        self.build_ui(&mut context, &texture);
+        self.items_texture = Some(texture);
+
+        context.task_pool.spawn_plugin_task(
+            io::load_file("data/recipes.ron"),
+            |result, game: &mut Game, ctx| {
+                let data = result
+                    .map_err(|err| format!("{err:?}"))
+                    .and_then(|bytes| CraftingData::from_bytes(&bytes));
+                match data {
+                    Ok(data) => game.set_data(data, ctx),
+                    Err(err) => {
+                        let message = format!("Unable to load recipes. Reason: {err}");
+                        game.set_status(ctx, &message, ERROR_COLOR);
+                        Log::err(message);
+                    }
+                }
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.update_crafting(context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Crafting\nDrag items from the inventory into the slots and \
+                        press Craft.\nFPS: {}\n{}",
+                        graphics_context.renderer.get_statistics().frames_per_second,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(WidgetMessage::Drop(dropped)) = message.data() {
+            if message.direction() != MessageDirection::FromWidget {
+                return;
+            }
+            let slot = self
+                .slots
+                .iter()
+                .position(|slot| slot.widget == message.destination());
+            let item = self
+                .inventory_widgets
+                .iter()
+                .find(|(widget, _)| widget == dropped)
+                .map(|(_, item)| *item);
+            if let (Some(slot), Some(item)) = (slot, item) {
+                self.drop_item(context, slot, item);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.craft_button {
+                self.start_crafting(context);
+            } else if message.destination() == self.clear_button {
+                self.clear_slots(context, true);
+                self.set_status(context, "", INFO_COLOR);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Items and crafting recipes, they're loaded from `data/recipes.ron`.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Index of an item in [`CraftingData::items`].
+pub type ItemId = usize;
+
+/// Amount of ingredient slots.
+pub const SLOT_COUNT: usize = 3;
+
+#[derive(Deserialize, Debug)]
+pub struct Item {
+    pub name: String,
+    /// Index of a cell in the item sheet.
+    pub icon: usize,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Recipe {
+    /// Ingredients and their amounts.
+    pub inputs: Vec<(ItemId, u32)>,
+    pub output: ItemId,
+    /// Time (in seconds) that it takes to craft the item.
+    pub craft_time: f32,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct CraftingData {
+    pub items: Vec<Item>,
+    /// Items that the player has at the start.
+    pub inventory: Vec<(ItemId, u32)>,
+    pub recipes: Vec<Recipe>,
+}
+
+/// Sums amounts of the same items, so ingredients could be compared regardless of their order.
+fn total_amounts(ingredients: impl IntoIterator<Item = (ItemId, u32)>) -> BTreeMap<ItemId, u32> {
+    let mut amounts = BTreeMap::new();
+    for (item, amount) in ingredients {
+        *amounts.entry(item).or_default() += amount;
+    }
+    amounts
+}
+
+impl CraftingData {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let data = ron::de::from_bytes::<Self>(bytes).map_err(|err| err.to_string())?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let check_item = |item: ItemId| {
+            if item < self.items.len() {
+                Ok(())
+            } else {
+                Err(format!("Item {item} does not exist."))
+            }
+        };
+
+        for (item, _) in self.inventory.iter() {
+            check_item(*item)?;
+        }
+
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            check_item(recipe.output)?;
+            for (item, _) in recipe.inputs.iter() {
+                check_item(*item)?;
+            }
+            if recipe.inputs.is_empty() || recipe.inputs.len() > SLOT_COUNT {
+                return Err(format!(
+                    "Recipe {index} must have from 1 to {SLOT_COUNT} ingredients."
+                ));
+            }
+            if recipe.craft_time <= 0.0 {
+                return Err(format!("Craft time of recipe {index} must be positive."));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an index of the recipe, that needs exactly the given ingredients.
+    pub fn find_recipe(&self, ingredients: &[(ItemId, u32)]) -> Option<usize> {
+        let ingredients = total_amounts(ingredients.iter().cloned());
+        self.recipes
+            .iter()
+            .position(|recipe| total_amounts(recipe.inputs.iter().cloned()) == ingredients)
+    }
+}
@@ -0,0 +1,463 @@
+//! Crowd of autonomous agents, that walk to random goals on a navigational mesh. Every agent
+//! follows its path with arrive steering, keeps distance from its neighbours (found with a spatial
+//! hash grid) and steers away from the walls, that are in front of it. Neighbours are avoided
+//! either with separation steering or with reciprocal velocity obstacles. Agents are updated in
+//! parallel.
+use crate::rvo::{self, Neighbor, NEIGHBOR_RADIUS};
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::{Color, Hsv},
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    rand::{thread_rng, Rng},
+    scene::{base::BaseBuilder, graph::Graph, node::Node, sprite::SpriteBuilder},
+    utils::navmesh::Navmesh,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Amount of cells of the walkability grid along each side.
+pub const GRID_SIZE: usize = 40;
+/// Size of a cell of the walkability grid in meters.
+pub const CELL_SIZE: f32 = 1.0;
+/// Blocked rectangles of the grid - `(x, y, width, height)` in cells.
+pub const OBSTACLES: [(usize, usize, usize, usize); 6] = [
+    (6, 6, 4, 12),
+    (18, 3, 3, 12),
+    (26, 18, 9, 3),
+    (8, 26, 14, 3),
+    (30, 28, 3, 8),
+    (16, 18, 4, 4),
+];
+
+const MAX_SPEED: f32 = 2.5;
+const MAX_FORCE: f32 = 8.0;
+/// Agents start to slow down at this distance from their goal.
+const SLOW_RADIUS: f32 = 2.0;
+/// An agent switches to the next point of its path, when it is closer than this to the current.
+const WAYPOINT_RADIUS: f32 = 0.5;
+/// Agents closer than this push each other away, it is also the size of a cell of the hash grid.
+const SEPARATION_RADIUS: f32 = 0.6;
+const SEPARATION_WEIGHT: f32 = 1.5;
+/// Length of the ray, that is cast in the direction of movement to find walls.
+const WALL_LOOKAHEAD: f32 = 1.0;
+const WALL_RAY_STEP: f32 = 0.1;
+const WALL_WEIGHT: f32 = 6.0;
+const AGENT_SIZE: f32 = 0.2;
+/// Radius of the circle, that an agent occupies. Agents, that are closer than two radii, collide.
+const AGENT_RADIUS: f32 = 0.15;
+/// Height of the agents above the floor.
+const AGENT_HEIGHT: f32 = 0.25;
+
+#[derive(Debug, Clone)]
+pub struct Agent {
+    node: Handle<Node>,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    path: Vec<Vector2<f32>>,
+    /// Index of the point of the path, the agent walks to.
+    waypoint: usize,
+}
+
+impl Agent {
+    fn has_arrived(&self) -> bool {
+        self.path
+            .last()
+            .map_or(true, |goal| (goal - self.position).norm() < WAYPOINT_RADIUS)
+    }
+}
+
+/// Key of a cell of the spatial hash grid.
+fn hash_cell(position: Vector2<f32>) -> (i32, i32) {
+    (
+        (position.x / SEPARATION_RADIUS).floor() as i32,
+        (position.y / SEPARATION_RADIUS).floor() as i32,
+    )
+}
+
+fn is_blocked_cell(x: usize, y: usize) -> bool {
+    OBSTACLES
+        .iter()
+        .any(|&(ox, oy, w, h)| x >= ox && x < ox + w && y >= oy && y < oy + h)
+}
+
+/// Converts a position on the floor to the cell of the walkability grid, the grid is centered at
+/// the origin.
+fn grid_cell(position: Vector2<f32>) -> Option<(usize, usize)> {
+    let half = GRID_SIZE as f32 * CELL_SIZE * 0.5;
+    let x = ((position.x + half) / CELL_SIZE).floor();
+    let y = ((position.y + half) / CELL_SIZE).floor();
+    (x >= 0.0 && y >= 0.0 && x < GRID_SIZE as f32 && y < GRID_SIZE as f32)
+        .then_some((x as usize, y as usize))
+}
+
+/// Returns `true` if the point is outside of the floor or inside of an obstacle.
+pub fn is_blocked(position: Vector2<f32>) -> bool {
+    grid_cell(position).map_or(true, |(x, y)| is_blocked_cell(x, y))
+}
+
+/// Center of the cell of the walkability grid.
+pub fn cell_center(x: usize, y: usize) -> Vector2<f32> {
+    let half = GRID_SIZE as f32 * CELL_SIZE * 0.5;
+    Vector2::new(
+        (x as f32 + 0.5) * CELL_SIZE - half,
+        (y as f32 + 0.5) * CELL_SIZE - half,
+    )
+}
+
+/// Builds the navigational mesh of the floor - two triangles for every walkable cell.
+fn build_navmesh() -> Navmesh {
+    let half = GRID_SIZE as f32 * CELL_SIZE * 0.5;
+    let row = GRID_SIZE + 1;
+    let vertices = (0..row * row)
+        .map(|index| {
+            Vector3::new(
+                (index % row) as f32 * CELL_SIZE - half,
+                0.0,
+                (index / row) as f32 * CELL_SIZE - half,
+            )
+        })
+        .collect();
+    let mut triangles = Vec::new();
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            if is_blocked_cell(x, y) {
+                continue;
+            }
+            let corner = (y * row + x) as u32;
+            let (a, b, c, d) = (
+                corner,
+                corner + 1,
+                corner + row as u32 + 1,
+                corner + row as u32,
+            );
+            triangles.push(TriangleDefinition([a, b, c]));
+            triangles.push(TriangleDefinition([c, d, a]));
+        }
+    }
+    Navmesh::new(triangles, vertices)
+}
+
+/// Casts a ray along the direction and returns the first blocked point and the distance to it.
+fn cast_wall_ray(
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    length: f32,
+) -> Option<(Vector2<f32>, f32)> {
+    let mut distance = WALL_RAY_STEP;
+    while distance <= length {
+        let point = origin + direction.scale(distance);
+        if is_blocked(point) {
+            return Some((point, distance));
+        }
+        distance += WALL_RAY_STEP;
+    }
+    None
+}
+
+/// Positions and velocities of all the agents at the beginning of the frame with the spatial hash
+/// grid of the agents.
+struct CrowdState {
+    positions: Vec<Vector2<f32>>,
+    velocities: Vec<Vector2<f32>>,
+    hash_grid: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl CrowdState {
+    /// Calls the function for every other agent within the radius with its index, offset from the
+    /// agent to it and distance.
+    fn for_each_neighbour(
+        &self,
+        index: usize,
+        radius: f32,
+        mut func: impl FnMut(usize, Vector2<f32>, f32),
+    ) {
+        let position = self.positions[index];
+        let (cx, cy) = hash_cell(position);
+        let range = (radius / SEPARATION_RADIUS).ceil() as i32;
+        for x in cx - range..=cx + range {
+            for y in cy - range..=cy + range {
+                let Some(neighbours) = self.hash_grid.get(&(x, y)) else {
+                    continue;
+                };
+                for &other in neighbours {
+                    if other == index {
+                        continue;
+                    }
+                    let offset = self.positions[other] - position;
+                    let distance = offset.norm();
+                    if distance < radius {
+                        func(other, offset, distance);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the velocity, that the agent wants to have - it walks to the current point of the path
+/// at full speed and slows down at the goal.
+fn arrive(agent: &mut Agent) -> Vector2<f32> {
+    let Some(target) = agent.path.get(agent.waypoint).copied() else {
+        return Vector2::default();
+    };
+    let offset = target - agent.position;
+    let distance = offset.norm();
+    let is_last = agent.waypoint + 1 == agent.path.len();
+    if distance < WAYPOINT_RADIUS && !is_last {
+        agent.waypoint += 1;
+    }
+    if distance <= f32::EPSILON {
+        return Vector2::default();
+    }
+    let speed = if is_last {
+        MAX_SPEED * (distance / SLOW_RADIUS).min(1.0)
+    } else {
+        MAX_SPEED
+    };
+    offset.scale(speed / distance)
+}
+
+/// Wall avoidance - steers away from the wall in front of the agent.
+fn avoid_walls(agent: &Agent) -> Vector2<f32> {
+    let Some(direction) = agent.velocity.try_normalize(f32::EPSILON) else {
+        return Vector2::default();
+    };
+    let Some((hit, distance)) = cast_wall_ray(agent.position, direction, WALL_LOOKAHEAD) else {
+        return Vector2::default();
+    };
+    grid_cell(hit)
+        .and_then(|(x, y)| (agent.position - cell_center(x, y)).try_normalize(f32::EPSILON))
+        .map_or(Vector2::default(), |away| {
+            away.scale(WALL_WEIGHT * (1.0 - distance / WALL_LOOKAHEAD))
+        })
+}
+
+fn clamp_speed(velocity: Vector2<f32>) -> Vector2<f32> {
+    if velocity.norm() > MAX_SPEED {
+        velocity.normalize().scale(MAX_SPEED)
+    } else {
+        velocity
+    }
+}
+
+/// Computes the new velocity of the agent from its steering forces and moves the agent. Returns
+/// the amount of the agents with greater indices, that the agent collides with, so every
+/// collision is counted once.
+fn steer(agent: &mut Agent, index: usize, state: &CrowdState, use_rvo: bool, dt: f32) -> usize {
+    let desired_velocity = arrive(agent);
+
+    if use_rvo {
+        // The walls are not velocity obstacles, so they only change the preferred velocity.
+        let preferred_velocity =
+            clamp_speed(desired_velocity + avoid_walls(agent).scale(MAX_SPEED / WALL_WEIGHT));
+        let mut neighbors = Vec::new();
+        state.for_each_neighbour(index, NEIGHBOR_RADIUS, |other, _, _| {
+            neighbors.push(Neighbor {
+                position: state.positions[other],
+                velocity: state.velocities[other],
+            })
+        });
+        agent.velocity = rvo::compute_velocity(
+            agent.position,
+            agent.velocity,
+            preferred_velocity,
+            AGENT_RADIUS * 2.0,
+            MAX_SPEED,
+            &neighbors,
+        );
+    } else {
+        let mut force = desired_velocity - agent.velocity;
+
+        // Separation - push away from the neighbours, the closer the neighbour the stronger the
+        // push.
+        state.for_each_neighbour(index, SEPARATION_RADIUS, |_, offset, distance| {
+            if distance > f32::EPSILON {
+                force -= offset.scale(
+                    SEPARATION_WEIGHT * MAX_SPEED * (SEPARATION_RADIUS - distance)
+                        / (SEPARATION_RADIUS * distance),
+                );
+            }
+        });
+
+        force += avoid_walls(agent);
+
+        if force.norm() > MAX_FORCE {
+            force = force.normalize().scale(MAX_FORCE);
+        }
+        agent.velocity = clamp_speed(agent.velocity + force.scale(dt));
+    }
+
+    let mut collisions = 0;
+    state.for_each_neighbour(index, AGENT_RADIUS * 2.0, |other, _, _| {
+        if other > index {
+            collisions += 1;
+        }
+    });
+
+    // Slide along the walls instead of going through them.
+    let step = agent.velocity.scale(dt);
+    for candidate in [step, Vector2::new(step.x, 0.0), Vector2::new(0.0, step.y)] {
+        if !is_blocked(agent.position + candidate) {
+            agent.position += candidate;
+            return collisions;
+        }
+    }
+    agent.velocity = Vector2::default();
+    collisions
+}
+
+#[derive(Debug)]
+pub struct Crowd {
+    navmesh: Navmesh,
+    agents: Vec<Agent>,
+    /// Avoid the neighbours with reciprocal velocity obstacles instead of separation steering.
+    pub use_rvo: bool,
+    average_speed: f32,
+    collision_count: usize,
+}
+
+impl Default for Crowd {
+    fn default() -> Self {
+        Self {
+            navmesh: build_navmesh(),
+            agents: Default::default(),
+            use_rvo: false,
+            average_speed: 0.0,
+            collision_count: 0,
+        }
+    }
+}
+
+impl Crowd {
+    pub fn agent_count(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Average speed of the agents in the last update, in meters per second.
+    pub fn average_speed(&self) -> f32 {
+        self.average_speed
+    }
+
+    /// Amount of the pairs of the agents, that overlapped in the last update.
+    pub fn collision_count(&self) -> usize {
+        self.collision_count
+    }
+
+    fn random_walkable_point() -> Vector2<f32> {
+        let mut rng = thread_rng();
+        loop {
+            let (x, y) = (rng.gen_range(0..GRID_SIZE), rng.gen_range(0..GRID_SIZE));
+            if !is_blocked_cell(x, y) {
+                return cell_center(x, y);
+            }
+        }
+    }
+
+    /// Finds a path on the navigational mesh from the agent to a random goal.
+    fn pick_new_goal(&mut self, index: usize) {
+        let from = self.agents[index].position;
+        let to = Self::random_walkable_point();
+        let mut path = Vec::new();
+        let _ = self.navmesh.build_path(
+            Vector3::new(from.x, 0.0, from.y),
+            Vector3::new(to.x, 0.0, to.y),
+            &mut path,
+        );
+        let mut path = path
+            .into_iter()
+            .map(|point| Vector2::new(point.x, point.z))
+            .collect::<Vec<_>>();
+        // Make sure the path goes from the agent to the goal.
+        if let (Some(first), Some(last)) = (path.first(), path.last()) {
+            if (first - from).norm() > (last - from).norm() {
+                path.reverse();
+            }
+        }
+
+        let agent = &mut self.agents[index];
+        agent.path = path;
+        agent.waypoint = 0;
+    }
+
+    /// Adds or removes agents, so the crowd has the given amount of them.
+    pub fn set_agent_count(&mut self, graph: &mut Graph, count: usize) {
+        while self.agents.len() > count {
+            if let Some(agent) = self.agents.pop() {
+                graph.remove_node(agent.node);
+            }
+        }
+
+        let mut rng = thread_rng();
+        while self.agents.len() < count {
+            let position = Self::random_walkable_point()
+                + Vector2::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3));
+            let node = SpriteBuilder::new(BaseBuilder::new())
+                .with_size(AGENT_SIZE)
+                .with_color(Color::from(Hsv::new(
+                    rng.gen_range(0.0..360.0),
+                    70.0,
+                    100.0,
+                )))
+                .build(graph);
+            self.agents.push(Agent {
+                node,
+                position,
+                velocity: Default::default(),
+                path: Default::default(),
+                waypoint: 0,
+            });
+            self.pick_new_goal(self.agents.len() - 1);
+        }
+    }
+
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        let mut state = CrowdState {
+            positions: self.agents.iter().map(|agent| agent.position).collect(),
+            velocities: self.agents.iter().map(|agent| agent.velocity).collect(),
+            hash_grid: HashMap::new(),
+        };
+        for (index, position) in state.positions.iter().enumerate() {
+            state
+                .hash_grid
+                .entry(hash_cell(*position))
+                .or_default()
+                .push(index);
+        }
+
+        let use_rvo = self.use_rvo;
+        self.collision_count = self
+            .agents
+            .par_iter_mut()
+            .enumerate()
+            .map(|(index, agent)| steer(agent, index, &state, use_rvo, dt))
+            .sum();
+        self.average_speed = if self.agents.is_empty() {
+            0.0
+        } else {
+            self.agents
+                .iter()
+                .map(|agent| agent.velocity.norm())
+                .sum::<f32>()
+                / self.agents.len() as f32
+        };
+
+        for index in 0..self.agents.len() {
+            if self.agents[index].has_arrived() {
+                self.pick_new_goal(index);
+            }
+        }
+
+        for agent in self.agents.iter() {
+            if let Some(node) = graph.try_get_mut(agent.node) {
+                node.local_transform_mut().set_position(Vector3::new(
+                    agent.position.x,
+                    AGENT_HEIGHT,
+                    agent.position.y,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Game project.
+use crate::crowd::{Crowd, CELL_SIZE, GRID_SIZE, OBSTACLES};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        instant::Instant,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+mod crowd;
+mod rvo;
+
+const MAX_AGENTS: usize = 500;
+/// Height of the obstacles.
+const WALL_HEIGHT: f32 = 1.0;
+/// Weight of a new sample in the moving average of the simulation time.
+const TIME_SMOOTHING: f32 = 0.05;
+
+const SCENE_NAME: &str = "crowd";
+
+#[derive(Visit, Reflect, Debug, Default)]
+pub struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    count_slider: Handle<UiNode>,
+    rvo_check_box: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    crowd: Crowd,
+    /// Average time of the update of the crowd, in seconds.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    simulation_time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_box(graph: &mut Graph, center: Vector3<f32>, size: Vector3<f32>) {
+    MeshBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(center).build()),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&size)),
+    ))
+    .with_material(MaterialResource::new_ok(
+        ResourceKind::Embedded,
+        Material::standard(),
+    ))
+    .build()])
+    .build(graph);
+}
+
+impl Plugin for Game {
+    fn register(&self, _context: PluginRegistrationContext) {}
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 32.0, -30.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        50.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        let floor_size = GRID_SIZE as f32 * CELL_SIZE;
+        make_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.05, 0.0),
+            Vector3::new(floor_size, 0.1, floor_size),
+        );
+        for (x, y, width, height) in OBSTACLES {
+            let min = crowd::cell_center(x, y) - Vector2::repeat(CELL_SIZE * 0.5);
+            let size = Vector2::new(width as f32, height as f32).scale(CELL_SIZE);
+            make_box(
+                &mut scene.graph,
+                Vector3::new(
+                    min.x + size.x * 0.5,
+                    WALL_HEIGHT * 0.5,
+                    min.y + size.y * 0.5,
+                ),
+                Vector3::new(size.x, WALL_HEIGHT, size.y),
+            );
+        }
+
+        self.crowd.set_agent_count(&mut scene.graph, MAX_AGENTS);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let count_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("Agent Count")
+        .build(ctx);
+        self.count_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.0)
+        .with_max(MAX_AGENTS as f32)
+        .with_step(1.0)
+        .with_value(MAX_AGENTS as f32)
+        .with_value_precision(0)
+        .show_value(true)
+        .build(ctx);
+        self.rvo_check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("RVO Avoidance")
+                .build(ctx),
+        )
+        .checked(Some(self.crowd.use_rvo))
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(116.0)
+                .with_desired_position(Vector2::new(5.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Crowd Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(count_text)
+                    .with_child(self.count_slider)
+                    .with_child(self.rvo_check_box),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            let start = Instant::now();
+            self.crowd.update(&mut scene.graph, context.dt);
+            let time = start.elapsed().as_secs_f32();
+            self.simulation_time += (time - self.simulation_time) * TIME_SMOOTHING;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Crowd Simulation\nAgents: {}\nAvoidance: {}\n\
+                        Average Speed: {:.2} m/s\nCollisions: {}\nFPS: {}\n\
+                        Simulation Time: {:.3} ms\nFrame Time: {:.3} ms\n{}\n{}",
+                        self.crowd.agent_count(),
+                        if self.crowd.use_rvo {
+                            "RVO"
+                        } else {
+                            "Separation"
+                        },
+                        self.crowd.average_speed(),
+                        self.crowd.collision_count(),
+                        statistics.frames_per_second,
+                        self.simulation_time * 1000.0,
+                        statistics.pure_frame_time * 1000.0,
+                        statistics,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.rvo_check_box {
+                self.crowd.use_rvo = *value;
+            }
+            return;
+        }
+
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.destination() != self.count_slider {
+            return;
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.crowd
+                .set_agent_count(&mut scene.graph, value.round() as usize);
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Reciprocal velocity obstacles (RVO). Every neighbour forms a cone of velocities in velocity
+//! space, that lead to a collision with it within the time horizon. The apex of the cone is moved to
+//! the average of the velocities of both agents, so each agent takes half of the responsibility
+//! for avoiding the collision and the agents do not oscillate. The new velocity is the sampled
+//! velocity, that is the closest to the preferred one and outside of all the cones. When there's no
+//! such velocity, the velocities, that lead to sooner collisions, are penalized more.
+use fyrox::core::algebra::Vector2;
+
+/// Only the agents within this distance are taken into account.
+pub const NEIGHBOR_RADIUS: f32 = 2.0;
+/// Collisions, that happen later than this (in seconds), are ignored.
+const TIME_HORIZON: f32 = 2.0;
+/// Amount of directions and speeds of the sampled velocities.
+const SAMPLE_DIRECTIONS: usize = 16;
+const SAMPLE_SPEEDS: usize = 4;
+/// Weight of the time to collision in the penalty of a velocity, that is inside of a cone.
+const COLLISION_WEIGHT: f32 = 2.0;
+
+/// Position and velocity of a neighbour of an agent.
+pub struct Neighbor {
+    pub position: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+}
+
+/// Returns time, when the circle of the given radius at `relative_position` is hit by a point, that
+/// moves from the origin with `relative_velocity`. Returns zero if the point is already inside of
+/// the circle.
+fn time_to_collision(
+    relative_position: Vector2<f32>,
+    relative_velocity: Vector2<f32>,
+    radius: f32,
+) -> Option<f32> {
+    let c = relative_position.norm_squared() - radius * radius;
+    if c < 0.0 {
+        return Some(0.0);
+    }
+    let a = relative_velocity.norm_squared();
+    if a <= f32::EPSILON {
+        return None;
+    }
+    let b = relative_position.dot(&relative_velocity);
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let time = (b - discriminant.sqrt()) / a;
+    (time >= 0.0).then_some(time)
+}
+
+/// Velocities, that are checked against the obstacles - the preferred velocity, no movement and
+/// a few speeds in uniformly distributed directions.
+fn sample_velocities(
+    preferred_velocity: Vector2<f32>,
+    max_speed: f32,
+) -> impl Iterator<Item = Vector2<f32>> {
+    let samples = (0..SAMPLE_DIRECTIONS).flat_map(move |direction| {
+        let angle = direction as f32 / SAMPLE_DIRECTIONS as f32 * std::f32::consts::TAU;
+        (1..=SAMPLE_SPEEDS).map(move |speed| {
+            Vector2::new(angle.cos(), angle.sin())
+                .scale(max_speed * speed as f32 / SAMPLE_SPEEDS as f32)
+        })
+    });
+    [preferred_velocity, Vector2::default()]
+        .into_iter()
+        .chain(samples)
+}
+
+/// Chooses the new velocity of the agent. `radius` is the sum of the radii of two agents.
+pub fn compute_velocity(
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    preferred_velocity: Vector2<f32>,
+    radius: f32,
+    max_speed: f32,
+    neighbors: &[Neighbor],
+) -> Vector2<f32> {
+    let mut best_velocity = Vector2::default();
+    let mut best_penalty = f32::MAX;
+    for candidate in sample_velocities(preferred_velocity, max_speed) {
+        // The earliest collision with any of the neighbours, if the agent moves with the
+        // candidate velocity and the neighbours keep their velocities.
+        let collision_time = neighbors
+            .iter()
+            .filter_map(|neighbor| {
+                time_to_collision(
+                    neighbor.position - position,
+                    candidate.scale(2.0) - velocity - neighbor.velocity,
+                    radius,
+                )
+            })
+            .filter(|&time| time < TIME_HORIZON)
+            .fold(f32::MAX, f32::min);
+
+        let mut penalty = (preferred_velocity - candidate).norm();
+        if collision_time < f32::MAX {
+            penalty += COLLISION_WEIGHT / collision_time.max(f32::EPSILON);
+        }
+        if penalty < best_penalty {
+            best_penalty = penalty;
+            best_velocity = candidate;
+        }
+    }
+    best_velocity
+}
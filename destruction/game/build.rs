@@ -0,0 +1,4 @@
+// Transcodes the textures, that are loaded from code, to a compressed format of the target platform.
+fn main() {
+    asset_pipeline::run(&["data/Crate.png"]);
+}
@@ -0,0 +1,179 @@
+use crate::{make_box, Game};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::MaterialResource,
+    rand::{thread_rng, Rng},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        collider::Collider,
+        rigidbody::{RigidBody, RigidBodyType},
+        sound::{SoundBufferResource, SoundBuilder, Status},
+        transform::TransformBuilder,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Breaks a rigid body into a set of shards, when it receives a hit strong enough.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8c0f5a2e-3d41-4f6b-b7e9-52a1c6d0e4f3")]
+#[visit(optional)]
+pub struct Destructible {
+    /// Minimal contact impulse required to break the body.
+    pub break_threshold: InheritableVariable<f32>,
+    /// A prefab of a single shard. If not set or not loaded, shards will be generated as small
+    /// boxes.
+    pub shard_prefab: InheritableVariable<Option<ModelResource>>,
+    pub shard_count: InheritableVariable<u32>,
+    /// Maximum impulse applied to each shard, away from the center of the body.
+    pub shard_impulse: InheritableVariable<f32>,
+    pub destruction_sound: InheritableVariable<Option<SoundBufferResource>>,
+    /// Material of generated shards.
+    pub shard_material: InheritableVariable<Option<MaterialResource>>,
+}
+
+impl Default for Destructible {
+    fn default() -> Self {
+        Self {
+            break_threshold: 8.0.into(),
+            shard_prefab: Default::default(),
+            shard_count: 8.into(),
+            shard_impulse: 2.0.into(),
+            destruction_sound: Default::default(),
+            shard_material: Default::default(),
+        }
+    }
+}
+
+impl Destructible {
+    /// Returns the strongest contact impulse of any collider of the body and a world-space point
+    /// where it was applied. The contact points are stored in the local space of both colliders of
+    /// a pair, the point in the space of the collider of the body is taken.
+    fn strongest_impact(&self, ctx: &ScriptContext) -> Option<(f32, Vector3<f32>)> {
+        let graph = &ctx.scene.graph;
+        let mut strongest: Option<(f32, Vector3<f32>)> = None;
+
+        for child in graph[ctx.handle].children() {
+            let Some(collider) = graph.try_get_of_type::<Collider>(*child) else {
+                continue;
+            };
+
+            for pair in collider.contacts(&graph.physics) {
+                let is_first = pair.collider1 == *child;
+                for manifold in pair.manifolds.iter() {
+                    for point in manifold.points.iter() {
+                        if strongest.map_or(true, |(impulse, _)| point.impulse > impulse) {
+                            let local_point = if is_first {
+                                point.local_p1
+                            } else {
+                                point.local_p2
+                            };
+                            let position = collider
+                                .global_transform()
+                                .transform_point(&Point3::from(local_point))
+                                .coords;
+                            strongest = Some((point.impulse, position));
+                        }
+                    }
+                }
+            }
+        }
+
+        strongest
+    }
+
+    fn shatter(&self, impact_point: Vector3<f32>, ctx: &mut ScriptContext) {
+        let body = &ctx.scene.graph[ctx.handle];
+        let origin = body.global_position();
+        let rotation = body.global_rotation();
+        let velocity = body
+            .cast::<RigidBody>()
+            .map(|body| body.lin_vel())
+            .unwrap_or_default();
+
+        let mut rng = thread_rng();
+        let mut shards = Vec::new();
+        for _ in 0..*self.shard_count {
+            let offset = Vector3::new(
+                rng.gen_range(-0.25..0.25),
+                rng.gen_range(-0.25..0.25),
+                rng.gen_range(-0.25..0.25),
+            );
+            let position = origin + rotation * offset;
+
+            let shard = match self.shard_prefab.as_ref() {
+                Some(prefab) if prefab.is_ok() => {
+                    prefab.instantiate_at(ctx.scene, position, rotation)
+                }
+                _ => make_box(
+                    &mut ctx.scene.graph,
+                    position,
+                    rotation * UnitQuaternion::from_euler_angles(
+                        rng.gen_range(0.0..1.0),
+                        rng.gen_range(0.0..1.0),
+                        rng.gen_range(0.0..1.0),
+                    ),
+                    Vector3::repeat(rng.gen_range(0.05..0.15)),
+                    RigidBodyType::Dynamic,
+                    self.shard_material.as_ref().cloned(),
+                ),
+            };
+
+            // The body of a prefab instance is a child of the root of the instance.
+            let body = ctx
+                .scene
+                .graph
+                .traverse_handle_iter(shard)
+                .find(|handle| ctx.scene.graph[*handle].cast::<RigidBody>().is_some())
+                .unwrap_or_default();
+            if let Some(shard_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(body) {
+                let direction = offset
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+                shard_body.set_lin_vel(velocity);
+                shard_body.apply_impulse(
+                    direction.scale(*self.shard_impulse * rng.gen_range(0.5..1.0)),
+                );
+            }
+
+            shards.push(shard);
+        }
+
+        if let Some(sound) = self.destruction_sound.as_ref() {
+            SoundBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(impact_point)
+                        .build(),
+                ),
+            )
+            .with_buffer(Some(sound.clone()))
+            .with_status(Status::Playing)
+            .with_play_once(true)
+            .build(&mut ctx.scene.graph);
+        }
+
+        if let Some(game) = ctx.plugins.of_type_mut::<Game>() {
+            game.add_shards(shards, &mut ctx.scene.graph);
+        }
+
+        ctx.scene.graph.remove_node(ctx.handle);
+    }
+}
+
+impl ScriptTrait for Destructible {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if let Some((impulse, impact_point)) = self.strongest_impact(ctx) {
+            if impulse > *self.break_threshold {
+                self.shatter(impact_point, ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Game project.
+use crate::destructible::Destructible;
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    graph::BaseSceneGraph,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::{model::Model, texture::Texture},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        sound::SoundBuffer,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+use std::{collections::VecDeque, path::PathBuf};
+
+mod destructible;
+
+/// Maximum amount of shards alive at the same time, the oldest ones are removed first.
+const MAX_SHARDS: usize = 50;
+/// Maximum amount of thrown balls alive at the same time, the oldest ones are removed first.
+const MAX_BALLS: usize = 10;
+
+const SCENE_NAME: &str = "destruction";
+
+const SHARD_PREFAB_PATH: &str = "data/shard.rgs";
+const DESTRUCTION_SOUND_PATH: &str = "data/crate_break.wav";
+
+/// Directory with the textures, that are transcoded for the target platform by `build.rs`.
+const ASSET_PATH: &str = env!("ASSET_PATH");
+
+/// Returns the path of the transcoded copy of a texture from `data`.
+fn platform_texture_path(path: &str) -> PathBuf {
+    common_scripts::transcoded_texture_path(
+        path,
+        ASSET_PATH,
+        env!("ASSET_EXTENSION"),
+        env!("TRANSCODED_ASSETS"),
+    )
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shards: VecDeque<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    balls: VecDeque<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Creates a rigid body with a box collider and a box mesh.
+pub(crate) fn make_box(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    half_extents: Vector3<f32>,
+    body_type: RigidBodyType,
+    material: Option<MaterialResource>,
+) -> Handle<Node> {
+    let mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&half_extents.scale(2.0))),
+        ))
+        .with_material(material.unwrap_or_else(|| {
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::standard())
+        }))
+        .build()])
+        .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_rotation(rotation)
+                    .build(),
+            )
+            .with_children(&[mesh, collider]),
+    )
+    .with_body_type(body_type)
+    .build(graph)
+}
+
+/// Generates the shard prefab and saves it to `data/shard.rgs`. The prefab is a committed asset,
+/// that could be edited in the editor, so this is done only by the `--generate-prefab` flag of the
+/// desktop executor, never by the game itself.
+pub fn generate_shard_prefab() {
+    let mut material = Material::standard();
+    Log::verify(material.set_property(&"diffuseColor".into(), Color::opaque(150, 110, 70).into()));
+
+    let mut scene = Scene::new();
+    make_box(
+        &mut scene.graph,
+        Vector3::default(),
+        Default::default(),
+        Vector3::new(0.12, 0.06, 0.09),
+        RigidBodyType::Dynamic,
+        Some(MaterialResource::new_ok(ResourceKind::Embedded, material)),
+    );
+
+    let mut visitor = Visitor::new();
+    Log::verify(scene.save("Scene", &mut visitor));
+    match visitor.save_binary(SHARD_PREFAB_PATH) {
+        Ok(()) => Log::info(format!("The prefab is saved to {SHARD_PREFAB_PATH}")),
+        Err(err) => Log::err(format!(
+            "Unable to save {SHARD_PREFAB_PATH}. Reason: {err:?}"
+        )),
+    }
+}
+
+impl Game {
+    pub(crate) fn add_shards(&mut self, shards: Vec<Handle<Node>>, graph: &mut Graph) {
+        self.shards.extend(shards);
+        while self.shards.len() > MAX_SHARDS {
+            if let Some(oldest) = self.shards.pop_front() {
+                if graph.is_valid_handle(oldest) {
+                    graph.remove_node(oldest);
+                }
+            }
+        }
+    }
+
+    fn throw_ball(&mut self, context: &mut PluginContext) {
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        let camera = &scene.graph[self.camera];
+        let position = camera.global_position();
+        let direction = camera.look_vector();
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_sphere(16, 16, 0.25, &Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.25))
+            // Make the ball heavy, so it could break crates.
+            .with_density(Some(50.0))
+            .build(&mut scene.graph);
+
+        let ball = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_children(&[mesh, collider]),
+        )
+        .with_body_type(RigidBodyType::Dynamic)
+        .build(&mut scene.graph);
+
+        if let Some(ball) = scene.graph.try_get_mut_of_type::<RigidBody>(ball) {
+            ball.set_lin_vel(direction.scale(30.0));
+        }
+
+        self.balls.push_back(ball);
+        while self.balls.len() > MAX_BALLS {
+            if let Some(oldest) = self.balls.pop_front() {
+                if scene.graph.is_valid_handle(oldest) {
+                    scene.graph.remove_node(oldest);
+                }
+            }
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Destructible>("Destructible");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let shard_prefab = context.resource_manager.request::<Model>(SHARD_PREFAB_PATH);
+        let destruction_sound = context
+            .resource_manager
+            .request::<SoundBuffer>(DESTRUCTION_SOUND_PATH);
+
+        let mut scene = Scene::new();
+
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 3.0, -10.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        10.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        // Ground
+        make_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.5, 0.0),
+            Default::default(),
+            Vector3::new(20.0, 0.5, 20.0),
+            RigidBodyType::Static,
+            None,
+        );
+
+        let mut crate_material = Material::standard();
+        Log::verify(crate_material.set_texture(
+            &"diffuseTexture".into(),
+            Some(
+                context
+                    .resource_manager
+                    .request::<Texture>(platform_texture_path("data/Crate.png")),
+            ),
+        ));
+        let crate_material = MaterialResource::new_ok(ResourceKind::Embedded, crate_material);
+
+        // A pyramid of crates, each one can be destroyed.
+        let levels = 4;
+        for level in 0..levels {
+            let count = levels - level;
+            for i in 0..count {
+                for j in 0..count {
+                    let body = make_box(
+                        &mut scene.graph,
+                        Vector3::new(
+                            i as f32 - (count - 1) as f32 * 0.5,
+                            level as f32 + 0.5,
+                            j as f32 - (count - 1) as f32 * 0.5,
+                        ),
+                        Default::default(),
+                        Vector3::repeat(0.5),
+                        RigidBodyType::Dynamic,
+                        Some(crate_material.clone()),
+                    );
+                    scene.graph[body].add_script(Script::new(Destructible {
+                        shard_prefab: Some(shard_prefab.clone()).into(),
+                        destruction_sound: Some(destruction_sound.clone()).into(),
+                        shard_material: Some(crate_material.clone()).into(),
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
+        self.scene = context.scenes.add(scene);
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new())
+            .build(&mut context.user_interfaces.first_mut().build_ctx());
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Destructible Environment\n[LMB] - throw a ball\nShards: {}/{}\nFPS: {}\n{}",
+                    self.shards.len(),
+                    MAX_SHARDS,
+                    graphics_context.renderer.get_statistics().frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if let Event::WindowEvent {
+            event:
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Left,
+                    ..
+                },
+            ..
+        } = event
+        {
+            self.throw_ball(&mut context);
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers shared between editor plugins of demo projects.
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        pool::Handle,
+    },
+    gui::{
+        button::ButtonBuilder, message::MessageDirection, text::TextMessage, widget::WidgetBuilder,
+        window::WindowMessage, BuildContext, Thickness, UiNode,
+    },
+    scene::Scene,
+};
+use fyroxed_base::{camera::CameraController, scene::GameScene, Editor};
+
+/// Creates a button with a text. Buttons are at least 90 units wide, so rows of short buttons
+/// have the same width, and grow for longer texts.
+pub fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_min_size(Vector2::new(90.0, 24.0))
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+/// Opens a tool window of a plugin in the user interface of the editor.
+pub fn open_window(editor: &Editor, window: Handle<UiNode>) {
+    editor
+        .engine
+        .user_interfaces
+        .first()
+        .send_message(WindowMessage::open(
+            window,
+            MessageDirection::ToWidget,
+            false,
+            false,
+        ));
+}
+
+/// Changes a text widget in the user interface of the editor.
+pub fn set_text(editor: &Editor, text: Handle<UiNode>, value: String) {
+    editor
+        .engine
+        .user_interfaces
+        .first()
+        .send_message(TextMessage::text(text, MessageDirection::ToWidget, value));
+}
+
+/// Returns the camera controller of the editor and the current scene, if it is a game scene.
+pub fn current_game_scene(editor: &mut Editor) -> Option<(&mut CameraController, &mut Scene)> {
+    let entry = editor.scenes.current_scene_entry_mut()?;
+    let game_scene = entry.controller.downcast_mut::<GameScene>()?;
+    let scene = editor.engine.scenes.try_get_mut(game_scene.scene)?;
+    Some((&mut game_scene.camera_controller, scene))
+}
+
+/// Places the editor camera at the position with the rotation. The camera controller calculates
+/// the camera transform from its own yaw and pitch every frame, so this must be done after the
+/// update of the editor (in `on_post_update`) to take effect. Once a camera animation ends, use
+/// [`CameraController::set_position`] to keep the camera where the animation has left it.
+pub fn place_camera(
+    controller: &CameraController,
+    scene: &mut Scene,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+) {
+    let graph = &mut scene.graph;
+    graph[controller.pivot]
+        .local_transform_mut()
+        .set_position(position)
+        .set_rotation(rotation);
+    graph[controller.camera_hinge]
+        .local_transform_mut()
+        .set_rotation(UnitQuaternion::identity());
+    graph[controller.camera]
+        .local_transform_mut()
+        .set_position(Vector3::default());
+}
@@ -0,0 +1,392 @@
+//! Game project.
+use crate::{player::Player, target::Target};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{Event, WindowEvent},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        image::ImageBuilder,
+        message::MessageDirection,
+        progress_bar::ProgressBarBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+    window::CursorGrabMode,
+};
+use std::path::PathBuf;
+
+mod player;
+mod target;
+
+const SCENE_NAME: &str = "fps";
+
+/// Directory with the textures, that are transcoded for the target platform by `build.rs`.
+const ASSET_PATH: &str = env!("ASSET_PATH");
+
+/// Returns the path of the transcoded copy of a texture from `data`.
+fn platform_texture_path(path: &str) -> PathBuf {
+    common_scripts::transcoded_texture_path(
+        path,
+        ASSET_PATH,
+        env!("ASSET_EXTENSION"),
+        env!("TRANSCODED_ASSETS"),
+    )
+}
+
+/// Widgets of the HUD, the player script updates them.
+#[derive(Default, Debug, Clone, Copy)]
+struct Hud {
+    root: Handle<UiNode>,
+    hit_marker: Handle<UiNode>,
+    reload_bar: Handle<UiNode>,
+    ammo_text: Handle<UiNode>,
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hud: Hud,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Creates a rigid body with a box collider and a box mesh.
+fn make_box(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    half_extents: Vector3<f32>,
+    body_type: RigidBodyType,
+    material: Option<MaterialResource>,
+) -> Handle<Node> {
+    let mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&half_extents.scale(2.0))),
+        ))
+        .with_material(material.unwrap_or_else(|| {
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::standard())
+        }))
+        .build()])
+        .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[mesh, collider]),
+    )
+    .with_body_type(body_type)
+    .build(graph)
+}
+
+/// Creates a capsule body of the player with a camera at the eye level.
+fn make_player(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    let camera = CameraBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 0.6, 0.0))
+                .build(),
+        ),
+    )
+    .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::capsule_y(0.5, 0.3))
+        .with_friction(0.0)
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[camera, collider])
+            .with_script(Script::new(Player::new(camera))),
+    )
+    .with_body_type(RigidBodyType::Dynamic)
+    .with_locked_rotations(true)
+    .with_can_sleep(false)
+    .build(graph)
+}
+
+impl Game {
+    fn handle_resize(&self, context: &mut PluginContext, new_size: Vector2<f32>) {
+        let ui = context.user_interfaces.first();
+        ui.send_message(WidgetMessage::width(
+            self.hud.root,
+            MessageDirection::ToWidget,
+            new_size.x,
+        ));
+        ui.send_message(WidgetMessage::height(
+            self.hud.root,
+            MessageDirection::ToWidget,
+            new_size.y,
+        ));
+    }
+
+    fn build_hud(&mut self, context: &mut PluginContext) {
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        let crosshair = ImageBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_width(32.0)
+                .with_height(32.0)
+                .with_hit_test_visibility(false),
+        )
+        .with_texture(
+            context
+                .resource_manager
+                .request::<Texture>(platform_texture_path("data/crosshair.png"))
+                .into(),
+        )
+        .build(ctx);
+
+        self.hud.hit_marker = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_font_size(40.0)
+        .with_text("+")
+        .build(ctx);
+
+        self.hud.reload_bar = ProgressBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .on_column(1)
+                .with_visibility(false)
+                .with_width(200.0)
+                .with_height(12.0)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::top(30.0)),
+        )
+        .build(ctx);
+
+        self.hud.ammo_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(2)
+                .on_column(2)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_margin(Thickness::uniform(10.0)),
+        )
+        .with_font_size(24.0)
+        .build(ctx);
+
+        self.hud.root = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_child(crosshair)
+                .with_child(self.hud.hit_marker)
+                .with_child(self.hud.reload_bar)
+                .with_child(self.hud.ammo_text),
+        )
+        .add_column(Column::stretch())
+        .add_column(Column::strict(200.0))
+        .add_column(Column::stretch())
+        .add_row(Row::stretch())
+        .add_row(Row::strict(40.0))
+        .add_row(Row::stretch())
+        .build(ctx);
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        let script_constructors = &context.serialization_context.script_constructors;
+        script_constructors.add::<Player>("Player");
+        script_constructors.add::<Target>("Target");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, mut context: PluginContext) {
+        // The HUD must exist before the player script starts.
+        self.build_hud(&mut context);
+
+        let mut scene = Scene::new();
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        // Ground and walls of the shooting range.
+        make_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.5, 0.0),
+            Vector3::new(15.0, 0.5, 20.0),
+            RigidBodyType::Static,
+            None,
+        );
+        for x in [-15.5, 15.5] {
+            make_box(
+                &mut scene.graph,
+                Vector3::new(x, 2.0, 0.0),
+                Vector3::new(0.5, 2.0, 20.0),
+                RigidBodyType::Static,
+                None,
+            );
+        }
+        make_box(
+            &mut scene.graph,
+            Vector3::new(0.0, 2.0, 20.5),
+            Vector3::new(16.0, 2.0, 0.5),
+            RigidBodyType::Static,
+            None,
+        );
+
+        let mut crate_material = Material::standard();
+        Log::verify(
+            crate_material.set_texture(
+                &"diffuseTexture".into(),
+                Some(
+                    context
+                        .resource_manager
+                        .request::<Texture>(platform_texture_path("data/Crate.png")),
+                ),
+            ),
+        );
+        let crate_material = MaterialResource::new_ok(ResourceKind::Embedded, crate_material);
+
+        // Stacks of crates at different distances, every crate could be shot down.
+        for (stack, distance) in [8.0, 12.0, 16.0].into_iter().enumerate() {
+            for column in 0..3 {
+                for level in 0..=stack {
+                    let body = make_box(
+                        &mut scene.graph,
+                        Vector3::new(
+                            (column as f32 - 1.0) * 4.0 + stack as f32,
+                            level as f32 + 0.5,
+                            distance,
+                        ),
+                        Vector3::repeat(0.5),
+                        RigidBodyType::Dynamic,
+                        Some(crate_material.clone()),
+                    );
+                    scene.graph[body].add_script(Script::new(Target::default()));
+                }
+            }
+        }
+
+        make_player(&mut scene.graph, Vector3::new(0.0, 1.0, -10.0));
+
+        self.scene = context.scenes.add(scene);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - First-Person Shooter\n[W][A][S][D] - move, [LMB] - shoot, [R] - reload\nFPS: {}\n{}",
+                    graphics_context.renderer.get_statistics().frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::Resized(size),
+            ..
+        } = event
+        {
+            self.handle_resize(
+                &mut context,
+                Vector2::new(size.width as f32, size.height as f32),
+            );
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        let graphics_context = context.graphics_context.as_initialized_mut();
+
+        prefs::quality::restore(&mut graphics_context.renderer, SCENE_NAME);
+
+        // Mouse-look needs the cursor to stay inside the window. Not every platform supports
+        // locking the cursor, confining it is good enough.
+        let window = &graphics_context.window;
+        if window.set_cursor_grab(CursorGrabMode::Locked).is_err() {
+            Log::verify(window.set_cursor_grab(CursorGrabMode::Confined));
+        }
+        window.set_cursor_visible(false);
+
+        let inner_size = window.inner_size();
+        self.handle_resize(
+            &mut context,
+            Vector2::new(inner_size.width as f32, inner_size.height as f32),
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
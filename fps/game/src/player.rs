@@ -0,0 +1,404 @@
+//! First-person player: mouse-look, movement, hitscan shooting and reloading.
+use crate::{target::Target, Game, Hud};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        color::Color,
+        color_gradient::{ColorGradient, GradientPoint},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection, progress_bar::ProgressBarMessage, text::TextMessage,
+        widget::WidgetMessage,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    scene::{
+        base::BaseBuilder,
+        graph::physics::RayCastOptions,
+        node::Node,
+        particle_system::{
+            emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+            ParticleSystemBuilder,
+        },
+        rigidbody::RigidBody,
+        transform::TransformBuilder,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Max distance of a shot.
+const SHOT_DISTANCE: f32 = 100.0;
+const MUZZLE_FLASH_DURATION: f32 = 0.05;
+const HIT_MARKER_DURATION: f32 = 0.2;
+/// Position of the muzzle relative to the camera.
+const MUZZLE_OFFSET: Vector3<f32> = Vector3::new(0.2, -0.15, 0.6);
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "b8e2f4a6-1c3d-4f5e-9a7b-0d2c4e6f8a13")]
+#[visit(optional)]
+pub struct Player {
+    camera: InheritableVariable<Handle<Node>>,
+    move_speed: InheritableVariable<f32>,
+    /// Damage of a single shot.
+    damage: InheritableVariable<f32>,
+    /// Impulse, that is applied to a body at the hit point.
+    impulse: InheritableVariable<f32>,
+    magazine_size: InheritableVariable<u32>,
+    /// Time (in seconds) that it takes to reload the weapon.
+    reload_time: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    yaw: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pitch: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_forward: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_backward: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_left: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_right: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shoot: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ammo: u32,
+    /// Time left until the end of reloading.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    reload_timer: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hit_marker_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    muzzle_flash: Option<(Handle<Node>, f32)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hud: Hud,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            move_speed: 4.0.into(),
+            damage: 25.0.into(),
+            impulse: 4.0.into(),
+            magazine_size: 12.into(),
+            reload_time: 1.5.into(),
+            yaw: 0.0,
+            pitch: 0.0,
+            move_forward: false,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            shoot: false,
+            ammo: 0,
+            reload_timer: None,
+            hit_marker_timer: 0.0,
+            muzzle_flash: None,
+            hud: Default::default(),
+        }
+    }
+}
+
+impl Player {
+    pub fn new(camera: Handle<Node>) -> Self {
+        Self {
+            camera: camera.into(),
+            ..Default::default()
+        }
+    }
+
+    fn update_ammo_text(&self, ctx: &ScriptContext) {
+        let text = if self.reload_timer.is_some() {
+            "Reloading...".to_string()
+        } else if self.ammo == 0 {
+            "Empty - press [R] to reload".to_string()
+        } else {
+            format!("{} / {}", self.ammo, *self.magazine_size)
+        };
+        ctx.user_interfaces.first().send_message(TextMessage::text(
+            self.hud.ammo_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn start_reload(&mut self, ctx: &ScriptContext) {
+        if self.reload_timer.is_some() || self.ammo == *self.magazine_size {
+            return;
+        }
+
+        self.reload_timer = Some(*self.reload_time);
+        ctx.user_interfaces
+            .first()
+            .send_message(WidgetMessage::visibility(
+                self.hud.reload_bar,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        self.update_ammo_text(ctx);
+    }
+
+    fn update_reload(&mut self, ctx: &ScriptContext) {
+        let Some(timer) = self.reload_timer.as_mut() else {
+            return;
+        };
+
+        *timer -= ctx.dt;
+        let ui = ctx.user_interfaces.first();
+        if *timer > 0.0 {
+            ui.send_message(ProgressBarMessage::progress(
+                self.hud.reload_bar,
+                MessageDirection::ToWidget,
+                1.0 - *timer / *self.reload_time,
+            ));
+        } else {
+            self.reload_timer = None;
+            self.ammo = *self.magazine_size;
+            ui.send_message(WidgetMessage::visibility(
+                self.hud.reload_bar,
+                MessageDirection::ToWidget,
+                false,
+            ));
+            self.update_ammo_text(ctx);
+        }
+    }
+
+    fn spawn_muzzle_flash(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+        let flash = ParticleSystemBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(MUZZLE_OFFSET)
+                    .build(),
+            ),
+        )
+        .with_acceleration(Vector3::zeros())
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(
+                0.0,
+                Color::from_rgba(255, 230, 150, 255),
+            ));
+            gradient.add_point(GradientPoint::new(1.0, Color::from_rgba(255, 120, 0, 0)));
+            gradient
+        })
+        .with_emitters(vec![SphereEmitterBuilder::new(
+            BaseEmitterBuilder::new()
+                .with_max_particles(30)
+                .with_spawn_rate(1000)
+                .with_size_range(0.03..0.08)
+                .with_x_velocity_range(-0.01..0.01)
+                .with_y_velocity_range(-0.01..0.01)
+                .with_z_velocity_range(0.01..0.04)
+                .with_lifetime_range(0.03..MUZZLE_FLASH_DURATION),
+        )
+        .with_radius(0.03)
+        .build()])
+        .build(graph);
+        graph.link_nodes(flash, *self.camera);
+
+        // Previous flash could still be alive, if the shots are fast enough.
+        if let Some((previous, _)) = self.muzzle_flash.replace((flash, MUZZLE_FLASH_DURATION)) {
+            if graph.is_valid_handle(previous) {
+                graph.remove_node(previous);
+            }
+        }
+    }
+
+    fn update_muzzle_flash(&mut self, ctx: &mut ScriptContext) {
+        if let Some((flash, timer)) = self.muzzle_flash.as_mut() {
+            *timer -= ctx.dt;
+            if *timer <= 0.0 {
+                if ctx.scene.graph.is_valid_handle(*flash) {
+                    ctx.scene.graph.remove_node(*flash);
+                }
+                self.muzzle_flash = None;
+            }
+        }
+    }
+
+    fn update_hit_marker(&mut self, ctx: &ScriptContext) {
+        if self.hit_marker_timer <= 0.0 {
+            return;
+        }
+
+        self.hit_marker_timer -= ctx.dt;
+        if self.hit_marker_timer <= 0.0 {
+            ctx.user_interfaces
+                .first()
+                .send_message(WidgetMessage::visibility(
+                    self.hud.hit_marker,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+        }
+    }
+
+    /// Casts a ray from the center of the screen and damages the closest body on its way.
+    fn shoot(&mut self, ctx: &mut ScriptContext) {
+        if self.reload_timer.is_some() || self.ammo == 0 {
+            return;
+        }
+        self.ammo -= 1;
+        self.update_ammo_text(ctx);
+        self.spawn_muzzle_flash(ctx);
+
+        let graph = &mut ctx.scene.graph;
+        let Some(camera) = graph.try_get(*self.camera) else {
+            return;
+        };
+        let Some(direction) = camera.look_vector().try_normalize(f32::EPSILON) else {
+            return;
+        };
+
+        let mut intersections = Vec::new();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(camera.global_position()),
+                ray_direction: direction,
+                max_len: SHOT_DISTANCE,
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+
+        // The ray starts inside the player's own collider.
+        let Some((body, point)) = intersections.into_iter().find_map(|intersection| {
+            let body = graph.try_get(intersection.collider)?.parent();
+            (body != ctx.handle && graph.try_get_of_type::<RigidBody>(body).is_some())
+                .then_some((body, intersection.position.coords))
+        }) else {
+            return;
+        };
+
+        if let Some(rigid_body) = graph.try_get_mut_of_type::<RigidBody>(body) {
+            rigid_body.wake_up();
+            rigid_body.apply_impulse_at_point(direction.scale(*self.impulse), point);
+        }
+
+        let destroyed = graph
+            .try_get_mut(body)
+            .and_then(|node| node.try_get_script_mut::<Target>())
+            .map_or(false, |target| target.damage(*self.damage));
+        if destroyed {
+            graph.remove_node(body);
+        }
+
+        self.hit_marker_timer = HIT_MARKER_DURATION;
+        ctx.user_interfaces
+            .first()
+            .send_message(WidgetMessage::visibility(
+                self.hud.hit_marker,
+                MessageDirection::ToWidget,
+                true,
+            ));
+    }
+}
+
+impl ScriptTrait for Player {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.hud = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| game.hud)
+            .unwrap_or_default();
+        self.ammo = *self.magazine_size;
+        self.update_ammo_text(ctx);
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput { event, .. } => {
+                    let pressed = event.state == ElementState::Pressed;
+                    if let PhysicalKey::Code(code) = event.physical_key {
+                        match code {
+                            KeyCode::KeyW => self.move_forward = pressed,
+                            KeyCode::KeyS => self.move_backward = pressed,
+                            KeyCode::KeyA => self.move_left = pressed,
+                            KeyCode::KeyD => self.move_right = pressed,
+                            KeyCode::KeyR if pressed => self.start_reload(ctx),
+                            _ => (),
+                        }
+                    }
+                }
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    self.shoot = true;
+                }
+                _ => (),
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                let mouse_sens = 0.2 * ctx.dt;
+                self.yaw -= (delta.0 as f32) * mouse_sens;
+                self.pitch = (self.pitch + (delta.1 as f32) * mouse_sens)
+                    .clamp(-89.0f32.to_radians(), 89.0f32.to_radians());
+            }
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw);
+        if let Some(camera) = ctx.scene.graph.try_get_mut(*self.camera) {
+            camera.local_transform_mut().set_rotation(
+                yaw * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch),
+            );
+        }
+
+        let mut direction = Vector3::<f32>::zeros();
+        if self.move_forward {
+            direction.z += 1.0;
+        }
+        if self.move_backward {
+            direction.z -= 1.0;
+        }
+        if self.move_left {
+            direction.x += 1.0;
+        }
+        if self.move_right {
+            direction.x -= 1.0;
+        }
+        let velocity = direction
+            .try_normalize(f32::EPSILON)
+            .map(|direction| (yaw * direction).scale(*self.move_speed))
+            .unwrap_or_default();
+        if let Some(body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            // Keep vertical velocity, so the player still falls.
+            let lin_vel = body.lin_vel();
+            body.set_lin_vel(Vector3::new(velocity.x, lin_vel.y, velocity.z));
+        }
+
+        if std::mem::take(&mut self.shoot) {
+            self.shoot(ctx);
+        }
+        self.update_reload(ctx);
+        self.update_muzzle_flash(ctx);
+        self.update_hit_marker(ctx);
+    }
+}
@@ -0,0 +1,32 @@
+//! A rigid body, that can be shot and destroyed.
+use fyrox::{
+    core::{
+        reflect::prelude::*, type_traits::prelude::*, variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    script::ScriptTrait,
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "6d0c4b1e-9f2a-4e37-8a5d-2c7b3e1f9a40")]
+#[visit(optional)]
+pub struct Target {
+    hp: InheritableVariable<f32>,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self { hp: 100.0.into() }
+    }
+}
+
+impl Target {
+    /// Reduces health of the target, returns `true` if the target must be destroyed.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        let hp = (*self.hp - amount).max(0.0);
+        self.hp.set_value_and_mark_modified(hp);
+        hp <= 0.0
+    }
+}
+
+impl ScriptTrait for Target {}
@@ -0,0 +1,160 @@
+(
+    name: "GpuParticlesShader",
+
+    properties: [
+        (
+            name: "time",
+            kind: Float(0.0),
+        ),
+        (
+            name: "lifetime",
+            kind: Float(3.0),
+        ),
+        (
+            name: "aliveCount",
+            kind: Float(0.0),
+        ),
+        (
+            name: "gravity",
+            kind: Float(-9.81),
+        ),
+        (
+            name: "launchSpeed",
+            kind: Float(14.0),
+        ),
+        (
+            name: "boundsExtent",
+            kind: Float(10.0),
+        ),
+        (
+            name: "maxBounds",
+            kind: Float(20.0),
+        ),
+        (
+            name: "particleSize",
+            kind: Float(0.05),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                // Billboards always face the camera.
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                // Additive blending does not depend on the order of the particles, so they do not
+                // have to be sorted by the distance to the camera.
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: Zero,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add,
+                    ),
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                // Random direction of the particle, scaled by maxBounds. All 4 corners of a
+                // particle have the same value.
+                layout(location = 0) in vec3 vertexPosition;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 fyrox_cameraPosition;
+
+                uniform float time;
+                uniform float lifetime;
+                uniform float aliveCount;
+                uniform float gravity;
+                uniform float launchSpeed;
+                uniform float boundsExtent;
+                uniform float maxBounds;
+                uniform float particleSize;
+
+                out vec2 texCoord;
+                out float age;
+
+                // PCG hash, returns a value in [0; 1).
+                float random(uint seed)
+                {
+                    uint state = seed * 747796405u + 2891336453u;
+                    uint word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+                    return float((word >> 22u) ^ word) / 4294967296.0;
+                }
+
+                void main()
+                {
+                    int particle = gl_VertexID / 4;
+                    int corner = gl_VertexID % 4;
+
+                    // Particles, that are not emitted, are moved out of the clip space.
+                    if (float(particle) >= aliveCount)
+                    {
+                        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+                        return;
+                    }
+
+                    // Every particle is respawned when its lifetime ends, the random phase spreads
+                    // the spawns evenly over the lifetime.
+                    float phase = random(uint(particle));
+                    float t = mod(time + phase * lifetime, lifetime);
+
+                    // The fountain shoots the particles upwards in a cone.
+                    vec3 direction = vertexPosition / maxBounds;
+                    vec3 velocity = vec3(direction.x * 0.25, 1.0, direction.z * 0.25) * launchSpeed
+                        * (0.75 + 0.25 * direction.y);
+                    vec3 position = velocity * t + vec3(0.0, 0.5 * gravity * t * t, 0.0);
+
+                    // Particles, that leave the bounds, enter them from the opposite side.
+                    position = mod(position + boundsExtent, 2.0 * boundsExtent) - boundsExtent;
+
+                    // The mesh is at the origin and is not transformed, so the local space is the
+                    // world space.
+                    vec3 toCamera = normalize(fyrox_cameraPosition - position);
+                    vec3 side = normalize(cross(vec3(0.0, 1.0, 0.0), toCamera));
+                    vec3 up = cross(toCamera, side);
+                    vec2 offset = vec2(corner == 1 || corner == 2 ? 1.0 : -1.0, corner >= 2 ? 1.0 : -1.0);
+
+                    texCoord = offset;
+                    age = t / lifetime;
+                    position += (side * offset.x + up * offset.y) * particleSize;
+                    gl_Position = fyrox_worldViewProjection * vec4(position, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                in vec2 texCoord;
+                in float age;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    float falloff = max(1.0 - length(texCoord), 0.0);
+                    // Young particles are hot, old ones cool down and fade out.
+                    vec3 color = mix(vec3(1.0, 0.6, 0.2), vec3(0.2, 0.4, 1.0), age);
+                    FragColor = vec4(color * falloff * (1.0 - age) * 0.5, 0.0);
+                }
+               "#,
+        ),
+    ],
+)
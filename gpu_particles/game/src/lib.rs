@@ -0,0 +1,301 @@
+//! Game project.
+use crate::particles::{make_particle_surface, PARTICLE_COUNT};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceResource},
+            MeshBuilder, RenderPath,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+mod particles;
+
+/// Every particle lives this long (in seconds) and then is respawned at the emitter.
+const LIFETIME: f32 = 3.0;
+/// Max half-size of the box, where the particles are wrapped.
+const MAX_BOUNDS: f32 = 20.0;
+
+const SCENE_NAME: &str = "gpu_particles";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    emission_rate_slider: Handle<UiNode>,
+    gravity_slider: Handle<UiNode>,
+    bounds_slider: Handle<UiNode>,
+    /// Amount of particles, that are spawned every second.
+    emission_rate: f32,
+    gravity: f32,
+    /// Half-size of the box, where the particles are wrapped.
+    bounds: f32,
+    /// Time of the simulation, wrapped to the lifetime of the particles.
+    time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(if step < 1.0 { 2 } else { 0 })
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    /// Amount of particles, that are alive at the same time. Every particle respawns once per
+    /// lifetime, so the emission rate is reached when this amount of particles is simulated.
+    fn alive_count(&self) -> usize {
+        ((self.emission_rate * LIFETIME) as usize).min(PARTICLE_COUNT)
+    }
+
+    fn build_particles(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::from_shader(shader, None));
+        {
+            let mut material = material.data_ref();
+            Log::verify(material.set_property(&"lifetime".into(), LIFETIME.into()));
+            Log::verify(material.set_property(&"maxBounds".into(), MAX_BOUNDS.into()));
+        }
+
+        // Particles are transparent, so they are drawn after the opaque objects.
+        MeshBuilder::new(BaseBuilder::new().with_cast_shadows(false))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                make_particle_surface(MAX_BOUNDS),
+            ))
+            .with_material(material.clone())
+            .build()])
+            .with_render_path(RenderPath::Forward)
+            .build(&mut scene.graph);
+
+        self.material = Some(material);
+    }
+
+    fn update_particles(&mut self, dt: f32, context: &mut PluginContext) {
+        // The shader needs the time only within the lifetime, which keeps the precision of the
+        // time in long sessions.
+        self.time = (self.time + dt) % LIFETIME;
+
+        if let Some(material) = self.material.as_ref() {
+            let mut material = material.data_ref();
+            Log::verify(material.set_property(&"time".into(), self.time.into()));
+            Log::verify(
+                material.set_property(&"aliveCount".into(), (self.alive_count() as f32).into()),
+            );
+            Log::verify(material.set_property(&"gravity".into(), self.gravity.into()));
+            Log::verify(material.set_property(&"boundsExtent".into(), self.bounds.into()));
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        scene.drawing_context.clear_lines();
+        scene.drawing_context.draw_aabb(
+            &AxisAlignedBoundingBox::from_min_max(
+                Vector3::repeat(-self.bounds),
+                Vector3::repeat(self.bounds),
+            ),
+            Color::opaque(80, 80, 80),
+        );
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.emission_rate = 200_000.0;
+        self.gravity = -9.81;
+        self.bounds = 10.0;
+
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 6.0, -32.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        10.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let emission_rate_text = make_text(ctx, 0, "Emission Rate (particles/s)");
+        self.emission_rate_slider = make_slider(
+            ctx,
+            1,
+            1000.0,
+            PARTICLE_COUNT as f32 / LIFETIME,
+            1000.0,
+            self.emission_rate,
+        );
+        let gravity_text = make_text(ctx, 2, "Gravity");
+        self.gravity_slider = make_slider(ctx, 3, -30.0, 0.0, 0.1, self.gravity);
+        let bounds_text = make_text(ctx, 4, "Wrap Bounds");
+        self.bounds_slider = make_slider(ctx, 5, 2.0, MAX_BOUNDS, 0.5, self.bounds);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(200.0)
+                .with_desired_position(Vector2::new(5.0, 140.0)),
+        )
+        .with_title(WindowTitle::text("Particle Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(emission_rate_text)
+                    .with_child(self.emission_rate_slider)
+                    .with_child(gravity_text)
+                    .with_child(self.gravity_slider)
+                    .with_child(bounds_text)
+                    .with_child(self.bounds_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/particles.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.build_particles(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load particle shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.update_particles(context.dt, context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - GPU Particles\nAlive Particles: {} / {}\nSimulation + Draw Time: {:.3} ms\n{}\n{}",
+                        self.alive_count(),
+                        PARTICLE_COUNT,
+                        statistics.pure_frame_time * 1000.0,
+                        statistics,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, _context: &mut PluginContext, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.emission_rate_slider {
+                self.emission_rate = *value;
+            } else if message.destination() == self.gravity_slider {
+                self.gravity = *value;
+            } else if message.destination() == self.bounds_slider {
+                self.bounds = *value;
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Mesh of the particles. Every particle is a quad of 4 vertices, which are moved by the vertex
+//! shader, so the vertices store only a random direction of the particle. The state of a particle
+//! (position, velocity and age) is a function of time, so it is computed on the GPU every frame
+//! without any buffers, that are written back.
+use fyrox::{
+    core::{algebra::Vector3, math::TriangleDefinition},
+    rand::{thread_rng, Rng},
+    scene::mesh::{
+        buffer::{
+            TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+            VertexAttributeUsage, VertexBuffer, VertexTrait,
+        },
+        surface::SurfaceData,
+    },
+};
+
+/// Amount of the particles in the mesh, the emission rate defines how many of them are alive.
+pub const PARTICLE_COUNT: usize = 1_000_000;
+
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+struct ParticleVertex {
+    /// Random direction of the particle, scaled by the max bounds. It is stored as the position,
+    /// so the bounding box of the mesh covers all the particles and the mesh is never culled
+    /// while they are visible.
+    direction: Vector3<f32>,
+}
+
+impl VertexTrait for ParticleVertex {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[VertexAttributeDescriptor {
+            usage: VertexAttributeUsage::Position,
+            data_type: VertexAttributeDataType::F32,
+            size: 3,
+            divisor: 0,
+            shader_location: 0,
+            normalized: false,
+        }]
+    }
+}
+
+/// Creates the quads of all the particles. The shader finds the particle and the corner of the
+/// quad by the index of the vertex.
+pub fn make_particle_surface(max_bounds: f32) -> SurfaceData {
+    let mut rng = thread_rng();
+    let mut vertices = Vec::with_capacity(PARTICLE_COUNT * 4);
+    let mut triangles = Vec::with_capacity(PARTICLE_COUNT * 2);
+    for _ in 0..PARTICLE_COUNT {
+        let direction = Vector3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        )
+        .scale(max_bounds);
+
+        let first = vertices.len() as u32;
+        vertices.extend([ParticleVertex { direction }; 4]);
+        triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        triangles.push(TriangleDefinition([first + 2, first + 3, first]));
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+    )
+}
@@ -0,0 +1,199 @@
+//! Runs every demo for a number of frames in an invisible window and checks that it neither panics
+//! nor reports errors to the log. Every demo runs in its own process, because an event loop could
+//! be created only once per process.
+use fyrox::{
+    core::{
+        log::{Log, MessageKind},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    dpi::LogicalSize,
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+    plugin::{Plugin, PluginContext},
+    window::WindowAttributes,
+};
+use std::{
+    path::Path,
+    process::{Command, ExitCode},
+    sync::mpsc,
+};
+
+/// Amount of frames every demo must survive.
+const FRAME_COUNT: usize = 300;
+
+/// Names of the demos, every name is also a folder of the demo.
+const DEMOS: &[&str] = &[
+    "animation",
+    "blendshape",
+    "bone_attachment",
+    "cloth",
+    "crafting",
+    "crowd",
+    "destruction",
+    "fps",
+    "gpu_particles",
+    "instancing_bench",
+    "irradiance",
+    "lightmap",
+    "many_lights",
+    "mirror",
+    "pathfind_vis",
+    "platformer",
+    "pool_stress",
+    "retarget",
+    "sdf_text",
+    "sky_demo",
+    "softbody",
+    "sound",
+    "streaming",
+    "terrain_paint",
+    "tex_streaming",
+    "ui",
+    "vehicle",
+    "volumetric",
+    "voxel",
+];
+
+/// Adds the plugin of the demo to the executor. Returns `false` if there's no such demo.
+fn add_demo_plugin(name: &str, executor: &mut Executor) -> bool {
+    match name {
+        "animation" => executor.add_plugin(animation::Game::default()),
+        "blendshape" => executor.add_plugin(blendshape::Game::default()),
+        "bone_attachment" => executor.add_plugin(bone_attachment::Game::default()),
+        "cloth" => executor.add_plugin(cloth::Game::default()),
+        "crafting" => executor.add_plugin(crafting::Game::default()),
+        "crowd" => executor.add_plugin(crowd::Game::default()),
+        "destruction" => executor.add_plugin(destruction::Game::default()),
+        "fps" => executor.add_plugin(fps::Game::default()),
+        "gpu_particles" => executor.add_plugin(gpu_particles::Game::default()),
+        "instancing_bench" => executor.add_plugin(instancing_bench::Game::default()),
+        "irradiance" => executor.add_plugin(irradiance::Game::default()),
+        "lightmap" => executor.add_plugin(lightmap::Game::default()),
+        "many_lights" => executor.add_plugin(many_lights::Game::default()),
+        "mirror" => executor.add_plugin(mirror::Game::default()),
+        "pathfind_vis" => executor.add_plugin(pathfind_vis::Game::default()),
+        "platformer" => executor.add_plugin(platformer::Game::default()),
+        "pool_stress" => executor.add_plugin(pool_stress::Game::default()),
+        "retarget" => executor.add_plugin(retarget::Game::default()),
+        "sdf_text" => executor.add_plugin(sdf_text::Game::default()),
+        "sky_demo" => executor.add_plugin(sky_demo::Game::default()),
+        "softbody" => executor.add_plugin(softbody::Game::default()),
+        "sound" => executor.add_plugin(sound::Game::default()),
+        "streaming" => executor.add_plugin(streaming::Game::default()),
+        "terrain_paint" => executor.add_plugin(terrain_paint::Game::default()),
+        "tex_streaming" => executor.add_plugin(tex_streaming::Game::default()),
+        "ui" => executor.add_plugin(ui::Game::default()),
+        "vehicle" => executor.add_plugin(vehicle::Game::default()),
+        "volumetric" => executor.add_plugin(volumetric::Game::default()),
+        "voxel" => executor.add_plugin(voxel::Game::default()),
+        _ => return false,
+    };
+    true
+}
+
+/// Stops the executor after the given amount of frames.
+#[derive(Default, Visit, Reflect, Debug)]
+struct FrameLimit {
+    frames_left: usize,
+}
+
+impl Plugin for FrameLimit {
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.frames_left > 0 {
+            self.frames_left -= 1;
+        } else if let Some(window_target) = context.window_target {
+            window_target.exit();
+        }
+    }
+}
+
+/// Runs a single demo in the current process.
+fn run_demo(name: &str) -> ExitCode {
+    // Demos load their assets using paths relative to their folders.
+    let demo_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(name);
+    if let Err(err) = std::env::set_current_dir(&demo_dir) {
+        eprintln!("Unable to enter {}: {err}", demo_dir.display());
+        return ExitCode::FAILURE;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    Log::add_listener(sender);
+
+    let mut window_attributes = WindowAttributes::default();
+    window_attributes.inner_size = Some(LogicalSize::new(1280.0, 720.0).into());
+    window_attributes.title = format!("Headless Test - {name}");
+    // The window is still required to create a graphics context, but it is never shown.
+    window_attributes.visible = false;
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            window_attributes,
+            vsync: false,
+            msaa_sample_count: None,
+        },
+    );
+    if !add_demo_plugin(name, &mut executor) {
+        eprintln!("Unknown demo {name}");
+        return ExitCode::FAILURE;
+    }
+    executor.add_plugin(FrameLimit {
+        frames_left: FRAME_COUNT,
+    });
+    executor.run();
+
+    let errors = receiver
+        .try_iter()
+        .filter(|message| matches!(message.kind, MessageKind::Error))
+        .map(|message| message.content)
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        for error in errors {
+            eprintln!("[{name}] {error}");
+        }
+        ExitCode::FAILURE
+    }
+}
+
+/// Runs every demo in a child process, so a panic in one of them does not stop the others.
+fn run_all(names: &[String]) -> ExitCode {
+    let exe = std::env::current_exe().expect("Unable to find the test executable");
+
+    let mut failed = Vec::new();
+    for name in names {
+        println!("Running {name} for {FRAME_COUNT} frames...");
+        let passed = Command::new(&exe)
+            .args(["--demo", name])
+            .status()
+            .map_or(false, |status| status.success());
+        println!("{name}: {}", if passed { "ok" } else { "FAILED" });
+        if !passed {
+            failed.push(name.as_str());
+        }
+    }
+
+    if failed.is_empty() {
+        println!("All {} demos passed.", names.len());
+        ExitCode::SUCCESS
+    } else {
+        println!("Failed demos: {}", failed.join(", "));
+        ExitCode::FAILURE
+    }
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.as_slice() {
+        [flag, name] if flag == "--demo" => run_demo(name),
+        // Specific demos could be passed by their names, all demos are tested otherwise.
+        [] => run_all(
+            &DEMOS
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+        ),
+        names => run_all(names),
+    }
+}
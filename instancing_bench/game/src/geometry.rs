@@ -0,0 +1,65 @@
+//! Procedural low-poly geometry used by the benchmark.
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        math::TriangleDefinition,
+    },
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexBuffer},
+        surface::SurfaceData,
+        vertex::StaticVertex,
+    },
+};
+
+/// Appends a unit cube centered at `offset` to the given vertex and triangle lists.
+pub fn append_cube(
+    offset: Vector3<f32>,
+    vertices: &mut Vec<StaticVertex>,
+    triangles: &mut Vec<TriangleDefinition>,
+) {
+    // Each face has its own set of vertices, so normals won't be smoothed across the edges.
+    let faces = [
+        (Vector3::x(), Vector3::y(), Vector3::z()),
+        (-Vector3::x(), Vector3::y(), -Vector3::z()),
+        (Vector3::y(), Vector3::z(), Vector3::x()),
+        (-Vector3::y(), Vector3::z(), -Vector3::x()),
+        (Vector3::z(), Vector3::y(), -Vector3::x()),
+        (-Vector3::z(), Vector3::y(), Vector3::x()),
+    ];
+
+    for (normal, up, right) in faces {
+        let base = vertices.len() as u32;
+        let center = offset + normal.scale(0.5);
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            let position = center + right.scale(u - 0.5) + up.scale(v - 0.5);
+            vertices.push(StaticVertex::from_pos_uv_normal(
+                position,
+                Vector2::new(u, v),
+                normal,
+            ));
+        }
+        triangles.push(TriangleDefinition([base, base + 1, base + 2]));
+        triangles.push(TriangleDefinition([base, base + 2, base + 3]));
+    }
+}
+
+/// Creates surface data from the given vertices and triangles, computing tangents for it.
+pub fn make_surface_data(
+    vertices: Vec<StaticVertex>,
+    triangles: Vec<TriangleDefinition>,
+) -> SurfaceData {
+    let mut data = SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+    );
+    data.calculate_tangents().unwrap();
+    data
+}
+
+/// Creates surface data for a single unit cube.
+pub fn make_cube() -> SurfaceData {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+    append_cube(Vector3::default(), &mut vertices, &mut triangles);
+    make_surface_data(vertices, triangles)
+}
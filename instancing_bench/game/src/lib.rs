@@ -0,0 +1,346 @@
+//! Game project.
+use crate::geometry::{append_cube, make_cube, make_surface_data};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        utils::make_dropdown_list_option,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext},
+    resource::model::{Model, ModelResource, ModelResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+mod geometry;
+
+/// Amount of cubes along each side of the grid, total amount of cubes is a square of this value.
+const GRID_SIZE: usize = 100;
+/// Distance between cubes in the grid.
+const GRID_SPACING: f32 = 2.0;
+/// Prefab of a single cube, that is instantiated for every cell of the grid.
+const PREFAB_PATH: &str = "data/cube.rgs";
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Visit, Reflect)]
+pub enum BenchmarkMode {
+    /// Every cube is a separate instance of the prefab with its own copy of the surface data and
+    /// material - no batching at all.
+    #[default]
+    IndividualNodes,
+    /// Geometry of every cube is merged into a single mesh.
+    StaticBatching,
+    /// Every cube is a separate instance of the prefab, all of them share the surface data and
+    /// the material of the prefab, so the renderer can draw them using instancing.
+    GpuInstancing,
+}
+
+impl BenchmarkMode {
+    const ALL: [BenchmarkMode; 3] = [
+        BenchmarkMode::IndividualNodes,
+        BenchmarkMode::StaticBatching,
+        BenchmarkMode::GpuInstancing,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            BenchmarkMode::IndividualNodes => "Individual Nodes",
+            BenchmarkMode::StaticBatching => "Static Batching",
+            BenchmarkMode::GpuInstancing => "GPU Instancing",
+        }
+    }
+}
+
+const SCENE_NAME: &str = "instancing_bench";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    instances_root: Handle<Node>,
+    mode: BenchmarkMode,
+    debug_text: Handle<UiNode>,
+    mode_selector: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prefab: Option<ModelResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Generates the prefab from the procedural cube and saves it to `data/cube.rgs`. The prefab is
+/// a committed asset, that is instantiated the same way as a model made in the editor, so this is
+/// done only by the `--generate-prefab` flag of the desktop executor, never by the game itself.
+pub fn generate_prefab() {
+    let mut scene = Scene::new();
+    MeshBuilder::new(BaseBuilder::new().with_name("Cube"))
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            make_cube(),
+        ))
+        .with_material(MaterialResource::new_ok(
+            ResourceKind::Embedded,
+            Material::standard(),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+
+    let mut visitor = Visitor::new();
+    Log::verify(scene.save("Scene", &mut visitor));
+    match visitor.save_binary(PREFAB_PATH) {
+        Ok(()) => Log::info(format!("The prefab is saved to {PREFAB_PATH}")),
+        Err(err) => Log::err(format!("Unable to save {PREFAB_PATH}. Reason: {err:?}")),
+    }
+}
+
+/// Gives every mesh of the instance its own copy of the surface data and a new material, which
+/// prevents the renderer from grouping them into instanced batches.
+fn make_unique(scene: &mut Scene, instance: Handle<Node>) {
+    let meshes = scene
+        .graph
+        .traverse_handle_iter(instance)
+        .filter(|handle| scene.graph[*handle].cast::<Mesh>().is_some())
+        .collect::<Vec<_>>();
+    for handle in meshes {
+        let Some(mesh) = scene.graph[handle].cast_mut::<Mesh>() else {
+            continue;
+        };
+        let surfaces = mesh
+            .surfaces()
+            .iter()
+            .map(|surface| {
+                let data = surface.data();
+                let data = data.data_ref().clone();
+                SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, data))
+                    .with_material(MaterialResource::new_ok(
+                        ResourceKind::Embedded,
+                        Material::standard(),
+                    ))
+                    .build()
+            })
+            .collect();
+        mesh.set_surfaces(surfaces);
+    }
+}
+
+fn grid_position(i: usize) -> Vector3<f32> {
+    let half_size = GRID_SIZE as f32 * GRID_SPACING * 0.5;
+    Vector3::new(
+        (i % GRID_SIZE) as f32 * GRID_SPACING - half_size,
+        0.0,
+        (i / GRID_SIZE) as f32 * GRID_SPACING - half_size,
+    )
+}
+
+fn build_instances(scene: &mut Scene, mode: BenchmarkMode, prefab: &ModelResource) -> Handle<Node> {
+    let material = MaterialResource::new_ok(ResourceKind::Embedded, Material::standard());
+
+    let mut instances = Vec::new();
+
+    match mode {
+        BenchmarkMode::IndividualNodes => {
+            for i in 0..GRID_SIZE * GRID_SIZE {
+                let instance = prefab.instantiate_at(scene, grid_position(i), Default::default());
+                make_unique(scene, instance);
+                instances.push(instance);
+            }
+        }
+        BenchmarkMode::StaticBatching => {
+            // The prefab is generated from the same cube, so the merged geometry matches it.
+            let mut vertices = Vec::new();
+            let mut triangles = Vec::new();
+            for i in 0..GRID_SIZE * GRID_SIZE {
+                append_cube(grid_position(i), &mut vertices, &mut triangles);
+            }
+
+            instances.push(
+                MeshBuilder::new(BaseBuilder::new())
+                    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                        ResourceKind::Embedded,
+                        make_surface_data(vertices, triangles),
+                    ))
+                    .with_material(material)
+                    .build()])
+                    .build(&mut scene.graph),
+            );
+        }
+        BenchmarkMode::GpuInstancing => {
+            // Instances share the surface data and material of the prefab, that is the only
+            // requirement for instancing, the renderer will group such surfaces automatically.
+            for i in 0..GRID_SIZE * GRID_SIZE {
+                instances.push(prefab.instantiate_at(scene, grid_position(i), Default::default()));
+            }
+        }
+    }
+
+    PivotBuilder::new(BaseBuilder::new().with_children(&instances)).build(&mut scene.graph)
+}
+
+impl Game {
+    fn set_mode(&mut self, mode: BenchmarkMode, context: &mut PluginContext) {
+        self.mode = mode;
+        // The instances are built, when the prefab is loaded.
+        let Some(prefab) = self.prefab.as_ref() else {
+            return;
+        };
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            if self.instances_root.is_some() {
+                scene.graph.remove_node(self.instances_root);
+            }
+            self.instances_root = build_instances(scene, mode, prefab);
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, mut context: PluginContext) {
+        context.task_pool.spawn_plugin_task(
+            context.resource_manager.request::<Model>(PREFAB_PATH),
+            |result, game: &mut Game, ctx| match result {
+                Ok(prefab) => {
+                    game.prefab = Some(prefab);
+                    game.set_mode(game.mode, ctx);
+                }
+                Err(err) => Log::err(format!("Unable to load {PREFAB_PATH}. Reason: {err:?}")),
+            },
+        );
+
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 60.0, -130.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        30.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+        self.set_mode(BenchmarkMode::IndividualNodes, &mut context);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let items = BenchmarkMode::ALL
+            .iter()
+            .map(|mode| make_dropdown_list_option(ctx, mode.name()))
+            .collect::<Vec<_>>();
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_desired_position(Vector2::new(5.0, 150.0)),
+        )
+        .with_title(WindowTitle::text("Benchmark Mode"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(WidgetBuilder::new().with_child({
+                self.mode_selector = DropdownListBuilder::new(
+                    WidgetBuilder::new()
+                        .with_height(22.0)
+                        .with_margin(Thickness::uniform(2.0)),
+                )
+                .with_items(items)
+                .with_selected(0)
+                .build(ctx);
+                self.mode_selector
+            }))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Geometry Instancing Benchmark\n\
+                    Mode: {}\nInstances: {}\nFrame Time: {:.3} ms\nDraw Calls: {}\n{}\n{}",
+                    self.mode.name(),
+                    GRID_SIZE * GRID_SIZE,
+                    statistics.pure_frame_time * 1000.0,
+                    statistics.geometry.draw_calls,
+                    statistics,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.mode_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(mode) = BenchmarkMode::ALL.get(*index) {
+                    if *mode != self.mode {
+                        self.set_mode(*mode, context);
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
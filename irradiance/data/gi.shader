@@ -0,0 +1,108 @@
+(
+    name: "ProbeLitShader",
+
+    properties: [
+        (
+            name: "diffuseColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+        // Spherical harmonics (L0 and L1 bands) of the radiance around the nearest probe.
+        (
+            name: "sh0",
+            kind: Vector3((0.0, 0.0, 0.0)),
+        ),
+        (
+            name: "sh1",
+            kind: Vector3((0.0, 0.0, 0.0)),
+        ),
+        (
+            name: "sh2",
+            kind: Vector3((0.0, 0.0, 0.0)),
+        ),
+        (
+            name: "sh3",
+            kind: Vector3((0.0, 0.0, 0.0)),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 normal;
+
+                void main()
+                {
+                    normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform vec4 diffuseColor;
+                uniform vec3 sh0;
+                uniform vec3 sh1;
+                uniform vec3 sh2;
+                uniform vec3 sh3;
+
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+
+                in vec3 normal;
+
+                const float PI = 3.14159265;
+
+                // Irradiance for the normal, the radiance is convolved with the clamped cosine
+                // lobe, which scales the bands by PI and 2*PI/3 (Ramamoorthi and Hanrahan).
+                vec3 irradiance(vec3 n)
+                {
+                    return PI * 0.282095 * sh0
+                        + 2.0 * PI / 3.0 * 0.488603 * (sh1 * n.y + sh2 * n.z + sh3 * n.x);
+                }
+
+                void main()
+                {
+                    vec3 n = normalize(normal);
+
+                    outColor = diffuseColor;
+                    outNormal = vec4(n * 0.5 + 0.5, 1.0);
+                    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+                    // Ambient lighting is multiplied by the albedo by the renderer, so only the
+                    // Lambertian normalization is applied here.
+                    outAmbient = vec4(max(irradiance(n), vec3(0.0)) / PI, 1.0);
+                    outDecalMask = 0u;
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,395 @@
+//! Game project.
+use crate::probes::{
+    PointLight, ProbeGrid, ShCoefficients, Sphere, PROBE_COUNT, ROOM_MAX, ROOM_MIN, WALLS,
+};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::SceneGraph,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, VerticalAlignment,
+    },
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+mod probes;
+
+const LIGHT_RADIUS: f32 = 9.0;
+const LIGHT_INTENSITY: f32 = 3.0;
+const LIGHT_HEIGHT: f32 = 3.2;
+/// Radius of the circle, that the light moves along.
+const LIGHT_ORBIT: f32 = 2.5;
+const WALL_THICKNESS: f32 = 0.1;
+const PROBE_MARKER_RADIUS: f32 = 0.08;
+/// Spheres, that move around the room and receive indirect light from the probes:
+/// `(radius, albedo, phase)`.
+const DYNAMIC_SPHERES: [(f32, [f32; 3], f32); 3] = [
+    (0.6, [0.9, 0.9, 0.9], 0.0),
+    (0.45, [0.9, 0.7, 0.3], 2.1),
+    (0.35, [0.3, 0.5, 0.9], 4.2),
+];
+
+const SCENE_NAME: &str = "irradiance";
+
+#[derive(Debug)]
+struct DynamicSphere {
+    node: Handle<Node>,
+    material: MaterialResource,
+    sphere: Sphere,
+    phase: f32,
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    light: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    probes_per_frame_slider: Handle<UiNode>,
+    /// Amount of probes, that are updated every frame.
+    probes_per_frame: usize,
+    time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    probe_grid: ProbeGrid,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spheres: Vec<DynamicSphere>,
+    /// Material of the marker of every probe, the markers show the lighting of their probes.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    probe_materials: Vec<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_mesh(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    surface: SurfaceData,
+    material: MaterialResource,
+) -> Handle<Node> {
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        surface,
+    ))
+    .with_material(material)
+    .build()])
+    .build(graph)
+}
+
+fn make_sphere_surface(radius: f32) -> SurfaceData {
+    SurfaceData::make_sphere(16, 16, radius, &Matrix4::identity())
+}
+
+fn to_color(albedo: Vector3<f32>) -> Color {
+    let [r, g, b] = [albedo.x, albedo.y, albedo.z].map(|c| (c * 255.0) as u8);
+    Color::opaque(r, g, b)
+}
+
+fn set_coefficients(material: &MaterialResource, coefficients: ShCoefficients) {
+    let mut material = material.data_ref();
+    for (index, coefficient) in coefficients.0.iter().enumerate() {
+        Log::verify(material.set_property(&format!("sh{index}").into(), (*coefficient).into()));
+    }
+}
+
+/// Walls of the room with the standard material, the engine lights them directly.
+fn build_room(graph: &mut Graph) {
+    let center = (ROOM_MIN + ROOM_MAX).scale(0.5);
+    let size = ROOM_MAX - ROOM_MIN;
+    for (axis, is_max, albedo) in WALLS {
+        let mut position = center;
+        position[axis] = if is_max {
+            ROOM_MAX[axis] + WALL_THICKNESS * 0.5
+        } else {
+            ROOM_MIN[axis] - WALL_THICKNESS * 0.5
+        };
+        let mut wall_size = size;
+        wall_size[axis] = WALL_THICKNESS;
+
+        let mut material = Material::standard();
+        Log::verify(material.set_property(&"diffuseColor".into(), to_color(albedo).into()));
+        make_mesh(
+            graph,
+            position,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&wall_size)),
+            MaterialResource::new_ok(ResourceKind::Embedded, material),
+        );
+    }
+}
+
+impl Game {
+    fn point_light(&self) -> PointLight {
+        PointLight {
+            position: Vector3::new(
+                self.time.cos() * LIGHT_ORBIT,
+                LIGHT_HEIGHT,
+                self.time.sin() * LIGHT_ORBIT,
+            ),
+            color: Vector3::repeat(LIGHT_INTENSITY),
+            radius: LIGHT_RADIUS,
+        }
+    }
+
+    fn make_material(shader: &ShaderResource, albedo: Vector3<f32>) -> MaterialResource {
+        let mut material = Material::from_shader(shader.clone(), None);
+        Log::verify(material.set_property(&"diffuseColor".into(), to_color(albedo).into()));
+        MaterialResource::new_ok(ResourceKind::Embedded, material)
+    }
+
+    /// Creates the objects, that are lit by the probes.
+    fn build_probe_lit_objects(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        for (radius, albedo, phase) in DYNAMIC_SPHERES {
+            let albedo = Vector3::from(albedo);
+            let material = Self::make_material(&shader, albedo);
+            let node = make_mesh(
+                &mut scene.graph,
+                Vector3::default(),
+                make_sphere_surface(radius),
+                material.clone(),
+            );
+            self.spheres.push(DynamicSphere {
+                node,
+                material,
+                sphere: Sphere {
+                    center: Default::default(),
+                    radius,
+                    albedo,
+                },
+                phase,
+            });
+        }
+
+        for position in self.probe_grid.positions().to_vec() {
+            let material = Self::make_material(&shader, Vector3::repeat(1.0));
+            make_mesh(
+                &mut scene.graph,
+                position,
+                make_sphere_surface(PROBE_MARKER_RADIUS),
+                material.clone(),
+            );
+            self.probe_materials.push(material);
+        }
+    }
+
+    fn update_scene(&mut self, dt: f32, context: &mut PluginContext) {
+        self.time += dt;
+
+        // Spheres move along the floor of the room and bounce up and down.
+        for dynamic in self.spheres.iter_mut() {
+            let t = self.time * 0.7 + dynamic.phase;
+            dynamic.sphere.center = Vector3::new(
+                t.sin() * 2.5,
+                dynamic.sphere.radius + (t * 2.0).sin().abs() * 1.2,
+                (t * 1.3).cos() * 2.5,
+            );
+        }
+
+        let light = self.point_light();
+        let spheres = self
+            .spheres
+            .iter()
+            .map(|dynamic| dynamic.sphere.clone())
+            .collect::<Vec<_>>();
+        self.probe_grid
+            .update(self.probes_per_frame, &light, &spheres);
+
+        for dynamic in self.spheres.iter() {
+            let nearest = self.probe_grid.nearest(dynamic.sphere.center);
+            set_coefficients(&dynamic.material, self.probe_grid.coefficients(nearest));
+        }
+        for (index, material) in self.probe_materials.iter().enumerate() {
+            set_coefficients(material, self.probe_grid.coefficients(index));
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        if let Some(node) = scene.graph.try_get_mut(self.light) {
+            node.local_transform_mut().set_position(light.position);
+        }
+        for dynamic in self.spheres.iter() {
+            if let Some(node) = scene.graph.try_get_mut(dynamic.node) {
+                node.local_transform_mut()
+                    .set_position(dynamic.sphere.center);
+            }
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.probes_per_frame = 8;
+
+        let mut scene = Scene::new();
+        // All the indirect light comes from the probes.
+        scene.rendering_options.ambient_lighting_color = Color::opaque(8, 8, 8);
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, ROOM_MIN.z + 0.3))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        build_room(&mut scene.graph);
+
+        self.light = PointLightBuilder::new(
+            BaseLightBuilder::new(BaseBuilder::new())
+                .with_color(Color::WHITE)
+                .with_intensity(LIGHT_INTENSITY),
+        )
+        .with_radius(LIGHT_RADIUS)
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let probes_per_frame_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("Probes Per Frame")
+        .build(ctx);
+        self.probes_per_frame_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(1.0)
+        .with_max(PROBE_COUNT as f32)
+        .with_step(1.0)
+        .with_value(self.probes_per_frame as f32)
+        .with_value_precision(0)
+        .show_value(true)
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(90.0)
+                .with_desired_position(Vector2::new(5.0, 140.0)),
+        )
+        .with_title(WindowTitle::text("Probe Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(probes_per_frame_text)
+                    .with_child(self.probes_per_frame_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context.resource_manager.request::<Shader>("data/gi.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.build_probe_lit_objects(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load probe shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.update_scene(context.dt, context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let probes_per_second = self.probes_per_frame as f32 / context.dt.max(f32::EPSILON);
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Irradiance Probes\nProbes: {PROBE_COUNT}, {} per frame \
+                        ({probes_per_second:.0} per second, full update every {} frames)\n\
+                        Encoding Time: {:.3} ms\n{}\n{}",
+                        self.probes_per_frame,
+                        PROBE_COUNT.div_ceil(self.probes_per_frame.max(1)),
+                        self.probe_grid.encoding_time.as_secs_f32() * 1000.0,
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, _context: &mut PluginContext, message: &UiMessage) {
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.probes_per_frame_slider
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.probes_per_frame = value.round() as usize;
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
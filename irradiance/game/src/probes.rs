@@ -0,0 +1,235 @@
+//! Grid of irradiance probes. Every probe stores the light, that comes to it from all directions,
+//! as spherical harmonics (first two bands, four coefficients per color channel). The light is
+//! captured on CPU by casting rays from the probe against the analytic description of the room:
+//! a hit surface reflects the direct light of the point light, so the probes contain one bounce
+//! of indirect lighting. Only a few probes are updated every frame, the rest keep their old values.
+use fyrox::core::algebra::Vector3;
+use std::{
+    f32::consts::PI,
+    time::{Duration, Instant},
+};
+
+/// Amount of probes along each axis of the grid.
+pub const GRID_SIZE: usize = 4;
+pub const PROBE_COUNT: usize = GRID_SIZE * GRID_SIZE * GRID_SIZE;
+pub const ROOM_MIN: Vector3<f32> = Vector3::new(-4.0, 0.0, -4.0);
+pub const ROOM_MAX: Vector3<f32> = Vector3::new(4.0, 4.0, 4.0);
+/// Amount of rays, that are cast from a probe to capture its lighting.
+const RAY_COUNT: usize = 128;
+/// Basis functions of the first two bands, without the direction.
+const SH_Y0: f32 = 0.282095;
+const SH_Y1: f32 = 0.488603;
+
+/// Albedo of the walls of the room: `(axis, is the max side, albedo)`.
+pub const WALLS: [(usize, bool, Vector3<f32>); 6] = [
+    (0, false, Vector3::new(0.8, 0.1, 0.1)),
+    (0, true, Vector3::new(0.1, 0.8, 0.1)),
+    (1, false, Vector3::new(0.75, 0.75, 0.75)),
+    (1, true, Vector3::new(0.75, 0.75, 0.75)),
+    (2, false, Vector3::new(0.75, 0.75, 0.75)),
+    (2, true, Vector3::new(0.75, 0.75, 0.75)),
+];
+
+#[derive(Clone, Debug)]
+pub struct Sphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+    pub albedo: Vector3<f32>,
+}
+
+/// Point light with a smooth falloff, that reaches zero at the radius.
+#[derive(Clone, Debug)]
+pub struct PointLight {
+    pub position: Vector3<f32>,
+    /// Color multiplied by the intensity.
+    pub color: Vector3<f32>,
+    pub radius: f32,
+}
+
+/// Radiance around a point as spherical harmonics: the constant band and the linear band along
+/// Y, Z and X axes.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ShCoefficients(pub [Vector3<f32>; 4]);
+
+/// Surface, that is hit by a ray.
+struct Hit {
+    distance: f32,
+    normal: Vector3<f32>,
+    albedo: Vector3<f32>,
+}
+
+/// Directions of the rays, evenly distributed over the sphere (Fibonacci lattice).
+fn sphere_directions(count: usize) -> Vec<Vector3<f32>> {
+    let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+    (0..count)
+        .map(|i| {
+            let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+            let radius = (1.0 - y * y).sqrt();
+            let angle = golden_angle * i as f32;
+            Vector3::new(radius * angle.cos(), y, radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Finds the wall, that the ray leaves the room through. The origin must be inside of the room.
+fn trace_walls(origin: Vector3<f32>, direction: Vector3<f32>) -> Option<Hit> {
+    WALLS
+        .iter()
+        .filter_map(|&(axis, is_max, albedo)| {
+            let (bound, sign) = if is_max {
+                (ROOM_MAX[axis], 1.0)
+            } else {
+                (ROOM_MIN[axis], -1.0)
+            };
+            if direction[axis] * sign <= 0.0 {
+                return None;
+            }
+            let mut normal = Vector3::default();
+            normal[axis] = -sign;
+            Some(Hit {
+                distance: (bound - origin[axis]) / direction[axis],
+                normal,
+                albedo,
+            })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+fn trace_sphere(origin: Vector3<f32>, direction: Vector3<f32>, sphere: &Sphere) -> Option<Hit> {
+    let offset = origin - sphere.center;
+    let b = offset.dot(&direction);
+    let c = offset.norm_squared() - sphere.radius * sphere.radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    if distance <= 0.0 {
+        return None;
+    }
+    Some(Hit {
+        distance,
+        normal: (offset + direction.scale(distance)).scale(1.0 / sphere.radius),
+        albedo: sphere.albedo,
+    })
+}
+
+/// Light, that comes to the point from the direction: the direct light of the point light,
+/// reflected by the first surface on the way.
+fn radiance(
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    light: &PointLight,
+    spheres: &[Sphere],
+) -> Vector3<f32> {
+    let Some(hit) = spheres
+        .iter()
+        .filter_map(|sphere| trace_sphere(origin, direction, sphere))
+        .chain(trace_walls(origin, direction))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    else {
+        return Vector3::default();
+    };
+
+    let point = origin + direction.scale(hit.distance);
+    let to_light = light.position - point;
+    let distance = to_light.norm();
+    if distance >= light.radius || distance <= f32::EPSILON {
+        return Vector3::default();
+    }
+    let cos_theta = hit.normal.dot(&to_light) / distance;
+    if cos_theta <= 0.0 {
+        return Vector3::default();
+    }
+    let falloff = (1.0 - (distance / light.radius).powi(2)).powi(2);
+    // Lambertian surface reflects albedo / PI of the irradiance in every direction.
+    light
+        .color
+        .component_mul(&hit.albedo)
+        .scale(cos_theta * falloff / PI)
+}
+
+#[derive(Debug)]
+pub struct ProbeGrid {
+    positions: Vec<Vector3<f32>>,
+    coefficients: Vec<ShCoefficients>,
+    directions: Vec<Vector3<f32>>,
+    /// Index of the probe, that will be updated next.
+    next: usize,
+    /// Time spent on the capture of the probes during the last update.
+    pub encoding_time: Duration,
+}
+
+impl Default for ProbeGrid {
+    fn default() -> Self {
+        let size = ROOM_MAX - ROOM_MIN;
+        // Probes are placed in the centers of the cells of the grid, so they are never on a wall.
+        let positions = (0..PROBE_COUNT)
+            .map(|index| {
+                let cell = Vector3::new(
+                    index % GRID_SIZE,
+                    (index / GRID_SIZE) % GRID_SIZE,
+                    index / (GRID_SIZE * GRID_SIZE),
+                )
+                .map(|c| (c as f32 + 0.5) / GRID_SIZE as f32);
+                ROOM_MIN + size.component_mul(&cell)
+            })
+            .collect();
+        Self {
+            positions,
+            coefficients: vec![Default::default(); PROBE_COUNT],
+            directions: sphere_directions(RAY_COUNT),
+            next: 0,
+            encoding_time: Default::default(),
+        }
+    }
+}
+
+impl ProbeGrid {
+    pub fn positions(&self) -> &[Vector3<f32>] {
+        &self.positions
+    }
+
+    pub fn coefficients(&self, index: usize) -> ShCoefficients {
+        self.coefficients[index]
+    }
+
+    /// Returns the index of the probe, that is the closest to the point.
+    pub fn nearest(&self, point: Vector3<f32>) -> usize {
+        self.positions
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (*a - point)
+                    .norm_squared()
+                    .total_cmp(&(*b - point).norm_squared())
+            })
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// Projects the light around the probe onto the spherical harmonics.
+    fn capture(&self, index: usize, light: &PointLight, spheres: &[Sphere]) -> ShCoefficients {
+        let origin = self.positions[index];
+        let mut sh = ShCoefficients::default();
+        for direction in self.directions.iter() {
+            let radiance = radiance(origin, *direction, light, spheres);
+            sh.0[0] += radiance.scale(SH_Y0);
+            sh.0[1] += radiance.scale(SH_Y1 * direction.y);
+            sh.0[2] += radiance.scale(SH_Y1 * direction.z);
+            sh.0[3] += radiance.scale(SH_Y1 * direction.x);
+        }
+        // Every ray covers an equal part of the sphere.
+        let weight = 4.0 * PI / self.directions.len() as f32;
+        ShCoefficients(sh.0.map(|c| c.scale(weight)))
+    }
+
+    /// Updates the next `count` probes (round-robin).
+    pub fn update(&mut self, count: usize, light: &PointLight, spheres: &[Sphere]) {
+        let start = Instant::now();
+        for _ in 0..count.min(PROBE_COUNT) {
+            self.coefficients[self.next] = self.capture(self.next, light, spheres);
+            self.next = (self.next + 1) % PROBE_COUNT;
+        }
+        self.encoding_time = start.elapsed();
+    }
+}
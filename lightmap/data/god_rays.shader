@@ -0,0 +1,119 @@
+(
+    name: "GodRaysShader",
+
+    properties: [
+        (
+            name: "lightScreenPosition",
+            kind: Vector2((0.5, 0.5)),
+        ),
+        (
+            name: "screenSize",
+            kind: Vector2((1.0, 1.0)),
+        ),
+        (
+            name: "lightColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+        (
+            name: "rayLength",
+            kind: Float(0.8),
+        ),
+        (
+            name: "intensity",
+            kind: Float(1.0),
+        ),
+        (
+            name: "decay",
+            kind: Float(0.97),
+        ),
+        (
+            name: "sampleCount",
+            kind: UInt(64),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                // The rays are drawn over the whole screen, regardless of the scene depth.
+                depth_test: false,
+                // Rays are added on top of the lit scene.
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add,
+                    ),
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+
+                void main()
+                {
+                    // The quad is already in normalized device coordinates.
+                    gl_Position = vec4(vertexPosition.xy, 0.0, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D fyrox_sceneDepth;
+                uniform vec2 lightScreenPosition;
+                uniform vec2 screenSize;
+                uniform vec4 lightColor;
+                uniform float rayLength;
+                uniform float intensity;
+                uniform float decay;
+                uniform uint sampleCount;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    vec2 texCoord = gl_FragCoord.xy / screenSize;
+                    float samples = float(max(sampleCount, 1u));
+                    vec2 delta = (lightScreenPosition - texCoord) * rayLength / samples;
+
+                    // March from the pixel towards the light and accumulate samples, that are not
+                    // occluded by the geometry - only the sky has the maximum depth. Distant samples
+                    // contribute less, which makes the rays fade out.
+                    float illumination = 0.0;
+                    float weight = 1.0;
+                    vec2 position = texCoord;
+                    for (uint i = 0u; i < sampleCount; ++i) {
+                        position += delta;
+                        if (all(greaterThanEqual(position, vec2(0.0))) && all(lessThanEqual(position, vec2(1.0)))) {
+                            float depth = texture(fyrox_sceneDepth, position).r;
+                            illumination += step(0.9999, depth) * weight;
+                        }
+                        weight *= decay;
+                    }
+
+                    FragColor = vec4(lightColor.rgb * illumination * intensity / samples, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
@@ -1,7 +1,10 @@
 //! Editor with your game connected to it as a plugin.
+mod spline_cam_path;
+
 use fyrox::event_loop::EventLoop;
 use fyroxed_base::{Editor, StartupData};
 use lightmap::Game;
+use spline_cam_path::SplineCamPath;
 
 fn main() {
     let event_loop = EventLoop::new().unwrap();
@@ -9,6 +12,7 @@ fn main() {
         working_directory: Default::default(),
         scenes: vec!["data/Sponza.rgs".into()],
     }));
-    editor.add_game_plugin(Game);
+    editor.add_game_plugin(Game::default());
+    editor.add_editor_plugin(SplineCamPath::default());
     editor.run(event_loop)
 }
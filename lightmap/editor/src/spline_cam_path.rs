@@ -0,0 +1,393 @@
+//! Editor plugin, that allows to author a camera path as a smooth spline. In the "Spline Path" mode
+//! every left click in the scene viewport adds a control point where the ray from the cursor hits
+//! the scene. The path is drawn in the viewport, can be previewed with the editor camera and
+//! exported to a file.
+use editor_utils::{current_game_scene, make_button, open_window, place_camera, set_text};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        button::ButtonMessage,
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        message::{MessageDirection, MouseButton, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        Orientation, Thickness, UiNode, VerticalAlignment,
+    },
+    scene::{
+        camera::Camera,
+        debug::Line,
+        graph::{physics::RayCastOptions, Graph},
+        node::Node,
+    },
+};
+use fyroxed_base::{plugin::EditorPlugin, Editor};
+use std::time::Instant;
+
+/// Path of the file, the control points are exported to.
+const EXPORT_PATH: &str = "data/camera_path.bin";
+/// Amount of line segments, that approximate a single span of the spline between two control
+/// points.
+const SEGMENTS_PER_SPAN: usize = 16;
+/// Max distance of the ray, that picks control points.
+const PICK_DISTANCE: f32 = 1000.0;
+const PATH_COLOR: Color = Color::opaque(255, 200, 0);
+const POINT_COLOR: Color = Color::opaque(255, 60, 60);
+const POINT_RADIUS: f32 = 0.1;
+
+/// Point on a Catmull-Rom spline segment between `p1` and `p2`, `t` is in `[0; 1]` range.
+fn catmull_rom(
+    p0: &Vector3<f32>,
+    p1: &Vector3<f32>,
+    p2: &Vector3<f32>,
+    p3: &Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+/// Point on the spline through all the control points, `t` is in `[0; 1]` range and covers the
+/// whole path. The end points are duplicated, so the spline passes through every control point.
+fn sample_spline(points: &[Vector3<f32>], t: f32) -> Vector3<f32> {
+    let span_count = points.len() - 1;
+    let position = t.clamp(0.0, 1.0) * span_count as f32;
+    let span = (position as usize).min(span_count - 1);
+    let point = |index: isize| points[index.clamp(0, span_count as isize) as usize];
+    let span = span as isize;
+    catmull_rom(
+        &point(span - 1),
+        &point(span),
+        &point(span + 1),
+        &point(span + 2),
+        position - span as f32,
+    )
+}
+
+pub struct SplineCamPath {
+    window: Handle<UiNode>,
+    mode: Handle<UiNode>,
+    play: Handle<UiNode>,
+    export: Handle<UiNode>,
+    clear: Handle<UiNode>,
+    duration_slider: Handle<UiNode>,
+    info: Handle<UiNode>,
+    /// Clicks in the viewport add control points only when the mode is enabled.
+    mode_enabled: bool,
+    control_points: Vec<Vector3<f32>>,
+    /// Time in seconds to travel the whole path.
+    duration: f32,
+    /// Start of the playback, `None` if the camera is not moving along the path.
+    playback_start: Option<Instant>,
+}
+
+impl Default for SplineCamPath {
+    fn default() -> Self {
+        Self {
+            window: Default::default(),
+            mode: Default::default(),
+            play: Default::default(),
+            export: Default::default(),
+            clear: Default::default(),
+            duration_slider: Default::default(),
+            info: Default::default(),
+            mode_enabled: false,
+            control_points: Default::default(),
+            duration: 10.0,
+            playback_start: None,
+        }
+    }
+}
+
+/// Casts the ray from the cursor into the physics of the scene. If it hits nothing (the level
+/// may have no colliders), the ray is intersected with the ground plane instead.
+fn pick(
+    graph: &Graph,
+    camera: Handle<Node>,
+    cursor: Vector2<f32>,
+    viewport_size: Vector2<f32>,
+) -> Option<Vector3<f32>> {
+    let camera = graph.try_get_of_type::<Camera>(camera)?;
+    let ray = camera.make_ray(cursor, viewport_size);
+    let direction = ray.dir.try_normalize(f32::EPSILON)?;
+
+    let mut intersections = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(ray.origin),
+            ray_direction: direction,
+            max_len: PICK_DISTANCE,
+            groups: Default::default(),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+    if let Some(intersection) = intersections.first() {
+        return Some(intersection.position.coords);
+    }
+
+    if direction.y.abs() <= f32::EPSILON {
+        return None;
+    }
+    let distance = -ray.origin.y / direction.y;
+    (distance > 0.0).then(|| ray.origin + direction * distance)
+}
+
+impl SplineCamPath {
+    fn sync_info(&self, editor: &Editor) {
+        let state = if self.playback_start.is_some() {
+            "Playing"
+        } else {
+            "Stopped"
+        };
+        set_text(
+            editor,
+            self.info,
+            format!("Control Points: {} - {}", self.control_points.len(), state),
+        );
+    }
+
+    /// Adds a control point under the cursor, the position of the cursor is in screen space.
+    fn add_control_point(&mut self, editor: &mut Editor, cursor: Vector2<f32>) {
+        let frame = editor.scene_viewer.frame();
+        let Some((origin, size)) = editor
+            .engine
+            .user_interfaces
+            .first()
+            .try_get(frame)
+            .map(|frame| (frame.screen_position(), frame.actual_global_size()))
+        else {
+            return;
+        };
+        let Some((controller, scene)) = current_game_scene(editor) else {
+            return;
+        };
+        if let Some(point) = pick(&scene.graph, controller.camera, cursor - origin, size) {
+            self.control_points.push(point);
+            self.sync_info(editor);
+        }
+    }
+
+    /// Draws the spline and its control points. The editor clears the lines of the scene every
+    /// frame, so this must be done after the update of the editor.
+    fn draw(&self, editor: &mut Editor) {
+        let Some((_, scene)) = current_game_scene(editor) else {
+            return;
+        };
+        let drawing_context = &mut scene.drawing_context;
+        for point in self.control_points.iter() {
+            drawing_context.draw_sphere(*point, 8, 8, POINT_RADIUS, POINT_COLOR);
+        }
+        if self.control_points.len() < 2 {
+            return;
+        }
+
+        let segment_count = (self.control_points.len() - 1) * SEGMENTS_PER_SPAN;
+        let strip = (0..=segment_count)
+            .map(|i| sample_spline(&self.control_points, i as f32 / segment_count as f32))
+            .collect::<Vec<_>>();
+        for pair in strip.windows(2) {
+            drawing_context.add_line(Line {
+                begin: pair[0],
+                end: pair[1],
+                color: PATH_COLOR,
+            });
+        }
+    }
+
+    /// Moves the editor camera to the point of the path. The camera looks at a point slightly
+    /// ahead on the path, at the end of the path it keeps the direction it had.
+    fn apply_camera(&self, editor: &mut Editor, t: f32) {
+        let position = sample_spline(&self.control_points, t);
+        let ahead = sample_spline(&self.control_points, (t + 0.01).min(1.0));
+        let Some((controller, scene)) = current_game_scene(editor) else {
+            return;
+        };
+        let rotation = match (ahead - position).try_normalize(f32::EPSILON) {
+            Some(direction) => UnitQuaternion::face_towards(&direction, &Vector3::y()),
+            None => **scene.graph[controller.pivot].local_transform().rotation(),
+        };
+        place_camera(controller, scene, position, rotation);
+    }
+
+    fn stop(&mut self, editor: &mut Editor) {
+        self.playback_start = None;
+        // The preview ends at the last control point, the camera stays there instead of
+        // returning to where it was before the preview.
+        if let Some(last) = self.control_points.last() {
+            if let Some((controller, scene)) = current_game_scene(editor) {
+                controller.set_position(&mut scene.graph, *last);
+            }
+        }
+        self.sync_info(editor);
+    }
+
+    fn export(&mut self, editor: &Editor) {
+        let mut visitor = Visitor::new();
+        Log::verify(self.control_points.visit("ControlPoints", &mut visitor));
+        match visitor.save_binary(EXPORT_PATH) {
+            Ok(_) => set_text(
+                editor,
+                self.info,
+                format!("Exported {} control points", self.control_points.len()),
+            ),
+            Err(err) => Log::err(format!(
+                "Unable to export the camera path to {EXPORT_PATH}. Reason: {err:?}"
+            )),
+        }
+    }
+}
+
+impl EditorPlugin for SplineCamPath {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let ctx = &mut editor.engine.user_interfaces.first_mut().build_ctx();
+
+        self.mode = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Spline Path")
+                .build(ctx),
+        )
+        .checked(Some(self.mode_enabled))
+        .build(ctx);
+        self.play = make_button(ctx, "Play");
+        self.export = make_button(ctx, "Export");
+        self.clear = make_button(ctx, "Clear");
+        self.duration_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(1.0)
+        .with_max(60.0)
+        .with_step(1.0)
+        .with_value(self.duration)
+        .show_value(true)
+        .build(ctx);
+        self.info = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_text("Control Points: 0 - Stopped")
+            .build(ctx);
+
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(420.0)
+                .with_height(100.0)
+                .with_desired_position(Vector2::new(300.0, 60.0)),
+        )
+        .with_title(WindowTitle::text("Spline Camera Path"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(self.mode)
+                                .with_child(self.play)
+                                .with_child(self.export)
+                                .with_child(self.clear),
+                        )
+                        .with_orientation(Orientation::Horizontal)
+                        .build(ctx),
+                    )
+                    .with_child(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(4.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_text("Duration (s)")
+                                    .build(ctx),
+                                )
+                                .with_child(self.duration_slider),
+                        )
+                        .with_orientation(Orientation::Horizontal)
+                        .build(ctx),
+                    )
+                    .with_child(self.info),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+
+        open_window(editor, self.window);
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        let destination = message.destination();
+        if let Some(WidgetMessage::MouseDown {
+            pos,
+            button: MouseButton::Left,
+        }) = message.data()
+        {
+            if self.mode_enabled && destination == editor.scene_viewer.frame() {
+                self.add_control_point(editor, *pos);
+            }
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if destination == self.mode && message.direction() == MessageDirection::FromWidget {
+                self.mode_enabled = *value;
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if destination == self.duration_slider
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.duration = *value;
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if destination == self.play {
+                if self.playback_start.is_some() {
+                    self.stop(editor);
+                } else if self.control_points.len() >= 2 {
+                    self.playback_start = Some(Instant::now());
+                    self.sync_info(editor);
+                } else {
+                    set_text(
+                        editor,
+                        self.info,
+                        "Add at least two control points".to_string(),
+                    );
+                }
+            } else if destination == self.export {
+                self.export(editor);
+            } else if destination == self.clear {
+                self.control_points.clear();
+                self.stop(editor);
+            }
+        }
+    }
+
+    fn on_post_update(&mut self, editor: &mut Editor) {
+        self.draw(editor);
+
+        let Some(start) = self.playback_start else {
+            return;
+        };
+        let t = start.elapsed().as_secs_f32() / self.duration;
+        if t >= 1.0 {
+            self.stop(editor);
+        } else {
+            self.apply_camera(editor, t);
+        }
+    }
+}
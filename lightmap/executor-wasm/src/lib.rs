@@ -39,6 +39,7 @@ pub fn set_panic_hook() {
 #[wasm_bindgen]
 pub fn main() {
     set_panic_hook();
+    common_scripts::throttle::watch_page_visibility();
     let mut executor = Executor::new();
     executor.add_plugin(Game::default());
     executor.run()
@@ -1,9 +1,44 @@
 //! Executor with your game connected to it as a plugin.
-use fyrox::engine::executor::Executor;
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
+use fyrox::{
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+};
 use lightmap::Game;
 
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+
 fn main() {
-    let mut executor = Executor::new();
-    executor.add_plugin(Game::default());
+    // Frames are paced by `FramePacer`, which needs vsync to be disabled.
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            vsync: false,
+            ..Default::default()
+        },
+    );
+    // `--benchmark` renders a fixed amount of frames, writes FPS statistics to a file and exits.
+    // `--tolerance <value>` sets max difference of a color channel of a pixel of the last frame
+    // from the golden reference, `--bless` writes the last frame as the golden reference.
+    if std::env::args().any(|arg| arg == "--benchmark") {
+        let tolerance = std::env::args()
+            .skip_while(|arg| arg != "--tolerance")
+            .nth(1)
+            .and_then(|value| value.parse().ok());
+        let bless = std::env::args().any(|arg| arg == "--bless");
+        executor.add_plugin(Game::with_benchmark(tolerance, bless));
+    } else {
+        executor.add_plugin(Game::default());
+        #[cfg(feature = "record")]
+        if let Some(recorder) = Recorder::from_args() {
+            executor.add_plugin(recorder);
+        }
+        // Pacing would distort the results of the benchmark.
+        executor.add_plugin(FramePacer::default());
+    }
     executor.run()
 }
@@ -0,0 +1,285 @@
+//! Baking of the lightmap at runtime. The baking runs in a separate thread, which shares its
+//! progress with the game, so the game stays responsive and shows the progress of every pass.
+//! There are no threads on wasm32, so the lightmap is baked right away there and the game freezes
+//! until it is done.
+use fyrox::{
+    core::{algebra::Vector2, instant::Instant, log::Log, pool::Handle},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        message::{MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, UserInterface,
+    },
+    scene::Scene,
+    utils::lightmap::{
+        CancellationToken, Lightmap, LightmapInputData, ProgressIndicator, ProgressStage,
+    },
+};
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+/// Density of the lightmap texels, lower values bake faster, but the lighting is less detailed.
+const TEXELS_PER_UNIT: u32 = 32;
+/// Relative spacing between the patches of the lightmap UV atlas.
+const UV_SPACING: f32 = 0.005;
+/// How often the baker thread publishes its progress.
+#[cfg(not(target_arch = "wasm32"))]
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A pass of the lightmapper. The lightmapper of the engine computes direct lighting only, so
+/// there are no indirect lighting or denoising passes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BakePass {
+    LightsCaching,
+    UvGeneration,
+    GeometryCaching,
+    DirectLighting,
+}
+
+impl BakePass {
+    fn from_stage(stage: ProgressStage) -> Self {
+        match stage {
+            ProgressStage::LightsCaching => Self::LightsCaching,
+            ProgressStage::UvGeneration => Self::UvGeneration,
+            ProgressStage::GeometryCaching => Self::GeometryCaching,
+            ProgressStage::CalculatingLight => Self::DirectLighting,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::LightsCaching => "Caching Lights",
+            Self::UvGeneration => "Generating UVs",
+            Self::GeometryCaching => "Caching Geometry",
+            Self::DirectLighting => "Direct Lighting",
+        }
+    }
+}
+
+/// Progress of the baking, that is shared between the baker thread and the game.
+#[derive(Debug)]
+pub struct BakeProgress {
+    pub pass: BakePass,
+    /// Progress of the current pass in `[0; 1]` range.
+    pub progress: f32,
+    /// Result of the baking, it is set when the baker thread finishes.
+    pub result: Option<Result<Lightmap, String>>,
+}
+
+/// Publishes the progress of the lightmapper until it finishes.
+#[cfg(not(target_arch = "wasm32"))]
+fn bake(data: LightmapInputData, progress: Arc<Mutex<BakeProgress>>) {
+    let indicator = ProgressIndicator::new();
+    let result = std::thread::scope(|scope| {
+        let baker = scope.spawn(|| {
+            Lightmap::new(
+                data,
+                TEXELS_PER_UNIT,
+                UV_SPACING,
+                CancellationToken::new(),
+                indicator.clone(),
+            )
+        });
+        while !baker.is_finished() {
+            if let Ok(mut progress) = progress.lock() {
+                progress.pass = BakePass::from_stage(indicator.stage());
+                progress.progress = indicator.progress_percent() as f32 / 100.0;
+            }
+            std::thread::sleep(PROGRESS_INTERVAL);
+        }
+        match baker.join() {
+            Ok(result) => result.map_err(|err| format!("{err:?}")),
+            Err(_) => Err("The baker thread has panicked.".to_string()),
+        }
+    });
+    if let Ok(mut progress) = progress.lock() {
+        progress.result = Some(result);
+    }
+}
+
+/// Bakes the lightmap on the current thread, the progress is not published.
+#[cfg(target_arch = "wasm32")]
+fn bake(data: LightmapInputData, progress: Arc<Mutex<BakeProgress>>) {
+    let result = Lightmap::new(
+        data,
+        TEXELS_PER_UNIT,
+        UV_SPACING,
+        CancellationToken::new(),
+        ProgressIndicator::new(),
+    )
+    .map_err(|err| format!("{err:?}"));
+    if let Ok(mut progress) = progress.lock() {
+        progress.result = Some(result);
+    }
+}
+
+/// A window, that starts the baking and shows its progress.
+#[derive(Default, Debug)]
+pub struct BakePanel {
+    bake_button: Handle<UiNode>,
+    progress_panel: Handle<UiNode>,
+    pass_text: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    progress: Option<Arc<Mutex<BakeProgress>>>,
+    started: Option<Instant>,
+}
+
+impl BakePanel {
+    pub fn build_window(&mut self, ui: &mut UserInterface) {
+        let ctx = &mut ui.build_ctx();
+
+        self.bake_button =
+            ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                .with_text("Bake Lightmap")
+                .build(ctx);
+        self.pass_text =
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0))).build(ctx);
+        self.progress_bar = ProgressBarBuilder::new(
+            WidgetBuilder::new()
+                .with_height(20.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .build(ctx);
+        self.progress_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(self.pass_text)
+                .with_child(self.progress_bar),
+        )
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(100.0)
+                .with_desired_position(Vector2::new(5.0, 605.0)),
+        )
+        .with_title(WindowTitle::text("Lightmap"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.bake_button)
+                    .with_child(self.progress_panel),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn set_baking(&self, ui: &UserInterface, baking: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.progress_panel,
+            MessageDirection::ToWidget,
+            baking,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.bake_button,
+            MessageDirection::ToWidget,
+            !baking,
+        ));
+    }
+
+    fn start(&mut self, ui: &UserInterface, scene: &Scene) {
+        if self.progress.is_some() {
+            return;
+        }
+
+        // Gathering of the input data needs the scene, so it is done right away. It is much
+        // faster than the rest of the baking.
+        let data = match LightmapInputData::from_scene(
+            scene,
+            |_, _| true,
+            CancellationToken::new(),
+            ProgressIndicator::new(),
+        ) {
+            Ok(data) => data,
+            Err(err) => {
+                Log::err(format!("Unable to bake lightmap. Reason: {err:?}"));
+                return;
+            }
+        };
+
+        let progress = Arc::new(Mutex::new(BakeProgress {
+            pass: BakePass::LightsCaching,
+            progress: 0.0,
+            result: None,
+        }));
+        let baker_progress = progress.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(move || bake(data, baker_progress));
+        #[cfg(target_arch = "wasm32")]
+        bake(data, baker_progress);
+        self.progress = Some(progress);
+        self.started = Some(Instant::now());
+        self.set_baking(ui, true);
+    }
+
+    /// Shows the progress of the baking and applies the lightmap to the scene, when the baking
+    /// is done.
+    pub fn update(&mut self, ui: &UserInterface, scene: Option<&mut Scene>) {
+        let Some(progress) = self.progress.as_ref() else {
+            return;
+        };
+        let Ok(mut progress) = progress.lock() else {
+            return;
+        };
+
+        let Some(result) = progress.result.take() else {
+            ui.send_message(TextMessage::text(
+                self.pass_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "{}: {:.0}%",
+                    progress.pass.name(),
+                    progress.progress * 100.0
+                ),
+            ));
+            ui.send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                progress.progress,
+            ));
+            return;
+        };
+        drop(progress);
+
+        match result {
+            Ok(lightmap) => {
+                Log::info(format!(
+                    "Lightmap baked in {:.1} s",
+                    self.started.map_or(0.0, |t| t.elapsed().as_secs_f32())
+                ));
+                if let Some(scene) = scene {
+                    if let Err(err) = scene.graph.set_lightmap(lightmap) {
+                        Log::err(format!("Unable to apply lightmap. Reason: {err:?}"));
+                    }
+                }
+            }
+            Err(err) => Log::err(format!("Unable to bake lightmap. Reason: {err}")),
+        }
+        self.progress = None;
+        self.started = None;
+        self.set_baking(ui, false);
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+        scene: Option<&Scene>,
+    ) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.bake_button {
+                if let Some(scene) = scene {
+                    self.start(ui, scene);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Benchmark mode - renders a fixed amount of frames, writes FPS statistics to a file and exits.
+//! The last frame of the scene is compared with a golden reference image to catch visual
+//! regressions. A missing reference is a failure, the reference is written only in bless mode.
+use common_scripts::{capture_scene, visual_regression};
+use fyrox::{
+    core::{log::Log, pool::Handle},
+    event_loop::EventLoopWindowTarget,
+    renderer::Renderer,
+    scene::Scene,
+};
+use std::{fmt::Write, fs, path::Path};
+
+/// Amount of frames, that are measured.
+const FRAME_COUNT: usize = 1000;
+/// Name of the file with the results, it is written to the working directory.
+const RESULTS_FILE: &str = "benchmark_results.txt";
+const GOLDEN_FILE: &str = "data/golden.png";
+const DIFF_FILE: &str = "data/diff.png";
+/// Max difference of a color channel of a pixel, that is not considered as a mismatch.
+pub const DEFAULT_TOLERANCE: u8 = 8;
+/// The frame does not match the reference, if more pixels than this (in percents) differ.
+const MAX_DIFFERING_PERCENT: f32 = 1.0;
+
+#[derive(Debug)]
+pub struct Benchmark {
+    /// FPS of every measured frame.
+    samples: Vec<f32>,
+    tolerance: u8,
+    /// `true` if the last frame is written as the golden reference instead of the comparison.
+    bless: bool,
+    /// `true` if the last frame does not match the golden reference.
+    regression: bool,
+}
+
+impl Benchmark {
+    pub fn new(tolerance: u8, bless: bool) -> Self {
+        Self {
+            samples: Default::default(),
+            tolerance,
+            bless,
+            regression: false,
+        }
+    }
+
+    /// Records the last frame. When all the frames are measured, the last frame of the scene is
+    /// compared with the golden reference, the results are written and the game exits. Does
+    /// nothing without a window target, the benchmark could not exit then.
+    pub fn update(
+        &mut self,
+        renderer: &Renderer,
+        scene: Handle<Scene>,
+        window_target: Option<&EventLoopWindowTarget<()>>,
+    ) {
+        let Some(window_target) = window_target else {
+            return;
+        };
+
+        let statistics = renderer.get_statistics();
+        // `frames_per_second` is counted once per second, it can't show slow individual frames,
+        // so the FPS of a frame is computed from its time instead.
+        let frame_time = statistics.pure_frame_time;
+        if frame_time <= 0.0 {
+            return;
+        }
+        self.samples.push(1.0 / frame_time);
+
+        if self.samples.len() < FRAME_COUNT {
+            return;
+        }
+
+        let mut report = self.report(renderer);
+        report += &self.compare_with_golden(renderer, scene);
+        match fs::write(RESULTS_FILE, &report) {
+            Ok(()) => Log::info(format!("Benchmark results are written to {RESULTS_FILE}")),
+            Err(err) => Log::err(format!(
+                "Unable to write benchmark results to {RESULTS_FILE}. Reason: {err:?}"
+            )),
+        }
+
+        // The event loop can't report an exit code, so the process is terminated right away to
+        // let CI know about the regression.
+        if self.regression {
+            std::process::exit(1);
+        }
+        window_target.exit();
+    }
+
+    /// Compares the last frame with the golden reference image, writes the diff image if they
+    /// do not match and returns the results of the comparison for the report. In bless mode the
+    /// frame is written as the new reference instead.
+    fn compare_with_golden(&mut self, renderer: &Renderer, scene: Handle<Scene>) -> String {
+        let frame = match capture_scene(renderer, scene) {
+            Ok(frame) => frame,
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to capture the last frame of the benchmark. Reason: {err}"
+                ));
+                self.regression = true;
+                return "\nVisual Regression: unable to capture the frame\n".to_string();
+            }
+        };
+
+        if self.bless {
+            return match frame.save(GOLDEN_FILE) {
+                Ok(()) => format!("\nVisual Regression: the frame is saved to {GOLDEN_FILE}\n"),
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to save golden reference to {GOLDEN_FILE}. Reason: {err:?}"
+                    ));
+                    self.regression = true;
+                    "\nVisual Regression: unable to save the reference\n".to_string()
+                }
+            };
+        }
+
+        if !Path::new(GOLDEN_FILE).exists() {
+            Log::err(format!(
+                "There is no golden reference at {GOLDEN_FILE}, run the benchmark with --bless \
+                to create it."
+            ));
+            self.regression = true;
+            return format!("\nVisual Regression: FAILED, there is no {GOLDEN_FILE}\n");
+        }
+
+        let golden = match image::open(GOLDEN_FILE) {
+            Ok(golden) => golden.to_rgba8(),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to load golden reference from {GOLDEN_FILE}. Reason: {err:?}"
+                ));
+                self.regression = true;
+                return "\nVisual Regression: unable to load the reference\n".to_string();
+            }
+        };
+
+        let Some(comparison) = visual_regression::compare(&frame, &golden, self.tolerance) else {
+            self.regression = true;
+            return format!(
+                "\nVisual Regression: frame size {}x{} does not match reference size {}x{}\n",
+                frame.width(),
+                frame.height(),
+                golden.width(),
+                golden.height()
+            );
+        };
+
+        self.regression = comparison.differing_percent > MAX_DIFFERING_PERCENT;
+        let mut text = String::new();
+        let _ = write!(
+            text,
+            "\nVisual Regression: {}\nMean Absolute Error: {:.3}\n\
+            Pixels Differing by More Than {}: {:.3}%\n",
+            if self.regression { "FAILED" } else { "Passed" },
+            comparison.mean_absolute_error,
+            self.tolerance,
+            comparison.differing_percent
+        );
+        if self.regression {
+            match comparison.diff.save(DIFF_FILE) {
+                Ok(()) => {
+                    let _ = writeln!(text, "Mismatching pixels are written to {DIFF_FILE}");
+                }
+                Err(err) => Log::err(format!(
+                    "Unable to write diff image to {DIFF_FILE}. Reason: {err:?}"
+                )),
+            }
+        }
+        text
+    }
+
+    fn report(&self, renderer: &Renderer) -> String {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f32::total_cmp);
+
+        let average = sorted.iter().sum::<f32>() / sorted.len() as f32;
+        let min = sorted.first().copied().unwrap_or_default();
+        let max = sorted.last().copied().unwrap_or_default();
+        // Average of the slowest 1% of the frames.
+        let low_count = sorted.len().div_ceil(100);
+        let one_percent_low = sorted[..low_count].iter().sum::<f32>() / low_count as f32;
+
+        let table = [
+            ("Average", average),
+            ("Min", min),
+            ("Max", max),
+            ("1% Low", one_percent_low),
+        ]
+        .iter()
+        .map(|(name, value)| format!("| {name:<10} | {value:>10.1} |\n"))
+        .collect::<String>();
+
+        // The renderer does not expose the name of the GPU, so the results contain the rest of the
+        // environment, that affects them.
+        let (width, height) = renderer.get_frame_size();
+        format!(
+            "Lightmap Benchmark\nPlatform: {} {}\nFrame Size: {width}x{height}\nFrames: {}\n\n\
+            | {:<10} | {:>10} |\n|{:-<12}|{:->12}|\n{table}",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            sorted.len(),
+            "Metric",
+            "FPS",
+            "",
+            ""
+        )
+    }
+}
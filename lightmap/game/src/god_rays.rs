@@ -0,0 +1,297 @@
+//! Sun shafts (god rays), rendered as a full-screen quad on top of the lit scene.
+use crate::{make_labeled_row, make_slider};
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, UserInterface,
+    },
+    material::{shader::ShaderResource, Material, MaterialResource},
+    scene::{
+        base::BaseBuilder,
+        camera::Camera,
+        light::directional::DirectionalLight,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+/// Distance to a point on the light ray, that is projected on the screen to find where the rays
+/// come from. Directional lights are infinitely far, so any large distance works.
+const LIGHT_DISTANCE: f32 = 1000.0;
+
+#[derive(Debug)]
+pub struct GodRays {
+    enabled: bool,
+    ray_length: f32,
+    intensity: f32,
+    decay: f32,
+    sample_count: u32,
+    material: Option<MaterialResource>,
+    quad: Handle<Node>,
+    camera: Handle<Node>,
+    sun: Handle<Node>,
+    enabled_check_box: Handle<UiNode>,
+    ray_length_slider: Handle<UiNode>,
+    intensity_slider: Handle<UiNode>,
+    decay_slider: Handle<UiNode>,
+    sample_count_slider: Handle<UiNode>,
+}
+
+impl Default for GodRays {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ray_length: 0.8,
+            intensity: 1.0,
+            decay: 0.97,
+            sample_count: 64,
+            material: None,
+            quad: Default::default(),
+            camera: Default::default(),
+            sun: Default::default(),
+            enabled_check_box: Default::default(),
+            ray_length_slider: Default::default(),
+            intensity_slider: Default::default(),
+            decay_slider: Default::default(),
+            sample_count_slider: Default::default(),
+        }
+    }
+}
+
+/// A quad that covers the whole screen, its vertices are in normalized device coordinates.
+fn make_screen_quad() -> SurfaceData {
+    let vertices = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .into_iter()
+        .map(|(x, y)| {
+            StaticVertex::from_pos_uv_normal(
+                Vector3::new(x, y, 0.0),
+                Vector2::new((x + 1.0) * 0.5, (y + 1.0) * 0.5),
+                Vector3::z(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(vec![
+            TriangleDefinition([0, 1, 2]),
+            TriangleDefinition([0, 2, 3]),
+        ]),
+    )
+}
+
+impl GodRays {
+    pub fn build_window(&mut self, ui: &mut UserInterface) {
+        let ctx = &mut ui.build_ctx();
+
+        self.enabled_check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .checked(Some(self.enabled))
+        .build(ctx);
+        self.ray_length_slider = make_slider(ctx, 1, 0.0, 1.0);
+        self.intensity_slider = make_slider(ctx, 2, 0.0, 5.0);
+        self.decay_slider = make_slider(ctx, 3, 0.8, 1.0);
+        self.sample_count_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(4)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(4.0)
+        .with_max(128.0)
+        .with_step(1.0)
+        .with_value_precision(0)
+        .show_value(true)
+        .build(ctx);
+
+        let mut children = Vec::new();
+        children.extend(make_labeled_row(ctx, 0, "Enabled", self.enabled_check_box));
+        children.extend(make_labeled_row(ctx, 1, "Length", self.ray_length_slider));
+        children.extend(make_labeled_row(ctx, 2, "Intensity", self.intensity_slider));
+        children.extend(make_labeled_row(ctx, 3, "Decay", self.decay_slider));
+        children.extend(make_labeled_row(
+            ctx,
+            4,
+            "Samples",
+            self.sample_count_slider,
+        ));
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(170.0)
+                .with_desired_position(Vector2::new(5.0, 430.0)),
+        )
+        .with_title(WindowTitle::text("God Rays"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(WidgetBuilder::new().with_children(children))
+                .add_column(Column::strict(70.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .build(ctx),
+        )
+        .build(ctx);
+
+        for (slider, value) in [
+            (self.ray_length_slider, self.ray_length),
+            (self.intensity_slider, self.intensity),
+            (self.decay_slider, self.decay),
+            (self.sample_count_slider, self.sample_count as f32),
+        ] {
+            ui.send_message(ScrollBarMessage::value(
+                slider,
+                MessageDirection::ToWidget,
+                value,
+            ));
+        }
+    }
+
+    /// Attaches the quad with the god rays material to the first enabled camera of the scene.
+    pub fn attach(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let graph = &mut scene.graph;
+
+        self.camera = graph
+            .pair_iter()
+            .find(|(_, node)| node.cast::<Camera>().map_or(false, |c| c.is_enabled()))
+            .map(|(handle, _)| handle)
+            .unwrap_or_default();
+        self.sun = graph
+            .pair_iter()
+            .find(|(_, node)| node.cast::<DirectionalLight>().is_some())
+            .map(|(handle, _)| handle)
+            .unwrap_or_default();
+        if self.camera.is_none() || self.sun.is_none() {
+            Log::warn("God rays require a camera and a directional light in the scene.");
+            return;
+        }
+
+        let material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::from_shader(shader, None));
+
+        // The quad is placed in front of the camera, so it is never culled, its actual position
+        // on the screen is defined by the shader.
+        self.quad = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_cast_shadows(false)
+                .with_visibility(self.enabled)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, 1.0))
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            make_screen_quad(),
+        ))
+        .with_material(material.clone())
+        .build()])
+        .with_render_path(RenderPath::Forward)
+        .build(graph);
+        graph.link_nodes(self.quad, self.camera);
+
+        self.material = Some(material);
+        self.apply_settings();
+    }
+
+    fn apply_settings(&self) {
+        let Some(material) = self.material.as_ref() else {
+            return;
+        };
+
+        let mut material = material.data_ref();
+        Log::verify(material.set_property(&"rayLength".into(), self.ray_length.into()));
+        Log::verify(material.set_property(&"decay".into(), self.decay.into()));
+        Log::verify(material.set_property(&"sampleCount".into(), self.sample_count.into()));
+    }
+
+    /// Projects the sun to the screen, the rays are coming from the projected point.
+    pub fn update(&self, scene: &Scene, frame_size: Vector2<f32>) {
+        let (Some(material), Some(camera), Some(sun)) = (
+            self.material.as_ref(),
+            scene.graph.try_get_of_type::<Camera>(self.camera),
+            scene.graph.try_get_of_type::<DirectionalLight>(self.sun),
+        ) else {
+            return;
+        };
+
+        // Directional light shines from its up vector.
+        let sun_position = camera.global_position() + sun.up_vector().scale(LIGHT_DISTANCE);
+        let clip_space = camera.view_projection_matrix()
+            * Vector4::new(sun_position.x, sun_position.y, sun_position.z, 1.0);
+
+        let mut material = material.data_ref();
+        if clip_space.w <= 0.0 {
+            // The sun is behind the camera, there are no visible rays.
+            Log::verify(material.set_property(&"intensity".into(), 0.0f32.into()));
+            return;
+        }
+
+        let ndc = clip_space.xy().scale(1.0 / clip_space.w);
+        let screen_position = (ndc + Vector2::repeat(1.0)).scale(0.5);
+        let color = sun.base_light_ref().color();
+
+        Log::verify(material.set_property(&"lightScreenPosition".into(), screen_position.into()));
+        Log::verify(material.set_property(&"screenSize".into(), frame_size.into()));
+        Log::verify(material.set_property(&"lightColor".into(), color.into()));
+        Log::verify(material.set_property(&"intensity".into(), self.intensity.into()));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, scene: Option<&mut Scene>) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.ray_length_slider {
+                self.ray_length = *value;
+            } else if message.destination() == self.intensity_slider {
+                self.intensity = *value;
+            } else if message.destination() == self.decay_slider {
+                self.decay = *value;
+            } else if message.destination() == self.sample_count_slider {
+                self.sample_count = value.round() as u32;
+            } else {
+                return;
+            }
+            self.apply_settings();
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() != self.enabled_check_box {
+                return;
+            }
+            self.enabled = *value;
+            if let Some(quad) = scene.and_then(|scene| scene.graph.try_get_mut(self.quad)) {
+                quad.set_visibility(self.enabled);
+            }
+        }
+    }
+}
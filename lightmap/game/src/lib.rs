@@ -1,18 +1,371 @@
 //! Game project.
-use fyrox::core::{reflect::prelude::*, visitor::prelude::*};
-use fyrox::plugin::{Plugin, PluginContext, PluginRegistrationContext};
+use crate::{
+    bake::BakePanel,
+    benchmark::Benchmark,
+    god_rays::GodRays,
+    material_animator::{add_torch_flames, MaterialAnimator},
+    transparent_fog::TransparentFog,
+};
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, log::Log, pool::Handle, reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        color::{ColorPickerBuilder, ColorPickerMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::shader::Shader,
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    scene::{FogDesc, Scene},
+};
+use std::path::Path;
+
+#[cfg(all(feature = "shader_hot_reload", debug_assertions))]
+use crate::shader_watcher::ShaderWatcher;
+
+mod bake;
+mod benchmark;
+mod god_rays;
+mod material_animator;
+#[cfg(all(feature = "shader_hot_reload", debug_assertions))]
+mod shader_watcher;
+mod transparent_fog;
+
+const SCENE_NAME: &str = "Sponza";
 
 #[derive(Visit, Reflect, Default, Debug)]
-pub struct Game;
+pub struct Game {
+    scene: Handle<Scene>,
+    fog_enabled: Handle<UiNode>,
+    fog_density: Handle<UiNode>,
+    fog_start: Handle<UiNode>,
+    fog_end: Handle<UiNode>,
+    fog_color: Handle<UiNode>,
+    debug_text: Handle<UiNode>,
+    enabled: bool,
+    density: f32,
+    start: f32,
+    end: f32,
+    color: Color,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    god_rays: GodRays,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bake_panel: BakePanel,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    transparent_fog: TransparentFog,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    benchmark: Option<Benchmark>,
+    #[cfg(all(feature = "shader_hot_reload", debug_assertions))]
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shader_watcher: Option<ShaderWatcher>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_labeled_row(
+    ctx: &mut BuildContext,
+    row: usize,
+    label: &str,
+    content: Handle<UiNode>,
+) -> [Handle<UiNode>; 2] {
+    [
+        TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text(label)
+        .build(ctx),
+        content,
+    ]
+}
+
+fn make_slider(ctx: &mut BuildContext, row: usize, min: f32, max: f32) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step((max - min) / 100.0)
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    /// Creates the game, that measures FPS of the first frames after the scene is loaded, writes
+    /// the results to a file and exits. The last frame is compared with the golden reference, a
+    /// color channel of a pixel may differ by `tolerance` at most. If `bless` is set, the last
+    /// frame is written as the golden reference instead.
+    pub fn with_benchmark(tolerance: Option<u8>, bless: bool) -> Self {
+        Self {
+            benchmark: Some(Benchmark::new(
+                tolerance.unwrap_or(benchmark::DEFAULT_TOLERANCE),
+                bless,
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn apply_fog(&self, context: &mut PluginContext) {
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        scene.rendering_options.fog = if self.enabled {
+            FogDesc {
+                // The renderer uses the color of the fog as is, so its alpha makes the fog thinner.
+                density: self.density * self.color.a as f32 / 255.0,
+                color: self.color,
+                start: self.start,
+                end: self.end,
+            }
+        } else {
+            // Zero density with infinitely far range has no visible effect.
+            FogDesc {
+                density: 0.0,
+                color: self.color,
+                start: f32::MAX,
+                end: f32::MAX,
+            }
+        };
+    }
+
+    fn build_fog_window(&mut self, ctx: &mut BuildContext) {
+        self.fog_enabled = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .checked(Some(true))
+        .build(ctx);
+        self.fog_density = make_slider(ctx, 1, 0.0, 0.2);
+        self.fog_start = make_slider(ctx, 2, 0.0, 100.0);
+        self.fog_end = make_slider(ctx, 3, 0.0, 200.0);
+        self.fog_color = ColorPickerBuilder::new(
+            WidgetBuilder::new()
+                .on_row(4)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .build(ctx);
+
+        let mut children = Vec::new();
+        children.extend(make_labeled_row(ctx, 0, "Enabled", self.fog_enabled));
+        children.extend(make_labeled_row(ctx, 1, "Density", self.fog_density));
+        children.extend(make_labeled_row(ctx, 2, "Start", self.fog_start));
+        children.extend(make_labeled_row(ctx, 3, "End", self.fog_end));
+        children.extend(make_labeled_row(ctx, 4, "Color", self.fog_color));
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(420.0)
+                .with_desired_position(Vector2::new(5.0, 5.0)),
+        )
+        .with_title(WindowTitle::text("Fog"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(WidgetBuilder::new().with_children(children))
+                .add_column(Column::strict(70.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::stretch())
+                .build(ctx),
+        )
+        .build(ctx);
+    }
+}
 
 impl Plugin for Game {
     fn register(&self, context: PluginRegistrationContext) {
-        fyrox_scripts::register(&context.serialization_context.script_constructors);
+        let script_constructors = &context.serialization_context.script_constructors;
+        fyrox_scripts::register(script_constructors);
+        script_constructors.add::<MaterialAnimator>("Material Animator");
     }
 
     fn init(&mut self, scene_path: Option<&str>, context: PluginContext) {
         context
             .async_scene_loader
             .request(scene_path.unwrap_or("data/Sponza.rgs"));
+
+        let ui = context.user_interfaces.first_mut();
+        self.god_rays.build_window(ui);
+        self.bake_panel.build_window(ui);
+        let ctx = &mut ui.build_ctx();
+        self.build_fog_window(ctx);
+        self.debug_text =
+            TextBuilder::new(WidgetBuilder::new().with_desired_position(Vector2::new(320.0, 5.0)))
+                .build(ctx);
+
+        #[cfg(all(feature = "shader_hot_reload", debug_assertions))]
+        {
+            self.shader_watcher = ShaderWatcher::new();
+        }
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        #[cfg(all(feature = "shader_hot_reload", debug_assertions))]
+        if let Some(shader_watcher) = self.shader_watcher.as_ref() {
+            shader_watcher.update(context.resource_manager);
+        }
+
+        self.bake_panel.update(
+            context.user_interfaces.first(),
+            context.scenes.try_get_mut(self.scene),
+        );
+
+        if let (GraphicsContext::Initialized(graphics_context), Some(scene)) = (
+            &context.graphics_context,
+            context.scenes.try_get(self.scene),
+        ) {
+            self.god_rays
+                .update(scene, graphics_context.renderer.get_frame_bounds());
+            self.transparent_fog.update(scene);
+
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    tracked_alloc::peak_ram_text(),
+                ));
+
+            if let Some(benchmark) = self.benchmark.as_mut() {
+                benchmark.update(
+                    &graphics_context.renderer,
+                    self.scene,
+                    context.window_target,
+                );
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        self.god_rays
+            .handle_ui_message(message, context.scenes.try_get_mut(self.scene));
+        self.bake_panel.handle_ui_message(
+            message,
+            context.user_interfaces.first(),
+            context.scenes.try_get(self.scene),
+        );
+
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.fog_density {
+                self.density = *value;
+            } else if message.destination() == self.fog_start {
+                self.start = *value;
+            } else if message.destination() == self.fog_end {
+                self.end = *value;
+            } else {
+                return;
+            }
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() != self.fog_enabled {
+                return;
+            }
+            self.enabled = *value;
+        } else if let Some(ColorPickerMessage::Color(color)) = message.data() {
+            if message.destination() != self.fog_color {
+                return;
+            }
+            self.color = *color;
+        } else {
+            return;
+        }
+
+        self.apply_fog(context);
+    }
+
+    fn on_scene_loaded(
+        &mut self,
+        _path: &Path,
+        scene: Handle<Scene>,
+        _data: &[u8],
+        context: &mut PluginContext,
+    ) {
+        self.scene = scene;
+
+        add_torch_flames(&mut context.scenes[scene].graph);
+        self.transparent_fog.init(&mut context.scenes[scene]);
+
+        let fog = &context.scenes[scene].rendering_options.fog;
+        self.enabled = true;
+        self.density = fog.density;
+        self.start = fog.start;
+        self.end = fog.end;
+        self.color = fog.color;
+
+        let ui = context.user_interfaces.first();
+        for (slider, value) in [
+            (self.fog_density, self.density),
+            (self.fog_start, self.start),
+            (self.fog_end, self.end),
+        ] {
+            ui.send_message(ScrollBarMessage::value(
+                slider,
+                MessageDirection::ToWidget,
+                value,
+            ));
+        }
+        ui.send_message(ColorPickerMessage::color(
+            self.fog_color,
+            MessageDirection::ToWidget,
+            self.color,
+        ));
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/god_rays.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.god_rays.attach(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load god rays shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
     }
 }
@@ -0,0 +1,259 @@
+//! Material animator - a script, that drives properties of materials along curves. It is used to
+//! make the flames of the torches flicker.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        log::Log,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::{shader::SamplerFallback, Material, MaterialResource, PropertyValue},
+    rand::{thread_rng, Rng},
+    resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+
+/// Standard shader property, that scales the emission texture.
+const EMISSION_PROPERTY: &str = "emissionStrength";
+const FLAME_RADIUS: f32 = 0.08;
+/// Height of the flame above the origin of the torch.
+const FLAME_OFFSET: f32 = 0.15;
+
+/// What happens when the time goes past the last key of a curve.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Visit, Reflect)]
+pub enum LoopMode {
+    /// The value stays at the last key.
+    Once,
+    /// The curve starts over from the first key.
+    #[default]
+    Repeat,
+    /// The curve is played backwards, then forwards again.
+    PingPong,
+}
+
+impl LoopMode {
+    /// Maps the time of the animator to a location on a curve of the given length.
+    fn location(self, time: f32, length: f32) -> f32 {
+        if length <= 0.0 {
+            return 0.0;
+        }
+        match self {
+            LoopMode::Once => time.min(length),
+            LoopMode::Repeat => time.rem_euclid(length),
+            LoopMode::PingPong => {
+                let location = time.rem_euclid(2.0 * length);
+                if location > length {
+                    2.0 * length - location
+                } else {
+                    location
+                }
+            }
+        }
+    }
+}
+
+/// Binds a curve to a property of the materials of a mesh.
+#[derive(Default, Clone, Debug, Visit, Reflect)]
+pub struct MaterialCurveBinding {
+    /// Mesh, which materials are animated. The node of the script is used, if it is not set.
+    pub node: Handle<Node>,
+    /// Name of the property of the material, for example `emissionStrength`.
+    pub material_property: String,
+    pub curve: Curve,
+}
+
+/// Converts the value of a curve to the type of the property. Vectors get the value in every
+/// component.
+fn property_value(current: &PropertyValue, value: f32) -> Option<PropertyValue> {
+    Some(match current {
+        PropertyValue::Float(_) => PropertyValue::Float(value),
+        PropertyValue::Vector2(_) => PropertyValue::Vector2(Vector2::repeat(value)),
+        PropertyValue::Vector3(_) => PropertyValue::Vector3(Vector3::repeat(value)),
+        PropertyValue::Vector4(_) => PropertyValue::Vector4(Vector4::repeat(value)),
+        _ => return None,
+    })
+}
+
+fn apply(graph: &Graph, node: Handle<Node>, property: &str, value: f32) {
+    let Some(mesh) = graph.try_get_of_type::<Mesh>(node) else {
+        return;
+    };
+    for surface in mesh.surfaces() {
+        let material = surface.material();
+        if !material.is_ok() {
+            continue;
+        }
+        let mut material = material.data_ref();
+        let Some(value) = material
+            .property_ref(&property.into())
+            .and_then(|current| property_value(current, value))
+        else {
+            continue;
+        };
+        Log::verify(material.set_property(&property.into(), value));
+    }
+}
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "9d4f2b6e-3a81-4c57-b0e9-7f1c5a2d8e36")]
+#[visit(optional)]
+pub struct MaterialAnimator {
+    pub bindings: Vec<MaterialCurveBinding>,
+    /// Multiplier of the time of the animation.
+    pub speed: InheritableVariable<f32>,
+    pub loop_mode: InheritableVariable<LoopMode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time: f32,
+}
+
+impl Default for MaterialAnimator {
+    fn default() -> Self {
+        Self {
+            bindings: Default::default(),
+            speed: 1.0.into(),
+            loop_mode: Default::default(),
+            time: 0.0,
+        }
+    }
+}
+
+impl ScriptTrait for MaterialAnimator {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.time += ctx.dt * *self.speed;
+        for binding in self.bindings.iter() {
+            let location = self
+                .loop_mode
+                .location(self.time, binding.curve.max_location());
+            let node = if binding.node.is_some() {
+                binding.node
+            } else {
+                ctx.handle
+            };
+            apply(
+                &ctx.scene.graph,
+                node,
+                &binding.material_property,
+                binding.curve.fetch(location),
+            );
+        }
+    }
+}
+
+/// Irregular bright and dim pulses of a flame. The last key is the same as the first one, so the
+/// curve loops without a jump.
+fn flicker_curve() -> Curve {
+    Curve::from(
+        [
+            (0.0, 3.0),
+            (0.15, 1.6),
+            (0.35, 3.8),
+            (0.5, 2.4),
+            (0.8, 4.2),
+            (0.95, 1.2),
+            (1.2, 3.4),
+            (1.45, 2.2),
+            (1.7, 3.0),
+        ]
+        .into_iter()
+        .map(|(location, value)| {
+            CurveKey::new(
+                location,
+                value,
+                CurveKeyKind::Cubic {
+                    left_tangent: 0.0,
+                    right_tangent: 0.0,
+                },
+            )
+        })
+        .collect::<Vec<_>>(),
+    )
+}
+
+fn make_flame_material() -> MaterialResource {
+    // The standard shader multiplies the emission strength by the emission texture, which is
+    // black by default, so the color of the flame is a single pixel texture.
+    let color = TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: 1,
+            height: 1,
+        },
+        TexturePixelKind::RGBA8,
+        vec![255, 170, 80, 255],
+        ResourceKind::Embedded,
+    );
+    let mut material = Material::standard();
+    Log::verify(material.set_property(
+        &"emissionTexture".into(),
+        PropertyValue::Sampler {
+            value: color,
+            fallback: SamplerFallback::White,
+        },
+    ));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Adds a glowing flame with its own material to every torch of the scene. Every flame has an
+/// animator, that makes its emission flicker. Animators run at slightly different speeds, so the
+/// flames do not flicker in sync. Does nothing if the scene already has animators.
+pub fn add_torch_flames(graph: &mut Graph) {
+    if graph
+        .linear_iter()
+        .any(|node| node.try_get_script::<MaterialAnimator>().is_some())
+    {
+        return;
+    }
+
+    let torches = graph
+        .pair_iter()
+        .filter(|(_, node)| node.name().starts_with("Torch"))
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+    let mut rng = thread_rng();
+    for torch in torches {
+        let flame = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name("Flame")
+                .with_cast_shadows(false)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, FLAME_OFFSET, 0.0))
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_sphere(8, 8, FLAME_RADIUS, &Matrix4::identity()),
+        ))
+        .with_material(make_flame_material())
+        .build()])
+        .build(graph);
+        graph.link_nodes(flame, torch);
+
+        graph[flame].add_script(Script::new(MaterialAnimator {
+            bindings: vec![MaterialCurveBinding {
+                node: Handle::NONE,
+                material_property: EMISSION_PROPERTY.to_string(),
+                curve: flicker_curve(),
+            }],
+            speed: rng.gen_range(0.8..1.2).into(),
+            ..Default::default()
+        }));
+    }
+}
@@ -0,0 +1,126 @@
+//! Hot reloading of shaders, that allows to see the changes of the shaders without restarting the
+//! game. Only available in debug builds with `shader_hot_reload` feature.
+use fyrox::{asset::manager::ResourceManager, core::log::Log, material::shader::Shader};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+};
+
+/// Directory with the shaders, relative to the working directory.
+const SHADERS_DIR: &str = "data";
+
+/// Watches the shaders for changes and reloads the modified ones.
+#[derive(Debug)]
+pub struct ShaderWatcher {
+    // The watcher stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+    /// Events contain absolute paths, while the resources use the paths relative to the working
+    /// directory.
+    working_dir: PathBuf,
+}
+
+fn is_shader_source(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "shader" || ext == "glsl")
+}
+
+impl ShaderWatcher {
+    pub fn new() -> Option<Self> {
+        let (sender, receiver) = channel();
+        let result = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            watcher.watch(Path::new(SHADERS_DIR), RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match result {
+            Ok(watcher) => Some(Self {
+                _watcher: watcher,
+                receiver,
+                working_dir: std::env::current_dir()
+                    .and_then(|dir| dir.canonicalize())
+                    .unwrap_or_default(),
+            }),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to watch shaders in {SHADERS_DIR}. Reason: {err:?}"
+                ));
+                None
+            }
+        }
+    }
+
+    /// Reloads the shaders, that were modified since the last call.
+    pub fn update(&self, resource_manager: &ResourceManager) {
+        // A single save of a file usually produces a few events.
+        let mut modified = HashSet::new();
+        for result in self.receiver.try_iter() {
+            match result {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        modified.extend(event.paths.into_iter().filter(|p| is_shader_source(p)));
+                    }
+                }
+                Err(err) => Log::err(format!("Unable to watch shaders. Reason: {err:?}")),
+            }
+        }
+
+        for path in modified {
+            let path = path
+                .strip_prefix(&self.working_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or(path);
+            self.reload(resource_manager, &path);
+        }
+    }
+
+    fn reload(&self, resource_manager: &ResourceManager, path: &Path) {
+        // It is unknown which shaders use the code from a GLSL file, so a change of a GLSL file
+        // reloads every shader in the same directory.
+        let shaders = if path.extension().is_some_and(|ext| ext == "shader") {
+            vec![path.to_path_buf()]
+        } else {
+            let dir = path.parent().unwrap_or(Path::new(SHADERS_DIR));
+            std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| dir.join(entry.file_name()))
+                        .filter(|p| p.extension().is_some_and(|ext| ext == "shader"))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        for shader_path in shaders {
+            // A shader with errors would break every material, that uses it, so the previous
+            // version of the shader is kept until the errors are fixed.
+            let source = match std::fs::read_to_string(&shader_path) {
+                Ok(source) => source,
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to read shader {}. Reason: {err:?}",
+                        shader_path.display()
+                    ));
+                    continue;
+                }
+            };
+            if let Err(err) = Shader::from_string(&source) {
+                Log::err(format!(
+                    "Unable to reload shader {}, the previous version is kept. Reason: {err:?}",
+                    shader_path.display()
+                ));
+                continue;
+            }
+
+            let mut state = resource_manager.state();
+            let Some(resource) = state.find(&shader_path).cloned() else {
+                // The shader is not used by the game, there's nothing to reload.
+                continue;
+            };
+            state.reload_resource(resource);
+            Log::info(format!("Shader reloaded: {}", shader_path.display()));
+        }
+    }
+}
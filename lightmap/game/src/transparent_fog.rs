@@ -0,0 +1,111 @@
+//! Fog for transparent meshes. The renderer fogs the meshes of the deferred pass only, the
+//! transparent ones are rendered in the forward pass and stay clear in the thickest fog. They are
+//! fogged here instead: the diffuse color of every such mesh is blended with the color of the fog
+//! by the distance from the camera to the mesh. The fog is applied per mesh, not per pixel, which
+//! is enough for small transparent meshes like glass or foliage.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{color::Color, log::Log, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    material::{MaterialProperty, MaterialResource},
+    scene::{
+        camera::Camera,
+        mesh::{Mesh, RenderPath},
+        node::Node,
+        FogDesc, Scene,
+    },
+};
+
+#[derive(Debug)]
+struct FoggedSurface {
+    mesh: Handle<Node>,
+    /// Copy of the material of the surface, materials are usually shared between meshes and
+    /// each mesh is fogged by its own distance.
+    material: MaterialResource,
+    /// Diffuse color of the material without the fog.
+    color: Color,
+    /// Last color, that was set to the material.
+    fogged: Color,
+}
+
+#[derive(Default, Debug)]
+pub struct TransparentFog {
+    surfaces: Vec<FoggedSurface>,
+}
+
+/// Amount of fog in `[0; 1]` range at the given distance from the camera.
+fn fog_factor(fog: &FogDesc, distance: f32) -> f32 {
+    let range = (fog.end - fog.start).max(f32::EPSILON);
+    let linear = ((distance - fog.start) / range).clamp(0.0, 1.0);
+    let exponential = 1.0 - (-fog.density * distance).exp();
+    linear * exponential
+}
+
+impl TransparentFog {
+    /// Replaces the materials of the transparent meshes of the scene with their copies, that are
+    /// fogged every frame. Materials without a diffuse color are left as is.
+    pub fn init(&mut self, scene: &mut Scene) {
+        self.surfaces.clear();
+        for (handle, node) in scene.graph.pair_iter_mut() {
+            let Some(mesh) = node.cast_mut::<Mesh>() else {
+                continue;
+            };
+            if mesh.render_path() != RenderPath::Forward {
+                continue;
+            }
+
+            for surface in mesh.surfaces_mut() {
+                let material = surface.material().data_ref().clone();
+                let Some(&MaterialProperty::Color(color)) =
+                    material.property_ref(&"diffuseColor".into())
+                else {
+                    continue;
+                };
+                let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+                surface.set_material(material.clone());
+                self.surfaces.push(FoggedSurface {
+                    mesh: handle,
+                    material,
+                    color,
+                    fogged: color,
+                });
+            }
+        }
+    }
+
+    pub fn update(&mut self, scene: &Scene) {
+        let Some(camera) = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled())
+        else {
+            return;
+        };
+        let camera_position = camera.global_position();
+        let fog = &scene.rendering_options.fog;
+
+        for surface in self.surfaces.iter_mut() {
+            let Some(mesh) = scene.graph.try_get(surface.mesh) else {
+                continue;
+            };
+            let distance = mesh
+                .world_bounding_box()
+                .center()
+                .metric_distance(&camera_position);
+            let mut fogged = surface.color.lerp(fog.color, fog_factor(fog, distance));
+            // The fog changes the color only, the transparency of the mesh stays the same.
+            fogged.a = surface.color.a;
+            if fogged == surface.fogged {
+                continue;
+            }
+            surface.fogged = fogged;
+            Log::verify(
+                surface
+                    .material
+                    .data_ref()
+                    .set_property(&"diffuseColor".into(), fogged.into()),
+            );
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::{Color, Hsv},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext},
+    rand::{thread_rng, Rng},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{
+            point::{PointLight, PointLightBuilder},
+            BaseLightBuilder,
+        },
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+/// Amount of lights along each side of the grid.
+const GRID_SIZE: usize = 16;
+const MAX_LIGHTS: usize = GRID_SIZE * GRID_SIZE;
+/// Distance between neighbouring lights of the grid.
+const SPACING: f32 = 2.0;
+/// Height of the vertical oscillation of the lights.
+const AMPLITUDE: f32 = 0.75;
+/// Difference of the phases of the oscillation of neighbouring lights.
+const PHASE_STEP: f32 = 0.35;
+
+const SCENE_NAME: &str = "many_lights";
+
+#[derive(Debug, Clone)]
+struct AnimatedLight {
+    handle: Handle<Node>,
+    base_height: f32,
+    /// Lights have random radii, the radius of a light is the radius from the slider multiplied
+    /// by this scale.
+    radius_scale: f32,
+}
+
+#[derive(Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    count_slider: Handle<UiNode>,
+    radius_slider: Handle<UiNode>,
+    speed_slider: Handle<UiNode>,
+    radius: f32,
+    speed: f32,
+    time: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    lights: Vec<AnimatedLight>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            scene: Default::default(),
+            debug_text: Default::default(),
+            count_slider: Default::default(),
+            radius_slider: Default::default(),
+            speed_slider: Default::default(),
+            radius: 2.0,
+            speed: 1.0,
+            time: 0.0,
+            lights: Default::default(),
+            throttle: Default::default(),
+        }
+    }
+}
+
+/// Position of the light with the given index on the grid, the grid is centered at the origin.
+fn grid_position(index: usize) -> Vector2<f32> {
+    let offset = (GRID_SIZE - 1) as f32 * SPACING * 0.5;
+    Vector2::new(
+        (index % GRID_SIZE) as f32 * SPACING - offset,
+        (index / GRID_SIZE) as f32 * SPACING - offset,
+    )
+}
+
+fn make_mesh(graph: &mut Graph, position: Vector3<f32>, surface: SurfaceData) {
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        surface,
+    ))
+    .with_material(MaterialResource::new_ok(
+        ResourceKind::Embedded,
+        Material::standard(),
+    ))
+    .build()])
+    .build(graph);
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(2)
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    /// Adds or removes lights at the end of the grid, so the scene has the given amount of them.
+    fn set_light_count(&mut self, graph: &mut Graph, count: usize) {
+        let count = count.min(MAX_LIGHTS);
+
+        while self.lights.len() > count {
+            if let Some(light) = self.lights.pop() {
+                graph.remove_node(light.handle);
+            }
+        }
+
+        let mut rng = thread_rng();
+        while self.lights.len() < count {
+            let position = grid_position(self.lights.len());
+            let base_height = rng.gen_range(0.5..3.0);
+            let radius_scale = rng.gen_range(0.5..1.5);
+            let color = Color::from(Hsv::new(rng.gen_range(0.0..360.0), 80.0, 100.0));
+
+            let handle = PointLightBuilder::new(
+                // Shadows of hundreds of point lights would take most of the frame time, the demo
+                // measures the cost of the lighting itself.
+                BaseLightBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(position.x, base_height, position.y))
+                            .build(),
+                    ),
+                )
+                .with_color(color)
+                .cast_shadows(false),
+            )
+            .with_radius(self.radius * radius_scale)
+            .build(graph);
+
+            self.lights.push(AnimatedLight {
+                handle,
+                base_height,
+                radius_scale,
+            });
+        }
+    }
+
+    fn apply_radius(&self, graph: &mut Graph) {
+        for light in self.lights.iter() {
+            if let Some(point_light) = graph.try_get_mut_of_type::<PointLight>(light.handle) {
+                point_light.set_radius(self.radius * light.radius_scale);
+            }
+        }
+    }
+
+    fn animate_lights(&mut self, graph: &mut Graph, dt: f32) {
+        self.time += dt * self.speed;
+        for (index, light) in self.lights.iter().enumerate() {
+            let height =
+                light.base_height + (self.time + index as f32 * PHASE_STEP).sin() * AMPLITUDE;
+            if let Some(node) = graph.try_get_mut(light.handle) {
+                let transform = node.local_transform_mut();
+                let mut position = **transform.position();
+                position.y = height;
+                transform.set_position(position);
+            }
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 14.0, -24.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        30.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        let floor_size = GRID_SIZE as f32 * SPACING + 4.0;
+        make_mesh(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.05, 0.0),
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                floor_size, 0.1, floor_size,
+            ))),
+        );
+        // Pillars between the lights, so the lights have something to light up besides the floor.
+        for index in (0..MAX_LIGHTS).step_by(5) {
+            let position = grid_position(index) + Vector2::repeat(SPACING * 0.5);
+            make_mesh(
+                &mut scene.graph,
+                Vector3::new(position.x, 0.0, position.y),
+                SurfaceData::make_cylinder(16, 0.3, 3.0, true, &Matrix4::identity()),
+            );
+        }
+
+        self.set_light_count(&mut scene.graph, MAX_LIGHTS);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let count_text = make_text(ctx, 0, "Light Count");
+        self.count_slider = make_slider(ctx, 1, 0.0, MAX_LIGHTS as f32, 1.0, MAX_LIGHTS as f32);
+        let radius_text = make_text(ctx, 2, "Light Radius");
+        self.radius_slider = make_slider(ctx, 3, 0.5, 6.0, 0.1, self.radius);
+        let speed_text = make_text(ctx, 4, "Animation Speed");
+        self.speed_slider = make_slider(ctx, 5, 0.0, 5.0, 0.05, self.speed);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(190.0)
+                .with_desired_position(Vector2::new(5.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Lighting Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(count_text)
+                    .with_child(self.count_slider)
+                    .with_child(radius_text)
+                    .with_child(self.radius_slider)
+                    .with_child(speed_text)
+                    .with_child(self.speed_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.animate_lights(&mut scene.graph, context.dt);
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Dynamic Lighting\nLights: {}\nPoint Lights On Screen: {}\n\
+                        Frame Time: {:.3} ms\n{}\n{}",
+                        self.lights.len(),
+                        statistics.lighting.point_lights_rendered,
+                        statistics.pure_frame_time * 1000.0,
+                        statistics,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        let destination = message.destination();
+        if destination == self.speed_slider {
+            self.speed = *value;
+            return;
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        if destination == self.count_slider {
+            self.set_light_count(&mut scene.graph, value.round() as usize);
+        } else if destination == self.radius_slider {
+            self.radius = *value;
+            self.apply_radius(&mut scene.graph);
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+(
+    name: "MirrorShader",
+
+    properties: [
+        (
+            name: "reflectionTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            // Distance between the samples of the blur kernel, in texels. Zero disables the blur.
+            name: "blurAmount",
+            kind: Float(0.0),
+        ),
+        (
+            name: "tint",
+            kind: Color(r: 230, g: 240, b: 255, a: 255),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                // The mirror is a single quad, it must be visible regardless of its winding.
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec4 clipPosition;
+
+                void main()
+                {
+                    clipPosition = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    gl_Position = clipPosition;
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D reflectionTexture;
+                uniform float blurAmount;
+                uniform vec4 tint;
+
+                in vec4 clipPosition;
+
+                out vec4 FragColor;
+
+                const int RADIUS = 4;
+
+                void main()
+                {
+                    // The reflection camera renders the scene from the mirrored position, so the
+                    // reflection is sampled at the screen position of the pixel. The camera can't
+                    // have a mirrored basis, which flips the image horizontally.
+                    vec2 screenPosition = clipPosition.xy / clipPosition.w * 0.5 + 0.5;
+                    vec2 texCoord = vec2(1.0 - screenPosition.x, screenPosition.y);
+
+                    // Materials can't chain passes, so both directions of the separable Gaussian
+                    // kernel are applied here at once - the weight of a sample is the product of
+                    // the weights of its horizontal and vertical offsets.
+                    vec2 texelSize = blurAmount / vec2(textureSize(reflectionTexture, 0));
+                    float sigma = float(RADIUS) * 0.5;
+                    vec3 color = vec3(0.0);
+                    float totalWeight = 0.0;
+                    for (int y = -RADIUS; y <= RADIUS; ++y) {
+                        float weightY = exp(-float(y * y) / (2.0 * sigma * sigma));
+                        for (int x = -RADIUS; x <= RADIUS; ++x) {
+                            float weight = weightY * exp(-float(x * x) / (2.0 * sigma * sigma));
+                            color += texture(reflectionTexture, texCoord + vec2(x, y) * texelSize).rgb * weight;
+                            totalWeight += weight;
+                        }
+                    }
+
+                    FragColor = vec4(color / totalWeight * tint.rgb, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
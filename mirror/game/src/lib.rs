@@ -0,0 +1,482 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    plugin::{Plugin, PluginContext},
+    resource::texture::{TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+const SCENE_NAME: &str = "mirror";
+
+/// Center of the mirror, it stands at the far end of the room and faces the camera.
+const MIRROR_POSITION: Vector3<f32> = Vector3::new(0.0, 2.0, 6.0);
+const MIRROR_NORMAL: Vector3<f32> = Vector3::new(0.0, 0.0, -1.0);
+const MIRROR_SIZE: Vector2<f32> = Vector2::new(6.0, 3.6);
+/// The camera swings around this point.
+const ORBIT_CENTER: Vector3<f32> = Vector3::new(0.0, 1.5, 0.0);
+const ORBIT_RADIUS: f32 = 5.0;
+/// Weight of a new frame time sample in the running average.
+const FRAME_TIME_SMOOTHING: f32 = 0.05;
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    /// A copy of the room without the mirror, it is rendered into a texture by the reflection
+    /// camera.
+    reflection_scene: Handle<Scene>,
+    camera: Handle<Node>,
+    reflection_camera: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    blur_slider: Handle<UiNode>,
+    /// Distance between the samples of the blur kernel, in texels.
+    blur: f32,
+    time: f32,
+    reflection_enabled: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spinning: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    reflected_spinning: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    mirror_material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    render_target: Option<TextureResource>,
+    /// Average frame time (in seconds) with and without the reflection. The renderer does not
+    /// measure scenes separately, so the overhead of the reflection is the difference between
+    /// them.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    frame_time_with_reflection: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    frame_time_without_reflection: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_material(color: Color) -> MaterialResource {
+    let mut material = Material::standard();
+    Log::verify(material.set_property(&"diffuseColor".into(), color.into()));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+fn make_mesh(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    surface: SurfaceData,
+    color: Color,
+) -> Handle<Node> {
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+        ResourceKind::Embedded,
+        surface,
+    ))
+    .with_material(make_material(color))
+    .build()])
+    .build(graph)
+}
+
+/// Builds everything in front of the mirror, returns the objects that spin. It is called for both
+/// scenes, so they look the same.
+fn build_room(graph: &mut Graph) -> Vec<Handle<Node>> {
+    PointLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 5.0, 0.0))
+                .build(),
+        ),
+    ))
+    .with_radius(20.0)
+    .build(graph);
+
+    make_mesh(
+        graph,
+        Vector3::new(0.0, -0.05, 0.0),
+        SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+            14.0, 0.1, 12.0,
+        ))),
+        Color::opaque(150, 150, 150),
+    );
+
+    let colors = [
+        Color::opaque(220, 60, 60),
+        Color::opaque(60, 200, 80),
+        Color::opaque(60, 100, 230),
+        Color::opaque(230, 200, 50),
+    ];
+    let mut spinning = Vec::new();
+    for (i, color) in colors.into_iter().enumerate() {
+        let x = (i as f32 - 1.5) * 2.0;
+        let surface = if i % 2 == 0 {
+            SurfaceData::make_cube(Matrix4::identity())
+        } else {
+            SurfaceData::make_sphere(24, 24, 0.5, &Matrix4::identity())
+        };
+        let object = make_mesh(graph, Vector3::new(x, 0.5, 3.0), surface, color);
+        if i % 2 == 0 {
+            spinning.push(object);
+        }
+    }
+
+    // A tall pillar close to the mirror shows that the reflection has the right depth.
+    make_mesh(
+        graph,
+        Vector3::new(2.5, 0.0, 5.0),
+        SurfaceData::make_cylinder(24, 0.3, 3.0, true, &Matrix4::identity()),
+        Color::opaque(200, 120, 220),
+    );
+
+    spinning
+}
+
+fn spin(graph: &mut Graph, objects: &[Handle<Node>], time: f32) {
+    for (i, object) in objects.iter().enumerate() {
+        if let Some(object) = graph.try_get_mut(*object) {
+            object
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    time * (i as f32 + 1.0),
+                ));
+        }
+    }
+}
+
+/// Mirrors the vector across the plane of the mirror.
+fn reflect(vector: Vector3<f32>) -> Vector3<f32> {
+    vector - MIRROR_NORMAL.scale(2.0 * vector.dot(&MIRROR_NORMAL))
+}
+
+impl Game {
+    fn build_mirror(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::from_shader(shader, None));
+
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(MIRROR_POSITION)
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_quad(&Matrix4::new_nonuniform_scaling(&Vector3::new(
+                MIRROR_SIZE.x,
+                MIRROR_SIZE.y,
+                1.0,
+            ))),
+        ))
+        .with_material(material.clone())
+        .build()])
+        .build(&mut scene.graph);
+
+        // The frame is behind the mirror, so it does not need to be in the reflection scene.
+        make_mesh(
+            &mut scene.graph,
+            MIRROR_POSITION + Vector3::new(0.0, 0.0, 0.06),
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                MIRROR_SIZE.x + 0.3,
+                MIRROR_SIZE.y + 0.3,
+                0.1,
+            ))),
+            Color::opaque(90, 60, 40),
+        );
+
+        self.mirror_material = Some(material);
+        self.apply_mirror_settings();
+    }
+
+    fn apply_mirror_settings(&self) {
+        let Some(material) = self.mirror_material.as_ref() else {
+            return;
+        };
+        let mut material = material.data_ref();
+        Log::verify(material.set_texture(&"reflectionTexture".into(), self.render_target.clone()));
+        Log::verify(material.set_property(&"blurAmount".into(), self.blur.into()));
+    }
+
+    /// The reflection must have the same aspect ratio as the frame, so the render target follows
+    /// the size of the window.
+    fn handle_resize(&mut self, context: &mut PluginContext, new_size: Vector2<f32>) {
+        let render_target = TextureResource::new_render_target(
+            (new_size.x as u32).max(1),
+            (new_size.y as u32).max(1),
+        );
+        if let Some(scene) = context.scenes.try_get_mut(self.reflection_scene) {
+            scene.rendering_options.render_target = Some(render_target.clone());
+        }
+        self.render_target = Some(render_target);
+        self.apply_mirror_settings();
+    }
+
+    fn update_cameras(&mut self, context: &mut PluginContext) {
+        let angle = (self.time * 0.3).sin();
+        let position =
+            ORBIT_CENTER + Vector3::new(angle.sin(), 0.2, -angle.cos()).scale(ORBIT_RADIUS);
+        let look_at = Vector3::new(0.0, 1.0, 3.0);
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        let camera = &mut scene.graph[self.camera];
+        camera
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(UnitQuaternion::face_towards(
+                &(look_at - position),
+                &Vector3::y(),
+            ));
+        spin(&mut scene.graph, &self.spinning, self.time);
+
+        // The reflection camera looks at the room from behind the mirror. Its basis can't be
+        // mirrored, so the image is flipped horizontally, the mirror shader flips it back.
+        let reflected_position = MIRROR_POSITION + reflect(position - MIRROR_POSITION);
+        let reflected_look = reflect(look_at - position);
+        let reflected_up = reflect(Vector3::y());
+
+        let Some(reflection_scene) = context.scenes.try_get_mut(self.reflection_scene) else {
+            return;
+        };
+        let reflection_camera = &mut reflection_scene.graph[self.reflection_camera];
+        reflection_camera
+            .local_transform_mut()
+            .set_position(reflected_position)
+            .set_rotation(UnitQuaternion::face_towards(&reflected_look, &reflected_up));
+        spin(
+            &mut reflection_scene.graph,
+            &self.reflected_spinning,
+            self.time,
+        );
+    }
+
+    fn set_reflection_enabled(&mut self, enabled: bool, context: &mut PluginContext) {
+        self.reflection_enabled = enabled;
+        if let Some(scene) = context.scenes.try_get_mut(self.reflection_scene) {
+            scene.set_enabled(enabled);
+        }
+    }
+
+    fn overhead_text(&self) -> String {
+        match (
+            self.frame_time_with_reflection,
+            self.frame_time_without_reflection,
+        ) {
+            (Some(with), Some(without)) => {
+                format!("Reflection Overhead: {:.2} ms", (with - without) * 1000.0)
+            }
+            _ => "Reflection Overhead: toggle the reflection to measure".to_string(),
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, mut context: PluginContext) {
+        self.reflection_enabled = true;
+
+        // Scenes are rendered in the order of addition, the reflection must be ready before the
+        // mirror is drawn.
+        let mut reflection_scene = Scene::new();
+        self.reflection_camera =
+            CameraBuilder::new(BaseBuilder::new()).build(&mut reflection_scene.graph);
+        self.reflected_spinning = build_room(&mut reflection_scene.graph);
+        self.reflection_scene = context.scenes.add(reflection_scene);
+
+        let mut scene = Scene::new();
+        self.camera = CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        self.spinning = build_room(&mut scene.graph);
+        self.scene = context.scenes.add(scene);
+        self.handle_resize(&mut context, Vector2::new(1280.0, 720.0));
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let blur_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("Reflection Blur")
+        .build(ctx);
+        self.blur_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.0)
+        .with_max(4.0)
+        .with_step(0.04)
+        .with_value(self.blur)
+        .with_value_precision(1)
+        .show_value(true)
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(90.0)
+                .with_desired_position(Vector2::new(5.0, 100.0)),
+        )
+        .with_title(WindowTitle::text("Mirror Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(blur_text)
+                    .with_child(self.blur_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/mirror.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.build_mirror(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load mirror shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.time += context.dt;
+        self.update_cameras(context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+
+            let average = if self.reflection_enabled {
+                &mut self.frame_time_with_reflection
+            } else {
+                &mut self.frame_time_without_reflection
+            };
+            let frame_time = statistics.pure_frame_time;
+            *average = Some(average.map_or(frame_time, |average| {
+                average + (frame_time - average) * FRAME_TIME_SMOOTHING
+            }));
+
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Mirror Surface\n[R] - toggle reflection\n{}\n{}\n{}",
+                        self.overhead_text(),
+                        statistics,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::Resized(size) => self.handle_resize(
+                    &mut context,
+                    Vector2::new(size.width as f32, size.height as f32),
+                ),
+                WindowEvent::KeyboardInput { event, .. } => {
+                    if event.state == ElementState::Pressed
+                        && event.physical_key == PhysicalKey::Code(KeyCode::KeyR)
+                    {
+                        self.set_reflection_enabled(!self.reflection_enabled, &mut context);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, _context: &mut PluginContext, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.blur_slider {
+                self.blur = *value;
+                self.apply_mirror_settings();
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        let graphics_context = context.graphics_context.as_initialized_mut();
+
+        prefs::quality::restore(&mut graphics_context.renderer, SCENE_NAME);
+
+        let inner_size = graphics_context.window.inner_size();
+        self.handle_resize(
+            &mut context,
+            Vector2::new(inner_size.width as f32, inner_size.height as f32),
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! A* search on a grid, that could be executed step by step, so every step could be visualized.
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+/// Amount of cells along each side of the grid.
+pub const GRID_SIZE: usize = 30;
+/// Costs of the steps are integers, so the order of the cells in the open set is exact. A
+/// diagonal step costs approximately `sqrt(2)` straight steps.
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+pub fn cell_index(x: usize, y: usize) -> usize {
+    y * GRID_SIZE + x
+}
+
+pub fn cell_coords(index: usize) -> (usize, usize) {
+    (index % GRID_SIZE, index / GRID_SIZE)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchState {
+    Running,
+    Found,
+    NoPath,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct OpenEntry {
+    /// Estimated cost of the path from the start to the goal through the cell.
+    f_score: u32,
+    /// Estimated cost of the rest of the path, the cells closer to the goal are preferred when
+    /// the estimated costs of the paths are equal.
+    h_score: u32,
+    cell: usize,
+}
+
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.f_score, self.h_score, self.cell).cmp(&(other.f_score, other.h_score, other.cell))
+    }
+}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug)]
+pub struct AStar {
+    goal: usize,
+    diagonal: bool,
+    /// Cells to expand, the entries of the cells, that were reached again by a shorter path, are
+    /// left in the heap and skipped when popped.
+    open: BinaryHeap<Reverse<OpenEntry>>,
+    in_open_set: Vec<bool>,
+    open_set_size: usize,
+    closed: Vec<bool>,
+    g_score: Vec<u32>,
+    came_from: Vec<Option<usize>>,
+    state: SearchState,
+    path: Vec<usize>,
+}
+
+impl AStar {
+    pub fn new(start: usize, goal: usize, diagonal: bool) -> Self {
+        let cell_count = GRID_SIZE * GRID_SIZE;
+        let mut search = Self {
+            goal,
+            diagonal,
+            open: BinaryHeap::new(),
+            in_open_set: vec![false; cell_count],
+            open_set_size: 0,
+            closed: vec![false; cell_count],
+            g_score: vec![u32::MAX; cell_count],
+            came_from: vec![None; cell_count],
+            state: SearchState::Running,
+            path: Vec::new(),
+        };
+        search.g_score[start] = 0;
+        search.push_open(start);
+        search
+    }
+
+    /// Estimated cost of the path from the cell to the goal, it never overestimates the cost, so
+    /// the found path is the shortest one.
+    fn heuristic(&self, cell: usize) -> u32 {
+        let (x, y) = cell_coords(cell);
+        let (goal_x, goal_y) = cell_coords(self.goal);
+        let dx = x.abs_diff(goal_x) as u32;
+        let dy = y.abs_diff(goal_y) as u32;
+        if self.diagonal {
+            // Octile distance.
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        } else {
+            // Manhattan distance.
+            STRAIGHT_COST * (dx + dy)
+        }
+    }
+
+    fn push_open(&mut self, cell: usize) {
+        let h_score = self.heuristic(cell);
+        self.open.push(Reverse(OpenEntry {
+            f_score: self.g_score[cell] + h_score,
+            h_score,
+            cell,
+        }));
+        if !self.in_open_set[cell] {
+            self.in_open_set[cell] = true;
+            self.open_set_size += 1;
+        }
+    }
+
+    /// Returns passable neighbours of the cell with the costs of the steps to them. Diagonal
+    /// steps can't cut the corners of the walls.
+    fn neighbours(&self, cell: usize, walls: &[bool]) -> Vec<(usize, u32)> {
+        let (x, y) = cell_coords(cell);
+        let is_open = |dx: i32, dy: i32| {
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            if (0..GRID_SIZE as i32).contains(&nx) && (0..GRID_SIZE as i32).contains(&ny) {
+                let index = cell_index(nx as usize, ny as usize);
+                (!walls[index]).then_some(index)
+            } else {
+                None
+            }
+        };
+
+        let mut neighbours = Vec::with_capacity(8);
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(index) = is_open(dx, dy) {
+                neighbours.push((index, STRAIGHT_COST));
+            }
+        }
+        if self.diagonal {
+            for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                if is_open(dx, 0).is_some() && is_open(0, dy).is_some() {
+                    if let Some(index) = is_open(dx, dy) {
+                        neighbours.push((index, DIAGONAL_COST));
+                    }
+                }
+            }
+        }
+        neighbours
+    }
+
+    /// Expands a single cell of the open set.
+    pub fn step(&mut self, walls: &[bool]) -> SearchState {
+        if self.state != SearchState::Running {
+            return self.state;
+        }
+
+        let cell = loop {
+            match self.open.pop() {
+                Some(Reverse(entry)) if !self.closed[entry.cell] => break entry.cell,
+                Some(_) => continue,
+                None => {
+                    self.state = SearchState::NoPath;
+                    return self.state;
+                }
+            }
+        };
+        self.in_open_set[cell] = false;
+        self.open_set_size -= 1;
+        self.closed[cell] = true;
+
+        if cell == self.goal {
+            self.state = SearchState::Found;
+            let mut current = Some(cell);
+            while let Some(cell) = current {
+                self.path.push(cell);
+                current = self.came_from[cell];
+            }
+            self.path.reverse();
+            return self.state;
+        }
+
+        for (neighbour, cost) in self.neighbours(cell, walls) {
+            if self.closed[neighbour] {
+                continue;
+            }
+            let g_score = self.g_score[cell] + cost;
+            if g_score < self.g_score[neighbour] {
+                self.g_score[neighbour] = g_score;
+                self.came_from[neighbour] = Some(cell);
+                self.push_open(neighbour);
+            }
+        }
+
+        self.state
+    }
+
+    pub fn state(&self) -> SearchState {
+        self.state
+    }
+
+    pub fn open_set_size(&self) -> usize {
+        self.open_set_size
+    }
+
+    pub fn is_in_open_set(&self, cell: usize) -> bool {
+        self.in_open_set[cell]
+    }
+
+    pub fn is_visited(&self, cell: usize) -> bool {
+        self.closed[cell]
+    }
+
+    /// Cells of the found path, from the start to the goal. Empty until the goal is reached.
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Length of the found path, a straight step has the length of one.
+    pub fn path_length(&self) -> Option<f32> {
+        let goal = self.path.last()?;
+        Some(self.g_score[*goal] as f32 / STRAIGHT_COST as f32)
+    }
+}
@@ -0,0 +1,357 @@
+//! Game project.
+use crate::astar::{cell_coords, cell_index, AStar, SearchState, GRID_SIZE};
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::plane::Plane,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    graph::BaseSceneGraph,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        message::{MessageDirection, UiMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, UserInterface,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, OrthographicProjection, Projection},
+        dim2::rectangle::RectangleBuilder,
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+mod astar;
+
+/// Size of the rectangle of a cell relative to the size of the cell, the gaps make the grid
+/// visible.
+const CELL_FILL: f32 = 0.9;
+
+const SCENE_NAME: &str = "pathfind_vis";
+
+const OPEN_COLOR: Color = Color::opaque(235, 235, 235);
+const WALL_COLOR: Color = Color::opaque(90, 90, 90);
+const START_COLOR: Color = Color::opaque(40, 200, 60);
+const GOAL_COLOR: Color = Color::opaque(220, 40, 40);
+const VISITED_COLOR: Color = Color::opaque(60, 100, 220);
+const FRONTIER_COLOR: Color = Color::opaque(140, 190, 250);
+const PATH_COLOR: Color = Color::opaque(250, 210, 40);
+
+/// Position of the center of the cell in the scene. The camera looks along +Z axis, so X axis of
+/// the scene points to the left and the cells are placed from right to left.
+fn cell_position(cell: usize) -> Vector3<f32> {
+    let (x, y) = cell_coords(cell);
+    let half_size = GRID_SIZE as f32 * 0.5;
+    Vector3::new(half_size - x as f32 - 0.5, y as f32 + 0.5 - half_size, 0.0)
+}
+
+/// Returns the cell at the given point of the plane of the grid, if any.
+fn cell_at(point: Vector3<f32>) -> Option<usize> {
+    let half_size = GRID_SIZE as f32 * 0.5;
+    let x = (half_size - point.x).floor();
+    let y = (point.y + half_size).floor();
+    let range = 0.0..GRID_SIZE as f32;
+    (range.contains(&x) && range.contains(&y)).then(|| cell_index(x as usize, y as usize))
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    diagonal_check_box: Handle<UiNode>,
+    /// Allows diagonal steps of the search.
+    diagonal: bool,
+    start: usize,
+    goal: usize,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    walls: Vec<bool>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cells: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    search: Option<AStar>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cursor_position: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    fn cell_color(&self, cell: usize) -> Color {
+        if cell == self.start {
+            START_COLOR
+        } else if cell == self.goal {
+            GOAL_COLOR
+        } else if self.walls[cell] {
+            WALL_COLOR
+        } else if let Some(search) = self.search.as_ref() {
+            if search.path().contains(&cell) {
+                PATH_COLOR
+            } else if search.is_in_open_set(cell) {
+                FRONTIER_COLOR
+            } else if search.is_visited(cell) {
+                VISITED_COLOR
+            } else {
+                OPEN_COLOR
+            }
+        } else {
+            OPEN_COLOR
+        }
+    }
+
+    fn update_colors(&self, context: &mut PluginContext) {
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        for (cell, handle) in self.cells.iter().enumerate() {
+            scene.graph[*handle]
+                .as_rectangle_mut()
+                .set_color(self.cell_color(cell));
+        }
+    }
+
+    /// Any change of the grid invalidates the search.
+    fn edit(&mut self, context: &mut PluginContext, edit: impl FnOnce(&mut Self)) {
+        edit(self);
+        self.search = None;
+        self.update_colors(context);
+    }
+
+    fn is_cursor_over_ui(&self, ui: &UserInterface) -> bool {
+        let picked = ui.hit_test(self.cursor_position);
+        picked.is_some() && picked != ui.root()
+    }
+
+    /// Projects the cursor onto the plane of the grid and returns the cell under it.
+    fn cell_at_cursor(&self, context: &PluginContext) -> Option<usize> {
+        let GraphicsContext::Initialized(graphics_context) = &context.graphics_context else {
+            return None;
+        };
+        let camera = context
+            .scenes
+            .try_get(self.scene)?
+            .graph
+            .try_get_of_type::<Camera>(self.camera)?;
+        let ray = camera.make_ray(
+            self.cursor_position,
+            graphics_context.renderer.get_frame_bounds(),
+        );
+        let plane = Plane::from_normal_and_point(&Vector3::z(), &Vector3::default())?;
+        cell_at(ray.plane_intersection_point(&plane)?)
+    }
+
+    fn handle_click(&mut self, button: MouseButton, context: &mut PluginContext) {
+        if self.is_cursor_over_ui(context.user_interfaces.first()) {
+            return;
+        }
+        let Some(cell) = self.cell_at_cursor(context) else {
+            return;
+        };
+
+        match button {
+            MouseButton::Left if cell != self.goal => self.edit(context, |game| {
+                game.start = cell;
+                game.walls[cell] = false;
+            }),
+            MouseButton::Right if cell != self.start => self.edit(context, |game| {
+                game.goal = cell;
+                game.walls[cell] = false;
+            }),
+            MouseButton::Middle if cell != self.start && cell != self.goal => {
+                self.edit(context, |game| game.walls[cell] = !game.walls[cell])
+            }
+            _ => (),
+        }
+    }
+
+    fn status_text(&self) -> String {
+        match self.search.as_ref() {
+            Some(search) => {
+                let path = match search.state() {
+                    SearchState::Running => "Searching...".to_string(),
+                    SearchState::Found => format!(
+                        "{:.1} ({} cells)",
+                        search.path_length().unwrap_or_default(),
+                        search.path().len()
+                    ),
+                    SearchState::NoPath => "No Path".to_string(),
+                };
+                format!(
+                    "Open Set: {}\nPath Length: {}",
+                    search.open_set_size(),
+                    path
+                )
+            }
+            None => "Open Set: 0\nPath Length: N/A".to_string(),
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.walls = vec![false; GRID_SIZE * GRID_SIZE];
+        self.start = cell_index(2, GRID_SIZE / 2);
+        self.goal = cell_index(GRID_SIZE - 3, GRID_SIZE / 2);
+        self.diagonal = true;
+
+        let mut scene = Scene::new();
+        scene.rendering_options.clear_color = Some(Color::opaque(30, 30, 30));
+
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -10.0))
+                    .build(),
+            ),
+        )
+        .with_projection(Projection::Orthographic(OrthographicProjection {
+            z_near: 0.0,
+            z_far: 100.0,
+            // Half of the visible height, with a margin around the grid.
+            vertical_size: GRID_SIZE as f32 * 0.5 + 1.0,
+        }))
+        .build(&mut scene.graph);
+
+        self.cells = (0..GRID_SIZE * GRID_SIZE)
+            .map(|cell| {
+                RectangleBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(cell_position(cell))
+                            .with_local_scale(Vector3::new(CELL_FILL, CELL_FILL, 1.0))
+                            .build(),
+                    ),
+                )
+                .with_color(self.cell_color(cell))
+                .build(&mut scene.graph)
+            })
+            .collect();
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        self.diagonal_check_box =
+            CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                .with_content(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Diagonal Movement")
+                        .build(ctx),
+                )
+                .checked(Some(self.diagonal))
+                .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_height(60.0)
+                .with_desired_position(Vector2::new(5.0, 200.0)),
+        )
+        .with_title(WindowTitle::text("Search Settings"))
+        .can_close(false)
+        .with_content(self.diagonal_check_box)
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        // One step of the search per frame, so the progress of the search is visible.
+        if let Some(search) = self.search.as_mut() {
+            if search.state() == SearchState::Running {
+                search.step(&self.walls);
+                self.update_colors(context);
+            }
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - A* Pathfinding\n[LMB] - Set Start\n[RMB] - Set Goal\n\
+                        [MMB] - Toggle Wall\n[Space] - Start Search\n[C] - Clear Walls\n{}\n{}\n{}",
+                        self.status_text(),
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Vector2::new(position.x as f32, position.y as f32);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => self.handle_click(*button, &mut context),
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::Space) => {
+                        self.search = Some(AStar::new(self.start, self.goal, self.diagonal));
+                    }
+                    PhysicalKey::Code(KeyCode::KeyC) => {
+                        self.edit(&mut context, |game| game.walls.fill(false))
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.diagonal_check_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.edit(context, |game| game.diagonal = *value);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 //! Editor with your game connected to it as a plugin.
+mod tile_painter;
+
 use fyrox::event_loop::EventLoop;
 use fyroxed_base::{Editor, StartupData};
 use platformer::Game;
+use tile_painter::TilePainter;
 
 fn main() {
     let event_loop = EventLoop::new().unwrap();
@@ -12,6 +15,7 @@ fn main() {
         }),
     );
     editor.add_game_plugin(Game::default());
+    editor.add_editor_plugin(TilePainter::default());
 
     editor.run(event_loop)
 }
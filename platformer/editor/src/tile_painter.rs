@@ -0,0 +1,351 @@
+//! Editor plugin, that paints tiles of the `Tile Map` script. Enable the "Paint Tiles" mode, pick a
+//! tile in the palette, then click in the viewport to place it, or drag to fill a rectangle with
+//! it. The tiles are painted on the selected tile map (or on the tile map, that is a parent of the
+//! selected node), if there is no such map - on the first tile map of the scene. Every stroke is an
+//! editor command, so it can be undone and the tiles are saved with the scene.
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector2, Vector3},
+        color::Color,
+        math::plane::Plane,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        image::ImageBuilder,
+        message::{MessageDirection, MouseButton, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::MaterialResource,
+    resource::texture::Texture,
+    scene::{camera::Camera, graph::Graph, node::Node},
+};
+use fyroxed_base::{
+    command::{CommandContext, CommandTrait},
+    plugin::EditorPlugin,
+    scene::{commands::GameSceneContext, GameScene},
+    Editor,
+};
+use platformer::tile_map::{
+    tile_material, tile_uv_rect, TileInstance, TileMap, SHEET_COLUMNS, SHEET_ROWS, TILE_SHEET_PATH,
+};
+
+/// Size of a tile in the palette, in pixels.
+const PALETTE_CELL_SIZE: f32 = 48.0;
+
+/// Replaces the tiles of a tile map. The command swaps the tiles of the map with its own, so the
+/// same swap both executes and reverts it.
+#[derive(Debug)]
+struct PaintTilesCommand {
+    tile_map: Handle<Node>,
+    tiles: Vec<TileInstance>,
+    material: MaterialResource,
+}
+
+impl PaintTilesCommand {
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let graph = &mut context.get_mut::<GameSceneContext>().scene.graph;
+        let tiles = std::mem::take(&mut self.tiles);
+        if let Some(previous) = TileMap::replace_tiles(graph, self.tile_map, tiles, &self.material)
+        {
+            self.tiles = previous;
+        }
+    }
+}
+
+impl CommandTrait for PaintTilesCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Paint Tiles".to_string()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+}
+
+#[derive(Default)]
+pub struct TilePainter {
+    window: Handle<UiNode>,
+    mode: Handle<UiNode>,
+    info: Handle<UiNode>,
+    palette_cells: Vec<Handle<UiNode>>,
+    /// Clicks in the viewport paint tiles only when the mode is enabled.
+    mode_enabled: bool,
+    selected_tile: u32,
+    material: Option<MaterialResource>,
+    /// The tile map and its cell, where the left mouse button was pressed.
+    drag_start: Option<(Handle<Node>, Vector2<i32>)>,
+}
+
+/// Returns the selected tile map, the tile map, that is an ancestor of the selected node, or the
+/// first tile map of the scene.
+fn find_tile_map(graph: &Graph, selected: Option<Handle<Node>>) -> Option<Handle<Node>> {
+    let is_tile_map = |node: Handle<Node>| {
+        graph
+            .try_get(node)
+            .is_some_and(|node| node.try_get_script::<TileMap>().is_some())
+    };
+
+    let mut node = selected.unwrap_or_default();
+    while node.is_some() {
+        if is_tile_map(node) {
+            return Some(node);
+        }
+        node = graph
+            .try_get(node)
+            .map(|node| node.parent())
+            .unwrap_or_default();
+    }
+
+    graph
+        .pair_iter()
+        .find(|(_, node)| node.try_get_script::<TileMap>().is_some())
+        .map(|(handle, _)| handle)
+}
+
+/// Projects the cursor onto the plane of the tile map and returns a grid cell under it.
+fn cell_at_cursor(
+    graph: &Graph,
+    camera: Handle<Node>,
+    tile_map: Handle<Node>,
+    cursor: Vector2<f32>,
+    viewport_size: Vector2<f32>,
+) -> Option<Vector2<i32>> {
+    let camera = graph.try_get_of_type::<Camera>(camera)?;
+    let ray = camera.make_ray(cursor, viewport_size);
+
+    let node = graph.try_get(tile_map)?;
+    let global_transform = node.global_transform();
+    let normal = global_transform.transform_vector(&Vector3::z());
+    let plane = Plane::from_normal_and_point(&normal, &node.global_position())?;
+    let point = ray.plane_intersection_point(&plane)?;
+    let local_point = global_transform
+        .try_inverse()?
+        .transform_point(&Point3::from(point));
+
+    let script = node.try_get_script::<TileMap>()?;
+    Some(script.cell_at(local_point.coords))
+}
+
+impl TilePainter {
+    fn set_info(&self, editor: &Editor, text: String) {
+        editor
+            .engine
+            .user_interfaces
+            .first()
+            .send_message(TextMessage::text(
+                self.info,
+                MessageDirection::ToWidget,
+                text,
+            ));
+    }
+
+    fn select_tile(&mut self, tile: u32, ui: &UserInterface) {
+        self.selected_tile = tile;
+        for (index, cell) in self.palette_cells.iter().enumerate() {
+            let color = if index as u32 == tile {
+                Color::opaque(255, 200, 0)
+            } else {
+                Color::TRANSPARENT
+            };
+            ui.send_message(WidgetMessage::foreground(
+                *cell,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+
+    /// Returns an index of the palette cell, that is the widget or one of its ancestors.
+    fn palette_cell_of(&self, ui: &UserInterface, mut widget: Handle<UiNode>) -> Option<u32> {
+        while widget.is_some() {
+            if let Some(index) = self.palette_cells.iter().position(|cell| *cell == widget) {
+                return Some(index as u32);
+            }
+            widget = ui.try_get(widget)?.parent();
+        }
+        None
+    }
+
+    /// Returns the tile map and its cell under the cursor, the position of the cursor is in screen
+    /// space.
+    fn pick(&self, editor: &Editor, cursor: Vector2<f32>) -> Option<(Handle<Node>, Vector2<i32>)> {
+        let frame = editor.scene_viewer.frame();
+        let (origin, size) = editor
+            .engine
+            .user_interfaces
+            .first()
+            .try_get(frame)
+            .map(|frame| (frame.screen_position(), frame.actual_global_size()))?;
+
+        let entry = editor.scenes.current_scene_entry_ref()?;
+        let game_scene = entry.controller.downcast_ref::<GameScene>()?;
+        let graph = &editor.engine.scenes.try_get(game_scene.scene)?.graph;
+        let selected = entry
+            .selection
+            .as_graph()
+            .and_then(|selection| selection.nodes().first().copied());
+        let tile_map = find_tile_map(graph, selected)?;
+        let camera = game_scene.camera_controller.camera;
+        let cell = cell_at_cursor(graph, camera, tile_map, cursor - origin, size)?;
+        Some((tile_map, cell))
+    }
+
+    /// Fills a rectangle between two cells (inclusive) with the selected tile.
+    fn paint(
+        &mut self,
+        editor: &mut Editor,
+        tile_map: Handle<Node>,
+        a: Vector2<i32>,
+        b: Vector2<i32>,
+    ) {
+        let Some(tiles) = editor
+            .scenes
+            .current_scene_entry_ref()
+            .and_then(|entry| entry.controller.downcast_ref::<GameScene>())
+            .and_then(|game_scene| editor.engine.scenes.try_get(game_scene.scene))
+            .and_then(|scene| scene.graph.try_get(tile_map))
+            .and_then(|node| node.try_get_script::<TileMap>())
+            .map(|script| script.filled(a, b, self.selected_tile))
+        else {
+            return;
+        };
+
+        let material = self
+            .material
+            .get_or_insert_with(|| tile_material(&editor.engine.resource_manager))
+            .clone();
+        editor.message_sender.do_command(PaintTilesCommand {
+            tile_map,
+            tiles,
+            material,
+        });
+
+        let size = (a - b).abs().add_scalar(1);
+        self.set_info(editor, format!("Painted {}x{} tiles", size.x, size.y));
+    }
+}
+
+impl EditorPlugin for TilePainter {
+    fn on_start(&mut self, editor: &mut Editor) {
+        let texture = editor
+            .engine
+            .resource_manager
+            .request::<Texture>(TILE_SHEET_PATH);
+        let ui = editor.engine.user_interfaces.first_mut();
+        let ctx = &mut ui.build_ctx();
+
+        self.palette_cells = (0..SHEET_COLUMNS * SHEET_ROWS)
+            .map(|tile| {
+                let image = ImageBuilder::new(WidgetBuilder::new())
+                    .with_texture(texture.clone().into())
+                    .with_uv_rect(tile_uv_rect(tile))
+                    .build(ctx);
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row((tile / SHEET_COLUMNS) as usize)
+                        .on_column((tile % SHEET_COLUMNS) as usize)
+                        .with_foreground(Brush::Solid(Color::TRANSPARENT))
+                        .with_child(image),
+                )
+                .with_stroke_thickness(Thickness::uniform(2.0))
+                .build(ctx)
+            })
+            .collect();
+
+        let mut grid =
+            GridBuilder::new(WidgetBuilder::new().with_children(self.palette_cells.clone()));
+        for _ in 0..SHEET_COLUMNS {
+            grid = grid.add_column(Column::strict(PALETTE_CELL_SIZE));
+        }
+        for _ in 0..SHEET_ROWS {
+            grid = grid.add_row(Row::strict(PALETTE_CELL_SIZE));
+        }
+        let grid = grid.build(ctx);
+
+        self.mode = CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            .checked(Some(false))
+            .with_content(
+                TextBuilder::new(WidgetBuilder::new())
+                    .with_text("Paint Tiles")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx),
+            )
+            .build(ctx);
+        self.info = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            .with_text("Add the Tile Map script to a node to paint on it")
+            .build(ctx);
+        self.window = WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(SHEET_COLUMNS as f32 * PALETTE_CELL_SIZE + 10.0)
+                .with_desired_position(Vector2::new(300.0, 100.0)),
+        )
+        .with_title(WindowTitle::text("Tile Painter"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(self.mode)
+                    .with_child(grid)
+                    .with_child(self.info),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            false,
+            false,
+        ));
+        self.select_tile(0, ui);
+    }
+
+    fn on_ui_message(&mut self, message: &mut UiMessage, editor: &mut Editor) {
+        let destination = message.destination();
+        if let Some(WidgetMessage::MouseDown {
+            pos,
+            button: MouseButton::Left,
+        }) = message.data()
+        {
+            let ui = editor.engine.user_interfaces.first();
+            if let Some(tile) = self.palette_cell_of(ui, destination) {
+                self.select_tile(tile, ui);
+            } else if self.mode_enabled && destination == editor.scene_viewer.frame() {
+                self.drag_start = self.pick(editor, *pos);
+            }
+        } else if let Some(WidgetMessage::MouseUp {
+            pos,
+            button: MouseButton::Left,
+        }) = message.data()
+        {
+            if let Some((tile_map, start)) = self.drag_start.take() {
+                // The end of the stroke must be on the same tile map.
+                match self.pick(editor, *pos) {
+                    Some((end_map, end)) if end_map == tile_map => {
+                        self.paint(editor, tile_map, start, end)
+                    }
+                    _ => self.paint(editor, tile_map, start, start),
+                }
+            }
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if destination == self.mode && message.direction() == MessageDirection::FromWidget {
+                self.mode_enabled = *value;
+                self.drag_start = None;
+            }
+        }
+    }
+}
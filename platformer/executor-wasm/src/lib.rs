@@ -40,9 +40,17 @@ pub fn set_panic_hook() {
     });
 }
 
+#[wasm_bindgen(inline_js = "export function is_touch_device() {
+    return 'ontouchstart' in window || navigator.maxTouchPoints > 0;
+}")]
+extern "C" {
+    fn is_touch_device() -> bool;
+}
+
 #[wasm_bindgen]
 pub fn main() {
     set_panic_hook();
+    common_scripts::throttle::watch_page_visibility();
     let mut window_attributes = WindowAttributes::default();
     window_attributes.inner_size = Some(LogicalSize::new(800, 600).into());
     window_attributes.resizable = true;
@@ -54,6 +62,11 @@ pub fn main() {
             vsync: true,
         },
     );
-    executor.add_plugin(Game::default());
+    // On-screen controls are shown only on touch screens, there is a keyboard otherwise.
+    if is_touch_device() {
+        executor.add_plugin(Game::with_touch_controls());
+    } else {
+        executor.add_plugin(Game::default());
+    }
     executor.run()
 }
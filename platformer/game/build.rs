@@ -0,0 +1,12 @@
+// Transcodes the textures, that are loaded from code, to a compressed format of the target platform.
+fn main() {
+    asset_pipeline::run(&[
+        "data/background/BG.png",
+        "data/objects/Mushroom_1.png",
+        "data/objects/Sign_2.png",
+        "data/tiles.png",
+        "data/objects/Crate.png",
+        "data/objects/Stone.png",
+        "data/objects/Mushroom_2.png",
+    ]);
+}
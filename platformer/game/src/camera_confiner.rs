@@ -0,0 +1,183 @@
+//! Keeps the camera within the bounds of a level, so it never shows the blank space around it.
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{
+        camera::Camera,
+        dim2::{
+            collider::{Collider, ColliderShape},
+            rigidbody::RigidBody,
+        },
+        graph::Graph,
+        rigidbody::RigidBodyType,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+
+const BOUNDS_COLOR: Color = Color::opaque(255, 220, 0);
+const VIEW_COLOR: Color = Color::opaque(0, 200, 80);
+
+/// Clamps the position of the camera, so its view stays within the bounds. The script must be
+/// assigned to a camera, which could be a child of the player - the camera keeps its initial local
+/// offset from the parent, until the view hits the bounds.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "59e9e43d-1001-4ee5-a6ed-fafe59059c06")]
+#[visit(optional)]
+pub struct CameraConfiner {
+    /// World-space rectangle, that the view of the camera must stay within.
+    pub bounds: InheritableVariable<Rect<f32>>,
+    /// Draws the bounds and the view of the camera. The script clears the debug lines of the
+    /// scene every frame, while it is enabled.
+    pub show_bounds: InheritableVariable<bool>,
+    /// Local position of the camera before confining.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    offset: Option<Vector3<f32>>,
+}
+
+impl Default for CameraConfiner {
+    fn default() -> Self {
+        Self {
+            bounds: Rect::new(-50.0, -20.0, 100.0, 40.0).into(),
+            show_bounds: false.into(),
+            offset: None,
+        }
+    }
+}
+
+impl CameraConfiner {
+    pub fn new(bounds: Rect<f32>) -> Self {
+        Self {
+            bounds: bounds.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Clamps the center of a view with the given half extent to the range, if the view is larger
+/// than the range, it is centered on the range.
+fn confine(center: f32, half_extent: f32, min: f32, size: f32) -> f32 {
+    let (low, high) = (min + half_extent, min + size - half_extent);
+    if low > high {
+        min + size * 0.5
+    } else {
+        center.clamp(low, high)
+    }
+}
+
+impl ScriptTrait for CameraConfiner {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+        let Some(node) = graph.try_get(ctx.handle) else {
+            return;
+        };
+        let offset = *self
+            .offset
+            .get_or_insert_with(|| **node.local_transform().position());
+        let parent_transform = graph
+            .try_get(node.parent())
+            .map(|parent| parent.global_transform())
+            .unwrap_or_else(Matrix4::identity);
+
+        // Orthographic projection maps half of the view to [-1; 1], so the half extents of the view
+        // in world units (which already include the zoom of the camera) are the inverse of the
+        // scale of the projection.
+        let half_extents = node
+            .cast::<Camera>()
+            .map(|camera| {
+                let projection = camera.projection_matrix();
+                let inverse = |scale: f32| {
+                    if scale.abs() > f32::EPSILON {
+                        1.0 / scale.abs()
+                    } else {
+                        0.0
+                    }
+                };
+                Vector2::new(inverse(projection[(0, 0)]), inverse(projection[(1, 1)]))
+            })
+            .unwrap_or_default();
+
+        let bounds = *self.bounds;
+        let desired = parent_transform.transform_point(&Point3::from(offset));
+        let confined = Point3::new(
+            confine(desired.x, half_extents.x, bounds.position.x, bounds.size.x),
+            confine(desired.y, half_extents.y, bounds.position.y, bounds.size.y),
+            desired.z,
+        );
+        let local_position = parent_transform
+            .try_inverse()
+            .map(|inverse| inverse.transform_point(&confined).coords)
+            .unwrap_or(confined.coords);
+        graph[ctx.handle]
+            .local_transform_mut()
+            .set_position(local_position);
+
+        if *self.show_bounds {
+            let drawing_context = &mut ctx.scene.drawing_context;
+            drawing_context.clear_lines();
+            drawing_context.draw_rectangle(
+                bounds.size.scale(0.5),
+                Matrix4::new_translation(&Vector3::new(
+                    bounds.position.x + bounds.size.x * 0.5,
+                    bounds.position.y + bounds.size.y * 0.5,
+                    0.0,
+                )),
+                BOUNDS_COLOR,
+            );
+            drawing_context.draw_rectangle(
+                half_extents,
+                Matrix4::new_translation(&Vector3::new(confined.x, confined.y, 0.0)),
+                VIEW_COLOR,
+            );
+        }
+    }
+}
+
+/// Returns the rectangle around static colliders of the level, which are the ground and the
+/// platforms. Rotation and scale of the colliders are ignored.
+pub fn level_bounds(graph: &Graph) -> Option<Rect<f32>> {
+    let mut corners = graph.linear_iter().filter_map(|node| {
+        let collider = node.cast::<Collider>()?;
+        let body = graph.try_get_of_type::<RigidBody>(node.parent())?;
+        if collider.is_sensor() || body.body_type() != RigidBodyType::Static {
+            return None;
+        }
+        let half_extents = match collider.shape() {
+            ColliderShape::Cuboid(cuboid) => cuboid.half_extents,
+            ColliderShape::Ball(ball) => Vector2::repeat(ball.radius),
+            _ => Vector2::default(),
+        };
+        let position = collider.global_position().xy();
+        Some((position - half_extents, position + half_extents))
+    });
+    let first = corners.next()?;
+    let (min, max) = corners.fold(first, |(min, max), (low, high)| {
+        (min.inf(&low), max.sup(&high))
+    });
+    Some(Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+}
+
+/// Adds the confiner to the cameras of the scene, that have none, with the bounds of the level.
+pub fn add_camera_confiners(graph: &mut Graph) {
+    let Some(bounds) = level_bounds(graph) else {
+        return;
+    };
+    let cameras = graph
+        .pair_iter()
+        .filter(|(_, node)| {
+            node.cast::<Camera>().is_some() && node.try_get_script::<CameraConfiner>().is_none()
+        })
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+    for camera in cameras {
+        graph[camera].add_script(Script::new(CameraConfiner::new(bounds)));
+    }
+}
@@ -0,0 +1,214 @@
+//! Simple enemies with health bars above their heads.
+use crate::{world_label::WorldSpaceLabel, Player, ShadowCaster};
+use fyrox::{
+    asset::{manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        task::TaskPool,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    event::{ElementState, Event, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    keyboard::{KeyCode, PhysicalKey},
+    material::{Material, MaterialResource},
+    resource::texture::{Texture, TextureResource},
+    scene::{
+        base::BaseBuilder,
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape},
+            rectangle::RectangleBuilder,
+            rigidbody::RigidBodyBuilder,
+        },
+        graph::Graph,
+        node::Node,
+        rigidbody::RigidBodyType,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+/// Prefab of an enemy, that is instantiated by wave spawners.
+pub const ENEMY_PREFAB_PATH: &str = "data/objects/enemy.rgs";
+/// Texture of the enemies. The prefab refers to the texture from `data`, not to its transcoded copy.
+pub const ENEMY_TEXTURE_PATH: &str = "data/objects/Mushroom_1.png";
+
+/// Amount of characters in the health bar.
+const HEALTH_BAR_LENGTH: usize = 10;
+
+fn health_bar(health: f32, max_health: f32) -> String {
+    let filled = ((health / max_health).clamp(0.0, 1.0) * HEALTH_BAR_LENGTH as f32).ceil() as usize;
+    format!(
+        "[{}{}] {}",
+        "|".repeat(filled),
+        ".".repeat(HEALTH_BAR_LENGTH - filled),
+        health.max(0.0) as u32
+    )
+}
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "5f1e9c3a-7b2d-4a68-b0e4-9d8c7a6b5f41")]
+#[visit(optional)]
+pub struct Enemy {
+    health: InheritableVariable<f32>,
+    max_health: InheritableVariable<f32>,
+    /// Damage dealt by the player with a single hit.
+    hit_damage: InheritableVariable<f32>,
+    /// Maximum distance from which the player can hit the enemy.
+    hit_distance: InheritableVariable<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hit_requested: bool,
+    /// Counter of alive enemies of the wave, that spawned this enemy.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wave_counter: Option<Arc<AtomicU32>>,
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self {
+            health: 100.0.into(),
+            max_health: 100.0.into(),
+            hit_damage: 25.0.into(),
+            hit_distance: 1.5.into(),
+            hit_requested: false,
+            wave_counter: None,
+        }
+    }
+}
+
+impl Enemy {
+    pub fn set_wave_counter(&mut self, counter: Arc<AtomicU32>) {
+        self.wave_counter = Some(counter);
+    }
+}
+
+impl ScriptTrait for Enemy {
+    fn on_os_event(&mut self, event: &Event<()>, _ctx: &mut ScriptContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event: input, .. },
+            ..
+        } = event
+        {
+            if input.state == ElementState::Pressed
+                && input.physical_key == PhysicalKey::Code(KeyCode::KeyF)
+            {
+                self.hit_requested = true;
+            }
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if !std::mem::take(&mut self.hit_requested) {
+            return;
+        }
+
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        let Some(player) = ctx
+            .scene
+            .graph
+            .linear_iter()
+            .find(|node| node.try_get_script::<Player>().is_some())
+        else {
+            return;
+        };
+
+        if player.global_position().metric_distance(&position) > *self.hit_distance {
+            return;
+        }
+
+        let health = *self.health - *self.hit_damage;
+        self.health.set_value_and_mark_modified(health);
+
+        if health <= 0.0 {
+            if let Some(counter) = self.wave_counter.take() {
+                counter.fetch_sub(1, Ordering::SeqCst);
+            }
+            ctx.scene.graph.remove_node(ctx.handle);
+        } else if let Some(label) =
+            ctx.scene.graph[ctx.handle].try_get_script_mut::<WorldSpaceLabel>()
+        {
+            label.set_text(health_bar(health, *self.max_health));
+        }
+    }
+}
+
+/// Creates an enemy at the given position. Enemies are affected by gravity, so they can be
+/// spawned above the ground.
+pub fn spawn_enemy(
+    graph: &mut Graph,
+    position: Vector2<f32>,
+    texture: TextureResource,
+) -> Handle<Node> {
+    let mut material = Material::standard_2d();
+    Log::verify(material.set_texture(&"diffuseTexture".into(), Some(texture)));
+
+    let sprite = RectangleBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_scale(Vector3::new(0.8, 0.8, 1.0))
+                .build(),
+        ),
+    )
+    .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+    .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(0.4, 0.4))
+        .build(graph);
+
+    let enemy = Enemy::default();
+    let label = WorldSpaceLabel::new(
+        health_bar(*enemy.health, *enemy.max_health),
+        Vector3::new(0.0, 0.7, 0.0),
+    );
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name("Enemy")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(position.x, position.y, 0.0))
+                    .build(),
+            )
+            .with_children(&[sprite, collider])
+            .with_script(Script::new(enemy))
+            .with_script(Script::new(label))
+            .with_script(Script::new(ShadowCaster::new(0.4, 0.7))),
+    )
+    .with_body_type(RigidBodyType::Dynamic)
+    .with_rotation_locked(true)
+    .build(graph)
+}
+
+/// Generates the enemy prefab and saves it to `data/objects/enemy.rgs`. The prefab is a committed
+/// asset, that could be edited in the editor, so this is done only by the `--generate-prefab` flag
+/// of the desktop executor, never by the game itself.
+pub fn generate_enemy_prefab() {
+    let resource_manager = ResourceManager::new(Arc::new(TaskPool::new()));
+    let mut scene = Scene::new();
+    spawn_enemy(
+        &mut scene.graph,
+        Vector2::default(),
+        resource_manager.request::<Texture>(ENEMY_TEXTURE_PATH),
+    );
+
+    let mut visitor = Visitor::new();
+    Log::verify(scene.save("Scene", &mut visitor));
+    match visitor.save_binary(ENEMY_PREFAB_PATH) {
+        Ok(()) => Log::info(format!("The prefab is saved to {ENEMY_PREFAB_PATH}")),
+        Err(err) => Log::err(format!(
+            "Unable to save {ENEMY_PREFAB_PATH}. Reason: {err:?}"
+        )),
+    }
+}
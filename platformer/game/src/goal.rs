@@ -0,0 +1,84 @@
+//! Level goal, that shows a message when the player reaches it.
+use crate::{Game, Player};
+use common_scripts::{TriggerCallback, TriggerVolume};
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{algebra::Vector3, log::Log, pool::Handle},
+    graph::BaseSceneGraph,
+    gui::{message::MessageDirection, widget::WidgetMessage},
+    material::{Material, MaterialResource},
+    resource::texture::TextureResource,
+    scene::{
+        base::BaseBuilder,
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape},
+            rectangle::RectangleBuilder,
+            rigidbody::RigidBodyBuilder,
+        },
+        graph::Graph,
+        node::Node,
+        rigidbody::RigidBodyType,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext},
+};
+
+#[derive(Debug, Clone)]
+struct GoalCallback;
+
+impl TriggerCallback for GoalCallback {
+    fn on_enter(&mut self, body: Handle<Node>, ctx: &mut ScriptContext) {
+        if ctx.scene.graph[body].try_get_script::<Player>().is_none() {
+            return;
+        }
+
+        let Some(game) = ctx.plugins.of_type_mut::<Game>() else {
+            return;
+        };
+        game.replay.finish_run();
+        let (hud, goal_text) = (game.hud, game.goal_text);
+
+        if let Some(hud) = ctx.user_interfaces.try_get(hud) {
+            hud.send_message(WidgetMessage::visibility(
+                goal_text,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+    }
+
+    fn on_exit(&mut self, _body: Handle<Node>, _ctx: &mut ScriptContext) {}
+
+    fn clone_box(&self) -> Box<dyn TriggerCallback> {
+        Box::new(self.clone())
+    }
+}
+
+/// Creates a sign with a sensor around it, that finishes the level when the player touches it.
+pub fn spawn_goal(graph: &mut Graph, position: Vector3<f32>, texture: TextureResource) {
+    let mut material = Material::standard_2d();
+    Log::verify(material.set_texture(&"diffuseTexture".into(), Some(texture)));
+
+    let sign = RectangleBuilder::new(BaseBuilder::new())
+        .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+        .build(graph);
+
+    let sensor = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(0.5, 1.0))
+        .with_sensor(true)
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name("Goal")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[sign, sensor])
+            .with_script(Script::new(TriggerVolume::new(GoalCallback))),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(graph);
+}
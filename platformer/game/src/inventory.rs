@@ -0,0 +1,301 @@
+//! Items, that are picked up by the player, and the inventory, that keeps them between the runs of
+//! the game. The inventory is shown on the HUD, the selected item could be dropped back to the
+//! level.
+use crate::{platform_texture_path, Game, Player};
+use common_scripts::TriggerVolume;
+use fyrox::{
+    asset::{manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        parking_lot::Mutex,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder,
+        dim2::{
+            collider::{ColliderBuilder, ColliderShape},
+            rectangle::RectangleBuilder,
+            rigidbody::RigidBodyBuilder,
+        },
+        graph::Graph,
+        node::Node,
+        rigidbody::RigidBodyType,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use std::sync::Arc;
+
+/// Path of the file with the inventory, relative to the working directory.
+const INVENTORY_PATH: &str = "data/inventory.bin";
+/// Size of the sprite of an item in the world, in meters.
+const ITEM_SIZE: f32 = 0.5;
+
+/// Items, that are placed around the player at the start of the level, and their offsets from
+/// the player.
+pub const LEVEL_ITEMS: [(&str, &str, [f32; 2]); 3] = [
+    ("Crate", "data/objects/Crate.png", [-2.0, 0.5]),
+    ("Stone", "data/objects/Stone.png", [5.0, 0.5]),
+    ("Mushroom", "data/objects/Mushroom_2.png", [9.0, 0.5]),
+];
+
+#[derive(Visit, Default, Clone, PartialEq, Debug)]
+pub struct ItemData {
+    pub name: String,
+    /// Path of the texture of the item in `data`, it is used to spawn the item again.
+    pub texture: String,
+}
+
+#[derive(Visit, Default, Clone, Debug)]
+pub struct PlayerInventory {
+    pub items: Vec<ItemData>,
+}
+
+impl PlayerInventory {
+    /// Loads the inventory from the file, starts with an empty one if the file does not exist or
+    /// is corrupted.
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read(INVENTORY_PATH) else {
+            return Self::default();
+        };
+
+        let mut inventory = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => {
+                if let Err(err) = inventory.visit("PlayerInventory", &mut visitor) {
+                    Log::err(format!("Unable to read player inventory: {err:?}"));
+                    inventory = Self::default();
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load player inventory: {err:?}")),
+        }
+        inventory
+    }
+
+    pub fn save(&self) {
+        let mut inventory = self.clone();
+        let mut visitor = Visitor::new();
+        Log::verify(inventory.visit("PlayerInventory", &mut visitor));
+        Log::verify(visitor.save_binary(INVENTORY_PATH));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.items.iter().any(|item| item.name == name)
+    }
+}
+
+/// An item, that is added to the inventory of the player and removed from the scene, when the
+/// player touches it. Overlaps are tracked by [`TriggerVolume`] on the same node.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8c4a2e6f-1d3b-4f75-a9e2-6b0d5c7f3e18")]
+#[visit(optional)]
+pub struct CollectibleItem {
+    item_name: InheritableVariable<String>,
+    /// Path of the texture of the item in `data`.
+    texture: InheritableVariable<String>,
+    /// A dropped item is picked up only after the player has left it, otherwise it would be
+    /// picked up right away.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wait_for_exit: bool,
+    /// Whether the player has been inside the dropped item. Overlaps of a new item are found
+    /// only on the next physics step, so the first updates do not see the player yet.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    seen_inside: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    inventory: Option<Arc<Mutex<PlayerInventory>>>,
+}
+
+impl ScriptTrait for CollectibleItem {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.inventory = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| game.inventory.clone());
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &ctx.scene.graph;
+        let Some(trigger) = graph[ctx.handle].try_get_script::<TriggerVolume>() else {
+            return;
+        };
+        let player_inside = trigger.overlapping().iter().any(|body| {
+            graph
+                .try_get(*body)
+                .is_some_and(|body| body.try_get_script::<Player>().is_some())
+        });
+
+        if self.wait_for_exit {
+            if player_inside {
+                self.seen_inside = true;
+            } else if self.seen_inside {
+                self.wait_for_exit = false;
+            }
+            return;
+        }
+        if !player_inside {
+            return;
+        }
+
+        if let Some(inventory) = self.inventory.as_ref() {
+            inventory.lock().items.push(ItemData {
+                name: (*self.item_name).clone(),
+                texture: (*self.texture).clone(),
+            });
+            ctx.scene.graph.remove_node(ctx.handle);
+        }
+    }
+}
+
+/// Creates a collectible item at the given position. `dropped` items are picked up only after the
+/// player has left them.
+pub fn spawn_item(
+    graph: &mut Graph,
+    position: Vector2<f32>,
+    item: &ItemData,
+    dropped: bool,
+    resource_manager: &ResourceManager,
+) -> Handle<Node> {
+    let texture = resource_manager.request::<Texture>(platform_texture_path(&item.texture));
+    let mut material = Material::standard_2d();
+    Log::verify(material.set_texture(&"diffuseTexture".into(), Some(texture)));
+
+    let sprite = RectangleBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_scale(Vector3::new(ITEM_SIZE, ITEM_SIZE, 1.0))
+                .build(),
+        ),
+    )
+    .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+    .build(graph);
+
+    let sensor = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(ITEM_SIZE * 0.5, ITEM_SIZE * 0.5))
+        .with_sensor(true)
+        .build(graph);
+
+    let script = CollectibleItem {
+        item_name: item.name.clone().into(),
+        texture: item.texture.clone().into(),
+        wait_for_exit: dropped,
+        seen_inside: false,
+        inventory: None,
+    };
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name(item.name.as_str())
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(position.x, position.y, 0.0))
+                    .build(),
+            )
+            .with_children(&[sprite, sensor])
+            .with_script(Script::new(TriggerVolume::default()))
+            .with_script(Script::new(script)),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(graph)
+}
+
+/// List of the items of the inventory on the HUD.
+#[derive(Default, Debug)]
+pub struct InventoryPanel {
+    list: Handle<UiNode>,
+    /// Items, that are shown in the list, in the same order.
+    items: Vec<ItemData>,
+    selected: Option<usize>,
+}
+
+fn make_item(ctx: &mut BuildContext, item: &ItemData) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_text(item.name.as_str())
+        .build(ctx)
+}
+
+impl InventoryPanel {
+    pub fn new(ctx: &mut BuildContext, inventory: &PlayerInventory) -> Self {
+        let items = inventory.items.clone();
+        let list = ListViewBuilder::new(WidgetBuilder::new().with_height(120.0))
+            .with_items(items.iter().map(|item| make_item(ctx, item)).collect())
+            .build(ctx);
+        StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(160.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness {
+                    left: 5.0,
+                    top: 50.0,
+                    right: 5.0,
+                    bottom: 5.0,
+                })
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Inventory ([Delete] - drop)")
+                        .build(ctx),
+                )
+                .with_child(list),
+        )
+        .build(ctx);
+
+        Self {
+            list,
+            items,
+            selected: None,
+        }
+    }
+
+    /// Rebuilds the list, if the items of the inventory have changed.
+    pub fn update(&mut self, inventory: &PlayerInventory, ui: &mut UserInterface) {
+        if self.items == inventory.items {
+            return;
+        }
+        self.items = inventory.items.clone();
+        self.selected = None;
+        let items = self
+            .items
+            .iter()
+            .map(|item| make_item(&mut ui.build_ctx(), item))
+            .collect();
+        ui.send_message(ListViewMessage::items(
+            self.list,
+            MessageDirection::ToWidget,
+            items,
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.selected = *selection;
+            }
+        }
+    }
+
+    /// Index of the selected item in the inventory.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+}
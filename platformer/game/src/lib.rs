@@ -1,9 +1,34 @@
 //! Game project.
+use crate::{
+    camera_confiner::CameraConfiner,
+    enemy::{spawn_enemy, Enemy},
+    goal::spawn_goal,
+    inventory::{
+        spawn_item, CollectibleItem, InventoryPanel, ItemData, PlayerInventory, LEVEL_ITEMS,
+    },
+    parallax::{build_parallax_background, ParallaxBackground},
+    physics_material::{PhysicsMaterialLibrary, PhysicsMaterialRef},
+    pixel_art::PixelArtMode,
+    prediction::{LatencyPanel, PlayerInput, PredictiveInput},
+    replay::GhostReplay,
+    sprite_batch::SpriteBatcher,
+    virtual_joystick::{VirtualJoystickBuilder, VirtualJoystickMessage},
+    wave::{add_wave_spawner, WaveSpawner},
+    world_label::WorldSpaceLabel,
+};
+use common_scripts::Throttle;
+#[cfg(feature = "haptic")]
+use fyrox::core::log::Log;
 use fyrox::{
+    asset::untyped::ResourceKind,
     core::{
-        algebra::{Vector2, Vector3},
+        algebra::{Matrix2, Point2, Point3, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        math::Rect,
+        parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
+        variable::InheritableVariable,
         visitor::prelude::*,
         TypeUuidProvider,
         type_traits::prelude::*
@@ -12,43 +37,262 @@ use fyrox::{
     event::{ElementState, Event, WindowEvent},
     gui::{
         button::ButtonMessage,
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        image::{ImageBuilder, ImageMessage},
         message::{MessageDirection, UiMessage},
-        text::TextMessage,
-        widget::WidgetMessage,
-        UiNode, UserInterface,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
     keyboard::{KeyCode, PhysicalKey},
     plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::texture::{
+        Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+    },
     scene::{
         animation::spritesheet::SpriteSheetAnimation,
-        dim2::{rectangle::Rectangle, rigidbody::RigidBody},
+        base::BaseBuilder,
+        camera::Camera,
+        dim2::{
+            collider::Collider,
+            physics::{Intersection, RayCastOptions},
+            rectangle::{Rectangle, RectangleBuilder},
+            rigidbody::RigidBody,
+        },
+        graph::Graph,
+        light::directional::DirectionalLight,
         node::Node,
+        pivot::PivotBuilder,
         Scene,
     },
-    script::{ScriptContext, ScriptTrait},
+    script::{Script, ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
 };
-use std::path::Path;
 use fyrox::core::ComponentProvider;
 use fyrox::graph::SceneGraph;
 
+mod camera_confiner;
+mod enemy;
+mod goal;
+mod inventory;
+mod parallax;
+mod physics_material;
+mod pixel_art;
+mod prediction;
+mod replay;
+mod sprite_batch;
+pub mod tile_map;
+mod virtual_joystick;
+mod wave;
+mod world_label;
+
+pub use enemy::generate_enemy_prefab;
+
+const SCENE_NAME: &str = "scene";
+
+/// Directory with the textures, that are transcoded for the target platform by `build.rs`.
+const ASSET_PATH: &str = env!("ASSET_PATH");
+
+/// Returns the path of the transcoded copy of a texture from `data`.
+pub(crate) fn platform_texture_path(path: &str) -> PathBuf {
+    common_scripts::transcoded_texture_path(
+        path,
+        ASSET_PATH,
+        env!("ASSET_EXTENSION"),
+        env!("TRANSCODED_ASSETS"),
+    )
+}
+
+/// Horizontal offset of the virtual joystick, that moves the player.
+const JOYSTICK_DEAD_ZONE: f32 = 0.3;
+/// Vertical offset of the virtual joystick, that makes the player jump.
+const JOYSTICK_JUMP_THRESHOLD: f32 = 0.6;
+const JOYSTICK_SIZE: f32 = 160.0;
+/// Vertical speed of the player, while the jump button is held.
+const JUMP_SPEED: f32 = 4.0;
+
 #[derive(Visit, Reflect, Debug, Default)]
 pub struct Game {
     scene: Handle<Scene>,
     debug_text: Handle<UiNode>,
     new_game: Handle<UiNode>,
     exit: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fog_of_war: FogOfWar,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pixel_art: PixelArtMode,
+    /// A user interface for in-game overlays, such as labels above enemies.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) hud: Handle<UserInterface>,
+    /// A text on the HUD, that is shown when the player reaches the goal.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) goal_text: Handle<UiNode>,
+    /// A text on the HUD with the countdown to the next wave of enemies.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) wave_text: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) replay: GhostReplay,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    sprite_batcher: SpriteBatcher,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) physics_materials: PhysicsMaterialLibrary,
+    /// Shows the virtual joystick on the HUD, it is used on touch screens.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    touch_controls: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    virtual_joystick: Handle<UiNode>,
+    /// Current value of the virtual joystick, see [`VirtualJoystickMessage::Value`].
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) joystick: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) haptics: HapticSystem,
+    /// Simulated network latency for the client-side prediction of the player.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) latency: LatencyPanel,
+    /// Items, that are picked up by the player, the inventory is kept between the runs.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) inventory: Arc<Mutex<PlayerInventory>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    inventory_panel: InventoryPanel,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    /// Creates the game with on-screen controls for touch screens.
+    pub fn with_touch_controls() -> Self {
+        Self {
+            touch_controls: true,
+            ..Default::default()
+        }
+    }
+
+    /// Removes the item, that is selected on the HUD, from the inventory and spawns it back to the
+    /// level at the position of the player.
+    fn drop_selected_item(&mut self, context: &mut PluginContext) {
+        let Some(index) = self.inventory_panel.selected() else {
+            return;
+        };
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        let Some(player_position) = scene
+            .graph
+            .linear_iter()
+            .find(|node| node.try_get_script::<Player>().is_some())
+            .map(|node| node.global_position())
+        else {
+            return;
+        };
+        let mut inventory = self.inventory.lock();
+        if index >= inventory.items.len() {
+            return;
+        }
+        let item = inventory.items.remove(index);
+        spawn_item(
+            &mut scene.graph,
+            player_position.xy(),
+            &item,
+            true,
+            context.resource_manager,
+        );
+    }
+}
+
+fn find_player_mut(graph: &mut Graph) -> Option<&mut Player> {
+    let handle = graph
+        .pair_iter()
+        .find(|(_, node)| node.try_get_script::<Player>().is_some())
+        .map(|(handle, _)| handle)?;
+    graph[handle].try_get_script_mut::<Player>()
 }
 
 impl Plugin for Game {
     fn register(&self, context: PluginRegistrationContext) {
         let script_constructors = &context.serialization_context.script_constructors;
         script_constructors.add::<Player>("Player");
+        script_constructors.add::<ParallaxBackground>("Parallax Background");
+        script_constructors.add::<Enemy>("Enemy");
+        script_constructors.add::<WorldSpaceLabel>("World Space Label");
+        script_constructors.add::<ShadowCaster>("Shadow Caster");
+        script_constructors.add::<WaveSpawner>("Wave Spawner");
+        script_constructors.add::<PhysicsMaterialRef>("Physics Material Ref");
+        script_constructors.add::<CameraConfiner>("Camera Confiner");
+        script_constructors.add::<CollectibleItem>("Collectible Item");
+        script_constructors.add::<tile_map::TileMap>("Tile Map");
+        common_scripts::register(script_constructors);
     }
 
-    fn init(&mut self, scene_path: Option<&str>, ctx: PluginContext) {
+    fn init(&mut self, scene_path: Option<&str>, mut ctx: PluginContext) {
         ctx.async_scene_loader
             .request(scene_path.unwrap_or("data/scene.rgs"));
 
+        self.fog_of_war.init(&mut ctx);
+        self.physics_materials = PhysicsMaterialLibrary::load(&mut ctx);
+        self.haptics.init();
+        self.inventory = Arc::new(Mutex::new(PlayerInventory::load()));
+
+        let mut hud = UserInterface::new(Vector2::new(100.0, 100.0));
+        let hud_ctx = &mut hud.build_ctx();
+        TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(5.0)))
+            .with_text("[F] - hit an enemy nearby. Find the sign to finish the level.")
+            .build(hud_ctx);
+        self.goal_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_desired_position(Vector2::new(5.0, 25.0)),
+        )
+        .with_text("Level complete!")
+        .build(hud_ctx);
+        self.wave_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(hud_ctx);
+        self.replay = GhostReplay::new(hud_ctx);
+        self.pixel_art.build_check_box(hud_ctx);
+        self.latency = LatencyPanel::new(hud_ctx);
+        self.inventory_panel = InventoryPanel::new(hud_ctx, &self.inventory.lock());
+        if self.touch_controls {
+            self.virtual_joystick = VirtualJoystickBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(JOYSTICK_SIZE)
+                    .with_height(JOYSTICK_SIZE)
+                    .with_horizontal_alignment(HorizontalAlignment::Left)
+                    .with_vertical_alignment(VerticalAlignment::Bottom)
+                    .with_margin(Thickness::uniform(30.0)),
+            )
+            .build(hud_ctx);
+        }
+        hud.send_message(WidgetMessage::visibility(
+            hud.root(),
+            MessageDirection::ToWidget,
+            false,
+        ));
+        self.hud = ctx.user_interfaces.add(hud);
+
         ctx.task_pool.spawn_plugin_task(
             UserInterface::load_from_file("data/menu.ui", ctx.resource_manager.clone()),
             |result, game: &mut Game, ctx| {
@@ -56,30 +300,159 @@ impl Plugin for Game {
                 game.new_game = ctx.user_interfaces.first().find_handle_by_name_from_root("NewGame");
                 game.exit = ctx.user_interfaces.first().find_handle_by_name_from_root("Exit");
                 game.debug_text = ctx.user_interfaces.first().find_handle_by_name_from_root("DebugText");
+                // The scene image of the pixel art mode must be under the menu, the fog and the HUD.
+                game.pixel_art.init(ctx.user_interfaces.first_mut());
             },
         );
     }
 
     fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.haptics.update();
+
+        let prediction_error = context
+            .scenes
+            .try_get_mut(self.scene)
+            .and_then(|scene| find_player_mut(&mut scene.graph))
+            .map(|player| player.prediction.error())
+            .unwrap_or_default();
+        let prediction_text = format!(
+            "RTT: {} ms, Prediction Error: {:.3} m",
+            self.latency.rtt_ms, prediction_error
+        );
+
         if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let resolution = self
+                .pixel_art
+                .effective_resolution(graphics_context.renderer.get_frame_size());
             context.user_interfaces.first().send_message(TextMessage::text(
                 self.debug_text,
                 MessageDirection::ToWidget,
-                format!("{}", graphics_context.renderer.get_statistics()),
+                format!(
+                    "{}\n{}\nPixel Resolution: {}x{}\n{}\n{}",
+                    graphics_context.renderer.get_statistics(),
+                    self.sprite_batcher.stats_text(),
+                    resolution.x,
+                    resolution.y,
+                    prediction_text,
+                    tracked_alloc::peak_ram_text()
+                ),
             ));
         }
+
+        if let (GraphicsContext::Initialized(graphics_context), Some(scene)) = (
+            &context.graphics_context,
+            context.scenes.try_get_mut(self.scene),
+        ) {
+            self.sprite_batcher
+                .update(scene, &graphics_context.renderer);
+        }
+
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            self.fog_of_war.update(scene, context);
+        }
+
+        if let (Some(scene), Some(hud)) = (
+            context.scenes.try_get_mut(self.scene),
+            context.user_interfaces.try_get(self.hud),
+        ) {
+            self.replay.update(scene, hud);
+        }
+
+        if let Some(hud) = context.user_interfaces.try_get_mut(self.hud) {
+            self.inventory_panel.update(&self.inventory.lock(), hud);
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::Resized(size) => {
+                    let size = Vector2::new(size.width as f32, size.height as f32);
+                    self.fog_of_war.resize(&mut context, size);
+                    self.pixel_art.resize(&mut context, size);
+                }
+                WindowEvent::KeyboardInput { event: input, .. } => {
+                    if input.state == ElementState::Pressed {
+                        match input.physical_key {
+                            PhysicalKey::Code(KeyCode::KeyB) => {
+                                if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                                    self.sprite_batcher.toggle(scene);
+                                }
+                            }
+                            PhysicalKey::Code(KeyCode::Delete) => {
+                                self.drop_selected_item(&mut context)
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+
+        let inner_size = context
+            .graphics_context
+            .as_initialized_ref()
+            .window
+            .inner_size();
+        let inner_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
+        self.fog_of_war.resize(&mut context, inner_size);
+        self.pixel_art.resize(&mut context, inner_size);
     }
 
     fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        self.fog_of_war.handle_ui_message(message);
+        self.pixel_art
+            .handle_ui_message(message, context, self.scene);
+        self.replay
+            .handle_ui_message(message, context.scenes.try_get_mut(self.scene));
+        self.latency.handle_ui_message(message);
+        self.inventory_panel.handle_ui_message(message);
+
+        if let Some(VirtualJoystickMessage::Value(value)) = message.data() {
+            if message.destination() == self.virtual_joystick
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.joystick = *value;
+            }
+        }
+
         if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.new_game {
-                context
-                    .user_interfaces.first()
-                    .send_message(WidgetMessage::visibility(
-                        context.user_interfaces.first().root(),
+                // The scene image of the pixel art mode is in the same interface, so only the
+                // widgets of the menu are hidden.
+                let ui = context.user_interfaces.first();
+                for &child in ui.node(ui.root()).children() {
+                    if child != self.pixel_art.image() {
+                        ui.send_message(WidgetMessage::visibility(
+                            child,
+                            MessageDirection::ToWidget,
+                            false,
+                        ));
+                    }
+                }
+                self.fog_of_war.set_visibility(context, true);
+                if let Some(hud) = context.user_interfaces.try_get(self.hud) {
+                    hud.send_message(WidgetMessage::visibility(
+                        hud.root(),
                         MessageDirection::ToWidget,
-                        false,
+                        true,
                     ));
+                    if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                        self.replay.start_run(scene, hud);
+                    }
+                }
             } else if message.destination() == self.exit {
                 if let Some(window_target) = context.window_target {
                     window_target.exit();
@@ -93,9 +466,422 @@ impl Plugin for Game {
         _path: &Path,
         scene: Handle<Scene>,
         _data: &[u8],
-        _context: &mut PluginContext,
+        context: &mut PluginContext,
     ) {
         self.scene = scene;
+
+        let scene = &mut context.scenes[scene];
+        let has_parallax = scene
+            .graph
+            .linear_iter()
+            .any(|node| node.try_get_script::<ParallaxBackground>().is_some());
+        let player = scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.try_get_script::<Player>().is_some())
+            .map(|(handle, node)| (handle, node.global_position()));
+        let Some((player, player_position)) = player else {
+            return;
+        };
+
+        if scene.graph[player]
+            .try_get_script::<ShadowCaster>()
+            .is_none()
+        {
+            scene.graph[player].add_script(Script::new(ShadowCaster::default()));
+        }
+
+        physics_material::assign_default_materials(&mut scene.graph);
+        camera_confiner::add_camera_confiners(&mut scene.graph);
+
+        // Add default background layers, if the scene does not have its own.
+        if !has_parallax {
+            build_parallax_background(
+                &mut scene.graph,
+                player,
+                context
+                    .resource_manager
+                    .request::<Texture>(platform_texture_path("data/background/BG.png")),
+            );
+        }
+
+        // A few enemies around the player, they'll fall down on the ground.
+        let enemy_texture = context
+            .resource_manager
+            .request::<Texture>(platform_texture_path("data/objects/Mushroom_1.png"));
+        for offset in [-4.0, 3.0, 6.0] {
+            spawn_enemy(
+                &mut scene.graph,
+                Vector2::new(player_position.x + offset, player_position.y + 1.0),
+                enemy_texture.clone(),
+            );
+        }
+
+        // Items, that are already in the inventory, are not placed again.
+        let inventory = self.inventory.lock();
+        for (name, texture, offset) in LEVEL_ITEMS {
+            if !inventory.contains(name) {
+                let item = ItemData {
+                    name: name.to_string(),
+                    texture: texture.to_string(),
+                };
+                spawn_item(
+                    &mut scene.graph,
+                    Vector2::new(player_position.x + offset[0], player_position.y + offset[1]),
+                    &item,
+                    false,
+                    context.resource_manager,
+                );
+            }
+        }
+
+        // Scenes without their own spawner get the default one.
+        if !scene
+            .graph
+            .linear_iter()
+            .any(|node| node.try_get_script::<WaveSpawner>().is_some())
+        {
+            add_wave_spawner(&mut scene.graph, player_position);
+        }
+
+        spawn_goal(
+            &mut scene.graph,
+            Vector3::new(player_position.x + 12.0, player_position.y, 0.0),
+            context
+                .resource_manager
+                .request::<Texture>(platform_texture_path("data/objects/Sign_2.png")),
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+        self.inventory.lock().save();
+    }
+}
+
+/// Size of the visibility grid of the fog of war along each axis.
+const FOG_MAP_SIZE: usize = 256;
+
+/// Fog of war that hides parts of the level that were not (or are not) seen by the player. The
+/// visibility is stored in a grid that covers a rectangular area of the world, the grid is uploaded
+/// to a texture every frame and shown on top of the screen using an image widget.
+#[derive(Debug)]
+struct FogOfWar {
+    // 0 - fully fogged, 255 - fully visible.
+    visibility: Vec<u8>,
+    texture: Option<TextureResource>,
+    // The fog lives in its own user interface, because the main one is hidden when the game starts.
+    ui: Handle<UserInterface>,
+    image: Handle<UiNode>,
+    persistent_check_box: Handle<UiNode>,
+    // Persistent fog never re-fogs already seen cells.
+    persistent: bool,
+    // World-space rectangle covered by the visibility grid.
+    world_origin: Vector2<f32>,
+    world_size: Vector2<f32>,
+    vision_radius: f32,
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        Self {
+            visibility: vec![0; FOG_MAP_SIZE * FOG_MAP_SIZE],
+            texture: None,
+            ui: Default::default(),
+            image: Default::default(),
+            persistent_check_box: Default::default(),
+            persistent: true,
+            world_origin: Vector2::new(-32.0, -32.0),
+            world_size: Vector2::new(64.0, 64.0),
+            vision_radius: 4.0,
+        }
+    }
+}
+
+impl FogOfWar {
+    fn init(&mut self, context: &mut PluginContext) {
+        let texture = TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: FOG_MAP_SIZE as u32,
+                height: FOG_MAP_SIZE as u32,
+            },
+            TexturePixelKind::RGBA8,
+            // Black opaque pixels - everything is fogged at the beginning.
+            [0, 0, 0, 255].repeat(FOG_MAP_SIZE * FOG_MAP_SIZE),
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+
+        let mut ui = UserInterface::new(Vector2::new(100.0, 100.0));
+        let ctx = &mut ui.build_ctx();
+
+        self.image = ImageBuilder::new(
+            WidgetBuilder::new()
+                // The fog must not steal mouse input from other widgets.
+                .with_hit_test_visibility(false),
+        )
+        .with_texture(texture.clone().into())
+        .build(ctx);
+
+        self.persistent_check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Persistent Fog")
+                .build(ctx),
+        )
+        .checked(Some(self.persistent))
+        .build(ctx);
+
+        ui.send_message(WidgetMessage::visibility(
+            ui.root(),
+            MessageDirection::ToWidget,
+            false,
+        ));
+
+        self.texture = Some(texture);
+        self.ui = context.user_interfaces.add(ui);
+    }
+
+    fn set_visibility(&self, context: &mut PluginContext, visibility: bool) {
+        if let Some(ui) = context.user_interfaces.try_get(self.ui) {
+            ui.send_message(WidgetMessage::visibility(
+                ui.root(),
+                MessageDirection::ToWidget,
+                visibility,
+            ));
+        }
+    }
+
+    fn resize(&self, context: &mut PluginContext, new_size: Vector2<f32>) {
+        if let Some(ui) = context.user_interfaces.try_get(self.ui) {
+            ui.send_message(WidgetMessage::width(
+                self.image,
+                MessageDirection::ToWidget,
+                new_size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.image,
+                MessageDirection::ToWidget,
+                new_size.y,
+            ));
+        }
+    }
+
+    fn handle_ui_message(&mut self, message: &UiMessage) {
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.persistent_check_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.persistent = *value;
+            }
+        }
+    }
+
+    fn world_to_grid(&self, position: Vector2<f32>) -> Vector2<f32> {
+        // The first row of the grid is at the top of the world.
+        Vector2::new(
+            (position.x - self.world_origin.x) / self.world_size.x,
+            (self.world_origin.y + self.world_size.y - position.y) / self.world_size.y,
+        )
+    }
+
+    fn reveal(&mut self, position: Vector2<f32>) {
+        let center = self.world_to_grid(position).scale(FOG_MAP_SIZE as f32);
+        let radius = self.vision_radius / self.world_size.x * FOG_MAP_SIZE as f32;
+
+        let min_x = (center.x - radius).floor().max(0.0) as usize;
+        let min_y = (center.y - radius).floor().max(0.0) as usize;
+        let max_x = ((center.x + radius).ceil().max(0.0) as usize).min(FOG_MAP_SIZE);
+        let max_y = ((center.y + radius).ceil().max(0.0) as usize).min(FOG_MAP_SIZE);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let dx = x as f32 + 0.5 - center.x;
+                let dy = y as f32 + 0.5 - center.y;
+                if dx * dx + dy * dy <= radius * radius {
+                    self.visibility[y * FOG_MAP_SIZE + x] = 255;
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, scene: &Scene, context: &PluginContext) {
+        let Some(player) = scene
+            .graph
+            .linear_iter()
+            .find(|node| node.try_get_script::<Player>().is_some())
+        else {
+            return;
+        };
+
+        if !self.persistent {
+            self.visibility.fill(0);
+        }
+
+        let position = player.global_position();
+        self.reveal(Vector2::new(position.x, position.y));
+
+        if let Some(texture) = self.texture.as_ref() {
+            let mut texture = texture.data_ref();
+            let mut modifier = texture.modify();
+            for (pixel, visibility) in modifier
+                .data_mut()
+                .chunks_exact_mut(4)
+                .zip(self.visibility.iter())
+            {
+                pixel[3] = 255 - *visibility;
+            }
+        }
+
+        // Show only the part of the grid that is visible by the camera. This is done by
+        // un-projecting the corners of the screen back to the world.
+        if let Some(inv_view_projection) = scene
+            .graph
+            .linear_iter()
+            .find_map(|node| node.cast::<Camera>())
+            .and_then(|camera| camera.view_projection_matrix().try_inverse())
+        {
+            let unproject = |x: f32, y: f32| {
+                let p = inv_view_projection.transform_point(&Point3::new(x, y, 0.0));
+                self.world_to_grid(Vector2::new(p.x, p.y))
+            };
+            let top_left = unproject(-1.0, 1.0);
+            let bottom_right = unproject(1.0, -1.0);
+
+            if let Some(ui) = context.user_interfaces.try_get(self.ui) {
+                ui.send_message(ImageMessage::uv_rect(
+                    self.image,
+                    MessageDirection::ToWidget,
+                    Rect::new(
+                        top_left.x,
+                        top_left.y,
+                        bottom_right.x - top_left.x,
+                        bottom_right.y - top_left.y,
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Rumble of the first connected gamepad, that supports force feedback. The gamepad is cached and
+/// updated when gamepads are connected or disconnected. Without the `haptic` feature the rumble
+/// requests are ignored.
+#[derive(Default)]
+pub struct HapticSystem {
+    #[cfg(feature = "haptic")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "haptic")]
+    gamepad: Option<gilrs::GamepadId>,
+    /// Effects, that are playing right now, with the time of their end. An effect stops when it
+    /// is dropped, so it must be kept alive until the end.
+    #[cfg(feature = "haptic")]
+    effects: Vec<(gilrs::ff::Effect, std::time::Instant)>,
+}
+
+impl std::fmt::Debug for HapticSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HapticSystem").finish_non_exhaustive()
+    }
+}
+
+impl HapticSystem {
+    pub fn init(&mut self) {
+        #[cfg(feature = "haptic")]
+        match gilrs::Gilrs::new() {
+            Ok(gilrs) => {
+                self.gilrs = Some(gilrs);
+                self.find_gamepad();
+            }
+            Err(err) => Log::err(format!(
+                "Unable to initialize gamepad support. Reason: {err:?}"
+            )),
+        }
+    }
+
+    #[cfg(feature = "haptic")]
+    fn find_gamepad(&mut self) {
+        self.gamepad = self.gilrs.as_ref().and_then(|gilrs| {
+            gilrs
+                .gamepads()
+                .find(|(_, gamepad)| gamepad.is_connected() && gamepad.is_ff_supported())
+                .map(|(id, _)| id)
+        });
+    }
+
+    /// Handles connection of gamepads and stops finished effects. Must be called every frame.
+    pub fn update(&mut self) {
+        #[cfg(feature = "haptic")]
+        {
+            let Some(gilrs) = self.gilrs.as_mut() else {
+                return;
+            };
+            let mut connection_changed = false;
+            while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+                if matches!(
+                    event,
+                    gilrs::EventType::Connected | gilrs::EventType::Disconnected
+                ) {
+                    connection_changed = true;
+                }
+            }
+            if connection_changed {
+                self.find_gamepad();
+            }
+
+            let now = std::time::Instant::now();
+            self.effects.retain(|(_, end)| *end > now);
+        }
+    }
+
+    /// Rumbles the gamepad. `strong` and `weak` are magnitudes of the low and the high frequency
+    /// motors in `[0; 1]` range, `duration` is in seconds.
+    #[allow(unused_variables)]
+    pub fn rumble(&mut self, strong: f32, weak: f32, duration: f32) {
+        #[cfg(feature = "haptic")]
+        {
+            use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Replay, Ticks};
+
+            let (Some(gilrs), Some(gamepad)) = (self.gilrs.as_mut(), self.gamepad) else {
+                return;
+            };
+            let magnitude = |value: f32| (value.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+            let scheduling = Replay {
+                play_for: Ticks::from_ms((duration * 1000.0) as u32),
+                ..Default::default()
+            };
+            let effect = EffectBuilder::new()
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Strong {
+                        magnitude: magnitude(strong),
+                    },
+                    scheduling,
+                    ..Default::default()
+                })
+                .add_effect(BaseEffect {
+                    kind: BaseEffectType::Weak {
+                        magnitude: magnitude(weak),
+                    },
+                    scheduling,
+                    ..Default::default()
+                })
+                .gamepads(&[gamepad])
+                .finish(gilrs);
+            match effect.and_then(|effect| effect.play().map(|_| effect)) {
+                Ok(effect) => self.effects.push((
+                    effect,
+                    std::time::Instant::now() + std::time::Duration::from_secs_f32(duration),
+                )),
+                Err(err) => Log::err(format!("Unable to rumble the gamepad. Reason: {err:?}")),
+            }
+        }
     }
 }
 
@@ -109,6 +895,32 @@ struct Player {
     jump: bool,
     animations: Vec<SpriteSheetAnimation>,
     current_animation: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    grounded: bool,
+    /// Time (in seconds) until the next footstep.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    step_timer: f32,
+    /// Magnitude of the rumble of the gamepad, when the player lands on the ground.
+    land_rumble_intensity: InheritableVariable<f32>,
+    /// Duration of the rumble on landing, in seconds.
+    land_rumble_duration: InheritableVariable<f32>,
+    /// Magnitude of the rumble of the gamepad, when the player is hit by an enemy.
+    hit_rumble_intensity: InheritableVariable<f32>,
+    /// Duration of the rumble on hit, in seconds.
+    hit_rumble_duration: InheritableVariable<f32>,
+    /// Time (in seconds) until the player could be hit again.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hit_cooldown: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prediction: PredictiveInput,
+    /// Local position of the sprite without the offset of the prediction.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    sprite_origin: Option<Vector3<f32>>,
 }
 
 impl Default for Player {
@@ -120,6 +932,109 @@ impl Default for Player {
             jump: false,
             animations: Default::default(),
             current_animation: 0,
+            grounded: false,
+            step_timer: 0.0,
+            land_rumble_intensity: 0.5.into(),
+            land_rumble_duration: 0.2.into(),
+            hit_rumble_intensity: 1.0.into(),
+            hit_rumble_duration: 0.1.into(),
+            hit_cooldown: 0.0,
+            prediction: Default::default(),
+            sprite_origin: None,
+        }
+    }
+}
+
+/// Distance from the origin of the player to the ground, at which the player is considered to be
+/// standing on it.
+const GROUND_PROBE_DISTANCE: f32 = 0.6;
+/// Time between footsteps when running, in seconds.
+const FOOTSTEP_INTERVAL: f32 = 0.3;
+/// Time (in seconds) after a hit by an enemy, during which the player can't be hit again.
+const HIT_COOLDOWN: f32 = 1.0;
+/// Ratio of the magnitudes of the weak (high frequency) and the strong motors of the gamepad.
+const WEAK_RUMBLE_RATIO: f32 = 0.4;
+
+impl Player {
+    /// Detects landings and steps on the ground. When the player lands, its colliders get the
+    /// physics material of the surface, so the friction and the bounciness of the contacts
+    /// depend on the surface.
+    fn update_footsteps(&mut self, ctx: &mut ScriptContext, running: bool) {
+        let ground = cast_to_ground(&ctx.scene.graph, ctx.handle, GROUND_PROBE_DISTANCE);
+        let landed = ground.is_some() && !self.grounded;
+        self.grounded = ground.is_some();
+
+        if landed {
+            self.rumble(ctx, *self.land_rumble_intensity, *self.land_rumble_duration);
+        }
+
+        let Some(kind) = ground
+            .and_then(|ground| physics_material::surface_kind(&ctx.scene.graph, ground.collider))
+        else {
+            return;
+        };
+        let Some(game) = ctx.plugins.of_type_ref::<Game>() else {
+            return;
+        };
+        let library = &game.physics_materials;
+
+        if landed {
+            if let Some(material) = library.material(kind) {
+                for child in ctx.scene.graph[ctx.handle].children().to_vec() {
+                    if let Some(collider) = ctx.scene.graph.try_get_mut_of_type::<Collider>(child) {
+                        material.apply(collider);
+                    }
+                }
+            }
+            library.play_footstep(&mut ctx.scene.graph, kind, ctx.handle);
+            self.step_timer = FOOTSTEP_INTERVAL;
+        } else if running {
+            self.step_timer -= ctx.dt;
+            if self.step_timer <= 0.0 {
+                library.play_footstep(&mut ctx.scene.graph, kind, ctx.handle);
+                self.step_timer = FOOTSTEP_INTERVAL;
+            }
+        }
+    }
+}
+
+impl Player {
+    fn rumble(&self, ctx: &mut ScriptContext, intensity: f32, duration: f32) {
+        if let Some(game) = ctx.plugins.of_type_mut::<Game>() {
+            game.haptics
+                .rumble(intensity, intensity * WEAK_RUMBLE_RATIO, duration);
+        }
+    }
+
+    /// Checks whether the player touches an enemy. A touch counts as a hit, the next hit is
+    /// possible only after [`HIT_COOLDOWN`].
+    fn update_hits(&mut self, ctx: &mut ScriptContext) {
+        self.hit_cooldown -= ctx.dt;
+        if self.hit_cooldown > 0.0 {
+            return;
+        }
+
+        let graph = &ctx.scene.graph;
+        let is_enemy = |collider: Handle<Node>| {
+            graph
+                .try_get(collider)
+                .and_then(|collider| graph.try_get(collider.parent()))
+                .is_some_and(|body| body.try_get_script::<Enemy>().is_some())
+        };
+        let hit = graph[ctx.handle].children().iter().any(|child| {
+            graph
+                .try_get_of_type::<Collider>(*child)
+                .is_some_and(|collider| {
+                    collider.contacts(&graph.physics2d).any(|pair| {
+                        pair.has_any_active_contact
+                            && (is_enemy(pair.collider1) || is_enemy(pair.collider2))
+                    })
+                })
+        });
+
+        if hit {
+            self.hit_cooldown = HIT_COOLDOWN;
+            self.rumble(ctx, *self.hit_rumble_intensity, *self.hit_rumble_duration);
         }
     }
 }
@@ -147,47 +1062,100 @@ impl ScriptTrait for Player {
     fn on_update(&mut self, context: &mut ScriptContext) {
         // The script can be assigned to any scene node, but we assert that it will work only with
         // 2d rigid body nodes.
-        if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
-            let x_speed = if self.move_left {
-                3.0
-            } else if self.move_right {
-                -3.0
-            } else {
-                0.0
-            };
+        // The virtual joystick works together with the keyboard.
+        let joystick = context
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| game.joystick)
+            .unwrap_or_default();
+        let move_left = self.move_left || joystick.x < -JOYSTICK_DEAD_ZONE;
+        let move_right = self.move_right || joystick.x > JOYSTICK_DEAD_ZONE;
+        let jump = self.jump || joystick.y > JOYSTICK_JUMP_THRESHOLD;
+        let (rtt, correction_alpha) = context
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| (game.latency.rtt(), game.latency.correction_alpha))
+            .unwrap_or((0.0, 1.0));
+
+        let x_speed = if move_left {
+            3.0
+        } else if move_right {
+            -3.0
+        } else {
+            0.0
+        };
+        let input = PlayerInput { x_speed, jump };
 
+        // With a simulated latency, the physics is the server - it gets the input later.
+        let server_input = if rtt > 0.0 {
+            self.prediction.send_input(input, rtt, context.dt)
+        } else {
+            self.prediction.reset();
+            input
+        };
+
+        if let Some(rigid_body) = context.scene.graph[context.handle].cast_mut::<RigidBody>() {
             if x_speed != 0.0 {
                 self.current_animation = 0;
             } else {
                 self.current_animation = 1;
             }
 
-            if self.jump {
-                rigid_body.set_lin_vel(Vector2::new(x_speed, 4.0))
+            let x_speed = server_input.x_speed;
+            if server_input.jump {
+                rigid_body.set_lin_vel(Vector2::new(x_speed, JUMP_SPEED))
             } else {
                 rigid_body.set_lin_vel(Vector2::new(x_speed, rigid_body.lin_vel().y))
             };
+        }
 
-            // It is always a good practice to check whether the handles are valid, at this point we don't know
-            // for sure what's the value of the `sprite` field. It can be unassigned and the following code won't
-            // execute. A simple `context.scene.graph[self.sprite]` would just panicked in this case.
-            if let Some(sprite) = context.scene.graph.try_get_mut(self.sprite) {
-                // We want to change player orientation only if he's moving.
-                if x_speed != 0.0 {
-                    let local_transform = sprite.local_transform_mut();
-
-                    let current_scale = **local_transform.scale();
-
-                    local_transform.set_scale(Vector3::new(
-                        // Just change X scaling to mirror player's sprite.
-                        current_scale.x.copysign(-x_speed),
-                        current_scale.y,
-                        current_scale.z,
-                    ));
-                }
+        // The sprite shows the predicted position, the offset from the body is the difference
+        // between the client and the server.
+        let offset = if rtt > 0.0 {
+            let body = &context.scene.graph[context.handle];
+            let server_position = body.global_position().xy();
+            let server_velocity = body
+                .cast::<RigidBody>()
+                .map(|rigid_body| rigid_body.lin_vel())
+                .unwrap_or_default();
+            self.prediction.predict(
+                input,
+                server_position,
+                server_velocity,
+                rtt,
+                correction_alpha,
+                context.dt,
+            ) - server_position
+        } else {
+            Vector2::default()
+        };
+
+        // It is always a good practice to check whether the handles are valid, at this point we don't know
+        // for sure what's the value of the `sprite` field. It can be unassigned and the following code won't
+        // execute. A simple `context.scene.graph[self.sprite]` would just panicked in this case.
+        if let Some(sprite) = context.scene.graph.try_get_mut(self.sprite) {
+            let local_transform = sprite.local_transform_mut();
+            let origin = *self
+                .sprite_origin
+                .get_or_insert(**local_transform.position());
+            local_transform.set_position(origin + Vector3::new(offset.x, offset.y, 0.0));
+
+            // We want to change player orientation only if he's moving.
+            if x_speed != 0.0 {
+                let current_scale = **local_transform.scale();
+
+                local_transform.set_scale(Vector3::new(
+                    // Just change X scaling to mirror player's sprite.
+                    current_scale.x.copysign(-x_speed),
+                    current_scale.y,
+                    current_scale.z,
+                ));
             }
         }
 
+        self.update_footsteps(context, move_left || move_right);
+        self.update_hits(context);
+
         if let Some(current_animation) = self.animations.get_mut(self.current_animation as usize) {
             current_animation.update(context.dt);
 
@@ -212,3 +1180,224 @@ impl ScriptTrait for Player {
         }
     }
 }
+
+/// Direction of the sun, that is used when there's no directional light in the scene.
+const DEFAULT_SUN_DIRECTION: Vector3<f32> = Vector3::new(0.4, -1.0, 0.0);
+/// Maximum distance to the ground, at which a shadow is still visible.
+const MAX_SHADOW_DISTANCE: f32 = 20.0;
+
+/// Thickness of the shadow, in meters.
+const SHADOW_THICKNESS: f32 = 0.15;
+
+/// Flat shadow beneath a character, that is cast by the sun (the first directional light in the
+/// scene). The shadow is displaced, stretched and skewed along the light direction and fades out
+/// when the character is above the ground.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8e3f5a71-2c4d-4b9e-a1f6-3d7c9b0e5a28")]
+#[visit(optional)]
+pub struct ShadowCaster {
+    color: InheritableVariable<Color>,
+    /// Height above the ground at which the shadow reaches its minimal opacity.
+    max_height: InheritableVariable<f32>,
+    /// Opacity of the shadow when the character stands on the ground.
+    max_opacity: InheritableVariable<f32>,
+    /// Opacity of the shadow when the character is at `max_height` (or above).
+    min_opacity: InheritableVariable<f32>,
+    /// Distance from the origin of the character to its feet.
+    foot_offset: InheritableVariable<f32>,
+    /// Width of the shadow when the light is straight above the character.
+    width: InheritableVariable<f32>,
+    /// Pivot of the shadow, that rotates and scales it. Together with the rotation of the
+    /// rectangle this skews the shadow, node transforms can't skew on their own.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shadow: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rectangle: Handle<Node>,
+}
+
+impl Default for ShadowCaster {
+    fn default() -> Self {
+        Self {
+            color: Color::from_rgba(0, 0, 0, 160).into(),
+            max_height: 3.0.into(),
+            max_opacity: 1.0.into(),
+            min_opacity: 0.2.into(),
+            foot_offset: 0.5.into(),
+            width: 0.8.into(),
+            shadow: Default::default(),
+            rectangle: Default::default(),
+        }
+    }
+}
+
+impl ShadowCaster {
+    pub fn new(foot_offset: f32, width: f32) -> Self {
+        Self {
+            foot_offset: foot_offset.into(),
+            width: width.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a direction of the sun projected on the screen plane.
+    fn sun_direction(graph: &Graph) -> Vector2<f32> {
+        let direction = graph
+            .linear_iter()
+            .find_map(|node| node.cast::<DirectionalLight>())
+            .map(|light| light.look_vector())
+            .unwrap_or(DEFAULT_SUN_DIRECTION);
+        let view_axis = graph
+            .linear_iter()
+            .find_map(|node| node.cast::<Camera>())
+            .and_then(|camera| camera.look_vector().try_normalize(f32::EPSILON))
+            .unwrap_or_else(Vector3::z);
+        let projected = direction - view_axis.scale(direction.dot(&view_axis));
+        Vector2::new(projected.x, projected.y)
+    }
+
+    /// Returns a point on the ground below the character and the distance to it.
+    fn find_ground(graph: &Graph, character: Handle<Node>) -> Option<(Vector2<f32>, f32)> {
+        cast_to_ground(graph, character, MAX_SHADOW_DISTANCE).map(|intersection| {
+            (
+                Vector2::new(intersection.position.x, intersection.position.y),
+                intersection.toi,
+            )
+        })
+    }
+}
+
+/// Casts a ray down from the character and returns the closest intersection with the ground.
+/// Colliders of the character and sensors are ignored.
+fn cast_to_ground(graph: &Graph, character: Handle<Node>, max_len: f32) -> Option<Intersection> {
+    let position = graph[character].global_position();
+    let mut intersections = Vec::new();
+    graph.physics2d.cast_ray(
+        RayCastOptions {
+            ray_origin: Point2::new(position.x, position.y),
+            ray_direction: Vector2::new(0.0, -1.0),
+            max_len,
+            groups: Default::default(),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+    intersections.into_iter().find(|intersection| {
+        graph
+            .try_get(intersection.collider)
+            .map_or(false, |collider| {
+                collider.parent() != character
+                    && collider
+                        .cast::<Collider>()
+                        .map_or(false, |collider| !collider.is_sensor())
+            })
+    })
+}
+
+impl ScriptTrait for ShadowCaster {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // The shadow is not a child of the character, otherwise it would be mirrored and moved
+        // together with the sprite.
+        self.rectangle = RectangleBuilder::new(BaseBuilder::new())
+            .with_color(*self.color)
+            .build(&mut ctx.scene.graph);
+        self.shadow = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Shadow")
+                .with_children(&[self.rectangle]),
+        )
+        .build(&mut ctx.scene.graph);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if ctx.scene.graph.is_valid_handle(self.shadow) {
+            ctx.scene.graph.remove_node(self.shadow);
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let sun = Self::sun_direction(&ctx.scene.graph);
+        let ground = Self::find_ground(&ctx.scene.graph, ctx.handle);
+        let character_z = ctx.scene.graph[ctx.handle].global_position().z;
+
+        let graph = &mut ctx.scene.graph;
+        if !graph.is_valid_handle(self.shadow) || !graph.is_valid_handle(self.rectangle) {
+            return;
+        }
+
+        let Some((ground_point, distance)) = ground else {
+            graph[self.shadow].set_visibility(false);
+            return;
+        };
+        graph[self.shadow].set_visibility(true);
+
+        let height = (distance - *self.foot_offset).max(0.0);
+
+        // Horizontal displacement of the shadow per meter of height, it is zero when the sun is in
+        // the zenith and grows when the sun goes down to the horizon.
+        let skew = if sun.y < -f32::EPSILON {
+            (sun.x / -sun.y).clamp(-3.0, 3.0)
+        } else {
+            0.0
+        };
+
+        let opacity_factor = (height / (*self.max_height).max(f32::EPSILON)).min(1.0);
+        let opacity = *self.max_opacity + (*self.min_opacity - *self.max_opacity) * opacity_factor;
+        let mut color = *self.color;
+        color.a = (color.a as f32 * opacity.clamp(0.0, 1.0)) as u8;
+        if let Some(rectangle) = graph[self.rectangle].cast_mut::<Rectangle>() {
+            rectangle.set_color(color);
+        }
+
+        // The upper edge of the shadow is shifted along the light direction relative to the lower
+        // one. Such a skew is split into a rotation of the rectangle, a non-uniform scale and a
+        // rotation of the pivot.
+        let width = *self.width * (1.0 + skew.abs() * 0.5) * (1.0 + height * 0.25);
+        let (pivot_rotation, scale, rectangle_rotation) = decompose(Matrix2::new(
+            width,
+            skew * SHADOW_THICKNESS,
+            0.0,
+            SHADOW_THICKNESS,
+        ));
+        graph[self.rectangle]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::z_axis(),
+                rectangle_rotation,
+            ));
+        graph[self.shadow]
+            .local_transform_mut()
+            .set_position(Vector3::new(
+                ground_point.x + skew * height,
+                ground_point.y + 0.05,
+                // Slightly behind the character.
+                character_z + 0.01,
+            ))
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::z_axis(),
+                pivot_rotation,
+            ))
+            .set_scale(Vector3::new(scale.x, scale.y, 1.0));
+    }
+}
+
+/// Splits a 2D linear transform with positive determinant into a rotation, a non-uniform scale
+/// and another rotation: `m = rotation(a) * scale * rotation(b)`. Returns `(a, scale, b)`, the
+/// angles are in radians.
+fn decompose(m: Matrix2<f32>) -> (f32, Vector2<f32>, f32) {
+    let svd = m.svd(true, true);
+    let (Some(mut u), Some(mut v_t)) = (svd.u, svd.v_t) else {
+        return (0.0, Vector2::new(m[(0, 0)], m[(1, 1)]), 0.0);
+    };
+    // Both factors are either rotations or reflections, the reflections cancel each other out.
+    if u.determinant() < 0.0 {
+        u.column_mut(0).neg_mut();
+        v_t.row_mut(0).neg_mut();
+    }
+    (
+        u[(1, 0)].atan2(u[(0, 0)]),
+        svd.singular_values,
+        v_t[(1, 0)].atan2(v_t[(0, 0)]),
+    )
+}
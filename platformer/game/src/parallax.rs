@@ -0,0 +1,160 @@
+//! Parallax scrolling for 2D backgrounds.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        log::Log,
+        math::Rect,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    material::{Material, MaterialResource},
+    resource::texture::TextureResource,
+    scene::{
+        base::BaseBuilder,
+        dim2::rectangle::{Rectangle, RectangleBuilder},
+        graph::Graph,
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct ParallaxLayer {
+    /// A rectangle that will be moved. It should be a child of the scene root, since the script
+    /// works with local positions of layers.
+    pub node: Handle<Node>,
+    /// Fraction of the target movement applied to the layer on each axis. 0.0 - the layer is
+    /// static, 1.0 - the layer moves together with the target.
+    pub scroll_factor: Vector2<f32>,
+}
+
+/// Moves background layers with different speeds, following a target (usually the player). If
+/// a layer is a rectangle with tiled texture (its UV rect is wider than 1.0), the layer is
+/// snapped back by one tile each time it falls behind the target horizontally, which makes the
+/// horizontal scrolling infinite. Layers are not tiled vertically, so they are never wrapped
+/// along the Y axis.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "0b7c3a9e-51d4-4e86-a2f0-6d9e8c1b7a25")]
+#[visit(optional)]
+pub struct ParallaxBackground {
+    pub target: InheritableVariable<Handle<Node>>,
+    pub layers: InheritableVariable<Vec<ParallaxLayer>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    last_target_position: Option<Vector2<f32>>,
+}
+
+/// Returns the width of a single tile of the layer, if the layer is tiled.
+fn tile_width(rectangle: &Rectangle) -> Option<f32> {
+    let uv_rect = rectangle.uv_rect();
+    let scale = rectangle.local_transform().scale();
+    (uv_rect.size.x > 1.0).then(|| scale.x.abs() / uv_rect.size.x)
+}
+
+/// Moves the value towards the target by whole tiles, until the distance is less than one tile.
+fn wrap(value: f32, target: f32, tile: f32) -> f32 {
+    if tile <= 0.0 {
+        return value;
+    }
+    value + ((target - value) / tile).trunc() * tile
+}
+
+impl ScriptTrait for ParallaxBackground {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let Some(target) = ctx.scene.graph.try_get(*self.target) else {
+            return;
+        };
+        let position = target.global_position().xy();
+
+        let displacement = self
+            .last_target_position
+            .map(|last| position - last)
+            .unwrap_or_default();
+        self.last_target_position = Some(position);
+
+        for layer in self.layers.iter() {
+            let Some(node) = ctx.scene.graph.try_get_mut(layer.node) else {
+                continue;
+            };
+
+            let tile_width = node.cast::<Rectangle>().and_then(tile_width);
+
+            let transform = node.local_transform_mut();
+            let mut layer_position = **transform.position();
+            layer_position.x += displacement.x * layer.scroll_factor.x;
+            layer_position.y += displacement.y * layer.scroll_factor.y;
+
+            if let Some(tile_width) = tile_width {
+                layer_position.x = wrap(layer_position.x, position.x, tile_width);
+            }
+
+            transform.set_position(layer_position);
+        }
+    }
+}
+
+/// Creates a set of tiled background layers around the target and a node with parallax script
+/// that moves them. Layers are sorted from the farthest to the closest one.
+pub fn build_parallax_background(
+    graph: &mut Graph,
+    target: Handle<Node>,
+    texture: TextureResource,
+) -> Handle<Node> {
+    let origin = graph
+        .try_get(target)
+        .map(|target| target.global_position())
+        .unwrap_or_default();
+
+    let mut layers = Vec::new();
+    for (i, (scroll_factor, color)) in [
+        (Vector2::new(0.9, 0.9), Color::opaque(90, 100, 140)),
+        (Vector2::new(0.7, 0.8), Color::opaque(140, 150, 190)),
+        (Vector2::new(0.4, 0.6), Color::opaque(200, 200, 220)),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut material = Material::standard_2d();
+        Log::verify(material.set_texture(&"diffuseTexture".into(), Some(texture.clone())));
+
+        // Tile the texture three times along X axis, so the layer covers the whole screen even
+        // after it was snapped back.
+        let repeat = 3.0;
+        let node = RectangleBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(origin.x, origin.y, 10.0 - i as f32))
+                    .with_local_scale(Vector3::new(20.0 * repeat, 12.0, 1.0))
+                    .build(),
+            ),
+        )
+        .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+        .with_color(color)
+        .with_uv_rect(Rect::new(0.0, 0.0, repeat, 1.0))
+        .build(graph);
+
+        layers.push(ParallaxLayer {
+            node,
+            scroll_factor,
+        });
+    }
+
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("ParallaxBackground")
+            .with_script(Script::new(ParallaxBackground {
+                target: target.into(),
+                layers: layers.into(),
+                last_target_position: None,
+            })),
+    )
+    .build(graph)
+}
@@ -0,0 +1,293 @@
+//! Physics materials of surfaces - friction, restitution and the sound of footsteps. Materials
+//! are stored in `data/physics_materials/*.physmat` RON files, one file per kind of surface, and
+//! colliders reference them with the `PhysicsMaterialRef` script.
+use crate::Game;
+use fyrox::{
+    core::{
+        io, log::Log, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    plugin::PluginContext,
+    rand::{thread_rng, Rng},
+    scene::{
+        base::BaseBuilder,
+        dim2::{collider::Collider, rigidbody::RigidBody},
+        graph::Graph,
+        node::Node,
+        rigidbody::RigidBodyType,
+        sound::{
+            DataSource, SoundBufferResource, SoundBufferResourceExtension, SoundBuilder, Status,
+        },
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const SAMPLE_RATE: usize = 44100;
+/// Width of the bands along X axis, that alternate between stone and metal on elevated platforms
+/// of a level without authored materials.
+const PLATFORM_BAND_WIDTH: f32 = 8.0;
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Reflect, Deserialize)]
+pub enum SurfaceKind {
+    #[default]
+    Dirt,
+    Metal,
+    Stone,
+}
+
+impl SurfaceKind {
+    pub const ALL: [SurfaceKind; 3] = [SurfaceKind::Dirt, SurfaceKind::Metal, SurfaceKind::Stone];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SurfaceKind::Dirt => "Dirt",
+            SurfaceKind::Metal => "Metal",
+            SurfaceKind::Stone => "Stone",
+        }
+    }
+
+    fn path(self) -> String {
+        format!(
+            "data/physics_materials/{}.physmat",
+            self.name().to_lowercase()
+        )
+    }
+
+    /// Generates a footstep sound - a thud for dirt, a click for stone and a ringing clank for
+    /// metal.
+    fn make_footstep(self) -> Option<SoundBufferResource> {
+        let mut rng = thread_rng();
+        let duration = match self {
+            SurfaceKind::Dirt => 0.12,
+            SurfaceKind::Metal => 0.4,
+            SurfaceKind::Stone => 0.07,
+        };
+        let sample_count = (SAMPLE_RATE as f32 * duration) as usize;
+        let mut noise = 0.0f32;
+        let samples = (0..sample_count)
+            .map(|i| {
+                let time = i as f32 / SAMPLE_RATE as f32;
+                let white = rng.gen_range(-1.0..1.0f32);
+                match self {
+                    SurfaceKind::Dirt => {
+                        // Low-passed noise.
+                        noise += (white - noise) * 0.08;
+                        noise * 3.0 * (-time * 35.0).exp()
+                    }
+                    SurfaceKind::Metal => {
+                        // Inharmonic partials ring longer than the initial hit.
+                        let ring = [523.0, 1187.0, 1760.0]
+                            .iter()
+                            .map(|frequency| (std::f32::consts::TAU * frequency * time).sin())
+                            .sum::<f32>()
+                            / 3.0;
+                        ring * (-time * 12.0).exp() + white * 0.3 * (-time * 120.0).exp()
+                    }
+                    SurfaceKind::Stone => white * (-time * 80.0).exp(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match SoundBufferResource::new_generic(DataSource::Raw {
+            sample_rate: SAMPLE_RATE,
+            channel_count: 1,
+            samples,
+        }) {
+            Ok(buffer) => Some(buffer),
+            Err(_) => {
+                Log::err(format!(
+                    "Unable to create {} footstep sound buffer!",
+                    self.name()
+                ));
+                None
+            }
+        }
+    }
+}
+
+#[derive(Visit, Reflect, Deserialize, Copy, Clone, Debug)]
+pub struct PhysicsMaterial {
+    pub friction: f32,
+    pub restitution: f32,
+}
+
+impl PhysicsMaterial {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        ron::de::from_bytes::<Self>(bytes).map_err(|err| err.to_string())
+    }
+
+    pub fn apply(&self, collider: &mut Collider) {
+        collider.set_friction(self.friction);
+        collider.set_restitution(self.restitution);
+    }
+}
+
+/// Physics materials and footstep sounds of every kind of surface.
+#[derive(Default, Debug)]
+pub struct PhysicsMaterialLibrary {
+    materials: HashMap<SurfaceKind, PhysicsMaterial>,
+    footsteps: HashMap<SurfaceKind, SoundBufferResource>,
+}
+
+impl PhysicsMaterialLibrary {
+    /// Generates footstep sounds and starts loading of the materials, they are added to the
+    /// library of the game once loaded.
+    pub fn load(ctx: &mut PluginContext) -> Self {
+        for kind in SurfaceKind::ALL {
+            ctx.task_pool.spawn_plugin_task(
+                io::load_file(kind.path()),
+                move |result, game: &mut Game, _ctx| {
+                    let material = result
+                        .map_err(|err| format!("{err:?}"))
+                        .and_then(|bytes| PhysicsMaterial::from_bytes(&bytes));
+                    match material {
+                        Ok(material) => {
+                            game.physics_materials.materials.insert(kind, material);
+                        }
+                        Err(err) => Log::err(format!(
+                            "Unable to load physics material {}. Reason: {err}",
+                            kind.path()
+                        )),
+                    }
+                },
+            );
+        }
+
+        Self {
+            materials: Default::default(),
+            footsteps: SurfaceKind::ALL
+                .into_iter()
+                .filter_map(|kind| Some((kind, kind.make_footstep()?)))
+                .collect(),
+        }
+    }
+
+    pub fn material(&self, kind: SurfaceKind) -> Option<&PhysicsMaterial> {
+        self.materials.get(&kind)
+    }
+
+    /// Plays the footstep sound of the surface at the given node.
+    pub fn play_footstep(&self, graph: &mut Graph, kind: SurfaceKind, node: Handle<Node>) {
+        let Some(buffer) = self.footsteps.get(&kind) else {
+            return;
+        };
+        let position = graph[node].global_position();
+        SoundBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_buffer(Some(buffer.clone()))
+        .with_gain(0.4)
+        // Footsteps are heard the same regardless of the camera position.
+        .with_spatial_blend_factor(0.0)
+        .with_status(Status::Playing)
+        .with_play_once(true)
+        .build(graph);
+    }
+}
+
+/// Defines the physics material of a collider. The script could be assigned either to a collider
+/// or to a rigid body, in the latter case the material is used by all colliders of the body.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "7d2e9b4f-3a61-4c85-9f0e-b6c1d8a3e527")]
+#[visit(optional)]
+pub struct PhysicsMaterialRef {
+    pub kind: InheritableVariable<SurfaceKind>,
+    /// Materials are loaded asynchronously, so they are applied to the colliders once loaded.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    applied: bool,
+}
+
+impl PhysicsMaterialRef {
+    pub fn new(kind: SurfaceKind) -> Self {
+        Self {
+            kind: kind.into(),
+            applied: false,
+        }
+    }
+}
+
+impl ScriptTrait for PhysicsMaterialRef {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.applied {
+            return;
+        }
+        let Some(material) = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .and_then(|game| game.physics_materials.material(*self.kind))
+            .copied()
+        else {
+            return;
+        };
+
+        let node = &ctx.scene.graph[ctx.handle];
+        let colliders = if node.cast::<Collider>().is_some() {
+            vec![ctx.handle]
+        } else {
+            node.children().to_vec()
+        };
+        for collider in colliders {
+            if let Some(collider) = ctx.scene.graph.try_get_mut_of_type::<Collider>(collider) {
+                material.apply(collider);
+            }
+        }
+        self.applied = true;
+    }
+}
+
+/// Returns the kind of the surface of the collider, if it or its rigid body has a material.
+pub fn surface_kind(graph: &Graph, collider: Handle<Node>) -> Option<SurfaceKind> {
+    let node = graph.try_get(collider)?;
+    [node.try_get_script::<PhysicsMaterialRef>()]
+        .into_iter()
+        .chain([graph
+            .try_get(node.parent())
+            .and_then(|parent| parent.try_get_script::<PhysicsMaterialRef>())])
+        .flatten()
+        .map(|material| *material.kind)
+        .next()
+}
+
+/// Assigns materials to the static colliders, that have no authored materials. The lowest
+/// colliders are the ground and are made of dirt, elevated platforms alternate between
+/// stone and metal.
+pub fn assign_default_materials(graph: &mut Graph) {
+    let colliders = graph
+        .pair_iter()
+        .filter_map(|(handle, node)| {
+            let collider = node.cast::<Collider>()?;
+            let body = graph.try_get_of_type::<RigidBody>(node.parent())?;
+            let has_material = surface_kind(graph, handle).is_some();
+            (!has_material && !collider.is_sensor() && body.body_type() == RigidBodyType::Static)
+                .then(|| (handle, collider.global_position()))
+        })
+        .collect::<Vec<_>>();
+
+    let Some(ground_level) = colliders
+        .iter()
+        .map(|(_, position)| position.y)
+        .min_by(|a, b| a.total_cmp(b))
+    else {
+        return;
+    };
+
+    for (handle, position) in colliders {
+        let kind = if position.y < ground_level + 0.5 {
+            SurfaceKind::Dirt
+        } else if ((position.x / PLATFORM_BAND_WIDTH).floor() as i32).rem_euclid(2) == 0 {
+            SurfaceKind::Stone
+        } else {
+            SurfaceKind::Metal
+        };
+        graph[handle].add_script(Script::new(PhysicsMaterialRef::new(kind)));
+    }
+}
@@ -0,0 +1,162 @@
+//! Pixel art mode - the scene is rendered into a small render target, which is shown on the whole
+//! screen without any filtering, so every pixel of the target becomes a large sharp square. The
+//! image is the lowest widget of the first user interface, so the rest of the interfaces (and the
+//! debug text) are drawn at the full resolution of the window on top of it.
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle},
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        image::ImageBuilder,
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    plugin::PluginContext,
+    resource::texture::{
+        TextureMagnificationFilter, TextureMinificationFilter, TextureResource,
+        TextureResourceExtension,
+    },
+    scene::Scene,
+};
+
+/// Resolution of the render target in pixel art mode.
+const PIXEL_ART_RESOLUTION: Vector2<u32> = Vector2::new(320, 180);
+
+#[derive(Default, Debug)]
+pub struct PixelArtMode {
+    enabled: bool,
+    render_target: Option<TextureResource>,
+    /// The image with the render target lives in the first user interface, that is drawn before
+    /// all the others.
+    image: Handle<UiNode>,
+    check_box: Handle<UiNode>,
+    size: Vector2<f32>,
+}
+
+impl PixelArtMode {
+    /// Adds the image with the scene to the first user interface, behind all of its widgets. Must
+    /// be called again, if the first user interface is replaced.
+    pub fn init(&mut self, ui: &mut UserInterface) {
+        let render_target =
+            TextureResource::new_render_target(PIXEL_ART_RESOLUTION.x, PIXEL_ART_RESOLUTION.y);
+        {
+            let mut data = render_target.data_ref();
+            data.set_magnification_filter(TextureMagnificationFilter::Nearest);
+            data.set_minification_filter(TextureMinificationFilter::Nearest);
+        }
+
+        self.image = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_visibility(self.enabled)
+                .with_width(self.size.x)
+                .with_height(self.size.y),
+        )
+        .with_texture(render_target.clone().into())
+        // Render targets are stored upside down.
+        .with_flip(true)
+        .build(&mut ui.build_ctx());
+        // Children are drawn in order, so the first child of the root is under everything else.
+        ui.send_message(WidgetMessage::lowest(
+            self.image,
+            MessageDirection::ToWidget,
+        ));
+
+        self.render_target = Some(render_target);
+    }
+
+    pub fn image(&self) -> Handle<UiNode> {
+        self.image
+    }
+
+    pub fn build_check_box(&mut self, ctx: &mut BuildContext) {
+        self.check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness {
+                    left: 5.0,
+                    top: 30.0,
+                    right: 5.0,
+                    bottom: 5.0,
+                }),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Pixel Art Mode")
+                .build(ctx),
+        )
+        .checked(Some(self.enabled))
+        .build(ctx);
+    }
+
+    /// The image always covers the whole window.
+    pub fn resize(&mut self, context: &mut PluginContext, new_size: Vector2<f32>) {
+        self.size = new_size;
+        if self.image.is_some() {
+            let ui = context.user_interfaces.first();
+            ui.send_message(WidgetMessage::width(
+                self.image,
+                MessageDirection::ToWidget,
+                new_size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.image,
+                MessageDirection::ToWidget,
+                new_size.y,
+            ));
+        }
+    }
+
+    /// Redirects the rendering of the scene to the low resolution target, or back to the window.
+    pub fn set_enabled(
+        &mut self,
+        enabled: bool,
+        context: &mut PluginContext,
+        scene: Handle<Scene>,
+    ) {
+        self.enabled = enabled;
+        if let Some(scene) = context.scenes.try_get_mut(scene) {
+            scene.rendering_options.render_target = if enabled {
+                self.render_target.clone()
+            } else {
+                None
+            };
+        }
+        if self.image.is_some() {
+            context
+                .user_interfaces
+                .first()
+                .send_message(WidgetMessage::visibility(
+                    self.image,
+                    MessageDirection::ToWidget,
+                    enabled,
+                ));
+        }
+    }
+
+    /// Returns the resolution, that the scene is rendered at.
+    pub fn effective_resolution(&self, frame_size: (u32, u32)) -> Vector2<u32> {
+        if self.enabled {
+            PIXEL_ART_RESOLUTION
+        } else {
+            Vector2::new(frame_size.0, frame_size.1)
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        context: &mut PluginContext,
+        scene: Handle<Scene>,
+    ) {
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.check_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.set_enabled(*value, context, scene);
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Client-side prediction on a simulated network. The physics of the player plays the role of the
+//! authoritative server: it gets the input of the player half of the round-trip time later, and
+//! its state gets back to the client after another half. The client moves the sprite of the player
+//! with the input right away and reconciles the predicted position with every state, that arrives
+//! from the server.
+use crate::JUMP_SPEED;
+use fyrox::{
+    core::{algebra::Vector2, pool::Handle},
+    gui::{
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+    },
+};
+use std::collections::VecDeque;
+
+const MAX_RTT_MS: f32 = 500.0;
+const DEFAULT_CORRECTION_ALPHA: f32 = 0.1;
+/// Predicted positions are kept a bit longer than the round-trip time, so a late state still
+/// finds the position, that it should be compared with.
+const HISTORY_MARGIN: f32 = 0.5;
+
+/// Input of the player, that is sent to the server.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PlayerInput {
+    pub x_speed: f32,
+    pub jump: bool,
+}
+
+#[derive(Clone, Debug)]
+struct InputPacket {
+    input: PlayerInput,
+    /// Time, when the packet reaches the server.
+    arrival: f32,
+}
+
+#[derive(Clone, Debug)]
+struct StatePacket {
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    /// Time, when the packet reaches the client.
+    arrival: f32,
+}
+
+/// Client-side prediction of the position of the player. It is disabled, when the round-trip time
+/// is zero.
+#[derive(Clone, Debug, Default)]
+pub struct PredictiveInput {
+    /// Local time of the simulated network, in seconds.
+    time: f32,
+    inputs: VecDeque<InputPacket>,
+    states: VecDeque<StatePacket>,
+    /// The last input, that has reached the server.
+    server_input: PlayerInput,
+    predicted: Option<Vector2<f32>>,
+    /// Vertical velocity from the last state. The client does not simulate the collisions with the
+    /// ground, so it moves vertically with the velocity of the server, unless it jumps.
+    vertical_velocity: f32,
+    /// Predicted positions of the last frames with their times.
+    history: VecDeque<(f32, Vector2<f32>)>,
+    /// Magnitude of the last prediction error, in meters.
+    error: f32,
+}
+
+impl PredictiveInput {
+    /// Returns the predicted position of the player, if the prediction is enabled.
+    pub fn predicted(&self) -> Option<Vector2<f32>> {
+        self.predicted
+    }
+
+    pub fn error(&self) -> f32 {
+        self.error
+    }
+
+    /// Drops everything in flight, the input goes directly to the physics again.
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Sends the input to the server and returns the input, that the server applies in this frame.
+    pub fn send_input(&mut self, input: PlayerInput, rtt: f32, dt: f32) -> PlayerInput {
+        self.time += dt;
+        self.inputs.push_back(InputPacket {
+            input,
+            arrival: self.time + rtt * 0.5,
+        });
+        while let Some(packet) = self.inputs.front() {
+            if packet.arrival > self.time {
+                break;
+            }
+            self.server_input = packet.input;
+            self.inputs.pop_front();
+        }
+        self.server_input
+    }
+
+    /// Sends the state of the server to the client, moves the predicted position with the input
+    /// and reconciles it with the states, that have reached the client. Returns the predicted
+    /// position.
+    pub fn predict(
+        &mut self,
+        input: PlayerInput,
+        server_position: Vector2<f32>,
+        server_velocity: Vector2<f32>,
+        rtt: f32,
+        correction_alpha: f32,
+        dt: f32,
+    ) -> Vector2<f32> {
+        self.states.push_back(StatePacket {
+            position: server_position,
+            velocity: server_velocity,
+            arrival: self.time + rtt * 0.5,
+        });
+
+        let mut predicted = self.predicted.unwrap_or(server_position);
+        let vertical_velocity = if input.jump {
+            JUMP_SPEED
+        } else {
+            self.vertical_velocity
+        };
+        predicted += Vector2::new(input.x_speed, vertical_velocity).scale(dt);
+
+        while let Some(state) = self.states.front() {
+            if state.arrival > self.time {
+                break;
+            }
+            let state = self.states.pop_front().unwrap();
+            self.vertical_velocity = state.velocity.y;
+
+            // The state includes the inputs, that were sent one round trip before it arrived, so
+            // it is compared with the prediction at that time.
+            let sent = state.arrival - rtt;
+            let Some(past) = self
+                .history
+                .iter()
+                .rev()
+                .find(|(time, _)| *time <= sent)
+                .map(|(_, position)| *position)
+            else {
+                continue;
+            };
+            let error = state.position - past;
+            self.error = error.norm();
+
+            // Exponential smoothing - lerp(predicted, authoritative, correction_alpha), where the
+            // authoritative position is moved forward by the inputs, that the server has not got
+            // yet. The history is corrected too, so the next states do not apply the same error
+            // again.
+            let correction = error.scale(correction_alpha);
+            predicted += correction;
+            for (_, position) in self.history.iter_mut() {
+                *position += correction;
+            }
+        }
+
+        self.history.push_back((self.time, predicted));
+        while self
+            .history
+            .front()
+            .is_some_and(|(time, _)| *time < self.time - rtt - HISTORY_MARGIN)
+        {
+            self.history.pop_front();
+        }
+
+        self.predicted = Some(predicted);
+        predicted
+    }
+}
+
+/// Debug panel with the settings of the simulated network.
+#[derive(Debug)]
+pub struct LatencyPanel {
+    rtt_slider: Handle<UiNode>,
+    correction_slider: Handle<UiNode>,
+    /// Simulated round-trip time, in milliseconds.
+    pub rtt_ms: f32,
+    /// Fraction of the prediction error, that is corrected on every state from the server.
+    pub correction_alpha: f32,
+}
+
+impl Default for LatencyPanel {
+    fn default() -> Self {
+        Self {
+            rtt_slider: Handle::NONE,
+            correction_slider: Handle::NONE,
+            rtt_ms: 0.0,
+            correction_alpha: DEFAULT_CORRECTION_ALPHA,
+        }
+    }
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .with_height(22.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(if step < 1.0 { 2 } else { 0 })
+    .show_value(true)
+    .build(ctx)
+}
+
+impl LatencyPanel {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let mut panel = Self::default();
+        panel.rtt_slider = make_slider(ctx, 0.0, MAX_RTT_MS, 10.0, panel.rtt_ms);
+        panel.correction_slider = make_slider(ctx, 0.01, 1.0, 0.01, panel.correction_alpha);
+        StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness {
+                    left: 5.0,
+                    top: 55.0,
+                    right: 5.0,
+                    bottom: 5.0,
+                })
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Simulated RTT (ms)")
+                        .build(ctx),
+                )
+                .with_child(panel.rtt_slider)
+                .with_child(
+                    TextBuilder::new(WidgetBuilder::new())
+                        .with_text("Prediction Correction")
+                        .build(ctx),
+                )
+                .with_child(panel.correction_slider),
+        )
+        .build(ctx);
+        panel
+    }
+
+    /// Round-trip time in seconds.
+    pub fn rtt(&self) -> f32 {
+        self.rtt_ms / 1000.0
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.rtt_slider {
+                self.rtt_ms = *value;
+            } else if message.destination() == self.correction_slider {
+                self.correction_alpha = *value;
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Ghost replay - the best run is recorded to a file, and it is played back by a translucent copy
+//! of the player in the next runs, so the player can race against their previous best.
+use crate::Player;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::MaterialResource,
+    scene::{
+        animation::spritesheet::SpriteSheetAnimation,
+        camera::Camera,
+        dim2::{collider::Collider, rectangle::Rectangle, rigidbody::RigidBody},
+        node::Node,
+        rigidbody::RigidBodyType,
+        Scene,
+    },
+};
+use std::path::Path;
+
+const REPLAY_PATH: &str = "data/replay.bin";
+/// Size of a single frame in the replay file.
+const FRAME_SIZE: usize = 20;
+const GHOST_ALPHA: u8 = 102;
+
+/// State of the player at a single frame of a run.
+#[derive(Copy, Clone, Debug, Default)]
+struct ReplayFrame {
+    position: Vector2<f32>,
+    animation: u32,
+    animation_frame: u32,
+    /// Sign of the horizontal scale of the sprite, it tells where the player looks at.
+    facing: f32,
+}
+
+impl ReplayFrame {
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.position.x.to_le_bytes());
+        bytes.extend_from_slice(&self.position.y.to_le_bytes());
+        bytes.extend_from_slice(&self.animation.to_le_bytes());
+        bytes.extend_from_slice(&self.animation_frame.to_le_bytes());
+        bytes.extend_from_slice(&self.facing.to_le_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Self {
+        let field = |i: usize| -> [u8; 4] { bytes[i * 4..i * 4 + 4].try_into().unwrap() };
+        Self {
+            position: Vector2::new(f32::from_le_bytes(field(0)), f32::from_le_bytes(field(1))),
+            animation: u32::from_le_bytes(field(2)),
+            animation_frame: u32::from_le_bytes(field(3)),
+            facing: f32::from_le_bytes(field(4)),
+        }
+    }
+}
+
+fn load_replay(path: &Path) -> Option<Vec<ReplayFrame>> {
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.len() % FRAME_SIZE != 0 {
+        Log::err(format!("Replay file {} is corrupted!", path.display()));
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(FRAME_SIZE)
+            .map(ReplayFrame::read)
+            .collect(),
+    )
+}
+
+fn save_replay(path: &Path, frames: &[ReplayFrame]) {
+    let mut bytes = Vec::with_capacity(frames.len() * FRAME_SIZE);
+    for frame in frames {
+        frame.write(&mut bytes);
+    }
+    if let Err(err) = std::fs::write(path, bytes) {
+        Log::err(format!(
+            "Unable to save replay to {}. Reason: {err}",
+            path.display()
+        ));
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct GhostReplay {
+    /// Frames of the current run.
+    recording: Vec<ReplayFrame>,
+    recording_active: bool,
+    /// Frames of the best run, that are played back by the ghost.
+    best_run: Vec<ReplayFrame>,
+    frame_index: usize,
+    ghost: Handle<Node>,
+    ghost_sprite: Handle<Node>,
+    ghost_animations: Vec<SpriteSheetAnimation>,
+    ghost_visible: bool,
+    check_box: Handle<UiNode>,
+}
+
+impl GhostReplay {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness {
+                    left: 5.0,
+                    top: 30.0,
+                    right: 5.0,
+                    bottom: 5.0,
+                }),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Show Ghost")
+                .build(ctx),
+        )
+        .checked(Some(true))
+        .build(ctx);
+
+        Self {
+            ghost_visible: true,
+            check_box,
+            ..Default::default()
+        }
+    }
+
+    /// Starts recording of a new run, and spawns the ghost if there's a recorded run.
+    pub fn start_run(&mut self, scene: &mut Scene, ui: &UserInterface) {
+        self.recording.clear();
+        self.recording_active = true;
+        self.frame_index = 0;
+        self.best_run = load_replay(Path::new(REPLAY_PATH)).unwrap_or_default();
+
+        if scene.graph.is_valid_handle(self.ghost) {
+            scene.graph.remove_node(self.ghost);
+        }
+        self.ghost = Handle::NONE;
+
+        if !self.best_run.is_empty() {
+            self.spawn_ghost(scene);
+        }
+
+        ui.send_message(WidgetMessage::visibility(
+            self.check_box,
+            MessageDirection::ToWidget,
+            self.ghost.is_some(),
+        ));
+    }
+
+    /// Stops recording and saves the run, if it is better than the previous one.
+    pub fn finish_run(&mut self) {
+        if !std::mem::take(&mut self.recording_active) {
+            return;
+        }
+
+        if self.best_run.is_empty() || self.recording.len() < self.best_run.len() {
+            save_replay(Path::new(REPLAY_PATH), &self.recording);
+            Log::info(format!(
+                "New best run: {:.2} s",
+                self.recording.len() as f32 / 60.0
+            ));
+        }
+    }
+
+    fn spawn_ghost(&mut self, scene: &mut Scene) {
+        let Some((player_handle, player)) = scene
+            .graph
+            .pair_iter()
+            .find_map(|(handle, node)| node.try_get_script::<Player>().map(|p| (handle, p)))
+        else {
+            return;
+        };
+        self.ghost_animations = player.animations.clone();
+
+        let (ghost, _) = scene
+            .graph
+            .copy_node_inplace(player_handle, &mut |_, _| true);
+        self.ghost = ghost;
+
+        let ghost_node = &mut scene.graph[ghost];
+        // The ghost must not react on input, it is driven by the replay.
+        ghost_node.remove_all_scripts();
+        ghost_node.set_name("Ghost");
+        if let Some(body) = ghost_node.cast_mut::<RigidBody>() {
+            body.set_body_type(RigidBodyType::KinematicPositionBased);
+        }
+
+        let descendants = scene.graph.traverse_handle_iter(ghost).collect::<Vec<_>>();
+        for handle in descendants {
+            let node = &mut scene.graph[handle];
+            if let Some(collider) = node.cast_mut::<Collider>() {
+                // Sensors don't push other bodies around.
+                collider.set_is_sensor(true);
+            } else if let Some(rectangle) = node.cast_mut::<Rectangle>() {
+                // The material is shared with the player, the ghost needs its own one to show
+                // its own animation frames.
+                let material = rectangle.material().data_ref().clone();
+                rectangle.set_material(MaterialResource::new_ok(ResourceKind::Embedded, material));
+                rectangle.set_color(Color::from_rgba(255, 255, 255, GHOST_ALPHA));
+                self.ghost_sprite = handle;
+            } else if let Some(camera) = node.cast_mut::<Camera>() {
+                // The camera could be attached to the player, there must be only one though.
+                camera.set_enabled(false);
+            }
+        }
+
+        scene.graph[ghost].set_visibility(self.ghost_visible);
+    }
+
+    fn record(&mut self, scene: &Scene) {
+        let Some((node, player)) = scene
+            .graph
+            .linear_iter()
+            .find_map(|node| node.try_get_script::<Player>().map(|p| (node, p)))
+        else {
+            return;
+        };
+
+        let position = node.global_position();
+        let animation = player.current_animation;
+        let facing = scene
+            .graph
+            .try_get(player.sprite)
+            .map_or(1.0, |sprite| sprite.local_transform().scale().x.signum());
+        self.recording.push(ReplayFrame {
+            position: Vector2::new(position.x, position.y),
+            animation,
+            animation_frame: player
+                .animations
+                .get(animation as usize)
+                .map_or(0, |animation| animation.current_frame() as u32),
+            facing,
+        });
+    }
+
+    fn play(&mut self, scene: &mut Scene, ui: &UserInterface) {
+        if self.ghost.is_none() {
+            return;
+        }
+
+        let Some(frame) = self.best_run.get(self.frame_index).cloned() else {
+            // The recording is over, the ghost has finished its run.
+            scene.graph.remove_node(self.ghost);
+            self.ghost = Handle::NONE;
+            ui.send_message(WidgetMessage::visibility(
+                self.check_box,
+                MessageDirection::ToWidget,
+                false,
+            ));
+            return;
+        };
+
+        if let Some(ghost) = scene.graph.try_get_mut(self.ghost) {
+            let z = ghost.local_transform().position().z;
+            ghost.local_transform_mut().set_position(Vector3::new(
+                frame.position.x,
+                frame.position.y,
+                z,
+            ));
+        }
+
+        let Some(sprite) = scene
+            .graph
+            .try_get_mut(self.ghost_sprite)
+            .and_then(|node| node.cast_mut::<Rectangle>())
+        else {
+            return;
+        };
+
+        let scale = **sprite.local_transform().scale();
+        sprite.local_transform_mut().set_scale(Vector3::new(
+            scale.x.abs() * frame.facing,
+            scale.y,
+            scale.z,
+        ));
+
+        if let Some(animation) = self.ghost_animations.get_mut(frame.animation as usize) {
+            animation.set_current_frame(frame.animation_frame as usize);
+            Log::verify(
+                sprite
+                    .material()
+                    .data_ref()
+                    .set_texture(&"diffuseTexture".into(), animation.texture()),
+            );
+            sprite.set_uv_rect(animation.current_frame_uv_rect().unwrap_or_default());
+        }
+    }
+
+    /// Records the current frame of the run and moves the ghost to the next frame of the best run.
+    pub fn update(&mut self, scene: &mut Scene, ui: &UserInterface) {
+        if !self.recording_active {
+            return;
+        }
+
+        self.record(scene);
+        self.play(scene, ui);
+        self.frame_index += 1;
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, scene: Option<&mut Scene>) {
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.check_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.ghost_visible = *value;
+                if let Some(ghost) = scene.and_then(|scene| scene.graph.try_get_mut(self.ghost)) {
+                    ghost.set_visibility(*value);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Sprite batching - rectangles with the same texture are merged into a single mesh, so they are
+//! drawn with one draw call instead of one call per rectangle.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Point3, Vector3},
+        color::Color,
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    material::{MaterialResource, PropertyValue},
+    renderer::Renderer,
+    resource::texture::TextureResource,
+    scene::{
+        base::BaseBuilder,
+        dim2::rectangle::Rectangle,
+        graph::Graph,
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexTrait,
+            },
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder, RenderPath,
+        },
+        node::Node,
+        Scene,
+    },
+};
+use std::collections::{HashMap, HashSet};
+
+/// Smoothing factor of the running averages of the frame time.
+const FRAME_TIME_SMOOTHING: f32 = 0.05;
+
+/// Same layout as the one the renderer uses for rectangles, so the batches can be drawn with
+/// the materials of the rectangles.
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+struct SpriteVertex {
+    position: Vector3<f32>,
+    tex_coord: [f32; 2],
+    color: Color,
+}
+
+impl VertexTrait for SpriteVertex {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Position,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 0,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord0,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 0,
+                shader_location: 1,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 0,
+                shader_location: 2,
+                normalized: true,
+            },
+        ]
+    }
+}
+
+/// Rectangles, that share the same texture.
+#[derive(Default)]
+struct Group {
+    material: Option<MaterialResource>,
+    rectangles: Vec<Handle<Node>>,
+}
+
+fn diffuse_texture(material: &MaterialResource) -> Option<TextureResource> {
+    if !material.is_ok() {
+        return None;
+    }
+    match material.data_ref().property_ref(&"diffuseTexture".into())? {
+        PropertyValue::Sampler { value, .. } => value.clone(),
+        _ => None,
+    }
+}
+
+/// Builds vertices and triangles of all the rectangles in world space.
+fn build_geometry(
+    graph: &Graph,
+    rectangles: &[Handle<Node>],
+) -> (Vec<SpriteVertex>, Vec<TriangleDefinition>) {
+    let mut vertices = Vec::with_capacity(rectangles.len() * 4);
+    let mut triangles = Vec::with_capacity(rectangles.len() * 2);
+    for rectangle in rectangles {
+        let Some(rectangle) = graph.try_get_of_type::<Rectangle>(*rectangle) else {
+            continue;
+        };
+        let transform = rectangle.global_transform();
+        let uv = rectangle.uv_rect();
+        let color = rectangle.color();
+        // X axis points to the left, so the left side of the quad has the right side of the
+        // texture.
+        let corners = [
+            ([-0.5, 0.5], [uv.position.x + uv.size.x, uv.position.y]),
+            ([0.5, 0.5], [uv.position.x, uv.position.y]),
+            ([0.5, -0.5], [uv.position.x, uv.position.y + uv.size.y]),
+            (
+                [-0.5, -0.5],
+                [uv.position.x + uv.size.x, uv.position.y + uv.size.y],
+            ),
+        ];
+
+        let first = vertices.len() as u32;
+        vertices.extend(corners.map(|([x, y], tex_coord)| SpriteVertex {
+            position: transform.transform_point(&Point3::new(x, y, 0.0)).coords,
+            tex_coord,
+            color,
+        }));
+        triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        triangles.push(TriangleDefinition([first + 2, first + 3, first]));
+    }
+    (vertices, triangles)
+}
+
+/// Replaces the geometry of a batch. The buffers are modified in place, so the renderer updates
+/// the existing GPU buffers instead of creating new ones.
+fn set_geometry(
+    data: &mut SurfaceData,
+    vertices: &[SpriteVertex],
+    triangles: Vec<TriangleDefinition>,
+) {
+    let mut vertex_buffer = data.vertex_buffer.modify();
+    vertex_buffer.clear();
+    Log::verify(vertex_buffer.push_vertices(vertices));
+    drop(vertex_buffer);
+    data.geometry_buffer.set_triangles(triangles);
+}
+
+/// Merges rectangles with the same texture into batches - meshes in world space, which are
+/// rebuilt every frame. Batched rectangles are hidden, so the scene is still drawn once. Sprites
+/// of the level have their own copies of the material, so the rectangles are grouped by their
+/// textures. The batcher remembers the visibility of the rectangles, that it hides, and gives it
+/// back when they leave a batch.
+#[derive(Debug)]
+pub struct SpriteBatcher {
+    /// Minimal amount of rectangles with the same texture, that are merged into a batch.
+    merge_threshold: usize,
+    enabled: bool,
+    batches: HashMap<TextureResource, Handle<Node>>,
+    /// Rectangles, that are hidden, because they are drawn as a part of a batch, with their own
+    /// visibility before they were hidden.
+    hidden: HashMap<Handle<Node>, bool>,
+    /// Average draw calls and frame time (in seconds) with batching (`[1]`) and without it
+    /// (`[0]`).
+    draw_calls: [Option<usize>; 2],
+    frame_time: [Option<f32>; 2],
+}
+
+impl Default for SpriteBatcher {
+    fn default() -> Self {
+        Self {
+            merge_threshold: 4,
+            enabled: true,
+            batches: Default::default(),
+            hidden: Default::default(),
+            draw_calls: Default::default(),
+            frame_time: Default::default(),
+        }
+    }
+}
+
+impl SpriteBatcher {
+    pub fn toggle(&mut self, scene: &mut Scene) {
+        self.enabled = !self.enabled;
+        if !self.enabled {
+            self.clear(&mut scene.graph);
+        }
+    }
+
+    /// Removes all the batches and gives the rectangles their visibility back.
+    fn clear(&mut self, graph: &mut Graph) {
+        for (_, batch) in self.batches.drain() {
+            if graph.is_valid_handle(batch) {
+                graph.remove_node(batch);
+            }
+        }
+        for (rectangle, visibility) in self.hidden.drain() {
+            if let Some(rectangle) = graph.try_get_mut(rectangle) {
+                rectangle.set_visibility(visibility);
+            }
+        }
+    }
+
+    /// Returns `true` if the rectangle would be visible without batching.
+    fn is_visible(&self, graph: &Graph, handle: Handle<Node>, node: &Node) -> bool {
+        match self.hidden.get(&handle) {
+            Some(visibility) => {
+                *visibility
+                    && graph
+                        .try_get(node.parent())
+                        .map_or(true, |parent| parent.global_visibility())
+            }
+            None => node.global_visibility(),
+        }
+    }
+
+    fn collect_groups(&self, graph: &Graph) -> HashMap<TextureResource, Group> {
+        let mut groups = HashMap::<TextureResource, Group>::new();
+        for (handle, node) in graph.pair_iter() {
+            let Some(rectangle) = node.cast::<Rectangle>() else {
+                continue;
+            };
+            // Invisible rectangles must stay invisible.
+            if !self.is_visible(graph, handle, node) {
+                continue;
+            }
+            // A batch is drawn as a whole, it can't be sorted by depth with other sprites.
+            if rectangle.global_position().z.abs() > f32::EPSILON {
+                continue;
+            }
+            let material = rectangle.material();
+            let Some(texture) = diffuse_texture(material) else {
+                continue;
+            };
+            let group = groups.entry(texture).or_default();
+            group.material.get_or_insert_with(|| material.clone());
+            group.rectangles.push(handle);
+        }
+        groups.retain(|_, group| group.rectangles.len() >= self.merge_threshold);
+        groups
+    }
+
+    fn update_batches(&mut self, graph: &mut Graph) {
+        let groups = self.collect_groups(graph);
+
+        // Batches of the groups, that are gone or became too small.
+        self.batches.retain(|texture, batch| {
+            let keep = groups.contains_key(texture);
+            if !keep && graph.is_valid_handle(*batch) {
+                graph.remove_node(*batch);
+            }
+            keep
+        });
+
+        let mut batched = HashSet::new();
+        for (texture, group) in groups {
+            let (vertices, triangles) = build_geometry(graph, &group.rectangles);
+            batched.extend(group.rectangles);
+
+            match self
+                .batches
+                .get(&texture)
+                .and_then(|batch| graph.try_get_mut_of_type::<Mesh>(*batch))
+            {
+                Some(mesh) => {
+                    if let Some(surface) = mesh.surfaces().first() {
+                        set_geometry(&mut surface.data().data_ref(), &vertices, triangles);
+                    }
+                }
+                None => {
+                    let data = SurfaceData::new(
+                        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+                        TriangleBuffer::new(triangles),
+                    );
+                    let Some(material) = group.material else {
+                        continue;
+                    };
+                    let batch = MeshBuilder::new(
+                        // The vertices move every frame, so the bounds of the mesh are unknown.
+                        BaseBuilder::new()
+                            .with_name("Sprite Batch")
+                            .with_frustum_culling(false),
+                    )
+                    .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                        ResourceKind::Embedded,
+                        data,
+                    ))
+                    .with_material(material)
+                    .build()])
+                    // 2D materials have no deferred pass.
+                    .with_render_path(RenderPath::Forward)
+                    .build(graph);
+                    self.batches.insert(texture, batch);
+                }
+            }
+        }
+
+        self.hidden.retain(|rectangle, visibility| {
+            let keep = batched.contains(rectangle);
+            if !keep {
+                if let Some(rectangle) = graph.try_get_mut(*rectangle) {
+                    rectangle.set_visibility(*visibility);
+                }
+            }
+            keep
+        });
+        for rectangle in batched {
+            let node = &mut graph[rectangle];
+            let visibility = node.visibility();
+            self.hidden.entry(rectangle).or_insert(visibility);
+            node.set_visibility(false);
+        }
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, renderer: &Renderer) {
+        let statistics = renderer.get_statistics();
+        let index = self.enabled as usize;
+        self.draw_calls[index] = Some(statistics.geometry.draw_calls);
+        let frame_time = self.frame_time[index].get_or_insert(statistics.pure_frame_time);
+        *frame_time += (statistics.pure_frame_time - *frame_time) * FRAME_TIME_SMOOTHING;
+
+        if self.enabled {
+            self.update_batches(&mut scene.graph);
+        }
+    }
+
+    pub fn stats_text(&self) -> String {
+        let draw_calls = |index: usize| {
+            self.draw_calls[index].map_or("-".to_string(), |draw_calls| draw_calls.to_string())
+        };
+        let frame_time = |index: usize| {
+            self.frame_time[index].map_or("-".to_string(), |frame_time| {
+                format!("{:.2} ms", frame_time * 1000.0)
+            })
+        };
+        format!(
+            "[B] - Sprite Batching: {} ({} batches, {} sprites)\n\
+            Draw Calls: {} batched / {} not batched\n\
+            Frame Time: {} batched / {} not batched",
+            if self.enabled { "On" } else { "Off" },
+            self.batches.len(),
+            self.hidden.len(),
+            draw_calls(1),
+            draw_calls(0),
+            frame_time(1),
+            frame_time(0)
+        )
+    }
+}
@@ -0,0 +1,168 @@
+//! Tile map, that is painted in the editor with the tile painter of the editor package. The tiles
+//! are ordinary rectangle nodes, that are saved with the scene, so the script does nothing at run
+//! time - it only keeps the list of the placed tiles for the editor.
+use fyrox::{
+    asset::{manager::ResourceManager, untyped::ResourceKind},
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        math::Rect,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    material::{Material, MaterialResource},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder, dim2::rectangle::RectangleBuilder, graph::Graph, node::Node,
+        transform::TransformBuilder,
+    },
+    script::ScriptTrait,
+};
+
+/// Sprite sheet with the tiles.
+pub const TILE_SHEET_PATH: &str = "data/tiles.png";
+/// Amount of tiles in a row of the sprite sheet.
+pub const SHEET_COLUMNS: u32 = 6;
+/// Amount of rows of tiles in the sprite sheet.
+pub const SHEET_ROWS: u32 = 3;
+
+/// A single tile placed on the map.
+#[derive(Visit, Reflect, Default, Debug, Clone, PartialEq)]
+pub struct TileInstance {
+    /// Position of the tile on the grid, in cells.
+    pub position: Vector2<i32>,
+    /// Index of the tile in the sprite sheet, row by row starting from the top-left one.
+    pub tile: u32,
+}
+
+/// Returns the texture coordinates of a tile in the sprite sheet.
+pub fn tile_uv_rect(tile: u32) -> Rect<f32> {
+    let size = Vector2::new(1.0 / SHEET_COLUMNS as f32, 1.0 / SHEET_ROWS as f32);
+    Rect::new(
+        (tile % SHEET_COLUMNS) as f32 * size.x,
+        (tile / SHEET_COLUMNS) as f32 * size.y,
+        size.x,
+        size.y,
+    )
+}
+
+/// Creates a material, that is shared by all the tiles.
+pub fn tile_material(resource_manager: &ResourceManager) -> MaterialResource {
+    let mut material = Material::standard_2d();
+    Log::verify(material.set_texture(
+        &"diffuseTexture".into(),
+        Some(resource_manager.request::<Texture>(TILE_SHEET_PATH)),
+    ));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Tiles from the `data/tiles.png` sprite sheet, placed on a grid. The tiles are created as
+/// children of the node with this script, so the node must not have any other children.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "58a294a9-c6e0-4d3d-b315-d0532dbdc8cb")]
+#[visit(optional)]
+pub struct TileMap {
+    /// Size of a grid cell, in world units.
+    cell_size: InheritableVariable<f32>,
+    tiles: InheritableVariable<Vec<TileInstance>>,
+}
+
+impl Default for TileMap {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0.into(),
+            tiles: Default::default(),
+        }
+    }
+}
+
+impl TileMap {
+    pub fn tiles(&self) -> &[TileInstance] {
+        &self.tiles
+    }
+
+    /// Returns a grid cell, that contains a point in the local space of the tile map.
+    pub fn cell_at(&self, point: Vector3<f32>) -> Vector2<i32> {
+        Vector2::new(
+            (point.x / *self.cell_size).floor() as i32,
+            (point.y / *self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Returns the tiles with a rectangle between two cells (inclusive) filled with a tile.
+    pub fn filled(&self, a: Vector2<i32>, b: Vector2<i32>, tile: u32) -> Vec<TileInstance> {
+        let min = a.inf(&b);
+        let max = a.sup(&b);
+
+        let mut tiles = (*self.tiles).clone();
+        tiles.retain(|instance| {
+            !(min.x..=max.x).contains(&instance.position.x)
+                || !(min.y..=max.y).contains(&instance.position.y)
+        });
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                tiles.push(TileInstance {
+                    position: Vector2::new(x, y),
+                    tile,
+                });
+            }
+        }
+        tiles
+    }
+
+    fn spawn_tile(
+        &self,
+        graph: &mut Graph,
+        instance: &TileInstance,
+        material: &MaterialResource,
+    ) -> Handle<Node> {
+        let cell_size = *self.cell_size;
+        RectangleBuilder::new(
+            BaseBuilder::new().with_name("Tile").with_local_transform(
+                TransformBuilder::new()
+                    // Rectangles are centered at their origin.
+                    .with_local_position(Vector3::new(
+                        (instance.position.x as f32 + 0.5) * cell_size,
+                        (instance.position.y as f32 + 0.5) * cell_size,
+                        0.0,
+                    ))
+                    .with_local_scale(Vector3::new(cell_size, cell_size, 1.0))
+                    .build(),
+            ),
+        )
+        .with_uv_rect(tile_uv_rect(instance.tile))
+        .with_material(material.clone())
+        .build(graph)
+    }
+
+    /// Replaces the tiles of a tile map, re-creates its tile nodes and returns the previous tiles.
+    /// Returns `None` if the node has no tile map script.
+    pub fn replace_tiles(
+        graph: &mut Graph,
+        tile_map: Handle<Node>,
+        tiles: Vec<TileInstance>,
+        material: &MaterialResource,
+    ) -> Option<Vec<TileInstance>> {
+        let script = graph
+            .try_get_mut(tile_map)?
+            .try_get_script_mut::<TileMap>()?;
+        let previous = std::mem::replace(script.tiles.get_value_mut_and_mark_modified(), tiles);
+        let script = script.clone();
+
+        for child in graph[tile_map].children().to_vec() {
+            graph.remove_node(child);
+        }
+        for instance in script.tiles.iter() {
+            let tile = script.spawn_tile(graph, instance, material);
+            graph.link_nodes(tile, tile_map);
+        }
+
+        Some(previous)
+    }
+}
+
+impl ScriptTrait for TileMap {}
@@ -0,0 +1,167 @@
+//! On-screen joystick for touch screens - a translucent ring with a knob, that could be dragged
+//! within the ring. The widget reports the offset of the knob from the center of the ring.
+use fyrox::{
+    core::{
+        algebra::Vector2, color::Color, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        message::{MessageDirection, UiMessage},
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+const RING_COLOR: Color = Color::from_rgba(255, 255, 255, 50);
+const KNOB_COLOR: Color = Color::from_rgba(255, 255, 255, 120);
+/// Radius of the knob relative to the radius of the ring.
+const KNOB_SCALE: f32 = 0.4;
+const SEGMENTS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VirtualJoystickMessage {
+    /// Offset of the knob from the center of the ring, the length is in `0..1` range. X axis
+    /// points to the right, Y axis points up.
+    Value(Vector2<f32>),
+}
+
+impl VirtualJoystickMessage {
+    define_constructor!(
+        VirtualJoystickMessage:Value => fn value(Vector2<f32>), layout: false
+    );
+}
+
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "3c8f2a61-7d04-4b9e-a5c2-e19d6b0f4a73")]
+struct VirtualJoystick {
+    widget: Widget,
+    value: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    dragging: bool,
+}
+
+define_widget_deref!(VirtualJoystick);
+
+impl VirtualJoystick {
+    fn ring_radius(&self) -> f32 {
+        let bounds = self.bounding_rect();
+        bounds.w().min(bounds.h()) * 0.5
+    }
+
+    fn center(&self) -> Vector2<f32> {
+        self.bounding_rect().center()
+    }
+
+    /// Moves the knob to the given point on the screen and reports the new value.
+    fn drag_to(&mut self, ui: &UserInterface, pos: Vector2<f32>) {
+        let travel = self.ring_radius() * (1.0 - KNOB_SCALE);
+        if travel <= 0.0 {
+            return;
+        }
+        let offset = (self.screen_to_local(pos) - self.center()).scale(1.0 / travel);
+        let length = offset.norm();
+        let offset = if length > 1.0 {
+            offset.scale(1.0 / length)
+        } else {
+            offset
+        };
+        // Y axis of the screen points down.
+        self.set_value(ui, Vector2::new(offset.x, -offset.y));
+    }
+
+    fn set_value(&mut self, ui: &UserInterface, value: Vector2<f32>) {
+        if self.value != value {
+            self.value = value;
+            ui.send_message(VirtualJoystickMessage::value(
+                self.handle(),
+                MessageDirection::FromWidget,
+                value,
+            ));
+            self.invalidate_visual();
+        }
+    }
+}
+
+impl Control for VirtualJoystick {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let radius = self.ring_radius();
+        let center = self.center();
+
+        drawing_context.push_circle(center, radius, SEGMENTS, Color::WHITE);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(RING_COLOR),
+            CommandTexture::None,
+            None,
+        );
+
+        let travel = radius * (1.0 - KNOB_SCALE);
+        let knob = center + Vector2::new(self.value.x, -self.value.y).scale(travel);
+        drawing_context.push_circle(knob, radius * KNOB_SCALE, SEGMENTS, Color::WHITE);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(KNOB_COLOR),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() != self.handle() {
+            return;
+        }
+        let Some(msg) = message.data::<WidgetMessage>() else {
+            return;
+        };
+        // Touches and the mouse are handled the same way, so the joystick could be tested on a
+        // desktop as well.
+        match msg {
+            WidgetMessage::MouseDown { pos, .. } | WidgetMessage::TouchStarted { pos, .. } => {
+                self.dragging = true;
+                ui.capture_mouse(self.handle());
+                self.drag_to(ui, *pos);
+            }
+            WidgetMessage::MouseMove { pos, .. } | WidgetMessage::TouchMoved { pos, .. } => {
+                if self.dragging {
+                    self.drag_to(ui, *pos);
+                }
+            }
+            WidgetMessage::MouseUp { .. } | WidgetMessage::TouchEnded { .. } => {
+                if self.dragging {
+                    self.dragging = false;
+                    ui.release_mouse_capture();
+                    self.set_value(ui, Vector2::default());
+                }
+            }
+            _ => return,
+        }
+        message.set_handled(true);
+    }
+}
+
+pub struct VirtualJoystickBuilder {
+    widget_builder: WidgetBuilder,
+}
+
+impl VirtualJoystickBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self { widget_builder }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let joystick = VirtualJoystick {
+            widget: self.widget_builder.build(),
+            value: Default::default(),
+            dragging: false,
+        };
+
+        ctx.add_node(UiNode::new(joystick))
+    }
+}
@@ -0,0 +1,253 @@
+//! Waves of enemies, that are spawned periodically at spawn points.
+use crate::{
+    enemy::{spawn_enemy, Enemy, ENEMY_PREFAB_PATH, ENEMY_TEXTURE_PATH},
+    platform_texture_path, Game,
+};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{message::MessageDirection, text::TextMessage},
+    rand::{seq::SliceRandom, thread_rng, Rng},
+    resource::{
+        model::{Model, ModelResource, ModelResourceExtension},
+        texture::{Texture, TextureResource},
+    },
+    scene::{
+        base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+/// Tag of the spawn points, that are added to the scenes without their own ones.
+pub const SPAWN_POINT_TAG: &str = "SpawnPoint";
+/// Time (in seconds) for which the message about a completed wave stays on the screen.
+const WAVE_COMPLETE_MESSAGE_TIME: f32 = 3.0;
+
+/// Spawns `enemies_per_wave` enemies every `wave_interval` seconds at random nodes with one of
+/// the `spawn_point_tags` tags. Enemies are instances of `enemy_prefab`, if the prefab is not set,
+/// `data/objects/enemy.rgs` is used. If the prefab is not loaded, enemies are built in code.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "2b7d4e91-6c3a-4f58-9e1d-8a5b0c7f3e62")]
+#[visit(optional)]
+pub struct WaveSpawner {
+    wave_interval: InheritableVariable<f32>,
+    enemies_per_wave: InheritableVariable<u32>,
+    spawn_point_tags: InheritableVariable<Vec<String>>,
+    enemy_prefab: InheritableVariable<Option<ModelResource>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wave: u32,
+    /// Numbers of the waves, that are not defeated yet, with the amount of their alive enemies.
+    /// Enemies decrement the counter of their wave when they die.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    waves: Vec<(u32, Arc<AtomicU32>)>,
+    /// Number of the last defeated wave.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    completed_wave: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wave_complete_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prefab: Option<ModelResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    enemy_texture: Option<TextureResource>,
+}
+
+impl Default for WaveSpawner {
+    fn default() -> Self {
+        Self {
+            wave_interval: 20.0.into(),
+            enemies_per_wave: 3.into(),
+            spawn_point_tags: vec![SPAWN_POINT_TAG.to_string()].into(),
+            enemy_prefab: Default::default(),
+            timer: 0.0,
+            wave: 0,
+            waves: Default::default(),
+            completed_wave: 0,
+            wave_complete_timer: 0.0,
+            prefab: None,
+            enemy_texture: None,
+        }
+    }
+}
+
+impl WaveSpawner {
+    fn spawn_wave(&mut self, ctx: &mut ScriptContext) {
+        let spawn_points = ctx
+            .scene
+            .graph
+            .linear_iter()
+            .filter(|node| self.spawn_point_tags.iter().any(|tag| tag == node.tag()))
+            .map(|node| node.global_position())
+            .collect::<Vec<_>>();
+
+        let alive = Arc::new(AtomicU32::new(0));
+        let mut rng = thread_rng();
+        let mut spawned = 0;
+        for _ in 0..*self.enemies_per_wave {
+            let Some(position) = spawn_points.choose(&mut rng) else {
+                break;
+            };
+            // Enemies at the same spawn point must not overlap.
+            let position = Vector2::new(position.x + rng.gen_range(-0.5..0.5), position.y);
+            let enemy = self.spawn_enemy(ctx, position);
+            if let Some(enemy) = ctx
+                .scene
+                .graph
+                .try_get_mut(enemy)
+                .and_then(|enemy| enemy.try_get_script_mut::<Enemy>())
+            {
+                enemy.set_wave_counter(alive.clone());
+                spawned += 1;
+            }
+        }
+
+        if spawned > 0 {
+            self.wave += 1;
+            alive.store(spawned, Ordering::SeqCst);
+            self.waves.push((self.wave, alive));
+        }
+    }
+
+    /// Returns the node with the enemy script.
+    fn spawn_enemy(&self, ctx: &mut ScriptContext, position: Vector2<f32>) -> Handle<Node> {
+        match self.prefab.as_ref() {
+            Some(prefab) if prefab.is_ok() => {
+                let instance = prefab.instantiate_at(
+                    ctx.scene,
+                    Vector3::new(position.x, position.y, 0.0),
+                    UnitQuaternion::default(),
+                );
+                let graph = &mut ctx.scene.graph;
+                let enemy = graph
+                    .traverse_handle_iter(instance)
+                    .find(|handle| graph[*handle].try_get_script::<Enemy>().is_some())
+                    .unwrap_or_default();
+                // The root of the prefab is the root of its scene, while the enemy removes only
+                // itself when it dies.
+                if enemy.is_some() && enemy != instance {
+                    let root = graph.get_root();
+                    graph.link_nodes_keep_global_position_rotation(enemy, root);
+                    graph.remove_node(instance);
+                }
+                enemy
+            }
+            _ => match self.enemy_texture.clone() {
+                Some(texture) => spawn_enemy(&mut ctx.scene.graph, position, texture),
+                None => Handle::NONE,
+            },
+        }
+    }
+
+    fn status_text(&self, alive: u32) -> String {
+        let mut text = if self.wave_complete_timer > 0.0 {
+            format!("Wave {} complete!", self.completed_wave)
+        } else {
+            format!("Wave {} in {:.0} s", self.wave + 1, self.timer.ceil())
+        };
+        if alive > 0 {
+            text += &format!("\nEnemies left: {alive}");
+        }
+        text
+    }
+}
+
+impl ScriptTrait for WaveSpawner {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.timer = *self.wave_interval;
+        self.prefab = Some(
+            self.enemy_prefab
+                .clone()
+                .unwrap_or_else(|| ctx.resource_manager.request::<Model>(ENEMY_PREFAB_PATH)),
+        );
+        self.enemy_texture = Some(
+            ctx.resource_manager
+                .request::<Texture>(platform_texture_path(ENEMY_TEXTURE_PATH)),
+        );
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.timer -= ctx.dt;
+        if self.timer <= 0.0 {
+            self.timer = *self.wave_interval;
+            self.spawn_wave(ctx);
+        }
+
+        let mut completed_wave = None;
+        self.waves.retain(|(wave, alive)| {
+            let defeated = alive.load(Ordering::SeqCst) == 0;
+            if defeated {
+                completed_wave = Some(*wave);
+            }
+            !defeated
+        });
+        if let Some(wave) = completed_wave {
+            self.completed_wave = wave;
+            self.wave_complete_timer = WAVE_COMPLETE_MESSAGE_TIME;
+        }
+        self.wave_complete_timer -= ctx.dt;
+        let alive = self
+            .waves
+            .iter()
+            .map(|(_, alive)| alive.load(Ordering::SeqCst))
+            .sum();
+
+        let Some((hud, wave_text)) = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| (game.hud, game.wave_text))
+        else {
+            return;
+        };
+        if let Some(hud) = ctx.user_interfaces.try_get(hud) {
+            hud.send_message(TextMessage::text(
+                wave_text,
+                MessageDirection::ToWidget,
+                self.status_text(alive),
+            ));
+        }
+    }
+}
+
+/// Adds a few spawn points around the given position and a spawner, that uses them.
+pub fn add_wave_spawner(graph: &mut Graph, position: Vector3<f32>) {
+    for offset in [-6.0, 2.0, 8.0] {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Spawn Point")
+                .with_tag(SPAWN_POINT_TAG.to_string())
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position + Vector3::new(offset, 2.0, 0.0))
+                        .build(),
+                ),
+        )
+        .build(graph);
+    }
+
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Wave Spawner")
+            .with_script(Script::new(WaveSpawner::default())),
+    )
+    .build(graph);
+}
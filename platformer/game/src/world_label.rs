@@ -0,0 +1,144 @@
+//! Text labels attached to scene nodes.
+use crate::Game;
+use common_scripts::world_to_screen;
+use fyrox::{
+    core::{
+        algebra::Vector3, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, UiNode, UserInterface,
+    },
+    scene::{camera::Camera, node::Node, Scene},
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+/// Shows a text above the node, the text is placed on the HUD of the game.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "a3d5e7f1-2b4c-4d6e-8f09-1a2b3c4d5e6f")]
+#[visit(optional)]
+pub struct WorldSpaceLabel {
+    text: InheritableVariable<String>,
+    /// Offset of the label from the node position, in world units.
+    offset: InheritableVariable<Vector3<f32>>,
+    /// A camera used for projection. If not set, the first enabled camera will be used.
+    camera: InheritableVariable<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui: Handle<UserInterface>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    label: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    text_changed: bool,
+}
+
+impl WorldSpaceLabel {
+    pub fn new(text: String, offset: Vector3<f32>) -> Self {
+        Self {
+            text: text.into(),
+            offset: offset.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text.set_value_and_mark_modified(text);
+        self.text_changed = true;
+    }
+
+    fn camera(&self, scene: &Scene) -> Handle<Node> {
+        if scene.graph.is_valid_handle(*self.camera) {
+            *self.camera
+        } else {
+            scene
+                .graph
+                .pair_iter()
+                .find(|(_, node)| node.cast::<Camera>().map_or(false, |c| c.is_enabled()))
+                .map(|(handle, _)| handle)
+                .unwrap_or_default()
+        }
+    }
+}
+
+impl ScriptTrait for WorldSpaceLabel {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.ui = ctx
+            .plugins
+            .of_type_ref::<Game>()
+            .map(|game| game.hud)
+            .unwrap_or_default();
+
+        if let Some(ui) = ctx.user_interfaces.try_get_mut(self.ui) {
+            self.label = TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(120.0)
+                    .with_hit_test_visibility(false),
+            )
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .with_text(self.text.as_str())
+            .build(&mut ui.build_ctx());
+        }
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(ui) = ctx.user_interfaces.try_get(self.ui) {
+            ui.send_message(WidgetMessage::remove(
+                self.label,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let Some(ui) = ctx.user_interfaces.try_get(self.ui) else {
+            return;
+        };
+        // The label is removed with the user interface, e.g. when the game goes back to the menu.
+        let Some(label) = ui.try_get(self.label) else {
+            return;
+        };
+        let size = label.actual_local_size();
+
+        let position = ctx.scene.graph[ctx.handle].global_position() + *self.offset;
+        let camera = self.camera(ctx.scene);
+
+        match world_to_screen(ctx.scene, camera, position) {
+            Some(screen_position) => {
+                let screen_position = screen_position.component_mul(&ui.screen_size());
+                ui.send_message(WidgetMessage::desired_position(
+                    self.label,
+                    MessageDirection::ToWidget,
+                    screen_position - size.scale(0.5),
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    self.label,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            }
+            None => {
+                // The node is behind the camera.
+                ui.send_message(WidgetMessage::visibility(
+                    self.label,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+        }
+
+        if self.text_changed {
+            self.text_changed = false;
+            ui.send_message(TextMessage::text(
+                self.label,
+                MessageDirection::ToWidget,
+                (*self.text).clone(),
+            ));
+        }
+    }
+}
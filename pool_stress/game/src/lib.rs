@@ -0,0 +1,315 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::Vector2,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        curve::{CurveEditorBuilder, CurveEditorMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    rand::{thread_rng, Rng},
+    scene::{base::BaseBuilder, camera::CameraBuilder, node::Node, pivot::PivotBuilder, Scene},
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use std::collections::VecDeque;
+
+/// Default amount of nodes spawned (and removed) every frame, 10,000 nodes per second at 60 FPS.
+const DEFAULT_SPAWN_COUNT: usize = 166;
+/// Maximum amount of nodes spawned (and removed) every frame.
+const MAX_SPAWN_COUNT: f32 = 500.0;
+/// Amount of nodes, that is spawned at start, so there's something to remove from the beginning.
+const INITIAL_NODE_COUNT: usize = 10_000;
+/// Time (in seconds) between two samples on the graphs.
+const SAMPLE_INTERVAL: f32 = 0.25;
+/// Maximum amount of samples on the graphs.
+const HISTORY_LENGTH: usize = 120;
+
+/// A lightweight script, that just counts frames. It exists to make the spawned nodes a bit more
+/// realistic - every node with a script must be initialized and updated by the script processor.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "4b8e2d6f-1a3c-4e97-b5d0-8c2f7a9e6b13")]
+#[visit(optional)]
+pub struct Counter {
+    count: u32,
+}
+
+impl ScriptTrait for Counter {
+    fn on_update(&mut self, _ctx: &mut ScriptContext) {
+        self.count = self.count.wrapping_add(1);
+    }
+}
+
+/// History of a value over time, that is shown in a curve editor.
+#[derive(Default, Debug)]
+struct Graph {
+    editor: Handle<UiNode>,
+    samples: VecDeque<(f32, f32)>,
+}
+
+impl Graph {
+    fn push(&mut self, time: f32, value: f32) {
+        if self.samples.len() >= HISTORY_LENGTH {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, value));
+    }
+
+    fn sync(&self, ctx: &PluginContext) {
+        let curve = Curve::from(
+            self.samples
+                .iter()
+                .map(|(time, value)| CurveKey::new(*time, *value, CurveKeyKind::Linear))
+                .collect::<Vec<_>>(),
+        );
+        let ui = ctx.user_interfaces.first();
+        ui.send_message(CurveEditorMessage::sync(
+            self.editor,
+            MessageDirection::ToWidget,
+            vec![curve],
+        ));
+        ui.send_message(CurveEditorMessage::zoom_to_fit(
+            self.editor,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+}
+
+const SCENE_NAME: &str = "pool_stress";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    spawn_count: usize,
+    time: f32,
+    sample_timer: f32,
+    debug_text: Handle<UiNode>,
+    spawn_count_slider: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    nodes: Vec<Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    fragmentation: Graph,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    frame_time: Graph,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_graph(ctx: &mut BuildContext, row: usize) -> Handle<UiNode> {
+    CurveEditorBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .build(ctx)
+}
+
+impl Game {
+    fn spawn_node(scene: &mut Scene) -> Handle<Node> {
+        PivotBuilder::new(BaseBuilder::new().with_script(Script::new(Counter::default())))
+            .build(&mut scene.graph)
+    }
+
+    fn spawn_and_despawn(&mut self, scene: &mut Scene) {
+        let mut rng = thread_rng();
+        for _ in 0..self.spawn_count {
+            if self.nodes.is_empty() {
+                break;
+            }
+            let node = self.nodes.swap_remove(rng.gen_range(0..self.nodes.len()));
+            scene.graph.remove_node(node);
+        }
+        for _ in 0..self.spawn_count {
+            self.nodes.push(Self::spawn_node(scene));
+        }
+    }
+
+    /// Fragmentation of the pool is a fraction of free records in the range of occupied indices.
+    /// Good handle reuse keeps it low.
+    fn fragmentation(&self, scene: &Scene) -> f32 {
+        let max_index = self
+            .nodes
+            .iter()
+            .map(|node| node.index())
+            .max()
+            .unwrap_or_default() as f32;
+        let live_count = scene.graph.node_count() as f32;
+        if max_index > 0.0 {
+            ((max_index - live_count) / max_index).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Counter>("Counter");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        self.nodes = (0..INITIAL_NODE_COUNT)
+            .map(|_| Self::spawn_node(&mut scene))
+            .collect();
+        self.spawn_count = DEFAULT_SPAWN_COUNT;
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        self.spawn_count_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(0.0)
+        .with_max(MAX_SPAWN_COUNT)
+        .with_step(1.0)
+        .with_value(self.spawn_count as f32)
+        .with_value_precision(0)
+        .show_value(true)
+        .build(ctx);
+
+        let spawn_count_text = make_text(ctx, 0, "Spawned/Removed Nodes per Frame");
+        let fragmentation_text = make_text(ctx, 2, "Pool Fragmentation (%)");
+        self.fragmentation.editor = make_graph(ctx, 3);
+        let frame_time_text = make_text(ctx, 4, "Frame Time (ms)");
+        self.frame_time.editor = make_graph(ctx, 5);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(400.0)
+                .with_height(500.0)
+                .with_desired_position(Vector2::new(5.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Pool Stress Test"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(spawn_count_text)
+                    .with_child(self.spawn_count_slider)
+                    .with_child(fragmentation_text)
+                    .with_child(self.fragmentation.editor)
+                    .with_child(frame_time_text)
+                    .with_child(self.frame_time.editor),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::stretch())
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        self.spawn_and_despawn(scene);
+        let fragmentation = self.fragmentation(scene);
+        let node_count = scene.graph.node_count();
+
+        self.time += context.dt;
+        self.sample_timer -= context.dt;
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+
+            if self.sample_timer <= 0.0 {
+                self.sample_timer = SAMPLE_INTERVAL;
+                self.fragmentation.push(self.time, fragmentation * 100.0);
+                self.frame_time
+                    .push(self.time, statistics.pure_frame_time * 1000.0);
+                self.fragmentation.sync(context);
+                self.frame_time.sync(context);
+            }
+
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Node Pool Stress Test\n\
+                    Nodes: {}\nSpawned/Removed per Second: {:.0}\n\
+                    Fragmentation: {:.1}%\nFrame Time: {:.3} ms\nFPS: {}\n{}",
+                    node_count,
+                    self.spawn_count as f32 / context.dt,
+                    fragmentation * 100.0,
+                    statistics.pure_frame_time * 1000.0,
+                    statistics.frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_ui_message(&mut self, _context: &mut PluginContext, message: &UiMessage) {
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.spawn_count_slider
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.spawn_count = value.round() as usize;
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
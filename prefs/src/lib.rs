@@ -0,0 +1,4 @@
+//! Preferences shared between demo projects.
+pub mod quality;
+
+pub use quality::SceneQualityPrefs;
@@ -0,0 +1,81 @@
+//! Persistent renderer quality settings of a scene.
+use fyrox::{
+    core::{log::Log, visitor::prelude::*},
+    renderer::{QualitySettings, Renderer},
+};
+use std::path::PathBuf;
+
+#[derive(Visit, Default, Debug, Clone)]
+pub struct SceneQualityPrefs {
+    pub quality_settings: QualitySettings,
+}
+
+fn prefs_path(scene_name: &str) -> PathBuf {
+    PathBuf::from(format!("data/{scene_name}_quality.bin"))
+}
+
+impl SceneQualityPrefs {
+    /// Loads the prefs of the scene, returns `None` if there's no prefs file or it is corrupted.
+    pub fn load(scene_name: &str) -> Option<Self> {
+        let data = std::fs::read(prefs_path(scene_name)).ok()?;
+
+        let mut prefs = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => match prefs.visit("QualityPrefs", &mut visitor) {
+                Ok(_) => Some(prefs),
+                Err(err) => {
+                    Log::err(format!(
+                        "Unable to read quality prefs of {scene_name}: {err:?}"
+                    ));
+                    None
+                }
+            },
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to load quality prefs of {scene_name}: {err:?}"
+                ));
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, scene_name: &str) {
+        let path = prefs_path(scene_name);
+        if let Some(dir) = path.parent() {
+            Log::verify(std::fs::create_dir_all(dir));
+        }
+
+        let mut prefs = self.clone();
+        let mut visitor = Visitor::new();
+        Log::verify(prefs.visit("QualityPrefs", &mut visitor));
+        Log::verify(visitor.save_binary(&path));
+    }
+
+    /// Removes the prefs file of the scene, so the next run starts with the default settings.
+    pub fn clear(scene_name: &str) {
+        let path = prefs_path(scene_name);
+        if path.exists() {
+            Log::verify(std::fs::remove_file(path));
+        }
+    }
+}
+
+/// Applies saved quality settings of the scene to the renderer. Returns `false` if there's no
+/// saved settings, so the caller could apply its own defaults.
+pub fn restore(renderer: &mut Renderer, scene_name: &str) -> bool {
+    match SceneQualityPrefs::load(scene_name) {
+        Some(prefs) => {
+            Log::verify(renderer.set_quality_settings(&prefs.quality_settings));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Saves current quality settings of the renderer as the prefs of the scene.
+pub fn store(renderer: &Renderer, scene_name: &str) {
+    SceneQualityPrefs {
+        quality_settings: renderer.get_quality_settings(),
+    }
+    .save(scene_name);
+}
@@ -0,0 +1,420 @@
+//! Game project.
+use crate::retargeter::SkeletonRetargeter;
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        io,
+        log::Log,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        inspector::{
+            editors::PropertyEditorDefinitionContainer, CollectionChanged, FieldKind,
+            InspectorBuilder, InspectorContext, InspectorMessage, PropertyAction,
+        },
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, UiNode,
+    },
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::model::{Model, ModelResourceExtension},
+    scene::{
+        animation::AnimationPlayer,
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::Mesh,
+        node::{Node, NodeTrait},
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+mod retargeter;
+
+const SCENE_NAME: &str = "retarget";
+const BONE_MAP_PATH: &str = "data/retarget_map.ron";
+/// The models are taken from the animation and the blendshape demos, so they must be present next
+/// to this project.
+const SOURCE_MODEL_PATH: &str = "../animation/data/paladin/paladin.fbx";
+const TARGET_MODEL_PATH: &str = "../blendshape/data/Gunan_animated2.fbx";
+/// Height of the target model (a head) in meters.
+const TARGET_HEIGHT: f32 = 0.35;
+/// The target model floats next to the head of the source model.
+const TARGET_POSITION: Vector3<f32> = Vector3::new(-0.8, 1.6, 0.0);
+
+/// Pair of the names of the bones, the target bone copies the motion of the source bone.
+#[derive(Reflect, Clone, Default, Debug)]
+struct BonePair {
+    source: String,
+    target: String,
+}
+
+/// Editable copy of the bone map, that is shown in the inspector.
+#[derive(Reflect, Clone, Default, Debug)]
+struct BoneMap {
+    bones: Vec<BonePair>,
+}
+
+impl BoneMap {
+    fn to_hash_map(&self) -> HashMap<String, String> {
+        self.bones
+            .iter()
+            .filter(|pair| !pair.source.is_empty() && !pair.target.is_empty())
+            .map(|pair| (pair.source.clone(), pair.target.clone()))
+            .collect()
+    }
+}
+
+fn make_inspector_context(bone_map: &BoneMap, ctx: &mut BuildContext) -> InspectorContext {
+    let container = PropertyEditorDefinitionContainer::with_default_editors();
+    container.register_inheritable_inspectable::<BonePair>();
+    container.register_inheritable_vec_collection::<BonePair>();
+
+    InspectorContext::from_object(
+        bone_map,
+        ctx,
+        Arc::new(container),
+        None,
+        u64::MAX,
+        0,
+        true,
+        Default::default(),
+    )
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    /// The model, that plays the animation.
+    source: Handle<Node>,
+    /// The model with a different skeleton, that mirrors the animation of the source.
+    target: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bone_map: Option<BoneMap>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    fn load_model(
+        &self,
+        context: &mut PluginContext,
+        path: &'static str,
+        on_loaded: fn(&mut Game, Handle<Node>, &mut PluginContext),
+    ) {
+        context.task_pool.spawn_plugin_task(
+            context.resource_manager.request::<Model>(path),
+            move |result, game: &mut Game, ctx| match result {
+                Ok(model) => {
+                    let model = model.instantiate(&mut ctx.scenes[game.scene]);
+                    on_loaded(game, model, ctx);
+                    game.try_start_retargeting(ctx);
+                }
+                Err(err) => Log::err(format!("Unable to load {path} model. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn on_source_loaded(&mut self, model: Handle<Node>, context: &mut PluginContext) {
+        self.source = model;
+        context.scenes[self.scene].graph[model]
+            .local_transform_mut()
+            .set_scale(Vector3::repeat(0.01))
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                180.0f32.to_radians(),
+            ));
+    }
+
+    /// Scales the target model to [`TARGET_HEIGHT`] and moves its center to [`TARGET_POSITION`].
+    /// The target plays its own animation, it is disabled, so it doesn't fight with the
+    /// retargeted one.
+    fn on_target_loaded(&mut self, model: Handle<Node>, context: &mut PluginContext) {
+        self.target = model;
+        let graph = &mut context.scenes[self.scene].graph;
+        graph.update_hierarchical_data();
+
+        let mut bounds: Option<AxisAlignedBoundingBox> = None;
+        let mut animation_players = Vec::new();
+        for (handle, node) in graph.traverse_iter(model) {
+            if node.cast::<AnimationPlayer>().is_some() {
+                animation_players.push(handle);
+            } else if let Some(mesh) = node.cast::<Mesh>() {
+                let mesh_bounds = mesh
+                    .local_bounding_box()
+                    .transform(&mesh.global_transform());
+                bounds.get_or_insert(mesh_bounds).add_box(mesh_bounds);
+            }
+        }
+
+        for handle in animation_players {
+            if let Some(animation_player) = graph[handle].cast_mut::<AnimationPlayer>() {
+                for animation in animation_player
+                    .animations_mut()
+                    .get_value_mut_silent()
+                    .iter_mut()
+                {
+                    animation.set_enabled(false);
+                }
+            }
+        }
+
+        let Some(bounds) = bounds else {
+            return;
+        };
+        let size = bounds.max - bounds.min;
+        let scale = if size.y > f32::EPSILON {
+            TARGET_HEIGHT / size.y
+        } else {
+            1.0
+        };
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 180.0f32.to_radians());
+        graph[model]
+            .local_transform_mut()
+            .set_scale(Vector3::repeat(scale))
+            .set_rotation(rotation)
+            .set_position(TARGET_POSITION - rotation * bounds.center() * scale);
+    }
+
+    fn on_bone_map_loaded(
+        &mut self,
+        bone_map: HashMap<String, String>,
+        context: &mut PluginContext,
+    ) {
+        // Sorted, so the order of the pairs in the inspector is stable.
+        let bone_map = BoneMap {
+            bones: bone_map
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .map(|(source, target)| BonePair { source, target })
+                .collect(),
+        };
+
+        let ui = context.user_interfaces.first_mut();
+        let inspector_context = make_inspector_context(&bone_map, &mut ui.build_ctx());
+        ui.send_message(InspectorMessage::context(
+            self.inspector,
+            MessageDirection::ToWidget,
+            inspector_context,
+        ));
+        self.bone_map = Some(bone_map);
+    }
+
+    /// Attaches the retargeter to the target, once both models and the bone map are loaded.
+    fn try_start_retargeting(&mut self, context: &mut PluginContext) {
+        let Some(bone_map) = self.bone_map.as_ref() else {
+            return;
+        };
+        if self.source.is_none() || self.target.is_none() {
+            return;
+        }
+
+        let graph = &mut context.scenes[self.scene].graph;
+        if graph[self.target]
+            .try_get_script::<SkeletonRetargeter>()
+            .is_some()
+        {
+            return;
+        }
+        let mut retargeter = SkeletonRetargeter {
+            source_root: self.source.into(),
+            scale_compensation: true.into(),
+            ..Default::default()
+        };
+        retargeter.set_bone_map(bone_map.to_hash_map());
+        graph[self.target].add_script(Script::new(retargeter));
+    }
+
+    fn retargeter_mut<'a>(
+        &self,
+        context: &'a mut PluginContext,
+    ) -> Option<&'a mut SkeletonRetargeter> {
+        context
+            .scenes
+            .try_get_mut(self.scene)?
+            .graph
+            .try_get_mut(self.target)?
+            .try_get_script_mut::<SkeletonRetargeter>()
+    }
+
+    fn build_bone_map_window(&mut self, ctx: &mut BuildContext) {
+        self.inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(360.0)
+                .with_height(400.0)
+                .with_desired_position(Vector2::new(5.0, 100.0)),
+        )
+        .with_title(WindowTitle::text("Bone Map"))
+        .can_close(false)
+        .with_content(
+            ScrollViewerBuilder::new(WidgetBuilder::new())
+                .with_content(self.inspector)
+                .build(ctx),
+        )
+        .build(ctx);
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<SkeletonRetargeter>("Skeleton Retargeter");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, mut context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(-0.4, 1.3, -3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+        self.build_bone_map_window(ctx);
+
+        self.load_model(&mut context, SOURCE_MODEL_PATH, Game::on_source_loaded);
+        self.load_model(&mut context, TARGET_MODEL_PATH, Game::on_target_loaded);
+        context.task_pool.spawn_plugin_task(
+            io::load_file(BONE_MAP_PATH),
+            |result, game: &mut Game, ctx| {
+                let bone_map = result.map_err(|err| format!("{err:?}")).and_then(|bytes| {
+                    ron::de::from_bytes::<HashMap<String, String>>(&bytes)
+                        .map_err(|err| err.to_string())
+                });
+                match bone_map {
+                    Ok(bone_map) => {
+                        game.on_bone_map_loaded(bone_map, ctx);
+                        game.try_start_retargeting(ctx);
+                    }
+                    Err(err) => Log::err(format!(
+                        "Unable to load the bone map from {BONE_MAP_PATH}. Reason: {err}"
+                    )),
+                }
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        let mapped_bones = self
+            .retargeter_mut(context)
+            .map_or(0, |retargeter| retargeter.mapped_bone_count());
+        let map_size = self
+            .bone_map
+            .as_ref()
+            .map_or(0, |bone_map| bone_map.bones.len());
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Skeletal Animation Retargeting\nThe floating head mirrors the \
+                    animation of the paladin, edit the bone map to change the mapping.\n\
+                    Mapped Bones: {mapped_bones}/{map_size}\nFPS: {}\n{}",
+                        graphics_context.renderer.get_statistics().frames_per_second,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(InspectorMessage::PropertyChanged(property)) = message.data() else {
+            return;
+        };
+        if message.destination() != self.inspector {
+            return;
+        }
+        let Some(bone_map) = self.bone_map.as_mut() else {
+            return;
+        };
+
+        PropertyAction::from_field_kind(&property.value).apply(
+            &property.path(),
+            bone_map,
+            &mut |e| Log::verify(e),
+        );
+        let bone_map = bone_map.clone();
+
+        // Added or removed pairs change the layout of the inspector.
+        let layout_changed = matches!(
+            &property.value,
+            FieldKind::Collection(collection)
+                if !matches!(**collection, CollectionChanged::ItemChanged { .. })
+        );
+        if layout_changed {
+            let ui = context.user_interfaces.first_mut();
+            let inspector_context = make_inspector_context(&bone_map, &mut ui.build_ctx());
+            ui.send_message(InspectorMessage::context(
+                self.inspector,
+                MessageDirection::ToWidget,
+                inspector_context,
+            ));
+        }
+
+        if let Some(retargeter) = self.retargeter_mut(context) {
+            retargeter.set_bone_map(bone_map.to_hash_map());
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Retargeting of skeletal animation - the bones of a target skeleton follow the bones of a source
+//! skeleton, that plays an animation. The skeletons are matched by bone names, the motion is
+//! transferred relative to the bind poses of both skeletons.
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    scene::{graph::Graph, node::Node},
+    script::{ScriptContext, ScriptTrait},
+};
+use std::collections::HashMap;
+
+/// Transform of a bone before any animation is applied.
+#[derive(Clone, Debug)]
+struct BindPose {
+    local_position: Vector3<f32>,
+    global_rotation: UnitQuaternion<f32>,
+}
+
+#[derive(Clone, Debug)]
+struct RetargetedBone {
+    source: Handle<Node>,
+    /// Root bones (the ones without mapped ancestors) copy the translation of the source bone as
+    /// well, the others copy only the rotation.
+    root: bool,
+}
+
+#[derive(Clone, Debug)]
+struct HierarchyEntry {
+    node: Handle<Node>,
+    bone: Option<RetargetedBone>,
+}
+
+/// Applies the animation of a source skeleton to the skeleton of the node with this script. Bones
+/// of the skeletons are matched by their names using a bone map. Every frame the rotation of a
+/// source bone relative to its bind pose is applied to the bind pose of the target bone, so the
+/// skeletons may have different proportions and different orientations of the bones.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8c41e7a2-5d93-4f06-b1e8-3a7f9c2d6b15")]
+#[visit(optional)]
+pub struct SkeletonRetargeter {
+    /// Root node of the model, that plays the animation.
+    pub source_root: InheritableVariable<Handle<Node>>,
+    /// Scales the translation of the root bones by the ratio of the sizes of the skeletons.
+    pub scale_compensation: InheritableVariable<bool>,
+    /// Names of the source bones and the names of the target bones, that follow them.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bone_map: HashMap<String, String>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    bind_poses: HashMap<Handle<Node>, BindPose>,
+    /// Every node of the target skeleton, parents go before their children.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    hierarchy: Vec<HierarchyEntry>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    needs_resolve: bool,
+}
+
+impl SkeletonRetargeter {
+    pub fn set_bone_map(&mut self, bone_map: HashMap<String, String>) {
+        self.bone_map = bone_map;
+        self.needs_resolve = true;
+    }
+
+    /// Returns the amount of target bones, that have a matching source bone.
+    pub fn mapped_bone_count(&self) -> usize {
+        self.hierarchy
+            .iter()
+            .filter(|entry| entry.bone.is_some())
+            .count()
+    }
+
+    fn capture_bind_poses(&mut self, graph: &Graph, root: Handle<Node>) {
+        for (handle, node) in graph.traverse_iter(root) {
+            self.bind_poses.insert(
+                handle,
+                BindPose {
+                    local_position: **node.local_transform().position(),
+                    global_rotation: node.global_rotation(),
+                },
+            );
+        }
+    }
+
+    /// Finds the bones of both skeletons, that are mentioned in the bone map.
+    fn resolve(&mut self, graph: &Graph, target_root: Handle<Node>) {
+        self.needs_resolve = false;
+
+        let target_to_source = self
+            .bone_map
+            .iter()
+            .map(|(source, target)| (target.as_str(), source.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        self.hierarchy.clear();
+        for (handle, node) in graph.traverse_iter(target_root) {
+            let source = target_to_source
+                .get(node.name())
+                .and_then(|name| graph.find_by_name(*self.source_root, name))
+                .map(|(source, _)| source);
+            self.hierarchy.push(HierarchyEntry {
+                node: handle,
+                bone: source.map(|source| RetargetedBone { source, root: true }),
+            });
+        }
+
+        // A bone is a root bone, if none of its ancestors are mapped.
+        let mapped = self
+            .hierarchy
+            .iter()
+            .filter(|entry| entry.bone.is_some())
+            .map(|entry| entry.node)
+            .collect::<Vec<_>>();
+        for entry in self.hierarchy.iter_mut() {
+            if let Some(bone) = entry.bone.as_mut() {
+                let mut ancestor = graph[entry.node].parent();
+                while ancestor.is_some() && ancestor != target_root {
+                    if mapped.contains(&ancestor) {
+                        bone.root = false;
+                        break;
+                    }
+                    ancestor = graph[ancestor].parent();
+                }
+            }
+        }
+    }
+
+    fn root_position(
+        &self,
+        graph: &Graph,
+        source: Handle<Node>,
+        target_bind: &BindPose,
+    ) -> Option<Vector3<f32>> {
+        let source_bind = self.bind_poses.get(&source)?;
+        let source_position = **graph[source].local_transform().position();
+        let scale = if *self.scale_compensation {
+            let source_length = source_bind.local_position.norm();
+            if source_length > f32::EPSILON {
+                target_bind.local_position.norm() / source_length
+            } else {
+                1.0
+            }
+        } else {
+            1.0
+        };
+        Some(target_bind.local_position + (source_position - source_bind.local_position) * scale)
+    }
+}
+
+impl ScriptTrait for SkeletonRetargeter {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // Bind poses are taken before the first update of the animations.
+        let graph = &mut ctx.scene.graph;
+        graph.update_hierarchical_data();
+        self.capture_bind_poses(graph, *self.source_root);
+        self.capture_bind_poses(graph, ctx.handle);
+        self.needs_resolve = true;
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        let graph = &mut ctx.scene.graph;
+        if self.needs_resolve {
+            self.resolve(graph, ctx.handle);
+        }
+
+        // New global rotations of the target nodes, the children are computed from them, instead
+        // of the rotations of the previous frame.
+        let mut global_rotations = HashMap::with_capacity(self.hierarchy.len());
+        for entry in self.hierarchy.iter() {
+            let parent = graph[entry.node].parent();
+            let parent_rotation = global_rotations.get(&parent).cloned().unwrap_or_else(|| {
+                graph
+                    .try_get(parent)
+                    .map_or(UnitQuaternion::identity(), |parent| {
+                        parent.global_rotation()
+                    })
+            });
+
+            let rotation = match (entry.bone.as_ref(), self.bind_poses.get(&entry.node)) {
+                (Some(bone), Some(target_bind)) => {
+                    let Some(source_bind) = self.bind_poses.get(&bone.source) else {
+                        continue;
+                    };
+                    // Rotation of the source bone relative to its bind pose in world space.
+                    let delta = graph[bone.source].global_rotation()
+                        * source_bind.global_rotation.inverse();
+                    let rotation = delta * target_bind.global_rotation;
+
+                    let position = if bone.root {
+                        self.root_position(graph, bone.source, target_bind)
+                    } else {
+                        None
+                    };
+                    let transform = graph[entry.node].local_transform_mut();
+                    transform.set_rotation(parent_rotation.inverse() * rotation);
+                    if let Some(position) = position {
+                        transform.set_position(position);
+                    }
+                    rotation
+                }
+                _ => parent_rotation * **graph[entry.node].local_transform().rotation(),
+            };
+            global_rotations.insert(entry.node, rotation);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Game project.
+use crate::{sdf_font::SdfFont, sdf_text::SdfTextBuilder};
+use common_scripts::Throttle;
+use fyrox::{
+    core::{
+        algebra::{Matrix3, Vector2, Vector3},
+        color::Color,
+        io,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode,
+    },
+    material::shader::Shader,
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, OrthographicProjection, Projection},
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+pub mod sdf_font;
+pub mod sdf_text;
+
+const SCENE_NAME: &str = "sdf_text";
+
+const FONT_DESCRIPTION_PATH: &str = "data/fonts/dejavu_sans_sdf.json";
+const FONT_ATLAS_PATH: &str = "data/fonts/dejavu_sans_sdf.png";
+const SHADER_PATH: &str = "data/sdf_text.shader";
+
+/// Font size of both texts before zooming, in pixels.
+const FONT_SIZE: f32 = 16.0;
+const MAX_ZOOM: f32 = 10.0;
+/// Distance from the left side of the screen to the texts, in pixels.
+const MARGIN: f32 = 20.0;
+/// Distance from the top of the screen to the distance field text, in pixels.
+const SDF_TEXT_TOP: f32 = 120.0;
+
+const SDF_TEXT: &str = "SDF: Quick Fox 123";
+const BITMAP_TEXT: &str = "Bitmap: Quick Fox 123";
+
+#[derive(Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    sdf_text: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    bitmap_text: Handle<UiNode>,
+    zoom_slider: Handle<UiNode>,
+    zoom: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    screen_size: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self {
+            scene: Default::default(),
+            camera: Default::default(),
+            sdf_text: Default::default(),
+            debug_text: Default::default(),
+            bitmap_text: Default::default(),
+            zoom_slider: Default::default(),
+            zoom: 1.0,
+            screen_size: Default::default(),
+            throttle: Default::default(),
+        }
+    }
+}
+
+impl Game {
+    fn on_font_loaded(&mut self, font: SdfFont, context: &mut PluginContext) {
+        let shader = context.resource_manager.request::<Shader>(SHADER_PATH);
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        self.sdf_text = SdfTextBuilder::new(BaseBuilder::new())
+            .with_text(SDF_TEXT)
+            .with_font_size(FONT_SIZE)
+            .with_color(Color::WHITE)
+            .build(&font, shader, &mut scene.graph);
+    }
+
+    /// One world unit is one pixel of the screen, the origin is at the center of the screen.
+    fn sync_scene(&self, scene: &mut Scene, screen_size: Vector2<f32>) {
+        if let Some(camera) = scene.graph.try_get_mut_of_type::<Camera>(self.camera) {
+            camera.set_projection(Projection::Orthographic(OrthographicProjection {
+                z_near: 0.0,
+                z_far: 100.0,
+                vertical_size: screen_size.y * 0.5,
+            }));
+        }
+        if let Some(sdf_text) = scene.graph.try_get_mut(self.sdf_text) {
+            // X axis points to the left.
+            sdf_text
+                .local_transform_mut()
+                .set_position(Vector3::new(
+                    screen_size.x * 0.5 - MARGIN,
+                    screen_size.y * 0.5 - SDF_TEXT_TOP,
+                    0.0,
+                ))
+                .set_scale(Vector3::new(self.zoom, self.zoom, 1.0));
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, _context: PluginRegistrationContext) {}
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -10.0))
+                    .build(),
+            ),
+        )
+        .with_projection(Projection::Orthographic(Default::default()))
+        .build(&mut scene.graph);
+        self.scene = context.scenes.add(scene);
+
+        let texture = context.resource_manager.request::<Texture>(FONT_ATLAS_PATH);
+        context.task_pool.spawn_plugin_task(
+            io::load_file(FONT_DESCRIPTION_PATH),
+            move |result, game: &mut Game, ctx| {
+                let font = result
+                    .map_err(|err| format!("{err:?}"))
+                    .and_then(|bytes| SdfFont::from_bytes(&bytes, texture));
+                match font {
+                    Ok(font) => game.on_font_loaded(font, ctx),
+                    Err(err) => Log::err(format!(
+                        "Unable to load font {FONT_DESCRIPTION_PATH}. Reason: {err}"
+                    )),
+                }
+            },
+        );
+
+        let ui = context.user_interfaces.first_mut();
+        let screen_size = ui.screen_size();
+        let ctx = &mut ui.build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        // The same text rendered by the UI, its glyphs are rasterized once at the font size and
+        // then stretched by the render transform.
+        self.bitmap_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_desired_position(Vector2::new(MARGIN, screen_size.y * 0.5 + MARGIN)),
+        )
+        .with_text(BITMAP_TEXT)
+        .with_font_size(FONT_SIZE)
+        .build(ctx);
+
+        self.zoom_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(1.0)
+        .with_max(MAX_ZOOM)
+        .with_step(0.1)
+        .with_value(self.zoom)
+        .with_value_precision(1)
+        .show_value(true)
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_height(90.0)
+                .with_desired_position(Vector2::new(screen_size.x - 270.0, 20.0)),
+        )
+        .with_title(WindowTitle::text("Text Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        TextBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(0)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_text("Zoom")
+                        .build(ctx),
+                    )
+                    .with_child(self.zoom_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        let GraphicsContext::Initialized(graphics_context) = context.graphics_context else {
+            return;
+        };
+        let window_size = graphics_context.window.inner_size();
+        let screen_size = Vector2::new(window_size.width as f32, window_size.height as f32);
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.sync_scene(scene, screen_size);
+        }
+
+        let ui = context.user_interfaces.first();
+        if self.screen_size != screen_size {
+            self.screen_size = screen_size;
+            ui.send_message(WidgetMessage::desired_position(
+                self.bitmap_text,
+                MessageDirection::ToWidget,
+                Vector2::new(MARGIN, screen_size.y * 0.5 + MARGIN),
+            ));
+        }
+        ui.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example - Signed Distance Field Text\nZoom: {:.1}x\n\
+                Top: distance field font, the edges are reconstructed per pixel.\n\
+                Bottom: bitmap font, the glyphs are stretched.\n{}\n{}",
+                self.zoom,
+                graphics_context.renderer.get_statistics(),
+                tracked_alloc::peak_ram_text()
+            ),
+        ));
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.destination() != self.zoom_slider
+            || message.direction() != MessageDirection::FromWidget
+        {
+            return;
+        }
+
+        self.zoom = *value;
+        context
+            .user_interfaces
+            .first()
+            .send_message(WidgetMessage::render_transform(
+                self.bitmap_text,
+                MessageDirection::ToWidget,
+                Matrix3::new_scaling(self.zoom),
+            ));
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
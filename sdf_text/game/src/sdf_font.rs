@@ -0,0 +1,84 @@
+//! Signed distance field font - an atlas of glyphs, where every texel stores the distance to the
+//! nearest edge of a glyph instead of its coverage, and a description of the glyphs. The
+//! description uses the JSON layout of `msdf-atlas-gen`, so atlases made by it can be used as is.
+use fyrox::resource::texture::TextureResource;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Copy, Clone, Debug, Default)]
+pub struct Bounds {
+    pub left: f32,
+    pub bottom: f32,
+    pub right: f32,
+    pub top: f32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasInfo {
+    /// Width of the distance field around the edges of the glyphs, in texels of the atlas.
+    pub distance_range: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FontMetrics {
+    pub line_height: f32,
+    pub ascender: f32,
+    pub descender: f32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Glyph {
+    pub unicode: u32,
+    /// Horizontal advance in ems.
+    pub advance: f32,
+    /// Bounds of the quad relative to the baseline, in ems. Missing for glyphs without an image,
+    /// such as space.
+    pub plane_bounds: Option<Bounds>,
+    /// Bounds of the glyph in the atlas, in texels. The origin is at the top of the atlas.
+    pub atlas_bounds: Option<Bounds>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FontDescription {
+    atlas: AtlasInfo,
+    metrics: FontMetrics,
+    glyphs: Vec<Glyph>,
+}
+
+#[derive(Debug)]
+pub struct SdfFont {
+    pub atlas: AtlasInfo,
+    pub metrics: FontMetrics,
+    pub texture: TextureResource,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl SdfFont {
+    pub fn from_bytes(bytes: &[u8], texture: TextureResource) -> Result<Self, String> {
+        let description =
+            serde_json::from_slice::<FontDescription>(bytes).map_err(|err| err.to_string())?;
+        if description.atlas.width == 0 || description.atlas.height == 0 {
+            return Err("The atlas has zero size.".to_string());
+        }
+
+        Ok(Self {
+            atlas: description.atlas,
+            metrics: description.metrics,
+            texture,
+            glyphs: description
+                .glyphs
+                .into_iter()
+                .filter_map(|glyph| Some((char::from_u32(glyph.unicode)?, glyph)))
+                .collect(),
+        })
+    }
+
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+}
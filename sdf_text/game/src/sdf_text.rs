@@ -0,0 +1,140 @@
+//! Text, that is rendered from a signed distance field font. The UI renderer draws widgets with
+//! its own fixed shader, so the text is a mesh in the scene with a material, that reconstructs the
+//! edges of the glyphs from the distance field. Unlike bitmap glyphs, the edges stay sharp at any
+//! scale.
+use crate::sdf_font::SdfFont;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    material::{
+        shader::{SamplerFallback, ShaderResource},
+        Material, MaterialResource, PropertyValue,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+    },
+};
+
+pub struct SdfTextBuilder {
+    base_builder: BaseBuilder,
+    text: String,
+    font_size: f32,
+    color: Color,
+}
+
+impl SdfTextBuilder {
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            text: Default::default(),
+            font_size: 16.0,
+            color: Color::WHITE,
+        }
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Size of an em in world units.
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Builds a quad for every glyph, the first line starts at the origin of the node and its
+    /// ascender is at the origin.
+    fn build_surface_data(&self, font: &SdfFont) -> SurfaceData {
+        let atlas_size = Vector2::new(font.atlas.width as f32, font.atlas.height as f32);
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        let mut pen = Vector2::new(0.0, -font.metrics.ascender);
+        for character in self.text.chars() {
+            if character == '\n' {
+                pen = Vector2::new(0.0, pen.y - font.metrics.line_height);
+                continue;
+            }
+            let Some(glyph) = font.glyph(character).or_else(|| font.glyph('?')) else {
+                continue;
+            };
+
+            if let (Some(plane), Some(atlas)) = (glyph.plane_bounds, glyph.atlas_bounds) {
+                // X axis points to the left, so the text goes towards negative X.
+                let corners = [
+                    (plane.left, plane.top, atlas.left, atlas.top),
+                    (plane.right, plane.top, atlas.right, atlas.top),
+                    (plane.right, plane.bottom, atlas.right, atlas.bottom),
+                    (plane.left, plane.bottom, atlas.left, atlas.bottom),
+                ];
+
+                let first = vertices.len() as u32;
+                vertices.extend(corners.map(|(x, y, u, v)| {
+                    StaticVertex::from_pos_uv_normal(
+                        Vector3::new(-(pen.x + x), pen.y + y, 0.0).scale(self.font_size),
+                        Vector2::new(u / atlas_size.x, v / atlas_size.y),
+                        Vector3::z(),
+                    )
+                }));
+                triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+                triangles.push(TriangleDefinition([first + 2, first + 3, first]));
+            }
+
+            pen.x += glyph.advance;
+        }
+
+        SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+        )
+    }
+
+    pub fn build(self, font: &SdfFont, shader: ShaderResource, graph: &mut Graph) -> Handle<Node> {
+        let mut material = Material::from_shader(shader, None);
+        Log::verify(material.set_property(
+            &"fontAtlas".into(),
+            PropertyValue::Sampler {
+                value: Some(font.texture.clone()),
+                fallback: SamplerFallback::White,
+            },
+        ));
+        Log::verify(material.set_property(&"textColor".into(), self.color.into()));
+        Log::verify(
+            material.set_property(&"distanceRange".into(), font.atlas.distance_range.into()),
+        );
+        Log::verify(material.set_property(
+            &"atlasSize".into(),
+            Vector2::new(font.atlas.width as f32, font.atlas.height as f32).into(),
+        ));
+
+        let surface_data = self.build_surface_data(font);
+        MeshBuilder::new(self.base_builder.with_cast_shadows(false))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                surface_data,
+            ))
+            .with_material(MaterialResource::new_ok(ResourceKind::Embedded, material))
+            .build()])
+            .with_render_path(RenderPath::Forward)
+            .build(graph)
+    }
+}
@@ -0,0 +1,142 @@
+(
+    name: "ProceduralSkyShader",
+
+    properties: [
+        (
+            name: "sunDirection",
+            kind: Vector3((0.0, 1.0, 0.0)),
+        ),
+        (
+            name: "density",
+            kind: Float(1.0),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                // The sky is infinitely far away, it must never occlude anything.
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 sunDirection;
+                uniform float density;
+
+                out vec3 direction;
+                out vec3 sunColor;
+
+                // Rayleigh and Mie scattering coefficients (per kilometer) multiplied by the scale
+                // heights of the respective particles, which gives optical depth at the zenith.
+                const vec3 rayleighDepth = vec3(5.8e-3, 13.5e-3, 33.1e-3) * 8.0;
+                const float mieDepth = 21e-3 * 1.2;
+
+                // Kasten-Young approximation of the relative length of the path through the
+                // atmosphere, it is 1.0 at the zenith and about 38.0 at the horizon.
+                float airMass(float height)
+                {
+                    float zenithAngle = degrees(acos(clamp(height, 0.0, 1.0)));
+                    return 1.0 / (cos(radians(zenithAngle)) + 0.50572 * pow(96.07995 - zenithAngle, -1.6364));
+                }
+
+                void main()
+                {
+                    direction = vertexPosition;
+
+                    // Color temperature of the sun - the lower the sun, the longer the path of its
+                    // light through the atmosphere and the more blue light is scattered out.
+                    vec3 sun = normalize(sunDirection);
+                    sunColor = exp(-(rayleighDepth + mieDepth) * density * airMass(sun.y));
+                    // The sun sets below the horizon gradually.
+                    sunColor *= smoothstep(-0.1, 0.0, sun.y);
+
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform vec3 sunDirection;
+                uniform float density;
+
+                in vec3 direction;
+                in vec3 sunColor;
+
+                out vec4 FragColor;
+
+                const float PI = 3.14159265;
+                const vec3 rayleighDepth = vec3(5.8e-3, 13.5e-3, 33.1e-3) * 8.0;
+                const float mieDepth = 21e-3 * 1.2;
+                const float mieAnisotropy = 0.76;
+                const float sunIntensity = 20.0;
+                // Cosine of the angular radius of the sun disc.
+                const float sunDiscSize = 0.9995;
+
+                float airMass(float height)
+                {
+                    float zenithAngle = degrees(acos(clamp(height, 0.0, 1.0)));
+                    return 1.0 / (cos(radians(zenithAngle)) + 0.50572 * pow(96.07995 - zenithAngle, -1.6364));
+                }
+
+                float rayleighPhase(float cosTheta)
+                {
+                    return 3.0 / (16.0 * PI) * (1.0 + cosTheta * cosTheta);
+                }
+
+                // Cornette-Shanks phase function, it gives the bright halo around the sun.
+                float miePhase(float cosTheta)
+                {
+                    float g2 = mieAnisotropy * mieAnisotropy;
+                    return 3.0 / (8.0 * PI) * ((1.0 - g2) * (1.0 + cosTheta * cosTheta))
+                        / ((2.0 + g2) * pow(1.0 + g2 - 2.0 * mieAnisotropy * cosTheta, 1.5));
+                }
+
+                void main()
+                {
+                    vec3 view = normalize(direction);
+                    vec3 sun = normalize(sunDirection);
+                    float cosTheta = dot(view, sun);
+
+                    vec3 rayleigh = rayleighDepth * density;
+                    float mie = mieDepth * density;
+                    vec3 extinction = rayleigh + mie;
+
+                    // Single scattering: the light of the sun is scattered towards the viewer
+                    // along the view ray, the rest of the ray is lost to extinction.
+                    vec3 viewTransmittance = exp(-extinction * airMass(view.y));
+                    vec3 scattering = (rayleigh * rayleighPhase(cosTheta) + mie * miePhase(cosTheta)) / extinction;
+                    vec3 color = sunIntensity * sunColor * scattering * (1.0 - viewTransmittance);
+
+                    // Sun disc, smoothed at the edge.
+                    color += sunIntensity * sunColor * viewTransmittance
+                        * smoothstep(sunDiscSize, sunDiscSize + 0.0002, cosTheta);
+
+                    // Fade to dark ground below the horizon.
+                    color *= mix(0.1, 1.0, smoothstep(-0.1, 0.0, view.y));
+
+                    // Simple exposure tone mapping.
+                    FragColor = vec4(1.0 - exp(-color), 1.0);
+                }
+               "#,
+        ),
+    ],
+)
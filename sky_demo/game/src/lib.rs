@@ -0,0 +1,357 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::SceneGraph,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, SkyBoxKind},
+        light::{
+            directional::{DirectionalLight, DirectionalLightBuilder},
+            BaseLightBuilder,
+        },
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+/// Radius of the sky sphere, it must fit into the far clipping plane of the camera.
+const SKY_RADIUS: f32 = 900.0;
+const CAMERA_POSITION: Vector3<f32> = Vector3::new(0.0, 2.0, 0.0);
+
+const SCENE_NAME: &str = "sky_demo";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    sun: Handle<Node>,
+    sky: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    azimuth_slider: Handle<UiNode>,
+    elevation_slider: Handle<UiNode>,
+    density_slider: Handle<UiNode>,
+    procedural_sky_check_box: Handle<UiNode>,
+    /// Azimuth of the sun, in degrees.
+    azimuth: f32,
+    /// Elevation of the sun above the horizon, in degrees.
+    elevation: f32,
+    /// Density of the atmosphere, relative to the Earth's one.
+    density: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    sky_material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step((max - min) / 100.0)
+    .with_value(value)
+    .with_value_precision(1)
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    fn sun_direction(&self) -> Vector3<f32> {
+        let azimuth = self.azimuth.to_radians();
+        let elevation = self.elevation.to_radians();
+        Vector3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            elevation.cos() * azimuth.cos(),
+        )
+    }
+
+    fn build_sky(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::from_shader(shader, None));
+
+        // Mirroring the sphere through its center flips the winding of its triangles, so it
+        // becomes visible from the inside.
+        self.sky = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(CAMERA_POSITION)
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_sphere(32, 32, 1.0, &Matrix4::new_scaling(-SKY_RADIUS)),
+        ))
+        .with_material(material.clone())
+        .build()])
+        .build(&mut scene.graph);
+
+        self.sky_material = Some(material);
+    }
+
+    /// Switches between the procedural sky and the built-in cube map skybox.
+    fn set_procedural_sky(&self, procedural: bool, context: &mut PluginContext) {
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        if let Some(sky) = scene.graph.try_get_mut(self.sky) {
+            sky.set_visibility(procedural);
+        }
+
+        if let Some(camera) = scene.graph.try_get_mut_of_type::<Camera>(self.camera) {
+            camera.set_skybox(if procedural {
+                None
+            } else {
+                Some(SkyBoxKind::built_in_skybox().clone())
+            });
+        }
+    }
+
+    fn update_sun(&self, context: &mut PluginContext) {
+        let sun_direction = self.sun_direction();
+
+        if let Some(material) = self.sky_material.as_ref() {
+            let mut material = material.data_ref();
+            Log::verify(material.set_property(&"sunDirection".into(), sun_direction.into()));
+            Log::verify(material.set_property(&"density".into(), self.density.into()));
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        // Directional light shines from its up vector, so the up vector follows the sun.
+        if let Some(sun) = scene.graph.try_get_mut(self.sun) {
+            sun.local_transform_mut().set_rotation(
+                UnitQuaternion::rotation_between(&Vector3::y(), &sun_direction)
+                    .unwrap_or_else(UnitQuaternion::identity),
+            );
+        }
+        if let Some(sun) = scene
+            .graph
+            .try_get_mut_of_type::<DirectionalLight>(self.sun)
+        {
+            sun.base_light_mut()
+                .set_intensity((sun_direction.y * 4.0).clamp(0.0, 1.0));
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.azimuth = 180.0;
+        self.elevation = 15.0;
+        self.density = 1.0;
+
+        let mut scene = Scene::new();
+
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(CAMERA_POSITION)
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        -10.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        self.sun = DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                // A thin box is used as the ground, so it is visible regardless of the winding.
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    400.0, 0.1, 400.0,
+                ))),
+            ))
+            .with_material(MaterialResource::new_ok(
+                ResourceKind::Embedded,
+                Material::standard(),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let azimuth_text = make_text(ctx, 0, "Sun Azimuth");
+        self.azimuth_slider = make_slider(ctx, 1, 0.0, 360.0, self.azimuth);
+        let elevation_text = make_text(ctx, 2, "Sun Elevation");
+        self.elevation_slider = make_slider(ctx, 3, -10.0, 90.0, self.elevation);
+        let density_text = make_text(ctx, 4, "Atmospheric Density");
+        self.density_slider = make_slider(ctx, 5, 0.1, 5.0, self.density);
+        self.procedural_sky_check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(6)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Procedural Sky")
+                .build(ctx),
+        )
+        .checked(Some(true))
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(230.0)
+                .with_desired_position(Vector2::new(5.0, 80.0)),
+        )
+        .with_title(WindowTitle::text("Sky Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(azimuth_text)
+                    .with_child(self.azimuth_slider)
+                    .with_child(elevation_text)
+                    .with_child(self.elevation_slider)
+                    .with_child(density_text)
+                    .with_child(self.density_slider)
+                    .with_child(self.procedural_sky_check_box),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/sky.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => {
+                    game.build_sky(shader, &mut ctx.scenes[game.scene]);
+                    game.set_procedural_sky(true, ctx);
+                }
+                Err(err) => Log::err(format!("Unable to load sky shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Procedural Sky\n{}\n{}",
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+
+            self.update_sun(context);
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.azimuth_slider {
+                self.azimuth = *value;
+            } else if message.destination() == self.elevation_slider {
+                self.elevation = *value;
+            } else if message.destination() == self.density_slider {
+                self.density = *value;
+            }
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.procedural_sky_check_box {
+                self.set_procedural_sky(*value, context);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
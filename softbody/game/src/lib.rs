@@ -0,0 +1,379 @@
+//! Game project.
+use crate::softbody::Softbody;
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        math::TriangleDefinition,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+use std::collections::HashMap;
+
+mod softbody;
+
+/// Every subdivision splits each triangle of the ball into four.
+const BALL_SUBDIVISIONS: usize = 2;
+const BALL_RADIUS: f32 = 0.75;
+const BALL_DROP_HEIGHT: f32 = 4.0;
+const FLOOR_SIZE: f32 = 20.0;
+
+const SCENE_NAME: &str = "softbody";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    ball: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    stiffness_slider: Handle<UiNode>,
+    damping_slider: Handle<UiNode>,
+    iterations_slider: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Creates an icosphere - a subdivided icosahedron. Unlike UV spheres it has no seams, so every
+/// point of the surface is a single vertex, shared by all its triangles.
+fn make_ball() -> SurfaceData {
+    let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+    let mut positions = vec![
+        Vector3::new(-1.0, t, 0.0),
+        Vector3::new(1.0, t, 0.0),
+        Vector3::new(-1.0, -t, 0.0),
+        Vector3::new(1.0, -t, 0.0),
+        Vector3::new(0.0, -1.0, t),
+        Vector3::new(0.0, 1.0, t),
+        Vector3::new(0.0, -1.0, -t),
+        Vector3::new(0.0, 1.0, -t),
+        Vector3::new(t, 0.0, -1.0),
+        Vector3::new(t, 0.0, 1.0),
+        Vector3::new(-t, 0.0, -1.0),
+        Vector3::new(-t, 0.0, 1.0),
+    ];
+    let mut triangles = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..BALL_SUBDIVISIONS {
+        // Neighbouring triangles share edges, so the middle of every edge is added only once.
+        let mut middles = HashMap::new();
+        let mut middle = |a: u32, b: u32| {
+            *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push((positions[a as usize] + positions[b as usize]).scale(0.5));
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = middle(a, b);
+                let bc = middle(b, c);
+                let ca = middle(c, a);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = positions
+        .iter()
+        .map(|position| {
+            let normal = position.normalize();
+            StaticVertex::from_pos_uv_normal(
+                normal.scale(BALL_RADIUS),
+                Vector2::new(
+                    0.5 + normal.z.atan2(normal.x) / std::f32::consts::TAU,
+                    normal.y,
+                ),
+                normal,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles.into_iter().map(TriangleDefinition).collect()),
+    )
+}
+
+/// The floor is a thin box, its top side is at zero height - where the floor plane of the ball
+/// is.
+fn make_floor() -> SurfaceData {
+    SurfaceData::make_cube(
+        Matrix4::new_translation(&Vector3::new(0.0, -0.05, 0.0))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(FLOOR_SIZE, 0.1, FLOOR_SIZE)),
+    )
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(2)
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    fn softbody<'a>(&self, context: &'a PluginContext) -> Option<&'a Softbody> {
+        context
+            .scenes
+            .try_get(self.scene)?
+            .graph
+            .try_get(self.ball)?
+            .try_get_script::<Softbody>()
+    }
+
+    fn softbody_mut<'a>(&self, context: &'a mut PluginContext) -> Option<&'a mut Softbody> {
+        context
+            .scenes
+            .try_get_mut(self.scene)?
+            .graph
+            .try_get_mut(self.ball)?
+            .try_get_script_mut::<Softbody>()
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Softbody>("Softbody");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.5, -7.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        10.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, Material::standard());
+        // The ball is squashed against the floor, folded triangles must stay visible.
+        let ball_material =
+            MaterialResource::new_ok(ResourceKind::Embedded, Material::standard_two_sides());
+
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                make_floor(),
+            ))
+            .with_material(material)
+            .build()])
+            .build(&mut scene.graph);
+
+        self.ball = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, BALL_DROP_HEIGHT, 0.0))
+                        .build(),
+                )
+                .with_script(Script::new(Softbody::default())),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            make_ball(),
+        ))
+        .with_material(ball_material)
+        .build()])
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let defaults = Softbody::default();
+        let stiffness_text = make_text(ctx, 0, "Stiffness");
+        self.stiffness_slider = make_slider(ctx, 1, 0.0, 1.0, 0.01, defaults.stiffness());
+        let damping_text = make_text(ctx, 2, "Damping");
+        self.damping_slider = make_slider(ctx, 3, 0.0, 5.0, 0.05, defaults.damping());
+        let iterations_text = make_text(ctx, 4, "Solver Iterations");
+        self.iterations_slider = make_slider(ctx, 5, 1.0, 32.0, 1.0, defaults.iterations() as f32);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(190.0)
+                .with_desired_position(Vector2::new(5.0, 80.0)),
+        )
+        .with_title(WindowTitle::text("Softbody Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(stiffness_text)
+                    .with_child(self.stiffness_slider)
+                    .with_child(damping_text)
+                    .with_child(self.damping_slider)
+                    .with_child(iterations_text)
+                    .with_child(self.iterations_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        let (particles, springs) = self
+            .softbody(context)
+            .map(|softbody| (softbody.particle_count(), softbody.spring_count()))
+            .unwrap_or_default();
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Softbody Simulation\nParticles: {particles}\nSprings: {springs}\n{}\n{}",
+                    graphics_context.renderer.get_statistics(),
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        let destination = message.destination();
+        if let Some(softbody) = self.softbody_mut(context) {
+            if destination == self.stiffness_slider {
+                softbody.set_stiffness(*value);
+            } else if destination == self.damping_slider {
+                softbody.set_damping(*value);
+            } else if destination == self.iterations_slider {
+                softbody.set_iterations(value.round() as u32);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
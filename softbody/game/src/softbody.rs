@@ -0,0 +1,286 @@
+//! Softbody simulation, that uses position based dynamics with springs between the particles.
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        log::Log,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    scene::mesh::{
+        buffer::{VertexAttributeUsage, VertexReadTrait, VertexWriteTrait},
+        Mesh,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+use std::collections::HashSet;
+
+/// Maximum time step of the simulation, larger steps make the body unstable.
+const MAX_TIME_STEP: f32 = 1.0 / 30.0;
+/// Fraction of the horizontal velocity, that is lost on every contact with the floor.
+const FRICTION: f32 = 0.1;
+
+#[derive(Debug, Clone)]
+struct Particle {
+    position: Vector3<f32>,
+    previous_position: Vector3<f32>,
+}
+
+#[derive(Debug, Clone)]
+struct Spring {
+    a: usize,
+    b: usize,
+    rest_length: f32,
+}
+
+/// Simulates a soft body using vertices of the first surface of a mesh as particles. The surface
+/// is split into tetrahedra - every triangle is connected to an extra particle in the center of
+/// the body, edges of the tetrahedra are springs, that keep the particles at their initial
+/// distance. The simulation runs in world space, so the body can bounce off the floor plane.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "5c1e8a3f-7d24-4b9e-b6f0-2a9d4e7c1b85")]
+#[visit(optional)]
+pub struct Softbody {
+    /// Gravity acceleration in world space.
+    gravity: InheritableVariable<Vector3<f32>>,
+    /// Stiffness of the springs in `[0; 1]` range, where `1` means rigid body.
+    stiffness: InheritableVariable<f32>,
+    /// Fraction of velocity, that is lost every second.
+    damping: InheritableVariable<f32>,
+    iterations: InheritableVariable<u32>,
+    /// Height of the horizontal floor plane in world space.
+    floor_height: InheritableVariable<f32>,
+    /// Fraction of the vertical velocity, that is kept after hitting the floor.
+    restitution: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    particles: Vec<Particle>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    springs: Vec<Spring>,
+}
+
+impl Default for Softbody {
+    fn default() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0).into(),
+            stiffness: 0.5.into(),
+            damping: 0.5.into(),
+            iterations: 8.into(),
+            floor_height: 0.0.into(),
+            restitution: 0.6.into(),
+            particles: Default::default(),
+            springs: Default::default(),
+        }
+    }
+}
+
+impl Softbody {
+    pub fn set_stiffness(&mut self, stiffness: f32) {
+        self.stiffness.set_value_and_mark_modified(stiffness);
+    }
+
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping.set_value_and_mark_modified(damping);
+    }
+
+    pub fn set_iterations(&mut self, iterations: u32) {
+        self.iterations.set_value_and_mark_modified(iterations);
+    }
+
+    pub fn stiffness(&self) -> f32 {
+        *self.stiffness
+    }
+
+    pub fn damping(&self) -> f32 {
+        *self.damping
+    }
+
+    pub fn iterations(&self) -> u32 {
+        *self.iterations
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn spring_count(&self) -> usize {
+        self.springs.len()
+    }
+
+    /// The center particle is not a vertex of the mesh, it is stored after the vertices.
+    fn center(&self) -> Vector3<f32> {
+        self.particles
+            .last()
+            .map(|particle| particle.position)
+            .unwrap_or_default()
+    }
+
+    /// Reads particles from the vertex buffer of the mesh and builds springs from the edges of
+    /// the triangles and from the lines, that connect the vertices with the center.
+    fn read_particles(&mut self, mesh: &Mesh) {
+        let Some(surface) = mesh.surfaces().first() else {
+            return;
+        };
+        let resource = surface.data();
+        if !resource.is_ok() {
+            return;
+        }
+        let data = resource.data_ref();
+
+        let to_world = mesh.global_transform();
+        let mut positions = data
+            .vertex_buffer
+            .iter()
+            .filter_map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position).ok())
+            .map(|position| to_world.transform_point(&Point3::from(position)).coords)
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            return;
+        }
+        let center = positions.iter().sum::<Vector3<f32>>() / positions.len() as f32;
+        let center_index = positions.len();
+        positions.push(center);
+
+        // Triangles share edges, every edge must produce only one spring.
+        let mut edges = HashSet::new();
+        for triangle in data.geometry_buffer.iter() {
+            let [a, b, c] = triangle.0.map(|index| index as usize);
+            for (a, b) in [(a, b), (b, c), (c, a)] {
+                edges.insert((a.min(b), a.max(b)));
+            }
+        }
+        // Without the inner springs the surface has no volume and collapses.
+        edges.extend((0..center_index).map(|index| (index, center_index)));
+
+        self.springs = edges
+            .into_iter()
+            .map(|(a, b)| Spring {
+                a,
+                b,
+                rest_length: positions[a].metric_distance(&positions[b]),
+            })
+            .collect();
+        self.particles = positions
+            .into_iter()
+            .map(|position| Particle {
+                position,
+                previous_position: position,
+            })
+            .collect();
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        let velocity_scale = (1.0 - *self.damping * dt).clamp(0.0, 1.0);
+        let acceleration = self.gravity.scale(dt * dt);
+        for particle in self.particles.iter_mut() {
+            let velocity = (particle.position - particle.previous_position).scale(velocity_scale);
+            particle.previous_position = particle.position;
+            particle.position += velocity + acceleration;
+        }
+    }
+
+    fn solve_springs(&mut self) {
+        let iterations = (*self.iterations).max(1);
+        // Stiffness is applied on every iteration, so it is scaled to make the result independent
+        // of the amount of iterations.
+        let stiffness =
+            1.0 - (1.0 - (*self.stiffness).clamp(0.0, 1.0)).powf(1.0 / iterations as f32);
+
+        for _ in 0..iterations {
+            for spring in self.springs.iter() {
+                let delta = self.particles[spring.a].position - self.particles[spring.b].position;
+                let length = delta.norm();
+                if length < f32::EPSILON {
+                    continue;
+                }
+
+                // Both particles have the same mass, so each of them is moved by a half of the
+                // extension.
+                let extension = length - spring.rest_length;
+                let correction = delta.scale(0.5 * stiffness * extension / length);
+                self.particles[spring.a].position -= correction;
+                self.particles[spring.b].position += correction;
+            }
+        }
+    }
+
+    /// Pushes the particles out of the floor and reflects their vertical velocity.
+    fn collide_with_floor(&mut self) {
+        let floor_height = *self.floor_height;
+        for particle in self.particles.iter_mut() {
+            if particle.position.y >= floor_height {
+                continue;
+            }
+            let velocity = particle.position - particle.previous_position;
+            particle.position.y = floor_height;
+            // Velocity is implicit in Verlet integration, so the previous position is moved to
+            // get the reflected velocity on the next step.
+            particle.previous_position = Vector3::new(
+                particle.position.x - velocity.x * (1.0 - FRICTION),
+                floor_height + velocity.y * *self.restitution,
+                particle.position.z - velocity.z * (1.0 - FRICTION),
+            );
+        }
+    }
+
+    fn write_particles(&self, mesh: &Mesh) {
+        let Some(surface) = mesh.surfaces().first() else {
+            return;
+        };
+        let to_local = mesh
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let center = self.center();
+        let resource = surface.data();
+        let mut data = resource.data_ref();
+
+        for (mut vertex, particle) in data
+            .vertex_buffer
+            .modify()
+            .iter_mut()
+            .zip(self.particles.iter())
+        {
+            let position = to_local
+                .transform_point(&Point3::from(particle.position))
+                .coords;
+            // The body is convex, so the direction from the center is a good enough normal and
+            // it does not depend on the winding of the triangles.
+            let normal = to_local
+                .transform_vector(&(particle.position - center))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+            Log::verify(vertex.write_3_f32(VertexAttributeUsage::Position, position));
+            Log::verify(vertex.write_3_f32(VertexAttributeUsage::Normal, normal));
+        }
+    }
+}
+
+impl ScriptTrait for Softbody {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        if let Some(mesh) = ctx.scene.graph.try_get_of_type::<Mesh>(ctx.handle) {
+            self.read_particles(mesh);
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let Some(mesh) = ctx.scene.graph.try_get_of_type::<Mesh>(ctx.handle) else {
+            return;
+        };
+
+        let dt = ctx.dt.min(MAX_TIME_STEP);
+        self.integrate(dt);
+        self.solve_springs();
+        self.collide_with_floor();
+        self.write_particles(mesh);
+    }
+}
@@ -1,9 +1,36 @@
 //! Executor with your game connected to it as a plugin.
-use fyrox::engine::executor::Executor;
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
+use fyrox::{
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+};
 use sound::Game;
 
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+
 fn main() {
-    let mut executor = Executor::new();
-    executor.add_plugin(Game::default());
+    // Frames are paced by `FramePacer`, which needs vsync to be disabled.
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            vsync: false,
+            ..Default::default()
+        },
+    );
+    // `--listen` enables discovery of other instances of the demo in local network.
+    if std::env::args().any(|arg| arg == "--listen") {
+        executor.add_plugin(Game::with_network_discovery());
+    } else {
+        executor.add_plugin(Game::default());
+    }
+    #[cfg(feature = "record")]
+    if let Some(recorder) = Recorder::from_args() {
+        executor.add_plugin(recorder);
+    }
+    executor.add_plugin(FramePacer::default());
     executor.run()
 }
@@ -0,0 +1,146 @@
+//! Debug overlay, that shows sound sources of the scene: their ranges, state and gain.
+use common_scripts::world_to_screen;
+use fyrox::{
+    core::{algebra::Vector3, color::Color, pool::Handle},
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, UiNode, UserInterface,
+    },
+    scene::{
+        camera::Camera,
+        node::Node,
+        sound::{Sound, Status},
+        Scene,
+    },
+};
+use std::collections::HashMap;
+
+/// Radius of the marker at the position of a source.
+const MARKER_RADIUS: f32 = 0.15;
+/// Offset of the label from the position of a source, so it does not cover the marker.
+const LABEL_OFFSET: Vector3<f32> = Vector3::new(0.0, 0.4, 0.0);
+const RADIUS_COLOR: Color = Color::opaque(255, 200, 0);
+const MAX_DISTANCE_COLOR: Color = Color::opaque(255, 90, 0);
+
+/// Sound sources of the engine are omnidirectional, so there are no cones to show - only the
+/// radius (the distance at which the attenuation starts) and the max distance (the distance
+/// at which the attenuation stops).
+#[derive(Default, Debug)]
+pub struct AudioDebugOverlay {
+    enabled: bool,
+    /// Floating labels of the sources.
+    labels: HashMap<Handle<Node>, Handle<UiNode>>,
+}
+
+impl AudioDebugOverlay {
+    pub fn toggle(&mut self, scene: Option<&mut Scene>, ui: &UserInterface) {
+        self.enabled = !self.enabled;
+        if !self.enabled {
+            for (_, label) in self.labels.drain() {
+                ui.send_message(WidgetMessage::remove(label, MessageDirection::ToWidget));
+            }
+            if let Some(scene) = scene {
+                scene.drawing_context.clear_lines();
+            }
+        }
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, ui: &mut UserInterface) {
+        if !self.enabled {
+            return;
+        }
+
+        scene.drawing_context.clear_lines();
+
+        // Labels of removed sources are not needed anymore.
+        self.labels.retain(|source, label| {
+            let alive = scene.graph.try_get_of_type::<Sound>(*source).is_some();
+            if !alive {
+                ui.send_message(WidgetMessage::remove(*label, MessageDirection::ToWidget));
+            }
+            alive
+        });
+
+        let camera = scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.cast::<Camera>().map_or(false, |c| c.is_enabled()))
+            .map(|(handle, _)| handle)
+            .unwrap_or_default();
+
+        let sources = scene
+            .graph
+            .pair_iter()
+            .filter_map(|(handle, node)| node.cast::<Sound>().map(|sound| (handle, sound)))
+            .map(|(handle, sound)| {
+                (
+                    handle,
+                    sound.global_position(),
+                    sound.radius(),
+                    sound.max_distance(),
+                    sound.status(),
+                    format!(
+                        "{}\nGain: {:.2}\n{:?}",
+                        sound.name(),
+                        sound.gain(),
+                        sound.status()
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (handle, position, radius, max_distance, status, text) in sources {
+            let marker_color = if status == Status::Playing {
+                Color::GREEN
+            } else {
+                Color::opaque(128, 128, 128)
+            };
+            let drawing_context = &mut scene.drawing_context;
+            drawing_context.draw_sphere(position, 10, 10, MARKER_RADIUS, marker_color);
+            drawing_context.draw_wire_sphere(position, radius, 32, RADIUS_COLOR);
+            // Max distance is unlimited by default.
+            if max_distance < f32::MAX {
+                drawing_context.draw_wire_sphere(position, max_distance, 32, MAX_DISTANCE_COLOR);
+            }
+
+            let label = *self.labels.entry(handle).or_insert_with(|| {
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(160.0)
+                        .with_hit_test_visibility(false),
+                )
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .build(&mut ui.build_ctx())
+            });
+
+            ui.send_message(TextMessage::text(label, MessageDirection::ToWidget, text));
+            match world_to_screen(scene, camera, position + LABEL_OFFSET) {
+                Some(screen_position) => {
+                    let size = ui.node(label).actual_local_size();
+                    let screen_position = screen_position.component_mul(&ui.screen_size());
+                    ui.send_message(WidgetMessage::desired_position(
+                        label,
+                        MessageDirection::ToWidget,
+                        screen_position - size.scale(0.5),
+                    ));
+                    ui.send_message(WidgetMessage::visibility(
+                        label,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                }
+                None => {
+                    // The source is behind the camera.
+                    ui.send_message(WidgetMessage::visibility(
+                        label,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1052 @@
+//! Dialogue editor - a node graph, where every node is a line of a conversation and every wire is
+//! a choice, that leads from one line to another. The graph is drawn by a custom widget, that
+//! follows the design of the blueprint node graph of the animation demo. The conversation can be
+//! played from the start node: every line is shown as a subtitle and plays its voice line
+//! (`data/dialogue/<id>.ogg`), if there is one. The graph is saved to `data/dialogue.ron`.
+use fyrox::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::Vector2, color::Color, log::Log, math::Rect, pool::Handle, reflect::prelude::*,
+        type_traits::prelude::*, visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        canvas::CanvasBuilder,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        formatted_text::{FormattedText, FormattedTextBuilder, WrapMode},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, MouseButton, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Control, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    resource::font::FontResource,
+    scene::{
+        base::BaseBuilder,
+        node::Node,
+        sound::{SoundBuffer, SoundBufferResource, SoundBuilder, Status},
+        Scene,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::{Deref, DerefMut},
+    path::Path,
+};
+
+pub const DIALOGUE_PATH: &str = "data/dialogue.ron";
+/// Folder with the voice lines, the voice line of a node is `<id>.ogg`. Voice lines are optional,
+/// the lines without them are shown as subtitles only.
+const VOICE_FOLDER: &str = "data/dialogue";
+const NODE_WIDTH: f32 = 180.0;
+const HEADER_HEIGHT: f32 = 22.0;
+const BODY_HEIGHT: f32 = 60.0;
+const PORT_RADIUS: f32 = 6.0;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.0;
+/// Labels are not readable when the canvas is zoomed out too much, so they are not drawn.
+const MIN_LABEL_ZOOM: f32 = 0.6;
+/// A line stays on the screen at least this long plus some time for every character, so lines
+/// without a voice could be read.
+const MIN_LINE_DURATION: f32 = 1.5;
+const SECONDS_PER_CHARACTER: f32 = 0.05;
+/// A line is skipped after this time, even if its voice is still playing.
+const MAX_LINE_DURATION: f32 = 30.0;
+
+/// A line of a conversation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueNode {
+    pub id: u32,
+    pub text: String,
+    /// Position of the top-left corner on the canvas.
+    pub position: (f32, f32),
+    /// Ids of the lines, that can follow this line.
+    pub choices: Vec<u32>,
+}
+
+impl DialogueNode {
+    fn position(&self) -> Vector2<f32> {
+        Vector2::new(self.position.0, self.position.1)
+    }
+
+    fn bounds(&self) -> Rect<f32> {
+        Rect::new(
+            self.position.0,
+            self.position.1,
+            NODE_WIDTH,
+            HEADER_HEIGHT + BODY_HEIGHT,
+        )
+    }
+
+    fn port_position(&self, is_output: bool) -> Vector2<f32> {
+        let x = if is_output { NODE_WIDTH } else { 0.0 };
+        self.position() + Vector2::new(x, HEADER_HEIGHT + BODY_HEIGHT * 0.5)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct DialogueGraph {
+    /// Id of the line, that starts the conversation.
+    pub start: u32,
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl DialogueGraph {
+    /// A short conversation, that is shown until the graph is saved.
+    fn sample() -> Self {
+        let node = |id, text: &str, position, choices: &[u32]| DialogueNode {
+            id,
+            text: text.to_string(),
+            position,
+            choices: choices.to_vec(),
+        };
+        Self {
+            start: 0,
+            nodes: vec![
+                node(0, "Hey, have you heard that music?", (20.0, 60.0), &[1, 2]),
+                node(1, "Yes, it comes from the courtyard.", (260.0, 10.0), &[3]),
+                node(2, "What music?", (260.0, 130.0), &[3]),
+                node(3, "Let's go and take a look.", (500.0, 70.0), &[]),
+            ],
+        }
+    }
+
+    pub fn node(&self, id: u32) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn node_mut(&mut self, id: u32) -> Option<&mut DialogueNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    fn add_node(&mut self, position: Vector2<f32>) {
+        let id = self.nodes.iter().map(|node| node.id + 1).max().unwrap_or(0);
+        self.nodes.push(DialogueNode {
+            id,
+            text: "New line".to_string(),
+            position: (position.x, position.y),
+            choices: Vec::new(),
+        });
+    }
+
+    /// Adds a choice from one line to another, or removes it if it already exists.
+    fn toggle_choice(&mut self, from: u32, to: u32) {
+        if from == to || self.node(to).is_none() {
+            return;
+        }
+        if let Some(node) = self.node_mut(from) {
+            if let Some(index) = node.choices.iter().position(|choice| *choice == to) {
+                node.choices.remove(index);
+            } else {
+                node.choices.push(to);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogueCanvasMessage {
+    /// Replaces the graph, when sent to the widget. When sent from the widget, it means that the
+    /// user has changed the graph.
+    Graph(DialogueGraph),
+    /// Adds a new line in the center of the view.
+    AddNode,
+    /// The user wants to edit the text of a line. The position and the size are of the body of
+    /// the line, relative to the widget.
+    EditText {
+        id: u32,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+    },
+    /// The user has clicked somewhere on the canvas, so the editing of the text is finished.
+    EndEdit,
+    SetText {
+        id: u32,
+        text: String,
+    },
+    /// Highlights the line, that is being played.
+    Highlight(Option<u32>),
+}
+
+impl DialogueCanvasMessage {
+    define_constructor!(
+        DialogueCanvasMessage:Graph => fn graph(DialogueGraph), layout: false
+    );
+    define_constructor!(
+        DialogueCanvasMessage:AddNode => fn add_node(), layout: false
+    );
+    define_constructor!(
+        DialogueCanvasMessage:EditText => fn edit_text(
+            id: u32, position: Vector2<f32>, size: Vector2<f32>
+        ), layout: false
+    );
+    define_constructor!(
+        DialogueCanvasMessage:EndEdit => fn end_edit(), layout: false
+    );
+    define_constructor!(
+        DialogueCanvasMessage:SetText => fn set_text(id: u32, text: String), layout: false
+    );
+    define_constructor!(
+        DialogueCanvasMessage:Highlight => fn highlight(Option<u32>), layout: false
+    );
+}
+
+/// State of the mouse between pressing and releasing a button.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+enum Drag {
+    #[default]
+    None,
+    /// A node is moved, the offset is from the cursor to the top-left corner of the node.
+    Node { id: u32, offset: Vector2<f32> },
+    /// A new wire goes from the port of the node to the cursor.
+    Wire { id: u32, is_output: bool },
+    /// The canvas is panned, the position is the last position of the cursor on the screen.
+    Pan(Vector2<f32>),
+}
+
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "3b8e5f17-6c2d-4a94-b1e7-0d9f4c2a8e53")]
+struct DialogueCanvas {
+    widget: Widget,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    graph: DialogueGraph,
+    /// Offset of the canvas in screen units.
+    pan: Vector2<f32>,
+    zoom: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    highlighted: Option<u32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    font: FontResource,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    drag: Drag,
+    /// Position of the cursor on the canvas.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cursor: Vector2<f32>,
+}
+
+define_widget_deref!(DialogueCanvas);
+
+impl DialogueCanvas {
+    /// Converts a point on the canvas to the local space of the widget, where it is drawn.
+    fn canvas_to_local(&self, point: Vector2<f32>) -> Vector2<f32> {
+        self.bounding_rect().position + self.pan + point.scale(self.zoom)
+    }
+
+    fn screen_to_canvas(&self, point: Vector2<f32>) -> Vector2<f32> {
+        (self.screen_to_local(point) - self.bounding_rect().position - self.pan)
+            .scale(1.0 / self.zoom)
+    }
+
+    /// Returns the id of the node and whether the port is the output.
+    fn port_at(&self, point: Vector2<f32>) -> Option<(u32, bool)> {
+        self.graph.nodes.iter().rev().find_map(|node| {
+            [false, true].into_iter().find_map(|is_output| {
+                (node.port_position(is_output).metric_distance(&point) <= PORT_RADIUS * 1.5)
+                    .then_some((node.id, is_output))
+            })
+        })
+    }
+
+    fn node_at(&self, point: Vector2<f32>) -> Option<u32> {
+        self.graph
+            .nodes
+            .iter()
+            .rev()
+            .find(|node| node.bounds().contains(point))
+            .map(|node| node.id)
+    }
+
+    fn notify_changed(&self, ui: &UserInterface) {
+        ui.send_message(DialogueCanvasMessage::graph(
+            self.handle(),
+            MessageDirection::FromWidget,
+            self.graph.clone(),
+        ));
+    }
+
+    fn push_wire(
+        &self,
+        drawing_context: &mut DrawingContext,
+        from: Vector2<f32>,
+        to: Vector2<f32>,
+        color: Color,
+    ) {
+        let from = self.canvas_to_local(from);
+        let to = self.canvas_to_local(to);
+        // Wires leave outputs to the right and enter inputs from the left.
+        let tangent = Vector2::new(((to.x - from.x).abs() * 0.5).max(30.0 * self.zoom), 0.0);
+        drawing_context.push_bezier(from, from + tangent, to - tangent, to, 24, 2.0);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(color),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    fn push_rect(&self, drawing_context: &mut DrawingContext, rect: Rect<f32>, color: Color) {
+        let position = self.canvas_to_local(rect.position);
+        drawing_context.push_rect_filled(
+            &Rect::new(
+                position.x,
+                position.y,
+                rect.w() * self.zoom,
+                rect.h() * self.zoom,
+            ),
+            None,
+        );
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(color),
+            CommandTexture::None,
+            None,
+        );
+    }
+
+    /// Draws the text with its top-left corner at the position. The text is wrapped, if it does
+    /// not fit into the width.
+    fn push_text(
+        &self,
+        drawing_context: &mut DrawingContext,
+        text: &str,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+    ) {
+        if self.zoom < MIN_LABEL_ZOOM {
+            return;
+        }
+        let mut formatted_text: FormattedText = FormattedTextBuilder::new(self.font.clone())
+            .with_text(text)
+            .with_brush(Brush::Solid(Color::WHITE))
+            .with_wrap(WrapMode::Word)
+            .with_constraint(size.scale(self.zoom))
+            .build();
+        formatted_text.build();
+        drawing_context.draw_text(
+            self.clip_bounds(),
+            self.canvas_to_local(position),
+            &formatted_text,
+        );
+    }
+
+    fn draw_node(&self, drawing_context: &mut DrawingContext, node: &DialogueNode) {
+        let bounds = node.bounds();
+        self.push_rect(drawing_context, bounds, Color::opaque(50, 50, 55));
+        let header_color = if self.highlighted == Some(node.id) {
+            Color::opaque(190, 130, 40)
+        } else if self.graph.start == node.id {
+            Color::opaque(60, 130, 70)
+        } else {
+            Color::opaque(70, 90, 140)
+        };
+        self.push_rect(
+            drawing_context,
+            Rect::new(bounds.x(), bounds.y(), bounds.w(), HEADER_HEIGHT),
+            header_color,
+        );
+        let title = if self.graph.start == node.id {
+            format!("#{} (Start)", node.id)
+        } else {
+            format!("#{}", node.id)
+        };
+        self.push_text(
+            drawing_context,
+            &title,
+            node.position() + Vector2::new(6.0, 3.0),
+            Vector2::new(NODE_WIDTH - 12.0, HEADER_HEIGHT),
+        );
+        self.push_text(
+            drawing_context,
+            &node.text,
+            node.position() + Vector2::new(PORT_RADIUS + 4.0, HEADER_HEIGHT + 4.0),
+            Vector2::new(NODE_WIDTH - 2.0 * (PORT_RADIUS + 4.0), BODY_HEIGHT - 8.0),
+        );
+
+        for is_output in [false, true] {
+            drawing_context.push_circle(
+                self.canvas_to_local(node.port_position(is_output)),
+                PORT_RADIUS * self.zoom,
+                12,
+                Color::WHITE,
+            );
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(Color::opaque(230, 230, 230)),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn handle_mouse_down(
+        &mut self,
+        ui: &mut UserInterface,
+        pos: Vector2<f32>,
+        button: MouseButton,
+    ) {
+        ui.send_message(DialogueCanvasMessage::end_edit(
+            self.handle(),
+            MessageDirection::FromWidget,
+        ));
+
+        let point = self.screen_to_canvas(pos);
+        self.drag = match button {
+            MouseButton::Left => {
+                if let Some((id, is_output)) = self.port_at(point) {
+                    Drag::Wire { id, is_output }
+                } else if let Some(id) = self.node_at(point) {
+                    // The dragged node goes on top of the others.
+                    let index = self.graph.nodes.iter().position(|node| node.id == id);
+                    let node = self.graph.nodes.remove(index.unwrap_or_default());
+                    let offset = node.position() - point;
+                    self.graph.nodes.push(node);
+                    Drag::Node { id, offset }
+                } else {
+                    Drag::None
+                }
+            }
+            MouseButton::Right => {
+                if let Some(id) = self.node_at(point) {
+                    self.graph.start = id;
+                    self.notify_changed(ui);
+                }
+                Drag::None
+            }
+            MouseButton::Middle => Drag::Pan(pos),
+            _ => Drag::None,
+        };
+        self.cursor = point;
+        if self.drag != Drag::None {
+            ui.capture_mouse(self.handle());
+        }
+    }
+
+    fn handle_mouse_move(&mut self, pos: Vector2<f32>) {
+        match self.drag {
+            Drag::Node { id, offset } => {
+                let point = self.screen_to_canvas(pos) + offset;
+                if let Some(node) = self.graph.node_mut(id) {
+                    node.position = (point.x, point.y);
+                }
+            }
+            Drag::Pan(last) => {
+                self.pan += pos - last;
+                self.drag = Drag::Pan(pos);
+            }
+            Drag::Wire { .. } | Drag::None => (),
+        }
+        self.cursor = self.screen_to_canvas(pos);
+    }
+
+    fn handle_mouse_up(&mut self, ui: &mut UserInterface, pos: Vector2<f32>) {
+        match self.drag {
+            Drag::Wire { id, is_output } => {
+                // The wire goes from the output, no matter which port was dragged.
+                if let Some((other, other_is_output)) = self.port_at(self.screen_to_canvas(pos)) {
+                    if is_output != other_is_output {
+                        let (from, to) = if is_output { (id, other) } else { (other, id) };
+                        self.graph.toggle_choice(from, to);
+                        self.notify_changed(ui);
+                    }
+                }
+            }
+            Drag::Node { .. } => self.notify_changed(ui),
+            Drag::Pan(_) | Drag::None => (),
+        }
+        if self.drag != Drag::None {
+            self.drag = Drag::None;
+            ui.release_mouse_capture();
+        }
+    }
+
+    /// Zooms the canvas around the cursor, so the point under it stays in place.
+    fn handle_mouse_wheel(&mut self, pos: Vector2<f32>, amount: f32) {
+        let point = self.screen_to_canvas(pos);
+        self.zoom = (self.zoom * (1.0 + amount * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.pan += self.screen_to_local(pos) - self.canvas_to_local(point);
+    }
+
+    fn handle_double_click(&self, ui: &UserInterface) {
+        let Some(node) = self.node_at(self.cursor).and_then(|id| self.graph.node(id)) else {
+            return;
+        };
+        ui.send_message(DialogueCanvasMessage::edit_text(
+            self.handle(),
+            MessageDirection::FromWidget,
+            node.id,
+            self.pan + (node.position() + Vector2::new(0.0, HEADER_HEIGHT)).scale(self.zoom),
+            Vector2::new(NODE_WIDTH, BODY_HEIGHT).scale(self.zoom),
+        ));
+    }
+
+    fn handle_canvas_message(&mut self, ui: &UserInterface, message: &DialogueCanvasMessage) {
+        match message {
+            DialogueCanvasMessage::Graph(graph) => {
+                self.graph = graph.clone();
+            }
+            DialogueCanvasMessage::AddNode => {
+                let center = (self.actual_local_size().scale(0.5) - self.pan)
+                    .scale(1.0 / self.zoom)
+                    - Vector2::new(NODE_WIDTH, HEADER_HEIGHT + BODY_HEIGHT).scale(0.5);
+                self.graph.add_node(center);
+                self.notify_changed(ui);
+            }
+            DialogueCanvasMessage::SetText { id, text } => {
+                if let Some(node) = self.graph.node_mut(*id) {
+                    node.text.clone_from(text);
+                    self.notify_changed(ui);
+                }
+            }
+            DialogueCanvasMessage::Highlight(id) => {
+                self.highlighted = *id;
+            }
+            DialogueCanvasMessage::EditText { .. } | DialogueCanvasMessage::EndEdit => return,
+        }
+        self.invalidate_visual();
+    }
+}
+
+impl Control for DialogueCanvas {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        drawing_context.push_rect_filled(&self.bounding_rect(), None);
+        drawing_context.commit(
+            self.clip_bounds(),
+            Brush::Solid(Color::opaque(30, 30, 32)),
+            CommandTexture::None,
+            None,
+        );
+
+        for node in self.graph.nodes.iter() {
+            for target in node.choices.iter().filter_map(|id| self.graph.node(*id)) {
+                self.push_wire(
+                    drawing_context,
+                    node.port_position(true),
+                    target.port_position(false),
+                    Color::opaque(230, 230, 230),
+                );
+            }
+        }
+
+        for node in self.graph.nodes.iter() {
+            self.draw_node(drawing_context, node);
+        }
+
+        // The wire, that is being dragged, is drawn on top of everything.
+        if let Drag::Wire { id, is_output } = self.drag {
+            if let Some(node) = self.graph.node(id) {
+                let position = node.port_position(is_output);
+                let (from, to) = if is_output {
+                    (position, self.cursor)
+                } else {
+                    (self.cursor, position)
+                };
+                self.push_wire(drawing_context, from, to, Color::opaque(230, 230, 230));
+            }
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if message.destination() != self.handle() {
+            return;
+        }
+        if let Some(msg) = message.data::<DialogueCanvasMessage>() {
+            if message.direction() == MessageDirection::ToWidget {
+                self.handle_canvas_message(ui, msg);
+            }
+            return;
+        }
+        let Some(msg) = message.data::<WidgetMessage>() else {
+            return;
+        };
+        match msg {
+            WidgetMessage::MouseDown { pos, button } => {
+                self.handle_mouse_down(ui, *pos, *button);
+            }
+            WidgetMessage::MouseMove { pos, .. } => {
+                self.handle_mouse_move(*pos);
+            }
+            WidgetMessage::MouseUp { pos, .. } => {
+                self.handle_mouse_up(ui, *pos);
+            }
+            WidgetMessage::MouseWheel { pos, amount } => {
+                self.handle_mouse_wheel(*pos, *amount);
+            }
+            WidgetMessage::DoubleClick {
+                button: MouseButton::Left,
+            } => {
+                self.handle_double_click(ui);
+            }
+            _ => return,
+        }
+        message.set_handled(true);
+        self.invalidate_visual();
+    }
+}
+
+pub struct DialogueCanvasBuilder {
+    widget_builder: WidgetBuilder,
+    graph: DialogueGraph,
+}
+
+impl DialogueCanvasBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            graph: Default::default(),
+        }
+    }
+
+    pub fn with_graph(mut self, graph: DialogueGraph) -> Self {
+        self.graph = graph;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let canvas = DialogueCanvas {
+            widget: self.widget_builder.build(),
+            graph: self.graph,
+            pan: Default::default(),
+            zoom: 1.0,
+            highlighted: None,
+            font: ctx.default_font(),
+            drag: Drag::None,
+            cursor: Default::default(),
+        };
+
+        ctx.add_node(UiNode::new(canvas))
+    }
+}
+
+/// A line, that is being played.
+#[derive(Debug)]
+struct Playback {
+    id: u32,
+    /// Sound of the voice line, `Handle::NONE` if the line has no voice.
+    sound: Handle<Node>,
+    voice: Option<SoundBufferResource>,
+    elapsed: f32,
+    /// The line has ended and has more than one choice, so the player has to pick one.
+    waiting_for_choice: bool,
+}
+
+fn line_duration(text: &str) -> f32 {
+    MIN_LINE_DURATION + text.chars().count() as f32 * SECONDS_PER_CHARACTER
+}
+
+/// Removes the sound of a voice line, unless it has already ended and removed itself.
+fn remove_sound(scene: &mut Scene, sound: Handle<Node>) {
+    if scene.graph.is_valid_handle(sound) {
+        scene.graph.remove_node(sound);
+    }
+}
+
+/// The window of the editor and the player of the conversations.
+#[derive(Default, Debug)]
+pub struct DialogueEditor {
+    window: Handle<UiNode>,
+    canvas: Handle<UiNode>,
+    text_box: Handle<UiNode>,
+    add: Handle<UiNode>,
+    play: Handle<UiNode>,
+    save: Handle<UiNode>,
+    subtitle: Handle<UiNode>,
+    /// Copy of the graph of the canvas.
+    graph: DialogueGraph,
+    /// Id of the line, which text is being edited.
+    editing: Option<u32>,
+    playback: Option<Playback>,
+    /// Index of the choice, that the player has picked.
+    choice: Option<usize>,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_width(80.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl DialogueEditor {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let graph = DialogueGraph::sample();
+
+        let add = make_button(ctx, "Add Line");
+        let play = make_button(ctx, "Play");
+        let save = make_button(ctx, "Save");
+        let canvas = DialogueCanvasBuilder::new(WidgetBuilder::new().on_row(1))
+            .with_graph(graph.clone())
+            .build(ctx);
+        let text_box = TextBoxBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_multiline(true)
+            .with_wrap(WrapMode::Word)
+            .build(ctx);
+        // The text box is shown on top of the line, which text is edited.
+        let overlay = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_hit_test_visibility(false)
+                .with_child(text_box),
+        )
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(720.0).with_height(400.0))
+            .with_title(WindowTitle::text(
+                "Dialogue Editor - [LMB] drag lines, drag from a right port to a left port to add \
+                 or remove a choice, [RMB] set start, double click to edit, [MMB] pan, [Wheel] zoom",
+            ))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child(add)
+                                    .with_child(play)
+                                    .with_child(save),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child(canvas)
+                        .with_child(overlay),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        let subtitle = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_foreground(Brush::Solid(Color::opaque(255, 230, 160)))
+                // Above the subtitles of the music.
+                .with_margin(Thickness {
+                    left: 320.0,
+                    top: 0.0,
+                    right: 320.0,
+                    bottom: 90.0,
+                }),
+        )
+        .with_font_size(22.0)
+        .with_wrap(WrapMode::Word)
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(ctx);
+
+        Self {
+            window,
+            canvas,
+            text_box,
+            add,
+            play,
+            save,
+            subtitle,
+            graph,
+            ..Default::default()
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+            true,
+        ));
+    }
+
+    /// Replaces the graph with the saved one.
+    pub fn load(&mut self, bytes: &[u8], ui: &UserInterface) -> Result<(), String> {
+        self.graph = ron::de::from_bytes(bytes).map_err(|err| err.to_string())?;
+        ui.send_message(DialogueCanvasMessage::graph(
+            self.canvas,
+            MessageDirection::ToWidget,
+            self.graph.clone(),
+        ));
+        Ok(())
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(&self.graph, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(DIALOGUE_PATH, text).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => Log::info(format!("Dialogue saved to {DIALOGUE_PATH}")),
+            Err(err) => Log::err(format!("Unable to save dialogue. Reason: {err}")),
+        }
+    }
+
+    /// Picks a choice of the current line, the index starts from zero.
+    pub fn choose(&mut self, index: usize) {
+        if self
+            .playback
+            .as_ref()
+            .is_some_and(|playback| playback.waiting_for_choice)
+        {
+            self.choice = Some(index);
+        }
+    }
+
+    fn show_subtitle(&self, ui: &UserInterface, text: Option<String>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.subtitle,
+            MessageDirection::ToWidget,
+            text.is_some(),
+        ));
+        if let Some(text) = text {
+            ui.send_message(TextMessage::text(
+                self.subtitle,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+
+    fn stop(&mut self, scene: Option<&mut Scene>, ui: &UserInterface) {
+        if let (Some(playback), Some(scene)) = (self.playback.take(), scene) {
+            remove_sound(scene, playback.sound);
+        }
+        self.choice = None;
+        self.show_subtitle(ui, None);
+        ui.send_message(DialogueCanvasMessage::highlight(
+            self.canvas,
+            MessageDirection::ToWidget,
+            None,
+        ));
+    }
+
+    /// Starts the line and plays its voice.
+    fn play_line(
+        &mut self,
+        id: u32,
+        scene: &mut Scene,
+        resource_manager: &ResourceManager,
+        ui: &UserInterface,
+    ) {
+        let Some(node) = self.graph.node(id) else {
+            self.stop(Some(scene), ui);
+            return;
+        };
+
+        // The sound of the previous line is still playing, if the line was skipped.
+        if let Some(playback) = self.playback.take() {
+            remove_sound(scene, playback.sound);
+        }
+
+        let path = format!("{VOICE_FOLDER}/{id}.ogg");
+        let voice = Path::new(&path)
+            .exists()
+            .then(|| resource_manager.request::<SoundBuffer>(path));
+        let sound = match voice.as_ref() {
+            Some(voice) => {
+                SoundBuilder::new(BaseBuilder::new().with_name(format!("Dialogue Line {id}")))
+                    .with_buffer(Some(voice.clone()))
+                    // Voice lines are not positioned in the world.
+                    .with_spatial_blend_factor(0.0)
+                    .with_status(Status::Playing)
+                    .with_play_once(true)
+                    .build(&mut scene.graph)
+            }
+            None => Handle::NONE,
+        };
+
+        self.show_subtitle(ui, Some(node.text.clone()));
+        ui.send_message(DialogueCanvasMessage::highlight(
+            self.canvas,
+            MessageDirection::ToWidget,
+            Some(id),
+        ));
+        self.playback = Some(Playback {
+            id,
+            sound,
+            voice,
+            elapsed: 0.0,
+            waiting_for_choice: false,
+        });
+    }
+
+    /// Moves to the next line, when the current one has ended.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        scene: Option<&mut Scene>,
+        resource_manager: &ResourceManager,
+        ui: &UserInterface,
+    ) {
+        let Some(playback) = self.playback.as_mut() else {
+            return;
+        };
+        let Some(scene) = scene else {
+            return;
+        };
+        let Some(node) = self.graph.node(playback.id) else {
+            self.stop(Some(scene), ui);
+            return;
+        };
+        let choices = node
+            .choices
+            .iter()
+            .filter_map(|id| self.graph.node(*id))
+            .collect::<Vec<_>>();
+
+        if playback.waiting_for_choice {
+            if let Some(next) = self
+                .choice
+                .take()
+                .and_then(|index| choices.get(index))
+                .map(|next| next.id)
+            {
+                self.play_line(next, scene, resource_manager, ui);
+            }
+            return;
+        }
+
+        playback.elapsed += dt;
+        // The play-once sound is removed, when it ends. A voice line, that failed to load, does not
+        // play at all, so its sound is removed here.
+        if let Some(voice) = playback.voice.as_ref() {
+            if !voice.is_loading() && !voice.is_ok() {
+                remove_sound(scene, playback.sound);
+                playback.voice = None;
+            }
+        }
+        let voice_finished = !scene.graph.is_valid_handle(playback.sound);
+        let ended = (voice_finished && playback.elapsed >= line_duration(&node.text))
+            || playback.elapsed >= MAX_LINE_DURATION;
+        if !ended {
+            return;
+        }
+
+        match choices.as_slice() {
+            [] => self.stop(Some(scene), ui),
+            [next] => {
+                let next = next.id;
+                self.play_line(next, scene, resource_manager, ui);
+            }
+            _ => {
+                let mut text = node.text.clone();
+                for (index, choice) in choices.iter().enumerate() {
+                    text += &format!("\n[{}] {}", index + 1, choice.text);
+                }
+                self.show_subtitle(ui, Some(text));
+                playback.waiting_for_choice = true;
+            }
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        scene: Option<&mut Scene>,
+        resource_manager: &ResourceManager,
+        ui: &UserInterface,
+    ) {
+        if let Some(msg) = message.data::<DialogueCanvasMessage>() {
+            if message.destination() != self.canvas
+                || message.direction() != MessageDirection::FromWidget
+            {
+                return;
+            }
+            match msg {
+                DialogueCanvasMessage::Graph(graph) => self.graph = graph.clone(),
+                DialogueCanvasMessage::EditText { id, position, size } => {
+                    self.editing = Some(*id);
+                    let text = self
+                        .graph
+                        .node(*id)
+                        .map(|node| node.text.clone())
+                        .unwrap_or_default();
+                    ui.send_message(TextMessage::text(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        text,
+                    ));
+                    ui.send_message(WidgetMessage::desired_position(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        *position,
+                    ));
+                    ui.send_message(WidgetMessage::width(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        size.x,
+                    ));
+                    ui.send_message(WidgetMessage::height(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        size.y,
+                    ));
+                    ui.send_message(WidgetMessage::visibility(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                    ui.send_message(WidgetMessage::focus(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                    ));
+                }
+                DialogueCanvasMessage::EndEdit => {
+                    self.editing = None;
+                    ui.send_message(WidgetMessage::visibility(
+                        self.text_box,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                }
+                _ => (),
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.text_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(id) = self.editing {
+                    ui.send_message(DialogueCanvasMessage::set_text(
+                        self.canvas,
+                        MessageDirection::ToWidget,
+                        id,
+                        text.clone(),
+                    ));
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            let destination = message.destination();
+            if destination == self.add {
+                ui.send_message(DialogueCanvasMessage::add_node(
+                    self.canvas,
+                    MessageDirection::ToWidget,
+                ));
+            } else if destination == self.save {
+                self.save();
+            } else if destination == self.play {
+                let Some(scene) = scene else {
+                    return;
+                };
+                self.stop(Some(&mut *scene), ui);
+                self.play_line(self.graph.start, scene, resource_manager, ui);
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Discovery of other instances of the demo in local network using UDP broadcast. This is the
+//! groundwork for a multiplayer audio demo, where peers will synchronize their spatial audio.
+use crate::Game;
+use fyrox::{
+    core::{algebra::Vector2, log::Log, pool::Handle},
+    gui::{
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    plugin::PluginContext,
+    rand::{thread_rng, Rng},
+};
+use std::{
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Port, that is used by every instance for discovery.
+pub const DISCOVERY_PORT: u16 = 7777;
+/// Time (in seconds) between two discovery broadcasts.
+const BROADCAST_INTERVAL: f32 = 2.0;
+/// Peers, that didn't respond for this time (in seconds), are removed from the list.
+const PEER_TIMEOUT: f32 = 10.0;
+/// Receiving task waits for packets for this time, then returns everything it got so far.
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(200);
+/// Every packet starts with this tag, so packets of other applications will be ignored.
+const PACKET_TAG: &str = "FYROX_SOUND_DEMO";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PacketKind {
+    /// Broadcasted periodically to find other peers.
+    Discover,
+    /// Sent directly to a peer, that sent [`PacketKind::Discover`].
+    Reply,
+    /// Sent directly to a peer, that was selected in the list.
+    Connect,
+}
+
+impl PacketKind {
+    fn name(&self) -> &'static str {
+        match self {
+            PacketKind::Discover => "discover",
+            PacketKind::Reply => "reply",
+            PacketKind::Connect => "connect",
+        }
+    }
+}
+
+/// Packets are plain text: `<tag> <kind> <session id> <machine name>`.
+#[derive(Debug, Clone)]
+struct Packet {
+    kind: PacketKind,
+    session_id: u64,
+    name: String,
+}
+
+impl Packet {
+    fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "{PACKET_TAG} {} {} {}",
+            self.kind.name(),
+            self.session_id,
+            self.name
+        )
+        .into_bytes()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let mut parts = text.splitn(4, ' ');
+        if parts.next()? != PACKET_TAG {
+            return None;
+        }
+        let kind = match parts.next()? {
+            "discover" => PacketKind::Discover,
+            "reply" => PacketKind::Reply,
+            "connect" => PacketKind::Connect,
+            _ => return None,
+        };
+        let session_id = parts.next()?.parse().ok()?;
+        let name = parts.next().unwrap_or_default().to_string();
+        Some(Self {
+            kind,
+            session_id,
+            name,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Peer {
+    name: String,
+    session_id: u64,
+    address: SocketAddr,
+    last_seen: f32,
+}
+
+fn machine_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "Unknown".to_string())
+}
+
+/// Blocks the current thread until the first packet arrives or until the timeout, then reads
+/// every packet, that is already in the queue.
+fn receive_packets(socket: &UdpSocket) -> Vec<(SocketAddr, Packet)> {
+    let mut packets = Vec::new();
+    let mut buffer = [0u8; 512];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((size, address)) => {
+                if let Some(packet) = Packet::from_bytes(&buffer[..size]) {
+                    packets.push((address, packet));
+                }
+            }
+            Err(err) => {
+                if !matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+                    Log::err(format!(
+                        "Unable to receive a discovery packet. Reason: {err}"
+                    ));
+                }
+                break;
+            }
+        }
+    }
+    packets
+}
+
+#[derive(Debug)]
+pub struct NetworkDiscovery {
+    socket: Arc<UdpSocket>,
+    session_id: u64,
+    name: String,
+    peers: Vec<Peer>,
+    peers_changed: bool,
+    broadcast_timer: f32,
+    time: f32,
+    peer_list: Handle<UiNode>,
+    status: Handle<UiNode>,
+}
+
+impl NetworkDiscovery {
+    /// Binds the discovery socket and creates the panel with the list of peers.
+    pub fn new(ctx: &mut BuildContext) -> Option<Self> {
+        let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+            .and_then(|socket| {
+                socket.set_broadcast(true)?;
+                socket.set_read_timeout(Some(RECEIVE_TIMEOUT))?;
+                Ok(socket)
+            });
+        let socket = match socket {
+            Ok(socket) => socket,
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to bind the discovery socket to port {DISCOVERY_PORT}. Reason: {err}"
+                ));
+                return None;
+            }
+        };
+
+        let name = machine_name();
+        let peer_list;
+        let status;
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(250.0)
+                .with_height(250.0)
+                .with_desired_position(Vector2::new(5.0, 200.0)),
+        )
+        .with_title(WindowTitle::text("Peers in Local Network"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child({
+                        status = TextBuilder::new(
+                            WidgetBuilder::new().with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_text(format!("Listening as {name}. Click a peer to connect."))
+                        .build(ctx);
+                        status
+                    })
+                    .with_child({
+                        peer_list = ListViewBuilder::new(
+                            WidgetBuilder::new()
+                                .with_height(180.0)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .build(ctx);
+                        peer_list
+                    }),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Some(Self {
+            socket: Arc::new(socket),
+            session_id: thread_rng().gen(),
+            name,
+            peers: Default::default(),
+            peers_changed: false,
+            broadcast_timer: 0.0,
+            time: 0.0,
+            peer_list,
+            status,
+        })
+    }
+
+    /// Starts a task, that waits for incoming packets on a thread pool. When the task is done,
+    /// received packets are handled and a new task is started.
+    pub fn spawn_receive_task(&self, context: &mut PluginContext) {
+        let socket = self.socket.clone();
+        context.task_pool.spawn_plugin_task(
+            async move { receive_packets(&socket) },
+            |packets, game: &mut Game, ctx| {
+                if let Some(discovery) = game.discovery.as_mut() {
+                    for (address, packet) in packets {
+                        discovery.handle_packet(address, packet, ctx.user_interfaces.first());
+                    }
+                    discovery.spawn_receive_task(ctx);
+                }
+            },
+        );
+    }
+
+    fn send(&self, address: SocketAddr, kind: PacketKind) {
+        let packet = Packet {
+            kind,
+            session_id: self.session_id,
+            name: self.name.clone(),
+        };
+        if let Err(err) = self.socket.send_to(&packet.to_bytes(), address) {
+            Log::err(format!(
+                "Unable to send a discovery packet to {address}. Reason: {err}"
+            ));
+        }
+    }
+
+    fn handle_packet(&mut self, address: SocketAddr, packet: Packet, ui: &UserInterface) {
+        // Broadcasts are received by the sender too.
+        if packet.session_id == self.session_id {
+            return;
+        }
+
+        match packet.kind {
+            PacketKind::Discover => self.send(address, PacketKind::Reply),
+            PacketKind::Reply => (),
+            PacketKind::Connect => {
+                Log::info(format!("{} ({address}) wants to connect.", packet.name));
+                ui.send_message(TextMessage::text(
+                    self.status,
+                    MessageDirection::ToWidget,
+                    format!("{} wants to connect.", packet.name),
+                ));
+            }
+        }
+
+        if let Some(peer) = self
+            .peers
+            .iter_mut()
+            .find(|peer| peer.session_id == packet.session_id)
+        {
+            peer.last_seen = self.time;
+            peer.address = address;
+        } else {
+            self.peers.push(Peer {
+                name: packet.name,
+                session_id: packet.session_id,
+                address,
+                last_seen: self.time,
+            });
+            self.peers_changed = true;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32, ui: &mut UserInterface) {
+        self.time += dt;
+
+        self.broadcast_timer -= dt;
+        if self.broadcast_timer <= 0.0 {
+            self.broadcast_timer = BROADCAST_INTERVAL;
+            self.send(
+                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::BROADCAST, DISCOVERY_PORT)),
+                PacketKind::Discover,
+            );
+        }
+
+        let count = self.peers.len();
+        let time = self.time;
+        self.peers
+            .retain(|peer| time - peer.last_seen < PEER_TIMEOUT);
+        if self.peers.len() != count {
+            self.peers_changed = true;
+        }
+
+        if std::mem::take(&mut self.peers_changed) {
+            let ctx = &mut ui.build_ctx();
+            let items = self
+                .peers
+                .iter()
+                .map(|peer| {
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                        .with_text(format!("{} ({})", peer.name, peer.address))
+                        .build(ctx)
+                })
+                .collect::<Vec<_>>();
+            ui.send_message(ListViewMessage::items(
+                self.peer_list,
+                MessageDirection::ToWidget,
+                items,
+            ));
+        }
+    }
+
+    pub fn handle_ui_message(&self, message: &UiMessage, ui: &UserInterface) {
+        if let Some(ListViewMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.peer_list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(peer) = self.peers.get(*index) {
+                    self.send(peer.address, PacketKind::Connect);
+                    ui.send_message(TextMessage::text(
+                        self.status,
+                        MessageDirection::ToWidget,
+                        format!("Connection request is sent to {}.", peer.name),
+                    ));
+                }
+            }
+        }
+    }
+}
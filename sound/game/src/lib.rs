@@ -1,23 +1,43 @@
 //! Game project.
+use crate::{
+    audio_debug::AudioDebugOverlay,
+    dialogue::{DialogueEditor, DIALOGUE_PATH},
+    discovery::NetworkDiscovery,
+    spectrum::{AudioSpectrumBuilder, AudioSpectrumMessage, SpectrumAnalyzer},
+    subtitles::Subtitles,
+};
+use common_scripts::Throttle;
 use fyrox::{
-    core::{algebra::Vector2, log::Log, pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    core::{
+        algebra::Vector2, io, log::Log, pool::Handle, reflect::prelude::*, visitor::prelude::*,
+    },
     engine::GraphicsContext,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, WindowEvent},
     gui::{
         grid::{Column, GridBuilder, Row},
-        message::MessageDirection,
+        message::{MessageDirection, UiMessage},
         progress_bar::{ProgressBarBuilder, ProgressBarMessage},
         stack_panel::StackPanelBuilder,
         text::{TextBuilder, TextMessage},
         widget::{WidgetBuilder, WidgetMessage},
         HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
     },
+    keyboard::{KeyCode, PhysicalKey},
     plugin::{Plugin, PluginContext, PluginRegistrationContext},
     renderer::QualitySettings,
     resource::texture::{loader::TextureLoader, CompressionOptions, TextureImportOptions},
     scene::Scene,
 };
 use std::path::Path;
+use weather::{WeatherController, WeatherPanel};
+
+mod audio_debug;
+mod dialogue;
+mod discovery;
+mod spectrum;
+mod subtitles;
+
+const SCENE_NAME: &str = "scene";
 
 #[derive(Default, Visit, Reflect, Debug)]
 pub struct Game {
@@ -25,9 +45,42 @@ pub struct Game {
     progress_bar: Handle<UiNode>,
     overlay_grid: Handle<UiNode>,
     debug_text: Handle<UiNode>,
+    spectrum: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spectrum_analyzer: SpectrumAnalyzer,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    listen: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    discovery: Option<NetworkDiscovery>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    audio_debug: AudioDebugOverlay,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    subtitles: Subtitles,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    weather_panel: WeatherPanel,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    dialogue_editor: DialogueEditor,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
 }
 
 impl Game {
+    /// Creates the game, that looks for other instances of the demo in local network.
+    pub fn with_network_discovery() -> Self {
+        Self {
+            listen: true,
+            ..Default::default()
+        }
+    }
+
     fn handle_resize(&self, context: &mut PluginContext, new_size: Vector2<f32>) {
         context
             .user_interfaces
@@ -51,9 +104,13 @@ impl Game {
 impl Plugin for Game {
     fn register(&self, context: PluginRegistrationContext) {
         fyrox_scripts::register(&context.serialization_context.script_constructors);
+        context
+            .serialization_context
+            .script_constructors
+            .add::<WeatherController>("Weather Controller");
     }
 
-    fn init(&mut self, scene_path: Option<&str>, context: PluginContext) {
+    fn init(&mut self, scene_path: Option<&str>, mut context: PluginContext) {
         context
             .resource_manager
             .state()
@@ -104,9 +161,63 @@ impl Plugin for Game {
         .build(ctx);
 
         self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        self.spectrum = AudioSpectrumBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(100.0)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .build(ctx);
+
+        self.subtitles = Subtitles::new(ctx);
+
+        self.weather_panel = WeatherPanel::new(ctx);
+
+        self.dialogue_editor = DialogueEditor::new(ctx);
+
+        if self.listen {
+            self.discovery = NetworkDiscovery::new(ctx);
+            if let Some(discovery) = self.discovery.as_ref() {
+                discovery.spawn_receive_task(&mut context);
+            }
+        }
+
+        context.task_pool.spawn_plugin_task(
+            io::load_file("data/subtitles.ron"),
+            |result, game: &mut Game, _ctx| {
+                if let Err(err) = result
+                    .map_err(|err| format!("{err:?}"))
+                    .and_then(|bytes| game.subtitles.load(&bytes))
+                {
+                    Log::err(format!("Unable to load subtitles. Reason: {err}"));
+                }
+            },
+        );
+
+        // The sample conversation is used until the dialogue is saved for the first time.
+        context.task_pool.spawn_plugin_task(
+            io::load_file(DIALOGUE_PATH),
+            |result, game: &mut Game, ctx| {
+                if let Ok(bytes) = result {
+                    if let Err(err) = game
+                        .dialogue_editor
+                        .load(&bytes, ctx.user_interfaces.first())
+                    {
+                        Log::err(format!("Unable to load dialogue. Reason: {err}"));
+                    }
+                }
+            },
+        );
     }
 
     fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
         let progress = context.resource_manager.state().loading_progress() as f32 / 100.0;
         context
             .user_interfaces
@@ -124,9 +235,62 @@ impl Plugin for Game {
                 .send_message(TextMessage::text(
                     self.debug_text,
                     MessageDirection::ToWidget,
-                    format!("{}", graphics_context.renderer.get_statistics()),
+                    format!(
+                        "{}\n{}",
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
                 ))
         }
+
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            let magnitudes = self.spectrum_analyzer.update(scene).to_vec();
+            context
+                .user_interfaces
+                .first()
+                .send_message(AudioSpectrumMessage::update(
+                    self.spectrum,
+                    MessageDirection::ToWidget,
+                    magnitudes,
+                ));
+        }
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.audio_debug
+                .update(scene, context.user_interfaces.first_mut());
+        }
+
+        self.subtitles.update(
+            context.scenes.try_get(self.scene),
+            context.user_interfaces.first(),
+        );
+
+        if let Some(discovery) = self.discovery.as_mut() {
+            discovery.update(context.dt, context.user_interfaces.first_mut());
+        }
+
+        self.dialogue_editor.update(
+            context.dt,
+            context.scenes.try_get_mut(self.scene),
+            context.resource_manager,
+            context.user_interfaces.first(),
+        );
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(discovery) = self.discovery.as_ref() {
+            discovery.handle_ui_message(message, context.user_interfaces.first());
+        }
+
+        self.weather_panel
+            .handle_ui_message(message, context.scenes.try_get_mut(self.scene));
+
+        self.dialogue_editor.handle_ui_message(
+            message,
+            context.scenes.try_get_mut(self.scene),
+            context.resource_manager,
+            context.user_interfaces.first(),
+        );
     }
 
     fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
@@ -137,6 +301,26 @@ impl Plugin for Game {
                         &mut context,
                         Vector2::new(size.width as f32, size.height as f32),
                     )
+                } else if let WindowEvent::KeyboardInput { event, .. } = event {
+                    if event.state != ElementState::Pressed {
+                        return;
+                    }
+                    let PhysicalKey::Code(code) = event.physical_key else {
+                        return;
+                    };
+                    match code {
+                        KeyCode::F1 => self.audio_debug.toggle(
+                            context.scenes.try_get_mut(self.scene),
+                            context.user_interfaces.first(),
+                        ),
+                        KeyCode::F2 => self.dialogue_editor.open(context.user_interfaces.first()),
+                        // Picks a choice of the dialogue, that is being played.
+                        KeyCode::Digit1 => self.dialogue_editor.choose(0),
+                        KeyCode::Digit2 => self.dialogue_editor.choose(1),
+                        KeyCode::Digit3 => self.dialogue_editor.choose(2),
+                        KeyCode::Digit4 => self.dialogue_editor.choose(3),
+                        _ => (),
+                    }
                 }
             }
             _ => (),
@@ -146,11 +330,13 @@ impl Plugin for Game {
     fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
         let graphics_context = context.graphics_context.as_initialized_mut();
 
-        Log::verify(
-            graphics_context
-                .renderer
-                .set_quality_settings(&QualitySettings::high()),
-        );
+        if !prefs::quality::restore(&mut graphics_context.renderer, SCENE_NAME) {
+            Log::verify(
+                graphics_context
+                    .renderer
+                    .set_quality_settings(&QualitySettings::high()),
+            );
+        }
 
         let inner_size = graphics_context.window.inner_size();
         self.handle_resize(
@@ -168,6 +354,10 @@ impl Plugin for Game {
     ) {
         self.scene = scene;
 
+        if let Some(scene) = context.scenes.try_get_mut(scene) {
+            WeatherController::add_if_missing(&mut scene.graph);
+        }
+
         context
             .user_interfaces
             .first()
@@ -177,4 +367,10 @@ impl Plugin for Game {
                 false,
             ));
     }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
 }
@@ -0,0 +1,217 @@
+//! Audio spectrum visualizer - a custom widget, that shows magnitudes of frequencies of the sound,
+//! that is currently playing.
+use fyrox::{
+    core::{
+        color::Color, math::Rect, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    graph::BaseSceneGraph,
+    gui::{
+        brush::Brush,
+        define_constructor, define_widget_deref,
+        draw::{CommandTexture, Draw, DrawingContext},
+        message::{MessageDirection, UiMessage},
+        widget::{Widget, WidgetBuilder},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+    scene::{
+        sound::{Sound, Status},
+        Scene,
+    },
+};
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+/// Amount of samples, that are used to compute the spectrum.
+pub const FFT_SIZE: usize = 1024;
+/// Amount of bars in the visualizer.
+const BAR_COUNT: usize = 48;
+/// Magnitudes below this level (in decibels) are not shown.
+const MIN_DECIBELS: f32 = -60.0;
+/// How fast the bars fall down, per update.
+const DECAY: f32 = 0.85;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioSpectrumMessage {
+    /// New magnitudes (in `0..1` range) for the bars, from low to high frequencies.
+    Update(Vec<f32>),
+}
+
+impl AudioSpectrumMessage {
+    define_constructor!(
+        AudioSpectrumMessage:Update => fn update(Vec<f32>), layout: false
+    );
+}
+
+#[derive(Clone, Debug, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "6a1d0f3e-94b2-4c57-8e2a-1b7f5d3c9e08")]
+struct AudioSpectrum {
+    widget: Widget,
+    magnitudes: Vec<f32>,
+}
+
+define_widget_deref!(AudioSpectrum);
+
+impl Control for AudioSpectrum {
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        let bounds = self.bounding_rect();
+        let count = self.magnitudes.len();
+        if count == 0 {
+            return;
+        }
+
+        let bar_width = bounds.w() / count as f32;
+        for (i, magnitude) in self.magnitudes.iter().enumerate() {
+            let height = bounds.h() * magnitude.clamp(0.0, 1.0);
+            drawing_context.push_rect_filled(
+                &Rect::new(
+                    bounds.x() + i as f32 * bar_width,
+                    bounds.y() + bounds.h() - height,
+                    (bar_width - 1.0).max(1.0),
+                    height,
+                ),
+                None,
+            );
+
+            // Low frequencies are blue, high frequencies are red.
+            let t = i as f32 / (count - 1).max(1) as f32;
+            let color = Color::opaque((255.0 * t) as u8, 0, (255.0 * (1.0 - t)) as u8);
+            drawing_context.commit(
+                self.clip_bounds(),
+                Brush::Solid(color),
+                CommandTexture::None,
+                None,
+            );
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(AudioSpectrumMessage::Update(magnitudes)) = message.data() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                self.magnitudes.clone_from(magnitudes);
+                self.invalidate_visual();
+            }
+        }
+    }
+}
+
+pub struct AudioSpectrumBuilder {
+    widget_builder: WidgetBuilder,
+}
+
+impl AudioSpectrumBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self { widget_builder }
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let spectrum = AudioSpectrum {
+            widget: self.widget_builder.build(),
+            magnitudes: vec![0.0; BAR_COUNT],
+        };
+
+        ctx.add_node(UiNode::new(spectrum))
+    }
+}
+
+/// Computes magnitudes of frequencies of the sound, that is currently playing.
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    bars: Vec<f32>,
+}
+
+impl std::fmt::Debug for SpectrumAnalyzer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpectrumAnalyzer").finish()
+    }
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            buffer: vec![Complex::default(); FFT_SIZE],
+            bars: vec![0.0; BAR_COUNT],
+        }
+    }
+}
+
+impl SpectrumAnalyzer {
+    /// Copies the last [`FFT_SIZE`] samples (mixed to mono), that were played by the first playing
+    /// sound source in the scene. Returns `false` if nothing is playing.
+    fn fetch_samples(&mut self, scene: &Scene) -> bool {
+        for sound in scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Sound>())
+            .filter(|sound| sound.status() == Status::Playing)
+        {
+            let Some(buffer) = sound.buffer() else {
+                continue;
+            };
+            if !buffer.is_ok() {
+                continue;
+            }
+            let buffer = buffer.data_ref();
+            let channels = buffer.channel_count().max(1);
+            let samples = buffer.samples();
+            let frame_count = samples.len() / channels;
+            if frame_count < FFT_SIZE {
+                continue;
+            }
+
+            let position = ((sound.playback_time() * buffer.sample_rate() as f32) as usize)
+                .clamp(FFT_SIZE, frame_count);
+            for (i, value) in self.buffer.iter_mut().enumerate() {
+                let frame = position - FFT_SIZE + i;
+                let sum = samples[frame * channels..(frame + 1) * channels]
+                    .iter()
+                    .sum::<f32>();
+                // Hann window reduces spectral leakage.
+                let window = 0.5
+                    - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos();
+                *value = Complex::new(sum / channels as f32 * window, 0.0);
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Updates the spectrum and returns magnitudes of the bars in `0..1` range. Bars are spread
+    /// logarithmically, because that's how humans hear frequencies.
+    pub fn update(&mut self, scene: &Scene) -> &[f32] {
+        let has_samples = self.fetch_samples(scene);
+        if has_samples {
+            self.fft.process(&mut self.buffer);
+        }
+
+        let bin_count = FFT_SIZE / 2;
+        for (i, bar) in self.bars.iter_mut().enumerate() {
+            let mut level = 0.0;
+            if has_samples {
+                let first = (bin_count as f32).powf(i as f32 / BAR_COUNT as f32) as usize;
+                let last = ((bin_count as f32).powf((i + 1) as f32 / BAR_COUNT as f32) as usize)
+                    .clamp(first + 1, bin_count);
+                let magnitude = self.buffer[first..last]
+                    .iter()
+                    .map(|bin| bin.norm())
+                    .fold(0.0, f32::max)
+                    * 2.0
+                    / FFT_SIZE as f32;
+                let decibels = 20.0 * magnitude.max(f32::EPSILON).log10();
+                level = ((decibels - MIN_DECIBELS) / -MIN_DECIBELS).clamp(0.0, 1.0);
+            }
+            *bar = level.max(*bar * DECAY);
+        }
+
+        &self.bars
+    }
+}
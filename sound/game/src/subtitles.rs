@@ -0,0 +1,131 @@
+//! Subtitles, that are synchronized with the playback position of sound sources. Subtitles are
+//! loaded from `data/subtitles.ron` and are bound to sound buffers by their resource paths.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::pool::Handle,
+    graph::BaseSceneGraph,
+    gui::{
+        formatted_text::WrapMode,
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{
+        sound::{Sound, Status},
+        Scene,
+    },
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct SubtitleEntry {
+    pub start_sec: f32,
+    pub end_sec: f32,
+    pub text: String,
+}
+
+#[derive(Default, Debug)]
+pub struct Subtitles {
+    /// Subtitles of sound buffers, keys are resource paths of the buffers.
+    entries: HashMap<String, Vec<SubtitleEntry>>,
+    text: Handle<UiNode>,
+    /// Subtitle, that is currently on the screen.
+    shown: Option<String>,
+}
+
+impl Subtitles {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                // Keeps the subtitles away from the spectrum in the bottom left corner.
+                .with_margin(Thickness {
+                    left: 320.0,
+                    top: 0.0,
+                    right: 320.0,
+                    bottom: 30.0,
+                }),
+        )
+        .with_font_size(22.0)
+        .with_wrap(WrapMode::Word)
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(ctx);
+
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let mut entries = ron::de::from_bytes::<HashMap<String, Vec<SubtitleEntry>>>(bytes)
+            .map_err(|err| err.to_string())?;
+        for (path, subtitles) in entries.iter_mut() {
+            if let Some(entry) = subtitles.iter().find(|e| e.end_sec <= e.start_sec) {
+                return Err(format!(
+                    "Subtitle \"{}\" of {path} must end after it starts.",
+                    entry.text
+                ));
+            }
+            subtitles.sort_by(|a, b| a.start_sec.total_cmp(&b.start_sec));
+        }
+        self.entries = entries;
+        Ok(())
+    }
+
+    /// Finds a subtitle for the current playback position of the first playing sound source,
+    /// that has subtitles.
+    fn active_subtitle(&self, scene: &Scene) -> Option<&str> {
+        scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Sound>())
+            .filter(|sound| sound.status() == Status::Playing)
+            .find_map(|sound| {
+                let buffer = sound.buffer()?;
+                let ResourceKind::External(path) = buffer.kind() else {
+                    return None;
+                };
+                let time = sound.playback_time();
+                self.entries
+                    .get(path.to_string_lossy().as_ref())?
+                    .iter()
+                    .find(|entry| entry.start_sec <= time && time < entry.end_sec)
+                    .map(|entry| entry.text.as_str())
+            })
+    }
+
+    pub fn update(&mut self, scene: Option<&Scene>, ui: &UserInterface) {
+        let active = scene.and_then(|scene| self.active_subtitle(scene));
+        if active == self.shown.as_deref() {
+            return;
+        }
+
+        match active {
+            Some(text) => {
+                ui.send_message(TextMessage::text(
+                    self.text,
+                    MessageDirection::ToWidget,
+                    text.to_string(),
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    self.text,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            }
+            None => {
+                ui.send_message(WidgetMessage::visibility(
+                    self.text,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+        }
+        self.shown = active.map(|text| text.to_string());
+    }
+}
@@ -0,0 +1,522 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, WindowEvent},
+    graph::BaseSceneGraph,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// Amount of chunks along each side of the world.
+const WORLD_SIZE: i32 = 5;
+/// Size of a chunk along each side, in meters.
+const CHUNK_SIZE: f32 = 32.0;
+/// Chunks are unloaded a bit farther than they're loaded, so a chunk on the border of the
+/// streaming radius won't be loaded and unloaded every frame.
+const UNLOAD_MARGIN: f32 = 8.0;
+const MOVE_SPEED: f32 = 12.0;
+const CHUNKS_DIR: &str = "data/chunks";
+
+const SCENE_NAME: &str = "streaming";
+
+type ChunkCoords = (i32, i32);
+
+fn chunk_path(coords: ChunkCoords) -> PathBuf {
+    Path::new(CHUNKS_DIR).join(format!("chunk_{}_{}.rgs", coords.0, coords.1))
+}
+
+/// Parses coordinates of a chunk from the path of its scene.
+fn chunk_coords(path: &Path) -> Option<ChunkCoords> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.strip_prefix("chunk_")?.split('_');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+fn chunk_center(coords: ChunkCoords) -> Vector2<f32> {
+    Vector2::new(
+        (coords.0 as f32 + 0.5) * CHUNK_SIZE,
+        (coords.1 as f32 + 0.5) * CHUNK_SIZE,
+    )
+}
+
+/// A tiny xorshift random number generator, the chunks must be the same on every run.
+struct ChunkRng(u32);
+
+impl ChunkRng {
+    fn new(coords: ChunkCoords) -> Self {
+        Self(
+            ((coords.0 as u32).wrapping_mul(73856093) ^ (coords.1 as u32).wrapping_mul(19349663))
+                | 1,
+        )
+    }
+
+    /// Returns a number in `[0; 1)` range.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 % 10000) as f32 / 10000.0
+    }
+}
+
+fn make_colored_material(color: Color) -> MaterialResource {
+    let mut material = Material::standard();
+    Log::verify(material.set_property(&"diffuseColor".into(), color.into()));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Builds a chunk of the world: a tile of the ground with a few buildings on it.
+fn build_chunk(coords: ChunkCoords) -> Scene {
+    let mut scene = Scene::new();
+    let mut rng = ChunkRng::new(coords);
+    let origin = Vector3::new(
+        coords.0 as f32 * CHUNK_SIZE,
+        0.0,
+        coords.1 as f32 * CHUNK_SIZE,
+    );
+
+    // Checkerboard colors make borders of the chunks visible.
+    let ground_color = if (coords.0 + coords.1) % 2 == 0 {
+        Color::opaque(90, 130, 80)
+    } else {
+        Color::opaque(110, 150, 90)
+    };
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(
+                Matrix4::new_translation(
+                    &(origin + Vector3::new(CHUNK_SIZE * 0.5, -0.1, CHUNK_SIZE * 0.5)),
+                ) * Matrix4::new_nonuniform_scaling(&Vector3::new(CHUNK_SIZE, 0.2, CHUNK_SIZE)),
+            ),
+        ))
+        .with_material(make_colored_material(ground_color))
+        .build()])
+        .build(&mut scene.graph);
+
+    let building_material = make_colored_material(Color::opaque(180, 170, 160));
+    for _ in 0..8 {
+        let size = Vector3::new(
+            2.0 + rng.next() * 4.0,
+            2.0 + rng.next() * 10.0,
+            2.0 + rng.next() * 4.0,
+        );
+        let position = origin
+            + Vector3::new(
+                size.x + rng.next() * (CHUNK_SIZE - 2.0 * size.x),
+                size.y * 0.5,
+                size.z + rng.next() * (CHUNK_SIZE - 2.0 * size.z),
+            );
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(
+                    Matrix4::new_translation(&position) * Matrix4::new_nonuniform_scaling(&size),
+                ),
+            ))
+            .with_material(building_material.clone())
+            .build()])
+            .build(&mut scene.graph);
+    }
+
+    scene
+}
+
+/// Generates the chunks procedurally and saves them as separate scenes to `data/chunks`. The chunks
+/// are committed assets, that are streamed the same way as scenes made in the editor, so this is
+/// done only by the `--generate-chunks` flag of the desktop executor, never by the game itself.
+pub fn generate_chunks() {
+    Log::verify(std::fs::create_dir_all(CHUNKS_DIR));
+
+    for x in 0..WORLD_SIZE {
+        for y in 0..WORLD_SIZE {
+            let path = chunk_path((x, y));
+            let mut scene = build_chunk((x, y));
+            let mut visitor = Visitor::new();
+            Log::verify(scene.save("Scene", &mut visitor));
+            Log::verify(visitor.save_binary(&path));
+        }
+    }
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    player: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    radius_slider: Handle<UiNode>,
+    /// Chunks within this distance from the player are loaded.
+    streaming_radius: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_direction: Vector2<f32>,
+    /// Root nodes of the loaded chunks in the master scene.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active_chunks: HashMap<ChunkCoords, Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    loading_chunks: HashMap<PathBuf, ChunkCoords>,
+    /// Chunks, that could not be loaded from the disk (they are not generated on WebAssembly), such
+    /// chunks are built in memory instead of requesting them again.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    missing_chunks: HashSet<ChunkCoords>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    fn is_chunk_in_range(&self, coords: ChunkCoords, player: Vector2<f32>, radius: f32) -> bool {
+        (0..WORLD_SIZE).contains(&coords.0)
+            && (0..WORLD_SIZE).contains(&coords.1)
+            && (chunk_center(coords) - player).norm() <= radius
+    }
+
+    fn player_position(&self, context: &PluginContext) -> Option<Vector2<f32>> {
+        let position = context
+            .scenes
+            .try_get(self.scene)?
+            .graph
+            .try_get(self.player)?
+            .global_position();
+        Some(Vector2::new(position.x, position.z))
+    }
+
+    /// Copies the content of the chunk into the master scene.
+    fn attach_chunk(&mut self, coords: ChunkCoords, chunk: &Graph, context: &mut PluginContext) {
+        let Some(master) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        let (root, _) = chunk.copy_node(chunk.get_root(), &mut master.graph, &mut |_, _| true);
+        self.active_chunks.insert(coords, root);
+    }
+
+    fn stream_chunks(&mut self, context: &mut PluginContext) {
+        let Some(player) = self.player_position(context) else {
+            return;
+        };
+
+        // Unload distant chunks.
+        let unload_radius = self.streaming_radius + UNLOAD_MARGIN;
+        let distant = self
+            .active_chunks
+            .keys()
+            .filter(|coords| !self.is_chunk_in_range(**coords, player, unload_radius))
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            for coords in distant {
+                if let Some(root) = self.active_chunks.remove(&coords) {
+                    scene.graph.remove_node(root);
+                }
+            }
+        }
+
+        // Request new chunks.
+        let radius_in_chunks = (self.streaming_radius / CHUNK_SIZE).ceil() as i32 + 1;
+        let player_chunk = (
+            (player.x / CHUNK_SIZE).floor() as i32,
+            (player.y / CHUNK_SIZE).floor() as i32,
+        );
+        for x in player_chunk.0 - radius_in_chunks..=player_chunk.0 + radius_in_chunks {
+            for y in player_chunk.1 - radius_in_chunks..=player_chunk.1 + radius_in_chunks {
+                let coords = (x, y);
+                if !self.is_chunk_in_range(coords, player, self.streaming_radius)
+                    || self.active_chunks.contains_key(&coords)
+                    || self.loading_chunks.values().any(|c| *c == coords)
+                {
+                    continue;
+                }
+
+                if self.missing_chunks.contains(&coords) {
+                    let chunk = build_chunk(coords);
+                    self.attach_chunk(coords, &chunk.graph, context);
+                    continue;
+                }
+
+                let path = chunk_path(coords);
+                context.async_scene_loader.request(path.clone());
+                self.loading_chunks.insert(path, coords);
+            }
+        }
+    }
+
+    fn move_player(&self, context: &mut PluginContext) {
+        let Some(direction) = self.move_direction.try_normalize(f32::EPSILON) else {
+            return;
+        };
+        let Some(player) = context
+            .scenes
+            .try_get_mut(self.scene)
+            .and_then(|scene| scene.graph.try_get_mut(self.player))
+        else {
+            return;
+        };
+
+        let offset = direction.scale(MOVE_SPEED * context.dt);
+        let transform = player.local_transform_mut();
+        let position = **transform.position();
+        transform.set_position(position + Vector3::new(offset.x, 0.0, offset.y));
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.streaming_radius = 40.0;
+
+        let mut scene = Scene::new();
+
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 25.0, -25.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        // The player is a marker in the middle of the world, the camera follows it.
+        let marker = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_sphere(16, 16, 0.5, &Matrix4::new_translation(&Vector3::y())),
+            ))
+            .with_material(make_colored_material(Color::opaque(220, 60, 40)))
+            .build()])
+            .build(&mut scene.graph);
+
+        let center = chunk_center((WORLD_SIZE / 2, WORLD_SIZE / 2));
+        self.player = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_children(&[camera, marker])
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(center.x, 0.0, center.y))
+                        .build(),
+                ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let radius_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(2.0))
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("Streaming Radius")
+        .build(ctx);
+        self.radius_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(CHUNK_SIZE * 0.5)
+        .with_max(CHUNK_SIZE * WORLD_SIZE as f32)
+        .with_step(1.0)
+        .with_value(self.streaming_radius)
+        .with_value_precision(0)
+        .show_value(true)
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(90.0)
+                .with_desired_position(Vector2::new(5.0, 120.0)),
+        )
+        .with_title(WindowTitle::text("Streaming Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(radius_text)
+                    .with_child(self.radius_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.move_player(context);
+        self.stream_chunks(context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Level Streaming\n[W][A][S][D] - Move\nActive Chunks: {}\n\
+                        Loading Chunks: {}\n{}\n{}",
+                        self.active_chunks.len(),
+                        self.loading_chunks.len(),
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, _context: PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            let value = if event.state == ElementState::Pressed {
+                1.0
+            } else {
+                0.0
+            };
+            // The camera looks along +Z axis, the player moves relative to it.
+            match event.physical_key {
+                PhysicalKey::Code(KeyCode::KeyW) => self.move_direction.y = value,
+                PhysicalKey::Code(KeyCode::KeyS) => self.move_direction.y = -value,
+                PhysicalKey::Code(KeyCode::KeyA) => self.move_direction.x = value,
+                PhysicalKey::Code(KeyCode::KeyD) => self.move_direction.x = -value,
+                _ => (),
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, _context: &mut PluginContext, message: &UiMessage) {
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.radius_slider
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.streaming_radius = *value;
+            }
+        }
+    }
+
+    fn on_scene_loaded(
+        &mut self,
+        path: &Path,
+        scene: Handle<Scene>,
+        _data: &[u8],
+        context: &mut PluginContext,
+    ) {
+        let coords = self
+            .loading_chunks
+            .remove(path)
+            .or_else(|| chunk_coords(path));
+
+        // Move the content of the chunk into the master scene, the chunk scene itself is not
+        // needed anymore. The chunk could go out of range while it was loading.
+        let player = self.player_position(context);
+        if let (Some(coords), Some(player)) = (coords, player) {
+            if self.is_chunk_in_range(coords, player, self.streaming_radius + UNLOAD_MARGIN)
+                && !self.active_chunks.contains_key(&coords)
+            {
+                let chunk = std::mem::replace(&mut context.scenes[scene].graph, Graph::new());
+                self.attach_chunk(coords, &chunk, context);
+            }
+        }
+
+        context.scenes.remove(scene);
+    }
+
+    fn on_scene_loading_failed(
+        &mut self,
+        path: &Path,
+        error: &VisitError,
+        _context: &mut PluginContext,
+    ) {
+        let coords = self
+            .loading_chunks
+            .remove(path)
+            .or_else(|| chunk_coords(path));
+        Log::warn(format!(
+            "Unable to load chunk {}, it will be built in memory. Reason: {error:?}",
+            path.display()
+        ));
+        if let Some(coords) = coords {
+            self.missing_chunks.insert(coords);
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
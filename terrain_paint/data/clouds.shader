@@ -0,0 +1,128 @@
+(
+    name: "CloudsShader",
+
+    properties: [
+        // Two octaves of tileable Perlin noise, the second one has higher frequency.
+        (
+            name: "noise1Texture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "noise2Texture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        // Time in seconds since the start of the game.
+        (
+            name: "time",
+            kind: Float(0.0),
+        ),
+        (
+            name: "windSpeed",
+            kind: Float(1.0),
+        ),
+        // Fraction of the sky, that is covered by the clouds.
+        (
+            name: "coverage",
+            kind: Float(0.5),
+        ),
+        (
+            name: "cloudColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                // The clouds are visible from both sides.
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: One,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add,
+                    ),
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D noise1Texture;
+                uniform sampler2D noise2Texture;
+                uniform float time;
+                uniform float windSpeed;
+                uniform float coverage;
+                uniform vec4 cloudColor;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+
+                // Scales of the texture coordinates and scroll directions of the octaves. The
+                // octaves move at different speeds and in slightly different directions, so the
+                // shape of the clouds changes over time instead of just sliding.
+                const float scale1 = 2.0;
+                const float scale2 = 5.0;
+                const vec2 scroll1 = vec2(0.010, 0.004);
+                const vec2 scroll2 = vec2(0.018, -0.006);
+
+                void main()
+                {
+                    float offset = time * windSpeed;
+                    float n1 = texture(noise1Texture, texCoord * scale1 + scroll1 * offset).r;
+                    float n2 = texture(noise2Texture, texCoord * scale2 + scroll2 * offset).r;
+                    float noise = n1 * 0.65 + n2 * 0.35;
+
+                    // The more coverage, the lower the threshold, above which the noise is a cloud.
+                    float threshold = 1.0 - coverage;
+                    float density = smoothstep(threshold, threshold + 0.2, noise);
+
+                    // Clouds fade out towards the edges of the mesh, so its borders are not visible.
+                    float edge = 1.0 - smoothstep(0.35, 0.5, length(texCoord - 0.5));
+
+                    // Denser parts of the clouds are brighter, which gives them some volume.
+                    vec3 color = cloudColor.rgb * mix(0.75, 1.0, noise);
+                    float alpha = density * edge * cloudColor.a;
+
+                    // Premultiplied alpha.
+                    FragColor = vec4(color * alpha, alpha);
+                }
+               "#,
+        ),
+    ],
+)
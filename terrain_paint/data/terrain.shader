@@ -0,0 +1,111 @@
+(
+    name: "TerrainBlendShader",
+
+    properties: [
+        (
+            name: "blendMap",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "grassTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "dirtTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "rockTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "snowTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "layerTiling",
+            kind: Float(32.0),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 normal;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D blendMap;
+                uniform sampler2D grassTexture;
+                uniform sampler2D dirtTexture;
+                uniform sampler2D rockTexture;
+                uniform sampler2D snowTexture;
+                uniform float layerTiling;
+
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+
+                in vec3 normal;
+                in vec2 texCoord;
+
+                void main()
+                {
+                    // Each channel of the blend map holds the weight of a layer, the weights are
+                    // normalized on CPU side, so there's no need to do it here.
+                    vec4 weights = texture(blendMap, texCoord);
+                    vec2 layerTexCoord = texCoord * layerTiling;
+
+                    vec3 color = texture(grassTexture, layerTexCoord).rgb * weights.r
+                        + texture(dirtTexture, layerTexCoord).rgb * weights.g
+                        + texture(rockTexture, layerTexCoord).rgb * weights.b
+                        + texture(snowTexture, layerTexCoord).rgb * weights.a;
+
+                    outColor = vec4(color, 1.0);
+                    outNormal = vec4(normalize(normal) * 0.5 + 0.5, 1.0);
+                    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+                    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+                    outDecalMask = 0u;
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,209 @@
+//! Blend map painting and its undo/redo history.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::algebra::Vector2,
+    resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
+};
+use std::collections::VecDeque;
+
+/// Size of the blend map along each axis.
+pub const BLEND_MAP_SIZE: usize = 512;
+/// Maximum amount of snapshots stored in the undo history, the oldest ones are discarded.
+const HISTORY_CAPACITY: usize = 16;
+
+/// Terrain layers, each one is stored in its own channel of the blend map.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Layer {
+    #[default]
+    Grass = 0,
+    Dirt = 1,
+    Rock = 2,
+    Snow = 3,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [Layer::Grass, Layer::Dirt, Layer::Rock, Layer::Snow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Grass => "Grass",
+            Layer::Dirt => "Dirt",
+            Layer::Rock => "Rock",
+            Layer::Snow => "Snow",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Brush {
+    pub layer: Layer,
+    /// Radius of the brush in blend map pixels.
+    pub radius: f32,
+    /// 0.0 - very soft falloff, 1.0 - almost flat top.
+    pub hardness: f32,
+    /// Amount of weight added at the center of the brush per single dab.
+    pub strength: f32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            layer: Layer::Dirt,
+            radius: 16.0,
+            hardness: 0.5,
+            strength: 0.25,
+        }
+    }
+}
+
+/// Snapshot of the blend map, stored as RGBA8 to save some memory.
+type Snapshot = Vec<u8>;
+
+#[derive(Debug)]
+pub struct BlendMap {
+    // Weights are kept in floating point form while painting, otherwise small amounts of weight
+    // added by each dab would be lost because of quantization.
+    weights: Vec<[f32; 4]>,
+    texture: TextureResource,
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    dirty: bool,
+}
+
+impl Default for BlendMap {
+    fn default() -> Self {
+        // Everything is covered with grass at the beginning.
+        let weights = vec![[1.0, 0.0, 0.0, 0.0]; BLEND_MAP_SIZE * BLEND_MAP_SIZE];
+
+        let texture = TextureResource::from_bytes(
+            TextureKind::Rectangle {
+                width: BLEND_MAP_SIZE as u32,
+                height: BLEND_MAP_SIZE as u32,
+            },
+            TexturePixelKind::RGBA8,
+            to_bytes(&weights),
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+
+        Self {
+            weights,
+            texture,
+            undo_stack: Default::default(),
+            redo_stack: Default::default(),
+            dirty: false,
+        }
+    }
+}
+
+fn to_bytes(weights: &[[f32; 4]]) -> Vec<u8> {
+    weights
+        .iter()
+        .flat_map(|w| w.map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8))
+        .collect()
+}
+
+fn from_bytes(bytes: &[u8]) -> Vec<[f32; 4]> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| [0, 1, 2, 3].map(|i| c[i] as f32 / 255.0))
+        .collect()
+}
+
+impl BlendMap {
+    pub fn texture(&self) -> &TextureResource {
+        &self.texture
+    }
+
+    /// Saves the current weights to the undo history, the oldest snapshot is dropped when the
+    /// history is full.
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == HISTORY_CAPACITY {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(to_bytes(&self.weights));
+    }
+
+    /// Must be called before every stroke, so the stroke could be undone.
+    pub fn begin_stroke(&mut self) {
+        self.push_undo();
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop_back() {
+            self.redo_stack.push(to_bytes(&self.weights));
+            self.weights = from_bytes(&snapshot);
+            self.dirty = true;
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            self.push_undo();
+            self.weights = from_bytes(&snapshot);
+            self.dirty = true;
+        }
+    }
+
+    /// Adds weight of the brush layer around the given point (in normalized [0; 1] coordinates)
+    /// using gaussian falloff and then re-normalizes the weights of each affected pixel.
+    pub fn paint(&mut self, uv: Vector2<f32>, brush: &Brush) {
+        let center = uv.scale(BLEND_MAP_SIZE as f32);
+        let radius = brush.radius.max(1.0);
+        // Hard brushes have wider bell, so the weight stays high almost up to the edge.
+        let sigma = 0.25 + 0.75 * brush.hardness.clamp(0.0, 1.0);
+
+        let min_x = (center.x - radius).floor().max(0.0) as usize;
+        let min_y = (center.y - radius).floor().max(0.0) as usize;
+        let max_x = ((center.x + radius).ceil().max(0.0) as usize).min(BLEND_MAP_SIZE);
+        let max_y = ((center.y + radius).ceil().max(0.0) as usize).min(BLEND_MAP_SIZE);
+
+        let channel = brush.layer as usize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let distance =
+                    Vector2::new(x as f32 + 0.5, y as f32 + 0.5).metric_distance(&center);
+                let t = distance / radius;
+                if t > 1.0 {
+                    continue;
+                }
+
+                let influence = brush.strength * (-(t * t) / (2.0 * sigma * sigma)).exp();
+
+                let pixel = &mut self.weights[y * BLEND_MAP_SIZE + x];
+                pixel[channel] += influence;
+
+                let sum = pixel.iter().sum::<f32>();
+                if sum > 0.0 {
+                    for weight in pixel.iter_mut() {
+                        *weight /= sum;
+                    }
+                }
+            }
+        }
+
+        self.dirty = true;
+    }
+
+    /// Uploads the weights to the texture, if there were any changes since the last call.
+    pub fn upload(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let mut texture = self.texture.data_ref();
+        let mut modifier = texture.modify();
+        for (pixel, weights) in modifier
+            .data_mut()
+            .chunks_exact_mut(4)
+            .zip(self.weights.iter())
+        {
+            for (byte, weight) in pixel.iter_mut().zip(weights) {
+                *byte = (weight.clamp(0.0, 1.0) * 255.0) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Sky clouds - a large flat mesh above the terrain with a material, that mixes two octaves of
+//! tileable noise scrolled by the wind.
+use crate::{make_label, make_slider};
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+    },
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::ScrollBarMessage,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, UiNode,
+    },
+    material::{shader::Shader, Material, MaterialResource},
+    resource::texture::TextureResource,
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder, RenderPath,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    },
+};
+
+/// Height of the clouds above the terrain.
+const CLOUDS_HEIGHT: f32 = 30.0;
+/// Size of the cloud mesh along each axis, it is larger than the terrain, so the clouds cover the
+/// whole play area.
+const CLOUDS_SIZE: f32 = 300.0;
+
+#[derive(Debug)]
+pub struct Clouds {
+    mesh: Handle<Node>,
+    material: Option<MaterialResource>,
+    /// Time in seconds since the start of the game, the noise is scrolled by it.
+    elapsed: f32,
+    coverage: f32,
+    wind_speed: f32,
+    color: Color,
+    coverage_slider: Handle<UiNode>,
+    wind_speed_slider: Handle<UiNode>,
+    /// Sliders of the red, green and blue components of the color of the clouds.
+    color_sliders: [Handle<UiNode>; 3],
+}
+
+impl Default for Clouds {
+    fn default() -> Self {
+        Self {
+            mesh: Default::default(),
+            material: None,
+            elapsed: 0.0,
+            coverage: 0.35,
+            wind_speed: 1.0,
+            color: Color::opaque(245, 245, 250),
+            coverage_slider: Default::default(),
+            wind_speed_slider: Default::default(),
+            color_sliders: Default::default(),
+        }
+    }
+}
+
+impl Clouds {
+    pub fn build_panel(&mut self, ctx: &mut BuildContext) {
+        self.coverage_slider = make_slider(ctx, 0, 0.0, 1.0, self.coverage);
+        self.wind_speed_slider = make_slider(ctx, 1, 0.0, 10.0, self.wind_speed);
+        let components = [self.color.r, self.color.g, self.color.b];
+        for (row, (slider, component)) in self.color_sliders.iter_mut().zip(components).enumerate()
+        {
+            *slider = make_slider(ctx, row + 2, 0.0, 1.0, component as f32 / 255.0);
+        }
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(280.0)
+                .with_desired_position(Vector2::new(5.0, 360.0)),
+        )
+        .with_title(WindowTitle::text("Clouds"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(make_label(ctx, 0, "Coverage"))
+                    .with_child(self.coverage_slider)
+                    .with_child(make_label(ctx, 1, "Wind Speed"))
+                    .with_child(self.wind_speed_slider)
+                    .with_child(make_label(ctx, 2, "Red"))
+                    .with_child(self.color_sliders[0])
+                    .with_child(make_label(ctx, 3, "Green"))
+                    .with_child(self.color_sliders[1])
+                    .with_child(make_label(ctx, 4, "Blue"))
+                    .with_child(self.color_sliders[2]),
+            )
+            .add_column(Column::strict(70.0))
+            .add_column(Column::stretch())
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    /// Creates the cloud mesh above the terrain.
+    pub fn build(
+        &mut self,
+        graph: &mut Graph,
+        shader: Shader,
+        noise_textures: [TextureResource; 2],
+    ) {
+        let mut material = Material::from_shader(shader, None);
+        let [noise1, noise2] = noise_textures;
+        Log::verify(material.set_texture(&"noise1Texture".into(), Some(noise1)));
+        Log::verify(material.set_texture(&"noise2Texture".into(), Some(noise2)));
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+
+        // The quad lies in XY plane, it is rotated to be parallel to the ground.
+        let transform =
+            Matrix4::new_nonuniform_scaling(&Vector3::new(CLOUDS_SIZE, CLOUDS_SIZE, 1.0));
+        self.mesh = MeshBuilder::new(
+            BaseBuilder::new().with_name("Clouds").with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, CLOUDS_HEIGHT, 0.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        90.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_quad(&transform),
+        ))
+        .with_material(material.clone())
+        .build()])
+        // The clouds are transparent, they must be drawn after the opaque terrain.
+        .with_render_path(RenderPath::Forward)
+        .build(graph);
+        self.material = Some(material);
+    }
+
+    /// Scrolls the clouds and applies the current settings to the material.
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+
+        let Some(material) = self.material.as_ref() else {
+            return;
+        };
+        let mut material = material.data_ref();
+        Log::verify(material.set_property(&"time".into(), self.elapsed.into()));
+        Log::verify(material.set_property(&"windSpeed".into(), self.wind_speed.into()));
+        Log::verify(material.set_property(&"coverage".into(), self.coverage.into()));
+        Log::verify(material.set_property(&"cloudColor".into(), self.color.into()));
+    }
+
+    /// Returns the amount of triangles in the cloud mesh, or zero if it is not built yet.
+    pub fn triangle_count(&self, graph: &Graph) -> usize {
+        graph.try_get_of_type::<Mesh>(self.mesh).map_or(0, |mesh| {
+            mesh.surfaces()
+                .iter()
+                .map(|surface| surface.data().data_ref().geometry_buffer.len())
+                .sum()
+        })
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if message.destination() == self.coverage_slider {
+            self.coverage = *value;
+        } else if message.destination() == self.wind_speed_slider {
+            self.wind_speed = *value;
+        } else if let Some(index) = self
+            .color_sliders
+            .iter()
+            .position(|slider| *slider == message.destination())
+        {
+            let component = (*value * 255.0) as u8;
+            match index {
+                0 => self.color.r = component,
+                1 => self.color.g = component,
+                _ => self.color.b = component,
+            }
+        }
+    }
+}
@@ -0,0 +1,656 @@
+//! Game project.
+use crate::{
+    blend_map::{BlendMap, Brush, Layer, BLEND_MAP_SIZE},
+    clouds::Clouds,
+    vertex_painter::VertexPainter,
+};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        math::plane::Plane,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        image::ImageBuilder,
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    material::{shader::Shader, Material, MaterialResource},
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    rand::{thread_rng, Rng},
+    resource::texture::{
+        Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::Mesh,
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::Script,
+};
+
+mod blend_map;
+mod clouds;
+mod vertex_painter;
+
+/// Size of the terrain in world units along each axis.
+const TERRAIN_SIZE: f32 = 100.0;
+
+const SCENE_NAME: &str = "terrain_paint";
+
+/// Names of the painting modes, the index of a mode is its index in the mode selector.
+const PAINT_MODES: [&str; 2] = ["Blend Map", "Vertex Colors"];
+/// Names of the vertex color channels, in the same order as the layers.
+const CHANNELS: [&str; 4] = ["R", "G", "B", "A"];
+const LAYER_TEXTURES: [&str; 4] = ["grassTexture", "dirtTexture", "rockTexture", "snowTexture"];
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    terrain: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    brush_size: Handle<UiNode>,
+    brush_hardness: Handle<UiNode>,
+    undo: Handle<UiNode>,
+    redo: Handle<UiNode>,
+    mode_list: Handle<UiNode>,
+    channel_list: Handle<UiNode>,
+    /// `true` if the layers are painted into vertex colors of the terrain instead of the blend map.
+    vertex_painting: bool,
+    cursor_position: Vector2<f32>,
+    painting: bool,
+    control_pressed: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    layer_buttons: Vec<(Layer, Handle<UiNode>)>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    layer_textures: Vec<TextureResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    blend_material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    vertex_color_material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    blend_map: BlendMap,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    brush: Brush,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    clouds: Clouds,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Creates a small noisy texture of the given color, so the layers could be distinguished without
+/// any external assets.
+fn make_layer_texture(color: Color) -> TextureResource {
+    let size = 64;
+    let mut rng = thread_rng();
+    let mut bytes = Vec::with_capacity(size * size * 4);
+    for _ in 0..size * size {
+        let brightness = rng.gen_range(0.75..1.0);
+        bytes.extend_from_slice(&[
+            (color.r as f32 * brightness) as u8,
+            (color.g as f32 * brightness) as u8,
+            (color.b as f32 * brightness) as u8,
+            255,
+        ]);
+    }
+
+    TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: size as u32,
+            height: size as u32,
+        },
+        TexturePixelKind::RGBA8,
+        bytes,
+        ResourceKind::Embedded,
+    )
+    .unwrap()
+}
+
+fn layer_color(layer: Layer) -> Color {
+    match layer {
+        Layer::Grass => Color::opaque(70, 140, 50),
+        Layer::Dirt => Color::opaque(120, 85, 50),
+        Layer::Rock => Color::opaque(110, 110, 115),
+        Layer::Snow => Color::opaque(240, 240, 250),
+    }
+}
+
+/// Creates a terrain material with the layer textures.
+fn make_terrain_material(shader: Shader, layer_textures: &[TextureResource]) -> Material {
+    let mut material = Material::from_shader(shader, None);
+    for (name, texture) in LAYER_TEXTURES.into_iter().zip(layer_textures.iter()) {
+        Log::verify(material.set_texture(&name.into(), Some(texture.clone())));
+    }
+    material
+}
+
+fn make_dropdown_list(ctx: &mut BuildContext, row: usize, items: &[&str]) -> Handle<UiNode> {
+    DropdownListBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_height(22.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_items(
+        items
+            .iter()
+            .map(|item| {
+                TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                    .with_text(*item)
+                    .build(ctx)
+            })
+            .collect(),
+    )
+    .with_selected(0)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_value(value)
+    .with_step((max - min) / 20.0)
+    .show_value(true)
+    .build(ctx)
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl Game {
+    fn build_palette(&mut self, ctx: &mut BuildContext) {
+        let mut buttons = Vec::new();
+        for (layer, texture) in Layer::ALL.iter().zip(self.layer_textures.iter()) {
+            let button = ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(64.0)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            ImageBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_width(48.0)
+                                    .with_height(48.0)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_texture(texture.clone().into())
+                            .build(ctx),
+                        )
+                        .with_child(
+                            TextBuilder::new(WidgetBuilder::new())
+                                .with_text(layer.name())
+                                .build(ctx),
+                        ),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+            self.layer_buttons.push((*layer, button));
+            buttons.push(button);
+        }
+
+        self.brush_size = make_slider(ctx, 0, 2.0, 64.0, self.brush.radius);
+        self.brush_hardness = make_slider(ctx, 1, 0.0, 1.0, self.brush.hardness);
+        self.mode_list = make_dropdown_list(ctx, 2, &PAINT_MODES);
+        let channels = CHANNELS
+            .iter()
+            .zip(Layer::ALL)
+            .map(|(channel, layer)| format!("{channel} - {}", layer.name()))
+            .collect::<Vec<_>>();
+        self.channel_list = make_dropdown_list(
+            ctx,
+            3,
+            &channels.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+
+        self.undo = ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+            .with_text("Undo")
+            .build(ctx);
+        self.redo = ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+            .with_text("Redo")
+            .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(280.0)
+                .with_desired_position(Vector2::new(5.0, 100.0)),
+        )
+        .with_title(WindowTitle::text("Tool Palette"))
+        .can_close(false)
+        .with_content(
+            StackPanelBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        StackPanelBuilder::new(WidgetBuilder::new().with_children(buttons))
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(make_label(ctx, 0, "Size"))
+                                .with_child(self.brush_size)
+                                .with_child(make_label(ctx, 1, "Hardness"))
+                                .with_child(self.brush_hardness)
+                                .with_child(make_label(ctx, 2, "Mode"))
+                                .with_child(self.mode_list)
+                                .with_child(make_label(ctx, 3, "Channel"))
+                                .with_child(self.channel_list)
+                                .with_child(
+                                    StackPanelBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(4)
+                                            .on_column(1)
+                                            .with_child(self.undo)
+                                            .with_child(self.redo),
+                                    )
+                                    .with_orientation(Orientation::Horizontal)
+                                    .build(ctx),
+                                ),
+                        )
+                        .add_column(Column::strict(70.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(26.0))
+                        .add_row(Row::strict(26.0))
+                        .add_row(Row::strict(26.0))
+                        .add_row(Row::strict(26.0))
+                        .add_row(Row::strict(26.0))
+                        .build(ctx),
+                    ),
+            )
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn paint_at_cursor(&mut self, context: &PluginContext) {
+        let GraphicsContext::Initialized(graphics_context) = &context.graphics_context else {
+            return;
+        };
+
+        let Some(camera) = context
+            .scenes
+            .try_get(self.scene)
+            .and_then(|scene| scene.graph.try_get_of_type::<Camera>(self.camera))
+        else {
+            return;
+        };
+
+        let ray = camera.make_ray(
+            self.cursor_position,
+            graphics_context.renderer.get_frame_bounds(),
+        );
+        let ground = Plane::from_normal_and_point(&Vector3::y(), &Vector3::default()).unwrap();
+        if let Some(point) = ray.plane_intersection_point(&ground) {
+            let uv = Vector2::new(
+                point.x / TERRAIN_SIZE + 0.5,
+                point.z / TERRAIN_SIZE + 0.5,
+            );
+            if (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y) {
+                self.blend_map.paint(uv, &self.brush);
+            }
+        }
+    }
+
+    fn vertex_painter_mut<'a>(
+        &self,
+        context: &'a mut PluginContext,
+    ) -> Option<&'a mut VertexPainter> {
+        context
+            .scenes
+            .try_get_mut(self.scene)?
+            .graph
+            .try_get_mut(self.terrain)?
+            .try_get_script_mut::<VertexPainter>()
+    }
+
+    /// Copies the brush to the vertex painter, the radius of the brush is converted from blend
+    /// map pixels to world units.
+    fn sync_vertex_painter(&self, context: &mut PluginContext) {
+        let brush = self.brush;
+        if let Some(painter) = self.vertex_painter_mut(context) {
+            painter
+                .radius
+                .set_value_and_mark_modified(brush.radius / BLEND_MAP_SIZE as f32 * TERRAIN_SIZE);
+            painter.hardness.set_value_and_mark_modified(brush.hardness);
+            painter.strength.set_value_and_mark_modified(brush.strength);
+        }
+    }
+
+    /// Switches the material of the terrain between the blend map and the vertex colors.
+    fn set_vertex_painting(&mut self, vertex_painting: bool, context: &mut PluginContext) {
+        let material = if vertex_painting {
+            self.vertex_color_material.clone()
+        } else {
+            self.blend_material.clone()
+        };
+        let Some(material) = material else {
+            Log::warn("Terrain material is not loaded yet!");
+            return;
+        };
+        self.vertex_painting = vertex_painting;
+        self.painting = false;
+
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            for chunk in scene.graph[self.terrain].children().to_vec() {
+                if let Some(mesh) = scene.graph.try_get_mut_of_type::<Mesh>(chunk) {
+                    for surface in mesh.surfaces_mut() {
+                        surface.set_material(material.clone());
+                    }
+                }
+            }
+        }
+        if let Some(painter) = self.vertex_painter_mut(context) {
+            painter.enabled = vertex_painting;
+        }
+    }
+
+    fn is_cursor_over_ui(&self, context: &PluginContext) -> bool {
+        let ui = context.user_interfaces.first();
+        let picked = ui.hit_test(self.cursor_position);
+        picked.is_some() && picked != ui.root()
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<VertexPainter>("Vertex Painter");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 60.0, -70.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        self.layer_textures = Layer::ALL
+            .iter()
+            .map(|layer| make_layer_texture(layer_color(*layer)))
+            .collect();
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+        self.build_palette(ctx);
+        self.clouds.build_panel(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/terrain.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => {
+                    let mut material = make_terrain_material(shader, &game.layer_textures);
+                    Log::verify(
+                        material.set_texture(
+                            &"blendMap".into(),
+                            Some(game.blend_map.texture().clone()),
+                        ),
+                    );
+                    let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+                    game.blend_material = Some(material.clone());
+
+                    let graph = &mut ctx.scenes[game.scene].graph;
+                    game.terrain = vertex_painter::build_terrain(graph, TERRAIN_SIZE, material);
+                    graph[game.terrain].add_script(Script::new(VertexPainter::new(game.camera)));
+                    game.sync_vertex_painter(ctx);
+                }
+                Err(err) => Log::err(format!("Unable to load terrain shader. Reason: {err:?}")),
+            },
+        );
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/clouds.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => {
+                    let noise_textures = ["data/noise1.png", "data/noise2.png"]
+                        .map(|path| ctx.resource_manager.request::<Texture>(path));
+                    game.clouds
+                        .build(&mut ctx.scenes[game.scene].graph, shader, noise_textures);
+                }
+                Err(err) => Log::err(format!("Unable to load clouds shader. Reason: {err:?}")),
+            },
+        );
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/vertex_color_terrain.shader"),
+            |result, game: &mut Game, _ctx| match result {
+                Ok(shader) => {
+                    game.vertex_color_material = Some(MaterialResource::new_ok(
+                        ResourceKind::Embedded,
+                        make_terrain_material(shader, &game.layer_textures),
+                    ));
+                }
+                Err(err) => Log::err(format!(
+                    "Unable to load vertex color terrain shader. Reason: {err:?}"
+                )),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.blend_map.upload();
+        self.clouds.update(context.dt);
+        let cloud_triangles = context
+            .scenes
+            .try_get(self.scene)
+            .map_or(0, |scene| self.clouds.triangle_count(&scene.graph));
+
+        let layer = if self.vertex_painting {
+            self.vertex_painter_mut(context)
+                .and_then(|painter| CHANNELS.get(*painter.channel))
+                .copied()
+                .unwrap_or_default()
+        } else {
+            self.brush.layer.name()
+        };
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Terrain Painting\nHold [LMB] to paint the selected layer, \
+                    [Ctrl+Z] to undo, [Ctrl+Y] to redo.\nMode: {}\nLayer: {}\n\
+                    Cloud Triangles: {}\nFPS: {}\n{}",
+                    PAINT_MODES[self.vertex_painting as usize],
+                    layer,
+                    cloud_triangles,
+                    graphics_context.renderer.get_statistics().frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, context: PluginContext) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor_position = Vector2::new(position.x as f32, position.y as f32);
+                    if self.painting {
+                        self.paint_at_cursor(&context);
+                    }
+                }
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                } => match state {
+                    ElementState::Pressed => {
+                        // Vertex colors are painted by the script of the terrain.
+                        if !self.vertex_painting && !self.is_cursor_over_ui(&context) {
+                            self.painting = true;
+                            self.blend_map.begin_stroke();
+                            self.paint_at_cursor(&context);
+                        }
+                    }
+                    ElementState::Released => self.painting = false,
+                },
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.control_pressed = modifiers.state().control_key();
+                }
+                WindowEvent::KeyboardInput { event, .. } => {
+                    if event.state == ElementState::Pressed
+                        && self.control_pressed
+                        && !self.vertex_painting
+                    {
+                        match event.physical_key {
+                            PhysicalKey::Code(KeyCode::KeyZ) => self.blend_map.undo(),
+                            PhysicalKey::Code(KeyCode::KeyY) => self.blend_map.redo(),
+                            _ => (),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        self.clouds.handle_ui_message(message);
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.undo {
+                if self.vertex_painting {
+                    if let Some(painter) = self.vertex_painter_mut(context) {
+                        painter.request_undo();
+                    }
+                } else {
+                    self.blend_map.undo();
+                }
+            } else if message.destination() == self.redo {
+                // Vertex painter keeps only the undo history.
+                if !self.vertex_painting {
+                    self.blend_map.redo();
+                }
+            } else if let Some((layer, _)) = self
+                .layer_buttons
+                .iter()
+                .find(|(_, button)| *button == message.destination())
+            {
+                self.brush.layer = *layer;
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.brush_size {
+                    self.brush.radius = *value;
+                } else if message.destination() == self.brush_hardness {
+                    self.brush.hardness = *value;
+                }
+                self.sync_vertex_painter(context);
+            }
+        } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.mode_list {
+                    self.set_vertex_painting(*index == 1, context);
+                } else if message.destination() == self.channel_list {
+                    let channel = *index;
+                    if let Some(painter) = self.vertex_painter_mut(context) {
+                        painter.channel.set_value_and_mark_modified(channel);
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Painting of the layer weights into vertex colors of the terrain mesh. Unlike the blend map, it
+//! needs no extra texture, but its resolution is limited by the density of the mesh.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Point3, Vector2, Vector3},
+        log::Log,
+        math::{plane::Plane, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, MouseButton, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    keyboard::{KeyCode, PhysicalKey},
+    material::MaterialResource,
+    scene::{
+        base::BaseBuilder,
+        camera::Camera,
+        graph::Graph,
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexReadTrait, VertexTrait, VertexWriteTrait,
+            },
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+use std::collections::VecDeque;
+
+/// Amount of chunks along each side of the terrain.
+const CHUNK_COUNT: usize = 4;
+/// Amount of quads along each side of a chunk.
+const CHUNK_RESOLUTION: usize = 32;
+/// Maximum amount of snapshots stored in the undo history, the oldest ones are discarded.
+const HISTORY_CAPACITY: usize = 16;
+
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+struct TerrainVertex {
+    position: Vector3<f32>,
+    tex_coord: Vector2<f32>,
+    normal: Vector3<f32>,
+    /// Weights of the layers, one per channel.
+    color: [u8; 4],
+}
+
+impl VertexTrait for TerrainVertex {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &[
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Position,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 0,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::TexCoord0,
+                data_type: VertexAttributeDataType::F32,
+                size: 2,
+                divisor: 0,
+                shader_location: 1,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Normal,
+                data_type: VertexAttributeDataType::F32,
+                size: 3,
+                divisor: 0,
+                shader_location: 2,
+                normalized: false,
+            },
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::U8,
+                size: 4,
+                divisor: 0,
+                shader_location: 3,
+                normalized: true,
+            },
+        ]
+    }
+}
+
+/// Builds a flat chunk of the terrain with its center at the origin. Texture coordinates are
+/// the coordinates on the whole terrain, so the blend map could be used with the chunks too.
+fn make_chunk_surface(size: f32, uv_offset: Vector2<f32>, uv_scale: f32) -> SurfaceData {
+    let step = size / CHUNK_RESOLUTION as f32;
+    let mut vertices = Vec::with_capacity((CHUNK_RESOLUTION + 1) * (CHUNK_RESOLUTION + 1));
+    for z in 0..=CHUNK_RESOLUTION {
+        for x in 0..=CHUNK_RESOLUTION {
+            let local = Vector2::new(x as f32, z as f32) / CHUNK_RESOLUTION as f32;
+            vertices.push(TerrainVertex {
+                position: Vector3::new(
+                    x as f32 * step - size * 0.5,
+                    0.0,
+                    z as f32 * step - size * 0.5,
+                ),
+                tex_coord: uv_offset + local.scale(uv_scale),
+                normal: Vector3::y(),
+                // Everything is covered with grass at the beginning.
+                color: [255, 0, 0, 0],
+            });
+        }
+    }
+
+    let row = CHUNK_RESOLUTION as u32 + 1;
+    let mut triangles = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION * 2);
+    for z in 0..CHUNK_RESOLUTION as u32 {
+        for x in 0..CHUNK_RESOLUTION as u32 {
+            let i = z * row + x;
+            triangles.push(TriangleDefinition([i, i + 1, i + row + 1]));
+            triangles.push(TriangleDefinition([i, i + row + 1, i + row]));
+        }
+    }
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+    )
+}
+
+/// Builds the terrain of the given size, that consists of a grid of chunks. Chunks are children
+/// of the returned node.
+pub fn build_terrain(graph: &mut Graph, size: f32, material: MaterialResource) -> Handle<Node> {
+    let chunk_size = size / CHUNK_COUNT as f32;
+    let uv_scale = 1.0 / CHUNK_COUNT as f32;
+    let mut chunks = Vec::with_capacity(CHUNK_COUNT * CHUNK_COUNT);
+    for z in 0..CHUNK_COUNT {
+        for x in 0..CHUNK_COUNT {
+            let center = Vector2::new(x as f32 + 0.5, z as f32 + 0.5).scale(chunk_size)
+                - Vector2::repeat(size * 0.5);
+            let chunk = MeshBuilder::new(
+                BaseBuilder::new()
+                    .with_name(format!("Chunk {x}-{z}"))
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(center.x, 0.0, center.y))
+                            .build(),
+                    ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                make_chunk_surface(
+                    chunk_size,
+                    Vector2::new(x as f32, z as f32).scale(uv_scale),
+                    uv_scale,
+                ),
+            ))
+            .with_material(material.clone())
+            .build()])
+            .build(graph);
+            chunks.push(chunk);
+        }
+    }
+
+    BaseBuilder::new()
+        .with_name("Terrain")
+        .with_children(&chunks)
+        .build(graph)
+}
+
+/// Vertex colors of every chunk of the terrain.
+type Snapshot = Vec<(Handle<Node>, Vec<[u8; 4]>)>;
+
+/// Paints the weight of a layer into vertex colors of the chunks of the terrain, while left mouse
+/// button is held. Must be assigned to the node, that contains the chunks.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "d41f7c28-9b3e-4a65-8e0d-6c2a5f1b9e73")]
+#[visit(optional)]
+pub struct VertexPainter {
+    pub camera: InheritableVariable<Handle<Node>>,
+    /// Index of the channel, that is painted: 0 - R, 1 - G, 2 - B, 3 - A.
+    pub channel: InheritableVariable<usize>,
+    /// Radius of the brush in world units.
+    pub radius: InheritableVariable<f32>,
+    /// 0.0 - very soft falloff, 1.0 - almost flat top.
+    pub hardness: InheritableVariable<f32>,
+    /// Amount of weight added at the center of the brush per single dab.
+    pub strength: InheritableVariable<f32>,
+
+    /// Painting is enabled only when the vertex colors are used by the terrain material.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub enabled: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cursor_position: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    painting: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    control_pressed: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    undo_requested: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    undo_stack: VecDeque<Snapshot>,
+}
+
+impl Default for VertexPainter {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            channel: 1.into(),
+            radius: 3.0.into(),
+            hardness: 0.5.into(),
+            strength: 0.25.into(),
+            enabled: false,
+            cursor_position: Default::default(),
+            painting: false,
+            control_pressed: false,
+            undo_requested: false,
+            undo_stack: Default::default(),
+        }
+    }
+}
+
+impl VertexPainter {
+    pub fn new(camera: Handle<Node>) -> Self {
+        Self {
+            camera: camera.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The last stroke will be undone on the next update.
+    pub fn request_undo(&mut self) {
+        self.undo_requested = true;
+    }
+
+    fn chunks(&self, ctx: &ScriptContext) -> Vec<Handle<Node>> {
+        ctx.scene.graph[ctx.handle]
+            .children()
+            .iter()
+            .copied()
+            .filter(|chunk| ctx.scene.graph.try_get_of_type::<Mesh>(*chunk).is_some())
+            .collect()
+    }
+
+    fn snapshot(&self, ctx: &ScriptContext) -> Snapshot {
+        let mut snapshot = Vec::new();
+        for chunk in self.chunks(ctx) {
+            let Some(surface) = ctx.scene.graph[chunk]
+                .cast::<Mesh>()
+                .and_then(|mesh| mesh.surfaces().first())
+            else {
+                continue;
+            };
+            let colors: Vec<[u8; 4]> = surface
+                .data()
+                .data_ref()
+                .vertex_buffer
+                .iter()
+                .filter_map(|vertex| vertex.read_4_u8(VertexAttributeUsage::Color).ok())
+                .map(|color| color.into())
+                .collect();
+            snapshot.push((chunk, colors));
+        }
+        snapshot
+    }
+
+    fn begin_stroke(&mut self, ctx: &ScriptContext) {
+        if self.undo_stack.len() == HISTORY_CAPACITY {
+            self.undo_stack.pop_front();
+        }
+        let snapshot = self.snapshot(ctx);
+        self.undo_stack.push_back(snapshot);
+    }
+
+    fn undo(&mut self, ctx: &mut ScriptContext) {
+        let Some(snapshot) = self.undo_stack.pop_back() else {
+            return;
+        };
+        for (chunk, colors) in snapshot {
+            let Some(surface) = ctx
+                .scene
+                .graph
+                .try_get_of_type::<Mesh>(chunk)
+                .and_then(|mesh| mesh.surfaces().first())
+            else {
+                continue;
+            };
+            let resource = surface.data();
+            let mut data = resource.data_ref();
+            for (mut vertex, color) in data.vertex_buffer.modify().iter_mut().zip(colors) {
+                Log::verify(vertex.write_4_u8(VertexAttributeUsage::Color, color.into()));
+            }
+        }
+    }
+
+    /// Returns the point of the terrain under the cursor.
+    fn pick(&self, ctx: &ScriptContext) -> Option<Vector3<f32>> {
+        let GraphicsContext::Initialized(graphics_context) = &ctx.graphics_context else {
+            return None;
+        };
+        let camera = ctx.scene.graph.try_get_of_type::<Camera>(*self.camera)?;
+        let ray = camera.make_ray(
+            self.cursor_position,
+            graphics_context.renderer.get_frame_bounds(),
+        );
+        let ground = Plane::from_normal_and_point(
+            &Vector3::y(),
+            &ctx.scene.graph[ctx.handle].global_position(),
+        )?;
+        ray.plane_intersection_point(&ground)
+    }
+
+    /// Adds weight of the channel around the given point using gaussian falloff and then
+    /// re-normalizes the weights of each affected vertex.
+    fn paint(&self, ctx: &ScriptContext, point: Vector3<f32>) {
+        let radius = self.radius.max(0.1);
+        // Hard brushes have wider bell, so the weight stays high almost up to the edge.
+        let sigma = 0.25 + 0.75 * self.hardness.clamp(0.0, 1.0);
+        let channel = (*self.channel).min(3);
+
+        for chunk in self.chunks(ctx) {
+            let Some(mesh) = ctx.scene.graph.try_get_of_type::<Mesh>(chunk) else {
+                continue;
+            };
+            // Only the chunks, that are touched by the brush, are modified.
+            let bounds = mesh.world_bounding_box();
+            let closest = point.sup(&bounds.min).inf(&bounds.max);
+            if Vector2::new(closest.x - point.x, closest.z - point.z).norm() > radius {
+                continue;
+            }
+
+            let Some(surface) = mesh.surfaces().first() else {
+                continue;
+            };
+            let to_world = mesh.global_transform();
+            let resource = surface.data();
+            let mut data = resource.data_ref();
+            for mut vertex in data.vertex_buffer.modify().iter_mut() {
+                let Ok(position) = vertex.read_3_f32(VertexAttributeUsage::Position) else {
+                    continue;
+                };
+                let position = to_world.transform_point(&Point3::from(position)).coords;
+                let t = Vector2::new(position.x - point.x, position.z - point.z).norm() / radius;
+                if t > 1.0 {
+                    continue;
+                }
+                let Ok(color) = vertex.read_4_u8(VertexAttributeUsage::Color) else {
+                    continue;
+                };
+
+                let mut weights = color.map(|c| c as f32 / 255.0);
+                weights[channel] += *self.strength * (-(t * t) / (2.0 * sigma * sigma)).exp();
+                let sum = weights.iter().sum::<f32>();
+                if sum > 0.0 {
+                    for weight in weights.iter_mut() {
+                        *weight /= sum;
+                    }
+                }
+
+                let color = weights.map(|w| (w.clamp(0.0, 1.0) * 255.0).round() as u8);
+                Log::verify(vertex.write_4_u8(VertexAttributeUsage::Color, color));
+            }
+        }
+    }
+
+    fn paint_at_cursor(&self, ctx: &ScriptContext) {
+        if let Some(point) = self.pick(ctx) {
+            self.paint(ctx, point);
+        }
+    }
+
+    fn is_cursor_over_ui(&self, ctx: &ScriptContext) -> bool {
+        let ui = ctx.user_interfaces.first();
+        let picked = ui.hit_test(self.cursor_position);
+        picked.is_some() && picked != ui.root()
+    }
+}
+
+impl ScriptTrait for VertexPainter {
+    fn on_os_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) {
+        let Event::WindowEvent { event, .. } = event else {
+            return;
+        };
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Vector2::new(position.x as f32, position.y as f32);
+                if self.painting {
+                    self.paint_at_cursor(ctx);
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    if self.enabled && !self.is_cursor_over_ui(ctx) {
+                        self.painting = true;
+                        self.begin_stroke(ctx);
+                        self.paint_at_cursor(ctx);
+                    }
+                }
+                ElementState::Released => self.painting = false,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.control_pressed = modifiers.state().control_key();
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if self.enabled
+                    && self.control_pressed
+                    && event.state == ElementState::Pressed
+                    && event.physical_key == PhysicalKey::Code(KeyCode::KeyZ)
+                {
+                    self.undo(ctx);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if self.undo_requested {
+            self.undo_requested = false;
+            self.undo(ctx);
+        }
+    }
+}
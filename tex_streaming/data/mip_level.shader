@@ -0,0 +1,96 @@
+(
+    name: "MipLevelShader",
+
+    properties: [
+        (
+            name: "diffuseTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "mipLevel",
+            kind: UInt(0),
+        ),
+        (
+            name: "mipCount",
+            kind: UInt(1),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 normal;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D diffuseTexture;
+                uniform uint mipLevel;
+                uniform uint mipCount;
+
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+
+                in vec3 normal;
+                in vec2 texCoord;
+
+                void main()
+                {
+                    // Sample only the mip level that is "streamed in" at the moment, finer levels
+                    // are considered as not loaded yet.
+                    vec3 color = textureLod(diffuseTexture, texCoord, float(mipLevel)).rgb;
+
+                    // Overlay goes from red (the coarsest level) to green (full resolution).
+                    float quality = mipCount > 1u
+                        ? 1.0 - float(mipLevel) / float(mipCount - 1u)
+                        : 1.0;
+                    vec3 overlay = mix(vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), quality);
+
+                    outColor = vec4(mix(color, overlay, 0.35), 1.0);
+                    outNormal = vec4(normalize(normal) * 0.5 + 0.5, 1.0);
+                    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+                    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+                    outDecalMask = 0u;
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,352 @@
+//! Game project.
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    plugin::{Plugin, PluginContext},
+    resource::texture::{Texture, TextureResource},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::path::{Path, PathBuf};
+
+/// Folder with the streamed textures - the diffuse textures of the Sponza scene.
+const TEXTURE_DIR: &str = "data/textures";
+/// Total amount of textured meshes in the scene.
+const MESH_COUNT: usize = 50;
+/// Amount of meshes in a single row of the grid.
+const GRID_WIDTH: usize = 10;
+/// Distance between meshes in the grid.
+const GRID_SPACING: f32 = 2.5;
+/// Maximum amount of textures, that can be loaded at the same time. Streaming works at low
+/// priority - it never occupies the loader with all the textures at once.
+const MAX_IN_FLIGHT: usize = 4;
+/// Time (in seconds) between two texture requests.
+const REQUEST_INTERVAL: f32 = 0.1;
+/// Time (in seconds) it takes to upgrade a loaded texture by one mip level.
+const MIP_UPGRADE_INTERVAL: f32 = 0.4;
+
+#[derive(Debug)]
+struct StreamedTexture {
+    path: PathBuf,
+    /// `None` until the texture is requested.
+    texture: Option<TextureResource>,
+    material: MaterialResource,
+    /// Mip level, that is currently visible. Zero is the full resolution.
+    mip_level: u32,
+    mip_count: u32,
+    upgrade_timer: f32,
+    failed: bool,
+}
+
+impl StreamedTexture {
+    fn is_loading(&self) -> bool {
+        self.texture
+            .as_ref()
+            .map_or(false, |texture| texture.is_loading())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.mip_count > 0
+    }
+
+    fn set_mip_level(&mut self, level: u32) {
+        self.mip_level = level;
+        Log::verify(
+            self.material
+                .data_ref()
+                .set_property(&"mipLevel".into(), level.into()),
+        );
+    }
+
+    fn update(&mut self, dt: f32) {
+        if self.failed {
+            return;
+        }
+
+        let Some(texture) = self.texture.clone() else {
+            return;
+        };
+
+        if !self.is_ready() {
+            if texture.is_loading() {
+                return;
+            }
+
+            if texture.is_ok() {
+                self.mip_count = texture.data_ref().mip_count().max(1);
+                let mut material = self.material.data_ref();
+                Log::verify(material.set_texture(&"diffuseTexture".into(), Some(texture)));
+                Log::verify(material.set_property(&"mipCount".into(), self.mip_count.into()));
+                drop(material);
+                // Start from the coarsest level and upgrade it progressively.
+                self.set_mip_level(self.mip_count - 1);
+            } else {
+                Log::err(format!("Unable to stream {}!", self.path.display()));
+                self.failed = true;
+            }
+            return;
+        }
+
+        if self.mip_level > 0 {
+            self.upgrade_timer += dt;
+            if self.upgrade_timer >= MIP_UPGRADE_INTERVAL {
+                self.upgrade_timer = 0.0;
+                self.set_mip_level(self.mip_level - 1);
+            }
+        }
+    }
+}
+
+const SCENE_NAME: &str = "tex_streaming";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    request_timer: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    textures: Vec<StreamedTexture>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+/// Collects paths of the textures to stream. The list is sorted to keep the grid stable between
+/// runs and repeated, if there's less textures than meshes.
+fn collect_texture_paths() -> Vec<PathBuf> {
+    let mut paths = std::fs::read_dir(TEXTURE_DIR)
+        .map(|dir| {
+            dir.flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .map_or(false, |ext| ext == "jpg" || ext == "png")
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if paths.is_empty() {
+        Log::err(format!("There's no textures in {TEXTURE_DIR}!"));
+        return paths;
+    }
+
+    paths.sort();
+    paths.into_iter().cycle().take(MESH_COUNT).collect()
+}
+
+fn grid_position(i: usize) -> Vector3<f32> {
+    let rows = MESH_COUNT.div_ceil(GRID_WIDTH);
+    Vector3::new(
+        ((i % GRID_WIDTH) as f32 - (GRID_WIDTH - 1) as f32 * 0.5) * GRID_SPACING,
+        ((rows - 1) as f32 * 0.5 - (i / GRID_WIDTH) as f32) * GRID_SPACING,
+        0.0,
+    )
+}
+
+impl Game {
+    fn build_meshes(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let surface = SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_scaling(2.0)),
+        );
+
+        self.textures = collect_texture_paths()
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                // Every mesh has its own material, because every mesh shows its own mip level.
+                let material = MaterialResource::new_ok(
+                    ResourceKind::Embedded,
+                    Material::from_shader(shader.clone(), None),
+                );
+
+                MeshBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(grid_position(i))
+                            .build(),
+                    ),
+                )
+                .with_surfaces(vec![SurfaceBuilder::new(surface.clone())
+                    .with_material(material.clone())
+                    .build()])
+                .build(&mut scene.graph);
+
+                StreamedTexture {
+                    path,
+                    texture: None,
+                    material,
+                    mip_level: 0,
+                    mip_count: 0,
+                    upgrade_timer: 0.0,
+                    failed: false,
+                }
+            })
+            .collect();
+    }
+
+    /// Requests the next texture, if the loader isn't busy with other textures.
+    fn request_next_texture(&mut self, context: &mut PluginContext) {
+        self.request_timer -= context.dt;
+        if self.request_timer > 0.0 {
+            return;
+        }
+
+        let in_flight = self.textures.iter().filter(|t| t.is_loading()).count();
+        if in_flight >= MAX_IN_FLIGHT {
+            return;
+        }
+
+        if let Some(streamed) = self.textures.iter_mut().find(|t| t.texture.is_none()) {
+            streamed.texture = Some(
+                context
+                    .resource_manager
+                    .request::<Texture>(streamed.path.as_path()),
+            );
+            self.request_timer = REQUEST_INTERVAL;
+        }
+    }
+}
+
+/// Returns the amount of loaded textures and the total amount of requested textures, according
+/// to the resource manager.
+fn streaming_progress(context: &PluginContext) -> (usize, usize) {
+    let state = context.resource_manager.state();
+    let mut loaded = 0;
+    let mut requested = 0;
+    for resource in state.resources() {
+        if let ResourceKind::External(path) = resource.kind() {
+            if path.starts_with(Path::new(TEXTURE_DIR)) {
+                requested += 1;
+                if resource.is_ok() {
+                    loaded += 1;
+                }
+            }
+        }
+    }
+    (loaded, requested)
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -16.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        30.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/mip_level.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.build_meshes(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load mip level shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.request_next_texture(context);
+
+        for streamed in self.textures.iter_mut() {
+            streamed.update(context.dt);
+        }
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let (loaded, requested) = streaming_progress(context);
+            let full_resolution = self
+                .textures
+                .iter()
+                .filter(|t| t.is_ready() && t.mip_level == 0)
+                .count();
+            let total = self.textures.len().max(1);
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Texture Streaming\nOverlay color shows the visible mip level: \
+                    red - the coarsest, green - full resolution.\n\
+                    Requested: {requested}\nLoaded: {loaded}\nFull Resolution: {}/{} ({:.0}%)\n\
+                    FPS: {}\n{}",
+                        full_resolution,
+                        self.textures.len(),
+                        full_resolution as f32 / total as f32 * 100.0,
+                        graphics_context.renderer.get_statistics().frames_per_second,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
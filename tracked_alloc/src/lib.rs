@@ -0,0 +1,92 @@
+//! Global allocator, that tracks the amount of allocated memory. It delegates all the work to the
+//! system allocator and only updates a few atomic counters, so it could be used to profile memory
+//! usage of a demo without external tools.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator, that must be registered with `#[global_allocator]` in an executor:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+/// ```
+pub struct TrackedAlloc;
+
+fn on_alloc(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+fn on_dealloc(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackedAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        on_dealloc(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        // The old block is still alive if the reallocation has failed.
+        if !new_ptr.is_null() {
+            on_dealloc(layout.size());
+            on_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Returns `true` if [`TrackedAlloc`] is registered as the global allocator. Any program
+/// allocates before `main`, so the counters of the registered allocator are never zero.
+pub fn is_installed() -> bool {
+    PEAK.load(Ordering::Relaxed) > 0
+}
+
+/// Amount of bytes, that are allocated right now.
+pub fn current_bytes() -> usize {
+    CURRENT.load(Ordering::Relaxed)
+}
+
+/// Max amount of bytes, that were allocated at the same time since the start of the program or
+/// the last call of [`reset_peak`].
+pub fn peak_bytes() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+/// Starts the tracking of the peak from the current amount of allocated memory.
+pub fn reset_peak() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// Line for the debug text of a demo. The executor must be built with `track_alloc` feature to
+/// register the allocator, otherwise there is nothing to show.
+pub fn peak_ram_text() -> String {
+    if is_installed() {
+        format!("Peak RAM: {:.1} MB", peak_bytes() as f32 / (1024.0 * 1024.0))
+    } else {
+        "Peak RAM: N/A (build with --features track_alloc)".to_string()
+    }
+}
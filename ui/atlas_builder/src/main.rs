@@ -0,0 +1,147 @@
+//! Packs the textures, listed in `data/atlas_inputs.ron`, into a single power-of-two atlas, so the
+//! widgets, that use them, could be drawn without switching textures. Writes the atlas to
+//! `data/atlas.png` and the UV region of every texture to `data/atlas.ron`. Must be run from the
+//! `ui` folder: `cargo run --package atlas_builder`.
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, process::ExitCode};
+
+const CONFIG_PATH: &str = "data/atlas_inputs.ron";
+const ATLAS_PATH: &str = "data/atlas.png";
+const REGIONS_PATH: &str = "data/atlas.ron";
+/// Max size of a side of the atlas, most GPUs support textures of this size.
+const MAX_ATLAS_SIZE: u32 = 8192;
+
+#[derive(Deserialize)]
+struct Config {
+    inputs: Vec<String>,
+    /// Amount of transparent pixels between the textures.
+    #[serde(default)]
+    padding: u32,
+}
+
+/// UV region of a texture in the atlas, all values are in `[0; 1]` range.
+#[derive(Serialize)]
+struct UvRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+/// Position of a texture in the atlas in pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Placement {
+    x: u32,
+    y: u32,
+}
+
+/// Places the rectangles on shelves: the rectangles are sorted by height and put one after
+/// another in a row, when a rectangle does not fit in the row, a new row is started on top of the
+/// tallest rectangle of the current row. Returns the placements in the order of the rectangles and
+/// the used height, or `None` if a rectangle is wider than the atlas.
+fn shelf_pack(sizes: &[(u32, u32)], width: u32, padding: u32) -> Option<(Vec<Placement>, u32)> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut placements = vec![Placement { x: 0, y: 0 }; sizes.len()];
+    let (mut x, mut shelf_y, mut shelf_height) = (0, 0, 0);
+    for i in order {
+        let (w, h) = sizes[i];
+        if w > width {
+            return None;
+        }
+        if x + w > width {
+            shelf_y += shelf_height + padding;
+            x = 0;
+            shelf_height = 0;
+        }
+        placements[i] = Placement { x, y: shelf_y };
+        x += w + padding;
+        shelf_height = shelf_height.max(h);
+    }
+    Some((placements, shelf_y + shelf_height))
+}
+
+/// Finds the power-of-two atlas size with the smallest area, that fits all the rectangles.
+fn pack(sizes: &[(u32, u32)], padding: u32) -> Option<(Vec<Placement>, u32, u32)> {
+    let min_width = sizes.iter().map(|(w, _)| *w).max()?.next_power_of_two();
+    let mut best: Option<(Vec<Placement>, u32, u32)> = None;
+    let mut width = min_width;
+    while width <= MAX_ATLAS_SIZE {
+        if let Some((placements, used_height)) = shelf_pack(sizes, width, padding) {
+            let height = used_height.next_power_of_two();
+            let is_better = best
+                .as_ref()
+                .is_none_or(|(_, w, h)| (width * height) < (w * h));
+            if height <= MAX_ATLAS_SIZE && is_better {
+                best = Some((placements, width, height));
+            }
+        }
+        width *= 2;
+    }
+    best
+}
+
+fn build() -> Result<(), String> {
+    let config = std::fs::read_to_string(CONFIG_PATH)
+        .map_err(|err| format!("Unable to read {CONFIG_PATH}. Reason: {err}"))?;
+    let config = ron::from_str::<Config>(&config)
+        .map_err(|err| format!("Unable to parse {CONFIG_PATH}. Reason: {err}"))?;
+
+    let images = config
+        .inputs
+        .iter()
+        .map(|path| {
+            image::open(path)
+                .map(|image| image.into_rgba8())
+                .map_err(|err| format!("Unable to load {path}. Reason: {err}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let sizes = images
+        .iter()
+        .map(|image| image.dimensions())
+        .collect::<Vec<_>>();
+
+    let (placements, width, height) = pack(&sizes, config.padding)
+        .ok_or_else(|| format!("Unable to fit the textures into {MAX_ATLAS_SIZE}px atlas"))?;
+
+    let mut atlas = RgbaImage::new(width, height);
+    let mut regions = BTreeMap::new();
+    for ((path, image), placement) in config.inputs.iter().zip(images.iter()).zip(placements) {
+        imageops::replace(&mut atlas, image, placement.x as i64, placement.y as i64);
+        regions.insert(
+            path.clone(),
+            UvRect {
+                x: placement.x as f32 / width as f32,
+                y: placement.y as f32 / height as f32,
+                w: image.width() as f32 / width as f32,
+                h: image.height() as f32 / height as f32,
+            },
+        );
+    }
+
+    atlas
+        .save(ATLAS_PATH)
+        .map_err(|err| format!("Unable to save {ATLAS_PATH}. Reason: {err}"))?;
+    let regions = ron::ser::to_string_pretty(&regions, Default::default())
+        .map_err(|err| format!("Unable to serialize atlas regions. Reason: {err}"))?;
+    std::fs::write(REGIONS_PATH, regions)
+        .map_err(|err| format!("Unable to save {REGIONS_PATH}. Reason: {err}"))?;
+
+    println!(
+        "Packed {} texture(s) into {width}x{height} atlas",
+        config.inputs.len()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match build() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,103 @@
+(
+    name: "ColorGradingShader",
+
+    properties: [
+        (
+            name: "sceneTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "lutFrom",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "lutTo",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "mixFactor",
+            kind: Float(0.0),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                // The quad covers the whole screen, there's nothing else in its scene.
+                depth_test: false,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    texCoord = vertexTexCoord;
+                    // The quad is already in normalized device coordinates.
+                    gl_Position = vec4(vertexPosition.xy, 0.0, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D sceneTexture;
+                uniform sampler2D lutFrom;
+                uniform sampler2D lutTo;
+                uniform float mixFactor;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+
+                // The LUT is 16x16x16, unwrapped into a 256x16 image: blue selects a slice, red goes
+                // along the slice width and green along its height.
+                vec3 fetchLut(sampler2D lut, ivec3 cell)
+                {
+                    return texelFetch(lut, ivec2(cell.b * 16 + cell.r, cell.g), 0).rgb;
+                }
+
+                // Trilinear interpolation between the eight nearest cells of the LUT.
+                vec3 sampleLut(sampler2D lut, vec3 color)
+                {
+                    vec3 position = clamp(color, 0.0, 1.0) * 15.0;
+                    ivec3 lo = ivec3(floor(position));
+                    ivec3 hi = min(lo + 1, ivec3(15));
+                    vec3 t = position - vec3(lo);
+
+                    vec3 c00 = mix(fetchLut(lut, ivec3(lo.r, lo.g, lo.b)), fetchLut(lut, ivec3(hi.r, lo.g, lo.b)), t.r);
+                    vec3 c10 = mix(fetchLut(lut, ivec3(lo.r, hi.g, lo.b)), fetchLut(lut, ivec3(hi.r, hi.g, lo.b)), t.r);
+                    vec3 c01 = mix(fetchLut(lut, ivec3(lo.r, lo.g, hi.b)), fetchLut(lut, ivec3(hi.r, lo.g, hi.b)), t.r);
+                    vec3 c11 = mix(fetchLut(lut, ivec3(lo.r, hi.g, hi.b)), fetchLut(lut, ivec3(hi.r, hi.g, hi.b)), t.r);
+
+                    return mix(mix(c00, c10, t.g), mix(c01, c11, t.g), t.b);
+                }
+
+                void main()
+                {
+                    vec3 color = texture(sceneTexture, texCoord).rgb;
+                    vec3 graded = mix(sampleLut(lutFrom, color), sampleLut(lutTo, color), mixFactor);
+                    FragColor = vec4(graded, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
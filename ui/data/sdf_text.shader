@@ -0,0 +1,99 @@
+(
+    name: "SdfTextShader",
+
+    properties: [
+        (
+            name: "fontAtlas",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "textColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+        (
+            name: "distanceRange",
+            kind: Float(6.0),
+        ),
+        (
+            name: "atlasSize",
+            kind: Vector2((512.0, 256.0)),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                // Text is drawn on top of everything.
+                depth_test: false,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add,
+                    ),
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D fontAtlas;
+                uniform vec4 textColor;
+                uniform float distanceRange;
+                uniform vec2 atlasSize;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    // 0.5 is the edge of a glyph, the distance field covers `distanceRange` texels
+                    // of the atlas. Convert the distance to screen pixels, so the edge is always
+                    // one pixel wide, no matter how large the text is.
+                    float distance = texture(fontAtlas, texCoord).r - 0.5;
+                    vec2 unitRange = vec2(distanceRange) / atlasSize;
+                    vec2 screenTexSize = vec2(1.0) / fwidth(texCoord);
+                    float screenPxRange = max(0.5 * dot(unitRange, screenTexSize), 1.0);
+                    float alpha = smoothstep(-0.5, 0.5, screenPxRange * distance);
+
+                    FragColor = vec4(textColor.rgb, textColor.a * alpha);
+                }
+               "#,
+        ),
+    ],
+)
@@ -1,4 +1,7 @@
 //! Executor with your game connected to it as a plugin.
+use common_scripts::FramePacer;
+#[cfg(feature = "record")]
+use common_scripts::Recorder;
 use fyrox::{
     dpi::LogicalSize,
     engine::{executor::Executor, GraphicsContextParams},
@@ -7,6 +10,10 @@ use fyrox::{
 };
 use ui::Game;
 
+#[cfg(feature = "track_alloc")]
+#[global_allocator]
+static GLOBAL: tracked_alloc::TrackedAlloc = tracked_alloc::TrackedAlloc;
+
 fn main() {
     let mut window_attributes = WindowAttributes::default();
     window_attributes.inner_size = Some(LogicalSize::new(1280.0, 720.0).into());
@@ -16,10 +23,25 @@ fn main() {
         EventLoop::new().unwrap(),
         GraphicsContextParams {
             window_attributes,
-            vsync: true,
+            // Frames are paced by `FramePacer`, which needs vsync to be disabled.
+            vsync: false,
             msaa_sample_count: Some(4),
         },
     );
-    executor.add_plugin(Game::default());
+    // `--test-ui` checks the layout of the UI against the references in `data/ui_test_refs.json`,
+    // prints the results and exits with code 1 if any of the checks failed. `--test-ui --bless`
+    // records the references.
+    if std::env::args().any(|arg| arg == "--test-ui") {
+        executor.add_plugin(Game::with_ui_test(
+            std::env::args().any(|arg| arg == "--bless"),
+        ));
+    } else {
+        executor.add_plugin(Game::default());
+    }
+    #[cfg(feature = "record")]
+    if let Some(recorder) = Recorder::from_args() {
+        executor.add_plugin(recorder);
+    }
+    executor.add_plugin(FramePacer::default());
     executor.run()
 }
@@ -0,0 +1,50 @@
+//! UV regions of the textures, that were packed into a single atlas by `atlas_builder`. The atlas
+//! is optional, the textures are used as is, when the atlas was not built.
+use fyrox::core::{log::Log, math::Rect};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Path of the atlas texture, relative to the working directory.
+pub const ATLAS_TEXTURE_PATH: &str = "data/atlas.png";
+/// Path of the file with the regions of the textures in the atlas.
+const REGIONS_PATH: &str = "data/atlas.ron";
+
+/// UV region of a texture in the atlas, all values are in `[0; 1]` range.
+#[derive(Deserialize, Debug)]
+struct UvRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(Debug)]
+pub struct Atlas {
+    regions: HashMap<String, UvRect>,
+}
+
+impl Atlas {
+    /// Loads the regions of the atlas, returns `None` if the atlas was not built.
+    pub fn load() -> Option<Self> {
+        let data = std::fs::read_to_string(REGIONS_PATH).ok()?;
+        match ron::from_str(&data) {
+            Ok(regions) => Some(Self { regions }),
+            Err(err) => {
+                Log::err(format!("Unable to parse {REGIONS_PATH}. Reason: {err:?}"));
+                None
+            }
+        }
+    }
+
+    /// Maps the UV rect in the original texture to the UV rect in the atlas, returns `None` if
+    /// the texture is not in the atlas.
+    pub fn map_uv_rect(&self, texture_path: &str, uv_rect: Rect<f32>) -> Option<Rect<f32>> {
+        let region = self.regions.get(texture_path)?;
+        Some(Rect::new(
+            region.x + uv_rect.position.x * region.w,
+            region.y + uv_rect.position.y * region.h,
+            uv_rect.size.x * region.w,
+            uv_rect.size.y * region.h,
+        ))
+    }
+}
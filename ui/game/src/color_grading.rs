@@ -0,0 +1,283 @@
+//! Color grading presets. Every preset is a 16x16x16 lookup table (LUT), unwrapped into a 256x16
+//! image: 16 slices along the blue axis are placed side by side, red goes along the slice width and
+//! green along its height. The camera can grade the colors with a single LUT only, so the scene is
+//! rendered into a texture instead, that is drawn on the screen by a full-screen quad of a separate
+//! scene. Its shader grades the colors with two LUTs at once and blends the results, so a transition
+//! between presets changes a single property of the material per frame.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        log::Log,
+        math::TriangleDefinition,
+        pool::Handle,
+    },
+    engine::GraphicsContext,
+    material::{shader::ShaderResource, Material, MaterialResource},
+    plugin::PluginContext,
+    resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            MeshBuilder, RenderPath,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+const LUT_SIZE: usize = 16;
+const LUT_WIDTH: usize = LUT_SIZE * LUT_SIZE;
+/// Size of the unwrapped LUT in bytes, the LUT has RGBA8 pixels.
+const LUT_BYTES: usize = LUT_WIDTH * LUT_SIZE * 4;
+
+/// Time (in seconds) that it takes to switch from one LUT to another.
+const TRANSITION_DURATION: f32 = 0.5;
+
+pub const SHADER_PATH: &str = "data/color_grading.shader";
+
+/// Names of the presets and names of their files in `data/luts`. The first preset keeps the colors
+/// as they are.
+pub const PRESETS: [(&str, Option<&str>); 5] = [
+    ("None", None),
+    ("Neutral", Some("neutral")),
+    ("Warm Sunset", Some("warm_sunset")),
+    ("Cool Night", Some("cool_night")),
+    ("Desaturated", Some("desaturated")),
+];
+
+pub fn lut_path(file_name: &str) -> String {
+    format!("data/luts/{file_name}.png")
+}
+
+/// The LUT, that maps every color to itself.
+fn identity_lut() -> TextureResource {
+    let mut bytes = Vec::with_capacity(LUT_BYTES);
+    for green in 0..LUT_SIZE {
+        for blue in 0..LUT_SIZE {
+            for red in 0..LUT_SIZE {
+                for channel in [red, green, blue] {
+                    bytes.push((channel * 255 / (LUT_SIZE - 1)) as u8);
+                }
+                bytes.push(255);
+            }
+        }
+    }
+    TextureResource::from_bytes(
+        TextureKind::Rectangle {
+            width: LUT_WIDTH as u32,
+            height: LUT_SIZE as u32,
+        },
+        TexturePixelKind::RGBA8,
+        bytes,
+        ResourceKind::Embedded,
+    )
+    .unwrap()
+}
+
+/// A quad that covers the whole screen, its vertices are in normalized device coordinates.
+fn make_screen_quad() -> SurfaceData {
+    let vertices = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .into_iter()
+        .map(|(x, y)| {
+            StaticVertex::from_pos_uv_normal(
+                Vector3::new(x, y, 0.0),
+                Vector2::new((x + 1.0) * 0.5, (y + 1.0) * 0.5),
+                Vector3::z(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    SurfaceData::new(
+        VertexBuffer::new(vertices.len(), vertices).unwrap(),
+        TriangleBuffer::new(vec![
+            TriangleDefinition([0, 1, 2]),
+            TriangleDefinition([0, 2, 3]),
+        ]),
+    )
+}
+
+#[derive(Debug)]
+pub struct ColorGrading {
+    /// Index of the selected preset in [`PRESETS`].
+    preset: usize,
+    /// Id of the last requested preset. LUTs are loaded asynchronously, so a LUT of a preset, that
+    /// was selected before the last one, may arrive later and must be ignored.
+    request: u64,
+    /// LUT at the end of the transition. The LUT at its start is stored in the material only.
+    to: TextureResource,
+    /// Transition progress in `[0; 1]` range, `None` when there's no transition.
+    transition: Option<f32>,
+    /// Material of the full-screen quad, `None` until the shader is loaded.
+    material: Option<MaterialResource>,
+    scene: Handle<Scene>,
+    /// Size of the texture, that the scene is rendered into.
+    frame_size: (u32, u32),
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self {
+            preset: 0,
+            request: 0,
+            to: identity_lut(),
+            transition: None,
+            material: None,
+            scene: Default::default(),
+            frame_size: (0, 0),
+        }
+    }
+}
+
+impl ColorGrading {
+    pub fn preset_name(&self) -> &'static str {
+        PRESETS[self.preset].0
+    }
+
+    /// Redirects the rendering of the scene to a texture and adds a scene with the full-screen
+    /// quad, that draws the texture on the screen with color grading.
+    pub fn init(
+        &mut self,
+        shader: ShaderResource,
+        context: &mut PluginContext,
+        scene: Handle<Scene>,
+    ) {
+        let Some(scene_ref) = context.scenes.try_get_mut(scene) else {
+            return;
+        };
+        // The colors are graded by the quad only.
+        for node in scene_ref.graph.linear_iter_mut() {
+            if let Some(camera) = node.cast_mut::<Camera>() {
+                camera.set_color_grading_enabled(false);
+            }
+        }
+
+        let mut material = Material::from_shader(shader, None);
+        Log::verify(material.set_texture(&"lutFrom".into(), Some(self.to.clone())));
+        Log::verify(material.set_texture(&"lutTo".into(), Some(self.to.clone())));
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+
+        let mut post_scene = Scene::new();
+        let graph = &mut post_scene.graph;
+        let camera = CameraBuilder::new(BaseBuilder::new()).build(graph);
+        // The quad is placed in front of the camera, so it is never culled, its actual position
+        // on the screen is defined by the shader.
+        let quad = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_cast_shadows(false)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, 1.0))
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            make_screen_quad(),
+        ))
+        .with_material(material.clone())
+        .build()])
+        .with_render_path(RenderPath::Forward)
+        .build(graph);
+        graph.link_nodes(quad, camera);
+        // Scenes are rendered in the order they were added, so the quad is drawn after the scene is
+        // rendered into the texture.
+        context.scenes.add(post_scene);
+
+        self.material = Some(material);
+        self.scene = scene;
+        // Forces creation of the texture on the next update.
+        self.frame_size = (0, 0);
+    }
+
+    /// Returns the id of a new request of a preset, that must be passed to [`Self::select`] once
+    /// the LUT of the preset is loaded.
+    pub fn next_request(&mut self) -> u64 {
+        self.request += 1;
+        self.request
+    }
+
+    /// Starts a transition to the given preset, unless a preset was requested after this one. The
+    /// "None" preset has no texture, the scene fades to the identity LUT.
+    pub fn select(&mut self, request: u64, preset: usize, texture: Option<&TextureResource>) {
+        if request != self.request {
+            return;
+        }
+
+        let to = match texture {
+            Some(texture) => {
+                let data = texture.data_ref();
+                let is_valid = data.pixel_kind() == TexturePixelKind::RGBA8
+                    && data.kind()
+                        == (TextureKind::Rectangle {
+                            width: LUT_WIDTH as u32,
+                            height: LUT_SIZE as u32,
+                        });
+                if !is_valid {
+                    Log::err(format!(
+                        "Color grading LUT {} must be a {LUT_WIDTH}x{LUT_SIZE} RGBA8 image.",
+                        PRESETS[preset].0
+                    ));
+                    return;
+                }
+                texture.clone()
+            }
+            None => identity_lut(),
+        };
+
+        self.preset = preset;
+        // The material can blend two LUTs only, so an unfinished transition jumps to its end and
+        // the new one starts from there.
+        let from = std::mem::replace(&mut self.to, to);
+        if let Some(material) = self.material.as_ref() {
+            let mut material = material.data_ref();
+            Log::verify(material.set_texture(&"lutFrom".into(), Some(from)));
+            Log::verify(material.set_texture(&"lutTo".into(), Some(self.to.clone())));
+            Log::verify(material.set_property(&"mixFactor".into(), 0.0f32.into()));
+        }
+        self.transition = Some(0.0);
+    }
+
+    pub fn update(&mut self, context: &mut PluginContext) {
+        let Some(material) = self.material.as_ref() else {
+            return;
+        };
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let frame_size = graphics_context.renderer.get_frame_size();
+            if frame_size != self.frame_size {
+                self.frame_size = frame_size;
+                let render_target =
+                    TextureResource::new_render_target(frame_size.0.max(1), frame_size.1.max(1));
+                if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+                    scene.rendering_options.render_target = Some(render_target.clone());
+                }
+                Log::verify(
+                    material
+                        .data_ref()
+                        .set_texture(&"sceneTexture".into(), Some(render_target)),
+                );
+            }
+        }
+
+        let Some(transition) = self.transition.as_mut() else {
+            return;
+        };
+        *transition = (*transition + context.dt / TRANSITION_DURATION).min(1.0);
+        let t = *transition;
+
+        let mut material = material.data_ref();
+        if t >= 1.0 {
+            // The start of the next transition is the end of this one.
+            Log::verify(material.set_texture(&"lutFrom".into(), Some(self.to.clone())));
+            Log::verify(material.set_property(&"mixFactor".into(), 0.0f32.into()));
+            self.transition = None;
+        } else {
+            Log::verify(material.set_property(&"mixFactor".into(), t.into()));
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! Font rendering quality comparison - the same text at a few sizes in three columns: a bitmap
+//! font, which atlas is sampled with nearest filtering, the same font with bilinear filtering and a
+//! signed distance field (SDF) font. The UI renderer draws text with its own fixed shader, so the
+//! SDF column is rendered by a scene with the text meshes of the `sdf_text` demo into a texture,
+//! which is shown by an image. The SDF column is available with `sdf` feature only.
+use crate::Game;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix3, Vector2},
+        io,
+        log::Log,
+        pool::Handle,
+    },
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    plugin::PluginContext,
+    resource::{
+        font::{Font, FontResource},
+        texture::{Texture, TextureMagnificationFilter, TextureMinificationFilter},
+    },
+};
+#[cfg(feature = "sdf")]
+use fyrox::{
+    core::{algebra::Vector3, color::Color},
+    graph::SceneGraph,
+    gui::image::{ImageBuilder, ImageMessage},
+    material::shader::Shader,
+    resource::texture::TextureResource,
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, OrthographicProjection, Projection},
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+#[cfg(feature = "sdf")]
+use sdf_text::{sdf_font::SdfFont, sdf_text::SdfTextBuilder};
+
+const FONT_PATH: &str = "data/fonts/DejaVuSans.ttf";
+/// Size of the pages of the glyph atlases of the bitmap fonts, in pixels.
+const FONT_PAGE_SIZE: usize = 1024;
+const SAMPLE_TEXT: &str = "The quick brown fox...";
+/// Font sizes of the rows of every column, in pixels.
+const FONT_SIZES: [f32; 4] = [8.0, 12.0, 24.0, 72.0];
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 4.0;
+
+#[cfg(feature = "sdf")]
+const SDF_FONT_DESCRIPTION_PATH: &str = "data/fonts/dejavu_sans_sdf.json";
+#[cfg(feature = "sdf")]
+const SDF_FONT_ATLAS_PATH: &str = "data/fonts/dejavu_sans_sdf.png";
+#[cfg(feature = "sdf")]
+const SDF_SHADER_PATH: &str = "data/sdf_text.shader";
+/// Distance from the sides of the SDF texture to the text, in pixels.
+#[cfg(feature = "sdf")]
+const SDF_PADDING: f32 = 2.0;
+
+fn make_header(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+/// Creates a column with a text of every size of [`FONT_SIZES`], the texts are added to the given
+/// list.
+fn make_bitmap_column(
+    ctx: &mut BuildContext,
+    title: &str,
+    texts: &mut Vec<Handle<UiNode>>,
+) -> Handle<UiNode> {
+    let mut children = vec![make_header(ctx, title)];
+    for font_size in FONT_SIZES {
+        let text = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            .with_text(SAMPLE_TEXT)
+            .with_font_size(font_size)
+            .build(ctx);
+        texts.push(text);
+        children.push(text);
+    }
+    StackPanelBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(4.0))
+            .with_vertical_alignment(VerticalAlignment::Top)
+            .with_children(children),
+    )
+    .build(ctx)
+}
+
+/// Creates a separate font resource, so the filtering of its atlas does not affect other texts,
+/// and assigns it to the texts.
+fn make_font(data: &[u8], ui: &UserInterface, texts: &[Handle<UiNode>]) -> Option<FontResource> {
+    match Font::from_memory(data.to_vec(), FONT_PAGE_SIZE) {
+        Ok(font) => {
+            let font = FontResource::new_ok(ResourceKind::Embedded, font);
+            for text in texts {
+                ui.send_message(TextMessage::font(
+                    *text,
+                    MessageDirection::ToWidget,
+                    font.clone(),
+                ));
+            }
+            Some(font)
+        }
+        Err(err) => {
+            Log::err(format!(
+                "Unable to create font from {FONT_PATH}. Reason: {err:?}"
+            ));
+            None
+        }
+    }
+}
+
+fn set_atlas_filtering(
+    font: &FontResource,
+    magnification: TextureMagnificationFilter,
+    minification: TextureMinificationFilter,
+) {
+    if !font.is_ok() {
+        return;
+    }
+    let font = font.data_ref();
+    for page in font.atlases.values().flat_map(|atlas| atlas.pages.iter()) {
+        let Some(texture) = page
+            .texture
+            .as_ref()
+            .and_then(|texture| texture.try_cast::<Texture>())
+        else {
+            continue;
+        };
+        let mut texture = texture.data_ref();
+        texture.set_magnification_filter(magnification);
+        texture.set_minification_filter(minification);
+    }
+}
+
+#[derive(Debug)]
+pub struct FontQualityWindow {
+    window: Handle<UiNode>,
+    zoom_slider: Handle<UiNode>,
+    /// Columns, that are scaled by the zoom.
+    columns: Vec<Handle<UiNode>>,
+    nearest_texts: Vec<Handle<UiNode>>,
+    bilinear_texts: Vec<Handle<UiNode>>,
+    /// Two copies of the same font, so their atlases could be sampled differently.
+    nearest_font: Option<FontResource>,
+    bilinear_font: Option<FontResource>,
+    zoom: f32,
+    #[cfg(feature = "sdf")]
+    sdf: SdfColumn,
+}
+
+impl Default for FontQualityWindow {
+    fn default() -> Self {
+        Self {
+            window: Default::default(),
+            zoom_slider: Default::default(),
+            columns: Default::default(),
+            nearest_texts: Default::default(),
+            bilinear_texts: Default::default(),
+            nearest_font: None,
+            bilinear_font: None,
+            zoom: 1.0,
+            #[cfg(feature = "sdf")]
+            sdf: Default::default(),
+        }
+    }
+}
+
+impl FontQualityWindow {
+    pub fn new(context: &mut PluginContext) -> Self {
+        let mut window = Self::default();
+
+        context.task_pool.spawn_plugin_task(
+            io::load_file(FONT_PATH),
+            |result, game: &mut Game, ctx| match result {
+                Ok(data) => game
+                    .font_quality
+                    .on_font_loaded(&data, ctx.user_interfaces.first()),
+                Err(err) => Log::err(format!("Unable to load font {FONT_PATH}. Reason: {err:?}")),
+            },
+        );
+
+        #[cfg(feature = "sdf")]
+        {
+            window.sdf = SdfColumn::new(context);
+        }
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        let nearest = make_bitmap_column(ctx, "Bitmap, Nearest", &mut window.nearest_texts);
+        let bilinear = make_bitmap_column(ctx, "Bitmap, Bilinear", &mut window.bilinear_texts);
+        #[cfg(feature = "sdf")]
+        let sdf = window.sdf.build(ctx);
+        #[cfg(not(feature = "sdf"))]
+        let sdf = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(4.0))
+                .with_child(make_header(ctx, "SDF"))
+                .with_child(make_header(
+                    ctx,
+                    "Run with `--features sdf` to compare\nwith the font of `sdf_text` demo.",
+                )),
+        )
+        .build(ctx);
+        window.columns = vec![nearest, bilinear, sdf];
+
+        window.zoom_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(MIN_ZOOM)
+        .with_max(MAX_ZOOM)
+        .with_step(0.1)
+        .with_value(window.zoom)
+        .with_value_precision(1)
+        .show_value(true)
+        .build(ctx);
+
+        window.window =
+            WindowBuilder::new(WidgetBuilder::new().with_width(900.0).with_height(420.0))
+                .with_title(WindowTitle::text("Font Rendering Quality"))
+                .can_close(false)
+                .open(false)
+                .with_content(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_margin(Thickness::uniform(2.0))
+                                        .with_vertical_alignment(VerticalAlignment::Center),
+                                )
+                                .with_text("Zoom")
+                                .build(ctx),
+                            )
+                            .with_child(window.zoom_slider)
+                            .with_child(
+                                ScrollViewerBuilder::new(
+                                    WidgetBuilder::new().on_row(1).on_column(0),
+                                )
+                                .with_horizontal_scroll_allowed(true)
+                                .with_content(
+                                    StackPanelBuilder::new(
+                                        WidgetBuilder::new().with_children(window.columns.clone()),
+                                    )
+                                    .with_orientation(Orientation::Horizontal)
+                                    .build(ctx),
+                                )
+                                .build(ctx),
+                            ),
+                    )
+                    .add_column(Column::strict(60.0))
+                    .add_column(Column::stretch())
+                    .add_row(Row::strict(26.0))
+                    .add_row(Row::stretch())
+                    .build(ctx),
+                )
+                .build(ctx);
+
+        window
+    }
+
+    fn on_font_loaded(&mut self, data: &[u8], ui: &UserInterface) {
+        self.nearest_font = make_font(data, ui, &self.nearest_texts);
+        self.bilinear_font = make_font(data, ui, &self.bilinear_texts);
+    }
+
+    pub fn set_visible(&self, context: &mut PluginContext, visible: bool) {
+        #[cfg(feature = "sdf")]
+        self.sdf.set_enabled(context, visible);
+
+        let ui = context.user_interfaces.first();
+        if visible {
+            ui.send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                true,
+                false,
+            ));
+        } else {
+            ui.send_message(WindowMessage::close(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    /// Glyphs are rasterized into the atlas on demand and a page of the atlas gets a new texture,
+    /// when new glyphs are added to it, so the filtering is applied every frame.
+    pub fn update(&self) {
+        if let Some(font) = self.nearest_font.as_ref() {
+            set_atlas_filtering(
+                font,
+                TextureMagnificationFilter::Nearest,
+                TextureMinificationFilter::Nearest,
+            );
+        }
+        if let Some(font) = self.bilinear_font.as_ref() {
+            set_atlas_filtering(
+                font,
+                TextureMagnificationFilter::Linear,
+                TextureMinificationFilter::Linear,
+            );
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, context: &mut PluginContext) {
+        let Some(ScrollBarMessage::Value(value)) = message.data() else {
+            return;
+        };
+        if message.destination() != self.zoom_slider
+            || message.direction() != MessageDirection::FromWidget
+        {
+            return;
+        }
+
+        self.zoom = *value;
+        let ui = context.user_interfaces.first();
+        for column in self.columns.iter() {
+            ui.send_message(WidgetMessage::layout_transform(
+                *column,
+                MessageDirection::ToWidget,
+                Matrix3::new_nonuniform_scaling(&Vector2::repeat(self.zoom)),
+            ));
+        }
+        #[cfg(feature = "sdf")]
+        self.sdf.set_zoom(self.zoom, context);
+    }
+}
+
+/// Column with the distance field text. The text is rendered into a texture, which has the size
+/// of the zoomed image, so the edges are reconstructed at the final resolution instead of being
+/// stretched.
+#[cfg(feature = "sdf")]
+#[derive(Default, Debug)]
+struct SdfColumn {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    image: Handle<UiNode>,
+    /// Size of the image without zoom, in pixels. Zero until the font is loaded.
+    size: Vector2<f32>,
+}
+
+#[cfg(feature = "sdf")]
+impl SdfColumn {
+    fn new(context: &mut PluginContext) -> Self {
+        let mut scene = Scene::new();
+        scene.rendering_options.clear_color = Some(Color::opaque(30, 30, 30));
+        // The scene is rendered only while the window is open.
+        scene.set_enabled(false);
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -10.0))
+                    .build(),
+            ),
+        )
+        .with_projection(Projection::Orthographic(Default::default()))
+        .build(&mut scene.graph);
+        let scene = context.scenes.add(scene);
+
+        let texture = context
+            .resource_manager
+            .request::<Texture>(SDF_FONT_ATLAS_PATH);
+        context.task_pool.spawn_plugin_task(
+            io::load_file(SDF_FONT_DESCRIPTION_PATH),
+            move |result, game: &mut Game, ctx| {
+                let font = result
+                    .map_err(|err| format!("{err:?}"))
+                    .and_then(|bytes| SdfFont::from_bytes(&bytes, texture));
+                match font {
+                    Ok(font) => {
+                        let zoom = game.font_quality.zoom;
+                        game.font_quality.sdf.on_font_loaded(font, zoom, ctx);
+                    }
+                    Err(err) => Log::err(format!(
+                        "Unable to load font {SDF_FONT_DESCRIPTION_PATH}. Reason: {err}"
+                    )),
+                }
+            },
+        );
+
+        Self {
+            scene,
+            camera,
+            image: Handle::NONE,
+            size: Vector2::default(),
+        }
+    }
+
+    fn build(&mut self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        self.image = ImageBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            // Render targets are stored upside down.
+            .with_flip(true)
+            .build(ctx);
+        StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(4.0))
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_child(make_header(ctx, "SDF"))
+                .with_child(self.image),
+        )
+        .build(ctx)
+    }
+
+    /// Builds a line of the text for every size of [`FONT_SIZES`]. One world unit is one pixel
+    /// of the image without zoom, the origin is at the center of the image.
+    fn on_font_loaded(&mut self, font: SdfFont, zoom: f32, context: &mut PluginContext) {
+        let shader = context.resource_manager.request::<Shader>(SDF_SHADER_PATH);
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        let text_width = SAMPLE_TEXT
+            .chars()
+            .filter_map(|character| font.glyph(character))
+            .map(|glyph| glyph.advance)
+            .sum::<f32>();
+        let line_heights = FONT_SIZES.map(|font_size| font_size * font.metrics.line_height);
+        self.size = Vector2::new(
+            text_width * FONT_SIZES[FONT_SIZES.len() - 1] + 2.0 * SDF_PADDING,
+            line_heights.iter().sum::<f32>() + 2.0 * SDF_PADDING,
+        );
+
+        // X axis points to the left.
+        let mut top = self.size.y * 0.5 - SDF_PADDING;
+        for (font_size, line_height) in FONT_SIZES.into_iter().zip(line_heights) {
+            SdfTextBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(
+                            self.size.x * 0.5 - SDF_PADDING,
+                            top,
+                            0.0,
+                        ))
+                        .build(),
+                ),
+            )
+            .with_text(SAMPLE_TEXT)
+            .with_font_size(font_size)
+            .with_color(Color::WHITE)
+            .build(&font, shader.clone(), &mut scene.graph);
+            top -= line_height;
+        }
+
+        if let Some(camera) = scene.graph.try_get_mut_of_type::<Camera>(self.camera) {
+            camera.set_projection(Projection::Orthographic(OrthographicProjection {
+                z_near: 0.0,
+                z_far: 100.0,
+                vertical_size: self.size.y * 0.5,
+            }));
+        }
+
+        let ui = context.user_interfaces.first();
+        ui.send_message(WidgetMessage::width(
+            self.image,
+            MessageDirection::ToWidget,
+            self.size.x,
+        ));
+        ui.send_message(WidgetMessage::height(
+            self.image,
+            MessageDirection::ToWidget,
+            self.size.y,
+        ));
+        self.set_zoom(zoom, context);
+    }
+
+    fn set_enabled(&self, context: &mut PluginContext, enabled: bool) {
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            scene.set_enabled(enabled);
+        }
+    }
+
+    /// The image is scaled by the layout transform of the column, the texture is resized to match
+    /// it pixel to pixel.
+    fn set_zoom(&self, zoom: f32, context: &mut PluginContext) {
+        if self.size == Vector2::default() {
+            return;
+        }
+        let render_target = TextureResource::new_render_target(
+            ((self.size.x * zoom) as u32).max(1),
+            ((self.size.y * zoom) as u32).max(1),
+        );
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            scene.rendering_options.render_target = Some(render_target.clone());
+        }
+        context
+            .user_interfaces
+            .first()
+            .send_message(ImageMessage::texture(
+                self.image,
+                MessageDirection::ToWidget,
+                Some(render_target.into()),
+            ));
+    }
+}
@@ -1,4 +1,8 @@
 //! Game project.
+use atlas::{Atlas, ATLAS_TEXTURE_PATH};
+use color_grading::ColorGrading;
+use common_scripts::Throttle;
+use font_quality::FontQualityWindow;
 use fyrox::graph::SceneGraph;
 use fyrox::{
     asset::manager::ResourceManager,
@@ -18,11 +22,11 @@ use fyrox::{
         brush::Brush,
         button::{ButtonBuilder, ButtonMessage},
         canvas::CanvasBuilder,
-        check_box::CheckBoxBuilder,
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
         curve::CurveEditorBuilder,
         decorator::DecoratorBuilder,
         dock::{DockingManagerBuilder, TileBuilder, TileContent},
-        dropdown_list::DropdownListBuilder,
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
         expander::ExpanderBuilder,
         formatted_text::WrapMode,
         grid::{Column, GridBuilder, Row},
@@ -51,18 +55,47 @@ use fyrox::{
         widget::WidgetMessage,
         window::{WindowBuilder, WindowTitle},
         wrap_panel::WrapPanelBuilder,
-        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, VerticalAlignment,
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
     },
+    material::shader::Shader,
     plugin::{Plugin, PluginContext},
     rand::{thread_rng, Rng},
-    renderer::{CsmSettings, ShadowMapPrecision},
+    renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
     resource::texture::Texture,
     scene::{node::Node, Scene},
 };
-use std::path::Path;
+use render_graph::RenderGraphWindow;
+use render_path::RenderPathSelector;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use theme::{Theme, UiPrefs};
+use ui_test::UiLayoutTest;
 
+mod atlas;
+mod color_grading;
 pub mod custom;
+mod font_quality;
+mod render_graph;
+mod render_path;
+mod shadow_cascades;
+mod theme;
+mod ui_test;
+
+const SCENE_NAME: &str = "scene";
+
+/// Directory with the textures, that are transcoded for the target platform by `build.rs`.
+const ASSET_PATH: &str = env!("ASSET_PATH");
+
+/// Returns the path of the transcoded copy of a texture from `data`.
+fn platform_texture_path(path: &str) -> PathBuf {
+    common_scripts::transcoded_texture_path(
+        path,
+        ASSET_PATH,
+        env!("ASSET_EXTENSION"),
+        env!("TRANSCODED_ASSETS"),
+    )
+}
 
 #[derive(Visit, Default, Reflect, Debug)]
 pub struct Game {
@@ -71,6 +104,100 @@ pub struct Game {
     #[reflect(hidden)]
     interface: Option<Interface>,
     paladin: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cas: ContrastAdaptiveSharpening,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    color_grading: ColorGrading,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    render_path: RenderPathSelector,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ui_test: Option<UiLayoutTest>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    font_quality: FontQualityWindow,
+}
+
+impl Game {
+    /// Creates the game, that checks the layout of the UI after a few frames, prints the results
+    /// and exits. The exit code is 1 if any of the checks failed. If `bless` is set, the measured
+    /// colors are saved as the references instead.
+    pub fn with_ui_test(bless: bool) -> Self {
+        Self {
+            ui_test: Some(UiLayoutTest::new(bless)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Weight of a new sample in the moving average of frame time.
+const FRAME_TIME_SMOOTHING: f32 = 0.05;
+
+/// Settings of Contrast Adaptive Sharpening (CAS) - a lightweight sharpening pass, that is usually
+/// applied after upscaling. The renderer does not support CAS yet (neither in `QualitySettings`,
+/// nor as a custom post-process effect), so the settings are only stored here and are ready to be
+/// passed to the renderer once it gets the support. Frame time is measured separately for both
+/// states, so the cost of the effect will be visible right away.
+#[derive(Debug)]
+struct ContrastAdaptiveSharpening {
+    enabled: bool,
+    sharpness: f32,
+    /// Average frame time (in seconds) with CAS disabled and enabled respectively.
+    frame_time: [Option<f32>; 2],
+}
+
+impl Default for ContrastAdaptiveSharpening {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sharpness: 0.5,
+            frame_time: [None; 2],
+        }
+    }
+}
+
+impl ContrastAdaptiveSharpening {
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if enabled {
+            Log::warn(
+                "Contrast Adaptive Sharpening is not supported by the renderer yet, \
+                the settings are not applied.",
+            );
+        }
+    }
+
+    fn set_sharpness(&mut self, sharpness: f32) {
+        self.sharpness = sharpness.clamp(0.0, 1.0);
+    }
+
+    fn add_frame_time(&mut self, frame_time: f32) {
+        let average = &mut self.frame_time[self.enabled as usize];
+        *average = Some(match *average {
+            Some(average) => average + (frame_time - average) * FRAME_TIME_SMOOTHING,
+            None => frame_time,
+        });
+    }
+
+    fn frame_time_text(&self) -> String {
+        let format = |time: Option<f32>| {
+            time.map_or_else(
+                || "N/A".to_string(),
+                |time| format!("{:.3} ms", time * 1000.0),
+            )
+        };
+        format!(
+            "Frame Time: {} without CAS, {} with CAS",
+            format(self.frame_time[0]),
+            format(self.frame_time[1])
+        )
+    }
 }
 
 impl Plugin for Game {
@@ -81,28 +208,108 @@ impl Plugin for Game {
     }
 
     fn update(&mut self, context: &mut PluginContext) {
-        if let Some(interface) = self.interface.as_ref() {
+        let debug_text = self
+            .interface
+            .as_ref()
+            .map(|interface| interface.debug_text)
+            .unwrap_or_default();
+        if self.throttle.update(context, debug_text) {
+            return;
+        }
+
+        self.color_grading.update(context);
+        self.font_quality.update();
+
+        if let (Some(ui_test), GraphicsContext::Initialized(ctx)) =
+            (self.ui_test.as_mut(), &mut *context.graphics_context)
+        {
+            let ui = context.user_interfaces.first_mut();
+            if let Some(passed) = ui_test.update(&mut ctx.renderer, ui) {
+                // The event loop can't report an exit code, so the process is terminated right
+                // away to let CI know about the regression.
+                if !passed {
+                    std::process::exit(1);
+                }
+                if let Some(window_target) = context.window_target {
+                    window_target.exit();
+                }
+                self.ui_test = None;
+            }
+        }
+
+        if let Some(interface) = self.interface.as_mut() {
             if let GraphicsContext::Initialized(ctx) = context.graphics_context {
+                let statistics = ctx.renderer.get_statistics();
+                interface.render_graph.update(
+                    context.user_interfaces.first(),
+                    &statistics,
+                    &ctx.renderer.get_quality_settings(),
+                );
+                self.cas.add_frame_time(statistics.pure_frame_time);
+                let window_size = ctx.window.inner_size();
+                let cascades_text = context
+                    .scenes
+                    .try_get(self.scene)
+                    .map(|scene| {
+                        shadow_cascades::texel_density_text(
+                            scene,
+                            &ctx.renderer.get_quality_settings(),
+                            window_size.width as f32 / window_size.height.max(1) as f32,
+                        )
+                    })
+                    .unwrap_or_default();
                 context
                     .user_interfaces
                     .first()
                     .send_message(TextMessage::text(
                         interface.debug_text,
                         MessageDirection::ToWidget,
-                        format!("FPS: {}", ctx.renderer.get_statistics().frames_per_second),
+                        format!(
+                            "FPS: {}\n{}\nColor Grading: {}\n{}\n{}\n{}",
+                            statistics.frames_per_second,
+                            self.cas.frame_time_text(),
+                            self.color_grading.preset_name(),
+                            self.render_path.stats_text(statistics.geometry.draw_calls),
+                            cascades_text,
+                            tracked_alloc::peak_ram_text()
+                        ),
                     ))
             }
         }
     }
 
     fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
-        self.interface = Some(Interface::new(&mut context));
+        // Saved settings must be applied before the interface is created, it shows them.
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+        // The UI test checks the colors of the default style, so it ignores the saved theme.
+        let prefs = if self.ui_test.is_some() {
+            UiPrefs::default()
+        } else {
+            UiPrefs::load()
+        };
+        let theme_index = prefs.theme_index();
+        let interface = Interface::new(&mut context, &self.cas, theme_index.unwrap_or_default());
+        // The default style is used until a theme is selected.
+        if let Some(theme) = theme_index.and_then(|index| Theme::all().get(index).cloned()) {
+            interface.apply_theme(context.user_interfaces.first(), &theme);
+        }
+        self.interface = Some(interface);
+        self.font_quality = FontQualityWindow::new(&mut context);
     }
 
     fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        self.font_quality.handle_ui_message(message, context);
+
         if let Some(interface) = self.interface.as_mut() {
             if let Some(ScrollBarMessage::Value(value)) = message.data() {
-                if message.direction() == MessageDirection::FromWidget {
+                if message.direction() == MessageDirection::FromWidget
+                    && message.destination() == interface.cas_sharpness
+                {
+                    self.cas.set_sharpness(*value);
+                } else if message.direction() == MessageDirection::FromWidget {
                     if let Some(paladin) = context
                         .scenes
                         .try_get_mut(self.scene)
@@ -147,6 +354,24 @@ impl Plugin for Game {
                             MessageDirection::ToWidget,
                             180.0f32,
                         ));
+                } else if message.destination() == interface.reset_quality {
+                    if let GraphicsContext::Initialized(graphics_context) = context.graphics_context
+                    {
+                        prefs::SceneQualityPrefs::clear(SCENE_NAME);
+                        let settings = QualitySettings::default();
+                        Log::verify(graphics_context.renderer.set_quality_settings(&settings));
+
+                        let ui = context.user_interfaces.first_mut();
+                        let inspector_context =
+                            make_quality_inspector_context(&settings, &mut ui.build_ctx());
+                        ui.send_message(InspectorMessage::context(
+                            interface.quality_inspector,
+                            MessageDirection::ToWidget,
+                            inspector_context,
+                        ));
+                    }
+                } else if message.destination() == interface.reset_peak_ram {
+                    tracked_alloc::reset_peak();
                 } else if message.destination() == interface.press_me_button {
                     interface.message_box = MessageBoxBuilder::new(
                         WindowBuilder::new(
@@ -181,6 +406,82 @@ impl Plugin for Game {
                         Log::verify(graphics_context.renderer.set_quality_settings(&settings));
                     }
                 }
+            } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+                if message.destination() == interface.cas_check_box
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    self.cas.set_enabled(*value);
+                } else if message.destination() == interface.render_graph_check_box
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    interface
+                        .render_graph
+                        .set_visible(context.user_interfaces.first(), *value);
+                } else if message.destination() == interface.font_quality_check_box
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    self.font_quality.set_visible(context, *value);
+                }
+            } else if let Some(DropdownListMessage::SelectionChanged(Some(preset))) = message.data()
+            {
+                if message.destination() == interface.color_grading_list
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    let preset = *preset;
+                    let request = self.color_grading.next_request();
+                    match color_grading::PRESETS[preset].1 {
+                        Some(file_name) => {
+                            context.task_pool.spawn_plugin_task(
+                                context
+                                    .resource_manager
+                                    .request::<Texture>(color_grading::lut_path(file_name)),
+                                move |result, game: &mut Game, _ctx| match result {
+                                    Ok(texture) => {
+                                        game.color_grading.select(request, preset, Some(&texture))
+                                    }
+                                    Err(err) => Log::err(format!(
+                                        "Unable to load color grading LUT {file_name}. Reason: {err:?}"
+                                    )),
+                                },
+                            );
+                        }
+                        None => self.color_grading.select(request, preset, None),
+                    }
+                } else if message.destination() == interface.render_path_list
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    self.render_path
+                        .select(*preset, context.scenes.try_get_mut(self.scene));
+                } else if message.destination() == interface.theme_list
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    if let Some(theme) = Theme::all().get(*preset) {
+                        interface.apply_theme(context.user_interfaces.first(), theme);
+                        UiPrefs {
+                            theme: theme.name.to_string(),
+                        }
+                        .save();
+                    }
+                } else if message.destination() == interface.shadow_resolution_list
+                    && message.direction() == MessageDirection::FromWidget
+                {
+                    if let GraphicsContext::Initialized(graphics_context) = context.graphics_context
+                    {
+                        let mut settings = graphics_context.renderer.get_quality_settings();
+                        shadow_cascades::set_resolution(&mut settings, *preset);
+                        Log::verify(graphics_context.renderer.set_quality_settings(&settings));
+
+                        // The inspector shows the same setting, keep it in sync.
+                        let ui = context.user_interfaces.first_mut();
+                        let inspector_context =
+                            make_quality_inspector_context(&settings, &mut ui.build_ctx());
+                        ui.send_message(InspectorMessage::context(
+                            interface.quality_inspector,
+                            MessageDirection::ToWidget,
+                            inspector_context,
+                        ));
+                    }
+                }
             } else if let Some(MessageBoxMessage::Close(_)) = message.data() {
                 if message.destination() == interface.message_box {
                     context
@@ -204,7 +505,20 @@ impl Plugin for Game {
     ) {
         self.scene = scene;
 
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>(color_grading::SHADER_PATH),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.color_grading.init(shader, ctx, game.scene),
+                Err(err) => Log::err(format!(
+                    "Unable to load color grading shader. Reason: {err:?}"
+                )),
+            },
+        );
+
         let scene_ref = &mut context.scenes[scene];
+        self.render_path.apply(scene_ref);
         if let Some((handle, paladin)) = scene_ref.graph.find_by_name_from_root("paladin.fbx") {
             if let Some(interface) = self.interface.as_ref() {
                 context
@@ -234,6 +548,12 @@ impl Plugin for Game {
             self.paladin = handle;
         }
     }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -243,10 +563,45 @@ struct Interface {
     scale: Handle<UiNode>,
     reset: Handle<UiNode>,
     quality_inspector: Handle<UiNode>,
+    reset_quality: Handle<UiNode>,
+    reset_peak_ram: Handle<UiNode>,
+    cas_check_box: Handle<UiNode>,
+    cas_sharpness: Handle<UiNode>,
+    color_grading_list: Handle<UiNode>,
+    render_path_list: Handle<UiNode>,
+    shadow_resolution_list: Handle<UiNode>,
+    theme_list: Handle<UiNode>,
+    render_graph_check_box: Handle<UiNode>,
+    render_graph: RenderGraphWindow,
+    font_quality_check_box: Handle<UiNode>,
     press_me_button: Handle<UiNode>,
     message_box: Handle<UiNode>,
+    /// Windows of the interface, the theme is applied to all of their descendants.
+    windows: Vec<Handle<UiNode>>,
+}
+
+fn make_quality_inspector_context(
+    settings: &QualitySettings,
+    ctx: &mut BuildContext,
+) -> InspectorContext {
+    let container = PropertyEditorDefinitionContainer::with_default_editors();
+    container.register_inheritable_inspectable::<CsmSettings>();
+    container.register_inheritable_enum::<ShadowMapPrecision, _>();
+
+    InspectorContext::from_object(
+        settings,
+        ctx,
+        Arc::new(container),
+        None,
+        u64::MAX,
+        0,
+        true,
+        Default::default(),
+    )
 }
 
+const POTIONS_TEXTURE_PATH: &str = "data/Potions.png";
+
 fn make_potions_images(
     ctx: &mut BuildContext,
     resource_manager: &ResourceManager,
@@ -255,8 +610,19 @@ fn make_potions_images(
 ) -> Vec<Handle<UiNode>> {
     let mut potions = Vec::new();
 
+    // The potions are taken from the atlas, if it was built with `atlas_builder`.
+    let atlas = Atlas::load();
+
     for y in 0..h {
         for x in 0..w {
+            let uv_rect = Rect::new(x as f32 / 6.0, y as f32 / 3.0, 1.0 / 6.0, 1.0 / 3.0);
+            let (texture_path, uv_rect) = match atlas
+                .as_ref()
+                .and_then(|atlas| atlas.map_uv_rect(POTIONS_TEXTURE_PATH, uv_rect))
+            {
+                Some(atlas_uv_rect) => (PathBuf::from(ATLAS_TEXTURE_PATH), atlas_uv_rect),
+                None => (platform_texture_path(POTIONS_TEXTURE_PATH), uv_rect),
+            };
             potions.push(
                 ImageBuilder::new(
                     WidgetBuilder::new()
@@ -268,17 +634,8 @@ fn make_potions_images(
                             thread_rng().gen_range(0.0..200.0),
                         )),
                 )
-                .with_uv_rect(Rect::new(
-                    x as f32 / 6.0,
-                    y as f32 / 3.0,
-                    1.0 / 6.0,
-                    1.0 / 3.0,
-                ))
-                .with_texture(
-                    resource_manager
-                        .request::<Texture>("data/Potions.png")
-                        .into(),
-                )
+                .with_uv_rect(uv_rect)
+                .with_texture(resource_manager.request::<Texture>(texture_path).into())
                 .build(ctx),
             );
         }
@@ -318,7 +675,9 @@ fn make_chests(ctx: &mut BuildContext, resource_manager: &ResourceManager) -> Ve
                                     ))
                                     .with_texture(
                                         resource_manager
-                                            .request::<Texture>("data/chests.png")
+                                            .request::<Texture>(platform_texture_path(
+                                                "data/chests.png",
+                                            ))
                                             .into(),
                                     )
                                     .build(ctx),
@@ -375,7 +734,7 @@ fn make_tree(
                         ))
                         .with_texture(
                             resource_manager
-                                .request::<Texture>("data/armours.png")
+                                .request::<Texture>(platform_texture_path("data/armours.png"))
                                 .into(),
                         )
                         .build(ctx),
@@ -421,7 +780,11 @@ fn make_tree_root(ctx: &mut BuildContext, resource_manager: &ResourceManager) ->
 }
 
 impl Interface {
-    fn new(plugin_ctx: &mut PluginContext) -> Self {
+    fn new(
+        plugin_ctx: &mut PluginContext,
+        cas: &ContrastAdaptiveSharpening,
+        selected_theme: usize,
+    ) -> Self {
         let ctx = plugin_ctx.graphics_context.as_initialized_ref();
         let window_width = ctx.renderer.get_frame_size().0 as f32;
 
@@ -538,13 +901,19 @@ impl Interface {
             .renderer
             .get_quality_settings();
 
-        let container = PropertyEditorDefinitionContainer::with_default_editors();
-        container.register_inheritable_inspectable::<CsmSettings>();
-        container.register_inheritable_enum::<ShadowMapPrecision, _>();
-
         // Create another window which will show some graphics options.
         let debug_text;
         let quality_inspector;
+        let reset_quality;
+        let reset_peak_ram;
+        let cas_check_box;
+        let cas_sharpness;
+        let color_grading_list;
+        let render_path_list;
+        let shadow_resolution_list;
+        let theme_list;
+        let render_graph_check_box;
+        let font_quality_check_box;
         let graphics = WindowBuilder::new(
             WidgetBuilder::new()
                 .with_desired_position(Vector2::new(window_width - 670.0, 0.0))
@@ -559,27 +928,305 @@ impl Interface {
                         debug_text
                     })
                     .with_child(
-                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(1)
+                                .with_child({
+                                    cas_check_box = CheckBoxBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(0)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_content(
+                                        TextBuilder::new(WidgetBuilder::new())
+                                            .with_text("Enable CAS")
+                                            .build(ctx),
+                                    )
+                                    .checked(Some(cas.enabled))
+                                    .build(ctx);
+                                    cas_check_box
+                                })
+                                .with_child({
+                                    cas_sharpness = ScrollBarBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(1)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center)
+                                            .with_tooltip(make_simple_tooltip(
+                                                ctx,
+                                                "Sharpness of Contrast Adaptive Sharpening",
+                                            )),
+                                    )
+                                    .with_min(0.0)
+                                    .with_max(1.0)
+                                    .with_step(0.05)
+                                    .with_value(cas.sharpness)
+                                    .show_value(true)
+                                    .build(ctx);
+                                    cas_sharpness
+                                }),
+                        )
+                        .add_column(Column::strict(100.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(30.0))
+                        .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(2)
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(0)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_text("Color Grading")
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    color_grading_list = DropdownListBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(1)
+                                            .with_height(22.0)
+                                            .with_margin(Thickness::uniform(2.0)),
+                                    )
+                                    .with_items(
+                                        color_grading::PRESETS
+                                            .iter()
+                                            .map(|(name, _)| {
+                                                TextBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .with_margin(Thickness::uniform(2.0)),
+                                                )
+                                                .with_text(*name)
+                                                .build(ctx)
+                                            })
+                                            .collect(),
+                                    )
+                                    .with_selected(0)
+                                    .build(ctx);
+                                    color_grading_list
+                                }),
+                        )
+                        .add_column(Column::strict(100.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(30.0))
+                        .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(3)
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(0)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_text("Render Path")
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    render_path_list = DropdownListBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(1)
+                                            .with_height(22.0)
+                                            .with_margin(Thickness::uniform(2.0)),
+                                    )
+                                    .with_items(
+                                        render_path::RENDER_PATHS
+                                            .iter()
+                                            .map(|(name, _)| {
+                                                TextBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .with_margin(Thickness::uniform(2.0)),
+                                                )
+                                                .with_text(*name)
+                                                .build(ctx)
+                                            })
+                                            .collect(),
+                                    )
+                                    .with_selected(0)
+                                    .build(ctx);
+                                    render_path_list
+                                }),
+                        )
+                        .add_column(Column::strict(100.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(30.0))
+                        .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(4)
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(0)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_text("Shadow Maps")
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    shadow_resolution_list = DropdownListBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(1)
+                                            .with_height(22.0)
+                                            .with_margin(Thickness::uniform(2.0)),
+                                    )
+                                    .with_items(
+                                        shadow_cascades::RESOLUTIONS
+                                            .iter()
+                                            .map(|size| {
+                                                TextBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .with_margin(Thickness::uniform(2.0)),
+                                                )
+                                                .with_text(format!("{size}x{size}"))
+                                                .build(ctx)
+                                            })
+                                            .collect(),
+                                    )
+                                    .with_selected(shadow_cascades::resolution_index(
+                                        &quality_settings,
+                                    ))
+                                    .build(ctx);
+                                    shadow_resolution_list
+                                }),
+                        )
+                        .add_column(Column::strict(100.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(30.0))
+                        .build(ctx),
+                    )
+                    .with_child(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(5)
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(0)
+                                            .with_margin(Thickness::uniform(2.0))
+                                            .with_vertical_alignment(VerticalAlignment::Center),
+                                    )
+                                    .with_text("Theme")
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    theme_list = DropdownListBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_column(1)
+                                            .with_height(22.0)
+                                            .with_margin(Thickness::uniform(2.0)),
+                                    )
+                                    .with_items(
+                                        Theme::all()
+                                            .iter()
+                                            .map(|theme| {
+                                                TextBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .with_margin(Thickness::uniform(2.0)),
+                                                )
+                                                .with_text(theme.name)
+                                                .build(ctx)
+                                            })
+                                            .collect(),
+                                    )
+                                    .with_selected(selected_theme)
+                                    .build(ctx);
+                                    theme_list
+                                }),
+                        )
+                        .add_column(Column::strict(100.0))
+                        .add_column(Column::stretch())
+                        .add_row(Row::strict(30.0))
+                        .build(ctx),
+                    )
+                    .with_child({
+                        render_graph_check_box = CheckBoxBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(6)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_content(
+                            TextBuilder::new(WidgetBuilder::new())
+                                .with_text("Show Render Graph")
+                                .build(ctx),
+                        )
+                        .checked(Some(false))
+                        .build(ctx);
+                        render_graph_check_box
+                    })
+                    .with_child(
+                        ScrollViewerBuilder::new(WidgetBuilder::new().on_row(7))
                             .with_content({
                                 quality_inspector = InspectorBuilder::new(WidgetBuilder::new())
-                                    .with_context(InspectorContext::from_object(
+                                    .with_context(make_quality_inspector_context(
                                         &quality_settings,
                                         ctx,
-                                        Arc::new(container),
-                                        None,
-                                        u64::MAX,
-                                        0,
-                                        true,
-                                        Default::default(),
                                     ))
                                     .build(ctx);
                                 quality_inspector
                             })
                             .build(ctx),
-                    ),
+                    )
+                    .with_child({
+                        reset_quality = ButtonBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(8)
+                                .with_height(26.0)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_text("Reset to Defaults")
+                        .build(ctx);
+                        reset_quality
+                    })
+                    .with_child({
+                        reset_peak_ram = ButtonBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(9)
+                                .with_height(26.0)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_text("Reset Peak RAM")
+                        .build(ctx);
+                        reset_peak_ram
+                    })
+                    .with_child({
+                        font_quality_check_box = CheckBoxBuilder::new(
+                            WidgetBuilder::new()
+                                .on_row(10)
+                                .with_margin(Thickness::uniform(2.0)),
+                        )
+                        .with_content(
+                            TextBuilder::new(WidgetBuilder::new())
+                                .with_text("Compare Font Rendering")
+                                .build(ctx),
+                        )
+                        .checked(Some(false))
+                        .build(ctx);
+                        font_quality_check_box
+                    }),
             )
             .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
             .add_row(Row::stretch())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
+            .add_row(Row::auto())
             .add_column(Column::stretch())
             .build(ctx),
         )
@@ -644,7 +1291,9 @@ impl Interface {
                                                             plugin_ctx
                                                                 .resource_manager
                                                                 .request::<Texture>(
-                                                                    "data/Potions.png",
+                                                                    platform_texture_path(
+                                                                        POTIONS_TEXTURE_PATH,
+                                                                    ),
                                                                 ).into(),
                                                         )
                                                         .with_uv_rect(Rect::new(
@@ -997,7 +1646,7 @@ impl Interface {
             .with_title(WindowTitle::text("Widget Gallery"))
             .build(ctx);
 
-        WindowBuilder::new(
+        let docking_manager = WindowBuilder::new(
             WidgetBuilder::new()
                 .with_width(500.0)
                 .with_height(650.0)
@@ -1038,14 +1687,52 @@ impl Interface {
         )
         .build(ctx);
 
+        let render_graph = RenderGraphWindow::new(ctx);
+
         Interface {
             debug_text,
             yaw,
             scale,
             reset,
             quality_inspector,
+            reset_quality,
+            reset_peak_ram,
+            cas_check_box,
+            cas_sharpness,
+            color_grading_list,
+            render_path_list,
+            shadow_resolution_list,
+            theme_list,
+            render_graph_check_box,
+            render_graph,
+            font_quality_check_box,
             press_me_button,
             message_box: Default::default(),
+            windows: vec![docking_manager, graphics, model_options, widget_gallery],
         }
     }
+
+    /// Applies the theme to every widget of the interface.
+    fn apply_theme(&self, ui: &UserInterface, theme: &Theme) {
+        let widgets = [
+            self.debug_text,
+            self.yaw,
+            self.scale,
+            self.reset,
+            self.quality_inspector,
+            self.reset_quality,
+            self.reset_peak_ram,
+            self.cas_check_box,
+            self.cas_sharpness,
+            self.color_grading_list,
+            self.render_path_list,
+            self.shadow_resolution_list,
+            self.theme_list,
+            self.render_graph_check_box,
+            self.font_quality_check_box,
+            self.press_me_button,
+            self.message_box,
+        ];
+        theme.apply(ui, &widgets, &self.windows);
+    }
 }
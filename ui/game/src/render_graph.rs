@@ -0,0 +1,284 @@
+//! Debug window with the passes of the renderer in the order of execution. The renderer does not
+//! expose its render graph nor GPU timings of the passes, so the passes are listed from the
+//! layout of the deferred renderer. Only the passes, that the renderer runs with its current
+//! quality settings, are shown. The passes are ranked by the amount of draw calls, where the frame
+//! statistics cover it.
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        brush::Brush,
+        expander::ExpanderBuilder,
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        tree::{TreeBuilder, TreeRootBuilder},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    renderer::{QualitySettings, Statistics},
+};
+
+/// Color of the pass with the most draw calls in the frame.
+const HIGHLIGHT_COLOR: Color = Color::opaque(255, 220, 0);
+
+struct Pass {
+    name: &'static str,
+    inputs: &'static [&'static str],
+    outputs: &'static [&'static str],
+    /// Whether the renderer runs the pass with the given settings.
+    enabled: fn(&QualitySettings) -> bool,
+    /// Amount of draw calls of the pass in the frame, `None` if the statistics do not cover the
+    /// pass.
+    draw_calls: fn(&Statistics) -> Option<usize>,
+}
+
+const PASSES: [Pass; 9] = [
+    Pass {
+        name: "Shadow Maps",
+        inputs: &["Scene Geometry"],
+        outputs: &[
+            "Cascaded Shadow Maps",
+            "Spot Shadow Maps",
+            "Point Shadow Cube Maps",
+        ],
+        enabled: |s| s.csm_settings.enabled || s.spot_shadows_enabled || s.point_shadows_enabled,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "G-Buffer",
+        inputs: &["Scene Geometry"],
+        outputs: &[
+            "Depth",
+            "Diffuse",
+            "Normal",
+            "Material",
+            "Ambient",
+            "Decal Mask",
+        ],
+        enabled: |_| true,
+        draw_calls: |s| Some(s.geometry.draw_calls),
+    },
+    Pass {
+        name: "Decals",
+        inputs: &["Depth", "Decal Mask"],
+        outputs: &["Diffuse", "Normal"],
+        enabled: |_| true,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "SSAO",
+        inputs: &["Depth", "Normal"],
+        outputs: &["Ambient Occlusion"],
+        enabled: |s| s.use_ssao,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "Deferred Lighting",
+        inputs: &[
+            "G-Buffer",
+            "Ambient Occlusion",
+            "Cascaded Shadow Maps",
+            "Spot Shadow Maps",
+            "Point Shadow Cube Maps",
+        ],
+        outputs: &["HDR Frame"],
+        enabled: |_| true,
+        // Every light is drawn separately, after a single full-screen ambient light draw.
+        draw_calls: |s| {
+            let lighting = &s.lighting;
+            Some(
+                1 + lighting.point_lights_rendered
+                    + lighting.spot_lights_rendered
+                    + lighting.directional_lights_rendered,
+            )
+        },
+    },
+    Pass {
+        name: "Forward",
+        inputs: &["Depth", "HDR Frame"],
+        outputs: &["HDR Frame"],
+        enabled: |_| true,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "HDR",
+        inputs: &["HDR Frame"],
+        outputs: &["Luminance", "Bloom", "LDR Frame"],
+        enabled: |_| true,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "FXAA",
+        inputs: &["LDR Frame"],
+        outputs: &["Frame"],
+        enabled: |s| s.fxaa,
+        draw_calls: |_| None,
+    },
+    Pass {
+        name: "User Interface",
+        inputs: &["Frame"],
+        outputs: &["Back Buffer"],
+        enabled: |_| true,
+        draw_calls: |_| None,
+    },
+];
+
+#[derive(Default, Debug)]
+pub struct RenderGraphWindow {
+    window: Handle<UiNode>,
+    /// Tree items of the passes, in the order of [`PASSES`].
+    items: Vec<Handle<UiNode>>,
+    /// Headers of the passes, in the order of [`PASSES`].
+    headers: Vec<Handle<UiNode>>,
+    /// Passes, that the renderer runs with its current settings.
+    enabled: Vec<bool>,
+    visible: bool,
+    /// Index of the highlighted pass.
+    busiest: Option<usize>,
+}
+
+impl RenderGraphWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let mut headers = Vec::new();
+        let items = PASSES
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                let header =
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                        .with_text(format!("{}. {}", index + 1, pass.name))
+                        .build(ctx);
+                headers.push(header);
+
+                let details =
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(16.0)))
+                        .with_text(format!(
+                            "Inputs: {}\nOutputs: {}",
+                            pass.inputs.join(", "),
+                            pass.outputs.join(", ")
+                        ))
+                        .build(ctx);
+
+                TreeBuilder::new(WidgetBuilder::new())
+                    .with_content(
+                        ExpanderBuilder::new(WidgetBuilder::new())
+                            .with_header(header)
+                            .with_content(details)
+                            .with_expanded(false)
+                            .build(ctx),
+                    )
+                    .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(360.0).with_height(420.0))
+            .with_title(WindowTitle::text("Render Graph"))
+            .can_close(false)
+            .open(false)
+            .with_content(
+                TreeRootBuilder::new(WidgetBuilder::new())
+                    .with_items(items.clone())
+                    .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            items,
+            headers,
+            enabled: vec![true; PASSES.len()],
+            visible: false,
+            busiest: None,
+        }
+    }
+
+    pub fn set_visible(&mut self, ui: &UserInterface, visible: bool) {
+        self.visible = visible;
+        if visible {
+            ui.send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                true,
+                false,
+            ));
+        } else {
+            ui.send_message(WindowMessage::close(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ui: &UserInterface,
+        statistics: &Statistics,
+        settings: &QualitySettings,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let enabled = PASSES
+            .iter()
+            .map(|pass| (pass.enabled)(settings))
+            .collect::<Vec<_>>();
+        if enabled != self.enabled {
+            for (item, visible) in self.items.iter().zip(enabled.iter()) {
+                ui.send_message(WidgetMessage::visibility(
+                    *item,
+                    MessageDirection::ToWidget,
+                    *visible,
+                ));
+            }
+            self.enabled = enabled;
+        }
+
+        let draw_calls = PASSES
+            .iter()
+            .zip(self.enabled.iter())
+            .map(|(pass, enabled)| enabled.then(|| (pass.draw_calls)(statistics)).flatten())
+            .collect::<Vec<_>>();
+        let busiest = draw_calls
+            .iter()
+            .enumerate()
+            .filter_map(|(index, draw_calls)| Some((index, (*draw_calls)?)))
+            .filter(|(_, draw_calls)| *draw_calls > 0)
+            .max_by_key(|(_, draw_calls)| *draw_calls)
+            .map(|(index, _)| index);
+
+        // Disabled passes are hidden, so the numbers follow the order of the passes, that run.
+        let mut number = 0;
+        for (index, (pass, header)) in PASSES.iter().zip(self.headers.iter()).enumerate() {
+            if !self.enabled[index] {
+                continue;
+            }
+            number += 1;
+            let draw_calls = match draw_calls[index] {
+                Some(draw_calls) => format!("{draw_calls} draw calls"),
+                None => "no statistics".to_string(),
+            };
+            ui.send_message(TextMessage::text(
+                *header,
+                MessageDirection::ToWidget,
+                format!("{number}. {} - {draw_calls}, GPU time: N/A", pass.name),
+            ));
+        }
+
+        if busiest != self.busiest {
+            for (index, header) in self.headers.iter().enumerate() {
+                let color = if Some(index) == busiest {
+                    HIGHLIGHT_COLOR
+                } else {
+                    Color::WHITE
+                };
+                ui.send_message(WidgetMessage::foreground(
+                    *header,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(color),
+                ));
+            }
+            self.busiest = busiest;
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Switching between deferred and forward rendering. The renderer has no global pipeline switch,
+//! the render path is a property of every mesh instead, so the selected path is applied to all
+//! meshes of the scene.
+use fyrox::{
+    graph::SceneGraph,
+    scene::{
+        mesh::{Mesh, RenderPath},
+        Scene,
+    },
+};
+
+/// Names of the render paths, in the order of the dropdown list.
+pub const RENDER_PATHS: [(&str, RenderPath); 2] = [
+    ("Deferred", RenderPath::Deferred),
+    ("Forward", RenderPath::Forward),
+];
+
+#[derive(Default, Debug)]
+pub struct RenderPathSelector {
+    /// Index of the selected path in [`RENDER_PATHS`].
+    selected: usize,
+}
+
+impl RenderPathSelector {
+    pub fn select(&mut self, index: usize, scene: Option<&mut Scene>) {
+        self.selected = index.min(RENDER_PATHS.len() - 1);
+        if let Some(scene) = scene {
+            self.apply(scene);
+        }
+    }
+
+    /// Sets the selected render path to every mesh of the scene.
+    pub fn apply(&self, scene: &mut Scene) {
+        let render_path = RENDER_PATHS[self.selected].1;
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(mesh) = node.cast_mut::<Mesh>() {
+                mesh.set_render_path(render_path);
+            }
+        }
+    }
+
+    pub fn stats_text(&self, batches: usize) -> String {
+        let name = RENDER_PATHS[self.selected].0;
+        format!("Render Path: {name}\nBatches: {batches}")
+    }
+}
@@ -0,0 +1,85 @@
+//! Resolution of cascaded shadow maps and the texel density it gives to every cascade. The
+//! renderer stores all cascades in one texture array, so they all share the resolution from
+//! `CsmSettings::size`; the density still differs, because the cascades cover different ranges.
+use fyrox::{
+    graph::SceneGraph,
+    renderer::QualitySettings,
+    scene::{
+        camera::{Camera, Projection},
+        light::directional::{DirectionalLight, FrustumSplitOptions, CSM_NUM_CASCADES},
+        Scene,
+    },
+};
+
+/// Resolutions of the shadow maps, in the order of the dropdown list.
+pub const RESOLUTIONS: [usize; 4] = [512, 1024, 2048, 4096];
+
+/// Index of the current resolution in [`RESOLUTIONS`], the closest one is used if the settings
+/// have a resolution that is not in the list.
+pub fn resolution_index(settings: &QualitySettings) -> usize {
+    RESOLUTIONS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, size)| size.abs_diff(settings.csm_settings.size))
+        .map_or(0, |(index, _)| index)
+}
+
+pub fn set_resolution(settings: &mut QualitySettings, index: usize) {
+    settings.csm_settings.size = RESOLUTIONS[index.min(RESOLUTIONS.len() - 1)];
+}
+
+/// Diameter of the sphere around the part of the view frustum between the given distances. The
+/// renderer fits the shadow map of a cascade around the same sphere.
+fn slice_diameter(fov: f32, aspect: f32, near: f32, far: f32) -> f32 {
+    let half_height = |distance: f32| distance * (fov * 0.5).tan();
+    let (near_height, far_height) = (half_height(near), half_height(far));
+    let (near_width, far_width) = (near_height * aspect, far_height * aspect);
+
+    let far_diagonal = 2.0 * far_width.hypot(far_height);
+    let slice_diagonal = ((near_width + far_width).powi(2)
+        + (near_height + far_height).powi(2)
+        + (far - near).powi(2))
+    .sqrt();
+    far_diagonal.max(slice_diagonal)
+}
+
+/// World units per shadow texel for every cascade of the first directional light of the scene.
+/// Smaller values mean sharper shadows.
+pub fn texel_density_text(scene: &Scene, settings: &QualitySettings, aspect: f32) -> String {
+    let Some(light) = scene
+        .graph
+        .linear_iter()
+        .find_map(|node| node.cast::<DirectionalLight>())
+    else {
+        return "Shadow Cascades: no directional light".to_string();
+    };
+    let Some(Projection::Perspective(perspective)) = scene
+        .graph
+        .linear_iter()
+        .filter_map(|node| node.cast::<Camera>())
+        .find(|camera| camera.is_enabled())
+        .map(|camera| camera.projection())
+    else {
+        return "Shadow Cascades: no perspective camera".to_string();
+    };
+
+    let far_planes: [f32; CSM_NUM_CASCADES] = match light.csm_options.split_options {
+        FrustumSplitOptions::Absolute { far_planes } => far_planes,
+        FrustumSplitOptions::Relative { fractions } => {
+            fractions.map(|fraction| fraction * perspective.z_far)
+        }
+    };
+
+    let resolution = settings.csm_settings.size as f32;
+    let mut near = perspective.z_near;
+    let mut text = format!("Shadow Cascades ({0}x{0}):", settings.csm_settings.size);
+    for (index, far) in far_planes.into_iter().enumerate() {
+        let diameter = slice_diameter(perspective.fov, aspect, near, far);
+        text += &format!(
+            "\n  #{index}: {near:.1}..{far:.1} m, {:.4} m/texel",
+            diameter / resolution
+        );
+        near = far;
+    }
+    text
+}
@@ -0,0 +1,171 @@
+//! Color themes of the interface, that could be switched at runtime. The selected theme is stored
+//! in the prefs file, so it is restored on the next run.
+use fyrox::{
+    core::{color::Color, log::Log, pool::Handle, visitor::prelude::*},
+    gui::{
+        border::Border,
+        brush::Brush,
+        decorator::{Decorator, DecoratorMessage},
+        message::MessageDirection,
+        widget::WidgetMessage,
+        UiNode, UserInterface,
+    },
+};
+
+/// Path of the file with the prefs of the interface, relative to the working directory.
+const PREFS_PATH: &str = "data/prefs.bin";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub foreground: Color,
+    /// Color of the selected and pressed widgets.
+    pub accent: Color,
+    /// Color of the widgets under the cursor.
+    pub hover: Color,
+}
+
+impl Theme {
+    /// Dark theme, that is close to the default style of the widgets.
+    pub fn dark() -> Self {
+        Self {
+            name: "Dark",
+            background: Color::opaque(40, 40, 40),
+            foreground: Color::opaque(220, 220, 220),
+            accent: Color::opaque(80, 118, 178),
+            hover: Color::opaque(70, 70, 70),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "Light",
+            background: Color::opaque(225, 225, 228),
+            foreground: Color::opaque(30, 30, 30),
+            accent: Color::opaque(110, 160, 230),
+            hover: Color::opaque(200, 205, 215),
+        }
+    }
+
+    pub fn all() -> [Theme; 2] {
+        [Self::dark(), Self::light()]
+    }
+
+    /// Applies the theme to the widgets. Containers pass the theme to all of their descendants:
+    /// texts get the foreground color, borders get the background color and decorators get all
+    /// the colors of their states.
+    pub fn apply(
+        &self,
+        ui: &UserInterface,
+        widgets: &[Handle<UiNode>],
+        containers: &[Handle<UiNode>],
+    ) {
+        for widget in widgets.iter().chain(containers).filter(|h| h.is_some()) {
+            self.set_colors(ui, *widget);
+        }
+
+        let mut stack = containers
+            .iter()
+            .filter(|h| h.is_some())
+            .flat_map(|container| ui.node(*container).children().to_vec())
+            .collect::<Vec<_>>();
+        while let Some(handle) = stack.pop() {
+            let node = ui.node(handle);
+            stack.extend_from_slice(node.children());
+
+            if node.cast::<Decorator>().is_some() {
+                for message in [
+                    DecoratorMessage::normal_brush(
+                        handle,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(self.background),
+                    ),
+                    DecoratorMessage::hover_brush(
+                        handle,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(self.hover),
+                    ),
+                    DecoratorMessage::pressed_brush(
+                        handle,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(self.accent),
+                    ),
+                    DecoratorMessage::selected_brush(
+                        handle,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(self.accent),
+                    ),
+                ] {
+                    ui.send_message(message);
+                }
+                // Decorators switch to the normal brush only on the next change of their state.
+                self.set_colors(ui, handle);
+            } else if node.cast::<Border>().is_some() {
+                self.set_colors(ui, handle);
+            } else {
+                ui.send_message(WidgetMessage::foreground(
+                    handle,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(self.foreground),
+                ));
+            }
+        }
+    }
+
+    fn set_colors(&self, ui: &UserInterface, handle: Handle<UiNode>) {
+        ui.send_message(WidgetMessage::background(
+            handle,
+            MessageDirection::ToWidget,
+            Brush::Solid(self.background),
+        ));
+        ui.send_message(WidgetMessage::foreground(
+            handle,
+            MessageDirection::ToWidget,
+            Brush::Solid(self.foreground),
+        ));
+    }
+}
+
+/// Prefs of the interface, that are stored between runs.
+#[derive(Visit, Default, Debug, Clone)]
+pub struct UiPrefs {
+    /// Name of the selected theme.
+    pub theme: String,
+}
+
+impl UiPrefs {
+    /// Loads the prefs from the file, falls back to the default prefs if the file does not exist
+    /// or is corrupted.
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read(PREFS_PATH) else {
+            return Self::default();
+        };
+
+        let mut prefs = Self::default();
+        match Visitor::load_from_memory(&data) {
+            Ok(mut visitor) => {
+                if let Err(err) = prefs.visit("UiPrefs", &mut visitor) {
+                    Log::err(format!("Unable to read UI prefs: {err:?}"));
+                    prefs = Self::default();
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load UI prefs: {err:?}")),
+        }
+        prefs
+    }
+
+    pub fn save(&self) {
+        let mut prefs = self.clone();
+        let mut visitor = Visitor::new();
+        Log::verify(prefs.visit("UiPrefs", &mut visitor));
+        Log::verify(visitor.save_binary(PREFS_PATH));
+    }
+
+    /// Returns the index of the saved theme in [`Theme::all`], if there's any.
+    pub fn theme_index(&self) -> Option<usize> {
+        Theme::all()
+            .iter()
+            .position(|theme| theme.name == self.theme)
+    }
+}
@@ -0,0 +1,157 @@
+//! Layout regression test of the UI - a lightweight alternative to a full screenshot diff. After a
+//! few frames of warm-up, the UI is rendered to a texture of the reference size, so the layout does
+//! not depend on the size and the DPI of the window. The average colors of a few regions of the
+//! texture, that correspond to known parts of the UI (title bars, tile headers, etc.), are compared
+//! with the reference colors. If a widget moves, the colors of the regions change and the test
+//! fails.
+//!
+//! The regions are stored in `data/ui_test_refs.json`. A check without a reference color fails,
+//! the references are recorded (or re-recorded) by running the test with `--bless` argument.
+use common_scripts::UiCapture;
+use fyrox::{
+    core::{algebra::Vector2, color::Color, log::Log, math::Rect},
+    gui::UserInterface,
+    renderer::Renderer,
+};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Amount of frames, that are rendered before the check, so the layout of the UI is settled.
+const WARM_UP_FRAMES: usize = 10;
+const REFERENCES_FILE: &str = "data/ui_test_refs.json";
+
+#[derive(Deserialize, Serialize, Debug)]
+struct Region {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct Check {
+    name: String,
+    /// Region of the frame in pixels, the origin is at the top left corner.
+    region: Region,
+    /// Expected average color of the region, `None` if it is not recorded yet.
+    expected: Option<[u8; 3]>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct References {
+    /// Size of the frame in logical pixels, that the UI is rendered to.
+    frame_size: [u32; 2],
+    /// Max difference of a color channel of the average color from the reference.
+    tolerance: u8,
+    checks: Vec<Check>,
+}
+
+#[derive(Default, Debug)]
+pub struct UiLayoutTest {
+    frame: usize,
+    /// Record the measured colors as the references instead of checking them.
+    bless: bool,
+    capture: UiCapture,
+}
+
+/// Returns the average color of the region of the frame.
+fn average_color(frame: &RgbaImage, region: Rect<u32>) -> [u8; 3] {
+    let mut sum = [0u64; 3];
+    for y in region.y()..region.y() + region.h() {
+        for x in region.x()..region.x() + region.w() {
+            for (sum, channel) in sum.iter_mut().zip(frame.get_pixel(x, y).0) {
+                *sum += channel as u64;
+            }
+        }
+    }
+    let count = (region.w() as u64 * region.h() as u64).max(1);
+    sum.map(|sum| (sum / count) as u8)
+}
+
+impl UiLayoutTest {
+    pub fn new(bless: bool) -> Self {
+        Self {
+            bless,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the result of the test, once the warm-up is over: `true` if all the checks passed.
+    pub fn update(&mut self, renderer: &mut Renderer, ui: &mut UserInterface) -> Option<bool> {
+        self.frame += 1;
+        if self.frame < WARM_UP_FRAMES {
+            return None;
+        }
+        Some(match self.run(renderer, ui) {
+            Ok(passed) => passed,
+            Err(err) => {
+                Log::err(format!("Unable to run UI layout test. Reason: {err}"));
+                false
+            }
+        })
+    }
+
+    fn run(&mut self, renderer: &mut Renderer, ui: &mut UserInterface) -> Result<bool, String> {
+        let mut references = fs::read(REFERENCES_FILE)
+            .map_err(|err| format!("{err:?}"))
+            .and_then(|bytes| {
+                serde_json::from_slice::<References>(&bytes).map_err(|err| err.to_string())
+            })?;
+
+        let frame_size = Vector2::from(references.frame_size);
+        let frame = self
+            .capture
+            .capture(renderer, ui, frame_size, Color::BLACK)?;
+
+        let mut passed = true;
+        let mut recorded = false;
+        for check in references.checks.iter_mut() {
+            let region = Rect::new(
+                check.region.x,
+                check.region.y,
+                check.region.w,
+                check.region.h,
+            );
+            if region.x() + region.w() > frame_size.x || region.y() + region.h() > frame_size.y {
+                println!("FAIL {}: the region is outside of the frame", check.name);
+                passed = false;
+                continue;
+            }
+
+            let actual = average_color(&frame, region);
+            match check.expected.filter(|_| !self.bless) {
+                Some(expected) => {
+                    let ok = actual.iter().zip(expected).all(|(actual, expected)| {
+                        actual.abs_diff(expected) <= references.tolerance
+                    });
+                    println!(
+                        "{} {}: expected {expected:?}, got {actual:?}",
+                        if ok { "PASS" } else { "FAIL" },
+                        check.name
+                    );
+                    passed &= ok;
+                }
+                None if self.bless => {
+                    println!("RECORDED {}: {actual:?}", check.name);
+                    check.expected = Some(actual);
+                    recorded = true;
+                }
+                None => {
+                    println!(
+                        "FAIL {}: no reference, got {actual:?}. Run with --bless to record it",
+                        check.name
+                    );
+                    passed = false;
+                }
+            }
+        }
+
+        if recorded {
+            let json = serde_json::to_string_pretty(&references).map_err(|err| err.to_string())?;
+            fs::write(REFERENCES_FILE, json).map_err(|err| format!("{err:?}"))?;
+        }
+
+        Ok(passed)
+    }
+}
@@ -0,0 +1,216 @@
+//! Game project.
+use crate::vehicle::{build_vehicle, Vehicle};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::BaseSceneGraph,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    plugin::{Plugin, PluginContext, PluginRegistrationContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+mod vehicle;
+
+/// Defines how fast the camera catches up with the car.
+const CAMERA_SMOOTHNESS: f32 = 4.0;
+
+const SCENE_NAME: &str = "vehicle";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    car: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_static_box(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    half_extents: Vector3<f32>,
+) {
+    let mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&half_extents.scale(2.0))),
+        ))
+        .build()])
+        .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .with_friction(1.0)
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_rotation(rotation)
+                    .build(),
+            )
+            .with_children(&[mesh, collider]),
+    )
+    .with_body_type(RigidBodyType::Static)
+    .build(graph);
+}
+
+impl Game {
+    fn update_camera(&self, scene: &mut Scene, dt: f32) {
+        let car = &scene.graph[self.car];
+        let car_position = car.global_position();
+        let car_look = car
+            .look_vector()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        let desired_position = car_position - car_look.scale(8.0) + Vector3::new(0.0, 3.0, 0.0);
+
+        let camera = &mut scene.graph[self.camera];
+        let position = camera
+            .global_position()
+            .lerp(&desired_position, (CAMERA_SMOOTHNESS * dt).min(1.0));
+        let rotation = UnitQuaternion::face_towards(&(car_position - position), &Vector3::y());
+        camera
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(rotation);
+    }
+}
+
+impl Plugin for Game {
+    fn register(&self, context: PluginRegistrationContext) {
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Vehicle>("Vehicle");
+    }
+
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        let mut scene = Scene::new();
+
+        self.camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 4.0, -8.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        60.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        // Ground
+        make_static_box(
+            &mut scene.graph,
+            Vector3::new(0.0, -0.5, 0.0),
+            Default::default(),
+            Vector3::new(100.0, 0.5, 100.0),
+        );
+
+        // A few ramps to test the suspension.
+        for (i, angle) in [8.0f32, 12.0, 16.0].into_iter().enumerate() {
+            make_static_box(
+                &mut scene.graph,
+                Vector3::new(-10.0 + i as f32 * 10.0, 0.0, 25.0),
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -angle.to_radians()),
+                Vector3::new(3.0, 0.5, 6.0),
+            );
+        }
+
+        self.car = build_vehicle(&mut scene.graph, Vector3::new(0.0, 1.5, 0.0));
+
+        self.scene = context.scenes.add(scene);
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new())
+            .build(&mut context.user_interfaces.first_mut().build_ctx());
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        self.update_camera(scene, context.dt);
+
+        let speed = scene
+            .graph
+            .try_get_of_type::<RigidBody>(self.car)
+            .map(|body| body.lin_vel().magnitude() * 3.6)
+            .unwrap_or_default();
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context.user_interfaces.first().send_message(TextMessage::text(
+                self.debug_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "Example - Vehicle Physics\n[W][S] - accelerate/reverse, [A][D] - steer\nSpeed: {:.1} km/h\nFPS: {}\n{}",
+                    speed,
+                    graphics_context.renderer.get_statistics().frames_per_second,
+                    tracked_alloc::peak_ram_text()
+                ),
+            ));
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,430 @@
+//! A car made of rigid bodies connected with joints.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    event::{ElementState, Event, WindowEvent},
+    graph::{BaseSceneGraph, SceneGraph},
+    keyboard::{KeyCode, PhysicalKey},
+    scene::{
+        base::BaseBuilder,
+        collider::{BitMask, Collider, ColliderBuilder, ColliderShape, InteractionGroups},
+        graph::Graph,
+        joint::{Joint, JointBuilder, JointParams, PrismaticJoint, RevoluteJoint},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+use std::f32::consts::FRAC_PI_2;
+
+const WHEEL_RADIUS: f32 = 0.4;
+const WHEEL_WIDTH: f32 = 0.3;
+/// How far a wheel can go down from its rest position.
+const MAX_EXTENSION: f32 = 0.1;
+/// Steering speed in radians per second.
+const STEERING_SPEED: f32 = 2.0;
+
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct Wheel {
+    /// A body that slides along the suspension axis.
+    hub: Handle<Node>,
+    /// A body that rotates around vertical axis to steer the wheel.
+    knuckle: Handle<Node>,
+    wheel: Handle<Node>,
+    suspension_joint: Handle<Node>,
+    steering_joint: Handle<Node>,
+    /// Position of the suspension attachment point in the local space of the chassis.
+    anchor: Vector3<f32>,
+    steerable: bool,
+    driven: bool,
+}
+
+#[derive(Default, Debug, Clone, Visit, Reflect)]
+struct InputController {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+}
+
+/// Controls the car: applies suspension forces, drives rear wheels and steers the front ones.
+/// Must be assigned to the chassis.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "4e9b2d71-6a0c-4f38-9d15-c3a87e2b50f6")]
+#[visit(optional)]
+pub struct Vehicle {
+    pub suspension_stiffness: InheritableVariable<f32>,
+    pub suspension_damping: InheritableVariable<f32>,
+    /// How far a wheel can go up from its rest position.
+    pub max_compression: InheritableVariable<f32>,
+    pub wheel_friction: InheritableVariable<f32>,
+    pub engine_torque: InheritableVariable<f32>,
+    /// Maximum steering angle in radians.
+    pub max_steering_angle: InheritableVariable<f32>,
+    pub wheels: Vec<Wheel>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    input: InputController,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    steering_angle: f32,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            suspension_stiffness: 40000.0.into(),
+            suspension_damping: 3000.0.into(),
+            max_compression: 0.25.into(),
+            wheel_friction: 1.5.into(),
+            engine_torque: 600.0.into(),
+            max_steering_angle: 30.0f32.to_radians().into(),
+            wheels: Default::default(),
+            input: Default::default(),
+            steering_angle: 0.0,
+        }
+    }
+}
+
+impl Vehicle {
+    fn set_joint_limits(graph: &mut Graph, joint: Handle<Node>, params: JointParams) {
+        if let Some(joint) = graph.try_get_mut_of_type::<Joint>(joint) {
+            joint.set_params(params);
+        }
+    }
+
+    fn apply_suspension_forces(&self, graph: &mut Graph, chassis: Handle<Node>) {
+        let chassis_transform = graph[chassis].global_transform();
+        let up = graph[chassis]
+            .up_vector()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let chassis_velocity = graph
+            .try_get_of_type::<RigidBody>(chassis)
+            .map(|body| body.lin_vel())
+            .unwrap_or_default();
+
+        for wheel in self.wheels.iter() {
+            let anchor = chassis_transform
+                .transform_point(&Point3::from(wheel.anchor))
+                .coords;
+
+            let Some(hub) = graph.try_get_mut_of_type::<RigidBody>(wheel.hub) else {
+                continue;
+            };
+
+            // Positive values mean that the spring is compressed.
+            let compression = (hub.global_position() - anchor).dot(&up);
+            let compression_velocity = (hub.lin_vel() - chassis_velocity).dot(&up);
+            let force = up.scale(
+                *self.suspension_stiffness * compression
+                    + *self.suspension_damping * compression_velocity,
+            );
+
+            hub.apply_force(-force);
+
+            if let Some(chassis) = graph.try_get_mut_of_type::<RigidBody>(chassis) {
+                chassis.apply_force_at_point(force, anchor);
+            }
+        }
+    }
+
+    fn update_steering(&mut self, dt: f32) {
+        let target = if self.input.left {
+            *self.max_steering_angle
+        } else if self.input.right {
+            -*self.max_steering_angle
+        } else {
+            0.0
+        };
+
+        let max_delta = STEERING_SPEED * dt;
+        self.steering_angle += (target - self.steering_angle).clamp(-max_delta, max_delta);
+    }
+}
+
+impl ScriptTrait for Vehicle {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        for wheel in self.wheels.iter() {
+            let colliders = ctx.scene.graph[wheel.wheel].children().to_vec();
+            for child in colliders {
+                if let Some(collider) = ctx.scene.graph.try_get_mut_of_type::<Collider>(child) {
+                    collider.set_friction(*self.wheel_friction);
+                }
+            }
+
+            Self::set_joint_limits(
+                &mut ctx.scene.graph,
+                wheel.suspension_joint,
+                JointParams::PrismaticJoint(PrismaticJoint {
+                    limits_enabled: true,
+                    limits: -MAX_EXTENSION..*self.max_compression,
+                }),
+            );
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, _ctx: &mut ScriptContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            let pressed = event.state == ElementState::Pressed;
+            if let PhysicalKey::Code(code) = event.physical_key {
+                match code {
+                    KeyCode::KeyW => self.input.forward = pressed,
+                    KeyCode::KeyS => self.input.backward = pressed,
+                    KeyCode::KeyA => self.input.left = pressed,
+                    KeyCode::KeyD => self.input.right = pressed,
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.apply_suspension_forces(&mut ctx.scene.graph, ctx.handle);
+
+        self.update_steering(ctx.dt);
+
+        let throttle = if self.input.forward {
+            1.0
+        } else if self.input.backward {
+            -1.0
+        } else {
+            0.0
+        };
+
+        for wheel in self.wheels.iter() {
+            if wheel.steerable {
+                // Locking the steering joint at the desired angle turns the wheel.
+                Self::set_joint_limits(
+                    &mut ctx.scene.graph,
+                    wheel.steering_joint,
+                    JointParams::RevoluteJoint(RevoluteJoint {
+                        limits_enabled: true,
+                        limits: self.steering_angle..self.steering_angle,
+                    }),
+                );
+            }
+
+            if wheel.driven && throttle != 0.0 {
+                if let Some(body) = ctx
+                    .scene
+                    .graph
+                    .try_get_mut_of_type::<RigidBody>(wheel.wheel)
+                {
+                    // Wheels are spinning around their local X axis.
+                    let axis = body
+                        .side_vector()
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::x);
+                    body.apply_torque(axis.scale(throttle * *self.engine_torque));
+                }
+            }
+        }
+    }
+}
+
+/// Creates a body that is used only to connect joints. It has a collider that does not collide
+/// with anything, but gives the body some mass and inertia.
+fn make_link_body(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::ball(0.1))
+        .with_collision_groups(InteractionGroups::new(BitMask(0), BitMask(0)))
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[collider]),
+    )
+    .with_body_type(RigidBodyType::Dynamic)
+    .with_can_sleep(false)
+    .build(graph)
+}
+
+/// Creates a joint at the given position. Both prismatic and revolute joints work along the X
+/// axis of the joint, so the rotation defines the direction of the joint axis.
+fn make_joint(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    params: JointParams,
+    body1: Handle<Node>,
+    body2: Handle<Node>,
+) -> Handle<Node> {
+    JointBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .with_local_rotation(rotation)
+                .build(),
+        ),
+    )
+    .with_params(params)
+    .with_body1(body1)
+    .with_body2(body2)
+    .with_contacts_enabled(false)
+    .build(graph)
+}
+
+fn make_wheel_body(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    let mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cylinder(
+                16,
+                WHEEL_RADIUS,
+                WHEEL_WIDTH,
+                true,
+                // Cylinder is built along Y axis, turn it to X axis and center it.
+                &(UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2).to_homogeneous()
+                    * Matrix4::new_translation(&Vector3::new(0.0, -WHEEL_WIDTH * 0.5, 0.0))),
+            ),
+        ))
+        .build()])
+        .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::ball(WHEEL_RADIUS))
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[mesh, collider]),
+    )
+    .with_body_type(RigidBodyType::Dynamic)
+    .with_can_sleep(false)
+    .build(graph)
+}
+
+/// Creates a car at the given position and returns a handle of its chassis.
+pub fn build_vehicle(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    let half_extents = Vector3::new(0.9, 0.3, 2.0);
+
+    let mesh = MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&half_extents.scale(2.0))),
+        ))
+        .build()])
+        .build(graph);
+
+    let collider = ColliderBuilder::new(BaseBuilder::new())
+        .with_shape(ColliderShape::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .with_density(Some(200.0))
+        .build(graph);
+
+    let chassis = RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name("Chassis")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            )
+            .with_children(&[mesh, collider]),
+    )
+    .with_body_type(RigidBodyType::Dynamic)
+    .with_can_sleep(false)
+    .build(graph);
+
+    // Suspension and steering axes are vertical, wheels are spinning around X axis.
+    let vertical = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2);
+
+    let mut wheels = Vec::new();
+    for (anchor, front) in [
+        (Vector3::new(1.0, -0.4, 1.4), true),
+        (Vector3::new(-1.0, -0.4, 1.4), true),
+        (Vector3::new(1.0, -0.4, -1.4), false),
+        (Vector3::new(-1.0, -0.4, -1.4), false),
+    ] {
+        let wheel_position = position + anchor;
+
+        let hub = make_link_body(graph, wheel_position);
+        let knuckle = make_link_body(graph, wheel_position);
+        let wheel = make_wheel_body(graph, wheel_position);
+
+        let suspension_joint = make_joint(
+            graph,
+            wheel_position,
+            vertical,
+            JointParams::PrismaticJoint(PrismaticJoint {
+                limits_enabled: true,
+                limits: -MAX_EXTENSION..0.25,
+            }),
+            chassis,
+            hub,
+        );
+
+        // Rear wheels are connected the same way, but their steering joint is locked.
+        let steering_joint = make_joint(
+            graph,
+            wheel_position,
+            vertical,
+            JointParams::RevoluteJoint(RevoluteJoint {
+                limits_enabled: true,
+                limits: 0.0..0.0,
+            }),
+            hub,
+            knuckle,
+        );
+
+        make_joint(
+            graph,
+            wheel_position,
+            Default::default(),
+            JointParams::RevoluteJoint(RevoluteJoint {
+                limits_enabled: false,
+                limits: Default::default(),
+            }),
+            knuckle,
+            wheel,
+        );
+
+        wheels.push(Wheel {
+            hub,
+            knuckle,
+            wheel,
+            suspension_joint,
+            steering_joint,
+            anchor,
+            steerable: front,
+            driven: !front,
+        });
+    }
+
+    graph[chassis].add_script(Script::new(Vehicle {
+        wheels,
+        ..Default::default()
+    }));
+
+    chassis
+}
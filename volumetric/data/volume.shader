@@ -0,0 +1,160 @@
+(
+    name: "VolumeShader",
+
+    properties: [
+        (
+            name: "densityVolume",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "stepCount",
+            kind: Int(32),
+        ),
+        (
+            name: "absorption",
+            kind: Float(4.0),
+        ),
+        (
+            name: "albedo",
+            kind: Vector3((0.9, 0.9, 0.95)),
+        ),
+        (
+            name: "densityScale",
+            kind: Float(1.0),
+        ),
+        (
+            name: "lightDirection",
+            kind: Vector3((0.4, 0.8, 0.3)),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                // Only the front faces start the rays, the back faces are found analytically.
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: One,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add,
+                    ),
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 fyrox_cameraPosition;
+
+                out vec3 localPosition;
+                out vec3 localCamera;
+
+                void main()
+                {
+                    // Rays are marched in the space of the cube, where it spans from -0.5 to 0.5.
+                    localPosition = vertexPosition;
+                    localCamera = (inverse(fyrox_worldMatrix) * vec4(fyrox_cameraPosition, 1.0)).xyz;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler3D densityVolume;
+                uniform int stepCount;
+                uniform float absorption;
+                uniform vec3 albedo;
+                uniform float densityScale;
+                uniform vec3 lightDirection;
+
+                in vec3 localPosition;
+                in vec3 localCamera;
+
+                out vec4 FragColor;
+
+                // Amount of steps towards the light, used to shadow the cloud by itself.
+                const int lightSteps = 4;
+                const vec3 ambient = vec3(0.25, 0.3, 0.4);
+
+                // Distance along the ray to the point, where it leaves the cube.
+                float exitDistance(vec3 origin, vec3 direction)
+                {
+                    vec3 t = (sign(direction) * 0.5 - origin) / direction;
+                    return min(min(t.x, t.y), t.z);
+                }
+
+                float density(vec3 position)
+                {
+                    return texture(densityVolume, position + 0.5).r * densityScale;
+                }
+
+                void main()
+                {
+                    vec3 direction = normalize(localPosition - localCamera);
+                    float rayLength = exitDistance(localPosition, direction);
+                    float stepSize = rayLength / float(stepCount);
+                    vec3 light = normalize(lightDirection);
+
+                    vec3 color = vec3(0.0);
+                    float transmittance = 1.0;
+                    for (int i = 0; i < stepCount; ++i)
+                    {
+                        vec3 position = localPosition + direction * (float(i) + 0.5) * stepSize;
+                        float sampleDensity = density(position);
+                        if (sampleDensity <= 0.0)
+                        {
+                            continue;
+                        }
+
+                        // Light, that reaches the sample through the cloud.
+                        float lightLength = exitDistance(position, light) / float(lightSteps);
+                        float lightDepth = 0.0;
+                        for (int j = 0; j < lightSteps; ++j)
+                        {
+                            lightDepth += density(position + light * (float(j) + 0.5) * lightLength);
+                        }
+                        float lightTransmittance = exp(-lightDepth * lightLength * absorption);
+
+                        // Beer-Lambert law - the part of the light absorbed by the step is scattered
+                        // towards the viewer in proportion to the albedo.
+                        float stepTransmittance = exp(-sampleDensity * stepSize * absorption);
+                        vec3 scattered = albedo * (lightTransmittance + ambient);
+                        color += transmittance * (1.0 - stepTransmittance) * scattered;
+                        transmittance *= stepTransmittance;
+
+                        if (transmittance < 0.01)
+                        {
+                            break;
+                        }
+                    }
+
+                    // Color is premultiplied by the opacity.
+                    FragColor = vec4(color, 1.0 - transmittance);
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,116 @@
+//! Procedural density field of a cloud - fractal value noise, that fades out towards the edges of
+//! the volume, so the cloud has a round shape and does not touch the sides of the cube.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::algebra::Vector3,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    resource::texture::{
+        TextureKind, TextureMinificationFilter, TexturePixelKind, TextureResource,
+        TextureResourceExtension, TextureWrapMode,
+    },
+};
+
+/// Amount of voxels of the volume along each side.
+pub const VOLUME_SIZE: usize = 64;
+/// Amount of random values of the noise lattice along each side, the lattice is tiled.
+const LATTICE_SIZE: usize = 16;
+const OCTAVES: usize = 4;
+/// Density below this value is cut off, which gives the cloud gaps and a sharper outline.
+const COVERAGE: f32 = 0.35;
+
+struct ValueNoise {
+    lattice: Vec<f32>,
+}
+
+impl ValueNoise {
+    fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self {
+            lattice: (0..LATTICE_SIZE * LATTICE_SIZE * LATTICE_SIZE)
+                .map(|_| rng.gen_range(0.0..1.0))
+                .collect(),
+        }
+    }
+
+    fn lattice_value(&self, x: usize, y: usize, z: usize) -> f32 {
+        let (x, y, z) = (x % LATTICE_SIZE, y % LATTICE_SIZE, z % LATTICE_SIZE);
+        self.lattice[(z * LATTICE_SIZE + y) * LATTICE_SIZE + x]
+    }
+
+    /// Smoothly interpolated value of the lattice at the point, the lattice has a unit step.
+    fn sample(&self, point: Vector3<f32>) -> f32 {
+        let cell = point.map(|c| c.floor());
+        let t = (point - cell).map(|t| t * t * (3.0 - 2.0 * t));
+        let [x, y, z] = [cell.x as usize, cell.y as usize, cell.z as usize];
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let plane = |z: usize| {
+            lerp(
+                lerp(
+                    self.lattice_value(x, y, z),
+                    self.lattice_value(x + 1, y, z),
+                    t.x,
+                ),
+                lerp(
+                    self.lattice_value(x, y + 1, z),
+                    self.lattice_value(x + 1, y + 1, z),
+                    t.x,
+                ),
+                t.y,
+            )
+        };
+        lerp(plane(z), plane(z + 1), t.z)
+    }
+
+    /// Sum of a few octaves of the noise with doubling frequency and halving amplitude, the
+    /// result is in `0..1` range.
+    fn fractal(&self, point: Vector3<f32>) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = 1.0;
+        for _ in 0..OCTAVES {
+            sum += self.sample(point.scale(frequency)) * amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / (1.0 - 0.5f32.powi(OCTAVES as i32))
+    }
+}
+
+/// Generates the density field and returns it as a single channel 3D texture.
+pub fn make_density_volume(seed: u64) -> Option<TextureResource> {
+    let noise = ValueNoise::new(seed);
+    let scale = 4.0 / VOLUME_SIZE as f32;
+    let center = Vector3::repeat(VOLUME_SIZE as f32 * 0.5);
+    let mut bytes = Vec::with_capacity(VOLUME_SIZE * VOLUME_SIZE * VOLUME_SIZE);
+    for z in 0..VOLUME_SIZE {
+        for y in 0..VOLUME_SIZE {
+            for x in 0..VOLUME_SIZE {
+                let point = Vector3::new(x as f32, y as f32, z as f32);
+                let distance = (point - center).norm() / center.x;
+                let falloff = (1.0 - distance).clamp(0.0, 1.0);
+                let density = noise.fractal(point.scale(scale)) * falloff.sqrt() - COVERAGE;
+                bytes.push(((density / (1.0 - COVERAGE)).clamp(0.0, 1.0) * 255.0) as u8);
+            }
+        }
+    }
+
+    let texture = TextureResource::from_bytes(
+        TextureKind::Volume {
+            width: VOLUME_SIZE as u32,
+            height: VOLUME_SIZE as u32,
+            depth: VOLUME_SIZE as u32,
+        },
+        TexturePixelKind::R8,
+        bytes,
+        ResourceKind::Embedded,
+    )?;
+    {
+        // The volume has no mip levels and it must not repeat at the sides of the cube.
+        let mut data = texture.data_ref();
+        data.set_minification_filter(TextureMinificationFilter::Linear);
+        data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        data.set_r_wrap_mode(TextureWrapMode::ClampToEdge);
+    }
+    Some(texture)
+}
@@ -0,0 +1,391 @@
+//! Game project.
+use crate::density::make_density_volume;
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    graph::SceneGraph,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{
+        shader::{SamplerFallback, Shader, ShaderResource},
+        Material, MaterialResource, PropertyValue,
+    },
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            Mesh, MeshBuilder, RenderPath,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+
+mod density;
+
+/// Size of the cube, that contains the cloud.
+const VOLUME_EXTENT: f32 = 6.0;
+/// Speed of rotation of the cloud, in radians per second.
+const ROTATION_SPEED: f32 = 0.2;
+const LIGHT_DIRECTION: Vector3<f32> = Vector3::new(0.4, 0.8, 0.3);
+/// Weight of a new sample in the moving average of the frame time.
+const TIME_SMOOTHING: f32 = 0.05;
+
+const SCENE_NAME: &str = "volumetric";
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    volume: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    step_count_slider: Handle<UiNode>,
+    density_scale_slider: Handle<UiNode>,
+    flat_material_check_box: Handle<UiNode>,
+    /// Amount of ray marching steps through the volume.
+    step_count: i32,
+    /// Fraction of light, that is absorbed by a unit of density over a unit of distance.
+    absorption: f32,
+    /// Fraction of absorbed light, that is scattered towards the viewer, per color channel.
+    albedo: Vector3<f32>,
+    density_scale: f32,
+    angle: f32,
+    flat: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    volume_material: Option<MaterialResource>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    flat_material: Option<MaterialResource>,
+    /// Average frame time (in seconds) with the volume material and with the flat material.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    frame_times: [Option<f32>; 2],
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+fn make_text(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(if step < 1.0 { 2 } else { 0 })
+    .show_value(true)
+    .build(ctx)
+}
+
+impl Game {
+    fn build_volume(&mut self, shader: ShaderResource, scene: &mut Scene) {
+        let mut material = Material::from_shader(shader, None);
+        match make_density_volume(0) {
+            Some(texture) => Log::verify(material.set_property(
+                &"densityVolume".into(),
+                PropertyValue::Sampler {
+                    value: Some(texture),
+                    fallback: SamplerFallback::Black,
+                },
+            )),
+            None => Log::err("Unable to create the density volume."),
+        }
+        Log::verify(material.set_property(&"lightDirection".into(), LIGHT_DIRECTION.into()));
+        let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+
+        // The cloud is semi-transparent, so it is drawn after the opaque objects.
+        self.volume = MeshBuilder::new(BaseBuilder::new().with_cast_shadows(false))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+                ResourceKind::Embedded,
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .with_material(material.clone())
+            .build()])
+            .with_render_path(RenderPath::Forward)
+            .build(&mut scene.graph);
+
+        self.volume_material = Some(material);
+    }
+
+    fn update_volume(&mut self, dt: f32, context: &mut PluginContext) {
+        if let Some(material) = self.volume_material.as_ref() {
+            let mut material = material.data_ref();
+            Log::verify(material.set_property(&"stepCount".into(), self.step_count.into()));
+            Log::verify(material.set_property(&"absorption".into(), self.absorption.into()));
+            Log::verify(material.set_property(&"albedo".into(), self.albedo.into()));
+            Log::verify(material.set_property(&"densityScale".into(), self.density_scale.into()));
+        }
+
+        self.angle += ROTATION_SPEED * dt;
+
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        if let Some(volume) = scene.graph.try_get_mut(self.volume) {
+            volume
+                .local_transform_mut()
+                .set_scale(Vector3::repeat(VOLUME_EXTENT))
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    self.angle,
+                ));
+        }
+    }
+
+    /// Replaces the volume material with a flat one, to measure the cost of the ray marching.
+    fn set_flat(&mut self, flat: bool, context: &mut PluginContext) {
+        self.flat = flat;
+
+        let material = if flat {
+            self.flat_material.clone()
+        } else {
+            self.volume_material.clone()
+        };
+        let Some(material) = material else {
+            return;
+        };
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+        if let Some(mesh) = scene.graph.try_get_mut_of_type::<Mesh>(self.volume) {
+            for surface in mesh.surfaces_mut() {
+                surface.set_material(material.clone());
+            }
+        }
+    }
+
+    fn overhead_text(&self) -> String {
+        match self.frame_times {
+            [Some(volume), Some(flat)] => {
+                format!("Ray Marching Overhead: {:.3} ms", (volume - flat) * 1000.0)
+            }
+            _ => "Ray Marching Overhead: toggle Flat Material to measure".to_string(),
+        }
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.step_count = 32;
+        self.absorption = 4.0;
+        self.albedo = Vector3::new(0.9, 0.9, 0.95);
+        self.density_scale = 1.0;
+        self.flat_material = Some(MaterialResource::new_ok(
+            ResourceKind::Embedded,
+            Material::standard(),
+        ));
+
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, -9.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        12.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(
+                        UnitQuaternion::rotation_between(&Vector3::y(), &LIGHT_DIRECTION)
+                            .unwrap_or_else(UnitQuaternion::identity),
+                    )
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, -VOLUME_EXTENT * 0.5 - 0.5, 0.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                40.0, 0.1, 40.0,
+            ))),
+        ))
+        .with_material(MaterialResource::new_ok(
+            ResourceKind::Embedded,
+            Material::standard(),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let step_count_text = make_text(ctx, 0, "Step Count");
+        self.step_count_slider = make_slider(ctx, 1, 4.0, 64.0, 1.0, self.step_count as f32);
+        let density_scale_text = make_text(ctx, 2, "Density Scale");
+        self.density_scale_slider = make_slider(ctx, 3, 0.1, 4.0, 0.05, self.density_scale);
+        self.flat_material_check_box = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(4)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("Flat Material")
+                .build(ctx),
+        )
+        .checked(Some(false))
+        .build(ctx);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(180.0)
+                .with_desired_position(Vector2::new(5.0, 140.0)),
+        )
+        .with_title(WindowTitle::text("Volume Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(step_count_text)
+                    .with_child(self.step_count_slider)
+                    .with_child(density_scale_text)
+                    .with_child(self.density_scale_slider)
+                    .with_child(self.flat_material_check_box),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        context.task_pool.spawn_plugin_task(
+            context
+                .resource_manager
+                .request::<Shader>("data/volume.shader"),
+            |result, game: &mut Game, ctx| match result {
+                Ok(shader) => game.build_volume(shader, &mut ctx.scenes[game.scene]),
+                Err(err) => Log::err(format!("Unable to load volume shader. Reason: {err:?}")),
+            },
+        );
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.update_volume(context.dt, context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            let statistics = graphics_context.renderer.get_statistics();
+
+            let frame_time = &mut self.frame_times[self.flat as usize];
+            let average = frame_time.get_or_insert(statistics.pure_frame_time);
+            *average += (statistics.pure_frame_time - *average) * TIME_SMOOTHING;
+
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Volume Rendering\nMaterial: {}\nFrame Time: {:.3} ms\n{}\n{}\n{}",
+                        if self.flat { "Flat" } else { "Volume" },
+                        statistics.pure_frame_time * 1000.0,
+                        self.overhead_text(),
+                        statistics,
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.step_count_slider {
+                self.step_count = value.round() as i32;
+            } else if message.destination() == self.density_scale_slider {
+                self.density_scale = *value;
+            }
+            // The cost of the volume depends on the settings, so it must be measured again.
+            self.frame_times[0] = None;
+        } else if let Some(CheckBoxMessage::Check(Some(value))) = message.data() {
+            if message.destination() == self.flat_material_check_box {
+                self.set_flat(*value, context);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Generation of the blocks of a chunk and greedy meshing of them.
+use crate::noise::Perlin;
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        instant::Instant,
+        math::TriangleDefinition,
+    },
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexBuffer},
+        surface::SurfaceData,
+        vertex::StaticVertex,
+    },
+};
+use std::time::Duration;
+
+/// Amount of blocks along each side of a chunk, every block is 1x1x1 meter.
+pub const CHUNK_SIZE: i32 = 16;
+/// Blocks of a chunk together with a layer of the neighbouring blocks around it, that is needed
+/// to find out which faces on the borders of the chunk are visible.
+const PADDED_SIZE: i32 = CHUNK_SIZE + 2;
+/// Empty space below this height is filled with water.
+const WATER_LEVEL: i32 = 0;
+/// Height of the grass line relative to the amplitude of the terrain.
+const GRASS_LINE: f32 = 0.3;
+const NOISE_SEED: u32 = 0x5eed;
+const NOISE_OCTAVES: usize = 3;
+
+pub type ChunkCoords = Vector3<i32>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Block {
+    Air,
+    Stone,
+    Grass,
+    Water,
+}
+
+impl Block {
+    pub const VISIBLE: [Block; 3] = [Block::Stone, Block::Grass, Block::Water];
+
+    fn is_opaque(self) -> bool {
+        matches!(self, Block::Stone | Block::Grass)
+    }
+
+    /// Returns `true` if the face of this block is visible, when the other block is next to it.
+    fn is_face_visible(self, neighbour: Block) -> bool {
+        match self {
+            Block::Air => false,
+            // There's no need to draw the faces between water blocks or under the terrain.
+            Block::Water => neighbour == Block::Air,
+            Block::Stone | Block::Grass => !neighbour.is_opaque(),
+        }
+    }
+}
+
+/// Parameters of the noise, that shapes the terrain.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerrainSettings {
+    /// Frequency of the first octave of the noise, in 1/blocks.
+    pub frequency: f32,
+    /// Maximum height of the hills and depth of the valleys, in blocks.
+    pub amplitude: f32,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            frequency: 0.02,
+            amplitude: 24.0,
+        }
+    }
+}
+
+/// The terrain is solid where the noise is larger than the height above the water level, so the
+/// noise shapes hills, overhangs and caves at the same time.
+fn is_solid(noise: &Perlin, settings: &TerrainSettings, position: Vector3<i32>) -> bool {
+    let point = position.cast::<f32>() * settings.frequency;
+    noise.fractal(point, NOISE_OCTAVES) * settings.amplitude > position.y as f32
+}
+
+fn block_at(noise: &Perlin, settings: &TerrainSettings, position: Vector3<i32>) -> Block {
+    if is_solid(noise, settings, position) {
+        // Grass covers the surface, except the peaks of the hills.
+        if (position.y as f32) < settings.amplitude * GRASS_LINE
+            && !is_solid(noise, settings, position + Vector3::y())
+        {
+            Block::Grass
+        } else {
+            Block::Stone
+        }
+    } else if position.y < WATER_LEVEL {
+        Block::Water
+    } else {
+        Block::Air
+    }
+}
+
+/// Blocks of a chunk with one layer of the neighbouring blocks around it.
+struct ChunkBlocks {
+    blocks: Vec<Block>,
+}
+
+impl ChunkBlocks {
+    fn generate(coords: ChunkCoords, settings: &TerrainSettings) -> Self {
+        let noise = Perlin::new(NOISE_SEED);
+        let origin = coords * CHUNK_SIZE;
+        let mut blocks = Vec::with_capacity((PADDED_SIZE * PADDED_SIZE * PADDED_SIZE) as usize);
+        for z in -1..=CHUNK_SIZE {
+            for y in -1..=CHUNK_SIZE {
+                for x in -1..=CHUNK_SIZE {
+                    blocks.push(block_at(&noise, settings, origin + Vector3::new(x, y, z)));
+                }
+            }
+        }
+        Self { blocks }
+    }
+
+    /// Returns the block at the given position relative to the chunk, coordinates are in
+    /// `[-1; CHUNK_SIZE]` range.
+    fn get(&self, position: Vector3<i32>) -> Block {
+        let p = position.add_scalar(1);
+        self.blocks[(p.x + p.y * PADDED_SIZE + p.z * PADDED_SIZE * PADDED_SIZE) as usize]
+    }
+}
+
+/// Vertices and triangles of the faces of the blocks of the same type.
+#[derive(Default)]
+struct FaceBuffer {
+    vertices: Vec<StaticVertex>,
+    triangles: Vec<TriangleDefinition>,
+}
+
+impl FaceBuffer {
+    /// Adds a quad, that starts at the origin and spans along the given edges. The quad faces
+    /// the direction of the normal.
+    fn push_quad(
+        &mut self,
+        origin: Vector3<f32>,
+        edges: [Vector3<f32>; 2],
+        size: Vector2<f32>,
+        normal: Vector3<f32>,
+    ) {
+        let base = self.vertices.len() as u32;
+        // Texture coordinates are in blocks, so a texture would tile once per block.
+        for (edge_u, edge_v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            self.vertices.push(StaticVertex::from_pos_uv_normal(
+                origin + edges[0].scale(edge_u) + edges[1].scale(edge_v),
+                Vector2::new(edge_u * size.x, edge_v * size.y),
+                normal,
+            ));
+        }
+        self.triangles
+            .push(TriangleDefinition([base, base + 1, base + 2]));
+        self.triangles
+            .push(TriangleDefinition([base, base + 2, base + 3]));
+    }
+
+    fn build(self) -> Option<SurfaceData> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+        let mut data = SurfaceData::new(
+            VertexBuffer::new(self.vertices.len(), self.vertices).unwrap(),
+            TriangleBuffer::new(self.triangles),
+        );
+        data.calculate_tangents().unwrap();
+        Some(data)
+    }
+}
+
+/// Generated mesh of a chunk, with one surface per visible block type. Vertices are relative to
+/// the origin of the chunk.
+pub struct ChunkMesh {
+    pub coords: ChunkCoords,
+    pub surfaces: Vec<(Block, SurfaceData)>,
+    /// Time spent on the generation of the blocks and the mesh.
+    pub elapsed: Duration,
+}
+
+impl ChunkMesh {
+    pub fn generate(coords: ChunkCoords, settings: TerrainSettings) -> Self {
+        let start = Instant::now();
+        let blocks = ChunkBlocks::generate(coords, &settings);
+        let mut buffers = Block::VISIBLE.map(|block| (block, FaceBuffer::default()));
+        greedy_mesh(&blocks, &mut buffers);
+        let surfaces = buffers
+            .into_iter()
+            .filter_map(|(block, buffer)| buffer.build().map(|data| (block, data)))
+            .collect();
+        Self {
+            coords,
+            surfaces,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// Merges visible faces of the same block type, that lie in the same plane, into rectangles. For
+/// every axis and both directions along it, the chunk is swept slice by slice: a mask of the
+/// visible faces of a slice is built first, then the mask is covered by as large rectangles as
+/// possible.
+fn greedy_mesh(blocks: &ChunkBlocks, buffers: &mut [(Block, FaceBuffer)]) {
+    let size = CHUNK_SIZE as usize;
+    let mut mask = vec![None; size * size];
+
+    for axis in 0..3 {
+        let u_axis = (axis + 1) % 3;
+        let v_axis = (axis + 2) % 3;
+        for positive in [false, true] {
+            let mut normal = Vector3::<i32>::zeros();
+            normal[axis] = if positive { 1 } else { -1 };
+
+            for slice in 0..CHUNK_SIZE {
+                for v in 0..CHUNK_SIZE {
+                    for u in 0..CHUNK_SIZE {
+                        let mut position = Vector3::zeros();
+                        position[axis] = slice;
+                        position[u_axis] = u;
+                        position[v_axis] = v;
+                        let block = blocks.get(position);
+                        mask[u as usize + v as usize * size] = block
+                            .is_face_visible(blocks.get(position + normal))
+                            .then_some(block);
+                    }
+                }
+
+                for v in 0..size {
+                    let mut u = 0;
+                    while u < size {
+                        let Some(block) = mask[u + v * size] else {
+                            u += 1;
+                            continue;
+                        };
+
+                        let mut width = 1;
+                        while u + width < size && mask[u + width + v * size] == Some(block) {
+                            width += 1;
+                        }
+                        let mut height = 1;
+                        while v + height < size
+                            && (u..u + width).all(|i| mask[i + (v + height) * size] == Some(block))
+                        {
+                            height += 1;
+                        }
+                        for j in v..v + height {
+                            for i in u..u + width {
+                                mask[i + j * size] = None;
+                            }
+                        }
+
+                        let mut origin = Vector3::<f32>::zeros();
+                        origin[axis] = slice as f32 + if positive { 1.0 } else { 0.0 };
+                        origin[u_axis] = u as f32;
+                        origin[v_axis] = v as f32;
+                        let mut edge_u = Vector3::zeros();
+                        edge_u[u_axis] = width as f32;
+                        let mut edge_v = Vector3::zeros();
+                        edge_v[v_axis] = height as f32;
+                        // Order of the edges defines the winding of the triangles, so the quad
+                        // faces the normal.
+                        let (edges, quad_size) = if positive {
+                            ([edge_v, edge_u], Vector2::new(height as f32, width as f32))
+                        } else {
+                            ([edge_u, edge_v], Vector2::new(width as f32, height as f32))
+                        };
+
+                        if let Some((_, buffer)) = buffers.iter_mut().find(|(b, _)| *b == block) {
+                            buffer.push_quad(origin, edges, quad_size, normal.cast::<f32>());
+                        }
+
+                        u += width;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Histogram of chunk generation times, that is shown in the debug text.
+use std::{fmt::Write, time::Duration};
+
+/// Upper bounds of the buckets in milliseconds, the last bucket has no upper bound.
+const BUCKET_BOUNDS: [f32; 6] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+/// Length of the bar of the largest bucket, in characters.
+const BAR_LENGTH: usize = 30;
+
+#[derive(Default, Debug, Clone)]
+pub struct Histogram {
+    counts: [usize; BUCKET_BOUNDS.len() + 1],
+    total: Duration,
+}
+
+impl Histogram {
+    pub fn add(&mut self, time: Duration) {
+        let ms = time.as_secs_f32() * 1000.0;
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|bound| ms < *bound)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        self.counts[bucket] += 1;
+        self.total += time;
+    }
+
+    pub fn count(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Prints the histogram as a column of bars, one per bucket.
+    pub fn text(&self) -> String {
+        let count = self.count();
+        let average = if count > 0 {
+            self.total.as_secs_f32() * 1000.0 / count as f32
+        } else {
+            0.0
+        };
+        let mut text = format!("Chunk Generation Time (avg. {average:.2} ms):");
+
+        let max = self.counts.iter().max().cloned().unwrap_or_default().max(1);
+        for (i, bucket_count) in self.counts.iter().enumerate() {
+            let label = match BUCKET_BOUNDS.get(i) {
+                Some(bound) => format!("< {bound} ms"),
+                None => format!(">= {} ms", BUCKET_BOUNDS[BUCKET_BOUNDS.len() - 1]),
+            };
+            let bar = "#".repeat(bucket_count * BAR_LENGTH / max);
+            let _ = write!(text, "\n{label:>9} | {bar} {bucket_count}");
+        }
+        text
+    }
+}
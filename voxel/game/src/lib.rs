@@ -0,0 +1,480 @@
+//! Game project.
+use crate::{
+    chunk::{Block, ChunkCoords, ChunkMesh, TerrainSettings, CHUNK_SIZE},
+    histogram::Histogram,
+};
+use common_scripts::Throttle;
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    engine::GraphicsContext,
+    event::{ElementState, Event, WindowEvent},
+    graph::BaseSceneGraph,
+    gui::{
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    keyboard::{KeyCode, PhysicalKey},
+    material::{Material, MaterialResource},
+    plugin::{Plugin, PluginContext},
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceResource},
+            MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::collections::{HashMap, HashSet};
+
+mod chunk;
+mod histogram;
+mod noise;
+
+/// Vertical range of the world in chunks, the terrain never goes beyond it.
+const MIN_CHUNK_Y: i32 = -2;
+const MAX_CHUNK_Y: i32 = 1;
+/// Chunks are unloaded a bit farther than they're loaded, so a chunk on the border of the radius
+/// won't be loaded and unloaded every time the player crosses the border of a chunk.
+const UNLOAD_MARGIN: i32 = 1;
+const MOVE_SPEED: f32 = 16.0;
+
+const SCENE_NAME: &str = "voxel";
+
+fn block_color(block: Block) -> Color {
+    match block {
+        Block::Air => Color::TRANSPARENT,
+        Block::Stone => Color::opaque(130, 130, 135),
+        Block::Grass => Color::opaque(90, 150, 60),
+        Block::Water => Color::opaque(50, 90, 180),
+    }
+}
+
+fn make_colored_material(color: Color) -> MaterialResource {
+    let mut material = Material::standard();
+    Log::verify(material.set_property(&"diffuseColor".into(), color.into()));
+    MaterialResource::new_ok(ResourceKind::Embedded, material)
+}
+
+/// Squared horizontal distance between two chunks, in chunks.
+fn horizontal_distance_sqr(a: ChunkCoords, b: ChunkCoords) -> i32 {
+    (a.x - b.x).pow(2) + (a.z - b.z).pow(2)
+}
+
+fn make_slider(
+    ctx: &mut BuildContext,
+    row: usize,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+    precision: usize,
+) -> Handle<UiNode> {
+    ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .with_value_precision(precision)
+    .show_value(true)
+    .build(ctx)
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_margin(Thickness::uniform(2.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+#[derive(Default, Visit, Reflect, Debug)]
+pub struct Game {
+    scene: Handle<Scene>,
+    player: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    frequency_slider: Handle<UiNode>,
+    amplitude_slider: Handle<UiNode>,
+    radius_slider: Handle<UiNode>,
+    /// Chunks within this horizontal distance (in chunks) from the player are loaded.
+    chunk_radius: i32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    settings: TerrainSettings,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    move_direction: Vector2<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    materials: HashMap<Block, MaterialResource>,
+    /// Mesh nodes of the generated chunks. Chunks without visible blocks have no mesh.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    chunks: HashMap<ChunkCoords, Handle<Node>>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending_chunks: HashSet<ChunkCoords>,
+    /// Chunk with the player in it, the set of the loaded chunks is updated only when the player
+    /// moves to another chunk.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player_chunk: Option<ChunkCoords>,
+    /// Incremented every time the settings of the terrain change, so the chunks, that were
+    /// generated with the old settings, could be discarded.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    generation: u32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    histogram: Histogram,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: Throttle,
+}
+
+impl Game {
+    fn player_chunk(&self, context: &PluginContext) -> Option<ChunkCoords> {
+        let position = context
+            .scenes
+            .try_get(self.scene)?
+            .graph
+            .try_get(self.player)?
+            .global_position();
+        Some(ChunkCoords::new(
+            (position.x / CHUNK_SIZE as f32).floor() as i32,
+            0,
+            (position.z / CHUNK_SIZE as f32).floor() as i32,
+        ))
+    }
+
+    fn update_chunks(&mut self, context: &mut PluginContext) {
+        let settings = self.settings;
+        let player_chunk = self.player_chunk(context);
+        if player_chunk == self.player_chunk {
+            return;
+        }
+        self.player_chunk = player_chunk;
+        let Some(player_chunk) = player_chunk else {
+            return;
+        };
+
+        // Unload distant chunks.
+        let unload_radius = self.chunk_radius + UNLOAD_MARGIN;
+        let distant = self
+            .chunks
+            .keys()
+            .filter(|coords| horizontal_distance_sqr(**coords, player_chunk) > unload_radius.pow(2))
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            for coords in distant {
+                if let Some(mesh) = self.chunks.remove(&coords) {
+                    if mesh.is_some() {
+                        scene.graph.remove_node(mesh);
+                    }
+                }
+            }
+        }
+
+        // Request new chunks.
+        let radius = self.chunk_radius;
+        for x in player_chunk.x - radius..=player_chunk.x + radius {
+            for z in player_chunk.z - radius..=player_chunk.z + radius {
+                for y in MIN_CHUNK_Y..=MAX_CHUNK_Y {
+                    let coords = ChunkCoords::new(x, y, z);
+                    if horizontal_distance_sqr(coords, player_chunk) > radius.pow(2)
+                        || self.chunks.contains_key(&coords)
+                        || self.pending_chunks.contains(&coords)
+                    {
+                        continue;
+                    }
+
+                    self.pending_chunks.insert(coords);
+                    let generation = self.generation;
+                    context.task_pool.spawn_plugin_task(
+                        async move { ChunkMesh::generate(coords, settings) },
+                        move |mesh, game: &mut Game, ctx| {
+                            if generation == game.generation {
+                                game.on_chunk_generated(mesh, ctx);
+                            }
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn on_chunk_generated(&mut self, mesh: ChunkMesh, context: &mut PluginContext) {
+        self.pending_chunks.remove(&mesh.coords);
+        self.histogram.add(mesh.elapsed);
+
+        // The chunk could go out of range while it was generating.
+        let in_range = self.player_chunk.is_some_and(|player_chunk| {
+            horizontal_distance_sqr(mesh.coords, player_chunk)
+                <= (self.chunk_radius + UNLOAD_MARGIN).pow(2)
+        });
+        if !in_range || self.chunks.contains_key(&mesh.coords) {
+            return;
+        }
+
+        let handle = if mesh.surfaces.is_empty() {
+            Handle::NONE
+        } else {
+            let surfaces = mesh
+                .surfaces
+                .into_iter()
+                .map(|(block, data)| {
+                    let material = self
+                        .materials
+                        .entry(block)
+                        .or_insert_with(|| make_colored_material(block_color(block)))
+                        .clone();
+                    SurfaceBuilder::new(SurfaceResource::new_ok(ResourceKind::Embedded, data))
+                        .with_material(material)
+                        .build()
+                })
+                .collect();
+            let coords = mesh.coords;
+            MeshBuilder::new(
+                BaseBuilder::new()
+                    .with_name(format!("Chunk {}-{}-{}", coords.x, coords.y, coords.z))
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position((coords * CHUNK_SIZE).cast::<f32>())
+                            .build(),
+                    ),
+            )
+            .with_surfaces(surfaces)
+            .build(&mut context.scenes[self.scene].graph)
+        };
+        self.chunks.insert(mesh.coords, handle);
+    }
+
+    /// Removes every chunk, so the world is generated again with the new settings.
+    fn regenerate(&mut self, context: &mut PluginContext) {
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            for mesh in self.chunks.values() {
+                if mesh.is_some() {
+                    scene.graph.remove_node(*mesh);
+                }
+            }
+        }
+        self.chunks.clear();
+        self.pending_chunks.clear();
+        self.generation = self.generation.wrapping_add(1);
+        self.player_chunk = None;
+    }
+
+    fn move_player(&self, context: &mut PluginContext) {
+        let Some(direction) = self.move_direction.try_normalize(f32::EPSILON) else {
+            return;
+        };
+        let Some(player) = context
+            .scenes
+            .try_get_mut(self.scene)
+            .and_then(|scene| scene.graph.try_get_mut(self.player))
+        else {
+            return;
+        };
+
+        let offset = direction.scale(MOVE_SPEED * context.dt);
+        let transform = player.local_transform_mut();
+        let position = **transform.position();
+        transform.set_position(position + Vector3::new(offset.x, 0.0, offset.y));
+    }
+}
+
+impl Plugin for Game {
+    fn init(&mut self, _scene_path: Option<&str>, context: PluginContext) {
+        self.settings = TerrainSettings::default();
+        self.chunk_radius = 4;
+
+        let mut scene = Scene::new();
+
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 40.0, -40.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        40.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        // The player is the point on the water level, that the camera looks at.
+        self.player =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[camera])).build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(
+                        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 30.0f32.to_radians())
+                            * UnitQuaternion::from_axis_angle(
+                                &Vector3::x_axis(),
+                                60.0f32.to_radians(),
+                            ),
+                    )
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        self.scene = context.scenes.add(scene);
+
+        let ctx = &mut context.user_interfaces.first_mut().build_ctx();
+
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+
+        let frequency_text = make_label(ctx, 0, "Noise Frequency");
+        self.frequency_slider = make_slider(ctx, 1, 0.005, 0.1, 0.005, self.settings.frequency, 3);
+        let amplitude_text = make_label(ctx, 2, "Noise Amplitude");
+        self.amplitude_slider = make_slider(ctx, 3, 4.0, 32.0, 1.0, self.settings.amplitude, 0);
+        let radius_text = make_label(ctx, 4, "Chunk Radius");
+        self.radius_slider = make_slider(ctx, 5, 1.0, 8.0, 1.0, self.chunk_radius as f32, 0);
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_height(190.0)
+                .with_desired_position(Vector2::new(5.0, 240.0)),
+        )
+        .with_title(WindowTitle::text("Terrain Settings"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(frequency_text)
+                    .with_child(self.frequency_slider)
+                    .with_child(amplitude_text)
+                    .with_child(self.amplitude_slider)
+                    .with_child(radius_text)
+                    .with_child(self.radius_slider),
+            )
+            .add_column(Column::stretch())
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .add_row(Row::strict(24.0))
+            .add_row(Row::strict(26.0))
+            .build(ctx),
+        )
+        .build(ctx);
+    }
+
+    fn update(&mut self, context: &mut PluginContext) {
+        if self.throttle.update(context, self.debug_text) {
+            return;
+        }
+
+        self.move_player(context);
+        self.update_chunks(context);
+
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            context
+                .user_interfaces
+                .first()
+                .send_message(TextMessage::text(
+                    self.debug_text,
+                    MessageDirection::ToWidget,
+                    format!(
+                        "Example - Voxel World\n[W][A][S][D] - Move\nLoaded Chunks: {}\n\
+                        Generating Chunks: {}\n{}\n{}\n{}",
+                        self.chunks.len(),
+                        self.pending_chunks.len(),
+                        self.histogram.text(),
+                        graphics_context.renderer.get_statistics(),
+                        tracked_alloc::peak_ram_text()
+                    ),
+                ));
+        }
+    }
+
+    fn on_os_event(&mut self, event: &Event<()>, _context: PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            let value = if event.state == ElementState::Pressed {
+                1.0
+            } else {
+                0.0
+            };
+            // The camera looks along +Z axis, the player moves relative to it.
+            match event.physical_key {
+                PhysicalKey::Code(KeyCode::KeyW) => self.move_direction.y = value,
+                PhysicalKey::Code(KeyCode::KeyS) => self.move_direction.y = -value,
+                PhysicalKey::Code(KeyCode::KeyA) => self.move_direction.x = value,
+                PhysicalKey::Code(KeyCode::KeyD) => self.move_direction.x = -value,
+                _ => (),
+            }
+        }
+    }
+
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.direction() != MessageDirection::FromWidget {
+                return;
+            }
+
+            let mut settings = self.settings;
+            if message.destination() == self.frequency_slider {
+                settings.frequency = *value;
+            } else if message.destination() == self.amplitude_slider {
+                settings.amplitude = *value;
+            } else if message.destination() == self.radius_slider {
+                // Chunks are requested again, when the player chunk is unknown.
+                self.chunk_radius = value.round() as i32;
+                self.player_chunk = None;
+            }
+
+            if self.settings != settings {
+                self.settings = settings;
+                self.regenerate(context);
+            }
+        }
+    }
+
+    fn on_graphics_context_initialized(&mut self, mut context: PluginContext) {
+        prefs::quality::restore(
+            &mut context.graphics_context.as_initialized_mut().renderer,
+            SCENE_NAME,
+        );
+    }
+
+    fn on_deinit(&mut self, context: PluginContext) {
+        if let GraphicsContext::Initialized(graphics_context) = context.graphics_context {
+            prefs::quality::store(&graphics_context.renderer, SCENE_NAME);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Classic 3-D Perlin noise, the permutation table is shuffled using a seed, so the world is the
+//! same on every run with the same seed.
+use fyrox::core::algebra::Vector3;
+
+pub struct Perlin {
+    /// Permutation of `0..256`, repeated twice to avoid wrapping of the indices.
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        let mut values = [0u8; 256];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+
+        // Fisher-Yates shuffle with a tiny xorshift generator.
+        let mut state = seed | 1;
+        for i in (1..values.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            values.swap(i, state as usize % (i + 1));
+        }
+
+        let mut permutation = [0u8; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = values[i % 256];
+        }
+        Self { permutation }
+    }
+
+    /// Returns the value of the noise at the given point, in `[-1; 1]` range.
+    pub fn get(&self, point: Vector3<f32>) -> f32 {
+        let cell = point.map(|c| c.floor());
+        let p = &self.permutation;
+        let xi = (cell.x as i32 & 255) as usize;
+        let yi = (cell.y as i32 & 255) as usize;
+        let zi = (cell.z as i32 & 255) as usize;
+
+        let local = point - cell;
+        let (x, y, z) = (local.x, local.y, local.z);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    /// Sums a few octaves of the noise, every next octave has twice the frequency and half the
+    /// amplitude of the previous one. The result is normalized to `[-1; 1]` range.
+    pub fn fractal(&self, point: Vector3<f32>, octaves: usize) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut total_amplitude = 0.0;
+        let mut point = point;
+        for _ in 0..octaves {
+            sum += self.get(point) * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= 0.5;
+            point *= 2.0;
+        }
+        sum / total_amplitude
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// Dot product of the offset and one of 12 gradient directions, selected by the hash.
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,525 @@
+//! Weather system - rain, that wets the ground, and thunder during storms. The weather is
+//! controlled by the [`WeatherController`] script and by the [`WeatherPanel`] window.
+use fyrox::{
+    asset::untyped::ResourceKind,
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        color_gradient::{ColorGradient, GradientPoint},
+        log::Log,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{BaseSceneGraph, SceneGraph},
+    gui::{
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, VerticalAlignment,
+    },
+    material::{
+        shader::{Shader, ShaderResource},
+        Material, MaterialResource,
+    },
+    rand::{thread_rng, Rng},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::Mesh,
+        node::Node,
+        particle_system::{
+            emitter::{base::BaseEmitterBuilder, cuboid::CuboidEmitterBuilder},
+            ParticleSystem, ParticleSystemBuilder,
+        },
+        sound::{
+            reverb::Reverb, AudioBus, DataSource, Effect, SoundBufferResource,
+            SoundBufferResourceExtension, SoundBuilder, Status,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+    script::{Script, ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+/// Shader of the ground, that gets darker and glossier as it gets wet.
+const WET_GROUND_SHADER: &str = include_str!("wet_ground.shader");
+/// Property of the wet ground shader, that tells how wet the ground is, in `[0; 1]` range.
+const WETNESS_PROPERTY: &str = "puddle_wetness";
+/// Textures, that are taken from the replaced materials of the ground.
+const GROUND_TEXTURES: [&str; 3] = ["diffuseTexture", "normalTexture", "roughnessTexture"];
+/// Name of the audio bus with the reverb, that is used by thunder sounds.
+const THUNDER_BUS: &str = "Thunder";
+/// Range of the delay (in seconds) before a thunder strike during a storm.
+const THUNDER_DELAY: std::ops::Range<f32> = 2.0..10.0;
+/// Time (in seconds) that it takes for the wet ground to dry completely.
+const DRYING_TIME: f32 = 90.0;
+/// Speed of the falling drops. Velocities of particles are per frame, not per second.
+const FALL_SPEED: f32 = 0.3;
+/// Horizontal speed of the drops at the max wind strength.
+const WIND_SPEED: f32 = 0.15;
+const RAIN_HEIGHT: f32 = 12.0;
+/// Size of the area, where the rain falls.
+const RAIN_AREA: Vector2<f32> = Vector2::new(30.0, 16.0);
+const MAX_DROPS: u32 = 6000;
+/// Sample rate of the generated thunder sound.
+const SAMPLE_RATE: usize = 44100;
+const THUNDER_DURATION: f32 = 4.0;
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Visit, Reflect)]
+pub enum RainIntensity {
+    #[default]
+    None,
+    Light,
+    Heavy,
+    /// Heavy rain with thunder.
+    Storm,
+}
+
+impl RainIntensity {
+    pub const ALL: [RainIntensity; 4] = [
+        RainIntensity::None,
+        RainIntensity::Light,
+        RainIntensity::Heavy,
+        RainIntensity::Storm,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RainIntensity::None => "None",
+            RainIntensity::Light => "Light",
+            RainIntensity::Heavy => "Heavy",
+            RainIntensity::Storm => "Storm",
+        }
+    }
+
+    /// Amount of drops emitted per second.
+    fn spawn_rate(self) -> u32 {
+        match self {
+            RainIntensity::None => 0,
+            RainIntensity::Light => 400,
+            RainIntensity::Heavy => 1600,
+            RainIntensity::Storm => 3000,
+        }
+    }
+
+    /// Time (in seconds) of the rain, that makes dry ground completely wet.
+    fn wetting_time(self) -> Option<f32> {
+        match self {
+            RainIntensity::None => None,
+            RainIntensity::Light => Some(120.0),
+            RainIntensity::Heavy => Some(60.0),
+            RainIntensity::Storm => Some(30.0),
+        }
+    }
+}
+
+/// Generates a rumble of a distant thunder - brown noise with a sharp attack and a long decay.
+fn make_thunder_sound() -> Option<SoundBufferResource> {
+    let mut rng = thread_rng();
+    let sample_count = (SAMPLE_RATE as f32 * THUNDER_DURATION) as usize;
+    let mut samples = Vec::with_capacity(sample_count);
+    let mut noise = 0.0f32;
+    for i in 0..sample_count {
+        let time = i as f32 / SAMPLE_RATE as f32;
+        noise = (noise + rng.gen_range(-1.0..1.0) * 0.05) * 0.995;
+        let envelope = (time * 40.0).min(1.0)
+            * (-time * 1.2).exp()
+            * (1.0 + 0.4 * (time * 5.0).sin() * (time * 1.7).sin());
+        samples.push(noise * envelope);
+    }
+
+    let peak = samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    if peak > 0.0 {
+        for sample in samples.iter_mut() {
+            *sample /= peak;
+        }
+    }
+
+    match SoundBufferResource::new_generic(DataSource::Raw {
+        sample_rate: SAMPLE_RATE,
+        channel_count: 1,
+        samples,
+    }) {
+        Ok(buffer) => Some(buffer),
+        Err(_) => {
+            Log::err("Unable to create thunder sound buffer!");
+            None
+        }
+    }
+}
+
+fn make_rain(graph: &mut Graph) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new().with_name("Rain").with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, RAIN_HEIGHT, 0.0))
+                .build(),
+        ),
+    )
+    // Drops fall with their terminal velocity.
+    .with_acceleration(Vector3::zeros())
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(
+            0.0,
+            Color::from_rgba(160, 160, 170, 180),
+        ));
+        gradient.add_point(GradientPoint::new(
+            1.0,
+            Color::from_rgba(160, 160, 170, 120),
+        ));
+        gradient
+    })
+    .with_emitters(vec![CuboidEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(MAX_DROPS)
+            .with_spawn_rate(0)
+            .with_size_range(0.01..0.02)
+            .with_x_velocity_range(-0.01..0.01)
+            .with_y_velocity_range(-FALL_SPEED..-FALL_SPEED * 0.8)
+            .with_z_velocity_range(-0.01..0.01)
+            .with_lifetime_range(0.8..1.0),
+    )
+    .with_width(RAIN_AREA.x)
+    .with_height(0.5)
+    .with_depth(RAIN_AREA.y)
+    .build()])
+    .build(graph)
+}
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "3e7b1d94-6a2c-4f58-8d03-b5c9e1f4a267")]
+#[visit(optional)]
+pub struct WeatherController {
+    pub intensity: InheritableVariable<RainIntensity>,
+    /// Strength of the wind along X axis, in `[-1; 1]` range.
+    pub wind: InheritableVariable<f32>,
+    /// Sound of a thunder, a generated rumble is used if it is not set.
+    pub thunder_sound: InheritableVariable<Option<SoundBufferResource>>,
+    /// Meshes, that get wet in the rain. If the list is empty, every mesh with `floor` in its
+    /// name is used.
+    pub ground_meshes: InheritableVariable<Vec<Handle<Node>>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    rain: Handle<Node>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    thunder_bus: Handle<AudioBus>,
+    /// Time left before the next thunder strike.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    thunder_timer: Option<f32>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    previous_intensity: RainIntensity,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wetness: f32,
+    /// Materials of the ground meshes with the wet ground shader.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    wet_materials: Vec<MaterialResource>,
+}
+
+impl Default for WeatherController {
+    fn default() -> Self {
+        Self {
+            intensity: Default::default(),
+            wind: 0.0.into(),
+            thunder_sound: Default::default(),
+            ground_meshes: Default::default(),
+            rain: Default::default(),
+            thunder_bus: Default::default(),
+            thunder_timer: None,
+            previous_intensity: Default::default(),
+            wetness: 0.0,
+            wet_materials: Default::default(),
+        }
+    }
+}
+
+impl WeatherController {
+    /// Adds a weather controller to the scene, if it does not have one.
+    pub fn add_if_missing(graph: &mut Graph) {
+        if graph
+            .linear_iter()
+            .any(|node| node.try_get_script::<WeatherController>().is_some())
+        {
+            return;
+        }
+        let weather = BaseBuilder::new().with_name("Weather").build(graph);
+        graph[weather].add_script(Script::new(WeatherController::default()));
+    }
+
+    /// Replaces the materials of the ground meshes with the materials of the wet ground shader,
+    /// that take the textures of the replaced materials.
+    fn make_ground_wettable(&mut self, graph: &mut Graph) {
+        let shader = match Shader::from_string(WET_GROUND_SHADER) {
+            Ok(shader) => ShaderResource::new_ok(ResourceKind::Embedded, shader),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to create wet ground shader. Reason: {err:?}"
+                ));
+                return;
+            }
+        };
+
+        for mesh in self.ground_meshes.iter() {
+            let Some(mesh) = graph.try_get_mut_of_type::<Mesh>(*mesh) else {
+                continue;
+            };
+            for surface in mesh.surfaces_mut() {
+                let mut material = Material::from_shader(shader.clone(), None);
+                {
+                    let ground = surface.material().data_ref();
+                    for name in GROUND_TEXTURES {
+                        if let Some(texture) = ground.property_ref(&name.into()) {
+                            Log::verify(material.set_property(&name.into(), texture.clone()));
+                        }
+                    }
+                }
+                let material = MaterialResource::new_ok(ResourceKind::Embedded, material);
+                surface.set_material(material.clone());
+                self.wet_materials.push(material);
+            }
+        }
+    }
+
+    fn update_rain(&self, graph: &mut Graph) {
+        let Some(rain) = graph.try_get_mut_of_type::<ParticleSystem>(self.rain) else {
+            return;
+        };
+        let wind = self.wind.clamp(-1.0, 1.0) * WIND_SPEED;
+        for emitter in rain.emitters.get_value_mut_and_mark_modified().iter_mut() {
+            emitter
+                .set_spawn_rate(self.intensity.spawn_rate())
+                .set_x_velocity_range(wind - 0.01..wind + 0.01);
+        }
+    }
+
+    fn update_wetness(&mut self, dt: f32) {
+        let wetness = match self.intensity.wetting_time() {
+            Some(wetting_time) => self.wetness + dt / wetting_time,
+            None => self.wetness - dt / DRYING_TIME,
+        }
+        .clamp(0.0, 1.0);
+        if wetness == self.wetness {
+            return;
+        }
+        self.wetness = wetness;
+
+        for material in self.wet_materials.iter() {
+            Log::verify(
+                material
+                    .data_ref()
+                    .set_property(&WETNESS_PROPERTY.into(), wetness.into()),
+            );
+        }
+    }
+
+    fn update_thunder(&mut self, ctx: &mut ScriptContext) {
+        if *self.intensity != self.previous_intensity {
+            self.previous_intensity = *self.intensity;
+            self.thunder_timer = (*self.intensity == RainIntensity::Storm)
+                .then(|| thread_rng().gen_range(THUNDER_DELAY));
+        }
+
+        let Some(timer) = self.thunder_timer.as_mut() else {
+            return;
+        };
+        *timer -= ctx.dt;
+        if *timer > 0.0 {
+            return;
+        }
+        // Storm goes on, so the next strike is scheduled right away.
+        *timer = thread_rng().gen_range(THUNDER_DELAY);
+
+        if let Some(buffer) = self.thunder_sound.as_ref() {
+            SoundBuilder::new(BaseBuilder::new().with_name("Thunder"))
+                .with_buffer(Some(buffer.clone()))
+                .with_gain(1.0)
+                // Thunder is everywhere, it must not be attenuated by distance.
+                .with_spatial_blend_factor(0.0)
+                .with_audio_bus(THUNDER_BUS.to_string())
+                .with_status(Status::Playing)
+                .with_play_once(true)
+                .build(&mut ctx.scene.graph);
+        }
+    }
+}
+
+impl ScriptTrait for WeatherController {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.rain = make_rain(&mut ctx.scene.graph);
+        ctx.scene.graph.link_nodes(self.rain, ctx.handle);
+
+        if self.thunder_sound.is_none() {
+            self.thunder_sound.set_value_silent(make_thunder_sound());
+        }
+
+        let mut reverb = Reverb::new();
+        reverb.set_decay_time(4.0);
+        reverb.set_wet(0.6);
+        let mut bus = AudioBus::new(THUNDER_BUS.to_string());
+        bus.add_effect(Effect::Reverb(reverb));
+        let mut state = ctx.scene.graph.sound_context.state();
+        let bus_graph = state.bus_graph_mut();
+        let primary_bus = bus_graph.primary_bus_handle();
+        self.thunder_bus = bus_graph.add_bus(bus, primary_bus);
+        drop(state);
+
+        if self.ground_meshes.is_empty() {
+            let ground_meshes = ctx
+                .scene
+                .graph
+                .pair_iter()
+                .filter(|(_, node)| {
+                    node.cast::<Mesh>().is_some() && node.name().to_lowercase().contains("floor")
+                })
+                .map(|(handle, _)| handle)
+                .collect();
+            self.ground_meshes.set_value_silent(ground_meshes);
+        }
+        self.make_ground_wettable(&mut ctx.scene.graph);
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        ctx.scene
+            .graph
+            .sound_context
+            .state()
+            .bus_graph_mut()
+            .remove_bus(self.thunder_bus);
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.update_rain(&mut ctx.scene.graph);
+        self.update_wetness(ctx.dt);
+        self.update_thunder(ctx);
+    }
+}
+
+/// A window with the controls of the weather.
+#[derive(Default, Debug)]
+pub struct WeatherPanel {
+    intensity_list: Handle<UiNode>,
+    wind_slider: Handle<UiNode>,
+}
+
+impl WeatherPanel {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let intensity_list = DropdownListBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .on_column(1)
+                .with_height(22.0)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_items(
+            RainIntensity::ALL
+                .iter()
+                .map(|intensity| {
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                        .with_text(intensity.name())
+                        .build(ctx)
+                })
+                .collect(),
+        )
+        .with_selected(0)
+        .build(ctx);
+
+        let wind_slider = ScrollBarBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_min(-1.0)
+        .with_max(1.0)
+        .with_step(0.05)
+        .with_value(0.0)
+        .with_value_precision(2)
+        .show_value(true)
+        .build(ctx);
+
+        let label = |ctx: &mut BuildContext, row: usize, text: &str| {
+            TextBuilder::new(
+                WidgetBuilder::new()
+                    .on_row(row)
+                    .with_margin(Thickness::uniform(2.0))
+                    .with_vertical_alignment(VerticalAlignment::Center),
+            )
+            .with_text(text)
+            .build(ctx)
+        };
+        let intensity_label = label(ctx, 0, "Rain");
+        let wind_label = label(ctx, 1, "Wind");
+
+        WindowBuilder::new(
+            WidgetBuilder::new()
+                .with_width(260.0)
+                .with_height(90.0)
+                .with_desired_position(Vector2::new(5.0, 150.0)),
+        )
+        .with_title(WindowTitle::text("Weather"))
+        .can_close(false)
+        .with_content(
+            GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(intensity_label)
+                    .with_child(intensity_list)
+                    .with_child(wind_label)
+                    .with_child(wind_slider),
+            )
+            .add_column(Column::strict(60.0))
+            .add_column(Column::stretch())
+            .add_row(Row::strict(28.0))
+            .add_row(Row::strict(28.0))
+            .build(ctx),
+        )
+        .build(ctx);
+
+        Self {
+            intensity_list,
+            wind_slider,
+        }
+    }
+
+    pub fn handle_ui_message(&self, message: &UiMessage, scene: Option<&mut Scene>) {
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+        let Some(scene) = scene else {
+            return;
+        };
+        let Some(controller) = scene
+            .graph
+            .linear_iter_mut()
+            .find_map(|node| node.try_get_script_mut::<WeatherController>())
+        else {
+            return;
+        };
+
+        if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
+            if message.destination() == self.intensity_list {
+                if let Some(intensity) = RainIntensity::ALL.get(*index) {
+                    controller.intensity.set_value_and_mark_modified(*intensity);
+                }
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            if message.destination() == self.wind_slider {
+                controller.wind.set_value_and_mark_modified(*value);
+            }
+        }
+    }
+}
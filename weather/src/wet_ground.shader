@@ -0,0 +1,107 @@
+(
+    name: "WetGroundShader",
+
+    properties: [
+        (
+            name: "diffuseTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "normalTexture",
+            kind: Sampler(default: None, fallback: Normal),
+        ),
+        (
+            name: "roughnessTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "puddle_wetness",
+            kind: Float(0.0),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 normal;
+                out vec3 tangent;
+                out vec3 binormal;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                    tangent = normalize(mat3(fyrox_worldMatrix) * vertexTangent.xyz);
+                    binormal = normalize(vertexTangent.w * cross(normal, tangent));
+                    texCoord = vertexTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+            fragment_shader:
+               r#"
+                uniform sampler2D diffuseTexture;
+                uniform sampler2D normalTexture;
+                uniform sampler2D roughnessTexture;
+                uniform float puddle_wetness;
+
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+
+                in vec3 normal;
+                in vec3 tangent;
+                in vec3 binormal;
+                in vec2 texCoord;
+
+                void main()
+                {
+                    // Wet surfaces absorb more light, so they look darker.
+                    vec3 diffuse = texture(diffuseTexture, texCoord).rgb;
+                    outColor = vec4(diffuse * mix(1.0, 0.45, puddle_wetness), 1.0);
+
+                    // Water fills the relief of the surface, so it becomes flatter and glossier.
+                    vec3 tangentNormal = texture(normalTexture, texCoord).xyz * 2.0 - 1.0;
+                    tangentNormal = normalize(mix(tangentNormal, vec3(0.0, 0.0, 1.0), puddle_wetness * 0.8));
+                    mat3 tangentSpace = mat3(normalize(tangent), normalize(binormal), normalize(normal));
+                    outNormal = vec4(normalize(tangentSpace * tangentNormal) * 0.5 + 0.5, 1.0);
+
+                    float roughness = texture(roughnessTexture, texCoord).r;
+                    outMaterial = vec4(0.0, mix(roughness, 0.08, puddle_wetness), 1.0, 1.0);
+                    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+                    outDecalMask = 0u;
+                }
+               "#,
+        ),
+    ],
+)